@@ -0,0 +1,110 @@
+//! Benchmarks for the database queries behind the TUI and CLI
+//!
+//! Run with `cargo bench --features debug-tools`. The database holds 10,000
+//! tasks by default; set `TICKIT_BENCH_TASKS` to try other sizes.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use tickit::app::AppState;
+use tickit::core::{TaskQuery, TickitCore};
+use tickit::fuzzy::fuzzy_match;
+use tickit::{Config, Database, ExportFormat, Priority, export, seed};
+
+fn seeded() -> (tempfile::TempDir, Database) {
+    let tasks = std::env::var("TICKIT_BENCH_TASKS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(10_000);
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_path(&dir.path().join("bench.db")).unwrap();
+    seed::seed(&db, tasks, 42).unwrap();
+    (dir, db)
+}
+
+fn queries(c: &mut Criterion) {
+    let (_dir, db) = seeded();
+    let list = db.get_lists().unwrap()[1].id;
+
+    c.bench_function("open tasks", |b| {
+        b.iter(|| db.get_tasks_with_filter(None, Some(false), None).unwrap())
+    });
+    c.bench_function("tasks in a list", |b| {
+        b.iter(|| db.get_tasks_for_list(black_box(list)).unwrap())
+    });
+    c.bench_function("list progress", |b| {
+        b.iter(|| db.get_list_progress().unwrap())
+    });
+    c.bench_function("search", |b| {
+        b.iter(|| db.search_tasks(black_box("report")).unwrap())
+    });
+}
+
+fn filtering(c: &mut Criterion) {
+    let (_dir, db) = seeded();
+    let tag = db.get_tags().unwrap()[0].id;
+    let titles: Vec<String> = db
+        .get_all_tasks()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.title)
+        .collect();
+    let core = TickitCore::new(db, Config::default());
+
+    c.bench_function("filter by tag and priority", |b| {
+        let query = TaskQuery {
+            tag_id: Some(tag),
+            priority: Some(Priority::High),
+            ..TaskQuery::default()
+        };
+        b.iter(|| core.tasks(black_box(&query)).unwrap())
+    });
+    c.bench_function("fuzzy match titles", |b| {
+        b.iter(|| {
+            titles
+                .iter()
+                .filter_map(|t| fuzzy_match(black_box("rvwrpt"), t))
+                .count()
+        })
+    });
+}
+
+fn tui_refresh(c: &mut Criterion) {
+    let (_dir, db) = seeded();
+    let mut state = AppState::new(Config::default(), db).unwrap();
+
+    c.bench_function("tui refresh", |b| b.iter(|| state.refresh_data().unwrap()));
+}
+
+fn exports(c: &mut Criterion) {
+    let (_dir, db) = seeded();
+    let tasks = db.get_tasks_with_filter(None, None, None).unwrap();
+    let lists = db.get_lists().unwrap();
+    let tags = db.get_tags().unwrap();
+    let config = Config::default();
+
+    let mut group = c.benchmark_group("export");
+    for (name, format) in [
+        ("json", ExportFormat::Json),
+        ("markdown", ExportFormat::Markdown),
+        ("csv", ExportFormat::Csv),
+        ("todotxt", ExportFormat::TodoTxt),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                export::export_tasks(&mut out, &tasks, &lists, &tags, &[], format, &config)
+                    .unwrap();
+                out
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = queries, filtering, tui_refresh, exports
+}
+criterion_main!(benches);
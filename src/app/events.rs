@@ -2,7 +2,10 @@
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use super::state::{AppState, EditorField, Focus, Mode, SettingsItem, View};
+use super::input;
+use super::state::{AppState, EditorField, FilterRow, Focus, Mode, SettingsItem, View};
+use crate::config::Density;
+use crate::models::DeletedListTasks;
 use crate::theme::Theme;
 
 /// Handle a key event
@@ -14,10 +17,7 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
             return;
         }
         Mode::Help => {
-            if matches!(key.code, KeyCode::Esc | KeyCode::Char('?') | KeyCode::Enter) {
-                state.mode = Mode::Normal;
-                state.show_help = false;
-            }
+            handle_help(state, key);
             return;
         }
         Mode::Settings => {
@@ -32,12 +32,16 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
             handle_about(state, key);
             return;
         }
+        Mode::SyncPanel => {
+            handle_sync_panel(state, key);
+            return;
+        }
         Mode::UpdateConfirm => {
             handle_update_confirm_key(state, key);
             return;
         }
         Mode::Updating => {
-            // No input during update
+            handle_updating(state, key);
             return;
         }
         Mode::AddTask | Mode::EditTask => {
@@ -60,6 +64,82 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
             handle_export(state, key);
             return;
         }
+        Mode::MoveTasks => {
+            handle_move_tasks(state, key);
+            return;
+        }
+        Mode::Workspaces => {
+            handle_workspaces(state, key);
+            return;
+        }
+        Mode::Warnings => {
+            handle_warnings(state, key);
+            return;
+        }
+        Mode::OpenLink => {
+            handle_open_link(state, key);
+            return;
+        }
+        Mode::TagTasks => {
+            handle_tag_tasks(state, key);
+            return;
+        }
+        Mode::ReassignTag => {
+            handle_reassign_tag(state, key);
+            return;
+        }
+        Mode::MoveListTasks => {
+            handle_move_list_tasks(state, key);
+            return;
+        }
+        Mode::QuickAdd => {
+            handle_quick_add(state, key);
+            return;
+        }
+        Mode::Comment => {
+            handle_comment(state, key);
+            return;
+        }
+        Mode::Defer => {
+            handle_defer(state, key);
+            return;
+        }
+        Mode::Filter => {
+            handle_filter(state, key);
+            return;
+        }
+        Mode::Jump => {
+            handle_jump(state, key);
+            return;
+        }
+        Mode::InlineEdit => {
+            handle_inline_edit(state, key);
+            return;
+        }
+        Mode::Focus => {
+            handle_focus(state, key);
+            return;
+        }
+        Mode::Timeline => {
+            handle_timeline(state, key);
+            return;
+        }
+        Mode::Planner => {
+            handle_planner(state, key);
+            return;
+        }
+        Mode::Stats => {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('I') => {
+                    state.mode = Mode::Normal;
+                }
+                KeyCode::Char('b') => state.toggle_stats_burndown(),
+                KeyCode::Tab => state.cycle_stats_scope(true),
+                KeyCode::BackTab => state.cycle_stats_scope(false),
+                _ => {}
+            }
+            return;
+        }
         Mode::Normal => {}
     }
 
@@ -77,8 +157,17 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
         }
         // Help
         (_, KeyCode::Char('?')) | (_, KeyCode::F(1)) => {
-            state.mode = Mode::Help;
-            state.show_help = true;
+            state.open_help();
+            return;
+        }
+        // Startup warnings
+        (_, KeyCode::Char('!')) => {
+            state.open_warnings();
+            return;
+        }
+        // Fuzzy jump to any task
+        (_, KeyCode::Char('\'')) => {
+            state.start_jump();
             return;
         }
         // Tab between views
@@ -125,18 +214,28 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
             state.mode = Mode::ThemePicker;
             return;
         }
-        // Manual sync (Ctrl+S or Shift+S)
-        (KeyModifiers::CONTROL, KeyCode::Char('s')) | (_, KeyCode::Char('S')) => {
-            if state.is_sync_enabled() {
-                state.set_syncing(true);
-                // Sync indicator on the right shows status, no need for left status message
-            } else {
-                state.set_status("Sync not configured");
-            }
+        // Manual sync (Ctrl+S); the sync indicator on the right shows status
+        (KeyModifiers::CONTROL, KeyCode::Char('s')) => {
+            state.request_sync(false);
+            return;
+        }
+        // Sync panel (Shift+S)
+        (_, KeyCode::Char('S')) => {
+            state.open_sync_panel();
+            return;
+        }
+        // Pause/resume auto-sync until resumed (Shift+P)
+        (_, KeyCode::Char('P')) => {
+            state.toggle_saved_sync_pause();
             return;
         }
-        // Settings dialog (s like Hazelnut)
-        (_, KeyCode::Char('s')) => {
+        // Settings dialog (s like Hazelnut; `s` sorts in the Tasks view)
+        (_, KeyCode::Char(',')) => {
+            state.settings_index = 0;
+            state.mode = Mode::Settings;
+            return;
+        }
+        (_, KeyCode::Char('s')) if state.view != View::Tasks => {
             state.settings_index = 0;
             state.mode = Mode::Settings;
             return;
@@ -146,9 +245,20 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
             state.mode = Mode::About;
             return;
         }
-        // Update (when available)
-        (_, KeyCode::Char('u')) | (_, KeyCode::Char('U')) => {
-            if state.update_available.is_some() {
+        // Undo / redo task operations
+        (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
+            let _ = state.redo();
+            return;
+        }
+        (_, KeyCode::Char('u')) => {
+            let _ = state.undo();
+            return;
+        }
+        // Update (when available), or reopen the progress of a running one
+        (_, KeyCode::Char('U')) => {
+            if state.update_cancel.is_some() {
+                state.mode = Mode::Updating;
+            } else if state.update_available.is_some() {
                 state.mode = Mode::UpdateConfirm;
             }
             return;
@@ -166,6 +276,18 @@ pub fn handle_key(state: &mut AppState, key: KeyEvent) {
 
 /// Handle tasks view keybindings
 fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
+    // Cut and paste tasks, before plain x (complete) and v (mark)
+    if key.modifiers.contains(KeyModifiers::CONTROL) && state.focus == Focus::Main {
+        match key.code {
+            KeyCode::Char('x') => return state.cut_tasks(),
+            KeyCode::Char('v') => {
+                let _ = state.paste_tasks();
+                return;
+            }
+            _ => {}
+        }
+    }
+
     match key.code {
         // Focus switching (sidebar/main) with h/l
         KeyCode::Char('h') | KeyCode::Left => {
@@ -177,11 +299,7 @@ fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
 
         // Navigation
         KeyCode::Char('j') | KeyCode::Down => match state.focus {
-            Focus::Sidebar => {
-                if !state.lists.is_empty() && state.list_index < state.lists.len() - 1 {
-                    state.list_index += 1;
-                }
-            }
+            Focus::Sidebar => state.set_sidebar_cursor(state.sidebar_cursor() + 1),
             Focus::Main => {
                 if !state.tasks.is_empty() && state.task_index < state.tasks.len() - 1 {
                     state.task_index += 1;
@@ -189,11 +307,7 @@ fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
             }
         },
         KeyCode::Char('k') | KeyCode::Up => match state.focus {
-            Focus::Sidebar => {
-                if state.list_index > 0 {
-                    state.list_index -= 1;
-                }
-            }
+            Focus::Sidebar => state.set_sidebar_cursor(state.sidebar_cursor().saturating_sub(1)),
             Focus::Main => {
                 if state.task_index > 0 {
                     state.task_index -= 1;
@@ -201,45 +315,93 @@ fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
             }
         },
         KeyCode::Char('g') | KeyCode::Home => match state.focus {
-            Focus::Sidebar => state.list_index = 0,
+            Focus::Sidebar => state.set_sidebar_cursor(0),
             Focus::Main => state.task_index = 0,
         },
         KeyCode::Char('G') | KeyCode::End => match state.focus {
-            Focus::Sidebar => {
-                if !state.lists.is_empty() {
-                    state.list_index = state.lists.len() - 1;
-                }
-            }
+            Focus::Sidebar => state.set_sidebar_cursor(state.sidebar_len()),
             Focus::Main => {
                 if !state.tasks.is_empty() {
                     state.task_index = state.tasks.len() - 1;
                 }
             }
         },
+        KeyCode::PageDown => {
+            let page = state.scroll.page();
+            match state.focus {
+                Focus::Sidebar => state.set_sidebar_cursor(state.sidebar_cursor() + page),
+                Focus::Main => {
+                    state.task_index = page_down(state.task_index, page, state.tasks.len())
+                }
+            }
+        }
+        KeyCode::PageUp => {
+            let page = state.scroll.page();
+            match state.focus {
+                Focus::Sidebar => {
+                    state.set_sidebar_cursor(state.sidebar_cursor().saturating_sub(page))
+                }
+                Focus::Main => state.task_index = state.task_index.saturating_sub(page),
+            }
+        }
 
         // Enter - select list or toggle task
         KeyCode::Enter => match state.focus {
-            Focus::Sidebar => {
-                if let Some(list) = state.lists.get(state.list_index) {
-                    // Inbox shows all tasks, other lists show only their tasks
-                    if list.is_inbox {
-                        state.selected_list_id = None;
-                    } else {
-                        state.selected_list_id = Some(list.id);
-                    }
-                }
-                let _ = state.refresh_tasks();
-                state.task_index = 0;
-                state.focus = Focus::Main;
-            }
+            Focus::Sidebar => state.open_sidebar_item(),
             Focus::Main => {
-                let _ = state.toggle_task();
+                if state.marked_tasks.is_empty() {
+                    let _ = state.toggle_task();
+                } else {
+                    let _ = state.toggle_marked();
+                }
             }
         },
 
         // Space or x - toggle task completion
         KeyCode::Char(' ') | KeyCode::Char('x') if state.focus == Focus::Main => {
-            let _ = state.toggle_task();
+            if state.marked_tasks.is_empty() {
+                let _ = state.toggle_task();
+            } else {
+                let _ = state.toggle_marked();
+            }
+        }
+
+        // Visual select: v marks the task, V marks a range from the last mark
+        KeyCode::Char('v') if state.focus == Focus::Main => {
+            state.toggle_mark();
+        }
+        KeyCode::Char('V') if state.focus == Focus::Main => {
+            state.mark_range();
+        }
+        KeyCode::Esc if !state.marked_tasks.is_empty() => {
+            state.clear_marks();
+        }
+        KeyCode::Esc if !state.cut_tasks.is_empty() => {
+            state.cancel_cut();
+        }
+        KeyCode::Esc if state.filter.is_active() => {
+            state.clear_filter();
+        }
+
+        // Reorder within the list (manual sort)
+        KeyCode::Char('J') if state.focus == Focus::Main => {
+            let _ = state.move_selected_task(1);
+        }
+        KeyCode::Char('K') if state.focus == Focus::Main => {
+            let _ = state.move_selected_task(-1);
+        }
+
+        // Move selected/marked tasks, or every shown task
+        KeyCode::Char('m') if state.focus == Focus::Main => {
+            state.start_move_tasks();
+        }
+        KeyCode::Char('M') if state.focus == Focus::Main => {
+            state.start_move_all_tasks();
+        }
+
+        // Bulk retag of marked tasks
+        KeyCode::Char('T') if state.focus == Focus::Main => {
+            state.start_tag_marked();
         }
 
         // Add new task (n like Hazelnut)
@@ -247,14 +409,88 @@ fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
             state.start_add_task();
         }
 
+        // Quick-add with inline syntax
+        KeyCode::Char('a') => {
+            state.start_quick_add();
+        }
+
+        // Timeline of dated tasks
+        KeyCode::Char('L') => {
+            state.open_timeline();
+        }
+
+        // Week planner
+        KeyCode::Char('w') => {
+            state.open_planner();
+        }
+
+        // Completion stats and heatmap
+        KeyCode::Char('I') => {
+            state.open_stats();
+        }
+
+        // Switch to another workspace's database
+        KeyCode::Char('W') => {
+            state.open_workspace_picker();
+        }
+
+        // Defer: push the due date of the selected (or marked) tasks
+        KeyCode::Char('.') if state.focus == Focus::Main => {
+            state.start_defer();
+        }
+
+        // Comment on the selected task
+        KeyCode::Char('N') if state.focus == Focus::Main => {
+            state.start_comment();
+        }
+
+        // Dependencies: pick a blocker / clear blockers
+        KeyCode::Char('b') if state.focus == Focus::Main => {
+            state.start_pick_blocker();
+        }
+        KeyCode::Char('B') if state.focus == Focus::Main => {
+            let _ = state.clear_blockers();
+        }
+
+        // Focus mode for the selected task
+        KeyCode::Char('z') if state.focus == Focus::Main => {
+            state.start_focus();
+        }
+
+        // Rename task in place
+        KeyCode::Char('i') if state.focus == Focus::Main => {
+            state.start_inline_edit();
+        }
+
         // Edit task (e like Hazelnut)
         KeyCode::Char('e') if state.focus == Focus::Main => {
             state.start_edit_task();
         }
 
+        // Duplicate task (y like yank)
+        KeyCode::Char('y') if state.focus == Focus::Main => {
+            let _ = state.duplicate_task();
+        }
+
         // Delete task (d like Hazelnut)
         KeyCode::Char('d') | KeyCode::Delete if state.focus == Focus::Main => {
-            state.confirm_delete_task();
+            if state.marked_tasks.is_empty() {
+                state.confirm_delete_task();
+            } else {
+                state.confirm_delete_marked();
+            }
+        }
+
+        // Nag: remind every few hours while overdue
+        KeyCode::Char('E') if state.focus == Focus::Main => {
+            if let Err(e) = state.toggle_nag() {
+                state.set_status(format!("Error: {}", e));
+            }
+        }
+
+        // Merge marked near-duplicates
+        KeyCode::Char('D') if state.focus == Focus::Main => {
+            state.confirm_merge_marked();
         }
 
         // Toggle show completed (c)
@@ -262,14 +498,39 @@ fn handle_tasks_view(state: &mut AppState, key: KeyEvent) {
             state.toggle_show_completed();
         }
 
+        // "Completed today" section: collapse/expand, reopen the latest
+        KeyCode::Char('C') => {
+            state.toggle_completed_today();
+        }
+        KeyCode::Char('R') => {
+            let _ = state.reopen_last_completed();
+        }
+
+        // Cycle sort order (s)
+        KeyCode::Char('s') => {
+            state.cycle_task_sort();
+        }
+
+        // Filter popup (f) / clear filter (F)
+        KeyCode::Char('f') => {
+            state.start_filter();
+        }
+        KeyCode::Char('F') => {
+            state.clear_filter();
+        }
+
         // Cycle priority (p)
         KeyCode::Char('p') if state.focus == Focus::Main => {
-            let _ = state.cycle_task_priority();
+            if state.marked_tasks.is_empty() {
+                let _ = state.cycle_task_priority();
+            } else {
+                let _ = state.cycle_marked_priority();
+            }
         }
 
         // Open URL (o)
         KeyCode::Char('o') if state.focus == Focus::Main => {
-            state.open_task_url();
+            state.open_task_link();
         }
 
         // Refresh (r)
@@ -301,15 +562,11 @@ fn handle_lists_view(state: &mut AppState, key: KeyEvent) {
 
     match key.code {
         // Navigation
-        KeyCode::Char('j') | KeyCode::Down => {
-            if state.list_index < len - 1 {
-                state.list_index += 1;
-            }
+        KeyCode::Char('j') | KeyCode::Down if state.list_index < len - 1 => {
+            state.list_index += 1;
         }
-        KeyCode::Char('k') | KeyCode::Up => {
-            if state.list_index > 0 {
-                state.list_index -= 1;
-            }
+        KeyCode::Char('k') | KeyCode::Up if state.list_index > 0 => {
+            state.list_index -= 1;
         }
         KeyCode::Char('g') | KeyCode::Home => {
             state.list_index = 0;
@@ -317,6 +574,12 @@ fn handle_lists_view(state: &mut AppState, key: KeyEvent) {
         KeyCode::Char('G') | KeyCode::End => {
             state.list_index = len - 1;
         }
+        KeyCode::PageDown => {
+            state.list_index = page_down(state.list_index, state.scroll.page(), len);
+        }
+        KeyCode::PageUp => {
+            state.list_index = state.list_index.saturating_sub(state.scroll.page());
+        }
 
         // Edit (e like Hazelnut)
         KeyCode::Char('e') => {
@@ -328,6 +591,11 @@ fn handle_lists_view(state: &mut AppState, key: KeyEvent) {
             state.confirm_delete_list();
         }
 
+        // Uncheck every task in a checklist
+        KeyCode::Char('x') => {
+            state.confirm_reset_list();
+        }
+
         _ => {}
     }
 }
@@ -351,15 +619,11 @@ fn handle_tags_view(state: &mut AppState, key: KeyEvent) {
 
     match key.code {
         // Navigation
-        KeyCode::Char('j') | KeyCode::Down => {
-            if state.tag_index < len - 1 {
-                state.tag_index += 1;
-            }
+        KeyCode::Char('j') | KeyCode::Down if state.tag_index < len - 1 => {
+            state.tag_index += 1;
         }
-        KeyCode::Char('k') | KeyCode::Up => {
-            if state.tag_index > 0 {
-                state.tag_index -= 1;
-            }
+        KeyCode::Char('k') | KeyCode::Up if state.tag_index > 0 => {
+            state.tag_index -= 1;
         }
         KeyCode::Char('g') | KeyCode::Home => {
             state.tag_index = 0;
@@ -367,6 +631,17 @@ fn handle_tags_view(state: &mut AppState, key: KeyEvent) {
         KeyCode::Char('G') | KeyCode::End => {
             state.tag_index = len - 1;
         }
+        KeyCode::PageDown => {
+            state.tag_index = page_down(state.tag_index, state.scroll.page(), len);
+        }
+        KeyCode::PageUp => {
+            state.tag_index = state.tag_index.saturating_sub(state.scroll.page());
+        }
+
+        // Show the tag's tasks
+        KeyCode::Enter => {
+            state.show_tasks_for_selected_tag();
+        }
 
         // Edit (e like Hazelnut)
         KeyCode::Char('e') => {
@@ -382,6 +657,11 @@ fn handle_tags_view(state: &mut AppState, key: KeyEvent) {
     }
 }
 
+/// Move an index down by a page, stopping at the last item
+fn page_down(index: usize, page: usize, len: usize) -> usize {
+    (index + page).min(len.saturating_sub(1))
+}
+
 /// Handle theme picker (like Hazelnut)
 /// Handle settings dialog
 fn handle_settings(state: &mut AppState, key: KeyEvent) {
@@ -469,34 +749,9 @@ fn handle_settings_input(state: &mut AppState, key: KeyEvent) {
             state.settings_editing = None;
             state.mode = Mode::Settings;
         }
-        KeyCode::Backspace => {
-            if state.cursor_pos > 0 {
-                state.input_buffer.remove(state.cursor_pos - 1);
-                state.cursor_pos -= 1;
-            }
-        }
-        KeyCode::Delete => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.input_buffer.remove(state.cursor_pos);
-            }
-        }
-        KeyCode::Left => {
-            state.cursor_pos = state.cursor_pos.saturating_sub(1);
-        }
-        KeyCode::Right => {
-            state.cursor_pos = (state.cursor_pos + 1).min(state.input_buffer.len());
-        }
-        KeyCode::Home => {
-            state.cursor_pos = 0;
-        }
-        KeyCode::End => {
-            state.cursor_pos = state.input_buffer.len();
-        }
-        KeyCode::Char(c) => {
-            state.input_buffer.insert(state.cursor_pos, c);
-            state.cursor_pos += 1;
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
         }
-        _ => {}
     }
 }
 
@@ -511,6 +766,37 @@ fn toggle_settings_item(state: &mut AppState) {
                 .unwrap_or(0);
             state.mode = Mode::ThemePicker;
         }
+        SettingsItem::Density => {
+            state.config.display.density = match state.config.display.density {
+                Density::Comfortable => Density::Compact,
+                Density::Compact => Density::Comfortable,
+            };
+            let _ = state.config.save();
+            let status = match state.config.display.density {
+                Density::Comfortable => "comfortable",
+                Density::Compact => "compact",
+            };
+            state.set_status(format!("Density: {}", status));
+        }
+        SettingsItem::Accessible
+        | SettingsItem::HighContrast
+        | SettingsItem::ConfirmDeleteTask
+        | SettingsItem::ConfirmBulkDelete
+        | SettingsItem::ConfirmDeleteList
+        | SettingsItem::ConfirmDeleteTag
+        | SettingsItem::ConfirmResetList
+        | SettingsItem::ConfirmDefaultYes => {
+            if let Some(on) = item.toggle_switch(&mut state.config) {
+                let _ = state.config.save();
+                let status = match (item, on) {
+                    (SettingsItem::ConfirmDefaultYes, true) => "Yes",
+                    (SettingsItem::ConfirmDefaultYes, false) => "No",
+                    (_, true) => "on",
+                    (_, false) => "off",
+                };
+                state.set_status(format!("{}: {}", item.label(), status));
+            }
+        }
         SettingsItem::SyncEnabled => {
             state.config.sync.enabled = !state.config.sync.enabled;
             let _ = state.config.save();
@@ -522,9 +808,9 @@ fn toggle_settings_item(state: &mut AppState) {
             state.set_status(format!("Sync {}", status));
         }
         SettingsItem::Notifications => {
-            state.config.notifications = !state.config.notifications;
+            state.config.notifications.enabled = !state.config.notifications.enabled;
             let _ = state.config.save();
-            let status = if state.config.notifications {
+            let status = if state.config.notifications.enabled {
                 "enabled"
             } else {
                 "disabled"
@@ -593,6 +879,7 @@ fn adjust_settings_item(state: &mut AppState, delta: i32) {
                 current_idx.checked_sub(1).unwrap_or(len - 1)
             };
             state.set_theme(Theme::from(themes[new_idx]));
+            let _ = state.config.save();
             state.set_status(format!("Theme: {}", state.theme.name()));
         }
         SettingsItem::SyncInterval => {
@@ -627,6 +914,7 @@ fn handle_theme_picker(state: &mut AppState, key: KeyEvent) {
             state.mode = Mode::Normal;
         }
         KeyCode::Enter => {
+            let _ = state.config.save();
             state.set_status(format!("Theme: {}", state.theme.name()));
             state.mode = Mode::Normal;
         }
@@ -675,7 +963,7 @@ fn handle_task_editor(state: &mut AppState, key: KeyEvent) {
     // Check if we're in a text input field
     let is_text_field = matches!(
         state.editor_field,
-        EditorField::Title | EditorField::Description | EditorField::DueDate
+        EditorField::Title | EditorField::Description | EditorField::Url | EditorField::DueDate
     );
 
     match key.code {
@@ -683,6 +971,22 @@ fn handle_task_editor(state: &mut AppState, key: KeyEvent) {
             state.mode = Mode::Normal;
             state.editing_task = None;
         }
+        // Shift/Alt+Enter adds a line break in the description
+        KeyCode::Enter
+            if state.editor_field == EditorField::Description
+                && key
+                    .modifiers
+                    .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+        {
+            input::handle_key(
+                &mut state.input_buffer,
+                &mut state.cursor_pos,
+                KeyEvent::new(KeyCode::Char('\n'), KeyModifiers::NONE),
+            );
+        }
+        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            state.start_external_description_edit();
+        }
         KeyCode::Enter if !key.modifiers.contains(KeyModifiers::SHIFT) => {
             // If on Tags field and cursor is on "Add new", start adding
             if state.editor_field == EditorField::Tags
@@ -696,37 +1000,38 @@ fn handle_task_editor(state: &mut AppState, key: KeyEvent) {
         KeyCode::Tab => state.next_editor_field(),
         KeyCode::BackTab => state.prev_editor_field(),
 
-        // Text input for title and description fields
-        KeyCode::Char(c) if is_text_field => {
-            state.input_buffer.insert(state.cursor_pos, c);
-            state.cursor_pos += 1;
-        }
-        KeyCode::Backspace if is_text_field => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
-                state.input_buffer.remove(state.cursor_pos);
-            }
-        }
-        KeyCode::Delete if is_text_field => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.input_buffer.remove(state.cursor_pos);
-            }
-        }
-        KeyCode::Left if is_text_field => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
-            }
-        }
-        KeyCode::Right if is_text_field => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.cursor_pos += 1;
+        // Due date picker (typing still works for natural-language dates)
+        KeyCode::Down if state.editor_field == EditorField::DueDate => {
+            state.shift_editor_due_date(1);
+        }
+        KeyCode::Up if state.editor_field == EditorField::DueDate => {
+            state.shift_editor_due_date(-1);
+        }
+        KeyCode::PageDown if state.editor_field == EditorField::DueDate => {
+            state.shift_editor_due_date(7);
+        }
+        KeyCode::PageUp if state.editor_field == EditorField::DueDate => {
+            state.shift_editor_due_date(-7);
+        }
+        KeyCode::Char(c @ ('t' | 'n' | 'b' | 'w' | 'x'))
+            if state.editor_field == EditorField::DueDate
+                && key.modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            let today = chrono::Local::now().date_naive();
+            match c {
+                't' => state.set_editor_due_date(Some(today)),
+                'n' => state.set_editor_due_date(Some(today + chrono::Duration::days(1))),
+                'b' => state.set_editor_due_date(
+                    crate::holidays::business_days().add_business_days(today, 1),
+                ),
+                'w' => state.set_editor_due_date(Some(today + chrono::Duration::days(7))),
+                _ => state.set_editor_due_date(None),
             }
         }
-        KeyCode::Home if is_text_field => {
-            state.cursor_pos = 0;
-        }
-        KeyCode::End if is_text_field => {
-            state.cursor_pos = state.input_buffer.len();
+
+        // Text input for title, description and due date fields
+        _ if is_text_field => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
         }
 
         // Priority field
@@ -738,15 +1043,16 @@ fn handle_task_editor(state: &mut AppState, key: KeyEvent) {
         }
 
         // List field
-        KeyCode::Char('j') | KeyCode::Down if state.editor_field == EditorField::List => {
-            if state.editor_list_index < state.lists.len() - 1 {
-                state.editor_list_index += 1;
-            }
+        KeyCode::Char('j') | KeyCode::Down
+            if state.editor_field == EditorField::List
+                && state.editor_list_index < state.lists.len() - 1 =>
+        {
+            state.editor_list_index += 1;
         }
-        KeyCode::Char('k') | KeyCode::Up if state.editor_field == EditorField::List => {
-            if state.editor_list_index > 0 {
-                state.editor_list_index -= 1;
-            }
+        KeyCode::Char('k') | KeyCode::Up
+            if state.editor_field == EditorField::List && state.editor_list_index > 0 =>
+        {
+            state.editor_list_index -= 1;
         }
 
         // Tags field - navigate and toggle
@@ -771,6 +1077,8 @@ fn handle_task_editor(state: &mut AppState, key: KeyEvent) {
 
 /// Handle list editor
 fn handle_list_editor(state: &mut AppState, key: KeyEvent) {
+    let color_focused = state.editor_field == EditorField::Color;
+    let checklist_focused = state.editor_field == EditorField::Checklist;
     match key.code {
         KeyCode::Esc => {
             state.mode = Mode::Normal;
@@ -779,32 +1087,33 @@ fn handle_list_editor(state: &mut AppState, key: KeyEvent) {
         KeyCode::Enter => {
             let _ = state.save_list();
         }
-        KeyCode::Char(c) => {
-            state.input_buffer.insert(state.cursor_pos, c);
-            state.cursor_pos += 1;
+        KeyCode::Tab => {
+            state.editor_field = match state.editor_field {
+                EditorField::Name => EditorField::Color,
+                EditorField::Color => EditorField::Checklist,
+                _ => EditorField::Name,
+            };
         }
-        KeyCode::Backspace => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
-                state.input_buffer.remove(state.cursor_pos);
-            }
+        KeyCode::BackTab => {
+            state.editor_field = match state.editor_field {
+                EditorField::Name => EditorField::Checklist,
+                EditorField::Checklist => EditorField::Color,
+                _ => EditorField::Name,
+            };
         }
-        KeyCode::Delete => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.input_buffer.remove(state.cursor_pos);
-            }
+        KeyCode::Char(' ') if checklist_focused => {
+            state.editor_list_checklist = !state.editor_list_checklist;
         }
-        KeyCode::Left => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
-            }
+        KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') if color_focused => {
+            state.cycle_list_color(true);
         }
-        KeyCode::Right => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.cursor_pos += 1;
-            }
+        KeyCode::Left | KeyCode::Char('h') if color_focused => {
+            state.cycle_list_color(false);
+        }
+        _ if color_focused || checklist_focused => {}
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
         }
-        _ => {}
     }
 }
 
@@ -818,44 +1127,441 @@ fn handle_tag_editor(state: &mut AppState, key: KeyEvent) {
         KeyCode::Enter => {
             let _ = state.save_tag();
         }
-        KeyCode::Char(c) => {
-            state.input_buffer.insert(state.cursor_pos, c);
-            state.cursor_pos += 1;
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
         }
-        KeyCode::Backspace => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
-                state.input_buffer.remove(state.cursor_pos);
-            }
+    }
+}
+
+/// Handle quick-add prompt
+fn handle_quick_add(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+        }
+        KeyCode::Enter => {
+            let _ = state.save_quick_add();
         }
-        KeyCode::Delete => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.input_buffer.remove(state.cursor_pos);
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
+        }
+    }
+}
+
+/// Handle the comment prompt
+fn handle_comment(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = state.save_comment() {
+                state.set_status(format!("Could not add comment: {}", e));
             }
         }
-        KeyCode::Left => {
-            if state.cursor_pos > 0 {
-                state.cursor_pos -= 1;
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
+        }
+    }
+}
+
+fn handle_defer(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = state.save_defer() {
+                state.set_status(format!("Could not defer: {}", e));
             }
         }
-        KeyCode::Right => {
-            if state.cursor_pos < state.input_buffer.len() {
-                state.cursor_pos += 1;
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
+        }
+    }
+}
+
+/// Handle the help overlay: typing filters, arrows scroll
+fn handle_help(state: &mut AppState, key: KeyEvent) {
+    let filtering = !state.input_buffer.is_empty();
+    let offset = state.scroll.help.get();
+    let page = state.scroll.page();
+
+    match key.code {
+        KeyCode::Esc if filtering => {
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+            state.scroll.help.set(0);
+        }
+        KeyCode::Esc | KeyCode::Enter | KeyCode::F(1) => state.close_help(),
+        KeyCode::Char('?') if !filtering => state.close_help(),
+        KeyCode::Down => state.scroll.help.set(offset + 1),
+        KeyCode::Up => state.scroll.help.set(offset.saturating_sub(1)),
+        KeyCode::PageDown => state.scroll.help.set(offset + page),
+        KeyCode::PageUp => state.scroll.help.set(offset.saturating_sub(page)),
+        _ => {
+            if input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key) {
+                state.scroll.help.set(0);
             }
         }
+    }
+}
+
+/// Handle focus mode (minimal keymap)
+fn handle_focus(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('z') | KeyCode::Char('q') => state.exit_focus(),
+        KeyCode::Char(' ') => {
+            let _ = state.toggle_focus_task();
+        }
+        KeyCode::Enter | KeyCode::Char('x') => {
+            let _ = state.toggle_focus_item();
+        }
+        KeyCode::Char('j') | KeyCode::Down => {
+            let last = state.focus_checklist().len().saturating_sub(1);
+            state.focus_item = (state.focus_item + 1).min(last);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.focus_item = state.focus_item.saturating_sub(1);
+        }
+        KeyCode::Char('p') => state.focus_timer.toggle(),
+        KeyCode::Char('r') => state.focus_timer.restart(),
+        _ => {}
+    }
+}
+
+/// Handle the timeline: h/l scroll by a week, H/L by a month, j/k by a row
+fn handle_timeline(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => state.mode = Mode::Normal,
+        KeyCode::Char('h') | KeyCode::Left => state.scroll_timeline_weeks(-1),
+        KeyCode::Char('l') | KeyCode::Right => state.scroll_timeline_weeks(1),
+        KeyCode::Char('H') | KeyCode::PageUp => state.scroll_timeline_month(false),
+        KeyCode::Char('L') | KeyCode::PageDown => state.scroll_timeline_month(true),
+        KeyCode::Char('t') => state.open_timeline(),
+        KeyCode::Char('j') | KeyCode::Down => state.timeline_scroll += 1,
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.timeline_scroll = state.timeline_scroll.saturating_sub(1);
+        }
         _ => {}
     }
 }
 
+/// Handle the week planner: h/j/k/l move the cursor, H/L move the task a
+/// column, 1-7 put it on a day and 0 back in the backlog
+fn handle_planner(state: &mut AppState, key: KeyEvent) {
+    let (column, _) = state.planner_cursor;
+    let target = match key.code {
+        KeyCode::Char('H') => column.saturating_sub(1),
+        KeyCode::Char('L') => column + 1,
+        KeyCode::Char(c @ '0'..='7') => c as usize - '0' as usize,
+        KeyCode::Backspace => 0,
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') => {
+            state.mode = Mode::Normal;
+            return;
+        }
+        KeyCode::Char('h') | KeyCode::Left => return state.move_planner_cursor(-1, 0),
+        KeyCode::Char('l') | KeyCode::Right => return state.move_planner_cursor(1, 0),
+        KeyCode::Char('j') | KeyCode::Down => return state.move_planner_cursor(0, 1),
+        KeyCode::Char('k') | KeyCode::Up => return state.move_planner_cursor(0, -1),
+        KeyCode::Char('[') | KeyCode::PageUp => return state.scroll_planner_weeks(-1),
+        KeyCode::Char(']') | KeyCode::PageDown => return state.scroll_planner_weeks(1),
+        KeyCode::Char('t') => return state.open_planner(),
+        KeyCode::Char('u') => {
+            let _ = state.undo();
+            return state.move_planner_cursor(0, 0);
+        }
+        _ => return,
+    };
+    if let Err(e) = state.plan_task(target) {
+        state.set_status(format!("Error: {}", e));
+    }
+}
+
+/// Handle inline title editing in the task list
+fn handle_inline_edit(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+        }
+        KeyCode::Enter => {
+            let _ = state.save_inline_edit();
+        }
+        _ => {
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
+        }
+    }
+}
+
+/// Handle the fuzzy jump finder
+fn handle_jump(state: &mut AppState, key: KeyEvent) {
+    let len = state.jump_results.len();
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.jump_blocker_for = None;
+            state.input_buffer.clear();
+            state.cursor_pos = 0;
+        }
+        KeyCode::Enter => {
+            let _ = state.jump_to_picked_task();
+        }
+        KeyCode::Down | KeyCode::Tab => {
+            if len > 0 {
+                state.picker_index = (state.picker_index + 1) % len;
+            }
+        }
+        KeyCode::Char('n') | KeyCode::Char('j') if ctrl => {
+            if len > 0 {
+                state.picker_index = (state.picker_index + 1) % len;
+            }
+        }
+        KeyCode::Up | KeyCode::BackTab => {
+            if len > 0 {
+                state.picker_index = (state.picker_index + len - 1) % len;
+            }
+        }
+        KeyCode::Char('p') | KeyCode::Char('k') if ctrl => {
+            if len > 0 {
+                state.picker_index = (state.picker_index + len - 1) % len;
+            }
+        }
+        _ => {
+            let before = state.input_buffer.clone();
+            input::handle_key(&mut state.input_buffer, &mut state.cursor_pos, key);
+            if state.input_buffer != before {
+                state.update_jump_results();
+            }
+        }
+    }
+}
+
 /// Handle confirmation dialog
 fn handle_confirm(state: &mut AppState, key: KeyEvent) {
     match key.code {
-        KeyCode::Char('y') | KeyCode::Enter => {
+        KeyCode::Char('y') => {
             let _ = state.execute_confirm();
         }
         KeyCode::Char('n') | KeyCode::Esc => {
             state.cancel_confirm();
         }
+        KeyCode::Char('r') => {
+            state.start_reassign_tag();
+        }
+        KeyCode::Char('m') => {
+            state.start_move_list_tasks();
+        }
+        KeyCode::Char('a') => {
+            state.set_deleted_list_tasks(DeletedListTasks::Archive);
+        }
+        KeyCode::Char('x') => {
+            state.set_deleted_list_tasks(DeletedListTasks::Delete);
+        }
+        KeyCode::Char('e') => {
+            if let Err(e) = state.export_deleted_tag_tasks() {
+                state.set_status(format!("Export failed: {}", e));
+            }
+        }
+        KeyCode::Enter => {
+            if state.confirm_yes {
+                let _ = state.execute_confirm();
+            } else {
+                state.cancel_confirm();
+            }
+        }
+        KeyCode::Left
+        | KeyCode::Right
+        | KeyCode::Tab
+        | KeyCode::BackTab
+        | KeyCode::Char('h')
+        | KeyCode::Char('l') => {
+            state.confirm_yes = !state.confirm_yes;
+        }
+        _ => {}
+    }
+}
+
+/// Handle list picker for moving marked tasks
+fn handle_move_tasks(state: &mut AppState, key: KeyEvent) {
+    let len = state.lists.len();
+
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter => {
+            let _ = state.move_tasks_to_picked_list();
+        }
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle the link picker; 1-9 open a link directly
+fn handle_open_link(state: &mut AppState, key: KeyEvent) {
+    let len = state.link_choices.len();
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter | KeyCode::Char('o') => state.open_picked_link(),
+        KeyCode::Char(c @ '1'..='9') => {
+            let index = c as usize - '1' as usize;
+            if index < len {
+                state.picker_index = index;
+                state.open_picked_link();
+            }
+        }
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle the workspace picker
+fn handle_workspaces(state: &mut AppState, key: KeyEvent) {
+    let len = state.config.workspace_names().len();
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter => state.switch_to_picked_workspace(),
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle the startup warnings
+fn handle_warnings(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('!') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Char('d') | KeyCode::Char('x') => state.dismiss_warning(),
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < state.warnings.len() => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle list picker for where a deleted list's tasks go
+fn handle_move_list_tasks(state: &mut AppState, key: KeyEvent) {
+    let len = state.move_list_candidates().len();
+
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Confirm;
+        }
+        KeyCode::Enter => {
+            state.pick_deleted_list_target();
+        }
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle tag picker for moving a deleted tag's tasks
+fn handle_reassign_tag(state: &mut AppState, key: KeyEvent) {
+    let len = state.reassign_candidates().len();
+
+    match key.code {
+        KeyCode::Esc => {
+            state.mode = Mode::Confirm;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = state.reassign_deleted_tag() {
+                state.set_status(format!("Error: {}", e));
+            }
+        }
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle tag picker for retagging marked tasks
+fn handle_tag_tasks(state: &mut AppState, key: KeyEvent) {
+    let len = state.tags.len();
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Char(' ') => {
+            let _ = state.toggle_picked_tag_on_marked();
+        }
+        KeyCode::Char('j') | KeyCode::Down if state.picker_index + 1 < len => {
+            state.picker_index += 1;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.picker_index = state.picker_index.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+/// Handle filter popup
+fn handle_filter(state: &mut AppState, key: KeyEvent) {
+    let len = FilterRow::all().len();
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('f') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Char('j') | KeyCode::Down => {
+            state.filter_row = (state.filter_row + 1) % len;
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            state.filter_row = state.filter_row.checked_sub(1).unwrap_or(len - 1);
+        }
+        KeyCode::Char('l') | KeyCode::Right | KeyCode::Char(' ') => {
+            state.adjust_filter(1);
+        }
+        KeyCode::Char('h') | KeyCode::Left => {
+            state.adjust_filter(-1);
+        }
+        KeyCode::Char('F') | KeyCode::Backspace => {
+            state.clear_filter();
+        }
         _ => {}
     }
 }
@@ -867,6 +1573,19 @@ fn handle_export(state: &mut AppState, key: KeyEvent) {
     }
 }
 
+/// Handle the sync panel: s/r sync, f forces a full sync, p pauses
+fn handle_sync_panel(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => {
+            state.mode = Mode::Normal;
+        }
+        KeyCode::Enter | KeyCode::Char('s') | KeyCode::Char('r') => state.request_sync(false),
+        KeyCode::Char('f') => state.request_sync(true),
+        KeyCode::Char('p') => state.toggle_sync_pause(),
+        _ => {}
+    }
+}
+
 /// Handle about dialog
 fn handle_about(state: &mut AppState, key: KeyEvent) {
     match key.code {
@@ -878,10 +1597,8 @@ fn handle_about(state: &mut AppState, key: KeyEvent) {
             let _ = open::that("https://github.com/ricardodantas/tickit");
         }
         // Handle update from about dialog
-        KeyCode::Char('u') | KeyCode::Char('U') => {
-            if state.update_available.is_some() {
-                state.mode = Mode::UpdateConfirm;
-            }
+        KeyCode::Char('u') | KeyCode::Char('U') if state.update_available.is_some() => {
+            state.mode = Mode::UpdateConfirm;
         }
         _ => {}
     }
@@ -902,24 +1619,16 @@ fn handle_update_confirm_key(state: &mut AppState, key: KeyEvent) {
     }
 }
 
-/// Process pending update (called from main loop)
-pub fn process_pending_update(state: &mut AppState) {
-    if !state.pending_update {
-        return;
-    }
-    state.pending_update = false;
-
-    let pm = crate::detect_package_manager();
-    match crate::run_update(&pm) {
-        Ok(()) => {
-            state.update_result = Some("Update complete! Please restart tickit.".to_string());
-            state.update_available = None;
-        }
-        Err(e) => {
-            state.update_result = Some(format!("Update failed: {}", e));
+/// Handle keys while an update runs: cancel it, or hide the overlay and keep working
+fn handle_updating(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('c') | KeyCode::Char('C') => state.cancel_update(),
+        KeyCode::Esc => {
+            state.mode = Mode::Normal;
+            state.set_status("Update continues in the background (U to show)");
         }
+        _ => {}
     }
-    state.mode = Mode::Normal;
 }
 
 /// OLD IMPLEMENTATION - keeping for reference but update now goes through UpdateConfirm
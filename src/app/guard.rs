@@ -0,0 +1,137 @@
+//! Terminal setup that is undone however the TUI exits
+//!
+//! [`TerminalGuard`] restores the terminal when dropped, and the panic hook
+//! restores it before printing anything, so a crash leaves a usable shell and
+//! a crash report instead of a screen stuck in raw mode.
+
+use std::fmt::Write as _;
+use std::io::{Write, stdout};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use anyhow::Result;
+use crossterm::{
+    cursor::Show,
+    execute,
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+        is_raw_mode_enabled,
+    },
+};
+
+use crate::config::Config;
+
+/// Raw mode and the alternate screen, for as long as this is alive
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Take over the terminal, installing the panic hook on first use
+    pub fn enter() -> Result<Self> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        if let Err(e) = execute!(stdout(), EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(e.into());
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leave raw mode and the alternate screen; a no-op when the TUI isn't running
+fn restore() {
+    if is_raw_mode_enabled().unwrap_or(false) {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen, Show);
+    }
+}
+
+/// Restore the terminal and write a crash report before the panic unwinds
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let tui_running = is_raw_mode_enabled().unwrap_or(false);
+            restore();
+            // Panics outside the TUI (or in background threads after it
+            // closed) keep the standard message
+            if !tui_running {
+                default_hook(info);
+                return;
+            }
+
+            let report = crash_report(info);
+            let mut stderr = std::io::stderr();
+            let _ = writeln!(stderr, "tickit crashed: {}", panic_message(info));
+            match write_crash_log(&report) {
+                Ok(path) => {
+                    let _ = writeln!(stderr, "Details were saved to {}", path.display());
+                }
+                Err(_) => {
+                    let _ = writeln!(stderr, "\n{}", report);
+                }
+            }
+            let _ = writeln!(
+                stderr,
+                "Your tasks are safe. Please report this at {}/issues",
+                env!("CARGO_PKG_REPOSITORY")
+            );
+        }));
+    });
+}
+
+/// The panic payload, when it's a string
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string())
+}
+
+/// Everything worth knowing about a panic, as plain text
+fn crash_report(info: &PanicHookInfo) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "tickit {} crashed at {}",
+        crate::VERSION,
+        chrono::Local::now()
+    );
+    let _ = writeln!(
+        report,
+        "{} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "message: {}", panic_message(info));
+    if let Some(location) = info.location() {
+        let _ = writeln!(report, "location: {}", location);
+    }
+    let _ = writeln!(report, "\n{}", std::backtrace::Backtrace::force_capture());
+    report
+}
+
+/// Append the report to crash.log next to config.toml
+fn write_crash_log(report: &str) -> Result<PathBuf> {
+    let dir = Config::default_path()?
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow::anyhow!("No config directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("crash.log");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", report)?;
+    Ok(path)
+}
@@ -0,0 +1,158 @@
+//! Single-line text input editing
+//!
+//! The cursor is a byte offset into the buffer that always sits on a grapheme
+//! boundary, so multi-byte characters and emoji are edited as a unit.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Apply an editing key to `buffer`, returning whether the key was handled
+///
+/// Handles typing, Backspace/Delete, Left/Right, Home/End, Ctrl+Left/Right word
+/// jumps, Ctrl+W delete-word and Ctrl+U clear-line.
+pub fn handle_key(buffer: &mut String, cursor: &mut usize, key: KeyEvent) -> bool {
+    *cursor = (*cursor).min(buffer.len());
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        KeyCode::Char('w') if ctrl => {
+            let start = prev_word(buffer, *cursor);
+            buffer.replace_range(start..*cursor, "");
+            *cursor = start;
+        }
+        KeyCode::Char('u') if ctrl => {
+            buffer.clear();
+            *cursor = 0;
+        }
+        KeyCode::Char(_) if ctrl => return false,
+        KeyCode::Char(c) => {
+            buffer.insert(*cursor, c);
+            *cursor += c.len_utf8();
+        }
+        KeyCode::Backspace => {
+            let start = prev_grapheme(buffer, *cursor);
+            buffer.replace_range(start..*cursor, "");
+            *cursor = start;
+        }
+        KeyCode::Delete => {
+            let end = next_grapheme(buffer, *cursor);
+            buffer.replace_range(*cursor..end, "");
+        }
+        KeyCode::Left if ctrl => *cursor = prev_word(buffer, *cursor),
+        KeyCode::Right if ctrl => *cursor = next_word(buffer, *cursor),
+        KeyCode::Left => *cursor = prev_grapheme(buffer, *cursor),
+        KeyCode::Right => *cursor = next_grapheme(buffer, *cursor),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = buffer.len(),
+        _ => return false,
+    }
+    true
+}
+
+/// Display column of the cursor (terminal cells before it)
+pub fn cursor_col(buffer: &str, cursor: usize) -> u16 {
+    buffer
+        .get(..cursor.min(buffer.len()))
+        .map_or(0, |before| before.width() as u16)
+}
+
+/// Display (column, row) of the cursor in a buffer that may contain line breaks
+pub fn cursor_line_col(buffer: &str, cursor: usize) -> (u16, u16) {
+    let before = buffer.get(..cursor.min(buffer.len())).unwrap_or(buffer);
+    let row = before.matches('\n').count() as u16;
+    let line = before.rsplit('\n').next().unwrap_or("");
+    (line.width() as u16, row)
+}
+
+/// Byte offset of the grapheme before `cursor`
+fn prev_grapheme(buffer: &str, cursor: usize) -> usize {
+    buffer[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(i, _)| i)
+}
+
+/// Byte offset of the grapheme after `cursor`
+fn next_grapheme(buffer: &str, cursor: usize) -> usize {
+    buffer[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(cursor, |g| cursor + g.len())
+}
+
+/// Start of the word before `cursor`, skipping any whitespace first
+fn prev_word(buffer: &str, cursor: usize) -> usize {
+    let before = buffer[..cursor].trim_end();
+    before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// End of the word after `cursor`, skipping any whitespace first
+fn next_word(buffer: &str, cursor: usize) -> usize {
+    let after = &buffer[cursor..];
+    let word_start = after.len() - after.trim_start().len();
+    after[word_start..]
+        .char_indices()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(buffer.len(), |(i, _)| cursor + word_start + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Press a sequence of keys on a buffer with the cursor at the end
+    fn edit(text: &str, keys: &[(KeyCode, KeyModifiers)]) -> (String, usize) {
+        let mut buffer = text.to_string();
+        let mut cursor = buffer.len();
+        for &(code, modifiers) in keys {
+            handle_key(&mut buffer, &mut cursor, KeyEvent::new(code, modifiers));
+        }
+        (buffer, cursor)
+    }
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+    const CTRL: KeyModifiers = KeyModifiers::CONTROL;
+
+    #[test]
+    fn test_multibyte_editing() {
+        let typed: Vec<_> = "café 🎉"
+            .chars()
+            .map(|c| (KeyCode::Char(c), NONE))
+            .collect();
+        let (buffer, cursor) = edit("", &typed);
+        assert_eq!(cursor, buffer.len());
+        assert_eq!(cursor_col(&buffer, cursor), 7);
+
+        let keys = [
+            (KeyCode::Backspace, NONE),
+            (KeyCode::Backspace, NONE),
+            (KeyCode::Left, NONE),
+            (KeyCode::Delete, NONE),
+        ];
+        assert_eq!(edit(&buffer, &keys), ("caf".to_string(), 3));
+    }
+
+    #[test]
+    fn test_word_navigation() {
+        let text = "buy oat milk";
+        assert_eq!(edit(text, &[(KeyCode::Left, CTRL)]).1, 8);
+        assert_eq!(edit(text, &[(KeyCode::Left, CTRL); 2]).1, 4);
+
+        let keys = [
+            (KeyCode::Left, CTRL),
+            (KeyCode::Left, CTRL),
+            (KeyCode::Right, CTRL),
+            (KeyCode::Char('w'), CTRL),
+        ];
+        assert_eq!(edit(text, &keys), ("buy  milk".to_string(), 4));
+        assert_eq!(
+            edit(text, &[(KeyCode::Char('u'), CTRL)]),
+            (String::new(), 0)
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Keybinding reference
+//!
+//! The help overlay and the status bar hints are generated from this table, so
+//! new bindings only need to be described here once. A test checks that every
+//! key the Normal mode handlers in `events.rs` match on is listed here.
+
+/// A key (or key combination) and what it does
+#[derive(Debug, Clone, Copy)]
+pub struct Binding {
+    pub keys: &'static str,
+    pub action: &'static str,
+    /// Short form for the status bar, if the binding earns a place there
+    pub hint: Option<Hint>,
+}
+
+/// A key and a word or two about it, as shown in the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    pub key: &'static str,
+    pub label: &'static str,
+}
+
+/// Where the user is, which decides the status bar hints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// Task list of the Tasks view
+    Tasks,
+    /// Sidebar of the Tasks view
+    Sidebar,
+    /// Lists or Tags view
+    Collections,
+    /// Task editor
+    Editor,
+}
+
+/// A group of bindings shown under one heading
+#[derive(Debug, Clone, Copy)]
+pub struct Section {
+    pub title: &'static str,
+    /// Where the hints of this section show in the status bar
+    pub contexts: &'static [Context],
+    pub bindings: &'static [Binding],
+}
+
+const fn bind(keys: &'static str, action: &'static str) -> Binding {
+    Binding {
+        keys,
+        action,
+        hint: None,
+    }
+}
+
+impl Binding {
+    const fn hint(self, key: &'static str, label: &'static str) -> Self {
+        Self {
+            hint: Some(Hint { key, label }),
+            ..self
+        }
+    }
+}
+
+const BROWSING: &[Context] = &[Context::Tasks, Context::Sidebar, Context::Collections];
+
+/// All keybindings, grouped as shown in the help overlay
+pub const SECTIONS: &[Section] = &[
+    Section {
+        title: "Navigation",
+        contexts: BROWSING,
+        bindings: &[
+            bind("Tab / Shift+Tab", "Switch between views").hint("Tab", "views"),
+            bind("1-3", "Jump to view directly"),
+            bind("j/k or ↑/↓", "Navigate lists"),
+            bind("'", "Fuzzy jump to any task"),
+            bind("g/G or Home/End", "Go to first/last item"),
+            bind("PgUp / PgDn", "Scroll by a page"),
+            bind("h/l or ←/→", "Focus sidebar/main").hint("l", "tasks"),
+        ],
+    },
+    Section {
+        title: "Tasks View",
+        contexts: &[Context::Tasks],
+        bindings: &[
+            bind("Enter/Space/x", "Toggle task complete").hint("Space", "done"),
+            bind("n", "Create new task").hint("n", "new"),
+            bind("a", "Quick add (#tag @list !high due:tomorrow)"),
+            bind("e", "Edit selected task").hint("e", "edit"),
+            bind("i", "Rename task in place"),
+            bind("d / Delete", "Delete selected task"),
+            bind("y", "Duplicate selected task"),
+            bind("p", "Cycle priority"),
+            bind("o", "Open task link (pick one if several)"),
+            bind("c", "Toggle show completed"),
+            bind("C / R", "Collapse completed today / reopen latest"),
+            bind("s", "Cycle sort order"),
+            bind("J / K", "Move task down/up (manual sort)"),
+            bind("f / F", "Filter tasks / clear filter (also Esc)"),
+            bind("v / V", "Mark task / mark range").hint("v", "select"),
+            bind("m / M", "Move task (or marked) / all shown tasks").hint("m", "move"),
+            bind("Ctrl+x / Ctrl+v", "Cut task (or marked) / paste here"),
+            bind("T", "Add/remove a tag on marked tasks"),
+            bind("D", "Merge marked tasks into the oldest"),
+            bind("E", "Nag: remind every few hours while overdue (toggle)"),
+            bind("b / B", "Add a blocking task / clear blockers"),
+            bind("N", "Comment on selected task"),
+            bind(".", "Defer task (or marked) to a date: monday, +1w..."),
+            bind("z", "Focus mode (full-screen task with timer)"),
+            bind("L", "Timeline of dated tasks (h/l week, H/L month)"),
+            bind("w", "Week planner: move tasks onto days (H/L, 1-7)"),
+            bind("I", "Stats: completions, streaks and heatmap (b burndown)"),
+            bind("W", "Switch workspace (another database)"),
+            bind("u / Ctrl+r", "Undo / redo last task change"),
+        ],
+    },
+    Section {
+        title: "Lists / Tags View",
+        contexts: &[Context::Collections],
+        bindings: &[
+            bind("n", "Create new item").hint("n", "new"),
+            bind("e", "Edit selected item").hint("e", "edit"),
+            bind("d / Delete", "Delete selected item").hint("d", "delete"),
+            bind("x", "Reset checklist (uncheck all its tasks)"),
+            bind("Enter", "Show tasks with the selected tag"),
+        ],
+    },
+    Section {
+        title: "Task Editor",
+        contexts: &[Context::Editor],
+        bindings: &[
+            bind("Tab / Shift+Tab", "Next/previous field").hint("Tab", "next field"),
+            bind("Enter / Esc", "Save / cancel").hint("Enter", "save"),
+            bind("Space", "Toggle tag (Tags field)"),
+            bind("↑/↓ PgUp/PgDn", "Change due date by a day/week"),
+            bind("Ctrl+t/n/w/x", "Due today/tomorrow/next week/clear").hint("Ctrl+t", "due today"),
+            bind("Ctrl+←/→", "Jump by word"),
+            bind("Ctrl+w / Ctrl+u", "Delete word / clear field"),
+            bind("Shift+Enter", "New line in the description"),
+            bind("Ctrl+e", "Edit the description in $EDITOR").hint("Ctrl+e", "$EDITOR"),
+        ],
+    },
+    Section {
+        title: "General",
+        contexts: BROWSING,
+        bindings: &[
+            bind(",", "Open settings (also s outside Tasks)"),
+            bind("t", "Open theme selector"),
+            bind("A", "About Tickit"),
+            bind("r", "Refresh data"),
+            bind("? / F1", "Toggle this help").hint("?", "help"),
+            bind("Ctrl+s", "Sync with server (if configured)"),
+            bind("S", "Sync panel: status, force sync, pause"),
+            bind("P", "Pause/resume auto-sync until resumed"),
+            bind("!", "Startup warnings (d dismisses one)"),
+            bind("U", "Install an available update"),
+            bind("q / Ctrl+c / Ctrl+q", "Quit application").hint("q", "quit"),
+        ],
+    },
+];
+
+/// Status bar hints for `context`, most specific first
+pub fn hints(context: Context) -> Vec<Hint> {
+    let (specific, shared): (Vec<&Section>, Vec<&Section>) = SECTIONS
+        .iter()
+        .filter(|section| section.contexts.contains(&context))
+        .partition(|section| section.contexts.len() == 1);
+    specific
+        .into_iter()
+        // General before Navigation, so help and quit are the last to go
+        // when the status bar is narrow
+        .chain(shared.into_iter().rev())
+        .flat_map(|section| section.bindings.iter().filter_map(|b| b.hint))
+        // Focusing the task list only means something from the sidebar
+        .filter(|hint| context == Context::Sidebar || hint.key != "l")
+        .collect()
+}
+
+/// Sections with only the bindings whose keys or action contain `query`
+/// (case-insensitive); sections left empty are dropped
+pub fn filtered(query: &str) -> Vec<(&'static str, Vec<Binding>)> {
+    let query = query.trim().to_lowercase();
+    SECTIONS
+        .iter()
+        .filter_map(|section| {
+            let bindings: Vec<Binding> = section
+                .bindings
+                .iter()
+                .filter(|b| {
+                    query.is_empty()
+                        || b.keys.to_lowercase().contains(&query)
+                        || b.action.to_lowercase().contains(&query)
+                })
+                .copied()
+                .collect();
+            (!bindings.is_empty()).then_some((section.title, bindings))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_bindings() {
+        assert_eq!(filtered("").len(), SECTIONS.len());
+
+        let sync = filtered("SYNC");
+        assert_eq!(sync.len(), 1);
+        assert_eq!(sync[0].0, "General");
+        assert_eq!(sync[0].1[0].keys, "Ctrl+s");
+        assert_eq!(sync[0].1[1].keys, "S");
+
+        assert!(filtered("no such binding").is_empty());
+    }
+
+    /// The keys a binding lists, e.g. `Ctrl+t/n` gives `Ctrl+t` and `Ctrl+n`
+    fn keys_of(binding: &Binding) -> Vec<String> {
+        let mut keys = Vec::new();
+        for word in binding.keys.split_whitespace() {
+            if word == "or" || word == "/" {
+                continue;
+            }
+            if let Some((first, last)) = word.split_once('-')
+                && let (Ok(first), Ok(last)) = (first.parse::<u32>(), last.parse::<u32>())
+            {
+                keys.extend((first..=last).map(|n| n.to_string()));
+                continue;
+            }
+            let mut parts = word.split('/');
+            let first = parts.next().unwrap_or_default();
+            let modifier = first.rsplit_once('+').map_or("", |(m, _)| m);
+            keys.push(first.to_string());
+            keys.extend(parts.map(|key| match modifier {
+                "" => key.to_string(),
+                modifier => format!("{}+{}", modifier, key),
+            }));
+        }
+        keys
+    }
+
+    /// Keys the Normal mode handlers in events.rs match on, named as in the table
+    fn handled_keys() -> Vec<String> {
+        let source = include_str!("events.rs");
+        let start = source.find("// Global keybindings").unwrap();
+        let end = source.find("fn page_down").unwrap();
+        let source = &source[start..end];
+        let mut keys = Vec::new();
+        for (at, _) in source.match_indices("KeyCode::") {
+            let variant = &source[at + "KeyCode::".len()..];
+            let key = if let Some(c) = variant.strip_prefix("Char('") {
+                match c.split_once("')").unwrap().0 {
+                    " " => "Space".to_string(),
+                    "\\'" => "'".to_string(),
+                    c => c.to_string(),
+                }
+            } else {
+                let name = variant
+                    .split(|c: char| !c.is_alphanumeric() && c != '(')
+                    .next()
+                    .unwrap();
+                match name {
+                    "BackTab" => "Shift+Tab",
+                    "F(1" => "F1",
+                    "PageUp" => "PgUp",
+                    "PageDown" => "PgDn",
+                    "Up" => "↑",
+                    "Down" => "↓",
+                    "Left" => "←",
+                    "Right" => "→",
+                    name => name,
+                }
+                .to_string()
+            };
+            let control = source[..at].ends_with("KeyModifiers::CONTROL, ");
+            keys.push(if control {
+                format!("Ctrl+{}", key)
+            } else {
+                key
+            });
+        }
+        keys
+    }
+
+    #[test]
+    fn test_handled_keys_are_listed() {
+        let listed: Vec<String> = SECTIONS
+            .iter()
+            .flat_map(|section| section.bindings.iter().flat_map(keys_of))
+            .collect();
+        let missing: Vec<String> = handled_keys()
+            .into_iter()
+            .filter(|key| !listed.contains(key))
+            .collect();
+        assert!(missing.is_empty(), "Not in the help table: {:?}", missing);
+    }
+
+    #[test]
+    fn test_hints_follow_context() {
+        let keys = |context| -> Vec<&str> { hints(context).iter().map(|h| h.key).collect() };
+
+        let tasks = keys(Context::Tasks);
+        assert_eq!(tasks[0], "Space");
+        assert!(tasks.contains(&"m") && tasks.contains(&"v") && tasks.contains(&"q"));
+        assert!(!tasks.contains(&"l"));
+
+        let sidebar = keys(Context::Sidebar);
+        assert!(sidebar.contains(&"l") && !sidebar.contains(&"m"));
+        assert!(!keys(Context::Collections).contains(&"m"));
+
+        let editor = keys(Context::Editor);
+        assert_eq!(editor[0], "Tab");
+        assert!(!editor.contains(&"q"));
+    }
+}
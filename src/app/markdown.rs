@@ -0,0 +1,266 @@
+//! Lightweight markdown styling for task descriptions
+//!
+//! Supports `**bold**`, `*italic*` / `_italic_`, `` `code` ``, `[text](url)`,
+//! bare URLs and issue references with a `[links.templates]` entry inline, plus `#` headings, `-` / `*` bullets, `- [ ]` checklist items
+//! and `>` quotes per line. Markers are stripped; anything that doesn't close is
+//! shown as typed.
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::autolink;
+use crate::config::LinksConfig;
+use crate::theme::ThemeColors;
+
+/// Render `text` as styled lines using the theme's colors
+pub fn render(text: &str, colors: &ThemeColors, links: &LinksConfig) -> Vec<Line<'static>> {
+    text.lines()
+        .map(|line| render_line(line, colors, links))
+        .collect()
+}
+
+/// Render a single line, handling block-level prefixes
+fn render_line(line: &str, colors: &ThemeColors, links: &LinksConfig) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    if let Some(heading) = trimmed
+        .strip_prefix("# ")
+        .or_else(|| trimmed.strip_prefix("## "))
+        .or_else(|| trimmed.strip_prefix("### "))
+    {
+        let style = colors.text_primary().add_modifier(Modifier::BOLD);
+        return Line::from(inline(heading, style, colors, links));
+    }
+
+    if let Some(quote) = trimmed.strip_prefix("> ") {
+        let mut spans = vec![Span::styled("▎ ", colors.text_muted())];
+        spans.extend(inline(
+            quote,
+            colors.text_dim().add_modifier(Modifier::ITALIC),
+            colors,
+            links,
+        ));
+        return Line::from(spans);
+    }
+
+    if let Some((checked, item)) = checklist_item(trimmed) {
+        let (mark, style) = if checked {
+            (
+                "☑ ",
+                colors.text_muted().add_modifier(Modifier::CROSSED_OUT),
+            )
+        } else {
+            ("☐ ", colors.text())
+        };
+        let mut spans = vec![Span::styled(
+            format!("{}{}", indent, mark),
+            colors.text_success(),
+        )];
+        spans.extend(inline(item, style, colors, links));
+        return Line::from(spans);
+    }
+
+    if let Some(item) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+    {
+        let mut spans = vec![Span::styled(
+            format!("{}• ", indent),
+            colors.text_secondary(),
+        )];
+        spans.extend(inline(item, colors.text(), colors, links));
+        return Line::from(spans);
+    }
+
+    Line::from(inline(line, colors.text(), colors, links))
+}
+
+/// Parse a `- [ ] item` / `- [x] item` line (leading whitespace already trimmed)
+fn checklist_item(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- [")
+        .or_else(|| line.strip_prefix("* ["))?;
+    let (mark, item) = (rest.get(..1)?, rest.get(1..)?.strip_prefix("] ")?);
+    match mark {
+        " " => Some((false, item)),
+        "x" | "X" => Some((true, item)),
+        _ => None,
+    }
+}
+
+/// Line indices of the checklist items in `text`, with their checked state
+pub fn checklist(text: &str) -> Vec<(usize, bool)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| checklist_item(line.trim_start()).map(|(done, _)| (i, done)))
+        .collect()
+}
+
+/// `text` with the checklist item on line `index` checked or unchecked
+pub fn toggle_checklist_line(text: &str, index: usize) -> String {
+    let mut toggled = String::with_capacity(text.len());
+    // Lines keep their own endings, so CRLF text stays CRLF
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\r', '\n']);
+        match checklist_item(content.trim_start()) {
+            Some((checked, _)) if i == index => {
+                // The mark sits right after the indent and "- ["
+                let pos = content.len() - content.trim_start().len() + 3;
+                toggled.push_str(&line[..pos]);
+                toggled.push_str(if checked { " " } else { "x" });
+                toggled.push_str(&line[pos + 1..]);
+            }
+            _ => toggled.push_str(line),
+        }
+    }
+    toggled
+}
+
+/// Split a line into styled spans for inline markup
+fn inline(
+    text: &str,
+    base: Style,
+    colors: &ThemeColors,
+    links: &LinksConfig,
+) -> Vec<Span<'static>> {
+    let link = colors.text_info().add_modifier(Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| {
+                (
+                    inner.to_string(),
+                    Style::default().fg(colors.accent).bg(colors.bg_highlight),
+                    len,
+                )
+            }),
+            '*' if rest.starts_with("**") => delimited(rest, "**")
+                .map(|(inner, len)| (inner.to_string(), base.add_modifier(Modifier::BOLD), len)),
+            // Skip `_` inside words so snake_case stays intact
+            '*' | '_' if !(c == '_' && plain.ends_with(|p: char| p.is_alphanumeric())) => {
+                delimited(rest, if c == '*' { "*" } else { "_" }).map(|(inner, len)| {
+                    (inner.to_string(), base.add_modifier(Modifier::ITALIC), len)
+                })
+            }
+            '[' => markdown_link(rest).map(|(label, len)| (label.to_string(), link, len)),
+            'h' => autolink::url_at(rest).map(|len| (rest[..len].to_string(), link, len)),
+            // References start a word: `a#1` and `FOO-BAR-1` are left alone
+            '#' | 'A'..='Z'
+                if !plain
+                    .ends_with(|p: char| p.is_alphanumeric() || matches!(p, '_' | '-' | '/')) =>
+            {
+                autolink::reference_at(rest, links)
+                    .map(|(len, _)| (rest[..len].to_string(), link, len))
+            }
+            _ => None,
+        };
+
+        match styled {
+            Some((content, style, len)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.push(Span::styled(content, style));
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+/// Content between an opening and closing `marker`, and the total length consumed
+fn delimited<'a>(text: &'a str, marker: &str) -> Option<(&'a str, usize)> {
+    let body = &text[marker.len()..];
+    let end = body.find(marker)?;
+    let inner = &body[..end];
+    if inner.is_empty() || inner.starts_with(' ') || inner.ends_with(' ') {
+        return None;
+    }
+    Some((inner, marker.len() * 2 + end))
+}
+
+/// Label of a `[label](url)` link, and the total length consumed
+fn markdown_link(text: &str) -> Option<(&str, usize)> {
+    let close = text.find("](")?;
+    let end = text[close..].find(')')? + close;
+    let label = &text[1..close];
+    if label.is_empty() || label.contains('[') {
+        return None;
+    }
+    Some((label, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    fn contents(text: &str) -> Vec<(String, Modifier)> {
+        let colors = Theme::default().colors();
+        let mut links = LinksConfig::default();
+        links
+            .templates
+            .insert("#".to_string(), "https://x.io/issues/{id}".to_string());
+        render(text, &colors, &links)
+            .into_iter()
+            .flat_map(|line| line.spans)
+            .map(|span| (span.content.into_owned(), span.style.add_modifier))
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_markup() {
+        let spans = contents("Read **this** and `run_it` or [docs](https://x.io)");
+        assert_eq!(spans[1], ("this".to_string(), Modifier::BOLD));
+        assert_eq!(spans[3].0, "run_it");
+        assert_eq!(spans[5], ("docs".to_string(), Modifier::UNDERLINED));
+        assert_eq!(spans.len(), 6);
+
+        let spans = contents("Fixes #12 (see https://x.io/a). Not a#3");
+        assert_eq!(spans[1], ("#12".to_string(), Modifier::UNDERLINED));
+        assert_eq!(
+            spans[3],
+            ("https://x.io/a".to_string(), Modifier::UNDERLINED)
+        );
+        assert_eq!(spans[4].0, "). Not a#3");
+    }
+
+    #[test]
+    fn test_checklist_toggle() {
+        let text = "Steps:\n- [ ] draft [ ] v2\n  - [x] review\n- not a task";
+        assert_eq!(checklist(text), vec![(1, false), (2, true)]);
+
+        let toggled = toggle_checklist_line(text, 1);
+        assert_eq!(checklist(&toggled), vec![(1, true), (2, true)]);
+        let toggled = toggle_checklist_line(&toggled, 2);
+        assert_eq!(
+            toggled,
+            "Steps:\n- [x] draft [ ] v2\n  - [ ] review\n- not a task"
+        );
+
+        // Line endings are left as they were
+        let crlf = "- [ ] one\r\n- [ ] two\r\n";
+        assert_eq!(toggle_checklist_line(crlf, 1), "- [ ] one\r\n- [x] two\r\n");
+    }
+
+    #[test]
+    fn test_unclosed_markers_are_literal() {
+        let spans = contents("snake_case_name costs 2 * 3 and **half");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].0, "snake_case_name costs 2 * 3 and **half");
+    }
+}
@@ -1,10 +1,18 @@
 //! TUI Application module
 
 mod events;
+mod guard;
+mod input;
+mod keymap;
+mod markdown;
+mod planner;
 mod state;
+mod timeline;
 mod ui;
 
-pub use state::AppState;
+use guard::TerminalGuard;
+pub use state::{AppState, StartAt};
+use state::{ConfirmAction, Mode};
 
 use anyhow::Result;
 use crossterm::{
@@ -14,69 +22,259 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 use std::io::stdout;
-use std::sync::mpsc;
-use std::time::{Duration, Instant};
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::config::Config;
+use crate::core::{SyncSummary, TickitCore};
 use crate::db::Database;
+use crate::health;
+use crate::hooks::{self, Hook};
 use crate::notifications;
-use crate::sync::{RecordType, SyncClient, SyncRecord, SyncResponse};
+use crate::sync::{self, BlobStore, SyncClient, SyncResponse};
+
+/// How often the task list is reloaded from the database while idle
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often config.toml is checked for edits made outside the TUI
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Messages from background tasks
 enum BackgroundMsg {
     UpdateAvailable(String),
+    UpdateProgress(String),
+    UpdateFinished(Result<(), String>),
+    SyncProgress(sync::SyncProgress),
+    SyncPreview(Result<sync::SyncPreview, String>),
     SyncComplete(Result<SyncResponse, String>),
+    Warnings(Vec<health::Warning>),
 }
 
 /// Run the TUI application
 pub fn run() -> Result<()> {
+    run_at(&StartAt::default())
+}
+
+/// Run the TUI application, opened at the given list, tag, view or task
+pub fn run_at(start: &StartAt) -> Result<()> {
     // Load config
     let config = Config::load()?;
+    // Holidays may need fetching; have them ready before the date picker opens
+    crate::holidays::configure(&config.calendar);
+    std::thread::spawn(crate::holidays::business_days);
 
     // Open database
-    let db = Database::open()?;
+    let mut db = Database::open()?;
+
+    // Archive and purge old tasks per [retention], and raise the priority of
+    // tasks due soon per [escalation], before loading them
+    let mut cleanup = None;
+    let mut escalated = Vec::new();
+    if config.retention.run_on_start && config.retention.is_set() {
+        let core = TickitCore::new(db, config.clone());
+        match core.maintain() {
+            Ok(done) => cleanup = Some(done),
+            Err(e) => tracing::warn!("Retention cleanup failed: {:#}", e),
+        }
+        db = core.into_db();
+    }
+    if !config.escalation.rules().is_empty() {
+        let core = TickitCore::new(db, config.clone());
+        match core.escalate() {
+            Ok(raised) => escalated = raised,
+            Err(e) => tracing::warn!("Priority escalation failed: {:#}", e),
+        }
+        db = core.into_db();
+    }
 
-    // Initialize terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Create app state before taking over the terminal, so errors print normally
+    let mut state = AppState::new(config.clone(), db)?;
+    state.open_at(start)?;
+    state.refresh_pending_changes();
+    if let Ok(path) = Config::default_path() {
+        report_config_issues(&mut state, &path);
+    }
+    if let Some(cleanup) = cleanup.filter(|c| !c.is_empty()) {
+        state.set_status(format!(
+            "Retention: archived {}, purged {} old task(s)",
+            cleanup.archived.len(),
+            cleanup.purged.len()
+        ));
+    }
+    if !escalated.is_empty() {
+        state.set_status(format!(
+            "Raised the priority of {} task(s) due soon (see `tickit show`)",
+            escalated.len()
+        ));
+    }
+    if state.db().is_in_memory() {
+        state.set_status("Ephemeral session: tasks are discarded on quit");
+    } else if let Ok(path) = Database::current_path() {
+        let warnings = health::check_database(state.db(), &path);
+        state.add_warnings(warnings);
+    }
+
+    // Initialize terminal; the guard restores it on return, error or panic
+    let guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    // Create app state
-    let mut state = AppState::new(config.clone(), db)?;
-
     // Spawn background update check
     let (tx, rx) = mpsc::channel();
-    std::thread::spawn(move || {
-        let check = crate::check_for_updates_crates_io_timeout(std::time::Duration::from_secs(5));
-        if let crate::VersionCheck::UpdateAvailable { latest, .. } = check {
-            let _ = tx.send(BackgroundMsg::UpdateAvailable(latest));
-        }
-    });
-
-    // Check for due tasks and send notifications (in background)
-    if config.notifications {
-        let db_for_notifications = Database::open().ok();
+    if state.is_sync_enabled() {
+        let sync = config.sync.clone();
+        let tx = tx.clone();
         std::thread::spawn(move || {
-            if let Some(db) = db_for_notifications {
-                let _ = check_and_notify_due_tasks(&db);
+            let _ = tx.send(BackgroundMsg::Warnings(health::check_server(&sync)));
+        });
+    }
+    if update_check_due(&state) {
+        std::thread::spawn(move || {
+            let check =
+                crate::check_for_updates_crates_io_timeout(std::time::Duration::from_secs(5));
+            if let crate::VersionCheck::UpdateAvailable { latest, .. } = check {
+                let _ = tx.send(BackgroundMsg::UpdateAvailable(latest));
             }
         });
     }
 
+    // Check for due tasks and send notifications (in background)
+    spawn_reminders(&config);
+
     // Main loop
     let result = run_app(&mut terminal, &mut state, rx);
 
-    // Restore terminal
+    drop(guard);
+    result
+}
+
+/// Last modification time of a file, if it can be read
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Apply config.toml if it differs from the running config, which our own saves don't
+fn reload_config(state: &mut AppState, path: &Path) {
+    match Config::load_from(&path.to_path_buf()) {
+        Ok(config) => {
+            if toml::to_string(&config).ok() != toml::to_string(&state.config).ok() {
+                state.apply_config(config);
+                report_config_issues(state, path);
+            }
+        }
+        Err(e) => state.set_status(format!("Config not reloaded: {}", e)),
+    }
+}
+
+/// Point to `tickit config check` in the status bar if config.toml has problems
+fn report_config_issues(state: &mut AppState, path: &Path) {
+    if let Ok(issues) = Config::check_file(&path.to_path_buf())
+        && !issues.is_empty()
+    {
+        state.set_status(format!(
+            "config.toml has {} problem(s), run `tickit config check`",
+            issues.len()
+        ));
+    }
+}
+
+/// sync_state key holding when the update check last ran
+const LAST_UPDATE_CHECK: &str = "last_update_check";
+
+/// Whether to ask crates.io for a new release now, per `updates.check`, and
+/// if so remember that it ran. A failed check counts, so offline machines
+/// aren't retried on every start; `TICKIT_NO_UPDATE_CHECK` turns checks off.
+fn update_check_due(state: &AppState) -> bool {
+    if std::env::var_os("TICKIT_NO_UPDATE_CHECK").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    let last_check = state
+        .db()
+        .get_sync_state(LAST_UPDATE_CHECK)
+        .ok()
+        .flatten()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|t| t.with_timezone(&chrono::Utc));
+    let now = chrono::Utc::now();
+    if !state.config.updates.check.is_due(last_check, now) {
+        return false;
+    }
+    let _ = state
+        .db()
+        .set_sync_state(LAST_UPDATE_CHECK, &now.to_rfc3339());
+    true
+}
+
+/// Send due-date reminders in the background, if notifications are enabled
+fn spawn_reminders(config: &Config) {
+    if !config.notifications.enabled {
+        return;
+    }
+    let quiet_hours = config.quiet_hours;
+    let rules = config.notification_rules.clone();
+    let nag_hours = config.notifications.nag_hours;
+    let review = config.notifications.review.clone();
+    if let Ok(db) = Database::open() {
+        std::thread::spawn(move || {
+            let _ = notifications::send_reminders(&db, quiet_hours, &rules, nag_hours);
+            let _ = notifications::send_review(&db, &review, quiet_hours);
+        });
+    }
+}
+
+/// Edit text in $VISUAL / $EDITOR (falling back to vi), suspending the TUI meanwhile
+fn edit_in_external_editor(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    text: &str,
+) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let path = std::env::temp_dir().join(format!("tickit-{}.md", uuid::Uuid::new_v4()));
+    std::fs::write(&path, text)?;
+
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status();
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+
+    let result = match status {
+        Ok(status) if status.success() => Ok(std::fs::read_to_string(&path)?),
+        Ok(status) => Err(anyhow::anyhow!("{} exited with {}", program, status)),
+        Err(e) => Err(anyhow::anyhow!("could not run {}: {}", program, e)),
+    };
+    let _ = std::fs::remove_file(&path);
     result
 }
 
+/// Install the update on a background thread, returning its cancel flag
+fn spawn_update(tx: mpsc::Sender<BackgroundMsg>) -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    std::thread::spawn(move || {
+        let pm = crate::detect_package_manager();
+        let result = crate::run_update_with_progress(
+            &pm,
+            |line| {
+                let _ = tx.send(BackgroundMsg::UpdateProgress(line.to_string()));
+            },
+            &flag,
+        );
+        let _ = tx.send(BackgroundMsg::UpdateFinished(result));
+    });
+    cancel
+}
+
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     state: &mut AppState,
@@ -84,12 +282,26 @@ fn run_app(
 ) -> Result<()> {
     // Track if sync is in progress (to prevent multiple syncs)
     let mut sync_in_progress = false;
+    // Records sent by the running sync, for the on_sync_finished hook
+    let mut sync_sent = 0;
+    // Tasks sent and the sync they were changed after, for settling conflicts
+    let mut sync_sent_tasks = Vec::new();
+    let mut sync_since = None;
     // Channel for sync results
     let (sync_tx, sync_rx) = mpsc::channel::<BackgroundMsg>();
+    // Channel for update progress
+    let (update_tx, update_rx) = mpsc::channel::<BackgroundMsg>();
     // Track last sync time for auto-sync interval
     let mut last_sync_attempt = Instant::now();
     // Initial sync on startup if enabled
     let mut needs_initial_sync = state.is_sync_enabled();
+    // Periodic reload so due/overdue markers (and CLI edits) show up while open
+    let mut last_refresh = Instant::now();
+    let mut today = chrono::Local::now().date_naive();
+    // Reload config.toml when it changes on disk
+    let config_path = Config::default_path().ok();
+    let mut config_modified = config_path.as_deref().and_then(modified_time);
+    let mut last_config_check = Instant::now();
 
     loop {
         // Check for background messages (non-blocking)
@@ -98,24 +310,83 @@ fn run_app(
                 BackgroundMsg::UpdateAvailable(version) => {
                     state.set_update_available(version);
                 }
-                BackgroundMsg::SyncComplete(_) => {
-                    // Handled by sync_rx
+                BackgroundMsg::Warnings(warnings) => state.add_warnings(warnings),
+                BackgroundMsg::SyncComplete(_)
+                | BackgroundMsg::SyncProgress(_)
+                | BackgroundMsg::SyncPreview(_)
+                | BackgroundMsg::UpdateProgress(_)
+                | BackgroundMsg::UpdateFinished(_) => {
+                    // Handled by sync_rx / update_rx
                 }
             }
         }
 
-        // Check for sync completion
-        if let Ok(msg) = sync_rx.try_recv()
-            && let BackgroundMsg::SyncComplete(result) = msg
-        {
+        // Stream progress of a running update
+        while let Ok(msg) = update_rx.try_recv() {
+            match msg {
+                BackgroundMsg::UpdateProgress(line) => state.update_progress(line),
+                BackgroundMsg::UpdateFinished(result) => state.finish_update(result),
+                _ => {}
+            }
+        }
+
+        // Check for sync progress and completion
+        while let Ok(msg) = sync_rx.try_recv() {
+            let result = match msg {
+                BackgroundMsg::SyncProgress(progress) => {
+                    state.sync_status.progress = Some(progress);
+                    continue;
+                }
+                BackgroundMsg::SyncPreview(result) => {
+                    sync_in_progress = false;
+                    match result {
+                        Ok(preview) => {
+                            state.set_syncing(false);
+                            state.confirm_first_sync(&preview);
+                        }
+                        Err(e) => state.sync_failed(e),
+                    }
+                    continue;
+                }
+                BackgroundMsg::SyncComplete(result) => result,
+                _ => continue,
+            };
             sync_in_progress = false;
             match result {
-                Ok(response) => {
-                    // Apply incoming changes from server
-                    let _applied = apply_incoming_changes(&state.db, &response);
+                Ok(mut response) => {
+                    // Apply incoming changes from server; nobody can be asked
+                    // about conflicts here, so `ask` falls back to newest-wins
+                    let policy = state.config.sync.conflict_policy;
+                    let kept_local = sync::resolve_conflicts(
+                        state.db(),
+                        &mut response,
+                        sync_since,
+                        policy,
+                        None,
+                    )
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Could not settle sync conflicts: {:#}", e);
+                        0
+                    });
+                    let applied = sync::apply_changes(state.db(), &response);
+                    if let Err(e) = sync::record_sync_base(state.db(), &sync_sent_tasks, &response)
+                    {
+                        tracing::warn!("Could not record synced tasks: {:#}", e);
+                    }
+                    let summary = SyncSummary {
+                        sent: sync_sent,
+                        received: response.changes.len(),
+                        applied,
+                        conflicts: response.conflicts.len(),
+                        kept_local,
+                        missing_blobs: BlobStore::for_database(state.db())
+                            .and_then(|store| sync::missing_blobs(state.db(), &store))
+                            .map_or(0, |blobs| blobs.len()),
+                    };
+                    hooks::run(&state.config.hooks, Hook::SyncFinished, &summary);
 
                     // Update last sync time in DB
-                    let _ = state.db.set_last_sync(response.server_time);
+                    let _ = state.db().set_last_sync(response.server_time);
                     state.set_last_sync(response.server_time);
 
                     // Sync indicator on the right shows "Synced" status
@@ -124,8 +395,8 @@ fn run_app(
                     let _ = state.refresh_data();
                 }
                 Err(e) => {
-                    state.set_sync_error(Some(e.clone()));
-                    // Sync indicator shows error status
+                    // Sync indicator shows error status; retried with backoff
+                    state.sync_failed(e);
                 }
             }
         }
@@ -133,34 +404,86 @@ fn run_app(
         // Draw UI
         terminal.draw(|frame| ui::render(frame, state))?;
 
-        // Process pending update after draw (so "Updating..." is visible)
+        // Run an accepted update in the background
         if state.pending_update {
-            events::process_pending_update(state);
-            // Redraw immediately after update completes
-            terminal.draw(|frame| ui::render(frame, state))?;
+            state.pending_update = false;
+            state.update_cancel = Some(spawn_update(update_tx.clone()));
+        }
+
+        // Hand the terminal to $EDITOR for the task description
+        if state.pending_external_edit {
+            state.pending_external_edit = false;
+            let result = edit_in_external_editor(terminal, &state.input_buffer);
+            state.finish_external_description_edit(result);
+            terminal.clear()?;
+            continue;
+        }
+
+        // Midnight rollover: "today" and overdue change meaning
+        let now_day = chrono::Local::now().date_naive();
+        if now_day != today {
+            today = now_day;
+            last_refresh = Instant::now();
+            let _ = state.reload_keeping_selection();
+            state.set_status(format!("New day: {}", now_day.format("%A, %b %-d")));
+            spawn_reminders(&state.config);
+        } else if state.mode == Mode::Normal && last_refresh.elapsed() >= REFRESH_INTERVAL {
+            // Only in normal mode so open dialogs keep their selection
+            last_refresh = Instant::now();
+            let _ = state.reload_keeping_selection();
+            // Catches reminders held back by quiet hours; sent ones aren't repeated
+            spawn_reminders(&state.config);
+        }
+
+        if last_config_check.elapsed() >= CONFIG_POLL_INTERVAL
+            && let Some(path) = &config_path
+        {
+            last_config_check = Instant::now();
+            let modified = modified_time(path);
+            if modified != config_modified {
+                config_modified = modified;
+                reload_config(state, path);
+            }
         }
 
         // Auto-sync on interval (if enabled and configured)
         let sync_interval = state.config.sync.interval_secs;
         let should_auto_sync = state.is_sync_enabled()
+            && !state.auto_sync_paused()
             && sync_interval > 0
             && !sync_in_progress
-            && (needs_initial_sync || last_sync_attempt.elapsed().as_secs() >= sync_interval);
+            && (needs_initial_sync
+                || state.sync_retry_due()
+                || last_sync_attempt.elapsed().as_secs() >= sync_interval);
+
+        // A first sync waits for its preview to be confirmed
+        let first_sync_on_hold = state.first_sync_confirmed == Some(false)
+            || matches!(state.confirm_action, Some(ConfirmAction::FirstSync));
 
         // Check if sync was requested (via Ctrl+S) or triggered by action or auto-sync
-        let should_sync = (state.sync_status.syncing || state.sync_pending || should_auto_sync)
+        let should_sync = (state.sync_status.syncing
+            || (state.sync_pending && !state.auto_sync_paused())
+            || should_auto_sync)
             && !sync_in_progress
+            && !first_sync_on_hold
             && state.is_sync_enabled();
 
         if should_sync {
             sync_in_progress = true;
             needs_initial_sync = false;
             last_sync_attempt = Instant::now();
+            state.sync_retry_at = None;
             state.set_syncing(true);
             state.sync_pending = false;
 
             let config = state.config.sync.clone();
-            let last_sync = state.db.get_last_sync().ok().flatten();
+            let synced_at = state.db().get_last_sync().ok().flatten();
+            // A forced sync sends and fetches everything
+            let last_sync = if std::mem::take(&mut state.sync_force) {
+                None
+            } else {
+                synced_at
+            };
 
             // Capture local time BEFORE gathering changes.
             // This ensures any changes made during or after sync will be picked up next time,
@@ -168,25 +491,63 @@ fn run_app(
             let local_sync_time = chrono::Utc::now();
 
             // Gather local changes
-            let changes = gather_local_changes(&state.db, last_sync);
+            let changes = sync::local_changes(state.db(), last_sync).unwrap_or_default();
+            state.sync_status.pending_changes = changes.len();
+            sync_sent = changes.len();
+            sync_sent_tasks = sync::task_ids(&changes);
+            sync_since = last_sync;
+
+            // Files are stored next to the database; none in memory
+            let store = BlobStore::for_database(state.db()).ok();
+            let missing = store
+                .as_ref()
+                .and_then(|store| sync::missing_blobs(state.db(), store).ok())
+                .unwrap_or_default();
 
             let tx = sync_tx.clone();
-            std::thread::spawn(move || {
-                let mut client = SyncClient::new(config);
-                let result = client.sync(changes, last_sync);
-                let msg = match result {
-                    Ok(mut response) => {
-                        // Use the EARLIER of local sync time and server time to avoid missing
-                        // changes due to clock skew.
-                        if local_sync_time < response.server_time {
-                            response.server_time = local_sync_time;
-                        }
-                        BackgroundMsg::SyncComplete(Ok(response))
+            if state.first_sync_confirmed.is_none() && sync::needs_preview(synced_at, &changes) {
+                // Download only, to show what the first sync would do
+                std::thread::spawn(move || {
+                    let mut client = SyncClient::new(config);
+                    let preview = client
+                        .sync(Vec::new(), None)
+                        .map(|remote| sync::SyncPreview::new(&changes, &remote.changes))
+                        .map_err(|e| e.to_string());
+                    let _ = tx.send(BackgroundMsg::SyncPreview(preview));
+                });
+            } else {
+                std::thread::spawn(move || {
+                    let mut client = SyncClient::new(config);
+                    let progress_tx = tx.clone();
+                    let mut progress = |progress| {
+                        let _ = progress_tx.send(BackgroundMsg::SyncProgress(progress));
+                    };
+                    let result = match &store {
+                        Some(store) => client.upload_blobs(store, &changes),
+                        None => Ok(()),
                     }
-                    Err(e) => BackgroundMsg::SyncComplete(Err(e.to_string())),
-                };
-                let _ = tx.send(msg);
-            });
+                    .and_then(|()| {
+                        client.sync_with_progress(changes, last_sync, Some(&mut progress))
+                    });
+                    let msg = match result {
+                        Ok(mut response) => {
+                            if let Some(store) = &store {
+                                let mut blobs = missing;
+                                blobs.extend(sync::attachment_blobs(&response.changes));
+                                client.download_blobs(store, &blobs);
+                            }
+                            // Use the EARLIER of local sync time and server time to avoid missing
+                            // changes due to clock skew.
+                            if local_sync_time < response.server_time {
+                                response.server_time = local_sync_time;
+                            }
+                            BackgroundMsg::SyncComplete(Ok(response))
+                        }
+                        Err(e) => BackgroundMsg::SyncComplete(Err(e.to_string())),
+                    };
+                    let _ = tx.send(msg);
+                });
+            }
         }
 
         // Handle events
@@ -204,177 +565,7 @@ fn run_app(
         }
     }
 
-    // Save config on exit
-    state.config.save()?;
-
+    // Settings are saved as they change; saving here would overwrite
+    // edits made to config.toml while the TUI ran, even ones that didn't load
     Ok(())
 }
-
-/// Gather local changes since last sync
-fn gather_local_changes(
-    db: &Database,
-    last_sync: Option<chrono::DateTime<chrono::Utc>>,
-) -> Vec<SyncRecord> {
-    let mut changes = Vec::new();
-
-    // Get modified tasks
-    if let Some(since) = last_sync {
-        if let Ok(tasks) = db.get_tasks_since(since) {
-            for task in tasks {
-                changes.push(SyncRecord::Task(task));
-            }
-        }
-        if let Ok(lists) = db.get_lists_since(since) {
-            for list in lists {
-                changes.push(SyncRecord::List(list));
-            }
-        }
-        if let Ok(tags) = db.get_tags_since(since) {
-            for tag in tags {
-                changes.push(SyncRecord::Tag(tag));
-            }
-        }
-        // Get tombstones
-        if let Ok(tombstones) = db.get_tombstones_since(since) {
-            for (id, record_type_str, deleted_at) in tombstones {
-                let record_type = match record_type_str.as_str() {
-                    "task" => RecordType::Task,
-                    "list" => RecordType::List,
-                    "tag" => RecordType::Tag,
-                    _ => RecordType::Task,
-                };
-                changes.push(SyncRecord::Deleted {
-                    id,
-                    record_type,
-                    deleted_at,
-                });
-            }
-        }
-    } else {
-        // Full sync: get all data
-        if let Ok(tasks) = db.get_all_tasks() {
-            for task in tasks {
-                changes.push(SyncRecord::Task(task));
-            }
-        }
-        if let Ok(lists) = db.get_lists() {
-            for list in lists {
-                changes.push(SyncRecord::List(list));
-            }
-        }
-        if let Ok(tags) = db.get_tags() {
-            for tag in tags {
-                changes.push(SyncRecord::Tag(tag));
-            }
-        }
-    }
-
-    changes
-}
-
-/// Apply incoming changes from the server to the local database
-fn apply_incoming_changes(db: &Database, response: &SyncResponse) -> usize {
-    let mut applied = 0;
-
-    // Sort changes: lists first, then tags, then tasks (to satisfy FK constraints)
-    let mut lists = Vec::new();
-    let mut tags = Vec::new();
-    let mut tasks = Vec::new();
-    let mut task_tags = Vec::new();
-    let mut deletes = Vec::new();
-
-    for record in &response.changes {
-        match record {
-            SyncRecord::List(_) => lists.push(record),
-            SyncRecord::Tag(_) => tags.push(record),
-            SyncRecord::Task(_) => tasks.push(record),
-            SyncRecord::TaskTag(_) => task_tags.push(record),
-            SyncRecord::Deleted { .. } => deletes.push(record),
-        }
-    }
-
-    // Disable FK constraints during sync
-    let _ = db.execute_raw("PRAGMA foreign_keys = OFF");
-
-    // Apply in order: lists, tags, tasks, task_tags, deletes
-    for record in lists
-        .iter()
-        .chain(tags.iter())
-        .chain(tasks.iter())
-        .chain(task_tags.iter())
-        .chain(deletes.iter())
-    {
-        let result = match record {
-            SyncRecord::Task(task) => db.upsert_task(task),
-            SyncRecord::List(list) => db.upsert_list(list),
-            SyncRecord::Tag(tag) => db.upsert_tag(tag),
-            SyncRecord::TaskTag(link) => db.upsert_task_tag(link),
-            SyncRecord::Deleted {
-                id, record_type, ..
-            } => {
-                match record_type {
-                    RecordType::Task => db.delete_task_by_id(*id),
-                    RecordType::List => db.delete_list_by_id(*id),
-                    RecordType::Tag => db.delete_tag_by_id(*id),
-                    RecordType::TaskTag => Ok(()), // Handled by task update
-                }
-            }
-        };
-
-        if result.is_ok() {
-            applied += 1;
-        }
-    }
-
-    // Re-enable FK constraints
-    let _ = db.execute_raw("PRAGMA foreign_keys = ON");
-
-    applied
-}
-
-/// Check for tasks due today/tomorrow and send notifications
-fn check_and_notify_due_tasks(db: &Database) -> usize {
-    use crate::models::Priority;
-    use chrono::Local;
-
-    let today = Local::now().date_naive();
-    let tomorrow = today.succ_opt().unwrap_or(today);
-
-    let mut notified = 0;
-
-    // Get all incomplete tasks with due dates
-    if let Ok(tasks) = db.get_all_tasks() {
-        for task in tasks {
-            // Skip completed tasks
-            if task.completed {
-                continue;
-            }
-
-            // Check if task has a due date
-            if let Some(due_datetime) = &task.due_date {
-                let due_date = due_datetime.date_naive();
-
-                if due_date == today {
-                    // Task is due today
-                    if notifications::notify_task_due_today(&task).is_ok() {
-                        notified += 1;
-                    }
-                } else if due_date == tomorrow
-                    && (task.priority == Priority::High || task.priority == Priority::Urgent)
-                {
-                    // High/urgent task due tomorrow - advance warning
-                    if notifications::notify_task_due_tomorrow(&task).is_ok() {
-                        notified += 1;
-                    }
-                } else if due_date < today {
-                    // Task is overdue
-                    if notifications::notify_task_overdue(&task).is_ok() {
-                        notified += 1;
-                    }
-                }
-            }
-        }
-    }
-
-    notified
-}
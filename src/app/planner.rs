@@ -0,0 +1,140 @@
+//! Week planner: a backlog column followed by a column per day of the week
+//!
+//! The backlog holds open tasks without a due date, and overdue ones that
+//! fall before the week shown. Each day holds the open tasks due that day.
+//! Moving a task onto a day sets its due date; moving it back to the
+//! backlog clears it.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::models::Task;
+
+/// Columns on the board: the backlog and seven days
+pub const COLUMNS: usize = 8;
+
+/// The first day of the week holding `today`
+pub fn week_start(today: NaiveDate, week_starts_on: Weekday) -> NaiveDate {
+    today - Duration::days(i64::from(today.weekday().days_since(week_starts_on)))
+}
+
+/// The day of `column` in the week starting `from`, or None for the backlog
+pub fn day(column: usize, from: NaiveDate) -> Option<NaiveDate> {
+    (1..COLUMNS)
+        .contains(&column)
+        .then(|| from + Duration::days(column as i64 - 1))
+}
+
+/// The column showing `date` in the week starting `from`, if any
+pub fn column_of(date: NaiveDate, from: NaiveDate) -> Option<usize> {
+    let offset = (date - from).num_days();
+    (0..COLUMNS as i64 - 1)
+        .contains(&offset)
+        .then(|| offset as usize + 1)
+}
+
+/// The open tasks of each column for the week starting `from`
+///
+/// Days list their tasks by priority, then manual order. The backlog puts
+/// overdue tasks first, oldest due date first, then undated ones by priority.
+pub fn columns(tasks: &[Task], from: NaiveDate, today: NaiveDate) -> Vec<Vec<Task>> {
+    let mut columns = vec![Vec::new(); COLUMNS];
+    for task in tasks.iter().filter(|t| !t.completed) {
+        let due = task.due_date.map(|d| d.date_naive());
+        let column = match due {
+            None => Some(0),
+            Some(date) => column_of(date, from).or((date < today && date < from).then_some(0)),
+        };
+        if let Some(column) = column {
+            columns[column].push(task.clone());
+        }
+    }
+    let (backlog, days) = columns.split_at_mut(1);
+    backlog[0].sort_by_key(|t| {
+        (
+            t.due_date.is_none(),
+            t.due_date,
+            std::cmp::Reverse(t.priority),
+            t.created_at,
+        )
+    });
+    for tasks in days {
+        tasks.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.sort_order, t.created_at));
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{List, Priority};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn due(task: &mut Task, s: &str) {
+        task.due_date = Some(date(s).and_hms_opt(23, 59, 59).unwrap().and_utc());
+    }
+
+    #[test]
+    fn test_days() {
+        // Thursday, weeks starting on Monday
+        let from = week_start(date("2026-03-12"), Weekday::Mon);
+        assert_eq!(from, date("2026-03-09"));
+        assert_eq!(
+            week_start(date("2026-03-15"), Weekday::Sun),
+            date("2026-03-15")
+        );
+        assert_eq!(day(0, from), None);
+        assert_eq!(day(1, from), Some(from));
+        assert_eq!(day(7, from), Some(date("2026-03-15")));
+        assert_eq!(day(8, from), None);
+        assert_eq!(column_of(date("2026-03-15"), from), Some(7));
+        assert_eq!(column_of(date("2026-03-16"), from), None);
+        assert_eq!(column_of(date("2026-03-08"), from), None);
+    }
+
+    #[test]
+    fn test_columns() {
+        let inbox = List::new("Inbox");
+        let undated = Task::new("Undated", inbox.id);
+        let mut urgent = Task::new("Urgent", inbox.id);
+        urgent.priority = Priority::Urgent;
+        let mut overdue = Task::new("Overdue", inbox.id);
+        due(&mut overdue, "2026-03-01");
+        let mut monday = Task::new("Monday", inbox.id);
+        due(&mut monday, "2026-03-09");
+        let mut later = Task::new("Next week", inbox.id);
+        due(&mut later, "2026-03-20");
+        let mut done = Task::new("Done", inbox.id);
+        done.complete();
+
+        let titles = |from: &str, today: &str| -> Vec<Vec<String>> {
+            columns(
+                &[
+                    undated.clone(),
+                    urgent.clone(),
+                    overdue.clone(),
+                    monday.clone(),
+                    later.clone(),
+                    done.clone(),
+                ],
+                date(from),
+                date(today),
+            )
+            .into_iter()
+            .map(|tasks| tasks.into_iter().map(|t| t.title).collect())
+            .collect()
+        };
+
+        let week = titles("2026-03-09", "2026-03-12");
+        assert_eq!(week[0], ["Overdue", "Urgent", "Undated"]);
+        assert_eq!(week[1], ["Monday"]);
+        assert!(week[2..].iter().all(|day| day.is_empty()));
+
+        // A week later, last week's task is overdue and the later one shows
+        let next = titles("2026-03-16", "2026-03-17");
+        assert_eq!(next[0], ["Overdue", "Monday", "Urgent", "Undated"]);
+        assert_eq!(next[5], ["Next week"]);
+    }
+}
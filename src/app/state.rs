@@ -1,62 +1,163 @@
 //! Application state management
 
 use anyhow::Result;
+use ratatui::style::Color;
+use std::cell::Cell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid::Uuid;
 
+use super::markdown;
 use crate::config::Config;
+use crate::core::TickitCore;
 use crate::db::Database;
-use crate::models::{List, Priority, Tag, Task};
+use crate::deps;
+use crate::fuzzy::fuzzy_match;
+use crate::models::{
+    COLORS, Comment, DeletedListTasks, ExportFormat, List, Priority, Tag, Task, TaskSort,
+};
+use crate::quickadd::{QuickAdd, parse_defer};
 use crate::sync::SyncStatus;
-use crate::theme::Theme;
+use crate::theme::{Theme, ThemeColors};
 
 /// Settings menu items
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SettingsItem {
     Theme,
+    Density,
+    Accessible,
+    HighContrast,
     SyncEnabled,
     SyncServer,
     SyncToken,
     SyncInterval,
     Notifications,
     ShowCompletedDefault,
+    ConfirmDeleteTask,
+    ConfirmBulkDelete,
+    ConfirmDeleteList,
+    ConfirmDeleteTag,
+    ConfirmResetList,
+    ConfirmDefaultYes,
 }
 
 impl SettingsItem {
     pub fn all() -> &'static [SettingsItem] {
         &[
             SettingsItem::Theme,
+            SettingsItem::Density,
+            SettingsItem::Accessible,
+            SettingsItem::HighContrast,
             SettingsItem::SyncEnabled,
             SettingsItem::SyncServer,
             SettingsItem::SyncToken,
             SettingsItem::SyncInterval,
             SettingsItem::Notifications,
             SettingsItem::ShowCompletedDefault,
+            SettingsItem::ConfirmDeleteTask,
+            SettingsItem::ConfirmBulkDelete,
+            SettingsItem::ConfirmDeleteList,
+            SettingsItem::ConfirmDeleteTag,
+            SettingsItem::ConfirmResetList,
+            SettingsItem::ConfirmDefaultYes,
         ]
     }
 
     pub fn label(&self) -> &'static str {
         match self {
             SettingsItem::Theme => "Theme",
+            SettingsItem::Density => "Density",
+            SettingsItem::Accessible => "Accessible Labels",
+            SettingsItem::HighContrast => "High Contrast",
             SettingsItem::SyncEnabled => "Sync Enabled",
             SettingsItem::SyncServer => "Sync Server",
             SettingsItem::SyncToken => "Sync Token",
             SettingsItem::SyncInterval => "Sync Interval",
             SettingsItem::Notifications => "Notifications",
             SettingsItem::ShowCompletedDefault => "Show Completed",
+            SettingsItem::ConfirmDeleteTask => "Confirm Delete Task",
+            SettingsItem::ConfirmBulkDelete => "Confirm Bulk Delete",
+            SettingsItem::ConfirmDeleteList => "Confirm Delete List",
+            SettingsItem::ConfirmDeleteTag => "Confirm Delete Tag",
+            SettingsItem::ConfirmResetList => "Confirm Reset Checklist",
+            SettingsItem::ConfirmDefaultYes => "Confirm Default",
         }
     }
 
     pub fn icon(&self) -> &'static str {
         match self {
             SettingsItem::Theme => "🎨",
+            SettingsItem::Density => "☰",
+            SettingsItem::Accessible => "🏷",
+            SettingsItem::HighContrast => "◐",
             SettingsItem::SyncEnabled => "🔄",
             SettingsItem::SyncServer => "🌐",
             SettingsItem::SyncToken => "🔑",
             SettingsItem::SyncInterval => "⏱️",
             SettingsItem::Notifications => "🔔",
             SettingsItem::ShowCompletedDefault => "✓",
+            SettingsItem::ConfirmDeleteTask
+            | SettingsItem::ConfirmBulkDelete
+            | SettingsItem::ConfirmDeleteList
+            | SettingsItem::ConfirmDeleteTag => "🗑",
+            SettingsItem::ConfirmResetList => "↺",
+            SettingsItem::ConfirmDefaultYes => "↵",
         }
     }
+
+    /// Icon for terminals without emoji support
+    pub fn ascii_icon(&self) -> &'static str {
+        match self {
+            SettingsItem::Theme => "*",
+            SettingsItem::Density => "=",
+            SettingsItem::Accessible => "a",
+            SettingsItem::HighContrast => "c",
+            SettingsItem::SyncEnabled => "~",
+            SettingsItem::SyncServer => "@",
+            SettingsItem::SyncToken => "k",
+            SettingsItem::SyncInterval => "t",
+            SettingsItem::Notifications => "n",
+            SettingsItem::ShowCompletedDefault => "x",
+            SettingsItem::ConfirmDeleteTask
+            | SettingsItem::ConfirmBulkDelete
+            | SettingsItem::ConfirmDeleteList
+            | SettingsItem::ConfirmDeleteTag
+            | SettingsItem::ConfirmResetList => "?",
+            SettingsItem::ConfirmDefaultYes => ">",
+        }
+    }
+
+    /// Current value of a plain on/off item
+    pub fn switch(&self, config: &Config) -> Option<bool> {
+        match self {
+            SettingsItem::Accessible => Some(config.display.accessible),
+            SettingsItem::HighContrast => Some(config.display.high_contrast),
+            SettingsItem::ConfirmDeleteTask => Some(config.confirm.delete_task),
+            SettingsItem::ConfirmBulkDelete => Some(config.confirm.bulk_delete),
+            SettingsItem::ConfirmDeleteList => Some(config.confirm.delete_list),
+            SettingsItem::ConfirmDeleteTag => Some(config.confirm.delete_tag),
+            SettingsItem::ConfirmResetList => Some(config.confirm.reset_list),
+            SettingsItem::ConfirmDefaultYes => Some(config.confirm.default_yes),
+            _ => None,
+        }
+    }
+
+    /// Flip a plain on/off item, returning its new value
+    pub fn toggle_switch(&self, config: &mut Config) -> Option<bool> {
+        let flag = match self {
+            SettingsItem::Accessible => &mut config.display.accessible,
+            SettingsItem::HighContrast => &mut config.display.high_contrast,
+            SettingsItem::ConfirmDeleteTask => &mut config.confirm.delete_task,
+            SettingsItem::ConfirmBulkDelete => &mut config.confirm.bulk_delete,
+            SettingsItem::ConfirmDeleteList => &mut config.confirm.delete_list,
+            SettingsItem::ConfirmDeleteTag => &mut config.confirm.delete_tag,
+            SettingsItem::ConfirmResetList => &mut config.confirm.reset_list,
+            SettingsItem::ConfirmDefaultYes => &mut config.confirm.default_yes,
+            _ => return None,
+        };
+        *flag = !*flag;
+        Some(*flag)
+    }
 }
 
 /// Input mode for the application
@@ -95,6 +196,42 @@ pub enum Mode {
     UpdateConfirm,
     /// Update in progress
     Updating,
+    /// List picker for moving marked tasks
+    MoveTasks,
+    /// Tag picker for retagging marked tasks
+    TagTasks,
+    /// Tag picker for the tasks of a tag being deleted
+    ReassignTag,
+    /// List picker for the tasks of a list being deleted
+    MoveListTasks,
+    /// Task filter popup
+    Filter,
+    /// One-line quick-add prompt
+    QuickAdd,
+    /// One-line prompt for a comment on the selected task
+    Comment,
+    /// One-line prompt for the date to defer tasks to
+    Defer,
+    /// Sync panel: server, device and last sync, with sync actions
+    SyncPanel,
+    /// Fuzzy finder over all tasks
+    Jump,
+    /// Renaming the selected task in place
+    InlineEdit,
+    /// Full-screen view of a single task
+    Focus,
+    /// Full-screen timeline of dated tasks
+    Timeline,
+    /// Full-screen week planner: a backlog and a column per day
+    Planner,
+    /// Completion counts, streaks and a yearly heatmap
+    Stats,
+    /// Workspace picker
+    Workspaces,
+    /// Picker for which of the selected task's links to open
+    OpenLink,
+    /// Problems found at startup, which can be dismissed
+    Warnings,
 }
 
 /// Current view/tab
@@ -129,6 +266,28 @@ impl View {
             Self::Tags => "🏷",
         }
     }
+
+    /// Icon for terminals without emoji support
+    pub const fn ascii_icon(&self) -> &'static str {
+        match self {
+            Self::Tasks => "x",
+            Self::Lists => "=",
+            Self::Tags => "#",
+        }
+    }
+}
+
+/// Where the TUI opens, from `tickit ui` arguments
+#[derive(Debug, Clone, Default)]
+pub struct StartAt {
+    /// List to open
+    pub list: Option<Uuid>,
+    /// Tag to filter tasks by
+    pub tag: Option<Uuid>,
+    /// `tasks`, `lists`, `tags` or a smart list such as `today`
+    pub view: Option<String>,
+    /// Task to select
+    pub task: Option<Uuid>,
 }
 
 /// Focus area within a view
@@ -141,6 +300,238 @@ pub enum Focus {
     Main,
 }
 
+/// Due date range for the task filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DueFilter {
+    #[default]
+    Any,
+    Overdue,
+    Today,
+    Week,
+    NoDate,
+}
+
+impl DueFilter {
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Any,
+            Self::Overdue,
+            Self::Today,
+            Self::Week,
+            Self::NoDate,
+        ]
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Any => "Any",
+            Self::Overdue => "Overdue",
+            Self::Today => "Due today",
+            Self::Week => "Next 7 days",
+            Self::NoDate => "No due date",
+        }
+    }
+
+    /// Check whether a task's due date falls in this range
+    pub fn matches(&self, task: &Task) -> bool {
+        let now = chrono::Utc::now();
+        let today = chrono::Local::now().date_naive();
+        match self {
+            Self::Any => true,
+            Self::Overdue => task.due_date.is_some_and(|d| d < now),
+            Self::Today => task
+                .due_date
+                .is_some_and(|d| d.with_timezone(&chrono::Local).date_naive() == today),
+            Self::Week => task.due_date.is_some_and(|d| {
+                let date = d.with_timezone(&chrono::Local).date_naive();
+                date >= today && date < today + chrono::Duration::days(7)
+            }),
+            Self::NoDate => task.due_date.is_none(),
+        }
+    }
+}
+
+/// Built-in date-based views listed above the lists in the sidebar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartList {
+    Today,
+    Upcoming,
+    Overdue,
+    Someday,
+    NoDate,
+}
+
+impl SmartList {
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Today,
+            Self::Upcoming,
+            Self::Overdue,
+            Self::Someday,
+            Self::NoDate,
+        ]
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Today => "Today",
+            Self::Upcoming => "Next 7 Days",
+            Self::Overdue => "Overdue",
+            Self::Someday => "Someday",
+            Self::NoDate => "No due date",
+        }
+    }
+
+    pub const fn icon(&self) -> &'static str {
+        match self {
+            Self::Today => "☀",
+            Self::Upcoming => "📆",
+            Self::Overdue => "⏰",
+            Self::Someday => "💭",
+            Self::NoDate => "∅",
+        }
+    }
+
+    /// Icon for terminals without emoji support
+    pub const fn ascii_icon(&self) -> &'static str {
+        match self {
+            Self::Today => "o",
+            Self::Upcoming => ">",
+            Self::Overdue => "!",
+            Self::Someday => "~",
+            Self::NoDate => "-",
+        }
+    }
+
+    /// Name used for `tickit ui --view`
+    pub const fn slug(&self) -> &'static str {
+        match self {
+            Self::Today => "today",
+            Self::Upcoming => "upcoming",
+            Self::Overdue => "overdue",
+            Self::Someday => "someday",
+            Self::NoDate => "no-date",
+        }
+    }
+
+    /// Check whether a task belongs in this view
+    pub fn matches(&self, task: &Task) -> bool {
+        let today = chrono::Local::now().date_naive();
+        let due = task
+            .due_date
+            .map(|d| d.with_timezone(&chrono::Local).date_naive());
+        match self {
+            Self::Today => due == Some(today),
+            Self::Upcoming => {
+                due.is_some_and(|d| d > today && d <= today + chrono::Duration::days(7))
+            }
+            Self::Overdue => {
+                !task.completed && task.due_date.is_some_and(|d| d < chrono::Utc::now())
+            }
+            Self::Someday => due.is_some_and(|d| d > today + chrono::Duration::days(7)),
+            Self::NoDate => due.is_none(),
+        }
+    }
+}
+
+/// Completion state for the task filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompletionFilter {
+    /// Follow the show-completed toggle
+    #[default]
+    Any,
+    Open,
+    Done,
+}
+
+impl CompletionFilter {
+    pub const fn all() -> &'static [Self] {
+        &[Self::Any, Self::Open, Self::Done]
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Any => "Any",
+            Self::Open => "Open",
+            Self::Done => "Completed",
+        }
+    }
+}
+
+/// Rows in the filter popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterRow {
+    Tag,
+    Priority,
+    Due,
+    Completion,
+}
+
+impl FilterRow {
+    pub const fn all() -> &'static [Self] {
+        &[Self::Tag, Self::Priority, Self::Due, Self::Completion]
+    }
+
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Tag => "Tag",
+            Self::Priority => "Priority",
+            Self::Due => "Due",
+            Self::Completion => "Status",
+        }
+    }
+}
+
+/// Combined filter applied to the Tasks view
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskFilter {
+    pub tag_id: Option<Uuid>,
+    pub priority: Option<Priority>,
+    pub due: DueFilter,
+    pub completion: CompletionFilter,
+}
+
+impl TaskFilter {
+    /// Whether any filter is set
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Check a task against the in-memory parts of the filter (tag is applied in SQL)
+    pub fn matches(&self, task: &Task) -> bool {
+        self.priority.is_none_or(|p| task.priority == p)
+            && self.due.matches(task)
+            && match self.completion {
+                CompletionFilter::Any => true,
+                CompletionFilter::Open => !task.completed,
+                CompletionFilter::Done => task.completed,
+            }
+    }
+
+    /// Short summary for the panel title, e.g. `#work · High · Overdue`
+    pub fn describe(&self, tags: &[Tag]) -> String {
+        let mut parts = Vec::new();
+        if let Some(tag_id) = self.tag_id {
+            let name = tags
+                .iter()
+                .find(|t| t.id == tag_id)
+                .map(|t| t.name.as_str())
+                .unwrap_or("?");
+            parts.push(format!("#{}", name));
+        }
+        if let Some(priority) = self.priority {
+            parts.push(priority.name().to_string());
+        }
+        if self.due != DueFilter::Any {
+            parts.push(self.due.name().to_string());
+        }
+        if self.completion != CompletionFilter::Any {
+            parts.push(self.completion.name().to_string());
+        }
+        parts.join(" · ")
+    }
+}
+
 /// Editor field being edited
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum EditorField {
@@ -155,14 +546,94 @@ pub enum EditorField {
     Name,
     Icon,
     Color,
+    Checklist,
+}
+
+/// Scroll offsets of the scrollable panels
+///
+/// Offsets are written back while rendering so the viewport only moves when the
+/// selection would leave it.
+#[derive(Debug, Default)]
+pub struct Viewports {
+    /// Sidebar (lists in the Tasks view)
+    pub sidebar: Cell<usize>,
+    /// Task list
+    pub tasks: Cell<usize>,
+    /// Lists view
+    pub lists: Cell<usize>,
+    /// Tags view
+    pub tags: Cell<usize>,
+    /// Help overlay (first visible line)
+    pub help: Cell<usize>,
+    /// Number of visible rows in the last rendered main panel (for paging)
+    pub page_rows: Cell<usize>,
+}
+
+impl Viewports {
+    /// Rows to move for page up/down
+    pub fn page(&self) -> usize {
+        self.page_rows.get().saturating_sub(1).max(1)
+    }
+}
+
+/// Where a workspace was left, restored when switching back to it
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceView {
+    view: View,
+    focus: Focus,
+    selected_list_id: Option<Uuid>,
+    list_index: usize,
+    smart_index: Option<usize>,
+    active_smart: Option<SmartList>,
+    task_index: usize,
+    tag_index: usize,
+    filter: TaskFilter,
+}
+
+/// Stopwatch shown in focus mode
+#[derive(Debug, Default)]
+pub struct FocusTimer {
+    /// Time accumulated before the current run
+    elapsed: std::time::Duration,
+    /// When the current run started (None while paused)
+    running_since: Option<std::time::Instant>,
+}
+
+impl FocusTimer {
+    /// Reset to zero and start running
+    pub fn restart(&mut self) {
+        self.elapsed = std::time::Duration::ZERO;
+        self.running_since = Some(std::time::Instant::now());
+    }
+
+    /// Pause or resume
+    pub fn toggle(&mut self) {
+        match self.running_since.take() {
+            Some(since) => self.elapsed += since.elapsed(),
+            None => self.running_since = Some(std::time::Instant::now()),
+        }
+    }
+
+    /// Whether the timer is running
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Total time on the clock
+    pub fn elapsed(&self) -> std::time::Duration {
+        self.elapsed
+            + self
+                .running_since
+                .map_or_else(Default::default, |s| s.elapsed())
+    }
 }
 
 /// Application state
 pub struct AppState {
     /// Configuration
     pub config: Config,
-    /// Database connection
-    pub db: Database,
+    /// Task operations shared with the CLI, over the database connection
+    pub core: TickitCore,
     /// Current theme (cached from config)
     pub theme: Theme,
     /// Whether to quit the app
@@ -181,20 +652,82 @@ pub struct AppState {
     pub tags: Vec<Tag>,
     /// Current tasks (filtered by selected list)
     pub tasks: Vec<Task>,
+    /// Tasks completed today, most recent first
+    pub completed_today: Vec<Task>,
+    /// Open and overdue task counts for the tab bar, across all lists
+    pub task_badges: (i32, i32),
+    /// Completed and total tasks of each list, for the sidebar
+    pub list_progress: std::collections::HashMap<Uuid, (i32, i32)>,
+    /// Open tasks in every list, for the smart lists, timeline and planner
+    pub open_tasks: Vec<Task>,
+    /// Open tasks in each of [`SmartList::all`], in order
+    pub smart_counts: Vec<usize>,
     /// Currently selected list ID (None = all tasks)
     pub selected_list_id: Option<Uuid>,
 
     // Selection indices
     /// Selected list index in sidebar
     pub list_index: usize,
+    /// Sidebar cursor when it is on a smart list rather than a list
+    pub smart_index: Option<usize>,
+    /// Smart list whose tasks are shown (instead of a list)
+    pub active_smart: Option<SmartList>,
     /// Selected task index in main view
     pub task_index: usize,
     /// Selected tag index in tags view
     pub tag_index: usize,
+    /// Tasks marked in visual-select mode
+    pub marked_tasks: Vec<Uuid>,
+    /// Task whose mark was toggled last (start of a `V` range)
+    pub mark_anchor: Option<Uuid>,
+    /// Tasks cut with Ctrl+x, waiting to be pasted into another list
+    pub cut_tasks: Vec<Uuid>,
     /// Theme picker index
     pub theme_index: usize,
     /// Settings menu index
     pub settings_index: usize,
+    /// Cursor in the list/tag picker dialogs
+    pub picker_index: usize,
+    /// Tasks the move picker will move
+    pub move_targets: Vec<Uuid>,
+    /// Links the link picker offers
+    pub link_choices: Vec<crate::autolink::Link>,
+    /// Every task, loaded when the fuzzy finder opens
+    pub jump_tasks: Vec<Task>,
+    /// Tasks matching the fuzzy jump query, best match first
+    pub jump_results: Vec<Task>,
+    /// Task that the fuzzy finder picks a blocker for (instead of jumping)
+    pub jump_blocker_for: Option<Uuid>,
+    /// Comments of every task, oldest first, for the detail pane
+    pub comments: std::collections::HashMap<Uuid, Vec<Comment>>,
+    /// All `(task, blocked_by)` dependency pairs
+    pub dependencies: Vec<(Uuid, Uuid)>,
+    /// Every task in a dependency, for the detail pane's trees
+    pub dependency_tasks: std::collections::HashMap<Uuid, Task>,
+    /// Tasks waiting on at least one open task
+    pub blocked_tasks: Vec<Uuid>,
+    /// Task shown in focus mode, as last loaded
+    pub focus_task: Option<Task>,
+    /// Checklist cursor in focus mode
+    pub focus_item: usize,
+    /// Focus mode stopwatch
+    pub focus_timer: FocusTimer,
+    /// First day shown in the timeline
+    pub timeline_from: chrono::NaiveDate,
+    /// Rows scrolled past in the timeline
+    pub timeline_scroll: usize,
+    /// First day of the week shown in the planner
+    pub planner_from: chrono::NaiveDate,
+    /// Planner cursor: column (0 is the backlog) and row
+    pub planner_cursor: (usize, usize),
+    /// Stats overlay contents, computed when it opens
+    pub stats: Option<(crate::stats::Summary, crate::stats::Heatmap)>,
+    /// Burndown shown in the stats overlay instead of the heatmap, with the
+    /// name of what it follows
+    pub stats_burndown: Option<(String, crate::stats::Burndown)>,
+    /// What the burndown follows: 0 for all tasks, then each list, then
+    /// each tag
+    pub stats_scope: usize,
     /// Which settings item is being edited (for text input)
     pub settings_editing: Option<SettingsItem>,
 
@@ -209,6 +742,10 @@ pub struct AppState {
     pub editing_task: Option<Task>,
     /// List being edited (for edit mode)
     pub editing_list: Option<List>,
+    /// Accent color chosen in the list editor
+    pub editor_list_color: Option<String>,
+    /// Checklist switch in the list editor
+    pub editor_list_checklist: bool,
     /// Tag being edited (for edit mode)
     pub editing_tag: Option<Tag>,
     /// Selected priority in editor
@@ -227,16 +764,26 @@ pub struct AppState {
     pub editor_title_buffer: String,
     /// Description buffer for tasks
     pub editor_description_buffer: String,
+    /// URL buffer for tasks
+    pub editor_url_buffer: String,
     /// Due date buffer for tasks (YYYY-MM-DD format)
     pub editor_due_date_buffer: String,
 
     // UI state
     /// Show completed tasks
     pub show_completed: bool,
+    /// Active filter for the Tasks view
+    pub filter: TaskFilter,
+    /// Selected row in the filter popup
+    pub filter_row: usize,
     /// Confirmation message
     pub confirm_message: String,
     /// Confirmation callback action
     pub confirm_action: Option<ConfirmAction>,
+    /// Whether "Yes" is selected in the confirm dialog
+    pub confirm_yes: bool,
+    /// Tasks affected by a pending tag or list delete
+    pub confirm_usage: usize,
     /// Status message
     pub status_message: Option<String>,
     /// Status message expiry tick
@@ -245,6 +792,8 @@ pub struct AppState {
     pub tick: usize,
     /// Show help overlay
     pub show_help: bool,
+    /// Scroll offsets for list panels
+    pub scroll: Viewports,
 
     // Update state
     /// Available update version (if any)
@@ -253,20 +802,134 @@ pub struct AppState {
     pub pending_update: bool,
     /// Update result message
     pub update_result: Option<String>,
+    /// Cancel flag of the update running in the background (if any)
+    pub update_cancel: Option<Arc<AtomicBool>>,
+
+    /// Description should be opened in $EDITOR after the next draw
+    pub pending_external_edit: bool,
 
     // Sync state
     /// Sync status for UI display
     pub sync_status: SyncStatus,
     /// Flag to trigger sync after data changes
     pub sync_pending: bool,
+    /// Answer to the preview of this device's first sync, once given
+    pub first_sync_confirmed: Option<bool>,
+    /// Next sync sends and fetches everything (ignores last_sync)
+    pub sync_force: bool,
+    /// Auto-sync paused for this session; manual syncs still run
+    pub sync_paused: bool,
+    /// Auto-sync paused until resumed (`P` or `tickit sync pause`), kept in
+    /// the database so it outlasts the session
+    pub sync_paused_saved: bool,
+    /// This device's ID and host name, read when the sync panel opens
+    pub sync_device: Option<(Uuid, Option<String>)>,
+    /// Background syncs failed in a row
+    pub sync_failures: u32,
+    /// When the failed sync is retried, if it still is
+    pub sync_retry_at: Option<std::time::Instant>,
+
+    // Undo state
+    /// Task operations that can be undone (most recent last)
+    pub undo_stack: Vec<UndoAction>,
+    /// Undone operations that can be redone (most recent last)
+    pub redo_stack: Vec<UndoAction>,
+
+    // Workspace state
+    /// Workspace whose database is open
+    pub workspace: String,
+    /// Where each other workspace was left
+    pub workspace_views: std::collections::HashMap<String, WorkspaceView>,
+    /// Startup warnings not dismissed yet
+    pub warnings: Vec<crate::health::Warning>,
 }
 
+/// Maximum number of operations kept in the undo history
+const UNDO_LIMIT: usize = 100;
+
+/// Number of tasks shown in the "Completed today" section
+const COMPLETED_TODAY_LIMIT: usize = 5;
+
 /// Actions that need confirmation
 #[derive(Debug, Clone)]
 pub enum ConfirmAction {
     DeleteTask(Uuid),
-    DeleteList(Uuid),
+    DeleteTasks(Vec<Uuid>),
+    MergeTasks(Vec<Uuid>),
+    DeleteList(Uuid, DeletedListTasks),
     DeleteTag(Uuid),
+    ResetList(Uuid),
+    FirstSync,
+}
+
+/// A reversible task operation recorded for undo/redo
+// The history is capped at UNDO_LIMIT entries, so boxing the tasks isn't worth it
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum UndoAction {
+    /// A task was created
+    Created(Task),
+    /// A task was changed (completed, edited, moved, reprioritized)
+    Updated {
+        /// What the change was, for status messages
+        label: &'static str,
+        before: Task,
+        after: Task,
+    },
+    /// A task was deleted
+    Deleted(Task),
+    /// Several operations applied together as one bulk action
+    Batch {
+        /// What the bulk action was, for status messages
+        label: &'static str,
+        actions: Vec<UndoAction>,
+    },
+}
+
+impl UndoAction {
+    /// Tasks this action completed
+    pub fn completed(&self) -> Vec<&Task> {
+        match self {
+            UndoAction::Updated { before, after, .. } if after.completed && !before.completed => {
+                vec![after]
+            }
+            UndoAction::Batch { actions, .. } => {
+                actions.iter().flat_map(UndoAction::completed).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The tasks this action changed or deleted, as they were, and the
+    /// ones it created or changed, as they are now
+    pub fn sides(&self) -> (Vec<Task>, Vec<Task>) {
+        match self {
+            UndoAction::Created(task) => (Vec::new(), vec![task.clone()]),
+            UndoAction::Updated { before, after, .. } => {
+                (vec![before.clone()], vec![after.clone()])
+            }
+            UndoAction::Deleted(task) => (vec![task.clone()], Vec::new()),
+            UndoAction::Batch { actions, .. } => {
+                let (mut before, mut after) = (Vec::new(), Vec::new());
+                for action in actions {
+                    let (was, is) = action.sides();
+                    before.extend(was);
+                    after.extend(is);
+                }
+                (before, after)
+            }
+        }
+    }
+
+    /// Short description for status messages
+    pub fn description(&self) -> String {
+        match self {
+            UndoAction::Created(task) => format!("create \"{}\"", task.title),
+            UndoAction::Updated { label, after, .. } => format!("{} \"{}\"", label, after.title),
+            UndoAction::Deleted(task) => format!("delete \"{}\"", task.title),
+            UndoAction::Batch { label, actions } => format!("{} {} tasks", label, actions.len()),
+        }
+    }
 }
 
 impl AppState {
@@ -274,10 +937,17 @@ impl AppState {
     pub fn new(config: Config, db: Database) -> Result<Self> {
         let theme = config.theme;
         let show_completed = config.show_completed;
+        // The workspace whose database `--workspace` (or nothing) picked
+        let workspace = config
+            .workspace_names()
+            .into_iter()
+            .find(|name| config.workspace_path(name).ok() == Database::current_path().ok())
+            .unwrap_or(crate::config::DEFAULT_WORKSPACE)
+            .to_string();
 
         let mut state = Self {
+            core: TickitCore::new(db, config.clone()),
             config,
-            db,
             theme,
             should_quit: false,
             mode: Mode::Normal,
@@ -286,18 +956,50 @@ impl AppState {
             lists: Vec::new(),
             tags: Vec::new(),
             tasks: Vec::new(),
+            completed_today: Vec::new(),
+            task_badges: (0, 0),
+            list_progress: std::collections::HashMap::new(),
+            open_tasks: Vec::new(),
+            smart_counts: Vec::new(),
             selected_list_id: None,
             list_index: 0,
+            smart_index: None,
+            active_smart: None,
             task_index: 0,
             tag_index: 0,
+            marked_tasks: Vec::new(),
+            mark_anchor: None,
+            cut_tasks: Vec::new(),
             theme_index: 0,
             settings_index: 0,
+            picker_index: 0,
+            move_targets: Vec::new(),
+            link_choices: Vec::new(),
+            jump_tasks: Vec::new(),
+            jump_results: Vec::new(),
+            jump_blocker_for: None,
+            comments: std::collections::HashMap::new(),
+            dependencies: Vec::new(),
+            dependency_tasks: std::collections::HashMap::new(),
+            blocked_tasks: Vec::new(),
+            focus_task: None,
+            focus_item: 0,
+            focus_timer: FocusTimer::default(),
+            timeline_from: chrono::Local::now().date_naive(),
+            timeline_scroll: 0,
+            planner_from: chrono::Local::now().date_naive(),
+            planner_cursor: (0, 0),
+            stats: None,
+            stats_burndown: None,
+            stats_scope: 0,
             settings_editing: None,
             editor_field: EditorField::Title,
             input_buffer: String::new(),
             cursor_pos: 0,
             editing_task: None,
             editing_list: None,
+            editor_list_color: None,
+            editor_list_checklist: false,
             editing_tag: None,
             editor_priority: Priority::Medium,
             editor_list_index: 0,
@@ -307,19 +1009,39 @@ impl AppState {
             editor_new_tag_buffer: String::new(),
             editor_title_buffer: String::new(),
             editor_description_buffer: String::new(),
+            editor_url_buffer: String::new(),
             editor_due_date_buffer: String::new(),
             show_completed,
+            filter: TaskFilter::default(),
+            filter_row: 0,
             confirm_message: String::new(),
             confirm_action: None,
+            confirm_yes: true,
+            confirm_usage: 0,
             status_message: None,
             status_expiry: 0,
             tick: 0,
             show_help: false,
+            scroll: Viewports::default(),
             update_available: None,
             pending_update: false,
+            pending_external_edit: false,
             update_result: None,
+            update_cancel: None,
             sync_status: SyncStatus::default(),
             sync_pending: false,
+            first_sync_confirmed: None,
+            sync_force: false,
+            sync_paused: false,
+            sync_paused_saved: false,
+            sync_device: None,
+            sync_failures: 0,
+            sync_retry_at: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            workspace,
+            workspace_views: std::collections::HashMap::new(),
+            warnings: Vec::new(),
         };
 
         state.refresh_data()?;
@@ -333,10 +1055,27 @@ impl AppState {
         Ok(state)
     }
 
+    /// The database connection
+    pub fn db(&self) -> &Database {
+        self.core.db()
+    }
+
+    /// Reload everything from the database, keeping the cursor on the same task
+    pub fn reload_keeping_selection(&mut self) -> Result<()> {
+        let selected_id = self.selected_task().map(|t| t.id);
+        self.refresh_data()?;
+        if let Some(i) = selected_id.and_then(|id| self.tasks.iter().position(|t| t.id == id)) {
+            self.task_index = i;
+        }
+        Ok(())
+    }
+
     /// Refresh all data from database
     pub fn refresh_data(&mut self) -> Result<()> {
-        self.lists = self.db.get_lists()?;
-        self.tags = self.db.get_tags()?;
+        // Picks up `tickit sync pause`/`resume` run elsewhere
+        self.sync_paused_saved = self.db().is_sync_paused()?;
+        self.lists = self.db().get_lists()?;
+        self.tags = self.db().get_tags()?;
         self.refresh_tasks()?;
 
         // Clamp indices (no more "All" so max is lists.len() - 1)
@@ -355,35 +1094,133 @@ impl AppState {
 
     /// Refresh tasks based on current filter
     pub fn refresh_tasks(&mut self) -> Result<()> {
-        let completed_filter = if self.show_completed {
-            None
-        } else {
-            Some(false)
+        let completed_filter = match self.filter.completion {
+            CompletionFilter::Any if !self.show_completed => Some(false),
+            CompletionFilter::Any => None,
+            CompletionFilter::Open => Some(false),
+            CompletionFilter::Done => Some(true),
         };
+        let tag_filter = self.filter.tag_id;
 
         // Check if Inbox is selected - if so, show all tasks (like "All" did before)
         let is_inbox_selected = self.selected_list().map(|l| l.is_inbox).unwrap_or(false);
 
-        self.tasks = if is_inbox_selected {
+        self.tasks = if let Some(smart) = self.active_smart {
+            let mut tasks = self
+                .db()
+                .get_tasks_with_filter(None, completed_filter, tag_filter)?;
+            tasks.retain(|t| smart.matches(t));
+            tasks
+        } else if is_inbox_selected {
             // Inbox shows all tasks from all lists
-            self.db
-                .get_tasks_with_filter(None, completed_filter, None)?
+            self.db()
+                .get_tasks_with_filter(None, completed_filter, tag_filter)?
         } else if let Some(list_id) = self.selected_list_id {
-            self.db
-                .get_tasks_with_filter(Some(list_id), completed_filter, None)?
+            self.db()
+                .get_tasks_with_filter(Some(list_id), completed_filter, tag_filter)?
         } else {
-            self.db
-                .get_tasks_with_filter(None, completed_filter, None)?
+            self.db()
+                .get_tasks_with_filter(None, completed_filter, tag_filter)?
         };
+        let filter = &self.filter;
+        self.tasks.retain(|t| filter.matches(t));
+
+        self.task_sort().apply(&mut self.tasks);
+
+        let midnight = chrono::Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.with_timezone(&chrono::Utc));
+        self.comments.clear();
+        for comment in self.db().get_all_comments()? {
+            self.comments
+                .entry(comment.task_id)
+                .or_default()
+                .push(comment);
+        }
+        self.dependencies = self.db().get_dependencies()?;
+        self.blocked_tasks = self.db().get_blocked_task_ids()?;
+        self.task_badges = (
+            self.db().get_total_task_count(false)?,
+            self.db().get_overdue_count(chrono::Utc::now())?,
+        );
+        self.list_progress = self.db().get_list_progress()?;
+        self.open_tasks = self.db().get_tasks_with_filter(None, Some(false), None)?;
+        self.dependency_tasks.clear();
+        for &(task, blocker) in &self.dependencies {
+            for id in [task, blocker] {
+                if self.dependency_tasks.contains_key(&id) {
+                    continue;
+                }
+                // Completed tasks aren't among the open ones
+                let found = match self.open_tasks.iter().find(|t| t.id == id) {
+                    Some(task) => Some(task.clone()),
+                    None => self.core.db().get_task(id).ok(),
+                };
+                if let Some(found) = found {
+                    self.dependency_tasks.insert(id, found);
+                }
+            }
+        }
+        // Undo, sync or an edit may have changed the task in focus mode
+        if let Some(id) = self.focus_task.as_ref().map(|t| t.id) {
+            self.focus_task = self.db().get_task(id).ok();
+        }
+        self.smart_counts = SmartList::all()
+            .iter()
+            .map(|smart| self.open_tasks.iter().filter(|t| smart.matches(t)).count())
+            .collect();
+
+        if let Some(midnight) = midnight {
+            self.completed_today = self
+                .db()
+                .get_completed_since(midnight, COMPLETED_TODAY_LIMIT)?;
+        }
 
         // Clamp task index
         if !self.tasks.is_empty() && self.task_index >= self.tasks.len() {
             self.task_index = self.tasks.len() - 1;
         }
 
+        // Drop marks for tasks that are no longer visible
+        let tasks = &self.tasks;
+        self.marked_tasks
+            .retain(|id| tasks.iter().any(|t| t.id == *id));
+        if self.marked_tasks.is_empty() {
+            self.mark_anchor = None;
+        }
+
         Ok(())
     }
 
+    /// UI colors of the current theme, with the high-contrast variant if configured
+    /// and reduced to 256 colors when the terminal has no truecolor
+    pub fn colors(&self) -> ThemeColors {
+        let mut colors = self.theme.colors();
+        if self.config.display.high_contrast {
+            colors = colors.high_contrast();
+        }
+        if !self.config.display.truecolor() {
+            colors = colors.to_ansi256();
+        }
+        colors
+    }
+
+    /// A color from user data (e.g. a tag color), reduced like the theme colors
+    pub fn fit_color(&self, color: Color) -> Color {
+        if self.config.display.truecolor() {
+            color
+        } else {
+            ThemeColors::ansi256(color)
+        }
+    }
+
+    /// Whether to draw emoji and symbol icons rather than their ASCII fallbacks
+    pub fn unicode(&self) -> bool {
+        self.config.display.unicode()
+    }
+
     /// Get the currently selected task
     pub fn selected_task(&self) -> Option<&Task> {
         self.tasks.get(self.task_index)
@@ -399,52 +1236,991 @@ impl AppState {
         self.tags.get(self.tag_index)
     }
 
-    /// Toggle show completed tasks
-    pub fn toggle_show_completed(&mut self) {
-        self.show_completed = !self.show_completed;
-        self.config.show_completed = self.show_completed;
+    /// Config key for the sort order of the current task view
+    fn task_sort_key(&self) -> String {
+        self.selected_list_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "all".to_string())
+    }
+
+    /// Get the sort order for the current task view
+    pub fn task_sort(&self) -> TaskSort {
+        self.config
+            .task_sort
+            .get(&self.task_sort_key())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Cycle the sort order for the current task view and persist it
+    pub fn cycle_task_sort(&mut self) {
+        let selected_id = self.selected_task().map(|t| t.id);
+        let sort = self.task_sort().next();
+        let key = self.task_sort_key();
+        self.config.task_sort.insert(key, sort);
+        let _ = self.config.save();
         let _ = self.refresh_tasks();
+
+        // Keep the cursor on the same task
+        if let Some(id) = selected_id
+            && let Some(idx) = self.tasks.iter().position(|t| t.id == id)
+        {
+            self.task_index = idx;
+        }
+        self.set_status(format!("Sort: {}", sort.name()));
     }
 
-    /// Set a status message
-    pub fn set_status(&mut self, message: impl Into<String>) {
-        self.status_message = Some(message.into());
-        self.status_expiry = self.tick + 30; // ~3 seconds
+    /// Move the selected task one place up (`-1`) or down (`1`) within its list
+    ///
+    /// Only available in manual sort; the whole list is renumbered so the new
+    /// order is stored in `sort_order`.
+    pub fn move_selected_task(&mut self, delta: isize) -> Result<()> {
+        if self.task_sort() != TaskSort::Manual {
+            self.set_status("Switch to manual sort (s) to reorder tasks");
+            return Ok(());
+        }
+        let Some(task) = self.selected_task().cloned() else {
+            return Ok(());
+        };
+        let Some(neighbor) = self
+            .task_index
+            .checked_add_signed(delta)
+            .and_then(|i| self.tasks.get(i))
+            .cloned()
+        else {
+            return Ok(());
+        };
+        if neighbor.list_id != task.list_id || neighbor.completed != task.completed {
+            self.set_status("Tasks can only be reordered within their list");
+            return Ok(());
+        }
+
+        let mut ordered = self.db().get_tasks_for_list(task.list_id)?;
+        TaskSort::Manual.apply(&mut ordered);
+        let from = ordered.iter().position(|t| t.id == task.id);
+        let to = ordered.iter().position(|t| t.id == neighbor.id);
+        let (Some(from), Some(to)) = (from, to) else {
+            return Ok(());
+        };
+        let moved = ordered.remove(from);
+        ordered.insert(to, moved);
+
+        let now = chrono::Utc::now();
+        let (before, after): (Vec<Task>, Vec<Task>) = ordered
+            .into_iter()
+            .enumerate()
+            .filter(|(i, t)| t.sort_order != *i as i32)
+            .map(|(i, t)| {
+                let mut updated = t.clone();
+                updated.sort_order = i as i32;
+                updated.updated_at = now;
+                (t, updated)
+            })
+            .unzip();
+        self.commit(UndoAction::Batch {
+            label: "reorder",
+            actions: before
+                .into_iter()
+                .zip(after)
+                .map(|(before, after)| UndoAction::Updated {
+                    label: "reorder",
+                    before,
+                    after,
+                })
+                .collect(),
+        })?;
+
+        self.refresh_tasks()?;
+        if let Some(idx) = self.tasks.iter().position(|t| t.id == task.id) {
+            self.task_index = idx;
+        }
+        self.mark_sync_pending();
+        Ok(())
     }
 
-    /// Tick for animations and status expiry
-    pub fn tick(&mut self) {
-        self.tick = self.tick.wrapping_add(1);
-        if self.status_expiry > 0 && self.tick >= self.status_expiry {
-            self.status_message = None;
+    /// Open the filter popup
+    pub fn start_filter(&mut self) {
+        self.filter_row = 0;
+        self.mode = Mode::Filter;
+    }
+
+    /// Step the value of the selected filter row forwards or backwards
+    pub fn adjust_filter(&mut self, delta: i32) {
+        fn step<T: Copy + PartialEq>(options: &[T], current: T, delta: i32) -> T {
+            let len = options.len() as i32;
+            let idx = options.iter().position(|o| *o == current).unwrap_or(0) as i32;
+            options[(idx + delta).rem_euclid(len) as usize]
+        }
+
+        match FilterRow::all()[self.filter_row] {
+            FilterRow::Tag => {
+                let options: Vec<Option<Uuid>> = std::iter::once(None)
+                    .chain(self.tags.iter().map(|t| Some(t.id)))
+                    .collect();
+                self.filter.tag_id = step(&options, self.filter.tag_id, delta);
+            }
+            FilterRow::Priority => {
+                let options: Vec<Option<Priority>> = std::iter::once(None)
+                    .chain(Priority::all().iter().copied().map(Some))
+                    .collect();
+                self.filter.priority = step(&options, self.filter.priority, delta);
+            }
+            FilterRow::Due => {
+                self.filter.due = step(DueFilter::all(), self.filter.due, delta);
+            }
+            FilterRow::Completion => {
+                self.filter.completion =
+                    step(CompletionFilter::all(), self.filter.completion, delta);
+            }
         }
+        self.task_index = 0;
+        let _ = self.refresh_tasks();
     }
 
-    /// Start adding a new task
-    pub fn start_add_task(&mut self) {
-        self.mode = Mode::AddTask;
-        self.editor_field = EditorField::Title;
-        self.input_buffer.clear();
-        self.cursor_pos = 0;
-        self.editing_task = None;
-        self.editor_priority = Priority::Medium;
-        self.editor_tag_indices.clear();
-        self.editor_tag_cursor = 0;
-        self.editor_adding_tag = false;
-        self.editor_new_tag_buffer.clear();
-        self.editor_title_buffer.clear();
-        self.editor_description_buffer.clear();
-        self.editor_due_date_buffer.clear();
+    /// Clear the task filter
+    pub fn clear_filter(&mut self) {
+        if self.filter.is_active() {
+            self.filter = TaskFilter::default();
+            self.task_index = 0;
+            let _ = self.refresh_tasks();
+            self.set_status("Filter cleared");
+        }
+    }
 
-        // Set editor list to current selected list or inbox
-        if let Some(list_id) = self.selected_list_id {
-            self.editor_list_index = self.lists.iter().position(|l| l.id == list_id).unwrap_or(0);
-        } else {
-            self.editor_list_index = self.lists.iter().position(|l| l.is_inbox).unwrap_or(0);
+    /// Jump to the Tasks view showing every task with the selected tag
+    pub fn show_tasks_for_selected_tag(&mut self) {
+        let Some(tag) = self.selected_tag().cloned() else {
+            return;
+        };
+        self.filter.tag_id = Some(tag.id);
+        // The Inbox entry shows tasks from every list
+        if let Some(inbox) = self.lists.iter().position(|l| l.is_inbox) {
+            self.list_index = inbox;
         }
+        self.smart_index = None;
+        self.active_smart = None;
+        self.selected_list_id = None;
+        self.view = View::Tasks;
+        self.focus = Focus::Main;
+        self.task_index = 0;
+        let _ = self.refresh_tasks();
+        self.set_status(format!("Tasks tagged #{} (Esc to clear)", tag.name));
     }
 
-    /// Start editing the selected task
+    /// Number of rows in the Tasks view sidebar (smart lists, then lists)
+    pub fn sidebar_len(&self) -> usize {
+        SmartList::all().len() + self.lists.len()
+    }
+
+    /// Position of the sidebar cursor across smart lists and lists
+    pub fn sidebar_cursor(&self) -> usize {
+        self.smart_index
+            .unwrap_or(SmartList::all().len() + self.list_index)
+    }
+
+    /// Move the sidebar cursor, clamped to the last row
+    pub fn set_sidebar_cursor(&mut self, pos: usize) {
+        let smart_count = SmartList::all().len();
+        let pos = pos.min(self.sidebar_len().saturating_sub(1));
+        if pos < smart_count {
+            self.smart_index = Some(pos);
+        } else {
+            self.smart_index = None;
+            self.list_index = pos - smart_count;
+        }
+    }
+
+    /// Show the tasks of the smart list or list under the sidebar cursor
+    pub fn open_sidebar_item(&mut self) {
+        if let Some(i) = self.smart_index {
+            self.active_smart = SmartList::all().get(i).copied();
+            self.selected_list_id = None;
+        } else if let Some(list) = self.lists.get(self.list_index) {
+            self.active_smart = None;
+            // Inbox shows all tasks, other lists show only their tasks
+            self.selected_list_id = if list.is_inbox { None } else { Some(list.id) };
+        }
+        let _ = self.refresh_tasks();
+        self.task_index = 0;
+        self.focus = Focus::Main;
+    }
+
+    /// Open the list, tag filter, view and task given on the command line
+    pub fn open_at(&mut self, start: &StartAt) -> Result<()> {
+        if let Some(i) = start
+            .list
+            .and_then(|id| self.lists.iter().position(|l| l.id == id))
+        {
+            self.set_sidebar_cursor(SmartList::all().len() + i);
+            self.open_sidebar_item();
+        }
+        if let Some(tag_id) = start.tag {
+            self.filter.tag_id = Some(tag_id);
+        }
+        match start.view.as_deref() {
+            None | Some("tasks") => {}
+            Some("lists") => self.view = View::Lists,
+            Some("tags") => self.view = View::Tags,
+            Some(name) => {
+                let i = SmartList::all()
+                    .iter()
+                    .position(|s| s.slug() == name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown view: {}", name))?;
+                self.set_sidebar_cursor(i);
+                self.open_sidebar_item();
+            }
+        }
+        self.refresh_tasks()?;
+        if let Some(task) = start.task.and_then(|id| self.db().get_task(id).ok()) {
+            self.reveal_task(&task)?;
+        }
+        Ok(())
+    }
+
+    /// Title of the task panel for the current list or smart list
+    pub fn task_panel_name(&self) -> String {
+        if let Some(smart) = self.active_smart {
+            return smart.name().to_string();
+        }
+        self.selected_list_id
+            .and_then(|id| self.lists.iter().find(|l| l.id == id))
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "All Tasks".to_string())
+    }
+
+    /// Toggle show completed tasks
+    pub fn toggle_show_completed(&mut self) {
+        self.show_completed = !self.show_completed;
+        self.config.show_completed = self.show_completed;
+        let _ = self.config.save();
+        let _ = self.refresh_tasks();
+    }
+
+    /// Set a status message
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+        self.status_expiry = self.tick + 30; // ~3 seconds
+    }
+
+    /// Tick for animations and status expiry
+    pub fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        if self.status_expiry > 0 && self.tick >= self.status_expiry {
+            self.status_message = None;
+        }
+    }
+
+    /// Start adding a new task
+    pub fn start_add_task(&mut self) {
+        self.mode = Mode::AddTask;
+        self.editor_field = EditorField::Title;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        self.editing_task = None;
+        self.editor_priority = Priority::Medium;
+        self.editor_tag_indices.clear();
+        self.editor_tag_cursor = 0;
+        self.editor_adding_tag = false;
+        self.editor_new_tag_buffer.clear();
+        self.editor_title_buffer.clear();
+        self.editor_description_buffer.clear();
+        self.editor_url_buffer.clear();
+        self.editor_due_date_buffer.clear();
+
+        // Set editor list to current selected list or inbox
+        if let Some(list_id) = self.selected_list_id {
+            self.editor_list_index = self.lists.iter().position(|l| l.id == list_id).unwrap_or(0);
+        } else {
+            self.editor_list_index = self.lists.iter().position(|l| l.is_inbox).unwrap_or(0);
+        }
+    }
+
+    /// Start the one-line quick-add prompt
+    pub fn start_quick_add(&mut self) {
+        self.mode = Mode::QuickAdd;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// Start a comment on the selected task
+    pub fn start_comment(&mut self) {
+        if self.selected_task().is_some() {
+            self.mode = Mode::Comment;
+            self.input_buffer.clear();
+            self.cursor_pos = 0;
+        }
+    }
+
+    /// Add the comment from the comment prompt to the selected task
+    pub fn save_comment(&mut self) -> Result<()> {
+        let body = self.input_buffer.trim();
+        if body.is_empty() {
+            self.set_status("Comment cannot be empty");
+            return Ok(());
+        }
+        let Some(task) = self.selected_task().cloned() else {
+            return Ok(());
+        };
+        self.core.add_comment(&task, body)?;
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        self.set_status("Comment added");
+        self.mark_sync_pending();
+        self.refresh_tasks()
+    }
+
+    /// Start the defer prompt for the marked tasks, or the selected task
+    pub fn start_defer(&mut self) {
+        if self.marked_tasks.is_empty() && self.selected_task().is_none() {
+            return;
+        }
+        self.mode = Mode::Defer;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// The marked tasks, or the selected one when none are marked
+    pub fn marked_or_selected(&self) -> Vec<Task> {
+        if self.marked_tasks.is_empty() {
+            self.selected_task().cloned().into_iter().collect()
+        } else {
+            self.marked()
+        }
+    }
+
+    /// Push the due dates of the defer prompt's tasks to the date typed in it
+    pub fn save_defer(&mut self) -> Result<()> {
+        let when = self.input_buffer.trim().to_string();
+        if parse_defer(&when, None).is_none() {
+            self.set_status(format!("Can't read \"{}\" as a date", when));
+            return Ok(());
+        }
+        let tasks = self.marked_or_selected();
+        let first = tasks.first().and_then(|t| parse_defer(&when, t.due_date));
+        let count = self.update_tasks(tasks, "defer", |t| {
+            t.due_date = parse_defer(&when, t.due_date);
+        })?;
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        match first {
+            Some(due) if count == 1 => self.set_status(format!(
+                "Deferred to {}",
+                self.config.format_date(due.date_naive())
+            )),
+            _ => self.set_status(format!("{} tasks deferred", count)),
+        }
+        self.clear_marks();
+        self.refresh_tasks()
+    }
+
+    /// Create a task from the quick-add prompt
+    ///
+    /// Unknown tags are created; an unknown list falls back to the current list.
+    pub fn save_quick_add(&mut self) -> Result<()> {
+        let parsed = QuickAdd::parse(&self.input_buffer);
+        if parsed.title.is_empty() {
+            self.set_status("Task title cannot be empty");
+            return Ok(());
+        }
+
+        let current_list = self
+            .selected_list_id
+            .or_else(|| self.lists.iter().find(|l| l.is_inbox).map(|l| l.id));
+        let mut unknown_list = None;
+        let list_id = match &parsed.list {
+            Some(name) => {
+                let wanted = name.to_lowercase();
+                let found = self.lists.iter().find(|l| {
+                    let list_name = l.name.to_lowercase();
+                    list_name == wanted || list_name.replace(' ', "-") == wanted
+                });
+                if found.is_none() {
+                    unknown_list = Some(name.clone());
+                }
+                found.map(|l| l.id).or(current_list)
+            }
+            None => current_list,
+        };
+        let Some(list_id) = list_id else {
+            return Ok(());
+        };
+
+        let mut task = Task::new(&parsed.title, list_id);
+        task.priority = parsed.priority.unwrap_or_default();
+        task.due_date = parsed.due_date;
+        for name in &parsed.tags {
+            let existing = self
+                .tags
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+                .map(|t| t.id);
+            let tag_id = match existing {
+                Some(id) => id,
+                None => {
+                    let tag = self.core.create_tag(name, None)?;
+                    self.tags.push(tag.clone());
+                    tag.id
+                }
+            };
+            task.tag_ids.push(tag_id);
+        }
+
+        self.commit(UndoAction::Created(task))?;
+        match unknown_list {
+            Some(name) => self.set_status(format!("Task created (no list named '{}')", name)),
+            None => self.set_status("Task created"),
+        }
+
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        self.refresh_data()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Open the help overlay with an empty filter
+    pub fn open_help(&mut self) {
+        self.mode = Mode::Help;
+        self.show_help = true;
+        self.scroll.help.set(0);
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// Close the help overlay
+    pub fn close_help(&mut self) {
+        self.mode = Mode::Normal;
+        self.show_help = false;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// Open the fuzzy finder over all tasks
+    pub fn start_jump(&mut self) {
+        self.mode = Mode::Jump;
+        self.jump_blocker_for = None;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        self.jump_tasks = self.db().get_all_tasks().unwrap_or_default();
+        self.update_jump_results();
+    }
+
+    /// Re-rank the loaded tasks against the jump query
+    pub fn update_jump_results(&mut self) {
+        let mut scored: Vec<(i64, &Task)> = self
+            .jump_tasks
+            .iter()
+            .filter_map(|task| {
+                fuzzy_match(&self.input_buffer, &task.title).map(|m| {
+                    // Open tasks first when scores tie
+                    (m.score * 2 - task.completed as i64, task)
+                })
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.jump_results = scored.into_iter().map(|(_, task)| task.clone()).collect();
+        self.picker_index = 0;
+    }
+
+    /// Select the task picked in the fuzzy finder, switching list and filters as needed
+    pub fn jump_to_picked_task(&mut self) -> Result<()> {
+        self.mode = Mode::Normal;
+        let Some(task) = self.jump_results.get(self.picker_index).cloned() else {
+            return Ok(());
+        };
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+        if let Some(task_id) = self.jump_blocker_for.take() {
+            return self.add_blocker(task_id, &task);
+        }
+        self.reveal_task(&task)
+    }
+
+    /// Select `task` in the Tasks view, switching list and filters as needed
+    fn reveal_task(&mut self, task: &Task) -> Result<()> {
+        self.view = View::Tasks;
+        self.focus = Focus::Main;
+
+        if !self.tasks.iter().any(|t| t.id == task.id) {
+            // Open the task's own list
+            self.active_smart = None;
+            self.smart_index = None;
+            if let Some(i) = self.lists.iter().position(|l| l.id == task.list_id) {
+                self.list_index = i;
+                self.selected_list_id = Some(task.list_id).filter(|_| !self.lists[i].is_inbox);
+            }
+            self.refresh_tasks()?;
+        }
+        if !self.tasks.iter().any(|t| t.id == task.id) && self.filter.is_active() {
+            self.filter = TaskFilter::default();
+            self.refresh_tasks()?;
+        }
+        if !self.tasks.iter().any(|t| t.id == task.id) && task.completed && !self.show_completed {
+            self.toggle_show_completed();
+        }
+
+        if let Some(i) = self.tasks.iter().position(|t| t.id == task.id) {
+            self.task_index = i;
+        }
+        Ok(())
+    }
+
+    /// Turn the selected task's row into an editable title field
+    pub fn start_inline_edit(&mut self) {
+        if let Some(task) = self.selected_task() {
+            self.input_buffer = task.title.clone();
+            self.cursor_pos = self.input_buffer.len();
+            self.mode = Mode::InlineEdit;
+        }
+    }
+
+    /// Save the inline-edited title of the selected task
+    pub fn save_inline_edit(&mut self) -> Result<()> {
+        let title = self.input_buffer.trim().to_string();
+        if title.is_empty() {
+            self.set_status("Task title cannot be empty");
+            return Ok(());
+        }
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+        self.cursor_pos = 0;
+
+        let Some(task) = self.tasks.get_mut(self.task_index) else {
+            return Ok(());
+        };
+        if task.title == title {
+            return Ok(());
+        }
+        let before = task.clone();
+        task.title = title;
+        task.updated_at = chrono::Utc::now();
+        let after = task.clone();
+        self.commit(UndoAction::Updated {
+            label: "rename",
+            before,
+            after,
+        })?;
+        self.set_status("Task renamed");
+        self.refresh_tasks()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Pick a task the selected task waits on, using the fuzzy finder
+    pub fn start_pick_blocker(&mut self) {
+        if let Some(task_id) = self.selected_task().map(|t| t.id) {
+            self.start_jump();
+            self.jump_blocker_for = Some(task_id);
+        }
+    }
+
+    /// Make `task_id` blocked by `blocker`, refusing cycles
+    fn add_blocker(&mut self, task_id: Uuid, blocker: &Task) -> Result<()> {
+        if deps::would_cycle(&self.dependencies, task_id, blocker.id) {
+            self.set_status("That would create a dependency cycle");
+            return Ok(());
+        }
+        self.db().add_dependency(task_id, blocker.id)?;
+        self.set_status(format!("Now waiting on \"{}\"", blocker.title));
+        self.refresh_tasks()
+    }
+
+    /// Remove every blocker of the selected task
+    pub fn clear_blockers(&mut self) -> Result<()> {
+        let Some(task_id) = self.selected_task().map(|t| t.id) else {
+            return Ok(());
+        };
+        if deps::neighbours(&self.dependencies, task_id, deps::Direction::BlockedBy).is_empty() {
+            self.set_status("Task has no blockers");
+            return Ok(());
+        }
+        self.db().clear_dependencies(task_id)?;
+        self.set_status("Blockers cleared");
+        self.refresh_tasks()
+    }
+
+    /// Show the selected task full-screen and start the focus timer
+    pub fn start_focus(&mut self) {
+        if let Some(task) = self.selected_task().cloned() {
+            self.focus_task = Some(task);
+            self.focus_item = 0;
+            self.focus_timer.restart();
+            self.mode = Mode::Focus;
+        }
+    }
+
+    /// Show the timeline, starting the week before this one
+    pub fn open_timeline(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        self.timeline_from = super::timeline::default_start(today, self.config.week_starts_on);
+        self.timeline_scroll = 0;
+        self.mode = Mode::Timeline;
+    }
+
+    /// Show the week planner for this week, with the cursor on the backlog
+    pub fn open_planner(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        self.planner_from = super::planner::week_start(today, self.config.week_starts_on);
+        self.planner_cursor = (0, 0);
+        self.mode = Mode::Planner;
+    }
+
+    /// The planner's columns for the week shown, from all open tasks
+    pub fn planner_columns(&self) -> Vec<Vec<Task>> {
+        let today = chrono::Local::now().date_naive();
+        super::planner::columns(&self.open_tasks, self.planner_from, today)
+    }
+
+    /// Move the planner cursor by `columns` and `rows`, staying on a task
+    pub fn move_planner_cursor(&mut self, columns: isize, rows: isize) {
+        let board = self.planner_columns();
+        let (column, row) = self.planner_cursor;
+        let column = column
+            .saturating_add_signed(columns)
+            .min(super::planner::COLUMNS - 1);
+        let last = board[column].len().saturating_sub(1);
+        self.planner_cursor = (column, row.saturating_add_signed(rows).min(last));
+    }
+
+    /// Move the planner by `weeks` weeks (back if negative)
+    pub fn scroll_planner_weeks(&mut self, weeks: i64) {
+        self.planner_from += chrono::Duration::weeks(weeks);
+        self.move_planner_cursor(0, 0);
+    }
+
+    /// Move the task under the planner cursor to `column`: a day sets its
+    /// due date, the backlog clears it. The cursor follows the task.
+    pub fn plan_task(&mut self, column: usize) -> Result<()> {
+        use super::planner;
+
+        let board = self.planner_columns();
+        let (from, row) = self.planner_cursor;
+        let Some(task) = board[from].get(row).cloned() else {
+            return Ok(());
+        };
+        let column = column.min(planner::COLUMNS - 1);
+        if column == from {
+            return Ok(());
+        }
+        let day = planner::day(column, self.planner_from);
+        self.update_tasks(vec![task.clone()], "plan", |t| {
+            t.due_date = day.and_then(crate::quickadd::end_of_day);
+        })?;
+        match day {
+            Some(day) => self.set_status(format!(
+                "\"{}\" planned for {} {}",
+                task.title,
+                day.format("%a"),
+                self.config.format_date(day)
+            )),
+            None => self.set_status(format!("\"{}\" moved to the backlog", task.title)),
+        }
+
+        self.refresh_tasks()?;
+        if let Some(row) = self.planner_columns()[column]
+            .iter()
+            .position(|t| t.id == task.id)
+        {
+            self.planner_cursor = (column, row);
+        }
+        Ok(())
+    }
+
+    /// Add startup warnings, leaving out ones dismissed before with the same title
+    pub fn add_warnings(&mut self, warnings: Vec<crate::health::Warning>) {
+        let before = self.warnings.len();
+        for warning in warnings {
+            let dismissed = self
+                .db()
+                .get_sync_state(&dismissed_warning_key(&warning))
+                .ok()
+                .flatten()
+                .is_some_and(|title| title == warning.title);
+            if !dismissed && !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+        if self.warnings.len() > before {
+            self.set_status(format!(
+                "{} warning(s) found, press ! to see them",
+                self.warnings.len()
+            ));
+        }
+    }
+
+    /// Show the startup warnings
+    pub fn open_warnings(&mut self) {
+        if self.warnings.is_empty() {
+            self.set_status("No warnings");
+        } else {
+            self.picker_index = 0;
+            self.mode = Mode::Warnings;
+        }
+    }
+
+    /// Dismiss the picked warning until it comes up with a different title
+    pub fn dismiss_warning(&mut self) {
+        if self.picker_index >= self.warnings.len() {
+            return;
+        }
+        let warning = self.warnings.remove(self.picker_index);
+        let _ = self
+            .db()
+            .set_sync_state(&dismissed_warning_key(&warning), &warning.title);
+        if self.warnings.is_empty() {
+            self.mode = Mode::Normal;
+            self.set_status("All warnings dismissed");
+        } else {
+            self.picker_index = self.picker_index.min(self.warnings.len() - 1);
+        }
+    }
+
+    /// Show the workspace picker, on the open workspace
+    pub fn open_workspace_picker(&mut self) {
+        if self.db().is_in_memory() {
+            self.set_status("Ephemeral session: there are no workspaces to switch to");
+            return;
+        }
+        if self.config.workspaces.is_empty() {
+            self.set_status(
+                "No workspaces yet: add [[workspaces]] with a name and path to config.toml",
+            );
+            return;
+        }
+        self.picker_index = self
+            .config
+            .workspace_names()
+            .iter()
+            .position(|name| *name == self.workspace)
+            .unwrap_or(0);
+        self.mode = Mode::Workspaces;
+    }
+
+    /// Close this workspace's database and open the picked one, back where
+    /// it was left
+    pub fn switch_to_picked_workspace(&mut self) {
+        self.mode = Mode::Normal;
+        let Some(name) = self
+            .config
+            .workspace_names()
+            .get(self.picker_index)
+            .map(|name| name.to_string())
+        else {
+            return;
+        };
+        if name == self.workspace {
+            return;
+        }
+        if self.sync_status.syncing {
+            self.set_status("Wait for the sync to finish before switching workspaces");
+            return;
+        }
+        let opened = self
+            .config
+            .workspace_path(&name)
+            .and_then(|path| Ok((Database::open_path(&path)?, path)));
+        let (db, path) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                self.set_status(format!("Error: {:#}", e));
+                return;
+            }
+        };
+
+        let left = WorkspaceView {
+            view: self.view,
+            focus: self.focus,
+            selected_list_id: self.selected_list_id,
+            list_index: self.list_index,
+            smart_index: self.smart_index,
+            active_smart: self.active_smart,
+            task_index: self.task_index,
+            tag_index: self.tag_index,
+            filter: std::mem::take(&mut self.filter),
+        };
+        let previous = std::mem::replace(&mut self.workspace, name.clone());
+        self.workspace_views.insert(previous, left);
+        // Drops the old connection; background tasks follow the new one
+        self.core = TickitCore::new(db, self.config.clone());
+        Database::set_path(path);
+
+        let view = self.workspace_views.remove(&name).unwrap_or_default();
+        self.view = view.view;
+        self.focus = view.focus;
+        self.selected_list_id = view.selected_list_id;
+        self.list_index = view.list_index;
+        self.smart_index = view.smart_index;
+        self.active_smart = view.active_smart;
+        self.task_index = view.task_index;
+        self.tag_index = view.tag_index;
+        self.filter = view.filter;
+
+        // Tasks of the other database can't be undone or marked here
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.marked_tasks.clear();
+        self.mark_anchor = None;
+        self.cut_tasks.clear();
+        self.sync_pending = false;
+        self.sync_failures = 0;
+        self.sync_retry_at = None;
+        if let Err(e) = self.refresh_data() {
+            self.set_status(format!("Error: {}", e));
+            return;
+        }
+        self.refresh_pending_changes();
+        self.set_status(format!("Workspace: {}", name));
+    }
+
+    /// Show completion stats and the heatmap as of today
+    pub fn open_stats(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        let week_starts_on = self.config.week_starts_on;
+        let stats = crate::stats::Completions::load(self.db()).and_then(|completions| {
+            let open = self.db().get_total_task_count(false)?.max(0) as u32;
+            Ok((
+                crate::stats::Summary::new(open, &completions, today, week_starts_on),
+                crate::stats::Heatmap::new(&completions, today, week_starts_on),
+            ))
+        });
+        match stats {
+            Ok(stats) => {
+                self.stats = Some(stats);
+                self.stats_burndown = None;
+                // Start the burndown on the list being viewed
+                self.stats_scope = self
+                    .selected_list_id
+                    .and_then(|id| self.lists.iter().position(|l| l.id == id))
+                    .map_or(0, |i| i + 1);
+                self.mode = Mode::Stats;
+            }
+            Err(e) => self.set_status(format!("Error: {}", e)),
+        }
+    }
+
+    /// Swap the heatmap in the stats overlay for a burndown, or back
+    pub fn toggle_stats_burndown(&mut self) {
+        if self.stats_burndown.take().is_none() {
+            self.load_stats_burndown();
+        }
+    }
+
+    /// Follow the next (or previous) list or tag in the burndown
+    pub fn cycle_stats_scope(&mut self, forward: bool) {
+        if self.stats_burndown.is_none() {
+            return;
+        }
+        let scopes = 1 + self.lists.len() + self.tags.len();
+        self.stats_scope = if forward {
+            (self.stats_scope + 1) % scopes
+        } else {
+            (self.stats_scope + scopes - 1) % scopes
+        };
+        self.load_stats_burndown();
+    }
+
+    fn load_stats_burndown(&mut self) {
+        use crate::stats::{BURNDOWN_DAYS, Burndown, Scope};
+
+        let (scope, name) = match self.stats_scope.checked_sub(1) {
+            None => (Scope::All, "All tasks".to_string()),
+            Some(i) if i < self.lists.len() => {
+                let list = &self.lists[i];
+                (Scope::List(list.id), format!("{} {}", list.icon, list.name))
+            }
+            Some(i) => match self.tags.get(i - self.lists.len()) {
+                Some(tag) => (Scope::Tag(tag.id), format!("#{}", tag.name)),
+                None => (Scope::All, "All tasks".to_string()),
+            },
+        };
+        let today = chrono::Local::now().date_naive();
+        match Burndown::load(self.db(), scope, today, BURNDOWN_DAYS) {
+            Ok(burndown) => self.stats_burndown = Some((name, burndown)),
+            Err(e) => self.set_status(format!("Error: {}", e)),
+        }
+    }
+
+    /// Move the timeline by `weeks` weeks (back if negative)
+    pub fn scroll_timeline_weeks(&mut self, weeks: i64) {
+        self.timeline_from += chrono::Duration::weeks(weeks);
+    }
+
+    /// Move the timeline a month forward or back
+    pub fn scroll_timeline_month(&mut self, forward: bool) {
+        let month = chrono::Months::new(1);
+        let moved = if forward {
+            self.timeline_from.checked_add_months(month)
+        } else {
+            self.timeline_from.checked_sub_months(month)
+        };
+        if let Some(from) = moved {
+            self.timeline_from = from;
+        }
+    }
+
+    /// Leave focus mode, keeping the cursor on the focused task if it is still shown
+    pub fn exit_focus(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(task) = self.focus_task.take()
+            && let Some(i) = self.tasks.iter().position(|t| t.id == task.id)
+        {
+            self.task_index = i;
+        }
+    }
+
+    /// Write a change to the focused task, recording it for undo
+    fn update_focus_task(
+        &mut self,
+        label: &'static str,
+        change: impl FnOnce(&mut Task),
+    ) -> Result<()> {
+        let Some(before) = self.focus_task.clone() else {
+            return Ok(());
+        };
+        let mut after = before.clone();
+        change(&mut after);
+        after.updated_at = chrono::Utc::now();
+        self.commit(UndoAction::Updated {
+            label,
+            before,
+            after,
+        })?;
+        self.reload_keeping_selection()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Toggle completion of the focused task
+    pub fn toggle_focus_task(&mut self) -> Result<()> {
+        self.update_focus_task("toggle", |task| task.toggle())
+    }
+
+    /// Checklist items of the focused task as `(line, checked)`
+    pub fn focus_checklist(&self) -> Vec<(usize, bool)> {
+        self.focus_task
+            .as_ref()
+            .and_then(|task| task.description.as_deref())
+            .map(markdown::checklist)
+            .unwrap_or_default()
+    }
+
+    /// Check or uncheck the checklist item under the focus-mode cursor
+    pub fn toggle_focus_item(&mut self) -> Result<()> {
+        let Some(task) = &self.focus_task else {
+            return Ok(());
+        };
+        let description = task.description.clone().unwrap_or_default();
+        let Some(&(line, _)) = markdown::checklist(&description).get(self.focus_item) else {
+            return Ok(());
+        };
+        let toggled = markdown::toggle_checklist_line(&description, line);
+        self.update_focus_task("check", |task| task.description = Some(toggled))
+    }
+
+    /// Start editing the selected task
     pub fn start_edit_task(&mut self) {
         if let Some(task) = self.selected_task().cloned() {
             self.mode = Mode::EditTask;
@@ -467,6 +2243,7 @@ impl AppState {
             self.editor_new_tag_buffer.clear();
             self.editor_title_buffer = task.title.clone();
             self.editor_description_buffer = task.description.clone().unwrap_or_default();
+            self.editor_url_buffer = task.url.clone().unwrap_or_default();
             self.editor_due_date_buffer = task
                 .due_date
                 .map(|dt| dt.format("%Y-%m-%d").to_string())
@@ -513,36 +2290,53 @@ impl AppState {
         };
 
         // Parse due date from buffer
-        let due_date = if self.editor_field == EditorField::DueDate {
-            Self::parse_due_date(&self.input_buffer)
-        } else {
-            Self::parse_due_date(&self.editor_due_date_buffer)
-        };
+        let due_text = self.editor_due_date_buffer.trim().to_string();
+        let due_date = crate::quickadd::parse_due_date(&due_text);
 
         if title.is_empty() {
             self.set_status("Task title cannot be empty");
             return Ok(());
         }
 
+        if !due_text.is_empty() && due_date.is_none() {
+            self.set_status(format!("Unrecognized due date '{}'", due_text));
+            return Ok(());
+        }
+
+        let url = match crate::core::TickitCore::normalize_url(&self.editor_url_buffer) {
+            Ok(url) => url,
+            Err(message) => {
+                self.set_status(message);
+                return Ok(());
+            }
+        };
+
         if let Some(mut task) = self.editing_task.take() {
             // Update existing task
+            let before = task.clone();
             task.title = title;
             task.description = description;
+            task.url = url;
             task.priority = self.editor_priority;
             task.list_id = list_id;
             task.tag_ids = tag_ids;
             task.due_date = due_date;
             task.updated_at = chrono::Utc::now();
-            self.db.update_task(&task)?;
+            self.commit(UndoAction::Updated {
+                label: "edit",
+                before,
+                after: task,
+            })?;
             self.set_status("Task updated");
         } else {
             // Create new task
             let mut task = Task::new(&title, list_id);
             task.description = description;
+            task.url = url;
             task.priority = self.editor_priority;
             task.tag_ids = tag_ids;
             task.due_date = due_date;
-            self.db.insert_task(&task)?;
+            self.commit(UndoAction::Created(task))?;
             self.set_status("Task created");
         }
 
@@ -555,13 +2349,23 @@ impl AppState {
     /// Toggle completion of the selected task
     pub fn toggle_task(&mut self) -> Result<()> {
         if let Some(task) = self.tasks.get_mut(self.task_index) {
+            let before = task.clone();
             task.toggle();
-            self.db.update_task(task)?;
             let status = if task.completed {
                 "completed"
             } else {
                 "reopened"
             };
+            let after = task.clone();
+            self.commit(UndoAction::Updated {
+                label: if after.completed {
+                    "complete"
+                } else {
+                    "reopen"
+                },
+                before,
+                after,
+            })?;
             self.set_status(format!("Task {}", status));
             self.refresh_tasks()?;
             self.mark_sync_pending();
@@ -569,14 +2373,78 @@ impl AppState {
         Ok(())
     }
 
+    /// Expand or collapse the "Completed today" section
+    pub fn toggle_completed_today(&mut self) {
+        self.config.show_completed_today = !self.config.show_completed_today;
+        let _ = self.config.save();
+    }
+
+    /// Reopen the most recently completed task from today
+    pub fn reopen_last_completed(&mut self) -> Result<()> {
+        let Some(mut task) = self.completed_today.first().cloned() else {
+            self.set_status("Nothing completed today");
+            return Ok(());
+        };
+        let before = task.clone();
+        task.toggle();
+        self.set_status(format!("Reopened \"{}\"", task.title));
+        self.commit(UndoAction::Updated {
+            label: "reopen",
+            before,
+            after: task,
+        })?;
+        self.refresh_tasks()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Copy the selected task, open and with the same details, and select the copy
+    pub fn duplicate_task(&mut self) -> Result<()> {
+        let Some(task) = self.selected_task() else {
+            return Ok(());
+        };
+        let copy = task.duplicate();
+        self.set_status(format!("Duplicated \"{}\"", copy.title));
+        let id = copy.id;
+        self.commit(UndoAction::Created(copy))?;
+        self.refresh_tasks()?;
+        if let Some(i) = self.tasks.iter().position(|t| t.id == id) {
+            self.task_index = i;
+        }
+        self.mark_sync_pending();
+        Ok(())
+    }
+
     /// Delete the selected task (with confirmation)
     pub fn confirm_delete_task(&mut self) {
         if let Some(task) = self.selected_task() {
             let title = task.title.clone();
             let id = task.id;
-            self.confirm_message = format!("Delete task \"{}\"?", title);
-            self.confirm_action = Some(ConfirmAction::DeleteTask(id));
+            self.request_confirm(
+                format!("Delete task \"{}\"?", title),
+                ConfirmAction::DeleteTask(id),
+            );
+        }
+    }
+
+    /// Ask for confirmation, or run the action right away if the config skips it
+    fn request_confirm(&mut self, message: String, action: ConfirmAction) {
+        let confirm = &self.config.confirm;
+        let required = match action {
+            ConfirmAction::DeleteTask(_) => confirm.delete_task,
+            ConfirmAction::DeleteTasks(_) | ConfirmAction::MergeTasks(_) => confirm.bulk_delete,
+            ConfirmAction::DeleteList(..) => confirm.delete_list,
+            ConfirmAction::DeleteTag(_) => confirm.delete_tag,
+            ConfirmAction::ResetList(_) => confirm.reset_list,
+            ConfirmAction::FirstSync => true,
+        };
+        self.confirm_yes = confirm.default_yes;
+        self.confirm_message = message;
+        self.confirm_action = Some(action);
+        if required {
             self.mode = Mode::Confirm;
+        } else if let Err(e) = self.execute_confirm() {
+            self.set_status(format!("Error: {}", e));
         }
     }
 
@@ -585,22 +2453,52 @@ impl AppState {
         if let Some(action) = self.confirm_action.take() {
             match action {
                 ConfirmAction::DeleteTask(id) => {
-                    self.db.delete_task(id)?;
-                    self.db.record_tombstone(id, "task")?;
+                    if let Some(task) = self.tasks.iter().find(|t| t.id == id).cloned() {
+                        self.commit(UndoAction::Deleted(task))?;
+                    }
                     self.set_status("Task deleted");
                 }
-                ConfirmAction::DeleteList(id) => {
-                    self.db.delete_list(id)?;
-                    self.db.record_tombstone(id, "list")?;
+                ConfirmAction::DeleteTasks(ids) => {
+                    let actions: Vec<UndoAction> = self
+                        .tasks
+                        .iter()
+                        .filter(|t| ids.contains(&t.id))
+                        .cloned()
+                        .map(UndoAction::Deleted)
+                        .collect();
+                    self.commit(UndoAction::Batch {
+                        label: "delete",
+                        actions,
+                    })?;
+                    self.clear_marks();
+                    self.set_status(format!("{} tasks deleted", ids.len()));
+                }
+                ConfirmAction::MergeTasks(ids) => self.merge_tasks(&ids)?,
+                ConfirmAction::DeleteList(id, tasks) => {
+                    self.db().delete_list_with(id, tasks)?;
                     self.selected_list_id = None;
                     self.list_index = 0;
                     self.set_status("List deleted");
                 }
                 ConfirmAction::DeleteTag(id) => {
-                    self.db.delete_tag(id)?;
-                    self.db.record_tombstone(id, "tag")?;
+                    self.db().delete_tag(id)?;
+                    self.db().record_tombstone(id, "tag")?;
                     self.set_status("Tag deleted");
                 }
+                ConfirmAction::ResetList(id) => {
+                    let done: Vec<Task> = self
+                        .db()
+                        .get_tasks_for_list(id)?
+                        .into_iter()
+                        .filter(|t| t.completed)
+                        .collect();
+                    let count = self.update_tasks(done, "reset", Task::uncomplete)?;
+                    self.set_status(format!("Checklist reset: {} tasks unchecked", count));
+                }
+                ConfirmAction::FirstSync => {
+                    self.first_sync_confirmed = Some(true);
+                    self.set_syncing(true);
+                }
             }
             self.mode = Mode::Normal;
             self.refresh_data()?;
@@ -611,31 +2509,72 @@ impl AppState {
 
     /// Cancel confirmation
     pub fn cancel_confirm(&mut self) {
+        if let Some(ConfirmAction::FirstSync) = self.confirm_action {
+            self.first_sync_confirmed = Some(false);
+            self.set_status("First sync cancelled; press S to review it again");
+        }
         self.confirm_action = None;
         self.mode = Mode::Normal;
     }
 
-    /// Open URL of selected task
-    pub fn open_task_url(&mut self) {
-        if let Some(task) = self.selected_task() {
-            if let Some(url) = &task.url {
-                if let Err(e) = open::that(url) {
-                    self.set_status(format!("Failed to open URL: {}", e));
-                } else {
-                    self.set_status("Opening URL in browser...");
-                }
-            } else {
-                self.set_status("Task has no URL");
+    /// Ask before the first sync of a device that already has tasks
+    pub fn confirm_first_sync(&mut self, preview: &crate::sync::SyncPreview) {
+        self.request_confirm(
+            format!("First sync: {}. Sync now?", preview.describe()),
+            ConfirmAction::FirstSync,
+        );
+    }
+
+    /// Open the selected task's link, or pick one if it has several (its
+    /// URL, and URLs and issue references in its title and description)
+    pub fn open_task_link(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        let links = crate::autolink::task_links(task, &self.config.links);
+        match links.as_slice() {
+            [] => self.set_status("Task has no links"),
+            [link] => {
+                let url = link.url.clone();
+                self.open_link(&url);
             }
+            _ => {
+                self.link_choices = links;
+                self.picker_index = 0;
+                self.mode = Mode::OpenLink;
+            }
+        }
+    }
+
+    /// Open the link picked in the link picker
+    pub fn open_picked_link(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(link) = self.link_choices.get(self.picker_index) {
+            let url = link.url.clone();
+            self.open_link(&url);
+        }
+    }
+
+    fn open_link(&mut self, url: &str) {
+        if let Err(e) = open::that(url) {
+            self.set_status(format!("Failed to open URL: {}", e));
+        } else {
+            self.set_status("Opening URL in browser...");
         }
     }
 
     /// Cycle task priority
     pub fn cycle_task_priority(&mut self) -> Result<()> {
         if let Some(task) = self.tasks.get_mut(self.task_index) {
+            let before = task.clone();
             task.priority = task.priority.next();
             task.updated_at = chrono::Utc::now();
-            self.db.update_task(task)?;
+            let after = task.clone();
+            self.commit(UndoAction::Updated {
+                label: "reprioritize",
+                before,
+                after,
+            })?;
             self.mark_sync_pending();
         }
         if let Some(task) = self.tasks.get(self.task_index) {
@@ -651,6 +2590,8 @@ impl AppState {
         self.input_buffer.clear();
         self.cursor_pos = 0;
         self.editing_list = None;
+        self.editor_list_color = None;
+        self.editor_list_checklist = false;
     }
 
     /// Start editing the selected list
@@ -664,25 +2605,35 @@ impl AppState {
             self.editor_field = EditorField::Name;
             self.input_buffer = list.name.clone();
             self.cursor_pos = self.input_buffer.len();
+            self.editor_list_color = list.color.clone();
+            self.editor_list_checklist = list.is_checklist;
             self.editing_list = Some(list);
         }
     }
 
     /// Save the current list being edited
     pub fn save_list(&mut self) -> Result<()> {
-        if self.input_buffer.is_empty() {
-            self.set_status("List name cannot be empty");
-            return Ok(());
-        }
+        let renaming = self.editing_list.as_ref().map(|l| l.id);
+        let name = match self.core.check_list_name(&self.input_buffer, renaming) {
+            Ok(name) => name,
+            Err(e) => {
+                self.set_status(e.to_string());
+                return Ok(());
+            }
+        };
 
         if let Some(mut list) = self.editing_list.take() {
-            list.name = self.input_buffer.clone();
+            list.name = name;
+            list.color = self.editor_list_color.take();
+            list.is_checklist = self.editor_list_checklist;
             list.updated_at = chrono::Utc::now();
-            self.db.update_list(&list)?;
+            self.db().update_list(&list)?;
             self.set_status("List updated");
         } else {
-            let list = List::new(&self.input_buffer);
-            self.db.insert_list(&list)?;
+            let mut list = List::new(&name);
+            list.color = self.editor_list_color.take();
+            list.is_checklist = self.editor_list_checklist;
+            self.db().insert_list(&list)?;
             self.set_status("List created");
         }
 
@@ -692,6 +2643,47 @@ impl AppState {
         Ok(())
     }
 
+    /// Step the list editor's color through the palette, with no color between
+    /// the last and the first
+    pub fn cycle_list_color(&mut self, forward: bool) {
+        let current = self
+            .editor_list_color
+            .as_deref()
+            .and_then(|c| COLORS.iter().position(|p| p.eq_ignore_ascii_case(c)));
+        let next = match (current, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(COLORS.len() - 1),
+            (Some(i), true) => (i + 1 < COLORS.len()).then_some(i + 1),
+            (Some(i), false) => i.checked_sub(1),
+        };
+        self.editor_list_color = next.map(|i| COLORS[i].to_string());
+    }
+
+    /// Uncheck every task in the selected checklist (with confirmation)
+    pub fn confirm_reset_list(&mut self) {
+        let Some(list) = self.selected_list() else {
+            return;
+        };
+        if !list.is_checklist {
+            self.set_status("Not a checklist; turn it on in the list editor (e)");
+            return;
+        }
+        let (id, name) = (list.id, list.name.clone());
+        let done = self
+            .db()
+            .get_tasks_for_list(id)
+            .map(|tasks| tasks.iter().filter(|t| t.completed).count())
+            .unwrap_or(0);
+        if done == 0 {
+            self.set_status("Nothing to reset");
+            return;
+        }
+        self.request_confirm(
+            format!("Uncheck all {} completed tasks in \"{}\"?", done, name),
+            ConfirmAction::ResetList(id),
+        );
+    }
+
     /// Confirm delete list
     pub fn confirm_delete_list(&mut self) {
         if let Some(list) = self.selected_list() {
@@ -699,15 +2691,101 @@ impl AppState {
                 self.set_status("Cannot delete inbox");
                 return;
             }
-            let name = list.name.clone();
             let id = list.id;
-            self.confirm_message =
-                format!("Delete list \"{}\"? Tasks will be moved to Inbox.", name);
-            self.confirm_action = Some(ConfirmAction::DeleteList(id));
-            self.mode = Mode::Confirm;
+            self.confirm_usage = self
+                .db()
+                .get_tasks_for_list(id)
+                .map(|tasks| tasks.len())
+                .unwrap_or(0);
+            let inbox = self.lists.iter().find(|l| l.is_inbox).map(|l| l.id);
+            let tasks = DeletedListTasks::Move(inbox.unwrap_or_default());
+            self.request_confirm(String::new(), ConfirmAction::DeleteList(id, tasks));
+            self.describe_list_delete();
         }
     }
 
+    /// Spell out what the pending list delete will do with its tasks
+    fn describe_list_delete(&mut self) {
+        let Some(ConfirmAction::DeleteList(id, tasks)) = self.confirm_action else {
+            return;
+        };
+        let list_name = |id: Uuid| {
+            self.lists
+                .iter()
+                .find(|l| l.id == id)
+                .map(|l| l.name.clone())
+                .unwrap_or_default()
+        };
+        let name = list_name(id);
+        let count = match self.confirm_usage {
+            0 => {
+                self.confirm_message = format!("Delete list \"{}\"? It has no tasks.", name);
+                return;
+            }
+            1 => "its task".to_string(),
+            n => format!("its {} tasks", n),
+        };
+        self.confirm_message = match tasks {
+            DeletedListTasks::Move(target) => format!(
+                "Delete list \"{}\" and move {} to {}?",
+                name,
+                count,
+                list_name(target)
+            ),
+            DeletedListTasks::Archive => format!("Delete list \"{}\" and archive {}?", name, count),
+            DeletedListTasks::Delete => format!("Delete list \"{}\" and {}?", name, count),
+        };
+    }
+
+    /// Change what the pending list delete does with its tasks
+    pub fn set_deleted_list_tasks(&mut self, tasks: DeletedListTasks) {
+        if let Some(ConfirmAction::DeleteList(_, current)) = &mut self.confirm_action {
+            *current = tasks;
+            // Deleting tasks is the destructive choice, so don't default to yes
+            self.confirm_yes = tasks != DeletedListTasks::Delete && self.config.confirm.default_yes;
+            self.describe_list_delete();
+        }
+    }
+
+    /// The list a pending delete would remove, if that's what is being confirmed
+    fn pending_list_delete(&self) -> Option<Uuid> {
+        match self.confirm_action {
+            Some(ConfirmAction::DeleteList(id, _)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Lists the tasks of a list being deleted can move to
+    pub fn move_list_candidates(&self) -> Vec<&List> {
+        let deleting = self.pending_list_delete();
+        self.lists
+            .iter()
+            .filter(|l| Some(l.id) != deleting)
+            .collect()
+    }
+
+    /// Open the list picker to choose where a deleted list's tasks go
+    pub fn start_move_list_tasks(&mut self) {
+        if self.pending_list_delete().is_none() || self.confirm_usage == 0 {
+            return;
+        }
+        self.picker_index = 0;
+        self.mode = Mode::MoveListTasks;
+    }
+
+    /// Send the deleted list's tasks to the list under the picker cursor and
+    /// return to the confirmation
+    pub fn pick_deleted_list_target(&mut self) {
+        if let Some(target) = self
+            .move_list_candidates()
+            .get(self.picker_index)
+            .map(|l| l.id)
+        {
+            self.set_deleted_list_tasks(DeletedListTasks::Move(target));
+        }
+        self.mode = Mode::Confirm;
+    }
+
     /// Start adding a new tag
     pub fn start_add_tag(&mut self) {
         self.mode = Mode::AddTag;
@@ -728,39 +2806,136 @@ impl AppState {
         }
     }
 
-    /// Save the current tag being edited
-    pub fn save_tag(&mut self) -> Result<()> {
-        if self.input_buffer.is_empty() {
-            self.set_status("Tag name cannot be empty");
+    /// Save the current tag being edited
+    pub fn save_tag(&mut self) -> Result<()> {
+        if self.input_buffer.is_empty() {
+            self.set_status("Tag name cannot be empty");
+            return Ok(());
+        }
+
+        if let Some(mut tag) = self.editing_tag.take() {
+            tag.name = self.input_buffer.clone();
+            tag.touch(); // Update the updated_at timestamp
+            self.db().update_tag(&tag)?;
+            self.set_status("Tag updated");
+        } else if let Err(e) = self.core.create_tag(&self.input_buffer, None) {
+            self.set_status(e.to_string());
+            return Ok(());
+        } else {
+            self.set_status("Tag created");
+        }
+
+        self.mode = Mode::Normal;
+        self.refresh_data()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Confirm delete tag, saying how many tasks carry it
+    pub fn confirm_delete_tag(&mut self) {
+        if let Some(tag) = self.selected_tag() {
+            let name = tag.name.clone();
+            let id = tag.id;
+            let usage = self
+                .db()
+                .get_tasks_with_filter(None, None, Some(id))
+                .map(|tasks| tasks.len())
+                .unwrap_or(0);
+            self.confirm_usage = usage;
+            let message = match usage {
+                0 => format!("Delete tag \"{}\"? No tasks use it.", name),
+                1 => format!("Delete tag \"{}\"? It will be removed from 1 task.", name),
+                n => format!(
+                    "Delete tag \"{}\"? It will be removed from {} tasks.",
+                    name, n
+                ),
+            };
+            self.request_confirm(message, ConfirmAction::DeleteTag(id));
+        }
+    }
+
+    /// The tag a pending delete would remove, if that's what is being confirmed
+    fn pending_tag_delete(&self) -> Option<Uuid> {
+        match self.confirm_action {
+            Some(ConfirmAction::DeleteTag(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Tags the tasks of a tag being deleted can move to
+    pub fn reassign_candidates(&self) -> Vec<&Tag> {
+        let deleting = self.pending_tag_delete();
+        self.tags
+            .iter()
+            .filter(|t| Some(t.id) != deleting)
+            .collect()
+    }
+
+    /// Open the tag picker to move a deleted tag's tasks to another tag
+    pub fn start_reassign_tag(&mut self) {
+        if self.pending_tag_delete().is_none() || self.confirm_usage == 0 {
+            return;
+        }
+        if self.reassign_candidates().is_empty() {
+            self.set_status("No other tags to move the tasks to");
+            return;
+        }
+        self.picker_index = 0;
+        self.mode = Mode::ReassignTag;
+    }
+
+    /// Delete the pending tag, moving its tasks to the tag under the picker cursor
+    pub fn reassign_deleted_tag(&mut self) -> Result<()> {
+        let Some(from) = self.pending_tag_delete() else {
             return Ok(());
-        }
-
-        if let Some(mut tag) = self.editing_tag.take() {
-            tag.name = self.input_buffer.clone();
-            tag.touch(); // Update the updated_at timestamp
-            self.db.update_tag(&tag)?;
-            self.set_status("Tag updated");
-        } else {
-            let tag = Tag::new(&self.input_buffer);
-            self.db.insert_tag(&tag)?;
-            self.set_status("Tag created");
-        }
-
+        };
+        let Some(into) = self
+            .reassign_candidates()
+            .get(self.picker_index)
+            .map(|t| (*t).clone())
+        else {
+            return Ok(());
+        };
+        self.confirm_action = None;
+        self.db().merge_tag(from, into.id)?;
+        self.set_status(format!(
+            "Tag deleted; {} tasks moved to {}",
+            self.confirm_usage, into.name
+        ));
         self.mode = Mode::Normal;
         self.refresh_data()?;
         self.mark_sync_pending();
         Ok(())
     }
 
-    /// Confirm delete tag
-    pub fn confirm_delete_tag(&mut self) {
-        if let Some(tag) = self.selected_tag() {
-            let name = tag.name.clone();
-            let id = tag.id;
-            self.confirm_message = format!("Delete tag \"{}\"?", name);
-            self.confirm_action = Some(ConfirmAction::DeleteTag(id));
-            self.mode = Mode::Confirm;
-        }
+    /// Save the tasks of a tag being deleted to a Markdown file in the home
+    /// directory, keeping the confirmation open
+    pub fn export_deleted_tag_tasks(&mut self) -> Result<()> {
+        let Some(id) = self.pending_tag_delete() else {
+            return Ok(());
+        };
+        let Some(tag) = self.tags.iter().find(|t| t.id == id) else {
+            return Ok(());
+        };
+        let tasks = self.db().get_tasks_with_filter(None, None, Some(id))?;
+        let dir = dirs::home_dir().unwrap_or_default();
+        let path = dir.join(format!("tickit-{}-tasks.md", tag.name.replace('/', "-")));
+        let mut file = std::fs::File::create(&path)?;
+        crate::export::export_tasks(
+            &mut file,
+            &tasks,
+            &self.lists,
+            &self.tags,
+            &self.db().get_all_comments()?,
+            ExportFormat::Markdown,
+            &self.config,
+        )?;
+        self.set_status(format!(
+            "Exported {} tasks to {}",
+            tasks.len(),
+            path.display()
+        ));
+        Ok(())
     }
 
     /// Change theme
@@ -769,6 +2944,20 @@ impl AppState {
         self.config.theme = theme;
     }
 
+    /// Switch to a config that was changed outside the TUI
+    pub fn apply_config(&mut self, config: Config) {
+        self.theme = config.theme;
+        self.theme_index = Theme::all()
+            .iter()
+            .position(|t| *t == self.theme.inner())
+            .unwrap_or(0);
+        self.show_completed = config.show_completed;
+        self.core.set_config(config.clone());
+        self.config = config;
+        let _ = self.reload_keeping_selection();
+        self.set_status("Config reloaded");
+    }
+
     /// Toggle tag selection at current cursor in task editor
     pub fn toggle_editor_tag(&mut self) {
         if self.tags.is_empty() {
@@ -812,11 +3001,16 @@ impl AppState {
             return Ok(());
         }
 
-        let tag = Tag::new(&self.editor_new_tag_buffer);
-        self.db.insert_tag(&tag)?;
+        let tag = match self.core.create_tag(&self.editor_new_tag_buffer, None) {
+            Ok(tag) => tag,
+            Err(e) => {
+                self.set_status(e.to_string());
+                return Ok(());
+            }
+        };
 
         // Refresh tags and select the new one
-        self.tags = self.db.get_tags()?;
+        self.tags = self.db().get_tags()?;
         if let Some(idx) = self.tags.iter().position(|t| t.id == tag.id) {
             self.editor_tag_indices.push(idx);
             self.editor_tag_cursor = idx;
@@ -841,7 +3035,8 @@ impl AppState {
 
         self.editor_field = match self.editor_field {
             EditorField::Title => EditorField::Description,
-            EditorField::Description => EditorField::DueDate,
+            EditorField::Description => EditorField::Url,
+            EditorField::Url => EditorField::DueDate,
             EditorField::DueDate => EditorField::Priority,
             EditorField::Priority => EditorField::List,
             EditorField::List => EditorField::Tags,
@@ -858,7 +3053,8 @@ impl AppState {
         self.editor_field = match self.editor_field {
             EditorField::Title => EditorField::Tags,
             EditorField::Description => EditorField::Title,
-            EditorField::DueDate => EditorField::Description,
+            EditorField::Url => EditorField::Description,
+            EditorField::DueDate => EditorField::Url,
             EditorField::Priority => EditorField::DueDate,
             EditorField::List => EditorField::Priority,
             EditorField::Tags => EditorField::List,
@@ -876,6 +3072,9 @@ impl AppState {
             EditorField::Description => {
                 self.editor_description_buffer = self.input_buffer.clone();
             }
+            EditorField::Url => {
+                self.editor_url_buffer = self.input_buffer.clone();
+            }
             EditorField::DueDate => {
                 self.editor_due_date_buffer = self.input_buffer.clone();
             }
@@ -888,21 +3087,58 @@ impl AppState {
         self.input_buffer = match self.editor_field {
             EditorField::Title => self.editor_title_buffer.clone(),
             EditorField::Description => self.editor_description_buffer.clone(),
+            EditorField::Url => self.editor_url_buffer.clone(),
             EditorField::DueDate => self.editor_due_date_buffer.clone(),
             _ => String::new(),
         };
         self.cursor_pos = self.input_buffer.len();
     }
 
-    /// Parse a due date string (YYYY-MM-DD format) into a DateTime
-    fn parse_due_date(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-        if s.trim().is_empty() {
-            return None;
+    /// Request editing the task description in $EDITOR (handled by the main loop)
+    pub fn start_external_description_edit(&mut self) {
+        self.save_current_field_to_buffer();
+        self.editor_field = EditorField::Description;
+        self.update_input_buffer_for_field();
+        self.pending_external_edit = true;
+    }
+
+    /// Replace the description with text returned from $EDITOR
+    pub fn finish_external_description_edit(&mut self, result: Result<String>) {
+        match result {
+            Ok(text) => {
+                self.input_buffer = text.trim_end().to_string();
+                self.cursor_pos = self.input_buffer.len();
+                self.editor_description_buffer = self.input_buffer.clone();
+                self.set_status("Description updated from editor");
+            }
+            Err(e) => self.set_status(format!("Editor failed: {}", e)),
         }
-        // Parse YYYY-MM-DD format
-        chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
-            .ok()
-            .map(|date| date.and_hms_opt(23, 59, 59).unwrap().and_utc())
+    }
+
+    /// Date shown in the editor's date picker: the typed date, if it parses
+    pub fn editor_due_date(&self) -> Option<chrono::NaiveDate> {
+        let text = if self.editor_field == EditorField::DueDate {
+            &self.input_buffer
+        } else {
+            &self.editor_due_date_buffer
+        };
+        crate::quickadd::parse_due_date(text).map(|d| d.date_naive())
+    }
+
+    /// Set the due date field from the date picker (`None` clears it)
+    pub fn set_editor_due_date(&mut self, date: Option<chrono::NaiveDate>) {
+        self.input_buffer = date
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        self.cursor_pos = self.input_buffer.len();
+    }
+
+    /// Move the picked due date by a number of days, starting from today if unset
+    pub fn shift_editor_due_date(&mut self, days: i64) {
+        let base = self
+            .editor_due_date()
+            .unwrap_or_else(|| chrono::Local::now().date_naive());
+        self.set_editor_due_date(Some(base + chrono::Duration::days(days)));
     }
 
     /// Set update available from background check
@@ -916,18 +3152,666 @@ impl AppState {
         self.set_status("Updating...");
     }
 
+    /// Show a line of output from the running update
+    pub fn update_progress(&mut self, line: String) {
+        self.set_status(format!("⬆ {}", line));
+        self.update_result = Some(line);
+    }
+
+    /// Record the outcome of a finished update
+    pub fn finish_update(&mut self, result: std::result::Result<(), String>) {
+        self.update_cancel = None;
+        let message = match result {
+            Ok(()) => {
+                self.update_available = None;
+                "Update complete! Please restart tickit.".to_string()
+            }
+            Err(e) => format!("Update failed: {}", e),
+        };
+        self.set_status(message.clone());
+        self.update_result = Some(message);
+        if self.mode == Mode::Updating {
+            self.mode = Mode::Normal;
+        }
+    }
+
+    /// Ask the running update to stop
+    pub fn cancel_update(&mut self) {
+        if let Some(cancel) = &self.update_cancel {
+            cancel.store(true, Ordering::Relaxed);
+            self.update_result = Some("Cancelling update...".to_string());
+        }
+    }
+
     /// Dismiss the update notification
     pub fn dismiss_update(&mut self) {
         self.update_available = None;
     }
 
+    // ==================== Multi-select ====================
+
+    /// Toggle the mark on the selected task
+    pub fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_task().map(|t| t.id) {
+            if let Some(pos) = self.marked_tasks.iter().position(|m| *m == id) {
+                self.marked_tasks.remove(pos);
+            } else {
+                self.marked_tasks.push(id);
+            }
+            self.mark_anchor = Some(id);
+        }
+    }
+
+    /// Mark every task between the anchor and the selected task
+    pub fn mark_range(&mut self) {
+        if self.tasks.is_empty() {
+            return;
+        }
+        // Found by ID, since sorting or a refresh may have moved the task
+        let anchor = self
+            .mark_anchor
+            .and_then(|id| self.tasks.iter().position(|t| t.id == id));
+        let Some(anchor) = anchor else {
+            self.toggle_mark();
+            return;
+        };
+        let (start, end) = if anchor <= self.task_index {
+            (anchor, self.task_index)
+        } else {
+            (self.task_index, anchor)
+        };
+        for task in &self.tasks[start..=end.min(self.tasks.len() - 1)] {
+            if !self.marked_tasks.contains(&task.id) {
+                self.marked_tasks.push(task.id);
+            }
+        }
+        self.mark_anchor = self.selected_task().map(|t| t.id);
+    }
+
+    /// Clear all marks
+    pub fn clear_marks(&mut self) {
+        self.marked_tasks.clear();
+        self.mark_anchor = None;
+    }
+
+    /// Check whether a task is marked
+    pub fn is_marked(&self, id: Uuid) -> bool {
+        self.marked_tasks.contains(&id)
+    }
+
+    /// Get the marked tasks in display order
+    fn marked(&self) -> Vec<Task> {
+        self.tasks
+            .iter()
+            .filter(|t| self.marked_tasks.contains(&t.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Apply a change to every marked task in one transaction and record it for undo
+    fn update_marked(&mut self, label: &'static str, change: impl Fn(&mut Task)) -> Result<usize> {
+        let ids = self.marked_tasks.clone();
+        self.update_task_set(&ids, label, change)
+    }
+
+    /// Apply a change to the given loaded tasks in one transaction and record it for undo
+    fn update_task_set(
+        &mut self,
+        ids: &[Uuid],
+        label: &'static str,
+        change: impl Fn(&mut Task),
+    ) -> Result<usize> {
+        let before: Vec<Task> = self
+            .tasks
+            .iter()
+            .filter(|t| ids.contains(&t.id))
+            .cloned()
+            .collect();
+        self.update_tasks(before, label, change)
+    }
+
+    /// Apply a change to `before` in one transaction and record it for undo
+    fn update_tasks(
+        &mut self,
+        before: Vec<Task>,
+        label: &'static str,
+        change: impl Fn(&mut Task),
+    ) -> Result<usize> {
+        let after: Vec<Task> = before
+            .iter()
+            .cloned()
+            .map(|mut task| {
+                change(&mut task);
+                task.updated_at = chrono::Utc::now();
+                task
+            })
+            .collect();
+
+        let count = after.len();
+        self.commit(UndoAction::Batch {
+            label,
+            actions: before
+                .into_iter()
+                .zip(after)
+                .map(|(before, after)| UndoAction::Updated {
+                    label,
+                    before,
+                    after,
+                })
+                .collect(),
+        })?;
+        self.mark_sync_pending();
+        Ok(count)
+    }
+
+    /// Complete all marked tasks (or reopen them if they are all completed)
+    pub fn toggle_marked(&mut self) -> Result<()> {
+        let complete = self.marked().iter().any(|t| !t.completed);
+        let count = if complete {
+            self.update_marked("complete", |t| {
+                if !t.completed {
+                    t.complete();
+                }
+            })?
+        } else {
+            self.update_marked("reopen", Task::uncomplete)?
+        };
+        self.clear_marks();
+        let status = if complete { "completed" } else { "reopened" };
+        self.set_status(format!("{} tasks {}", count, status));
+        self.refresh_tasks()
+    }
+
+    /// Set all marked tasks to the priority after the first marked task's
+    pub fn cycle_marked_priority(&mut self) -> Result<()> {
+        let Some(priority) = self.marked().first().map(|t| t.priority.next()) else {
+            return Ok(());
+        };
+        let count = self.update_marked("reprioritize", |t| t.priority = priority)?;
+        self.set_status(format!("Priority: {} ({} tasks)", priority.name(), count));
+        self.refresh_tasks()
+    }
+
+    /// Turn nagging (reminders every few hours while overdue) on or off for
+    /// the marked tasks, or the selected task, following the first of them
+    pub fn toggle_nag(&mut self) -> Result<()> {
+        let tasks = self.marked_or_selected();
+        let Some(first) = tasks.first() else {
+            return Ok(());
+        };
+        let nag = first.nag_hours.is_none().then_some(0);
+        let count = self.update_tasks(tasks, "nag", |t| t.nag_hours = nag)?;
+        let hours = self.config.notifications.nag_hours;
+        self.set_status(match (nag, count) {
+            (Some(_), 1) => format!("Reminding every {} hours while overdue", hours),
+            (Some(_), _) => format!("{} tasks remind every {} hours while overdue", count, hours),
+            (None, 1) => "Reminding once a day while overdue".to_string(),
+            (None, _) => format!("{} tasks remind once a day while overdue", count),
+        });
+        self.clear_marks();
+        self.refresh_tasks()
+    }
+
+    /// Ask for confirmation before deleting all marked tasks
+    pub fn confirm_delete_marked(&mut self) {
+        let ids: Vec<Uuid> = self.marked().iter().map(|t| t.id).collect();
+        if ids.is_empty() {
+            return;
+        }
+        self.request_confirm(
+            format!("Delete {} marked tasks?", ids.len()),
+            ConfirmAction::DeleteTasks(ids),
+        );
+    }
+
+    /// Ask for confirmation before merging the marked tasks into the oldest one
+    pub fn confirm_merge_marked(&mut self) {
+        let mut marked = self.marked();
+        if marked.len() < 2 {
+            self.set_status("Mark the tasks to merge with v first");
+            return;
+        }
+        marked.sort_by_key(|t| t.created_at);
+        self.request_confirm(
+            format!(
+                "Merge {} marked tasks into \"{}\"?",
+                marked.len(),
+                marked[0].title
+            ),
+            ConfirmAction::MergeTasks(marked.iter().map(|t| t.id).collect()),
+        );
+    }
+
+    /// Fold the tasks after the first of `ids` into it and delete them
+    fn merge_tasks(&mut self, ids: &[Uuid]) -> Result<()> {
+        let tasks: Vec<Task> = ids
+            .iter()
+            .filter_map(|id| self.tasks.iter().find(|t| t.id == *id).cloned())
+            .collect();
+        let Some((before, others)) = tasks.split_first() else {
+            return Ok(());
+        };
+        let merged = self.core.merge(before.clone(), others)?;
+        let mut actions = vec![UndoAction::Updated {
+            label: "merge",
+            before: before.clone(),
+            after: merged.clone(),
+        }];
+        actions.extend(others.iter().cloned().map(UndoAction::Deleted));
+        self.record_undo(UndoAction::Batch {
+            label: "merge",
+            actions,
+        });
+        self.clear_marks();
+        self.set_status(format!(
+            "Merged {} tasks into \"{}\"",
+            tasks.len(),
+            merged.title
+        ));
+        Ok(())
+    }
+
+    /// Open the list picker to move the marked tasks, or the selected task
+    pub fn start_move_tasks(&mut self) {
+        self.move_targets = if self.marked_tasks.is_empty() {
+            self.selected_task().map(|t| t.id).into_iter().collect()
+        } else {
+            self.marked_tasks.clone()
+        };
+        self.open_move_picker();
+    }
+
+    /// Cut the marked tasks, or the selected task, to paste into another
+    /// list (or place, in manual sort) with Ctrl+v
+    pub fn cut_tasks(&mut self) {
+        let ids: Vec<Uuid> = if self.marked_tasks.is_empty() {
+            self.selected_task().map(|t| t.id).into_iter().collect()
+        } else {
+            self.marked_tasks.clone()
+        };
+        if ids.is_empty() {
+            self.set_status("No tasks to cut");
+            return;
+        }
+        self.set_status(format!(
+            "Cut {} task(s): go to where they belong and press Ctrl+v (Esc to cancel)",
+            ids.len()
+        ));
+        self.cut_tasks = ids;
+        self.clear_marks();
+    }
+
+    /// Forget the cut tasks without moving them
+    pub fn cancel_cut(&mut self) {
+        self.cut_tasks.clear();
+        self.set_status("Cut cancelled");
+    }
+
+    /// Paste the cut tasks into the selected task's list, right after it in
+    /// manual sort and at the end otherwise; into the list being viewed if
+    /// it's empty
+    pub fn paste_tasks(&mut self) -> Result<()> {
+        if self.cut_tasks.is_empty() {
+            self.set_status("Nothing to paste: cut tasks with Ctrl+x first");
+            return Ok(());
+        }
+        let anchor = self
+            .selected_task()
+            .filter(|t| !self.cut_tasks.contains(&t.id))
+            .cloned();
+        let Some(list_id) = anchor.as_ref().map(|t| t.list_id).or(self
+            .selected_list_id
+            .filter(|_| self.active_smart.is_none()))
+        else {
+            self.set_status("Go to a list, or a task in it, to paste");
+            return Ok(());
+        };
+        let list_name = self
+            .lists
+            .iter()
+            .find(|l| l.id == list_id)
+            .map_or_else(String::new, |l| l.name.clone());
+
+        // Tasks deleted since they were cut are skipped
+        let cut: Vec<Task> = std::mem::take(&mut self.cut_tasks)
+            .into_iter()
+            .filter_map(|id| self.db().get_task(id).ok())
+            .collect();
+        let mut ordered = self.db().get_tasks_for_list(list_id)?;
+        TaskSort::Manual.apply(&mut ordered);
+        ordered.retain(|t| !cut.iter().any(|c| c.id == t.id));
+        let manual = self.task_sort() == TaskSort::Manual;
+        let at = match &anchor {
+            Some(anchor) if manual => ordered
+                .iter()
+                .position(|t| t.id == anchor.id)
+                .map_or(ordered.len(), |i| i + 1),
+            _ => ordered.len(),
+        };
+        let first = ordered.iter().map(|t| t.sort_order + 1).max().unwrap_or(0);
+        let cut_ids: Vec<Uuid> = cut.iter().map(|t| t.id).collect();
+        let count = cut.len();
+        ordered.splice(at..at, cut);
+
+        let now = chrono::Utc::now();
+        let (before, after): (Vec<Task>, Vec<Task>) = ordered
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, t)| {
+                let is_cut = cut_ids.contains(&t.id);
+                // Out of manual sort, the rest of the list keeps its order
+                let sort_order = match (manual, is_cut) {
+                    (true, _) => i as i32,
+                    (false, true) => first + (i - at) as i32,
+                    (false, false) => t.sort_order,
+                };
+                if t.list_id == list_id && t.sort_order == sort_order {
+                    return None;
+                }
+                let mut updated = t.clone();
+                updated.list_id = list_id;
+                updated.sort_order = sort_order;
+                updated.updated_at = now;
+                Some((t, updated))
+            })
+            .unzip();
+        self.commit(UndoAction::Batch {
+            label: "paste",
+            actions: before
+                .into_iter()
+                .zip(after)
+                .map(|(before, after)| UndoAction::Updated {
+                    label: "paste",
+                    before,
+                    after,
+                })
+                .collect(),
+        })?;
+        self.mark_sync_pending();
+
+        self.refresh_data()?;
+        if let Some(i) = self.tasks.iter().position(|t| cut_ids.contains(&t.id)) {
+            self.task_index = i;
+        }
+        self.set_status(format!("Pasted {} task(s) into {}", count, list_name));
+        Ok(())
+    }
+
+    /// Open the list picker to move every task currently shown
+    pub fn start_move_all_tasks(&mut self) {
+        self.move_targets = self.tasks.iter().map(|t| t.id).collect();
+        self.open_move_picker();
+    }
+
+    fn open_move_picker(&mut self) {
+        if self.move_targets.is_empty() {
+            self.set_status("No tasks to move");
+            return;
+        }
+        // Start on the list of the first task so Enter is a no-op by default
+        let current_list = self
+            .tasks
+            .iter()
+            .find(|t| self.move_targets.contains(&t.id))
+            .map(|t| t.list_id);
+        self.picker_index = self
+            .lists
+            .iter()
+            .position(|l| Some(l.id) == current_list)
+            .unwrap_or(0);
+        self.mode = Mode::MoveTasks;
+    }
+
+    /// Move the picker's target tasks to the list under the picker cursor
+    pub fn move_tasks_to_picked_list(&mut self) -> Result<()> {
+        let Some(list) = self.lists.get(self.picker_index).cloned() else {
+            return Ok(());
+        };
+        let targets = std::mem::take(&mut self.move_targets);
+        let count = self.update_task_set(&targets, "move", |t| t.list_id = list.id)?;
+        self.clear_marks();
+        self.mode = Mode::Normal;
+        if count == 1 {
+            self.set_status(format!("Moved task to {}", list.name));
+        } else {
+            self.set_status(format!("Moved {} tasks to {}", count, list.name));
+        }
+        self.refresh_data()
+    }
+
+    /// Open the tag picker to retag marked tasks
+    pub fn start_tag_marked(&mut self) {
+        if self.marked_tasks.is_empty() {
+            self.set_status("Mark tasks with v first");
+            return;
+        }
+        if self.tags.is_empty() {
+            self.set_status("No tags yet");
+            return;
+        }
+        self.picker_index = 0;
+        self.mode = Mode::TagTasks;
+    }
+
+    /// Count how many marked tasks carry the given tag
+    pub fn marked_with_tag(&self, tag_id: Uuid) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| self.marked_tasks.contains(&t.id) && t.tag_ids.contains(&tag_id))
+            .count()
+    }
+
+    /// Toggle the tag under the picker cursor on all marked tasks
+    ///
+    /// If every marked task already has the tag it is removed, otherwise it is added.
+    pub fn toggle_picked_tag_on_marked(&mut self) -> Result<()> {
+        let Some(tag) = self.tags.get(self.picker_index).cloned() else {
+            return Ok(());
+        };
+        let remove = self.marked_with_tag(tag.id) == self.marked_tasks.len();
+        let count = self.update_marked("retag", |t| {
+            if remove {
+                t.tag_ids.retain(|id| *id != tag.id);
+            } else if !t.tag_ids.contains(&tag.id) {
+                t.tag_ids.push(tag.id);
+            }
+        })?;
+        let verb = if remove { "Removed" } else { "Added" };
+        self.set_status(format!("{} '{}' on {} tasks", verb, tag.name, count));
+        self.refresh_tasks()
+    }
+
+    // ==================== Undo ====================
+
+    /// Save a task change through the core, so the undo journal and hooks
+    /// see it as they would the same change from the CLI, and record it
+    fn commit(&mut self, action: UndoAction) -> Result<()> {
+        let (before, after) = action.sides();
+        self.core.save(&action.description(), &before, &after)?;
+        self.record_undo(action);
+        Ok(())
+    }
+
+    /// Record an operation in the undo history (clears the redo history)
+    ///
+    /// Every task change goes through here, so this is where the TUI closes
+    /// the linked issues of completed tasks.
+    pub fn record_undo(&mut self, action: UndoAction) {
+        self.core.close_issues_in_background(action.completed());
+        self.undo_stack.push(action);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Undo the most recent task operation
+    pub fn undo(&mut self) -> Result<()> {
+        let Some(action) = self.undo_stack.pop() else {
+            self.set_status("Nothing to undo");
+            return Ok(());
+        };
+
+        self.apply_undo(&action)?;
+
+        self.set_status(format!("Undo: {}", action.description()));
+        self.redo_stack.push(action);
+        self.refresh_data()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Redo the most recently undone task operation
+    pub fn redo(&mut self) -> Result<()> {
+        let Some(action) = self.redo_stack.pop() else {
+            self.set_status("Nothing to redo");
+            return Ok(());
+        };
+
+        self.apply_redo(&action)?;
+
+        self.set_status(format!("Redo: {}", action.description()));
+        self.undo_stack.push(action);
+        self.refresh_data()?;
+        self.mark_sync_pending();
+        Ok(())
+    }
+
+    /// Revert the effect of an operation on the database
+    fn apply_undo(&mut self, action: &UndoAction) -> Result<()> {
+        match action {
+            UndoAction::Created(task) => self.remove_task_for_undo(task),
+            UndoAction::Updated { before, .. } => self.db().update_task(before),
+            UndoAction::Deleted(task) => self.restore_task_for_undo(task),
+            UndoAction::Batch { actions, .. } => {
+                for action in actions.iter().rev() {
+                    self.apply_undo(action)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Re-apply the effect of an operation on the database
+    fn apply_redo(&mut self, action: &UndoAction) -> Result<()> {
+        match action {
+            UndoAction::Created(task) => self.restore_task_for_undo(task),
+            UndoAction::Updated { after, .. } => self.db().update_task(after),
+            UndoAction::Deleted(task) => self.remove_task_for_undo(task),
+            UndoAction::Batch { actions, .. } => {
+                for action in actions {
+                    self.apply_redo(action)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Re-insert a task that was deleted, dropping its pending tombstone
+    fn restore_task_for_undo(&mut self, task: &Task) -> Result<()> {
+        let mut task = task.clone();
+        task.updated_at = chrono::Utc::now();
+        self.db().insert_task(&task)?;
+        self.db().remove_tombstone(task.id)?;
+        Ok(())
+    }
+
+    /// Delete a task as part of undo/redo, recording a tombstone for sync
+    fn remove_task_for_undo(&mut self, task: &Task) -> Result<()> {
+        self.db().delete_task(task.id)?;
+        self.db().record_tombstone(task.id, "task")?;
+        Ok(())
+    }
+
     // ==================== Sync ====================
 
     /// Check if sync is enabled and configured
     pub fn is_sync_enabled(&self) -> bool {
-        self.config.sync.enabled
+        !self.db().is_in_memory()
+            && self.workspace == crate::config::DEFAULT_WORKSPACE
+            && self.config.sync.enabled
             && self.config.sync.server.is_some()
-            && self.config.sync.token.is_some()
+            && self.config.sync.has_token()
+    }
+
+    /// Sync now, or with `force` send and fetch everything
+    pub fn request_sync(&mut self, force: bool) {
+        if !self.is_sync_enabled() {
+            self.set_status("Sync not configured");
+            return;
+        }
+        // Show the preview of a first sync again if it was cancelled
+        if self.first_sync_confirmed == Some(false) {
+            self.first_sync_confirmed = None;
+        }
+        self.sync_force |= force;
+        self.sync_retry_at = None;
+        self.set_syncing(true);
+    }
+
+    /// Record a failed background sync and, while auto-sync is on and
+    /// retries are left, when to try again
+    pub fn sync_failed(&mut self, error: String) {
+        self.set_sync_error(Some(error));
+        self.sync_failures += 1;
+        let auto_sync = self.config.sync.interval_secs > 0 && !self.auto_sync_paused();
+        self.sync_retry_at = (auto_sync && self.sync_failures <= crate::sync::MAX_SYNC_RETRIES)
+            .then(|| std::time::Instant::now() + crate::sync::retry_delay(self.sync_failures));
+    }
+
+    /// Whether a failed sync is due for its retry
+    pub fn sync_retry_due(&self) -> bool {
+        !self.auto_sync_paused()
+            && self
+                .sync_retry_at
+                .is_some_and(|at| std::time::Instant::now() >= at)
+    }
+
+    /// Open the sync panel
+    pub fn open_sync_panel(&mut self) {
+        if self.sync_device.is_none() {
+            self.sync_device = Some((
+                crate::sync::SyncClient::get_or_create_device_id(),
+                crate::sync::SyncClient::device_name(),
+            ));
+        }
+        self.refresh_pending_changes();
+        self.mode = Mode::SyncPanel;
+    }
+
+    /// Whether auto-sync is paused, for this session or until resumed
+    pub fn auto_sync_paused(&self) -> bool {
+        self.sync_paused || self.sync_paused_saved
+    }
+
+    /// Pause or resume auto-sync for this session
+    pub fn toggle_sync_pause(&mut self) {
+        self.sync_paused = !self.sync_paused;
+        self.set_status(if self.sync_paused {
+            "Auto-sync paused for this session"
+        } else if self.sync_paused_saved {
+            "Auto-sync still paused until resumed (P)"
+        } else {
+            "Auto-sync resumed"
+        });
+    }
+
+    /// Pause or resume auto-sync until toggled again, here or with
+    /// `tickit sync pause`/`resume`
+    pub fn toggle_saved_sync_pause(&mut self) {
+        let paused = !self.sync_paused_saved;
+        if let Err(e) = self.db().set_sync_paused(paused) {
+            self.set_status(format!("Error: {}", e));
+            return;
+        }
+        self.sync_paused_saved = paused;
+        self.set_status(if paused {
+            "Auto-sync paused until resumed; Ctrl+S still syncs"
+        } else if self.sync_paused {
+            "Auto-sync still paused for this session (p in the sync panel)"
+        } else {
+            "Auto-sync resumed"
+        });
     }
 
     /// Update sync status
@@ -938,25 +3822,45 @@ impl AppState {
     /// Mark sync as in progress
     pub fn set_syncing(&mut self, syncing: bool) {
         self.sync_status.syncing = syncing;
+        self.sync_status.progress = None;
     }
 
     /// Set sync error
     pub fn set_sync_error(&mut self, error: Option<String>) {
         self.sync_status.last_error = error;
         self.sync_status.syncing = false;
+        self.sync_status.progress = None;
     }
 
     /// Set last sync time
     pub fn set_last_sync(&mut self, time: chrono::DateTime<chrono::Utc>) {
         self.sync_status.last_sync = Some(time);
         self.sync_status.last_error = None;
+        self.sync_failures = 0;
+        self.sync_retry_at = None;
         self.sync_status.syncing = false;
+        self.sync_status.progress = None;
+        self.refresh_pending_changes();
     }
 
     /// Mark that data has changed and sync is needed
     pub fn mark_sync_pending(&mut self) {
         if self.is_sync_enabled() {
             self.sync_pending = true;
+            self.refresh_pending_changes();
+        }
+    }
+
+    /// Recount local changes that have not been synced yet
+    pub fn refresh_pending_changes(&mut self) {
+        if self.is_sync_enabled() {
+            let since = self.db().get_last_sync().ok().flatten();
+            self.sync_status.pending_changes = self.db().count_changes_since(since).unwrap_or(0);
         }
     }
 }
+
+/// sync_state key remembering that a warning was dismissed
+fn dismissed_warning_key(warning: &crate::health::Warning) -> String {
+    format!("dismissed_warning:{}", warning.id)
+}
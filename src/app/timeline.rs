@@ -0,0 +1,132 @@
+//! Timeline of dated tasks, grouped by list
+//!
+//! Each open task with a due date is a bar ending on that date. Tasks have no
+//! start date, so a bar starts on the day the task was created, or on its
+//! due date if it was created later. The window scrolls a week or a month
+//! at a time.
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+
+use crate::models::{List, Task};
+
+/// A line of the timeline
+#[derive(Debug, Clone)]
+pub enum Row {
+    /// A list heading, with its icon
+    List(String),
+    /// A task's bar, from `start` to `end` inclusive
+    Task {
+        task: Task,
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+}
+
+/// The rows for `tasks`: open tasks with a due date, by list in list order,
+/// earliest due first
+///
+/// Creation is a moment, shown on its local day; a due date is stored as the
+/// last second of its day in UTC, so it's read as it is.
+pub fn rows(tasks: &[Task], lists: &[List]) -> Vec<Row> {
+    let local = |at: chrono::DateTime<chrono::Utc>| at.with_timezone(&Local).date_naive();
+    let mut rows = Vec::new();
+    for list in lists {
+        let mut dated: Vec<_> = tasks
+            .iter()
+            .filter(|t| t.list_id == list.id && !t.completed)
+            .filter_map(|t| Some((t, t.due_date?.date_naive())))
+            .collect();
+        if dated.is_empty() {
+            continue;
+        }
+        dated.sort_by_key(|(t, end)| (*end, local(t.created_at)));
+        rows.push(Row::List(format!("{} {}", list.icon, list.name)));
+        rows.extend(dated.into_iter().map(|(task, end)| Row::Task {
+            task: task.clone(),
+            start: local(task.created_at).min(end),
+            end,
+        }));
+    }
+    rows
+}
+
+/// Columns `start..=end` covers in a window of `days` days from `from`, or
+/// None if the bar is outside it
+pub fn bar(
+    start: NaiveDate,
+    end: NaiveDate,
+    from: NaiveDate,
+    days: usize,
+) -> Option<(usize, usize)> {
+    let last = from + Duration::days(days as i64 - 1);
+    if days == 0 || end < from || start > last {
+        return None;
+    }
+    let column = |date: NaiveDate| (date - from).num_days().clamp(0, days as i64 - 1) as usize;
+    Some((column(start), column(end)))
+}
+
+/// The first day shown when the timeline opens: the start of last week, so
+/// recent overdue tasks are in view
+pub fn default_start(today: NaiveDate, week_starts_on: chrono::Weekday) -> NaiveDate {
+    let since_start = today.weekday().days_since(week_starts_on);
+    today - Duration::days(i64::from(since_start) + 7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_bar() {
+        let from = date("2026-03-02");
+        assert_eq!(
+            bar(date("2026-03-03"), date("2026-03-05"), from, 10),
+            Some((1, 3))
+        );
+        // Clipped at both edges
+        assert_eq!(
+            bar(date("2026-02-01"), date("2026-04-01"), from, 10),
+            Some((0, 9))
+        );
+        assert_eq!(bar(date("2026-02-01"), date("2026-03-01"), from, 10), None);
+        assert_eq!(bar(date("2026-03-12"), date("2026-03-20"), from, 10), None);
+
+        // Thursday, weeks starting on Monday: the Monday of the week before
+        assert_eq!(
+            default_start(date("2026-03-12"), chrono::Weekday::Mon),
+            date("2026-03-02")
+        );
+    }
+
+    #[test]
+    fn test_rows() {
+        let work = List::new("Work");
+        let home = List::new("Home");
+        let today = Local::now().date_naive();
+        let mut later = Task::new("Later", work.id);
+        later.due_date = crate::quickadd::end_of_day(today + Duration::days(10));
+        let mut sooner = Task::new("Sooner", work.id);
+        sooner.due_date = crate::quickadd::end_of_day(today + Duration::days(2));
+        let undated = Task::new("Undated", home.id);
+
+        let rows = rows(&[later, sooner, undated], &[home, work]);
+        let titles: Vec<String> = rows
+            .iter()
+            .map(|row| match row {
+                Row::List(name) => name.clone(),
+                Row::Task { task, .. } => task.title.clone(),
+            })
+            .collect();
+        assert_eq!(titles, ["📋 Work", "Sooner", "Later"]);
+        // Bars end on the due date itself, whatever the local timezone
+        let Row::Task { end, .. } = &rows[1] else {
+            panic!("expected a task row");
+        };
+        assert_eq!(*end, today + Duration::days(2));
+    }
+}
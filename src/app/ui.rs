@@ -2,13 +2,28 @@
 
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Tabs, Wrap,
+    },
 };
-
-use super::state::{AppState, EditorField, Focus, Mode, SettingsItem, View};
+use std::cell::Cell;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::input;
+use super::keymap;
+use super::markdown;
+use super::planner;
+use super::state::{
+    AppState, ConfirmAction, EditorField, FilterRow, Focus, Mode, SettingsItem, SmartList, View,
+};
+use super::timeline::{self, Row};
+use crate::config::{Density, StatusSegment};
+use crate::deps;
+use crate::models::{DeletedListTasks, Priority, Task};
 use crate::theme::Theme;
 
 /// ASCII art logo for Tickit (used in help screen)
@@ -27,13 +42,29 @@ const ICON: &str = "✓";
 
 /// Render the entire UI
 pub fn render(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     // Set background
     let area = frame.area();
     let bg_block = Block::default().style(Style::default().bg(colors.bg));
     frame.render_widget(bg_block, area);
 
+    // Focus mode takes the whole screen
+    if state.mode == Mode::Focus {
+        render_focus(frame, state, area);
+        return;
+    }
+
+    if state.mode == Mode::Timeline {
+        render_timeline(frame, state, area);
+        return;
+    }
+
+    if state.mode == Mode::Planner {
+        render_planner(frame, state, area);
+        return;
+    }
+
     // Check if we need to show update banner
     let has_update = state.update_available.is_some();
 
@@ -106,11 +137,67 @@ pub fn render(frame: &mut Frame, state: &AppState) {
     if state.mode == Mode::About {
         render_about_dialog(frame, state);
     }
+
+    if state.mode == Mode::SyncPanel {
+        render_sync_panel(frame, state);
+    }
+
+    if state.mode == Mode::Stats {
+        render_stats(frame, state);
+    }
+
+    if state.mode == Mode::Warnings {
+        render_warnings(frame, state);
+    }
+
+    if state.mode == Mode::Workspaces {
+        render_workspace_picker(frame, state);
+    }
+
+    if state.mode == Mode::OpenLink {
+        render_link_picker(frame, state);
+    }
+
+    if state.mode == Mode::MoveTasks {
+        render_move_picker(frame, state);
+    }
+
+    if state.mode == Mode::TagTasks {
+        render_tag_picker(frame, state);
+    }
+
+    if state.mode == Mode::ReassignTag {
+        render_reassign_picker(frame, state);
+    }
+
+    if state.mode == Mode::MoveListTasks {
+        render_list_target_picker(frame, state);
+    }
+
+    if state.mode == Mode::QuickAdd {
+        render_quick_add(frame, state);
+    }
+
+    if state.mode == Mode::Comment {
+        render_comment_prompt(frame, state);
+    }
+
+    if state.mode == Mode::Defer {
+        render_defer_prompt(frame, state);
+    }
+
+    if state.mode == Mode::Filter {
+        render_filter_popup(frame, state);
+    }
+
+    if state.mode == Mode::Jump {
+        render_jump(frame, state);
+    }
 }
 
 /// Render the tab bar
 fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     let titles: Vec<Line> = View::all()
         .iter()
@@ -121,17 +208,39 @@ fn render_tabs(frame: &mut Frame, state: &AppState, area: Rect) {
             } else {
                 colors.tab()
             };
-            Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!(" {} ", i + 1), colors.key_hint()),
-                Span::styled(format!("{} {} ", view.icon(), view.name()), style),
-            ])
+                Span::styled(
+                    format!("{} {} ", view_icon(state, *view), view.name()),
+                    style,
+                ),
+            ];
+            let count = match view {
+                View::Tasks => state.task_badges.0 as usize,
+                View::Lists => state.lists.len(),
+                View::Tags => state.tags.len(),
+            };
+            spans.push(Span::styled(format!("{} ", count), colors.text_muted()));
+            // Overdue tasks show whichever view is open
+            let overdue = state.task_badges.1;
+            if *view == View::Tasks && overdue > 0 {
+                spans.push(Span::styled(
+                    format!("{} {} ", glyph(state, "⚠", "!"), overdue),
+                    colors.text_error(),
+                ));
+            }
+            Line::from(spans)
         })
         .collect();
 
     let tabs = Tabs::new(titles)
         .block(
             Block::default()
-                .title(format!(" {} Tickit ", ICON))
+                .title(if state.workspace == crate::config::DEFAULT_WORKSPACE {
+                    format!(" {} Tickit ", ICON)
+                } else {
+                    format!(" {} Tickit · {} ", ICON, state.workspace)
+                })
                 .title_style(colors.logo_style_primary())
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
@@ -159,12 +268,12 @@ fn render_main(frame: &mut Frame, state: &AppState, area: Rect) {
 
 /// Render the tasks view with sidebar
 fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(24), // Sidebar
+            Constraint::Length(30), // Sidebar
             Constraint::Min(0),     // Task list
         ])
         .split(area);
@@ -179,33 +288,71 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
 
     let mut list_items: Vec<ListItem> = Vec::new();
 
-    // Lists (no more "All" - Inbox shows all tasks)
-    for (i, list) in state.lists.iter().enumerate() {
-        let selected = state.list_index == i;
+    // Smart lists (open tasks by due date)
+    for (i, smart) in SmartList::all().iter().enumerate() {
+        let selected = state.smart_index == Some(i);
         let style = if selected {
             colors.selected()
+        } else if state.active_smart == Some(*smart) {
+            colors.text_primary()
         } else {
             colors.text()
         };
-        // Inbox shows total task count (all tasks), other lists show their own count
-        let count = if list.is_inbox {
-            state
-                .db
-                .get_total_task_count(state.show_completed)
-                .unwrap_or(0)
+        let count = state.smart_counts.get(i).copied().unwrap_or_default();
+        let overdue = *smart == SmartList::Overdue && count > 0;
+        let count_style = if overdue {
+            colors.text_error()
         } else {
-            state
-                .db
-                .get_task_count(list.id, state.show_completed)
-                .unwrap_or(0)
+            colors.text_muted()
+        };
+        let count_text = if overdue && state.config.display.accessible {
+            format!(" ({}!)", count)
+        } else {
+            format!(" ({})", count)
         };
         list_items.push(ListItem::new(Line::from(vec![
-            Span::styled(format!("  {} ", list.icon), style),
-            Span::styled(&list.name, style),
-            Span::styled(format!(" ({})", count), colors.text_muted()),
+            Span::styled(format!("  {} ", smart_icon(state, smart)), style),
+            Span::styled(smart.name(), style),
+            Span::styled(count_text, count_style),
         ])));
     }
 
+    // Lists (no more "All" - Inbox shows all tasks) with completion progress
+    let progress = &state.list_progress;
+    for (i, list) in state.lists.iter().enumerate() {
+        let selected = state.smart_index.is_none() && state.list_index == i;
+        let style = if selected {
+            colors.selected()
+        } else {
+            colors.text()
+        };
+        // Inbox shows progress over all tasks, other lists their own
+        let (done, total) = if list.is_inbox {
+            progress
+                .values()
+                .fold((0, 0), |(d, t), (done, total)| (d + done, t + total))
+        } else {
+            progress.get(&list.id).copied().unwrap_or((0, 0))
+        };
+        let mut spans = vec![
+            Span::styled(format!("  {} ", list_icon(state, &list.icon)), style),
+            Span::styled(&list.name, style),
+            Span::styled(format!(" {}/{} ", done, total), colors.text_muted()),
+        ];
+        if total > 0 {
+            let filled = (done * 4 + total / 2) / total;
+            spans.push(Span::styled(
+                "▓".repeat(filled as usize),
+                colors.text_success(),
+            ));
+            spans.push(Span::styled(
+                "░".repeat(4 - filled as usize),
+                colors.text_muted(),
+            ));
+        }
+        list_items.push(ListItem::new(Line::from(spans)));
+    }
+
     let sidebar = List::new(list_items).block(
         Block::default()
             .title(" Lists ")
@@ -214,25 +361,32 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
             .border_style(sidebar_style),
     );
 
-    frame.render_widget(sidebar, chunks[0]);
+    render_scrolled_list(
+        frame,
+        sidebar,
+        chunks[0],
+        state.sidebar_cursor(),
+        state.sidebar_len(),
+        &state.scroll.sidebar,
+    );
 
-    // Task list
+    // Task list, tinted with the list's own color if it has one
     let main_focused = state.focus == Focus::Main;
-    let main_style = if main_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
+    let accent = panel_list_color(state);
+    let main_style = match (main_focused, accent) {
+        (true, Some(accent)) => Style::default().fg(accent),
+        (true, None) => colors.block_focus(),
+        (false, _) => colors.block(),
     };
+    let title_style = accent.map_or(colors.text(), |accent| Style::default().fg(accent));
 
-    let list_name = if state.list_index == 0 {
-        "All Tasks".to_string()
-    } else {
-        state
-            .lists
-            .get(state.list_index - 1)
-            .map(|l| l.name.clone())
-            .unwrap_or_else(|| "Tasks".to_string())
-    };
+    let list_name = state.task_panel_name();
+    let compact = state.config.display.density == Density::Compact;
+    let accessible = state.config.display.accessible;
+    // Tasks from several lists are shown together, so each row names its list
+    let show_list = state.active_smart.is_some() || state.selected_list_id.is_none();
+    // Inside the borders, less the scrollbar column
+    let row_width = chunks[1].width.saturating_sub(3) as usize;
 
     let task_items: Vec<ListItem> = state
         .tasks
@@ -246,7 +400,7 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
                 colors.text()
             };
 
-            let checkbox = if task.completed { "☑" } else { "☐" };
+            let checkbox = checkbox(state, task.completed);
             let checkbox_style = if task.completed {
                 colors.text_success()
             } else {
@@ -261,14 +415,57 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
                 base_style
             };
 
-            let priority_style = colors.priority_style(task.priority);
-            let priority_icon = task.priority.icon();
+            // Overdue tasks set to nag escalate to urgent styling
+            let nagging = task.nag_hours.is_some() && task.is_overdue(chrono::Utc::now());
+            let priority_style = if nagging {
+                colors.priority_style(Priority::Urgent)
+            } else {
+                colors.priority_style(task.priority)
+            };
+            let title_style = if nagging && !selected {
+                title_style
+                    .patch(priority_style)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                title_style
+            };
+
+            let marked = state.is_marked(task.id);
+            let title_style = if marked {
+                title_style.add_modifier(Modifier::BOLD)
+            } else {
+                title_style
+            };
 
+            // Gutter shows the priority color, or the mark
+            let gutter = if marked {
+                Span::styled("▌", colors.text_primary())
+            } else {
+                Span::styled("▎", priority_style)
+            };
             let mut spans = vec![
-                Span::styled(format!(" {} ", checkbox), checkbox_style),
-                Span::styled(format!("{} ", priority_icon), priority_style),
-                Span::styled(&task.title, title_style),
+                gutter,
+                Span::styled(format!("{} ", checkbox), checkbox_style),
             ];
+            let marker = priority_marker(state, task.priority);
+            if !marker.is_empty() {
+                spans.push(Span::styled(marker, priority_style));
+            }
+            if selected && state.mode == Mode::InlineEdit {
+                spans.push(Span::styled(
+                    state.input_buffer.as_str(),
+                    colors.text().bg(colors.bg_highlight),
+                ));
+                return ListItem::new(Line::from(spans));
+            }
+            // Cut tasks are faded until they're pasted
+            let title_style = if state.cut_tasks.contains(&task.id) {
+                spans.push(Span::styled(glyph(state, "✂ ", "~ "), colors.text_muted()));
+                title_style.add_modifier(Modifier::DIM | Modifier::ITALIC)
+            } else {
+                title_style
+            };
+            spans.extend(linked(state, &task.title, title_style));
 
             // Add due date indicator
             if let Some(due_date) = task.due_date {
@@ -284,24 +481,54 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
                     colors.text_muted()
                 };
 
-                let due_str = due_date.format("%m/%d").to_string();
-                spans.push(Span::styled(format!(" 📅{}", due_str), due_style));
+                let due_str = state.config.format_date(due_date.date_naive());
+                spans.push(Span::styled(
+                    format!(" {}{}", glyph(state, "📅", "due:"), due_str),
+                    due_style,
+                ));
+                if accessible && is_overdue {
+                    spans.push(Span::styled(" (overdue)", due_style));
+                } else if accessible && is_soon && !task.completed {
+                    spans.push(Span::styled(" (soon)", due_style));
+                }
+            }
+
+            // Waiting on an open task
+            if state.blocked_tasks.contains(&task.id) {
+                spans.push(Span::styled(
+                    glyph(state, " ⛓", " [blocked]"),
+                    colors.text_warning(),
+                ));
             }
 
             // Add URL indicator
             if task.url.is_some() {
-                spans.push(Span::styled(" 🔗", colors.text_info()));
+                spans.push(Span::styled(
+                    glyph(state, " 🔗", " [url]"),
+                    colors.text_info(),
+                ));
             }
 
-            // Add tag indicators
-            if !task.tag_ids.is_empty() {
-                let tag_count = task.tag_ids.len();
+            if task.nag_hours.is_some() && !task.completed {
                 spans.push(Span::styled(
-                    format!(" [{}]", tag_count),
-                    colors.text_secondary(),
+                    glyph(state, " 🔔", " [nag]"),
+                    if nagging {
+                        priority_style
+                    } else {
+                        colors.text_muted()
+                    },
                 ));
             }
 
+            // List and tag chips in whatever room is left on the row
+            let used: usize = spans.iter().map(Span::width).sum();
+            spans.extend(location_chips(
+                state,
+                task,
+                show_list,
+                row_width.saturating_sub(used),
+            ));
+
             ListItem::new(Line::from(spans))
         })
         .collect();
@@ -311,20 +538,239 @@ fn render_tasks_view(frame: &mut Frame, state: &AppState, area: Rect) {
     } else {
         " (hiding completed)"
     };
+    let filter_status = if state.filter.is_active() {
+        format!(" [{}]", state.filter.describe(&state.tags))
+    } else {
+        String::new()
+    };
+    let marked_status = if state.marked_tasks.is_empty() {
+        String::new()
+    } else {
+        format!(" [{} selected]", state.marked_tasks.len())
+    };
     let tasks_block = List::new(task_items).block(
         Block::default()
-            .title(format!(" {} {} ", list_name, show_status))
+            .title(
+                Line::from(format!(
+                    " {} {}{}{} ",
+                    list_name, show_status, filter_status, marked_status
+                ))
+                .style(title_style),
+            )
+            .title(
+                Line::from(format!(" ⇅ {} ", state.task_sort().name()))
+                    .style(colors.text_muted())
+                    .right_aligned(),
+            )
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .border_style(main_style),
     );
 
-    frame.render_widget(tasks_block, chunks[1]);
+    // "Completed today" section at the bottom
+    let (main_area, completed_area) = if state.completed_today.is_empty() || compact {
+        (chunks[1], None)
+    } else {
+        let height = if state.config.show_completed_today {
+            state.completed_today.len() as u16 + 2
+        } else {
+            1
+        };
+        let [main_area, completed_area] =
+            Layout::vertical([Constraint::Min(5), Constraint::Length(height)]).areas(chunks[1]);
+        (main_area, Some(completed_area))
+    };
+
+    // Detail pane below the list for the description, dependency graph and comments
+    let selected_task = state.selected_task().filter(|_| !compact);
+    let mut details = Vec::new();
+    if let Some(task) = selected_task {
+        if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            details = markdown::render(description, &colors, &state.config.links);
+        }
+        let graph = dependency_lines(state, task.id);
+        if !graph.is_empty() && !details.is_empty() {
+            details.push(Line::from(""));
+        }
+        details.extend(graph);
+        let comments = comment_lines(state, task.id);
+        if !comments.is_empty() && !details.is_empty() {
+            details.push(Line::from(""));
+        }
+        details.extend(comments);
+    }
+    let (list_area, detail_area) = match details.len() {
+        0 => (main_area, None),
+        len => {
+            let lines = len as u16 + 2;
+            let [list_area, detail_area] = Layout::vertical([
+                Constraint::Min(5),
+                Constraint::Length(lines.min(main_area.height / 3).max(3)),
+            ])
+            .areas(main_area);
+            (list_area, Some(detail_area))
+        }
+    };
+
+    state
+        .scroll
+        .page_rows
+        .set(list_area.height.saturating_sub(2) as usize);
+    render_scrolled_list(
+        frame,
+        tasks_block,
+        list_area,
+        state.task_index,
+        state.tasks.len(),
+        &state.scroll.tasks,
+    );
+
+    if state.mode == Mode::InlineEdit {
+        // Gutter, checkbox and priority marker come before the title
+        let marker = state
+            .selected_task()
+            .map_or(String::new(), |t| priority_marker(state, t.priority));
+        let prefix = 1 + 2 + Line::from(marker).width() as u16;
+        let row = state.task_index.saturating_sub(state.scroll.tasks.get()) as u16;
+        frame.set_cursor_position((
+            list_area.x + 1 + prefix + input::cursor_col(&state.input_buffer, state.cursor_pos),
+            list_area.y + 1 + row,
+        ));
+    }
+
+    if let (Some(task), Some(area)) = (selected_task, detail_area) {
+        let details = Paragraph::new(details).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(format!(" {} ", task.title))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(colors.block()),
+        );
+        frame.render_widget(details, area);
+    }
+
+    if let Some(area) = completed_area {
+        render_completed_today(frame, state, area);
+    }
+}
+
+/// Comments on a task for the detail pane, oldest first
+fn comment_lines(state: &AppState, task_id: uuid::Uuid) -> Vec<Line<'static>> {
+    let colors = state.colors();
+    let comments = state.comments.get(&task_id).map(Vec::as_slice);
+    comments
+        .unwrap_or_default()
+        .iter()
+        .map(|comment| {
+            let at = comment.created_at.with_timezone(&chrono::Local);
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{}{}  ",
+                        glyph(state, "💬 ", ""),
+                        state.config.format_datetime(at.naive_local())
+                    ),
+                    colors.text_muted(),
+                ),
+                Span::styled(comment.body.clone(), colors.text()),
+            ])
+        })
+        .collect()
+}
+
+/// Blocked-by and blocks trees of a task for the detail pane
+fn dependency_lines(state: &AppState, task_id: uuid::Uuid) -> Vec<Line<'static>> {
+    let colors = state.colors();
+    let mut lines = Vec::new();
+
+    for (direction, heading) in [
+        (
+            deps::Direction::BlockedBy,
+            glyph(state, "⛓ Blocked by", "Blocked by"),
+        ),
+        (deps::Direction::Blocks, glyph(state, "→ Blocks", "Blocks")),
+    ] {
+        let rows = deps::tree(&state.dependencies, task_id, direction);
+        if rows.is_empty() {
+            continue;
+        }
+        lines.push(Line::from(Span::styled(
+            heading,
+            colors.text_primary().add_modifier(Modifier::BOLD),
+        )));
+        for (prefix, id) in rows {
+            let Some(task) = state.dependency_tasks.get(&id) else {
+                continue;
+            };
+            let style = if task.completed {
+                colors.text_muted().add_modifier(Modifier::CROSSED_OUT)
+            } else {
+                colors.text()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(prefix, colors.text_muted()),
+                Span::styled(format!("{} ", checkbox(state, task.completed)), style),
+                Span::styled(task.title.clone(), style),
+            ]));
+        }
+    }
+    lines
+}
+
+/// Render the collapsible "Completed today" section of the Tasks view
+fn render_completed_today(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.colors();
+    let title = format!(" ✓ Completed today ({}) ", state.completed_today.len());
+
+    if !state.config.show_completed_today {
+        let line = Line::from(vec![
+            Span::styled(format!(" ▸{}", title), colors.text_success()),
+            Span::styled("C", colors.key_hint()),
+            Span::styled(": expand", colors.text_muted()),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+        return;
+    }
+
+    let items: Vec<ListItem> = state
+        .completed_today
+        .iter()
+        .map(|task| {
+            let time = task
+                .completed_at
+                .map(|t| {
+                    state
+                        .config
+                        .format_time(t.with_timezone(&chrono::Local).time())
+                })
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {} ", time), colors.text_muted()),
+                Span::styled(format!("{} ", checkbox(state, true)), colors.text_success()),
+                Span::styled(&task.title, colors.text_dim()),
+            ]))
+        })
+        .collect();
+
+    let section = List::new(items).block(
+        Block::default()
+            .title(format!(" ▾{}", title))
+            .title_style(colors.text_success())
+            .title(
+                Line::from(" R: reopen latest · C: collapse ")
+                    .style(colors.text_muted())
+                    .right_aligned(),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block()),
+    );
+    frame.render_widget(section, area);
 }
 
 /// Render the lists view
 fn render_lists_view(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     let list_items: Vec<ListItem> = state
         .lists
@@ -338,13 +784,26 @@ fn render_lists_view(frame: &mut Frame, state: &AppState, area: Rect) {
                 colors.text()
             };
 
-            let inbox_marker = if list.is_inbox { " (default)" } else { "" };
+            let inbox_marker = if list.is_inbox {
+                " (default)"
+            } else if list.is_checklist {
+                " (checklist)"
+            } else {
+                ""
+            };
 
-            ListItem::new(Line::from(vec![
-                Span::styled(format!("  {} ", list.icon), style),
+            let mut spans = vec![
+                Span::styled(format!("  {} ", list_icon(state, &list.icon)), style),
                 Span::styled(&list.name, style),
                 Span::styled(inbox_marker, colors.text_muted()),
-            ]))
+            ];
+            if let Some(color) = list.color.as_deref().and_then(|c| named_color(state, c)) {
+                spans.push(Span::styled(
+                    glyph(state, " ●", " #"),
+                    Style::default().fg(color),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -356,12 +815,23 @@ fn render_lists_view(frame: &mut Frame, state: &AppState, area: Rect) {
             .border_style(colors.block_focus()),
     );
 
-    frame.render_widget(lists, area);
+    state
+        .scroll
+        .page_rows
+        .set(area.height.saturating_sub(2) as usize);
+    render_scrolled_list(
+        frame,
+        lists,
+        area,
+        state.list_index,
+        state.lists.len(),
+        &state.scroll.lists,
+    );
 }
 
 /// Render the tags view
 fn render_tags_view(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     let tag_items: Vec<ListItem> = state
         .tags
@@ -376,10 +846,12 @@ fn render_tags_view(frame: &mut Frame, state: &AppState, area: Rect) {
             };
 
             // Parse hex color for tag
-            let tag_color = parse_hex_color(&tag.color).unwrap_or(colors.accent);
+            let tag_color = parse_hex_color(&tag.color)
+                .map(|c| state.fit_color(c))
+                .unwrap_or(colors.accent);
 
             ListItem::new(Line::from(vec![
-                Span::styled("  ● ", Style::default().fg(tag_color)),
+                Span::styled(glyph(state, "  ● ", "  # "), Style::default().fg(tag_color)),
                 Span::styled(&tag.name, style),
             ]))
         })
@@ -402,30 +874,111 @@ fn render_tags_view(frame: &mut Frame, state: &AppState, area: Rect) {
             .border_style(colors.block_focus()),
     );
 
-    frame.render_widget(tags, area);
+    state
+        .scroll
+        .page_rows
+        .set(area.height.saturating_sub(2) as usize);
+    render_scrolled_list(
+        frame,
+        tags,
+        area,
+        state.tag_index,
+        state.tags.len(),
+        &state.scroll.tags,
+    );
+}
+
+/// Render a bordered list scrolled to keep `selected` visible, with a scrollbar
+/// on the right border when the items overflow
+fn render_scrolled_list(
+    frame: &mut Frame,
+    list: List,
+    area: Rect,
+    selected: usize,
+    len: usize,
+    offset: &Cell<usize>,
+) {
+    let mut list_state = ListState::default()
+        .with_offset(offset.get())
+        .with_selected((len > 0).then_some(selected));
+    frame.render_stateful_widget(list, area, &mut list_state);
+    offset.set(list_state.offset());
+
+    let visible = area.height.saturating_sub(2) as usize;
+    if len > visible {
+        let mut scrollbar_state = ScrollbarState::new(len.saturating_sub(visible))
+            .position(list_state.offset())
+            .viewport_content_length(visible);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+    }
 }
 
 /// Render the status bar
 fn render_status_bar(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
-
-    // Calculate sync indicator width for layout
-    let sync_width = if state.is_sync_enabled() { 16u16 } else { 0u16 };
-    let content_width = area.width.saturating_sub(sync_width);
-
-    // Split area into content and sync indicator
-    let content_area = Rect {
-        x: area.x,
-        y: area.y,
-        width: content_width,
-        height: area.height,
-    };
-    let sync_area = Rect {
-        x: area.x + content_width,
-        y: area.y,
-        width: sync_width,
-        height: area.height,
-    };
+    let colors = state.colors();
+
+    // Right-hand segments, in the configured order
+    let mut segments: Vec<Vec<Span>> = Vec::new();
+    for segment in &state.config.status_segments {
+        let spans = match segment {
+            StatusSegment::View => {
+                let name = if state.view == View::Tasks {
+                    state.task_panel_name()
+                } else {
+                    state.view.name().to_string()
+                };
+                vec![Span::styled(name, colors.text_primary())]
+            }
+            StatusSegment::Filter if state.filter.is_active() => vec![Span::styled(
+                format!(
+                    "{} {}",
+                    glyph(state, "⚲", "Filter:"),
+                    state.filter.describe(&state.tags)
+                ),
+                colors.text_info(),
+            )],
+            StatusSegment::Filter => continue,
+            StatusSegment::Tasks if state.view == View::Tasks => {
+                let done = state.tasks.iter().filter(|t| t.completed).count();
+                vec![Span::styled(
+                    format!("{} open · {} done", state.tasks.len() - done, done),
+                    colors.text_muted(),
+                )]
+            }
+            StatusSegment::Tasks => continue,
+            StatusSegment::Sync if state.is_sync_enabled() => vec![sync_segment(state)],
+            StatusSegment::Sync => continue,
+            StatusSegment::Clock => vec![Span::styled(
+                state.config.format_time(chrono::Local::now().time()),
+                colors.text_muted(),
+            )],
+        };
+        segments.push(spans);
+    }
+
+    let mut right: Vec<Span> = Vec::new();
+    for (i, spans) in segments.into_iter().enumerate() {
+        right.push(Span::styled(
+            if i == 0 { " " } else { " │ " },
+            colors.text_muted(),
+        ));
+        right.extend(spans);
+    }
+    right.push(Span::raw(" "));
+    let right = Line::from(right);
+    let right_width = (right.width() as u16).min(area.width / 2);
+
+    let [content_area, segments_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(right_width)]).areas(area);
 
     // Render main content
     let content = if let Some(msg) = &state.status_message {
@@ -434,72 +987,141 @@ fn render_status_bar(frame: &mut Frame, state: &AppState, area: Rect) {
             Span::styled(msg, colors.text_secondary()),
         ]
     } else {
-        vec![
-            Span::styled(" ", Style::default()),
-            Span::styled("Tab", colors.key_hint()),
-            Span::styled(": views  ", colors.text_muted()),
-            Span::styled("?", colors.key_hint()),
-            Span::styled(": help  ", colors.text_muted()),
-            Span::styled("s", colors.key_hint()),
-            Span::styled(": settings  ", colors.text_muted()),
-            Span::styled("t", colors.key_hint()),
-            Span::styled(": theme  ", colors.text_muted()),
-            Span::styled("A", colors.key_hint()),
-            Span::styled(": about  ", colors.text_muted()),
-            Span::styled("q", colors.key_hint()),
-            Span::styled(": quit", colors.text_muted()),
-        ]
+        let context = match (state.mode, state.view, state.focus) {
+            (Mode::AddTask | Mode::EditTask, _, _) => keymap::Context::Editor,
+            (_, View::Tasks, Focus::Sidebar) => keymap::Context::Sidebar,
+            (_, View::Tasks, Focus::Main) => keymap::Context::Tasks,
+            (_, View::Lists | View::Tags, _) => keymap::Context::Collections,
+        };
+        // Only as many hints as fit whole
+        let mut spans = vec![Span::raw(" ")];
+        let mut width = 1;
+        for hint in keymap::hints(context) {
+            let label = format!(": {}  ", hint.label);
+            width += hint.key.width() + label.width();
+            if width > content_area.width as usize {
+                break;
+            }
+            spans.push(Span::styled(hint.key, colors.key_hint()));
+            spans.push(Span::styled(label, colors.text_muted()));
+        }
+        spans
     };
 
     let status =
         Paragraph::new(Line::from(content)).style(Style::default().bg(colors.bg_secondary));
     frame.render_widget(status, content_area);
 
-    // Render sync indicator separately (only this part updates during sync)
-    if state.is_sync_enabled() {
-        let sync_indicator = if state.sync_status.syncing {
-            Span::styled(
-                " ↻ Syncing... ",
-                Style::default().fg(Color::Cyan).bg(colors.bg_secondary),
-            )
-        } else if let Some(ref error) = state.sync_status.last_error {
-            // Truncate error for status bar, show abbreviated message
-            let short_error = if error.len() > 20 {
-                format!(" ⚠ {}… ", &error[..20])
-            } else {
-                format!(" ⚠ {} ", error)
-            };
-            Span::styled(
-                short_error,
-                Style::default().fg(Color::Red).bg(colors.bg_secondary),
-            )
-        } else if state.sync_status.last_sync.is_some() {
-            Span::styled(
-                " ☁ Synced ",
-                Style::default().fg(Color::Green).bg(colors.bg_secondary),
-            )
+    let segments_widget = Paragraph::new(right)
+        .style(Style::default().bg(colors.bg_secondary))
+        .alignment(Alignment::Right);
+    frame.render_widget(segments_widget, segments_area);
+}
+
+/// Sync state for the status bar: spinner while syncing, then errors or unsynced changes
+fn sync_segment(state: &AppState) -> Span<'static> {
+    const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    const ASCII_SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+    let pending = state.sync_status.pending_changes;
+    let (warning, cloud) = (glyph(state, "⚠", "!"), glyph(state, "☁ ", ""));
+
+    if state.auto_sync_paused()
+        && !state.sync_status.syncing
+        && state.sync_status.last_error.is_none()
+    {
+        let paused = if state.sync_paused_saved {
+            "Sync paused"
         } else {
-            Span::styled(
-                " S: sync ",
-                Style::default().fg(colors.fg_muted).bg(colors.bg_secondary),
+            "Sync paused this session"
+        };
+        let text = if pending > 0 {
+            format!(
+                "{} {}, {} unsynced",
+                glyph(state, "⏸", "||"),
+                paused,
+                pending
             )
+        } else {
+            format!("{} {}", glyph(state, "⏸", "||"), paused)
         };
+        return Span::styled(text, Style::default().fg(Color::Yellow));
+    }
 
-        let sync_widget = Paragraph::new(Line::from(vec![sync_indicator]))
-            .style(Style::default().bg(colors.bg_secondary))
-            .alignment(Alignment::Right);
-        frame.render_widget(sync_widget, sync_area);
+    if state.sync_status.syncing {
+        let frame = if state.unicode() {
+            SPINNER[state.tick % SPINNER.len()]
+        } else {
+            ASCII_SPINNER[state.tick % ASCII_SPINNER.len()]
+        };
+        let text = if let Some(progress) = state.sync_status.progress {
+            format!("{} Syncing {}/{}…", frame, progress.sent, progress.total)
+        } else if pending > 0 {
+            format!("{} Syncing {} changes…", frame, pending)
+        } else {
+            format!("{} Syncing…", frame)
+        };
+        Span::styled(text, Style::default().fg(Color::Cyan))
+    } else if let Some(ref error) = state.sync_status.last_error {
+        // Truncate error for status bar, show abbreviated message
+        let mut short_error = if error.chars().count() > 20 {
+            format!(
+                "{} {}…",
+                warning,
+                error.chars().take(20).collect::<String>()
+            )
+        } else {
+            format!("{} {}", warning, error)
+        };
+        // Countdown to the automatic retry; Ctrl+S retries now
+        if let Some(wait) = sync_retry_wait(state) {
+            short_error.push_str(&format!(" {} {}s", glyph(state, "↻", "retry"), wait));
+        }
+        Span::styled(short_error, Style::default().fg(Color::Red))
+    } else if pending > 0 {
+        Span::styled(
+            format!("{}{} unsynced", cloud, pending),
+            Style::default().fg(Color::Yellow),
+        )
+    } else if state.sync_status.last_sync.is_some() {
+        Span::styled(
+            format!("{}Synced", cloud),
+            Style::default().fg(Color::Green),
+        )
+    } else {
+        Span::styled("S: sync", Style::default().fg(state.colors().fg_muted))
     }
 }
 
 /// Render help popup
 fn render_help_popup(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = frame.area();
 
-    // Calculate popup size
+    let mut help_content = Vec::new();
+    for (title, bindings) in keymap::filtered(&state.input_buffer) {
+        help_content.push(Line::from(""));
+        help_content.push(Line::from(vec![Span::styled(
+            format!("  {}", title),
+            colors.text_primary().add_modifier(Modifier::BOLD),
+        )]));
+        for binding in bindings {
+            help_content.push(Line::from(vec![
+                Span::styled(format!("  {:<19}", binding.keys), colors.key_hint()),
+                Span::styled(binding.action, colors.text()),
+            ]));
+        }
+    }
+    if help_content.is_empty() {
+        help_content.push(Line::from(""));
+        help_content.push(Line::from(Span::styled(
+            "  No shortcuts match",
+            colors.text_muted(),
+        )));
+    }
+
+    // Calculate popup size (content + filter line + borders)
     let popup_width = 60u16.min(area.width.saturating_sub(4));
-    let popup_height = 34u16.min(area.height.saturating_sub(4));
+    let popup_height = (help_content.len() as u16 + 4).min(area.height.saturating_sub(4));
 
     let popup_area = Rect {
         x: area.x + (area.width - popup_width) / 2,
@@ -510,125 +1132,67 @@ fn render_help_popup(frame: &mut Frame, state: &AppState) {
 
     frame.render_widget(Clear, popup_area);
 
-    let help_content = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "  Navigation",
-            colors.text_primary().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::styled("  Tab / Shift+Tab    ", colors.key_hint()),
-            Span::styled("Switch between views", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  1-3                ", colors.key_hint()),
-            Span::styled("Jump to view directly", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  j/k or ↑/↓         ", colors.key_hint()),
-            Span::styled("Navigate lists", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  g/G                ", colors.key_hint()),
-            Span::styled("Go to first/last item", colors.text()),
-        ]),
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg_secondary))
+        .title(glyph(
+            state,
+            " ⌨ Keyboard Shortcuts ",
+            " Keyboard Shortcuts ",
+        ))
+        .title_style(colors.text_primary())
+        .title_bottom(Line::from(" ↑↓ scroll │ type to filter │ Esc close ").centered());
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [filter_area, content_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+    let filter_line = if state.input_buffer.is_empty() {
+        Line::from(Span::styled("  / type to filter", colors.text_muted()))
+    } else {
         Line::from(vec![
-            Span::styled("  h/l or ←/→         ", colors.key_hint()),
-            Span::styled("Focus sidebar/main", colors.text()),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "  Tasks View",
-            colors.text_primary().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::styled("  Enter/Space        ", colors.key_hint()),
-            Span::styled("Toggle task complete", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  n                  ", colors.key_hint()),
-            Span::styled("Create new task", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  e                  ", colors.key_hint()),
-            Span::styled("Edit selected task", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  d                  ", colors.key_hint()),
-            Span::styled("Delete selected task", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  p                  ", colors.key_hint()),
-            Span::styled("Cycle priority", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  o                  ", colors.key_hint()),
-            Span::styled("Open task URL", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  c                  ", colors.key_hint()),
-            Span::styled("Toggle show completed", colors.text()),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "  General",
-            colors.text_primary().add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::styled("  s                  ", colors.key_hint()),
-            Span::styled("Open settings", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  t                  ", colors.key_hint()),
-            Span::styled("Open theme selector", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  A                  ", colors.key_hint()),
-            Span::styled("About Tickit", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  r                  ", colors.key_hint()),
-            Span::styled("Refresh data", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  ?                  ", colors.key_hint()),
-            Span::styled("Toggle this help", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  Ctrl+s / S         ", colors.key_hint()),
-            Span::styled("Sync with server (if configured)", colors.text()),
-        ]),
-        Line::from(vec![
-            Span::styled("  q / Ctrl+c         ", colors.key_hint()),
-            Span::styled("Quit application", colors.text()),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  Press ", colors.text_muted()),
-            Span::styled("Esc", colors.key_hint()),
-            Span::styled(" or ", colors.text_muted()),
-            Span::styled("?", colors.key_hint()),
-            Span::styled(" to close", colors.text_muted()),
-        ]),
-    ];
+            Span::styled("  / ", colors.key_hint()),
+            Span::styled(state.input_buffer.as_str(), colors.text()),
+        ])
+    };
+    frame.render_widget(Paragraph::new(filter_line), filter_area);
+    if !state.input_buffer.is_empty() {
+        frame.set_cursor_position((
+            filter_area.x + 4 + input::cursor_col(&state.input_buffer, state.cursor_pos),
+            filter_area.y,
+        ));
+    }
 
-    let help = Paragraph::new(help_content)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(colors.block_focus())
-                .style(Style::default().bg(colors.bg_secondary))
-                .title(" ⌨ Keyboard Shortcuts ")
-                .title_style(colors.text_primary()),
-        )
-        .wrap(Wrap { trim: false });
+    // Clamp the scroll offset to the content
+    let visible = content_area.height as usize;
+    let len = help_content.len();
+    let offset = state.scroll.help.get().min(len.saturating_sub(visible));
+    state.scroll.help.set(offset);
+
+    frame.render_widget(
+        Paragraph::new(help_content).scroll((offset as u16, 0)),
+        content_area,
+    );
 
-    frame.render_widget(help, popup_area);
+    if len > visible {
+        let mut scrollbar_state = ScrollbarState::new(len.saturating_sub(visible))
+            .position(offset)
+            .viewport_content_length(visible);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            content_area,
+            &mut scrollbar_state,
+        );
+    }
 }
 
 /// Render theme picker
 fn render_theme_picker(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = centered_rect(50, 70, frame.area());
 
     frame.render_widget(Clear, area);
@@ -674,26 +1238,28 @@ fn render_theme_picker(frame: &mut Frame, state: &AppState) {
             .border_type(BorderType::Rounded)
             .style(Style::default().bg(colors.bg))
             .title(format!(
-                " 🎨 Select Theme ({}/{}) ",
+                " {}Select Theme ({}/{}) ",
+                glyph(state, "🎨 ", ""),
                 state.theme_index + 1,
                 themes.len()
             ))
             .title_bottom(Line::from(" ↑↓ navigate │ ↵ apply │ Esc cancel ").centered()),
     );
 
-    frame.render_widget(theme_list, area);
+    let mut list_state = ListState::default().with_selected(Some(state.theme_index));
+    frame.render_stateful_widget(theme_list, area, &mut list_state);
 }
 
 /// Render settings dialog
 fn render_settings_dialog(frame: &mut Frame, state: &AppState) {
     use crate::app::state::SettingsItem;
 
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = frame.area();
 
     // Calculate popup size
     let popup_width = 60u16.min(area.width.saturating_sub(4));
-    let popup_height = 18u16.min(area.height.saturating_sub(4));
+    let popup_height = (SettingsItem::all().len() as u16 + 2).min(area.height.saturating_sub(4));
 
     let popup_area = Rect {
         x: (area.width - popup_width) / 2,
@@ -717,9 +1283,7 @@ fn render_settings_dialog(frame: &mut Frame, state: &AppState) {
             // If we're editing this item, show the input buffer with cursor at position
             let value_str = if is_editing && state.settings_editing == Some(*item) {
                 let buf = &state.input_buffer;
-                let pos = state.cursor_pos.min(buf.len());
-                let before: String = buf.chars().take(pos).collect();
-                let after: String = buf.chars().skip(pos).collect();
+                let (before, after) = buf.split_at(state.cursor_pos.min(buf.len()));
                 format!("{}▏{}", before, after)
             } else {
                 get_settings_value_display(state, *item)
@@ -741,7 +1305,17 @@ fn render_settings_dialog(frame: &mut Frame, state: &AppState) {
 
             ListItem::new(Line::from(vec![
                 Span::styled(format!(" {} ", cursor), style),
-                Span::styled(format!("{} ", item.icon()), style),
+                Span::styled(
+                    format!(
+                        "{} ",
+                        if state.unicode() {
+                            item.icon()
+                        } else {
+                            item.ascii_icon()
+                        }
+                    ),
+                    style,
+                ),
                 Span::styled(format!("{:<20}", item.label()), style),
                 Span::styled(value_str, value_style),
             ]))
@@ -761,21 +1335,37 @@ fn render_settings_dialog(frame: &mut Frame, state: &AppState) {
             .border_type(BorderType::Rounded)
             .style(Style::default().bg(colors.bg))
             .title(format!(
-                " ⚙ Settings ({}/{}) ",
+                " {}Settings ({}/{}) ",
+                glyph(state, "⚙ ", ""),
                 state.settings_index + 1,
                 items.len()
             ))
             .title_bottom(Line::from(footer_text).centered()),
     );
 
-    frame.render_widget(settings_list, popup_area);
+    // Scrolls to keep the cursor visible on short terminals
+    let mut list_state = ListState::default().with_selected(Some(state.settings_index));
+    frame.render_stateful_widget(settings_list, popup_area, &mut list_state);
 }
 
 fn get_settings_value_display(state: &AppState, item: SettingsItem) -> String {
     use crate::app::state::SettingsItem;
 
+    if let Some(on) = item.switch(&state.config) {
+        return match (item, on) {
+            (SettingsItem::ConfirmDefaultYes, true) => "Yes".to_string(),
+            (SettingsItem::ConfirmDefaultYes, false) => "No".to_string(),
+            (_, true) => "✓ On".to_string(),
+            (_, false) => "✗ Off".to_string(),
+        };
+    }
+
     match item {
         SettingsItem::Theme => state.theme.name().to_string(),
+        SettingsItem::Density => match state.config.display.density {
+            Density::Comfortable => "Comfortable".to_string(),
+            Density::Compact => "Compact".to_string(),
+        },
         SettingsItem::SyncEnabled => {
             if state.config.sync.enabled {
                 "✓ Enabled".to_string()
@@ -790,7 +1380,9 @@ fn get_settings_value_display(state: &AppState, item: SettingsItem) -> String {
             .clone()
             .unwrap_or_else(|| "Not set".to_string()),
         SettingsItem::SyncToken => {
-            if state.config.sync.token.is_some() {
+            if state.config.sync.token_command.is_some() {
+                "From token_command".to_string()
+            } else if state.config.sync.token.is_some() {
                 "••••••••".to_string()
             } else {
                 "Not set".to_string()
@@ -804,7 +1396,7 @@ fn get_settings_value_display(state: &AppState, item: SettingsItem) -> String {
             }
         }
         SettingsItem::Notifications => {
-            if state.config.notifications {
+            if state.config.notifications.enabled {
                 "✓ Enabled".to_string()
             } else {
                 "✗ Disabled".to_string()
@@ -817,27 +1409,88 @@ fn get_settings_value_display(state: &AppState, item: SettingsItem) -> String {
                 "✗ Hide".to_string()
             }
         }
+        // Plain on/off items are handled above
+        _ => String::new(),
     }
 }
 
 /// Render confirmation dialog
 fn render_confirm_dialog(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
-    let area = centered_rect(50, 20, frame.area());
+    let colors = state.colors();
+    let area = centered_rect(50, 25, frame.area());
 
     frame.render_widget(Clear, area);
 
-    let text = vec![
+    let mut text = vec![
         Line::from(""),
         Line::from(state.confirm_message.as_str()),
         Line::from(""),
         Line::from(vec![
-            Span::styled("  y  ", colors.key_hint()),
-            Span::raw("Yes  "),
-            Span::styled("  n  ", colors.key_hint()),
-            Span::raw("No"),
+            Span::styled(
+                if state.confirm_yes { " [y] " } else { "  y  " },
+                colors.key_hint(),
+            ),
+            Span::styled(
+                "Yes  ",
+                if state.confirm_yes {
+                    colors.selected()
+                } else {
+                    colors.text()
+                },
+            ),
+            Span::styled(
+                if state.confirm_yes { "  n  " } else { " [n] " },
+                colors.key_hint(),
+            ),
+            Span::styled(
+                "No",
+                if state.confirm_yes {
+                    colors.text()
+                } else {
+                    colors.selected()
+                },
+            ),
         ]),
+        Line::from(""),
+        Line::from(Span::styled("←→ switch │ ↵ choose", colors.text_muted())),
     ];
+    if matches!(state.confirm_action, Some(ConfirmAction::DeleteTag(_))) && state.confirm_usage > 0
+    {
+        text.push(Line::from(vec![
+            Span::styled("r", colors.key_hint()),
+            Span::styled(" move tasks to another tag │ ", colors.text_muted()),
+            Span::styled("e", colors.key_hint()),
+            Span::styled(" export them first", colors.text_muted()),
+        ]));
+    }
+    if let Some(ConfirmAction::DeleteList(_, tasks)) = state.confirm_action
+        && state.confirm_usage > 0
+    {
+        let option = |key: &'static str, label: &'static str, chosen: bool| {
+            [
+                Span::styled(key, colors.key_hint()),
+                Span::styled(
+                    label,
+                    if chosen {
+                        colors.selected()
+                    } else {
+                        colors.text_muted()
+                    },
+                ),
+            ]
+        };
+        let mut spans = Vec::new();
+        spans.extend(option(
+            "m",
+            " move to a list",
+            matches!(tasks, DeletedListTasks::Move(_)),
+        ));
+        spans.push(Span::styled(" │ ", colors.text_muted()));
+        spans.extend(option("a", " archive", tasks == DeletedListTasks::Archive));
+        spans.push(Span::styled(" │ ", colors.text_muted()));
+        spans.extend(option("x", " delete", tasks == DeletedListTasks::Delete));
+        text.push(Line::from(spans));
+    }
 
     let dialog = Paragraph::new(text)
         .block(
@@ -853,187 +1506,642 @@ fn render_confirm_dialog(frame: &mut Frame, state: &AppState) {
     frame.render_widget(dialog, area);
 }
 
-/// Render task editor
-fn render_task_editor(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
-    let area = centered_rect(60, 70, frame.area());
+/// Render list picker for moving marked tasks
+fn render_move_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(40, 50, frame.area());
 
     frame.render_widget(Clear, area);
 
-    let title = if state.mode == Mode::AddTask {
-        " New Task "
-    } else {
-        " Edit Task "
-    };
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(3), // Title input
-            Constraint::Length(3), // Description input
-            Constraint::Length(3), // Due Date input
-            Constraint::Length(3), // Priority
-            Constraint::Length(3), // List
-            Constraint::Min(5),    // Tags (expanded)
-            Constraint::Length(1), // Help
-        ])
-        .split(area);
-
-    // Title field
-    let title_focused = state.editor_field == EditorField::Title;
-    let title_style = if title_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
-
-    // Show the correct title value depending on focus
-    let title_display = if title_focused {
-        state.input_buffer.as_str()
-    } else {
-        state.editor_title_buffer.as_str()
-    };
+    let items: Vec<ListItem> = state
+        .lists
+        .iter()
+        .enumerate()
+        .map(|(i, list)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(format!("{} ", list_icon(state, &list.icon)), style),
+                Span::styled(&list.name, style),
+            ]))
+        })
+        .collect();
 
-    let title_input = Paragraph::new(title_display).block(
+    let picker = List::new(items).block(
         Block::default()
-            .title(" Title ")
+            .title(match state.move_targets.len() {
+                1 => " Move task to ".to_string(),
+                n => format!(" Move {} tasks to ", n),
+            })
+            .title_style(colors.text_primary())
             .borders(Borders::ALL)
-            .border_style(title_style),
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ↵ move │ Esc cancel ").centered()),
     );
-    frame.render_widget(title_input, chunks[0]);
 
-    if title_focused && !state.editor_adding_tag {
-        frame.set_cursor_position((chunks[0].x + state.cursor_pos as u16 + 1, chunks[0].y + 1));
-    }
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
 
-    // Description field
-    let desc_focused = state.editor_field == EditorField::Description;
-    let desc_style = if desc_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
-    let desc_display = if desc_focused {
-        state.input_buffer.as_str()
-    } else {
-        state.editor_description_buffer.as_str()
-    };
-    let desc_input = Paragraph::new(desc_display).block(
-        Block::default()
-            .title(" Description (optional) ")
-            .borders(Borders::ALL)
-            .border_style(desc_style),
-    );
-    frame.render_widget(desc_input, chunks[1]);
+/// Render the picker for the selected task's links: what the task says,
+/// then where it goes when that isn't the same
+fn render_link_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(60, 50, frame.area());
+    let width = area.width.saturating_sub(8) as usize;
 
-    if desc_focused && !state.editor_adding_tag {
-        frame.set_cursor_position((chunks[1].x + state.cursor_pos as u16 + 1, chunks[1].y + 1));
-    }
+    frame.render_widget(Clear, area);
 
-    // Due Date field
-    let due_focused = state.editor_field == EditorField::DueDate;
-    let due_style = if due_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
-    let due_display = if due_focused {
-        state.input_buffer.as_str()
-    } else {
-        state.editor_due_date_buffer.as_str()
-    };
-    let due_placeholder = if due_display.is_empty() {
-        "YYYY-MM-DD"
-    } else {
-        due_display
-    };
-    let due_input = Paragraph::new(due_placeholder)
-        .style(if due_display.is_empty() && !due_focused {
-            colors.text_muted()
-        } else {
-            colors.text()
+    let items: Vec<ListItem> = state
+        .link_choices
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            let mut spans = vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(format!("{} ", i + 1), colors.key_hint()),
+                Span::styled(truncate(&link.label, width), style),
+            ];
+            if link.label != link.url {
+                let room = width.saturating_sub(link.label.width() + 3);
+                spans.push(Span::styled(
+                    format!("  {}", truncate(&link.url, room)),
+                    colors.text_muted(),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
-        .block(
-            Block::default()
-                .title(" Due Date (optional) ")
-                .borders(Borders::ALL)
-                .border_style(due_style),
-        );
-    frame.render_widget(due_input, chunks[2]);
-
-    if due_focused && !state.editor_adding_tag {
-        frame.set_cursor_position((chunks[2].x + state.cursor_pos as u16 + 1, chunks[2].y + 1));
-    }
-
-    // Priority field
-    let priority_focused = state.editor_field == EditorField::Priority;
-    let priority_style = if priority_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
-    let priority_text = format!(
-        "{} {}",
-        state.editor_priority.icon(),
-        state.editor_priority.name()
-    );
-    let priority_input = Paragraph::new(priority_text).block(
-        Block::default()
-            .title(" Priority (j/k to change) ")
-            .borders(Borders::ALL)
-            .border_style(priority_style),
-    );
-    frame.render_widget(priority_input, chunks[3]);
+        .collect();
 
-    // List field
-    let list_focused = state.editor_field == EditorField::List;
-    let list_style = if list_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
-    let list_text = state
-        .lists
-        .get(state.editor_list_index)
-        .map(|l| format!("{} {}", l.icon, l.name))
-        .unwrap_or_else(|| "📥 Inbox".to_string());
-    let list_input = Paragraph::new(list_text).block(
+    let picker = List::new(items).block(
         Block::default()
-            .title(" List (j/k to change) ")
+            .title(" Open link ")
+            .title_style(colors.text_primary())
             .borders(Borders::ALL)
-            .border_style(list_style),
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ↵ open │ Esc cancel ").centered()),
     );
-    frame.render_widget(list_input, chunks[4]);
 
-    // Tags field - show as selectable list
-    let tags_focused = state.editor_field == EditorField::Tags;
-    let tags_style = if tags_focused {
-        colors.block_focus()
-    } else {
-        colors.block()
-    };
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
 
-    let mut tag_items: Vec<ListItem> = state
-        .tags
-        .iter()
-        .enumerate()
-        .map(|(i, tag)| {
-            let is_selected = state.editor_tag_indices.contains(&i);
-            let is_cursor = tags_focused && i == state.editor_tag_cursor;
+/// Render the workspace picker, marking the open workspace
+fn render_workspace_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(40, 50, frame.area());
 
-            let checkbox = if is_selected { "☑" } else { "☐" };
-            let marker = if is_cursor { "► " } else { "  " };
+    frame.render_widget(Clear, area);
 
-            let style = if is_cursor {
+    let items: Vec<ListItem> = state
+        .config
+        .workspace_names()
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
                 colors.selected()
             } else {
                 colors.text()
             };
+            let mut spans = vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(name.to_string(), style),
+            ];
+            if name == state.workspace {
+                spans.push(Span::styled(" (open)", colors.text_muted()));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
 
-            let tag_color = parse_hex_color(&tag.color).unwrap_or(colors.accent);
-
-            ListItem::new(Line::from(vec![
+    let picker = List::new(items).block(
+        Block::default()
+            .title(" Workspaces ")
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ↵ switch │ Esc cancel ").centered()),
+    );
+
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
+
+/// Render the startup warnings
+fn render_warnings(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(60, 60, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let mut lines = Vec::new();
+    for (i, warning) in state.warnings.iter().enumerate() {
+        let selected = i == state.picker_index;
+        if i > 0 {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(vec![
+            Span::styled(if selected { " ▸ " } else { "   " }, colors.text_warning()),
+            Span::styled(
+                warning.title.clone(),
+                if selected {
+                    colors.selected()
+                } else {
+                    colors.text_warning()
+                },
+            ),
+        ]));
+        lines.push(Line::from(Span::styled(
+            format!("   {}", warning.detail),
+            colors.text_secondary(),
+        )));
+    }
+
+    let warnings = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(format!(" {} Warnings ", glyph(state, "⚠", "!")))
+            .title_style(colors.text_warning())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ d dismiss │ Esc close ").centered()),
+    );
+    frame.render_widget(warnings, area);
+}
+
+/// Render list picker for where a deleted list's tasks go
+fn render_list_target_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(40, 50, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = state
+        .move_list_candidates()
+        .into_iter()
+        .enumerate()
+        .map(|(i, list)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(format!("{} ", list_icon(state, &list.icon)), style),
+                Span::styled(&list.name, style),
+            ]))
+        })
+        .collect();
+
+    let picker = List::new(items).block(
+        Block::default()
+            .title(match state.confirm_usage {
+                1 => " Move its task to ".to_string(),
+                n => format!(" Move its {} tasks to ", n),
+            })
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ↵ choose │ Esc back ").centered()),
+    );
+
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
+
+/// Render tag picker for moving a deleted tag's tasks
+fn render_reassign_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(40, 50, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = state
+        .reassign_candidates()
+        .into_iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            let tag_color = parse_hex_color(&tag.color)
+                .map(|c| state.fit_color(c))
+                .unwrap_or(colors.accent);
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(glyph(state, "● ", "# "), Style::default().fg(tag_color)),
+                Span::styled(&tag.name, style),
+            ]))
+        })
+        .collect();
+
+    let picker = List::new(items).block(
+        Block::default()
+            .title(match state.confirm_usage {
+                1 => " Move 1 task to ".to_string(),
+                n => format!(" Move {} tasks to ", n),
+            })
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ↵ move and delete │ Esc back ").centered()),
+    );
+
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
+
+/// Render tag picker for retagging marked tasks
+fn render_tag_picker(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(40, 50, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let total = state.marked_tasks.len();
+    let items: Vec<ListItem> = state
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            let selected = i == state.picker_index;
+            let style = if selected {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+            let tagged = state.marked_with_tag(tag.id);
+            let checkbox = if tagged == 0 || tagged == total {
+                checkbox(state, tagged > 0)
+            } else {
+                glyph(state, "◩", "[-]")
+            };
+            let tag_color = parse_hex_color(&tag.color)
+                .map(|c| state.fit_color(c))
+                .unwrap_or(colors.accent);
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(format!("{} ", checkbox), colors.text_success()),
+                Span::styled(glyph(state, "● ", "# "), Style::default().fg(tag_color)),
+                Span::styled(&tag.name, style),
+            ]))
+        })
+        .collect();
+
+    let picker = List::new(items).block(
+        Block::default()
+            .title(format!(" Tags for {} tasks ", total))
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ navigate │ ␣ toggle │ Esc done ").centered()),
+    );
+
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(picker, area, &mut list_state);
+}
+
+/// Render the task filter popup
+fn render_filter_popup(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = frame.area();
+
+    let popup_width = 50u16.min(area.width.saturating_sub(4));
+    let popup_height = 8u16.min(area.height.saturating_sub(4));
+    let popup_area = Rect {
+        x: area.x + (area.width - popup_width) / 2,
+        y: area.y + (area.height - popup_height) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    frame.render_widget(Clear, popup_area);
+
+    let filter = &state.filter;
+    let items: Vec<ListItem> = FilterRow::all()
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let selected = i == state.filter_row;
+            let style = if selected {
+                colors.selected().add_modifier(Modifier::BOLD)
+            } else {
+                colors.text()
+            };
+            let value = match row {
+                FilterRow::Tag => filter
+                    .tag_id
+                    .and_then(|id| state.tags.iter().find(|t| t.id == id))
+                    .map(|t| format!("#{}", t.name))
+                    .unwrap_or_else(|| "Any".to_string()),
+                FilterRow::Priority => filter
+                    .priority
+                    .map(|p| format!("{} {}", priority_icon(state, p), p.name()))
+                    .unwrap_or_else(|| "Any".to_string()),
+                FilterRow::Due => filter.due.name().to_string(),
+                FilterRow::Completion => filter.completion.name().to_string(),
+            };
+            let value_style = if value == "Any" {
+                colors.text_muted()
+            } else {
+                colors.text_primary()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(if selected { " ▸ " } else { "   " }, style),
+                Span::styled(format!("{:<10}", row.label()), style),
+                Span::styled(format!("◂ {} ▸", value), value_style),
+            ]))
+        })
+        .collect();
+
+    let popup = List::new(items).block(
+        Block::default()
+            .title(format!(" Filter ({} tasks) ", state.tasks.len()))
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg))
+            .title_bottom(Line::from(" ↑↓ row │ ←→ change │ F clear │ ↵ done ").centered()),
+    );
+
+    frame.render_widget(popup, popup_area);
+}
+
+/// Render task editor
+fn render_task_editor(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(60, 80, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let title = if state.mode == Mode::AddTask {
+        " New Task "
+    } else {
+        " Edit Task "
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Title input
+            Constraint::Length(5), // Description input
+            Constraint::Length(3), // URL input
+            Constraint::Length(3), // Due Date input
+            Constraint::Length(3), // Priority
+            Constraint::Length(3), // List
+            Constraint::Min(5),    // Tags (expanded)
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    // Title field
+    let title_focused = state.editor_field == EditorField::Title;
+    let title_style = if title_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+
+    // Show the correct title value depending on focus
+    let title_display = if title_focused {
+        state.input_buffer.as_str()
+    } else {
+        state.editor_title_buffer.as_str()
+    };
+
+    let title_input = Paragraph::new(title_display).block(
+        Block::default()
+            .title(" Title ")
+            .borders(Borders::ALL)
+            .border_style(title_style),
+    );
+    frame.render_widget(title_input, chunks[0]);
+
+    if title_focused && !state.editor_adding_tag {
+        frame.set_cursor_position((
+            chunks[0].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+            chunks[0].y + 1,
+        ));
+    }
+
+    // Description field
+    let desc_focused = state.editor_field == EditorField::Description;
+    let desc_style = if desc_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+    let desc_display = if desc_focused {
+        state.input_buffer.as_str()
+    } else {
+        state.editor_description_buffer.as_str()
+    };
+    let desc_inner_height = chunks[1].height.saturating_sub(2);
+    let desc_inner_width = chunks[1].width.saturating_sub(2);
+    let (desc_col, desc_row) = input::cursor_line_col(desc_display, state.cursor_pos);
+    // While editing, scroll instead of wrapping so the cursor stays where it is drawn
+    let desc_input = if desc_focused {
+        let scroll = (
+            desc_row.saturating_sub(desc_inner_height.saturating_sub(1)),
+            desc_col.saturating_sub(desc_inner_width.saturating_sub(1)),
+        );
+        frame.set_cursor_position((
+            chunks[1].x + 1 + desc_col - scroll.1,
+            chunks[1].y + 1 + desc_row - scroll.0,
+        ));
+        Paragraph::new(desc_display).scroll(scroll)
+    } else {
+        Paragraph::new(markdown::render(desc_display, &colors, &state.config.links))
+            .wrap(Wrap { trim: false })
+    };
+    let desc_input = desc_input.block(
+        Block::default()
+            .title(" Description (optional · ⇧↵ new line · ^E $EDITOR) ")
+            .borders(Borders::ALL)
+            .border_style(desc_style),
+    );
+    frame.render_widget(desc_input, chunks[1]);
+
+    // URL field
+    let url_focused = state.editor_field == EditorField::Url;
+    let url_display = if url_focused {
+        state.input_buffer.as_str()
+    } else {
+        state.editor_url_buffer.as_str()
+    };
+    let link_prefix = glyph(state, "🔗 ", "URL: ");
+    let url_input = if url_display.is_empty() && !url_focused {
+        Paragraph::new("https://…").style(colors.text_muted())
+    } else {
+        Paragraph::new(Line::from(vec![
+            Span::styled(link_prefix, colors.text_info()),
+            Span::styled(url_display, colors.text()),
+        ]))
+    };
+    let url_input = url_input.block(
+        Block::default()
+            .title(" URL (optional) ")
+            .borders(Borders::ALL)
+            .border_style(if url_focused {
+                colors.block_focus()
+            } else {
+                colors.block()
+            }),
+    );
+    frame.render_widget(url_input, chunks[2]);
+
+    if url_focused {
+        // Offset by the border and the width of the link prefix
+        frame.set_cursor_position((
+            chunks[2].x
+                + input::cursor_col(&state.input_buffer, state.cursor_pos)
+                + 1
+                + Line::from(link_prefix).width() as u16,
+            chunks[2].y + 1,
+        ));
+    }
+
+    // Due Date field
+    let due_focused = state.editor_field == EditorField::DueDate;
+    let due_style = if due_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+    let due_display = if due_focused {
+        state.input_buffer.as_str()
+    } else {
+        state.editor_due_date_buffer.as_str()
+    };
+    let due_placeholder = if due_display.is_empty() {
+        "YYYY-MM-DD, tomorrow, fri, 3d..."
+    } else {
+        due_display
+    };
+    let due_input = Paragraph::new(due_placeholder)
+        .style(if due_display.is_empty() && !due_focused {
+            colors.text_muted()
+        } else {
+            colors.text()
+        })
+        .block(
+            Block::default()
+                .title(" Due Date (optional, ↑↓ to pick) ")
+                .borders(Borders::ALL)
+                .border_style(due_style),
+        );
+    frame.render_widget(due_input, chunks[3]);
+
+    if due_focused && !state.editor_adding_tag {
+        frame.set_cursor_position((
+            chunks[3].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+            chunks[3].y + 1,
+        ));
+    }
+
+    // Priority field
+    let priority_focused = state.editor_field == EditorField::Priority;
+    let priority_style = if priority_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+    let priority_text = format!(
+        "{} {}",
+        priority_icon(state, state.editor_priority),
+        state.editor_priority.name()
+    );
+    let priority_input = Paragraph::new(priority_text).block(
+        Block::default()
+            .title(" Priority (j/k to change) ")
+            .borders(Borders::ALL)
+            .border_style(priority_style),
+    );
+    frame.render_widget(priority_input, chunks[4]);
+
+    // List field
+    let list_focused = state.editor_field == EditorField::List;
+    let list_style = if list_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+    let list_text = state
+        .lists
+        .get(state.editor_list_index)
+        .map(|l| format!("{} {}", list_icon(state, &l.icon), l.name))
+        .unwrap_or_else(|| "Inbox".to_string());
+    let list_input = Paragraph::new(list_text).block(
+        Block::default()
+            .title(" List (j/k to change) ")
+            .borders(Borders::ALL)
+            .border_style(list_style),
+    );
+    frame.render_widget(list_input, chunks[5]);
+
+    // Tags field - show as selectable list
+    let tags_focused = state.editor_field == EditorField::Tags;
+    let tags_style = if tags_focused {
+        colors.block_focus()
+    } else {
+        colors.block()
+    };
+
+    let mut tag_items: Vec<ListItem> = state
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            let is_selected = state.editor_tag_indices.contains(&i);
+            let is_cursor = tags_focused && i == state.editor_tag_cursor;
+
+            let checkbox = checkbox(state, is_selected);
+            let marker = if is_cursor { "► " } else { "  " };
+
+            let style = if is_cursor {
+                colors.selected()
+            } else {
+                colors.text()
+            };
+
+            let tag_color = parse_hex_color(&tag.color)
+                .map(|c| state.fit_color(c))
+                .unwrap_or(colors.accent);
+
+            ListItem::new(Line::from(vec![
                 Span::styled(marker, style),
                 Span::styled(
                     format!("{} ", checkbox),
@@ -1043,7 +2151,7 @@ fn render_task_editor(frame: &mut Frame, state: &AppState) {
                         colors.text_muted()
                     },
                 ),
-                Span::styled("● ", Style::default().fg(tag_color)),
+                Span::styled(glyph(state, "● ", "# "), Style::default().fg(tag_color)),
                 Span::styled(&tag.name, style),
             ]))
         })
@@ -1056,43 +2164,249 @@ fn render_task_editor(frame: &mut Frame, state: &AppState) {
     } else {
         colors.text_muted()
     };
-    let add_marker = if add_new_cursor { "► " } else { "  " };
+    let add_marker = if add_new_cursor { "► " } else { "  " };
+
+    if state.editor_adding_tag {
+        // Show input field for new tag
+        tag_items.push(ListItem::new(Line::from(vec![
+            Span::styled(add_marker, add_style),
+            Span::styled("+ ", colors.text_success()),
+            Span::styled(&state.editor_new_tag_buffer, colors.text()),
+            Span::styled("_", colors.text_primary()), // cursor
+        ])));
+    } else {
+        tag_items.push(ListItem::new(Line::from(vec![
+            Span::styled(add_marker, add_style),
+            Span::styled("+ Add new tag...", add_style),
+        ])));
+    }
+
+    let tags_list = List::new(tag_items).block(
+        Block::default()
+            .title(" Tags (Space: toggle, n: new) ")
+            .borders(Borders::ALL)
+            .border_style(tags_style),
+    );
+    frame.render_widget(tags_list, chunks[6]);
+
+    // Help text
+    let help_text = if state.editor_adding_tag {
+        "Enter: save tag │ Esc: cancel"
+    } else {
+        "Tab: next field │ Enter: save │ Esc: cancel"
+    };
+    let help = Paragraph::new(help_text)
+        .style(colors.text_muted())
+        .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[7]);
+
+    // Outer block
+    let outer = Block::default()
+        .title(title)
+        .title_style(colors.text_primary())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg));
+    frame.render_widget(outer, area);
+
+    if due_focused && !state.editor_adding_tag {
+        render_date_picker(frame, state, chunks[3]);
+    }
+}
+
+/// Render the due-date calendar below (or above) the due date field
+fn render_date_picker(frame: &mut Frame, state: &AppState, anchor: Rect) {
+    use chrono::{Datelike, Duration, Local, NaiveDate};
+
+    let colors = state.colors();
+    let full = frame.area();
+    let width = 28u16;
+    let height = 10u16;
+    if full.width < width || full.height < height {
+        return;
+    }
+    let x = anchor
+        .right()
+        .saturating_sub(width + 1)
+        .max(anchor.x)
+        .min(full.right() - width);
+    let y = if anchor.bottom() + height <= full.bottom() {
+        anchor.bottom()
+    } else {
+        anchor.y.saturating_sub(height)
+    };
+    let area = Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+
+    let today = Local::now().date_naive();
+    let picked = state.editor_due_date();
+    let shown = picked.unwrap_or(today);
+    let first = NaiveDate::from_ymd_opt(shown.year(), shown.month(), 1).unwrap_or(shown);
+    let week_start = state.config.week_starts_on;
+    let start = first - Duration::days(first.weekday().days_since(week_start) as i64);
+
+    let header: String = state
+        .config
+        .week_days()
+        .map(|d| format!(" {}", &d.to_string()[..2]))
+        .collect();
+    let mut lines = vec![Line::from(Span::styled(header, colors.text_muted()))];
+    let business = crate::holidays::business_days();
+    for week in 0..6 {
+        let mut spans = Vec::new();
+        for weekday in 0..7 {
+            let day = start + Duration::days(week * 7 + weekday);
+            let style = if Some(day) == picked {
+                colors.selected().add_modifier(Modifier::BOLD)
+            } else if day == today {
+                colors.text_primary().add_modifier(Modifier::UNDERLINED)
+            } else if day.month() != shown.month() {
+                colors.text_muted()
+            } else if !business.is_business_day(day) {
+                colors.text_muted().add_modifier(Modifier::DIM)
+            } else {
+                colors.text()
+            };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(format!("{:>2}", day.day()), style));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(Span::styled(
+        " ^T tdy ^N tmw ^B bd ^W wk",
+        colors.key_hint(),
+    )));
+
+    frame.render_widget(Clear, area);
+    let calendar = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" {} ", shown.format("%B %Y")))
+            .title_style(colors.text_primary())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(colors.block_focus())
+            .style(Style::default().bg(colors.bg_secondary)),
+    );
+    frame.render_widget(calendar, area);
+}
+
+/// Render simple name editor (for lists and tags)
+fn render_simple_editor(frame: &mut Frame, state: &AppState, item_type: &str) {
+    let colors = state.colors();
+    let is_list = matches!(state.mode, Mode::AddList | Mode::EditList);
+    let full = frame.area();
+    let area = centered_rect(50, 25, full);
+    // Lists have color and checklist rows too; make sure every field fits on
+    // short terminals
+    let height = area
+        .height
+        .max(if is_list { 13 } else { 6 })
+        .min(full.height);
+    let area = Rect {
+        y: full.y + (full.height - height) / 2,
+        height,
+        ..area
+    };
+
+    frame.render_widget(Clear, area);
+
+    let title = if state.mode == Mode::AddList || state.mode == Mode::AddTag {
+        format!(" New {} ", item_type)
+    } else {
+        format!(" Edit {} ", item_type)
+    };
 
-    if state.editor_adding_tag {
-        // Show input field for new tag
-        tag_items.push(ListItem::new(Line::from(vec![
-            Span::styled(add_marker, add_style),
-            Span::styled("+ ", colors.text_success()),
-            Span::styled(&state.editor_new_tag_buffer, colors.text()),
-            Span::styled("_", colors.text_primary()), // cursor
-        ])));
-    } else {
-        tag_items.push(ListItem::new(Line::from(vec![
-            Span::styled(add_marker, add_style),
-            Span::styled("+ Add new tag...", add_style),
-        ])));
-    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),                           // Name input
+            Constraint::Length(if is_list { 3 } else { 0 }), // List color
+            Constraint::Length(if is_list { 3 } else { 0 }), // Checklist switch
+            Constraint::Min(0),                              // Spacer
+            Constraint::Length(1),                           // Help
+        ])
+        .split(area);
 
-    let tags_list = List::new(tag_items).block(
+    let color_focused = is_list && state.editor_field == EditorField::Color;
+    let checklist_focused = is_list && state.editor_field == EditorField::Checklist;
+    let field_style = |focused: bool| {
+        if focused {
+            colors.block_focus()
+        } else {
+            colors.block()
+        }
+    };
+
+    let input = Paragraph::new(state.input_buffer.as_str()).block(
         Block::default()
-            .title(" Tags (Space: toggle, n: new) ")
+            .title(" Name ")
             .borders(Borders::ALL)
-            .border_style(tags_style),
+            .border_style(field_style(!color_focused && !checklist_focused)),
     );
-    frame.render_widget(tags_list, chunks[5]);
+    frame.render_widget(input, chunks[0]);
 
-    // Help text
-    let help_text = if state.editor_adding_tag {
-        "Enter: save tag │ Esc: cancel"
-    } else {
+    if is_list {
+        let swatch = match state.editor_list_color.as_deref() {
+            Some(hex) => {
+                let color = named_color(state, hex).unwrap_or(colors.fg);
+                Line::from(vec![
+                    Span::styled(glyph(state, "◂ ", "< "), colors.text_muted()),
+                    Span::styled(glyph(state, "● ", "# "), Style::default().fg(color)),
+                    Span::styled(hex, Style::default().fg(color)),
+                    Span::styled(glyph(state, " ▸", " >"), colors.text_muted()),
+                ])
+            }
+            None => Line::from(Span::styled(
+                glyph(state, "◂ Theme default ▸", "< Theme default >"),
+                colors.text_muted(),
+            )),
+        };
+        let color_input = Paragraph::new(swatch).block(
+            Block::default()
+                .title(" Color (←→ to change) ")
+                .borders(Borders::ALL)
+                .border_style(field_style(color_focused)),
+        );
+        frame.render_widget(color_input, chunks[1]);
+
+        let switch = Line::from(vec![
+            Span::styled(
+                format!("{} ", checkbox(state, state.editor_list_checklist)),
+                colors.text_success(),
+            ),
+            Span::styled("Can be reset to unchecked in one go", colors.text()),
+        ]);
+        let checklist_input = Paragraph::new(switch).block(
+            Block::default()
+                .title(" Checklist (Space to toggle) ")
+                .borders(Borders::ALL)
+                .border_style(field_style(checklist_focused)),
+        );
+        frame.render_widget(checklist_input, chunks[2]);
+    }
+
+    if !color_focused && !checklist_focused {
+        frame.set_cursor_position((
+            chunks[0].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+            chunks[0].y + 1,
+        ));
+    }
+
+    let help = Paragraph::new(if is_list {
         "Tab: next field │ Enter: save │ Esc: cancel"
-    };
-    let help = Paragraph::new(help_text)
-        .style(colors.text_muted())
-        .alignment(Alignment::Center);
-    frame.render_widget(help, chunks[6]);
+    } else {
+        "Enter: save │ Esc: cancel"
+    })
+    .style(colors.text_muted())
+    .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[4]);
 
-    // Outer block
     let outer = Block::default()
         .title(title)
         .title_style(colors.text_primary())
@@ -1103,78 +2417,709 @@ fn render_task_editor(frame: &mut Frame, state: &AppState) {
     frame.render_widget(outer, area);
 }
 
-/// Render simple name editor (for lists and tags)
-fn render_simple_editor(frame: &mut Frame, state: &AppState, item_type: &str) {
-    let colors = state.theme.colors();
-    let area = centered_rect(50, 25, frame.area());
+/// Render the quick-add prompt with a preview of the parsed task
+fn render_quick_add(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let full = frame.area();
+    let width = 70u16.min(full.width.saturating_sub(4));
+    let height = 8u16.min(full.height);
+    let area = Rect {
+        x: full.x + (full.width.saturating_sub(width)) / 2,
+        y: full.y + (full.height.saturating_sub(height)) / 3,
+        width,
+        height,
+    };
 
     frame.render_widget(Clear, area);
 
-    let title = if state.mode == Mode::AddList || state.mode == Mode::AddTag {
-        format!(" New {} ", item_type)
-    } else {
-        format!(" Edit {} ", item_type)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Input
+            Constraint::Length(1), // Preview
+            Constraint::Min(0),    // Spacer
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let outer = Block::default()
+        .title(glyph(state, " ⚡ Quick Add ", " Quick Add "))
+        .title_style(colors.text_primary())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg));
+    frame.render_widget(outer, area);
+
+    let input = Paragraph::new(state.input_buffer.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(colors.block_focus()),
+    );
+    frame.render_widget(input, chunks[0]);
+    frame.set_cursor_position((
+        chunks[0].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+        chunks[0].y + 1,
+    ));
+
+    let parsed = crate::quickadd::QuickAdd::parse(&state.input_buffer);
+    let mut preview = vec![Span::styled(" → ", colors.text_muted())];
+    if let Some(priority) = parsed.priority {
+        preview.push(Span::styled(
+            format!("{} ", priority_icon(state, priority)),
+            colors.priority_style(priority),
+        ));
+    }
+    preview.push(Span::styled(parsed.title, colors.text()));
+    if let Some(list) = parsed.list {
+        preview.push(Span::styled(format!("  @{}", list), colors.text_info()));
+    }
+    for tag in parsed.tags {
+        preview.push(Span::styled(format!("  #{}", tag), colors.text_secondary()));
+    }
+    if let Some(due) = parsed.due_date {
+        preview.push(Span::styled(
+            format!(
+                "  {}{}",
+                glyph(state, "📅", "due:"),
+                state.config.format_date(due.date_naive())
+            ),
+            colors.text_warning(),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(preview)), chunks[1]);
+
+    let help = Paragraph::new("#tag  @list  !high  due:tomorrow │ Enter: add │ Esc: cancel")
+        .style(colors.text_muted())
+        .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[3]);
+}
+
+/// Render the one-line comment prompt for the selected task
+fn render_comment_prompt(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let full = frame.area();
+    let width = 70u16.min(full.width.saturating_sub(4));
+    let height = 6u16.min(full.height);
+    let area = Rect {
+        x: full.x + (full.width.saturating_sub(width)) / 2,
+        y: full.y + (full.height.saturating_sub(height)) / 3,
+        width,
+        height,
     };
 
+    frame.render_widget(Clear, area);
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(3), // Name input
-            Constraint::Min(1),    // Spacer
+            Constraint::Length(3), // Input
             Constraint::Length(1), // Help
         ])
         .split(area);
 
+    let title = state.selected_task().map_or("", |t| t.title.as_str());
+    let outer = Block::default()
+        .title(format!(" {}Comment on {} ", glyph(state, "💬 ", ""), title))
+        .title_style(colors.text_primary())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg));
+    frame.render_widget(outer, area);
+
+    let input = Paragraph::new(state.input_buffer.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(colors.block_focus()),
+    );
+    frame.render_widget(input, chunks[0]);
+    frame.set_cursor_position((
+        chunks[0].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+        chunks[0].y + 1,
+    ));
+
+    let help = Paragraph::new("Enter: add │ Esc: cancel")
+        .style(colors.text_muted())
+        .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[1]);
+}
+
+/// Render the prompt for the date to defer tasks to, with the date it reads as
+fn render_defer_prompt(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let full = frame.area();
+    let width = 70u16.min(full.width.saturating_sub(4));
+    let height = 7u16.min(full.height);
+    let area = Rect {
+        x: full.x + (full.width.saturating_sub(width)) / 2,
+        y: full.y + (full.height.saturating_sub(height)) / 3,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Input
+            Constraint::Length(1), // Preview
+            Constraint::Length(1), // Help
+        ])
+        .split(area);
+
+    let targets = state.marked_or_selected();
+    let title = match targets.as_slice() {
+        [task] => format!(" {}Defer {} ", glyph(state, "⏭ ", ""), task.title),
+        tasks => format!(" {}Defer {} tasks ", glyph(state, "⏭ ", ""), tasks.len()),
+    };
+    let outer = Block::default()
+        .title(title)
+        .title_style(colors.text_primary())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .style(Style::default().bg(colors.bg));
+    frame.render_widget(outer, area);
+
     let input = Paragraph::new(state.input_buffer.as_str()).block(
         Block::default()
-            .title(" Name ")
             .borders(Borders::ALL)
             .border_style(colors.block_focus()),
     );
     frame.render_widget(input, chunks[0]);
+    frame.set_cursor_position((
+        chunks[0].x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+        chunks[0].y + 1,
+    ));
+
+    let due = targets
+        .first()
+        .and_then(|t| crate::quickadd::parse_defer(&state.input_buffer, t.due_date));
+    let preview = match due {
+        Some(due) => Span::styled(
+            format!(" → due {}", state.config.format_date(due.date_naive())),
+            colors.text_warning(),
+        ),
+        None => Span::styled(" → ?", colors.text_muted()),
+    };
+    frame.render_widget(Paragraph::new(Line::from(preview)), chunks[1]);
+
+    let help = Paragraph::new("monday  tonight  3d  +1w │ Enter: defer │ Esc: cancel")
+        .style(colors.text_muted())
+        .alignment(Alignment::Center);
+    frame.render_widget(help, chunks[2]);
+}
+
+/// Create a centered rectangle
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// Render focus mode: one task full-screen with its notes, checklist and timer
+fn render_focus(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.colors();
+    let Some(task) = &state.focus_task else {
+        return;
+    };
+
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .title(" ◎ Focus ")
+        .title_style(colors.text_primary())
+        .title_bottom(
+            Line::from(" Space: complete │ j/k ↵: checklist │ p: pause │ r: reset │ Esc: exit ")
+                .style(colors.text_muted())
+                .centered(),
+        );
+    let inner = outer.inner(area).inner(Margin {
+        vertical: 1,
+        horizontal: 4,
+    });
+    frame.render_widget(outer, area);
+
+    let [header_area, _, body_area] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+
+    // Title, details and timer
+    let title_style = if task.completed {
+        colors
+            .text_muted()
+            .add_modifier(Modifier::BOLD | Modifier::CROSSED_OUT)
+    } else {
+        colors.text().add_modifier(Modifier::BOLD)
+    };
+    let mut meta = Vec::new();
+    if let Some(list) = state.lists.iter().find(|l| l.id == task.list_id) {
+        meta.push(Span::styled(
+            format!("{} {}", list_icon(state, &list.icon), list.name),
+            colors.text_muted(),
+        ));
+    }
+    if let Some(due) = task.due_date {
+        meta.push(Span::styled(
+            format!(
+                "  {} {}",
+                glyph(state, "📅", "due:"),
+                state.config.format_date(due.date_naive())
+            ),
+            colors.text_warning(),
+        ));
+    }
+    for tag in state.tags.iter().filter(|t| task.tag_ids.contains(&t.id)) {
+        meta.push(Span::styled(
+            format!("  #{}", tag.name),
+            colors.text_secondary(),
+        ));
+    }
+
+    let elapsed = state.focus_timer.elapsed().as_secs();
+    let timer = format!(
+        "{} {:02}:{:02}:{:02}",
+        if state.focus_timer.is_running() {
+            glyph(state, "⏱", ">")
+        } else {
+            glyph(state, "⏸", "||")
+        },
+        elapsed / 3600,
+        elapsed / 60 % 60,
+        elapsed % 60
+    );
+
+    let mut title = vec![Span::styled(
+        format!("{} ", priority_icon(state, task.priority)),
+        colors.priority_style(task.priority),
+    )];
+    title.extend(linked(state, &task.title, title_style));
+    let header = vec![
+        Line::from(title),
+        Line::from(meta),
+        Line::from(""),
+        Line::from(Span::styled(timer, colors.text_primary())),
+    ];
+    frame.render_widget(
+        Paragraph::new(header).alignment(Alignment::Center),
+        header_area,
+    );
+
+    // Notes, with the checklist cursor highlighted
+    let description = task.description.clone().unwrap_or_default();
+    let checklist = markdown::checklist(&description);
+    let mut body = markdown::render(&description, &colors, &state.config.links);
+    let cursor = state.focus_item.min(checklist.len().saturating_sub(1));
+    if let Some(line) = checklist.get(cursor).and_then(|&(i, _)| body.get_mut(i)) {
+        *line = line.clone().patch_style(colors.selected());
+    }
+    if !checklist.is_empty() {
+        let done = checklist.iter().filter(|(_, checked)| *checked).count();
+        body.insert(
+            0,
+            Line::from(Span::styled(
+                format!("Checklist {}/{}", done, checklist.len()),
+                colors.text_muted(),
+            )),
+        );
+        body.insert(1, Line::from(""));
+    }
+    if body.is_empty() {
+        body.push(Line::from(Span::styled(
+            "No notes. Add a description (with - [ ] items for a checklist) in the editor.",
+            colors.text_muted(),
+        )));
+    }
+    frame.render_widget(Paragraph::new(body).wrap(Wrap { trim: false }), body_area);
+}
+
+/// Render the timeline: a bar per dated task from creation to due date, by list
+fn render_timeline(frame: &mut Frame, state: &AppState, area: Rect) {
+    use chrono::Datelike;
+
+    let colors = state.colors();
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .title(" Timeline ")
+        .title_style(colors.text_primary())
+        .title_bottom(
+            Line::from(" h/l: week │ H/L: month │ t: today │ j/k: scroll │ Esc: exit ")
+                .style(colors.text_muted())
+                .centered(),
+        );
+    let inner = outer.inner(area);
+    frame.render_widget(outer, area);
+
+    let rows = timeline::rows(&state.open_tasks, &state.lists);
+    if rows.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No open tasks with a due date.")
+                .style(colors.text_muted())
+                .alignment(Alignment::Center),
+            inner,
+        );
+        return;
+    }
+
+    let label_width = (inner.width as usize / 3).clamp(10, 28);
+    let days = (inner.width as usize).saturating_sub(label_width + 1);
+    let from = state.timeline_from;
+    let today = chrono::Local::now().date_naive();
+    let date_at = |column: usize| from + chrono::Duration::days(column as i64);
+
+    // Month names where a month starts, and day numbers at each week start
+    let mut months = vec![' '; days];
+    let mut weeks = vec![' '; days];
+    for column in 0..days {
+        let date = date_at(column);
+        if column == 0 || date.day() == 1 {
+            for (i, c) in date.format("%b %Y").to_string().chars().enumerate() {
+                if let Some(cell) = months.get_mut(column + i) {
+                    *cell = c;
+                }
+            }
+        }
+        if date.weekday() == state.config.week_starts_on {
+            for (i, c) in date.day().to_string().chars().enumerate() {
+                if let Some(cell) = weeks.get_mut(column + i) {
+                    *cell = c;
+                }
+            }
+        }
+    }
+    let pad = " ".repeat(label_width + 1);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("{}{}", pad, months.iter().collect::<String>()),
+            colors.text_primary(),
+        )),
+        Line::from(Span::styled(
+            format!("{}{}", pad, weeks.iter().collect::<String>()),
+            colors.text_muted(),
+        )),
+    ];
+
+    let today_column = (today >= from && today < date_at(days)).then(|| (today - from).num_days());
+    let visible = (inner.height as usize).saturating_sub(lines.len());
+    let scroll = state
+        .timeline_scroll
+        .min(rows.len().saturating_sub(visible));
+    for row in rows.iter().skip(scroll).take(visible) {
+        match row {
+            Row::List(name) => lines.push(Line::from(Span::styled(
+                truncate(name, label_width),
+                colors.text_primary().add_modifier(Modifier::BOLD),
+            ))),
+            Row::Task { task, start, end } => {
+                let style = if *end < today {
+                    colors.text_error()
+                } else {
+                    colors.priority_style(task.priority)
+                };
+                let label = truncate(&task.title, label_width.saturating_sub(2));
+                let mut spans = vec![Span::styled(
+                    format!("  {:<width$} ", label, width = label_width - 2),
+                    colors.text(),
+                )];
+                let bar = timeline::bar(*start, *end, from, days);
+                for column in 0..days {
+                    let span = match bar {
+                        Some((first, last)) if (first..=last).contains(&column) => {
+                            let cell = if column == first && *start < from {
+                                glyph(state, "◀", "<")
+                            } else if column == last && *end >= date_at(days) {
+                                glyph(state, "▶", ">")
+                            } else {
+                                glyph(state, "█", "#")
+                            };
+                            Span::styled(cell, style)
+                        }
+                        _ if today_column == Some(column as i64) => {
+                            Span::styled(glyph(state, "│", "|"), colors.text_muted())
+                        }
+                        _ => Span::raw(" "),
+                    };
+                    spans.push(span);
+                }
+                lines.push(Line::from(spans));
+            }
+        }
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Render the week planner: the backlog, then a column per day
+fn render_planner(frame: &mut Frame, state: &AppState, area: Rect) {
+    let colors = state.colors();
+    let from = state.planner_from;
+    let to = from + chrono::Duration::days(6);
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(colors.block_focus())
+        .title(format!(
+            " Week planner: {} – {} ",
+            state.config.format_date(from),
+            state.config.format_date(to)
+        ))
+        .title_style(colors.text_primary())
+        .title_bottom(
+            Line::from(
+                " hjkl: move │ H/L: task to prev/next day │ 1-7/0: day/backlog │ [/]: week │ t: this week │ u: undo │ Esc: exit ",
+            )
+            .style(colors.text_muted())
+            .centered(),
+        );
+    let inner = outer.inner(area);
+    frame.render_widget(outer, area);
+
+    let [board_area, status_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+    if let Some(message) = &state.status_message {
+        frame.render_widget(
+            Paragraph::new(message.as_str()).style(colors.text_info()),
+            status_area,
+        );
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let board = state.planner_columns();
+    let areas =
+        Layout::horizontal([Constraint::Ratio(1, planner::COLUMNS as u32); planner::COLUMNS])
+            .split(board_area);
+    let (cursor_column, cursor_row) = state.planner_cursor;
+    for (column, (tasks, &column_area)) in board.iter().zip(areas.iter()).enumerate() {
+        let day = planner::day(column, from);
+        let title = match day {
+            None => format!(" Backlog {} ", tasks.len()),
+            Some(day) => format!(" {} {} ", day.format("%a %-d"), tasks.len()),
+        };
+        let focused = column == cursor_column;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(if focused {
+                colors.block_focus()
+            } else {
+                colors.block()
+            })
+            .title(title)
+            .title_style(if day == Some(today) {
+                colors.text_primary().add_modifier(Modifier::BOLD)
+            } else if day.is_some_and(|d| d < today) {
+                colors.text_muted()
+            } else {
+                colors.text()
+            });
+        let list_area = block.inner(column_area);
+        frame.render_widget(block, column_area);
+
+        let width = list_area.width as usize;
+        let height = list_area.height as usize;
+        let skip = if focused {
+            (cursor_row + 1).saturating_sub(height)
+        } else {
+            0
+        };
+        let lines: Vec<Line> = tasks
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(height)
+            .map(|(row, task)| {
+                let overdue = task.due_date.is_some_and(|d| d.date_naive() < today);
+                let style = if focused && row == cursor_row {
+                    colors.selected()
+                } else if overdue {
+                    colors.text_error()
+                } else {
+                    colors.priority_style(task.priority)
+                };
+                Line::from(Span::styled(truncate(&task.title, width), style))
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), list_area);
+    }
+}
 
-    frame.set_cursor_position((chunks[0].x + state.cursor_pos as u16 + 1, chunks[0].y + 1));
+/// Render the fuzzy jump finder
+fn render_jump(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(60, 60, frame.area());
 
-    let help = Paragraph::new("Enter: save │ Esc: cancel")
-        .style(colors.text_muted())
-        .alignment(Alignment::Center);
-    frame.render_widget(help, chunks[2]);
+    frame.render_widget(Clear, area);
 
     let outer = Block::default()
-        .title(title)
+        .title(if state.jump_blocker_for.is_some() {
+            format!(
+                " {}Blocked by… ({}) ",
+                glyph(state, "⛓ ", ""),
+                state.jump_results.len()
+            )
+        } else {
+            format!(" ' Jump to task ({}) ", state.jump_results.len())
+        })
         .title_style(colors.text_primary())
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(colors.block_focus())
-        .style(Style::default().bg(colors.bg));
+        .style(Style::default().bg(colors.bg))
+        .title_bottom(Line::from(" ↑↓ navigate │ ↵ jump │ Esc cancel ").centered());
+    let inner = outer.inner(area);
     frame.render_widget(outer, area);
+
+    let [input_area, results_area] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(inner);
+
+    let input_widget = Paragraph::new(state.input_buffer.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(colors.block_focus()),
+    );
+    frame.render_widget(input_widget, input_area);
+    frame.set_cursor_position((
+        input_area.x + input::cursor_col(&state.input_buffer, state.cursor_pos) + 1,
+        input_area.y + 1,
+    ));
+
+    let items: Vec<ListItem> = state
+        .jump_results
+        .iter()
+        .enumerate()
+        .map(|(i, task)| {
+            let selected = i == state.picker_index;
+            let base = if selected {
+                colors.selected()
+            } else if task.completed {
+                colors.text_muted()
+            } else {
+                colors.text()
+            };
+            let matched = crate::fuzzy::fuzzy_match(&state.input_buffer, &task.title)
+                .map(|m| m.indices)
+                .unwrap_or_default();
+
+            let mut spans = vec![Span::styled(
+                format!(" {} ", checkbox(state, task.completed)),
+                base,
+            )];
+            spans.extend(task.title.chars().enumerate().map(|(ci, c)| {
+                let style = if matched.contains(&ci) {
+                    base.fg(colors.accent).add_modifier(Modifier::BOLD)
+                } else {
+                    base
+                };
+                Span::styled(c.to_string(), style)
+            }));
+            if let Some(list) = state.lists.iter().find(|l| l.id == task.list_id) {
+                spans.push(Span::styled(
+                    format!("  {} {}", list_icon(state, &list.icon), list.name),
+                    colors.text_muted(),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let mut list_state = ListState::default().with_selected(Some(state.picker_index));
+    frame.render_stateful_widget(List::new(items), results_area, &mut list_state);
 }
 
-/// Create a centered rectangle
-fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(area);
+/// Priority shown before a task title: the icon unless compact, plus a text
+/// label in accessible mode so priority doesn't rely on color alone
+fn priority_marker(state: &AppState, priority: Priority) -> String {
+    let compact = state.config.display.density == Density::Compact;
+    let icon = priority_icon(state, priority);
+    match (compact, state.config.display.accessible) {
+        (false, false) => format!("{} ", icon),
+        (false, true) => format!("{} {} ", icon, priority.label()),
+        (true, true) => format!("{} ", priority.label()),
+        (true, false) => String::new(),
+    }
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+/// `unicode` when the terminal can draw it, `ascii` otherwise
+fn glyph(state: &AppState, unicode: &'static str, ascii: &'static str) -> &'static str {
+    if state.unicode() { unicode } else { ascii }
+}
+
+/// `text` with its URLs and issue references underlined in the link color
+fn linked<'a>(state: &AppState, text: &'a str, style: Style) -> Vec<Span<'a>> {
+    let link = style
+        .patch(state.colors().text_info())
+        .add_modifier(Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut at = 0;
+    for (range, _) in crate::autolink::find(text, &state.config.links) {
+        if range.start > at {
+            spans.push(Span::styled(&text[at..range.start], style));
+        }
+        spans.push(Span::styled(&text[range.clone()], link));
+        at = range.end;
+    }
+    if at < text.len() || spans.is_empty() {
+        spans.push(Span::styled(&text[at..], style));
+    }
+    spans
+}
+
+fn checkbox(state: &AppState, checked: bool) -> &'static str {
+    if checked {
+        glyph(state, "☑", "[x]")
+    } else {
+        glyph(state, "☐", "[ ]")
+    }
+}
+
+fn priority_icon(state: &AppState, priority: Priority) -> &'static str {
+    glyph(state, priority.icon(), priority.ascii_icon())
+}
+
+fn view_icon(state: &AppState, view: View) -> &'static str {
+    glyph(state, view.icon(), view.ascii_icon())
+}
+
+fn smart_icon(state: &AppState, smart: &SmartList) -> &'static str {
+    glyph(state, smart.icon(), smart.ascii_icon())
+}
+
+/// A list's icon, or a plain marker for an emoji the terminal can't draw
+fn list_icon<'a>(state: &AppState, icon: &'a str) -> &'a str {
+    if state.unicode() || icon.is_ascii() {
+        icon
+    } else {
+        "*"
+    }
 }
 
 /// Render about dialog
 fn render_about_dialog(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = centered_rect(70, 60, frame.area());
 
     frame.render_widget(Clear, area);
@@ -1305,7 +3250,465 @@ fn render_about_dialog(frame: &mut Frame, state: &AppState) {
     frame.render_widget(paragraph, area);
 }
 
+/// Seconds until a failed sync is retried, if it will be
+fn sync_retry_wait(state: &AppState) -> Option<u64> {
+    let at = state.sync_retry_at.filter(|_| !state.auto_sync_paused())?;
+    Some(
+        at.saturating_duration_since(std::time::Instant::now())
+            .as_secs(),
+    )
+}
+
+/// Server, device and last sync, with the sync actions
+fn render_sync_panel(frame: &mut Frame, state: &AppState) {
+    let colors = state.colors();
+    let area = centered_rect(60, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let sync = &state.config.sync;
+    let status = &state.sync_status;
+    let row = |label: &'static str, value: String, style: Style| {
+        Line::from(vec![
+            Span::styled(format!(" {:<14}", label), colors.text_muted()),
+            Span::styled(value, style),
+        ])
+    };
+    let unset = || "not set".to_string();
+
+    let (device_id, device_name) = match &state.sync_device {
+        Some((id, name)) => (id.to_string(), name.clone().unwrap_or_else(unset)),
+        None => (unset(), unset()),
+    };
+    let (state_text, state_style) = if !state.is_sync_enabled() {
+        ("Not configured".to_string(), colors.text_warning())
+    } else if status.syncing {
+        match status.progress {
+            Some(p) => (
+                format!("Syncing {}/{}…", p.sent, p.total),
+                colors.text_info(),
+            ),
+            None => ("Syncing…".to_string(), colors.text_info()),
+        }
+    } else if status.last_error.is_some() {
+        ("Last sync failed".to_string(), colors.text_error())
+    } else {
+        ("Idle".to_string(), colors.text_success())
+    };
+    let auto_sync = if state.sync_paused_saved {
+        "paused until resumed (P)".to_string()
+    } else if state.sync_paused {
+        "paused for this session".to_string()
+    } else if sync.interval_secs == 0 {
+        "off (manual only)".to_string()
+    } else {
+        format!("every {}s", sync.interval_secs)
+    };
+    let last_sync = status
+        .last_sync
+        .map(|t| {
+            state
+                .config
+                .format_datetime(t.with_timezone(&chrono::Local).naive_local())
+        })
+        .unwrap_or_else(|| "never".to_string());
+
+    let mut lines = vec![
+        Line::from(""),
+        row("Status", state_text, state_style),
+        row(
+            "Server",
+            sync.server.clone().unwrap_or_else(unset),
+            colors.text_primary(),
+        ),
+        row("Device", device_name, colors.text()),
+        row("Device ID", device_id, colors.text_dim()),
+        row("Last sync", last_sync, colors.text()),
+        row(
+            "Pending",
+            format!("{} changes", status.pending_changes),
+            if status.pending_changes > 0 {
+                colors.text_warning()
+            } else {
+                colors.text()
+            },
+        ),
+        row("Auto-sync", auto_sync, colors.text()),
+        row(
+            "Conflicts",
+            sync.conflict_policy.name().to_string(),
+            colors.text(),
+        ),
+    ];
+    if status.last_error.is_some() {
+        let retry = match sync_retry_wait(state) {
+            Some(wait) => format!(
+                "in {}s (attempt {} of {})",
+                wait,
+                state.sync_failures,
+                crate::sync::MAX_SYNC_RETRIES
+            ),
+            None if state.sync_failures > crate::sync::MAX_SYNC_RETRIES => format!(
+                "gave up after {} attempts; next at the interval",
+                crate::sync::MAX_SYNC_RETRIES
+            ),
+            None => "not scheduled".to_string(),
+        };
+        lines.push(row("Retry", retry, colors.text_warning()));
+    }
+    if let Some(error) = &status.last_error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(" Last error", colors.text_muted())));
+        lines.push(Line::from(Span::styled(
+            format!(" {}", error),
+            colors.text_error(),
+        )));
+    }
+    lines.push(Line::from(""));
+    let action = |key: &'static str, label: &'static str| {
+        [
+            Span::styled(key, colors.key_hint()),
+            Span::styled(label, colors.text_muted()),
+        ]
+    };
+    lines.push(Line::from(
+        [
+            action(
+                " ↵",
+                if status.last_error.is_some() {
+                    " retry now │ "
+                } else {
+                    " sync now │ "
+                },
+            ),
+            action("f", " force full sync │ "),
+            action(
+                "p",
+                if state.sync_paused {
+                    " resume │ "
+                } else {
+                    " pause for this session │ "
+                },
+            ),
+            action("Esc", " close"),
+        ]
+        .concat(),
+    ));
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(colors.primary))
+            .style(Style::default().bg(colors.bg))
+            .title(format!(" {}Sync ", glyph(state, "☁ ", "")))
+            .title_style(
+                Style::default()
+                    .fg(colors.primary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+/// Render the stats overlay: task counts, streaks, and a heatmap of the
+/// last year's completions or a burndown of a list or tag
+fn render_stats(frame: &mut Frame, state: &AppState) {
+    use crate::stats::HEATMAP_WEEKS;
+
+    let Some((summary, heatmap)) = &state.stats else {
+        return;
+    };
+    let colors = state.colors();
+    let screen = frame.area();
+    let width = (HEATMAP_WEEKS as u16 + 10).min(screen.width);
+    let height = 21.min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(colors.primary))
+        .style(Style::default().bg(colors.bg))
+        .title(format!(" {}Stats ", glyph(state, "📊 ", "")))
+        .title_style(
+            Style::default()
+                .fg(colors.primary)
+                .add_modifier(Modifier::BOLD),
+        );
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [top, middle, bottom] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(8),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .areas(inner);
+
+    let row = |label: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!(" {:<18}", label), colors.text_muted()),
+            Span::styled(value, colors.text()),
+        ])
+    };
+    let days = |n: u32| format!("{} day{}", n, if n == 1 { "" } else { "s" });
+    let lines = vec![
+        Line::from(""),
+        row("Open", summary.open.to_string()),
+        row("Completed", summary.completed.to_string()),
+        row("This week", summary.completed_this_week.to_string()),
+        row("Past year", summary.completed_last_year.to_string()),
+        row("Current streak", days(summary.current_streak)),
+        row("Longest streak", days(summary.longest_streak)),
+    ];
+    frame.render_widget(Paragraph::new(lines), top);
+
+    let action = |key: &'static str, label: &'static str| {
+        [
+            Span::styled(key, colors.key_hint()),
+            Span::styled(label, colors.text_muted()),
+        ]
+    };
+    let hints = match &state.stats_burndown {
+        Some((name, burndown)) => {
+            render_burndown(frame, state, name, burndown, middle);
+            [
+                action(" Tab", " next list/tag │ "),
+                action("b", " heatmap │ "),
+                action("Esc", " close"),
+            ]
+            .concat()
+        }
+        None => {
+            render_heatmap(frame, state, heatmap, middle);
+            [action(" b", " burndown │ "), action("Esc", " close")].concat()
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(""), Line::from(hints)]),
+        bottom,
+    );
+}
+
+/// A column per week and a row per weekday, shaded by completions
+fn render_heatmap(
+    frame: &mut Frame,
+    state: &AppState,
+    heatmap: &crate::stats::Heatmap,
+    area: Rect,
+) {
+    use crate::stats::shade_glyph;
+
+    let colors = state.colors();
+    let unicode = state.unicode();
+    let cell_style = |shade: u8| match shade {
+        0 => colors.text_muted(),
+        1 | 2 => colors.text_success(),
+        _ => colors.text_success().add_modifier(Modifier::BOLD),
+    };
+    let mut lines = vec![Line::from(Span::styled(
+        format!("     {}", heatmap.month_labels()),
+        colors.text_muted(),
+    ))];
+    for (i, cells) in heatmap.rows.iter().enumerate() {
+        // Label every other weekday, like a contribution graph
+        let label = if i % 2 == 1 {
+            heatmap.row_day(i).to_string()
+        } else {
+            String::new()
+        };
+        let mut spans = vec![Span::styled(format!(" {:<4}", label), colors.text_muted())];
+        spans.extend(cells.iter().map(|cell| match cell {
+            Some(shade) => {
+                Span::styled(shade_glyph(*shade, unicode).to_string(), cell_style(*shade))
+            }
+            None => Span::raw(" "),
+        }));
+        lines.push(Line::from(spans));
+    }
+
+    let mut legend = vec![Span::styled("     Less ", colors.text_muted())];
+    legend.extend(
+        (0..=4)
+            .map(|shade| Span::styled(shade_glyph(shade, unicode).to_string(), cell_style(shade))),
+    );
+    legend.push(Span::styled(" More", colors.text_muted()));
+    lines.push(Line::from(legend));
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// Open tasks (burndown) and tasks done since the first day (burnup) as
+/// lines over the days of `burndown`
+fn render_burndown(
+    frame: &mut Frame,
+    state: &AppState,
+    name: &str,
+    burndown: &crate::stats::Burndown,
+    area: Rect,
+) {
+    use ratatui::symbols::Marker;
+    use ratatui::widgets::{Axis, Chart, Dataset, GraphType};
+
+    let colors = state.colors();
+    let points = |value: fn(&crate::stats::BurndownDay) -> u32| -> Vec<(f64, f64)> {
+        burndown
+            .days
+            .iter()
+            .enumerate()
+            .map(|(i, day)| (i as f64, f64::from(value(day))))
+            .collect()
+    };
+    let open = points(|day| day.open);
+    let done = points(|day| day.done);
+    let marker = if state.unicode() {
+        Marker::Braille
+    } else {
+        Marker::Dot
+    };
+    let dataset = |label: &'static str, data, style| {
+        Dataset::default()
+            .name(label)
+            .marker(marker)
+            .graph_type(GraphType::Line)
+            .style(style)
+            .data(data)
+    };
+
+    let last = burndown.days.len().saturating_sub(1);
+    let date = |i: usize| {
+        burndown
+            .days
+            .get(i)
+            .map(|day| day.day.format("%b %d").to_string())
+            .unwrap_or_default()
+    };
+    let most = burndown.most().max(1);
+    let chart = Chart::new(vec![
+        dataset("Open", &open, colors.text_warning()),
+        dataset("Done", &done, colors.text_success()),
+    ])
+    .block(Block::default().title(Line::from(vec![
+        Span::styled(format!(" Burndown: {} · ", name), colors.text_primary()),
+        Span::styled("open", colors.text_warning()),
+        Span::styled(" / ", colors.text_muted()),
+        Span::styled("done", colors.text_success()),
+    ])))
+    .x_axis(
+        Axis::default()
+            .style(colors.text_muted())
+            .bounds([0.0, last as f64])
+            .labels([date(0), date(last / 2), date(last)]),
+    )
+    .y_axis(
+        Axis::default()
+            .style(colors.text_muted())
+            .bounds([0.0, f64::from(most)])
+            .labels(["0".to_string(), most.to_string()]),
+    );
+    frame.render_widget(chart, area);
+}
+
+/// ` 📋 Work #home #urgent +2`: the task's list (when `show_list`) and tags in
+/// their own colors, fitted into `width` columns. Names are shortened and
+/// tags that don't fit are counted instead.
+fn location_chips(
+    state: &AppState,
+    task: &Task,
+    show_list: bool,
+    width: usize,
+) -> Vec<Span<'static>> {
+    let colors = state.colors();
+    let mut chips: Vec<(String, Style)> = Vec::new();
+    if show_list && let Some(list) = state.lists.iter().find(|l| l.id == task.list_id) {
+        let style = list
+            .color
+            .as_deref()
+            .and_then(|c| named_color(state, c))
+            .map_or(colors.text_muted(), |c| Style::default().fg(c));
+        chips.push((
+            format!("{} {}", list_icon(state, &list.icon), list.name),
+            style,
+        ));
+    }
+    for tag in task
+        .tag_ids
+        .iter()
+        .filter_map(|id| state.tags.iter().find(|t| t.id == *id))
+    {
+        let style = named_color(state, &tag.color)
+            .map_or(colors.text_secondary(), |c| Style::default().fg(c));
+        chips.push((format!("#{}", tag.name), style));
+    }
+
+    let mut spans = Vec::new();
+    let mut left = width;
+    for (i, (text, style)) in chips.iter().enumerate() {
+        let rest = chips.len() - i - 1;
+        // Room for a " +N" after this chip if more are coming
+        let reserve = if rest > 0 {
+            2 + rest.to_string().len()
+        } else {
+            0
+        };
+        let room = left.saturating_sub(1 + reserve);
+        let text = if text.width() <= room {
+            text.clone()
+        } else if room >= 4 {
+            truncate(text, room)
+        } else {
+            let hidden = chips.len() - i;
+            if left >= 2 + hidden.to_string().len() {
+                spans.push(Span::styled(format!(" +{}", hidden), colors.text_muted()));
+            }
+            break;
+        };
+        left -= 1 + text.width();
+        spans.push(Span::styled(format!(" {}", text), *style));
+    }
+    spans
+}
+
+/// `text` cut to `width` columns, ending in "…" if anything was cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for c in text.chars() {
+        if out.width() + c.width().unwrap_or(0) + 1 > width {
+            break;
+        }
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
 /// Parse a hex color string
+/// A list color ("#89b4fa" or a name like "blue"), reduced to what the terminal shows
+fn named_color(state: &AppState, color: &str) -> Option<Color> {
+    parse_hex_color(color)
+        .or_else(|| color.parse().ok())
+        .map(|c| state.fit_color(c))
+}
+
+/// Color of the list shown in the task panel, if it has one
+fn panel_list_color(state: &AppState) -> Option<Color> {
+    if state.active_smart.is_some() {
+        return None;
+    }
+    let id = state.selected_list_id?;
+    let list = state.lists.iter().find(|l| l.id == id)?;
+    named_color(state, list.color.as_deref()?)
+}
+
 fn parse_hex_color(hex: &str) -> Option<Color> {
     let hex = hex.trim_start_matches('#');
     if hex.len() != 6 {
@@ -1318,12 +3721,15 @@ fn parse_hex_color(hex: &str) -> Option<Color> {
 }
 
 fn render_update_banner(frame: &mut Frame, state: &AppState, area: Rect) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
 
     if let Some(ref latest) = state.update_available {
         let pm = crate::detect_package_manager();
         let banner = Paragraph::new(Line::from(vec![
-            Span::styled("  ⬆️  ", Style::default().fg(colors.warning)),
+            Span::styled(
+                glyph(state, "  ⬆️  ", "  ^ "),
+                Style::default().fg(colors.warning),
+            ),
             Span::styled("Update available: ", colors.text()),
             Span::styled(
                 format!("v{}", latest),
@@ -1356,7 +3762,7 @@ fn render_update_banner(frame: &mut Frame, state: &AppState, area: Rect) {
 }
 
 fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = frame.area();
 
     let popup_width = 50u16;
@@ -1412,7 +3818,7 @@ fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::Yellow))
             .style(Style::default().bg(colors.bg))
-            .title(" ⬆️ Update Tickit ")
+            .title(glyph(state, " ⬆️ Update Tickit ", " Update Tickit "))
             .title_style(
                 Style::default()
                     .fg(Color::Yellow)
@@ -1424,7 +3830,7 @@ fn render_update_confirm_dialog(frame: &mut Frame, state: &AppState) {
 }
 
 fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
-    let colors = state.theme.colors();
+    let colors = state.colors();
     let area = frame.area();
 
     // Dim the background with semi-transparent overlay
@@ -1432,8 +3838,8 @@ fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
     frame.render_widget(overlay, area);
 
     // Centered modal
-    let popup_width = 40u16;
-    let popup_height = 5u16;
+    let popup_width = 60u16;
+    let popup_height = 6u16;
 
     // Calculate centered position
     let x = area.width.saturating_sub(popup_width) / 2;
@@ -1454,15 +3860,31 @@ fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
         .as_deref()
         .unwrap_or("Updating... please wait");
 
+    // Keep the latest output line on one row
+    let max_chars = popup_area.width.saturating_sub(6) as usize;
+    let msg: String = if msg.chars().count() > max_chars {
+        let mut short: String = msg.chars().take(max_chars.saturating_sub(1)).collect();
+        short.push('…');
+        short
+    } else {
+        msg.to_string()
+    };
+
     let text = vec![
         Line::from(""),
         Line::from(Span::styled(
-            format!("⏳ {}", msg),
+            format!("{}{}", glyph(state, "⏳ ", ""), msg),
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
+        Line::from(vec![
+            Span::styled("c", colors.key_hint()),
+            Span::styled(": cancel  ", colors.text_muted()),
+            Span::styled("Esc", colors.key_hint()),
+            Span::styled(": run in background", colors.text_muted()),
+        ]),
     ];
 
     let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
@@ -1477,3 +3899,286 @@ fn render_updating_overlay(frame: &mut Frame, state: &AppState) {
 
     frame.render_widget(paragraph, popup_area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::Database;
+    use crate::models::Tag;
+
+    #[test]
+    fn test_location_chips_fit() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let mut task = Task::new("Call mom", inbox.id);
+        for name in ["family", "phone", "weekend"] {
+            let tag = Tag::new(name);
+            db.insert_tag(&tag).unwrap();
+            task.tag_ids.push(tag.id);
+        }
+        db.insert_task(&task).unwrap();
+        let state = AppState::new(Config::default(), db).unwrap();
+        let text = |width| {
+            location_chips(&state, &task, true, width)
+                .iter()
+                .map(|s| s.content.to_string())
+                .collect::<String>()
+        };
+
+        let icon = list_icon(&state, &inbox.icon);
+        assert_eq!(text(80), format!(" {} Inbox #family #phone #weekend", icon));
+        // Tags that don't fit are counted, names are shortened
+        assert_eq!(
+            text(icon.width() + 23),
+            format!(" {} Inbox #family #ph… +1", icon)
+        );
+        assert_eq!(text(3), " +4");
+        assert_eq!(text(0), "");
+    }
+
+    #[test]
+    fn test_confirm_config() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let state = |confirm: crate::config::ConfirmConfig| {
+            let db = Database::open_in_memory().unwrap();
+            let inbox = db.get_inbox().unwrap();
+            for title in ["One", "Two"] {
+                db.insert_task(&Task::new(title, inbox.id)).unwrap();
+            }
+            let config = Config {
+                confirm,
+                ..Default::default()
+            };
+            let mut state = AppState::new(config, db).unwrap();
+            state.focus = Focus::Main;
+            state
+        };
+        let key = |state: &mut AppState, code| {
+            crate::app::events::handle_key(state, KeyEvent::from(code));
+        };
+
+        // Asked by default, with Yes preselected
+        let mut asked = state(Default::default());
+        key(&mut asked, KeyCode::Char('d'));
+        assert_eq!(asked.mode, Mode::Confirm);
+        key(&mut asked, KeyCode::Enter);
+        assert_eq!(asked.tasks.len(), 1);
+
+        // Not asked when turned off
+        let mut rapid = state(crate::config::ConfirmConfig {
+            delete_task: false,
+            ..Default::default()
+        });
+        key(&mut rapid, KeyCode::Char('d'));
+        assert_eq!(rapid.mode, Mode::Normal);
+        assert_eq!(rapid.tasks.len(), 1);
+
+        // With No preselected, Enter cancels until Yes is chosen
+        let mut cautious = state(crate::config::ConfirmConfig {
+            default_yes: false,
+            ..Default::default()
+        });
+        key(&mut cautious, KeyCode::Char('d'));
+        key(&mut cautious, KeyCode::Enter);
+        assert_eq!(cautious.mode, Mode::Normal);
+        assert_eq!(cautious.tasks.len(), 2);
+        key(&mut cautious, KeyCode::Char('d'));
+        key(&mut cautious, KeyCode::Char('l'));
+        key(&mut cautious, KeyCode::Enter);
+        assert_eq!(cautious.tasks.len(), 1);
+    }
+
+    #[test]
+    fn test_changes_are_journaled() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        for title in ["Passport", "passport photos", "Renew passport"] {
+            db.insert_task(&Task::new(title, inbox.id)).unwrap();
+        }
+        let mut state = AppState::new(Config::default(), db).unwrap();
+        state.focus = Focus::Main;
+        let key = |state: &mut AppState, code| {
+            crate::app::events::handle_key(state, KeyEvent::from(code));
+        };
+
+        // `tickit undo` reverts what the TUI did, as it would a CLI command
+        key(&mut state, KeyCode::Char('x'));
+        assert!(
+            state
+                .db()
+                .get_all_tasks()
+                .unwrap()
+                .iter()
+                .any(|t| t.completed)
+        );
+        crate::journal::undo(state.db()).unwrap();
+        assert!(
+            !state
+                .db()
+                .get_all_tasks()
+                .unwrap()
+                .iter()
+                .any(|t| t.completed)
+        );
+
+        state.refresh_data().unwrap();
+        for _ in 0..3 {
+            key(&mut state, KeyCode::Char('v'));
+            key(&mut state, KeyCode::Char('j'));
+        }
+        key(&mut state, KeyCode::Char('D'));
+        key(&mut state, KeyCode::Enter);
+        assert_eq!(state.db().get_all_tasks().unwrap().len(), 1);
+        crate::journal::undo(state.db()).unwrap();
+        assert_eq!(state.db().get_all_tasks().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_comment_lines() {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task::new("Call the bank", db.get_inbox().unwrap().id);
+        db.insert_task(&task).unwrap();
+        let mut state = AppState::new(Config::default(), db).unwrap();
+        assert!(comment_lines(&state, task.id).is_empty());
+
+        state.start_comment();
+        state.input_buffer = "Asked for a callback".to_string();
+        state.save_comment().unwrap();
+        let lines = comment_lines(&state, task.id);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[1].content, "Asked for a callback");
+    }
+
+    #[test]
+    fn test_dependency_lines() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let trip = Task::new("Book the trip", inbox.id);
+        let mut passport = Task::new("Renew passport", inbox.id);
+        passport.complete();
+        for task in [&trip, &passport] {
+            db.insert_task(task).unwrap();
+        }
+        db.add_dependency(trip.id, passport.id).unwrap();
+        let state = AppState::new(Config::default(), db).unwrap();
+
+        // A completed blocker is shown too, from the tasks loaded on refresh
+        let lines = dependency_lines(&state, trip.id);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].spans[2].content, "Renew passport");
+        assert_eq!(dependency_lines(&state, passport.id).len(), 2);
+    }
+
+    #[test]
+    fn test_mark_range_after_refresh() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let task = |title: &str, priority| {
+            let mut task = Task::new(title, inbox.id);
+            task.priority = priority;
+            task
+        };
+        for t in [
+            task("Alpha", Priority::Urgent),
+            task("Bravo", Priority::High),
+            task("Charlie", Priority::Medium),
+        ] {
+            db.insert_task(&t).unwrap();
+        }
+        let mut state = AppState::new(Config::default(), db).unwrap();
+        state.task_index = 1;
+        state.toggle_mark();
+
+        // A new task at the top moves Bravo down; the range still starts there
+        state
+            .db()
+            .insert_task(&task("Urgent call", Priority::Urgent))
+            .unwrap();
+        state.refresh_tasks().unwrap();
+        state.task_index = 3;
+        state.mark_range();
+        let marked: Vec<&str> = state
+            .tasks
+            .iter()
+            .filter(|t| state.is_marked(t.id))
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(marked, ["Bravo", "Charlie"]);
+    }
+
+    #[test]
+    fn test_task_filter() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let work = Tag::new("work");
+        db.insert_tag(&work).unwrap();
+        let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
+
+        let mut report = Task::new("Report", inbox.id);
+        report.priority = Priority::High;
+        report.tag_ids.push(work.id);
+        report.due_date = crate::quickadd::end_of_day(yesterday);
+        let mut slides = Task::new("Slides", inbox.id);
+        slides.priority = Priority::High;
+        slides.tag_ids.push(work.id);
+        let mut groceries = Task::new("Groceries", inbox.id);
+        groceries.priority = Priority::High;
+        let mut filed = Task::new("Filed", inbox.id);
+        filed.tag_ids.push(work.id);
+        filed.complete();
+        for task in [&report, &slides, &groceries, &filed] {
+            db.insert_task(task).unwrap();
+        }
+
+        let mut state = AppState::new(Config::default(), db).unwrap();
+        let titles = |state: &AppState| -> Vec<String> {
+            let mut titles: Vec<String> = state.tasks.iter().map(|t| t.title.clone()).collect();
+            titles.sort();
+            titles
+        };
+        let key = |state: &mut AppState, code| {
+            crate::app::events::handle_key(state, KeyEvent::from(code));
+        };
+        assert_eq!(titles(&state).len(), 4);
+
+        // f opens the popup; each row steps through its values with l
+        key(&mut state, KeyCode::Char('f'));
+        assert_eq!(state.mode, Mode::Filter);
+        key(&mut state, KeyCode::Char('l'));
+        assert_eq!(titles(&state), ["Filed", "Report", "Slides"]);
+        key(&mut state, KeyCode::Char('j'));
+        while state.filter.priority != Some(Priority::High) {
+            key(&mut state, KeyCode::Char('l'));
+        }
+        assert_eq!(titles(&state), ["Report", "Slides"]);
+        key(&mut state, KeyCode::Char('j'));
+        key(&mut state, KeyCode::Char('l'));
+        assert_eq!(titles(&state), ["Report"]);
+        assert_eq!(state.filter.describe(&state.tags), "#work · High · Overdue");
+
+        // Status alone, from a fresh filter: completed tasks only
+        key(&mut state, KeyCode::Char('F'));
+        assert!(!state.filter.is_active());
+        assert_eq!(titles(&state).len(), 4);
+        key(&mut state, KeyCode::Char('j'));
+        key(&mut state, KeyCode::Char('l'));
+        key(&mut state, KeyCode::Char('l'));
+        assert_eq!(titles(&state), ["Filed"]);
+        assert_eq!(state.filter.describe(&state.tags), "Completed");
+
+        // Esc closes the popup and keeps the filter; F in the Tasks view clears it
+        key(&mut state, KeyCode::Esc);
+        assert_eq!(state.mode, Mode::Normal);
+        assert!(state.filter.is_active());
+        key(&mut state, KeyCode::Char('l'));
+        key(&mut state, KeyCode::Char('F'));
+        assert!(!state.filter.is_active());
+        assert_eq!(titles(&state).len(), 4);
+    }
+}
@@ -0,0 +1,212 @@
+//! URLs and issue references found in task titles and descriptions
+//!
+//! Besides `http(s)://` URLs, references like `#4567` or `OPS-12` become links
+//! through the templates in `[links.templates]`, keyed by what comes before
+//! the number (`"#"`, `"OPS-"`). In a template, `{id}` is the number and
+//! `{ref}` the whole reference. Keys need a template of their own, so things
+//! like `UTF-8` or `ISO-8601` stay plain text.
+
+use std::ops::Range;
+
+use crate::config::LinksConfig;
+use crate::models::Task;
+
+/// Something in a task that can be opened in the browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// What the task says: the URL itself, or the reference
+    pub label: String,
+    pub url: String,
+}
+
+/// Length of the URL at the start of `text`, leaving out trailing
+/// punctuation that usually ends the sentence around it
+pub fn url_at(text: &str) -> Option<usize> {
+    if !text.starts_with("http://") && !text.starts_with("https://") {
+        return None;
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+        .unwrap_or(text.len());
+    let mut url = &text[..end];
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+        // Keep a closing paren that belongs to the URL, as on Wikipedia
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(inner) if inner.matches('(').count() < trimmed.matches(')').count() => inner,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            break;
+        }
+        url = trimmed;
+    }
+    (url.len() > "https://".len()).then_some(url.len())
+}
+
+/// Length and link of the issue reference at the start of `text`, e.g.
+/// `#4567` or `OPS-12`
+pub fn reference_at(text: &str, config: &LinksConfig) -> Option<(usize, String)> {
+    let prefix_len = if text.starts_with('#') {
+        1
+    } else {
+        let key_len = text
+            .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(text.len());
+        if key_len < 2
+            || !text.starts_with(|c: char| c.is_ascii_uppercase())
+            || !text[key_len..].starts_with('-')
+        {
+            return None;
+        }
+        key_len + 1
+    };
+    let rest = &text[prefix_len..];
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    // `#12abc` or `OPS-12x` isn't a reference
+    if digits == 0 || rest[digits..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let len = prefix_len + digits;
+    let (prefix, id, reference) = (&text[..prefix_len], &rest[..digits], &text[..len]);
+    let template = config.templates.get(prefix)?;
+    Some((
+        len,
+        template.replace("{id}", id).replace("{ref}", reference),
+    ))
+}
+
+/// Every URL and reference in `text`, with where it is
+pub fn find(text: &str, config: &LinksConfig) -> Vec<(Range<usize>, String)> {
+    let mut found = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        // Only at the start of a word, so `a#1` or `FOO-BAR-1` are left alone
+        let boundary =
+            previous.is_none_or(|p| !(p.is_alphanumeric() || matches!(p, '_' | '-' | '/')));
+        let hit = if boundary {
+            let rest = &text[i..];
+            url_at(rest)
+                .map(|len| (len, rest[..len].to_string()))
+                .or_else(|| reference_at(rest, config))
+        } else {
+            None
+        };
+        match hit {
+            Some((len, url)) => {
+                found.push((i..i + len, url));
+                previous = text[..i + len].chars().next_back();
+                i += len;
+            }
+            None => {
+                previous = Some(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    found
+}
+
+/// The links of `task`: its URL, then any in its title and description,
+/// each once
+pub fn task_links(task: &Task, config: &LinksConfig) -> Vec<Link> {
+    let mut links: Vec<Link> = Vec::new();
+    let mut add = |label: &str, url: String| {
+        if !links.iter().any(|l| l.url == url) {
+            links.push(Link {
+                label: label.to_string(),
+                url,
+            });
+        }
+    };
+    if let Some(url) = task.url.as_deref().filter(|u| !u.trim().is_empty()) {
+        add(url, url.to_string());
+    }
+    for text in [Some(task.title.as_str()), task.description.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        for (range, url) in find(text, config) {
+            add(&text[range], url);
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LinksConfig {
+        let mut config = LinksConfig::default();
+        config.templates.insert(
+            "OPS-".to_string(),
+            "https://acme.atlassian.net/browse/{ref}".to_string(),
+        );
+        config.templates.insert(
+            "#".to_string(),
+            "https://github.com/acme/app/issues/{id}".to_string(),
+        );
+        config.templates.insert(
+            "WEB-".to_string(),
+            "https://tracker.example/{ref}".to_string(),
+        );
+        config
+    }
+
+    #[test]
+    fn test_url_at() {
+        assert_eq!(url_at("https://x.io/a."), Some(14));
+        assert_eq!(url_at("https://x.io/a), then"), Some(14));
+        assert_eq!(
+            url_at("https://en.wikipedia.org/wiki/Rust_(language)"),
+            Some(45)
+        );
+        assert_eq!(url_at("https://"), None);
+        assert_eq!(url_at("see https://x.io"), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let config = config();
+        let text = "[OPS-12] fix #4567 and WEB-9, see https://x.io/a. Not a#1, #12abc or UTF-8";
+        let found: Vec<(&str, String)> = find(text, &config)
+            .into_iter()
+            .map(|(range, url)| (&text[range], url))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "OPS-12",
+                    "https://acme.atlassian.net/browse/OPS-12".to_string()
+                ),
+                (
+                    "#4567",
+                    "https://github.com/acme/app/issues/4567".to_string()
+                ),
+                ("WEB-9", "https://tracker.example/WEB-9".to_string()),
+                ("https://x.io/a", "https://x.io/a".to_string()),
+            ]
+        );
+
+        // Without templates, only URLs are links
+        assert!(find("OPS-12 and #4", &LinksConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_task_links() {
+        let mut task = Task::new("OPS-12: see https://x.io", uuid::Uuid::new_v4());
+        task.url = Some("https://x.io".to_string());
+        task.description = Some("Also #7 and OPS-12".to_string());
+        let labels: Vec<String> = task_links(&task, &config())
+            .into_iter()
+            .map(|link| link.label)
+            .collect();
+        assert_eq!(labels, ["https://x.io", "OPS-12", "#7"]);
+    }
+}
@@ -1,9 +1,13 @@
 //! Configuration module
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
+use crate::hooks::Hook;
+use crate::models::{Priority, TaskSort};
 use crate::theme::Theme;
 
 /// Application configuration
@@ -17,6 +21,10 @@ pub struct Config {
     #[serde(default = "default_show_completed")]
     pub show_completed: bool,
 
+    /// Expand the "Completed today" section of the Tasks view
+    #[serde(default = "default_show_completed_today")]
+    pub show_completed_today: bool,
+
     /// Default list ID for new tasks (None = inbox)
     pub default_list_id: Option<String>,
 
@@ -24,21 +32,447 @@ pub struct Config {
     #[serde(default = "default_date_format")]
     pub date_format: String,
 
+    /// Time format string
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+
+    /// First day of the week in calendars
+    #[serde(default = "default_week_start")]
+    pub week_starts_on: Weekday,
+
     /// Enable vim-like keybindings
     #[serde(default = "default_vim_mode")]
     pub vim_mode: bool,
 
-    /// Enable desktop notifications for due tasks
-    #[serde(default = "default_notifications")]
-    pub notifications: bool,
+    /// Desktop notifications for due tasks and the weekly review
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Daily window in which no notifications are sent
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// Reminders turned off or sent early for some lists and tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notification_rules: Vec<NotificationRule>,
+
+    /// Task sort order per list (keyed by list ID, `all` for the all-tasks view)
+    #[serde(default)]
+    pub task_sort: HashMap<String, TaskSort>,
+
+    /// Segments shown on the right of the status bar, in order
+    #[serde(default = "default_status_segments")]
+    pub status_segments: Vec<StatusSegment>,
+
+    /// Which destructive actions ask for confirmation
+    #[serde(default)]
+    pub confirm: ConfirmConfig,
+
+    /// Task list appearance
+    #[serde(default)]
+    pub display: DisplayConfig,
 
     /// Sync configuration (optional)
     #[serde(default)]
     pub sync: SyncConfig,
+
+    /// Local HTTP API (`tickit serve`)
+    #[serde(default)]
+    pub serve: ServeConfig,
+
+    /// Commands run on task events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// GitHub/GitLab/Jira issue links
+    #[serde(default)]
+    pub links: LinksConfig,
+
+    /// When the TUI looks for a new release
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+
+    /// When old completed tasks are archived and purged
+    #[serde(default)]
+    pub retention: RetentionConfig,
+
+    /// Priorities raised automatically as due dates approach
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+
+    /// Working days for business-day due dates
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+
+    /// Other databases to switch to (`W` in the TUI, `--workspace`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<Workspace>,
 }
 
-/// Sync configuration
+/// A daily window without notifications, which may wrap past midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Start of the window, e.g. "22:00"
+    pub start: NaiveTime,
+    /// End of the window, e.g. "07:00"
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Check whether `time` falls inside the window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Desktop notifications; `notifications = false` still turns them all off
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationsConfig {
+    /// Send reminders for due tasks
+    pub enabled: bool,
+
+    /// Hours between reminders for overdue tasks set to nag, unless the
+    /// task sets its own
+    pub nag_hours: u32,
+
+    /// Weekly reminder to look at lists and tasks that have gone quiet
+    pub review: ReviewConfig,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            nag_hours: 4,
+            review: ReviewConfig::default(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationsConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(default)]
+        struct Table {
+            enabled: bool,
+            nag_hours: u32,
+            review: ReviewConfig,
+        }
+
+        impl Default for Table {
+            fn default() -> Self {
+                let NotificationsConfig {
+                    enabled,
+                    nag_hours,
+                    review,
+                } = NotificationsConfig::default();
+                Self {
+                    enabled,
+                    nag_hours,
+                    review,
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Enabled(bool),
+            Table(Table),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Enabled(enabled) => Self {
+                enabled,
+                ..Self::default()
+            },
+            Repr::Table(Table {
+                enabled,
+                nag_hours,
+                review,
+            }) => Self {
+                enabled,
+                nag_hours,
+                review,
+            },
+        })
+    }
+}
+
+/// Weekly review reminder, see [`crate::notifications::Review`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Send the review reminder
+    pub enabled: bool,
+
+    /// Day of the week it's sent on (or the first check after)
+    pub day: Weekday,
+
+    /// Lists with no changes for this many days are listed
+    pub stale_list_days: u32,
+
+    /// Open tasks with no due date created this many days ago are listed
+    pub undated_task_days: u32,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            day: Weekday::Mon,
+            stale_list_days: 14,
+            undated_task_days: 30,
+        }
+    }
+}
+
+/// Reminders for the tasks of a list or tag, e.g. none for "Someday", or
+/// three days early for "Bills"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationRule {
+    /// List name the rule applies to (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list: Option<String>,
+
+    /// Tag name the rule applies to (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// Send reminders at all
+    #[serde(default = "default_true")]
+    pub notify: bool,
+
+    /// Remind every day from this many days before the due date, whatever
+    /// the task's priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_before: Option<u32>,
+}
+
+impl NotificationRule {
+    /// Whether the rule applies to a task in `list` with `tags`
+    pub fn matches(&self, list: Option<&str>, tags: &[&str]) -> bool {
+        let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+        let list_matches = self
+            .list
+            .as_deref()
+            .is_some_and(|want| list.is_some_and(|l| same(want, l)));
+        let tag_matches = self
+            .tag
+            .as_deref()
+            .is_some_and(|want| tags.iter().any(|t| same(want, t)));
+        list_matches || tag_matches
+    }
+}
+
+/// A separate database with its own lists, tags and tasks, e.g. one for
+/// work and one for home
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Name to switch to it by
+    pub name: String,
+
+    /// SQLite file, created on first use (`~/` is the home directory)
+    pub path: String,
+}
+
+/// config.toml with every option and its default, commented, as written on
+/// first run and by `tickit config init`
+pub const TEMPLATE: &str = include_str!("config_template.toml");
+
+/// Name of the workspace at the usual database path
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// A piece of information on the right side of the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatusSegment {
+    /// Current view and list
+    View,
+    /// Active task filter
+    Filter,
+    /// Open/done counts of the shown tasks
+    Tasks,
+    /// Sync state and unsynced change count
+    Sync,
+    /// Current time
+    Clock,
+}
+
+/// Confirmation prompts for destructive actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmConfig {
+    /// Confirm before deleting a single task
+    #[serde(default = "default_true")]
+    pub delete_task: bool,
+
+    /// Confirm before deleting several marked tasks
+    #[serde(default = "default_true")]
+    pub bulk_delete: bool,
+
+    /// Confirm before deleting a list
+    #[serde(default = "default_true")]
+    pub delete_list: bool,
+
+    /// Confirm before deleting a tag
+    #[serde(default = "default_true")]
+    pub delete_tag: bool,
+
+    /// Confirm before unchecking every task in a checklist
+    #[serde(default = "default_true")]
+    pub reset_list: bool,
+
+    /// Preselect "Yes" in the confirm dialog (Enter confirms); otherwise "No"
+    #[serde(default = "default_true")]
+    pub default_yes: bool,
+
+    /// CLI commands touching more tasks than this only list them, as with
+    /// `--dry-run`, unless `--yes` is given (0 for no limit)
+    #[serde(default = "default_bulk_limit")]
+    pub bulk_limit: usize,
+}
+
+const fn default_bulk_limit() -> usize {
+    25
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            delete_task: true,
+            bulk_delete: true,
+            delete_list: true,
+            delete_tag: true,
+            reset_list: true,
+            default_yes: true,
+            bulk_limit: default_bulk_limit(),
+        }
+    }
+}
+
+/// How much room the Tasks view gives each task
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Density {
+    /// Priority icons, detail pane and the "Completed today" section
+    #[default]
+    Comfortable,
+    /// Task rows only, with priority shown by the gutter color
+    Compact,
+}
+
+/// Task list appearance
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    /// Row density of the Tasks view
+    #[serde(default)]
+    pub density: Density,
+
+    /// Spell out cues that are otherwise only shown by color (priority, due state)
+    #[serde(default)]
+    pub accessible: bool,
+
+    /// Use the high-contrast variant of the theme
+    #[serde(default)]
+    pub high_contrast: bool,
+
+    /// Color depth to draw with ("auto" detects it from `COLORTERM`)
+    #[serde(default)]
+    pub colors: ColorDepth,
+
+    /// Draw emoji and symbol icons; unset detects it from the terminal and locale
+    pub unicode: Option<bool>,
+}
+
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorDepth {
+    /// Truecolor if `COLORTERM` says so, 256 colors otherwise
+    #[default]
+    Auto,
+    /// 24-bit RGB colors
+    Truecolor,
+    /// The xterm 256-color palette
+    #[serde(rename = "256")]
+    Ansi256,
+}
+
+// Accepts `colors = 256` as well as `colors = "256"`
+impl<'de> Deserialize<'de> for ColorDepth {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Name(String),
+            Count(u32),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Name(name) if name == "auto" => Ok(Self::Auto),
+            Raw::Name(name) if name == "truecolor" => Ok(Self::Truecolor),
+            Raw::Name(name) if name == "256" => Ok(Self::Ansi256),
+            Raw::Count(256) => Ok(Self::Ansi256),
+            _ => Err(serde::de::Error::custom(
+                "expected \"auto\", \"truecolor\" or 256",
+            )),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Whether theme colors can be drawn as RGB
+    pub fn truecolor(&self) -> bool {
+        match self.colors {
+            ColorDepth::Auto => detect_truecolor(),
+            ColorDepth::Truecolor => true,
+            ColorDepth::Ansi256 => false,
+        }
+    }
+
+    /// Whether emoji and symbol icons can be drawn, or ASCII should be used
+    pub fn unicode(&self) -> bool {
+        self.unicode.unwrap_or_else(detect_unicode)
+    }
+}
+
+fn detect_truecolor() -> bool {
+    static TRUECOLOR: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        std::env::var("COLORTERM")
+            .map(|v| v.eq_ignore_ascii_case("truecolor") || v.eq_ignore_ascii_case("24bit"))
+            .unwrap_or(false)
+            || cfg!(windows)
+    })
+}
+
+fn detect_unicode() -> bool {
+    static UNICODE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *UNICODE.get_or_init(|| {
+        if cfg!(windows) {
+            return true;
+        }
+        if std::env::var("TERM").is_ok_and(|t| t == "linux" || t == "dumb") {
+            return false;
+        }
+        // The first of these that is set decides the character encoding
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+            .is_some_and(|locale| {
+                let locale = locale.to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            })
+    })
+}
+
+/// Sync configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
     /// Enable sync feature
     #[serde(default)]
@@ -50,24 +484,511 @@ pub struct SyncConfig {
     /// API token for authentication
     pub token: Option<String>,
 
+    /// Command that prints the API token, used instead of `token`
+    /// (e.g. "pass show tickit" or a keychain lookup)
+    pub token_command: Option<String>,
+
     /// Auto-sync interval in seconds (0 = manual only)
     #[serde(default = "default_sync_interval")]
     pub interval_secs: u64,
+
+    /// Which side wins when a field was changed both here and on the server
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+
+    /// Most records uploaded per request; larger syncs go in several (0 = no limit)
+    #[serde(default = "default_sync_batch_records")]
+    pub max_batch_records: usize,
+
+    /// Most bytes of records uploaded per request (0 = no limit)
+    #[serde(default)]
+    pub max_batch_bytes: usize,
+
+    /// Pause between upload requests, in milliseconds, to spare slow links
+    #[serde(default)]
+    pub batch_pause_ms: u64,
+
+    /// Largest file blob uploaded or downloaded, in bytes (0 = no limit)
+    #[serde(default = "default_max_blob_bytes")]
+    pub max_blob_bytes: u64,
+
+    /// Command run before each sync; sync is skipped unless it exits
+    /// successfully (e.g. a check for the home Wi-Fi)
+    pub network_check: Option<String>,
+}
+
+/// How sync settles a change made both locally and on the server, see
+/// [`crate::sync::resolve_conflicts`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Keep the server's value
+    ServerWins,
+    /// Keep the local value, and upload it on the next sync
+    ClientWins,
+    /// Keep whichever copy was edited last
+    #[default]
+    NewestWins,
+    /// Ask for each field in `tickit sync`; newest wins where nobody can be asked
+    Ask,
+}
+
+impl ConflictPolicy {
+    /// The name used in config.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ServerWins => "server-wins",
+            Self::ClientWins => "client-wins",
+            Self::NewestWins => "newest-wins",
+            Self::Ask => "ask",
+        }
+    }
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: None,
+            token: None,
+            token_command: None,
+            interval_secs: default_sync_interval(),
+            conflict_policy: ConflictPolicy::default(),
+            max_batch_records: default_sync_batch_records(),
+            max_batch_bytes: 0,
+            batch_pause_ms: 0,
+            max_blob_bytes: default_max_blob_bytes(),
+            network_check: None,
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Whether a token is configured, directly or through `token_command`
+    pub fn has_token(&self) -> bool {
+        self.token.is_some() || self.token_command.is_some()
+    }
+
+    /// The API token: the first line printed by `token_command` if set, otherwise `token`
+    pub fn resolve_token(&self) -> Result<Option<String>> {
+        match &self.token_command {
+            Some(command) => run_token_command(command).map(Some),
+            None => Ok(self.token.clone()),
+        }
+    }
+
+    /// Whether `network_check` (if set) allows syncing now
+    pub fn network_allowed(&self) -> Result<bool> {
+        let Some(command) = self
+            .network_check
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+        else {
+            return Ok(true);
+        };
+        #[cfg(windows)]
+        let output = std::process::Command::new("cmd")
+            .args(["/C", command])
+            .output();
+        #[cfg(not(windows))]
+        let output = std::process::Command::new("sh")
+            .args(["-c", command])
+            .output();
+        let output =
+            output.with_context(|| format!("Failed to run network_check `{}`", command))?;
+        Ok(output.status.success())
+    }
+}
+
+/// The first line a token command prints
+fn run_token_command(command: &str) -> Result<String> {
+    #[cfg(windows)]
+    let output = std::process::Command::new("cmd")
+        .args(["/C", command])
+        .output();
+    #[cfg(not(windows))]
+    let output = std::process::Command::new("sh")
+        .args(["-c", command])
+        .output();
+    let output = output.with_context(|| format!("Failed to run token_command `{}`", command))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "token_command `{}` failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().next().map(str::trim) {
+        Some(token) if !token.is_empty() => Ok(token.to_string()),
+        _ => anyhow::bail!("token_command `{}` printed no token", command),
+    }
+}
+
+/// GitHub and GitLab access for tasks linked to issues
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinksConfig {
+    /// GitHub API token (needed for private repos and closing issues)
+    pub github_token: Option<String>,
+
+    /// Command that prints the GitHub token, used instead of `github_token`
+    pub github_token_command: Option<String>,
+
+    /// GitLab API token
+    pub gitlab_token: Option<String>,
+
+    /// Command that prints the GitLab token, used instead of `gitlab_token`
+    pub gitlab_token_command: Option<String>,
+
+    /// Self-hosted GitLab host (e.g. "gitlab.example.com"), besides gitlab.com
+    pub gitlab_host: Option<String>,
+
+    /// Jira host (e.g. "acme.atlassian.net"); links to `/browse/KEY-1` there are issues
+    pub jira_host: Option<String>,
+
+    /// Jira Cloud account email, sent with `jira_token`; leave unset to send
+    /// the token as a Jira Server/Data Center personal access token
+    pub jira_email: Option<String>,
+
+    /// Jira API token
+    pub jira_token: Option<String>,
+
+    /// Command that prints the Jira token, used instead of `jira_token`
+    pub jira_token_command: Option<String>,
+
+    /// Close the linked issue when its task is completed
+    #[serde(default)]
+    pub close_issues: bool,
+
+    /// Links for references in task text, keyed by what comes before the
+    /// number: `"#" = "https://github.com/owner/repo/issues/{id}"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, String>,
+}
+
+impl LinksConfig {
+    /// The GitHub token, from `github_token_command` if set
+    pub fn github_token(&self) -> Result<Option<String>> {
+        match &self.github_token_command {
+            Some(command) => run_token_command(command).map(Some),
+            None => Ok(self.github_token.clone()),
+        }
+    }
+
+    /// The GitLab token, from `gitlab_token_command` if set
+    pub fn gitlab_token(&self) -> Result<Option<String>> {
+        match &self.gitlab_token_command {
+            Some(command) => run_token_command(command).map(Some),
+            None => Ok(self.gitlab_token.clone()),
+        }
+    }
+
+    /// The Jira token, from `jira_token_command` if set
+    pub fn jira_token(&self) -> Result<Option<String>> {
+        match &self.jira_token_command {
+            Some(command) => run_token_command(command).map(Some),
+            None => Ok(self.jira_token.clone()),
+        }
+    }
+}
+
+/// Local HTTP API configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeConfig {
+    /// Port on 127.0.0.1 to listen on
+    #[serde(default = "default_serve_port")]
+    pub port: u16,
+
+    /// Bearer token clients must send; when unset, `tickit serve` generates
+    /// one into `.serve_token` next to this file
+    pub token: Option<String>,
+
+    /// Read-only token for `/calendar.ics?token=...`, kept apart from `token`
+    /// since calendar apps store the URL; when unset, `tickit serve` generates
+    /// one into `.calendar_token`
+    pub calendar_token: Option<String>,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            port: default_serve_port(),
+            token: None,
+            calendar_token: None,
+        }
+    }
+}
+
+/// How often the TUI asks crates.io for a newer release
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpdateCheck {
+    /// Every time the TUI starts
+    OnStart,
+    /// At most once a day
+    #[default]
+    Daily,
+    /// Never; `tickit update` still checks when run
+    Never,
+}
+
+impl UpdateCheck {
+    /// Whether a check is due, given when the last one ran
+    pub fn is_due(self, last_check: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        match self {
+            UpdateCheck::OnStart => true,
+            UpdateCheck::Daily => last_check.is_none_or(|last| now - last >= Duration::days(1)),
+            UpdateCheck::Never => false,
+        }
+    }
+}
+
+/// Update check configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdatesConfig {
+    /// When to check: "on-start", "daily" or "never"
+    #[serde(default)]
+    pub check: UpdateCheck,
+}
+
+/// Automatic cleanup of completed tasks, run by `tickit maintain` and on TUI start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Archive tasks this long after they were completed, e.g. "14d"
+    pub archive_completed_after: Option<String>,
+
+    /// Permanently delete tasks this long after they were archived, e.g. "6m"
+    pub purge_archived_after: Option<String>,
+
+    /// Apply the policy when the TUI starts
+    #[serde(default = "default_true")]
+    pub run_on_start: bool,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            archive_completed_after: None,
+            purge_archived_after: None,
+            run_on_start: true,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// How long completed tasks stay before being archived, if set and valid
+    pub fn archive_after(&self) -> Option<Duration> {
+        self.archive_completed_after
+            .as_deref()
+            .and_then(|s| parse_age(s).ok())
+    }
+
+    /// How long archived tasks stay before being purged, if set and valid
+    pub fn purge_after(&self) -> Option<Duration> {
+        self.purge_archived_after
+            .as_deref()
+            .and_then(|s| parse_age(s).ok())
+    }
+
+    /// Whether any part of the policy is set
+    pub fn is_set(&self) -> bool {
+        self.archive_after().is_some() || self.purge_after().is_some()
+    }
+}
+
+/// Which days count as business days, see [`crate::holidays`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Holidays as an .ics file: a path, or an http(s) URL fetched once a day
+    pub holidays: Option<String>,
+
+    /// Saturdays and Sundays aren't business days
+    pub skip_weekends: bool,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            holidays: None,
+            skip_weekends: true,
+        }
+    }
+}
+
+/// Raise open tasks to a priority once they're due within a window, e.g.
+/// `urgent = "1d"`; each key is the priority to raise to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    pub medium: Option<String>,
+    pub high: Option<String>,
+    pub urgent: Option<String>,
+}
+
+impl EscalationConfig {
+    /// (window, priority) rules that are set and valid, highest priority first
+    pub fn rules(&self) -> Vec<(Duration, Priority)> {
+        [
+            (&self.urgent, Priority::Urgent),
+            (&self.high, Priority::High),
+            (&self.medium, Priority::Medium),
+        ]
+        .into_iter()
+        .filter_map(|(window, priority)| Some((parse_age(window.as_deref()?).ok()?, priority)))
+        .collect()
+    }
+
+    /// The highest priority whose window a task due at `due` falls in (overdue
+    /// tasks fall in every window)
+    pub fn priority_for(&self, due: DateTime<Utc>, now: DateTime<Utc>) -> Option<Priority> {
+        self.rules()
+            .into_iter()
+            .find(|(window, _)| due - now <= *window)
+            .map(|(_, priority)| priority)
+    }
+}
+
+/// Rewrite the keys of `target` whose values went from `old` to `new`,
+/// keeping the comments and formatting of everything else
+fn patch_table(
+    target: &mut dyn toml_edit::TableLike,
+    old: &toml_edit::Table,
+    new: &toml_edit::Table,
+) {
+    use toml_edit::Item;
+
+    for (key, item) in new.iter() {
+        let before = old.get(key);
+        if let Item::Table(table) = item {
+            let empty = toml_edit::Table::new();
+            let before = before.and_then(Item::as_table).unwrap_or(&empty);
+            match target.get_mut(key).and_then(Item::as_table_like_mut) {
+                Some(section) => patch_table(section, before, table),
+                None => {
+                    // Only sections with changes are added
+                    let mut section = toml_edit::Table::new();
+                    patch_table(&mut section, before, table);
+                    if !section.is_empty() {
+                        target.insert(key, Item::Table(section));
+                    }
+                }
+            }
+            continue;
+        }
+        if before.is_some_and(|before| before.to_string() == item.to_string()) {
+            continue;
+        }
+        match (target.get_mut(key), item) {
+            (Some(Item::Value(current)), Item::Value(value)) => {
+                let decor = current.decor().clone();
+                *current = value.clone();
+                *current.decor_mut() = decor;
+            }
+            _ => {
+                target.insert(key, item.clone());
+            }
+        }
+    }
+    // Settings that were unset
+    for (key, _) in old.iter() {
+        if !new.contains_key(key) {
+            target.remove(key);
+        }
+    }
+}
+
+/// Parse an age such as `30d`, `6w`, `3m` or `1y` (a month is 30 days, a year 365)
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    // The unit is the last character, which may not be ASCII in bad input
+    let age = s.char_indices().last().and_then(|(at, unit)| {
+        let days = match unit {
+            'd' => 1,
+            'w' => 7,
+            'm' => 30,
+            'y' => 365,
+            _ => return None,
+        };
+        let count = s[..at].parse::<i64>().ok().filter(|n| *n >= 0)?;
+        Duration::try_days(count.checked_mul(days)?)
+    });
+    age.ok_or_else(|| format!("expected an age like 30d, 6w, 3m or 1y, got \"{}\"", s))
+}
+
+/// Shell commands run on task events, see [`crate::hooks`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Run with the new task as JSON
+    pub on_task_created: Option<String>,
+
+    /// Run with the completed task as JSON
+    pub on_task_completed: Option<String>,
+
+    /// Run with a summary of the sync as JSON
+    pub on_sync_finished: Option<String>,
+}
+
+impl HooksConfig {
+    /// The command attached to `hook`, ignoring blank ones
+    pub fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::TaskCreated => &self.on_task_created,
+            Hook::TaskCompleted => &self.on_task_completed,
+            Hook::SyncFinished => &self.on_sync_finished,
+        }
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+    }
+}
+
+fn default_serve_port() -> u16 {
+    7171
+}
+
+fn default_sync_batch_records() -> usize {
+    500
+}
+
+fn default_max_blob_bytes() -> u64 {
+    25 * 1024 * 1024
 }
 
 fn default_show_completed() -> bool {
     true
 }
 
+fn default_show_completed_today() -> bool {
+    true
+}
+
 fn default_date_format() -> String {
     "%Y-%m-%d".to_string()
 }
 
+fn default_time_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_week_start() -> Weekday {
+    Weekday::Mon
+}
+
 fn default_vim_mode() -> bool {
     true
 }
 
-fn default_notifications() -> bool {
+fn default_status_segments() -> Vec<StatusSegment> {
+    vec![
+        StatusSegment::View,
+        StatusSegment::Filter,
+        StatusSegment::Tasks,
+        StatusSegment::Sync,
+        StatusSegment::Clock,
+    ]
+}
+
+fn default_true() -> bool {
     true
 }
 
@@ -80,16 +1001,59 @@ impl Default for Config {
         Self {
             theme: Theme::default(),
             show_completed: default_show_completed(),
+            show_completed_today: default_show_completed_today(),
             default_list_id: None,
             date_format: default_date_format(),
+            time_format: default_time_format(),
+            week_starts_on: default_week_start(),
             vim_mode: default_vim_mode(),
-            notifications: default_notifications(),
+            notifications: NotificationsConfig::default(),
+            quiet_hours: None,
+            notification_rules: Vec::new(),
+            task_sort: HashMap::new(),
+            status_segments: default_status_segments(),
+            confirm: ConfirmConfig::default(),
+            display: DisplayConfig::default(),
             sync: SyncConfig::default(),
+            serve: ServeConfig::default(),
+            hooks: HooksConfig::default(),
+            links: LinksConfig::default(),
+            updates: UpdatesConfig::default(),
+            retention: RetentionConfig::default(),
+            escalation: EscalationConfig::default(),
+            calendar: CalendarConfig::default(),
+            workspaces: Vec::new(),
         }
     }
 }
 
 impl Config {
+    /// Names of every workspace, the default one first
+    pub fn workspace_names(&self) -> Vec<&str> {
+        std::iter::once(DEFAULT_WORKSPACE)
+            .chain(self.workspaces.iter().map(|w| w.name.as_str()))
+            .collect()
+    }
+
+    /// Database file of the workspace called `name` (case-insensitive)
+    pub fn workspace_path(&self, name: &str) -> Result<PathBuf> {
+        if name.eq_ignore_ascii_case(DEFAULT_WORKSPACE) {
+            return crate::db::Database::default_path();
+        }
+        match self
+            .workspaces
+            .iter()
+            .find(|w| w.name.eq_ignore_ascii_case(name))
+        {
+            Some(workspace) => Ok(crate::holidays::expand_home(&workspace.path)),
+            None => anyhow::bail!(
+                "No workspace named \"{}\" (have: {})",
+                name,
+                self.workspace_names().join(", ")
+            ),
+        }
+    }
+
     /// Get the default config file path
     /// Always uses ~/.config/tickit/config.toml for consistency across all OSs
     pub fn default_path() -> Result<PathBuf> {
@@ -103,9 +1067,8 @@ impl Config {
         let path = Self::default_path()?;
         if !path.exists() {
             // Create default config file on first run
-            let config = Self::default();
-            config.save_to(&path)?;
-            return Ok(config);
+            Self::write_template(&path)?;
+            return Ok(Self::default());
         }
         Self::load_from(&path)
     }
@@ -120,6 +1083,14 @@ impl Config {
         }
     }
 
+    /// Write the commented default config to `path`
+    pub fn write_template(path: &PathBuf) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        std::fs::write(path, TEMPLATE).context("Failed to write config file")
+    }
+
     /// Save config to the default path
     pub fn save(&self) -> Result<()> {
         let path = Self::default_path()?;
@@ -127,15 +1098,821 @@ impl Config {
     }
 
     /// Save config to a specific path
+    ///
+    /// An existing file keeps its comments and layout: only the settings that
+    /// differ from what it holds are rewritten. A file that doesn't load is
+    /// left alone, since saving over it would lose whatever was being edited.
     pub fn save_to(&self, path: &PathBuf) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
-        let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        let content = match std::fs::read_to_string(path) {
+            Ok(existing) => {
+                let saved: Config = toml::from_str(&existing).with_context(|| {
+                    format!(
+                        "Not saving over {}, which has errors (`tickit config check` shows them)",
+                        path.display()
+                    )
+                })?;
+                let mut document: toml_edit::DocumentMut = existing.parse()?;
+                patch_table(
+                    document.as_table_mut(),
+                    saved.to_document()?.as_table(),
+                    self.to_document()?.as_table(),
+                );
+                document.to_string()
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                toml::to_string_pretty(self).context("Failed to serialize config")?
+            }
+            Err(e) => return Err(e).context("Failed to read config file"),
+        };
         std::fs::write(path, content).context("Failed to write config file")?;
 
         Ok(())
     }
+
+    fn to_document(&self) -> Result<toml_edit::DocumentMut> {
+        let content = toml::to_string(self).context("Failed to serialize config")?;
+        Ok(content.parse()?)
+    }
+
+    /// Format a date with `date_format`
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(checked_format(&self.date_format, "%Y-%m-%d"))
+            .to_string()
+    }
+
+    /// Format a time of day with `time_format`
+    pub fn format_time(&self, time: NaiveTime) -> String {
+        time.format(checked_format(&self.time_format, "%H:%M"))
+            .to_string()
+    }
+
+    /// Format a timestamp as `date_format` followed by `time_format`
+    pub fn format_datetime(&self, datetime: NaiveDateTime) -> String {
+        format!(
+            "{} {}",
+            self.format_date(datetime.date()),
+            self.format_time(datetime.time())
+        )
+    }
+
+    /// The seven days of a calendar week in order, starting at `week_starts_on`
+    pub fn week_days(&self) -> impl Iterator<Item = Weekday> {
+        std::iter::successors(Some(self.week_starts_on), |d| Some(d.succ())).take(7)
+    }
+}
+
+/// Whether chrono can format with `format`; it panics on invalid ones
+fn valid_format(format: &str) -> bool {
+    !chrono::format::StrftimeItems::new(format)
+        .any(|item| matches!(item, chrono::format::Item::Error))
+}
+
+/// `format` if it is valid, otherwise `fallback`
+fn checked_format<'a>(format: &'a str, fallback: &'a str) -> &'a str {
+    if valid_format(format) {
+        format
+    } else {
+        fallback
+    }
+}
+
+// ==================== Validation ====================
+
+/// Tables of config.toml and the keys each accepts (`""` is the top level).
+/// `task_sort` is keyed by list ID, so its keys aren't checked.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "theme",
+            "show_completed",
+            "show_completed_today",
+            "default_list_id",
+            "date_format",
+            "time_format",
+            "week_starts_on",
+            "vim_mode",
+            "notifications",
+            "quiet_hours",
+            "notification_rules",
+            "task_sort",
+            "status_segments",
+            "confirm",
+            "display",
+            "sync",
+            "serve",
+            "hooks",
+            "links",
+            "updates",
+            "retention",
+            "escalation",
+            "calendar",
+            "workspaces",
+        ],
+    ),
+    ("notifications", &["enabled", "nag_hours", "review"]),
+    (
+        "notifications.review",
+        &["enabled", "day", "stale_list_days", "undated_task_days"],
+    ),
+    ("quiet_hours", &["start", "end"]),
+    (
+        "confirm",
+        &[
+            "delete_task",
+            "bulk_delete",
+            "delete_list",
+            "delete_tag",
+            "reset_list",
+            "default_yes",
+            "bulk_limit",
+        ],
+    ),
+    (
+        "display",
+        &[
+            "density",
+            "accessible",
+            "high_contrast",
+            "colors",
+            "unicode",
+        ],
+    ),
+    (
+        "sync",
+        &[
+            "enabled",
+            "server",
+            "token",
+            "token_command",
+            "interval_secs",
+            "conflict_policy",
+            "max_batch_records",
+            "max_batch_bytes",
+            "batch_pause_ms",
+            "max_blob_bytes",
+            "network_check",
+        ],
+    ),
+    ("serve", &["port", "token", "calendar_token"]),
+    (
+        "hooks",
+        &["on_task_created", "on_task_completed", "on_sync_finished"],
+    ),
+    (
+        "links",
+        &[
+            "github_token",
+            "github_token_command",
+            "gitlab_token",
+            "gitlab_token_command",
+            "gitlab_host",
+            "jira_host",
+            "jira_email",
+            "jira_token",
+            "jira_token_command",
+            "close_issues",
+            "templates",
+        ],
+    ),
+    ("updates", &["check"]),
+    (
+        "retention",
+        &[
+            "archive_completed_after",
+            "purge_archived_after",
+            "run_on_start",
+        ],
+    ),
+    ("escalation", &["medium", "high", "urgent"]),
+    ("calendar", &["holidays", "skip_weekends"]),
+];
+
+/// A problem found in config.toml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Line in the file (1-based), if the problem can be pinned to one
+    pub line: Option<usize>,
+    /// Dotted key, e.g. `sync.server` (empty for syntax errors)
+    pub key: String,
+    /// What's wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Config {
+    /// Check the text of a config file for syntax errors, unknown keys and bad values
+    pub fn validate(content: &str) -> Vec<ConfigIssue> {
+        let doc = match toml::de::DeTable::parse(content) {
+            Ok(doc) => doc,
+            Err(e) => {
+                return vec![ConfigIssue {
+                    line: e.span().map(|span| line_at(content, span.start)),
+                    key: String::new(),
+                    message: e.message().to_string(),
+                }];
+            }
+        };
+
+        let mut issues = Vec::new();
+        let mut lines = HashMap::new();
+        for (key, value) in doc.get_ref().iter() {
+            let name = key.get_ref().as_ref();
+            let line = line_at(content, key.span().start);
+            lines.insert(name.to_string(), line);
+            if let Some(message) = unknown_key("", name) {
+                issues.push(ConfigIssue {
+                    line: Some(line),
+                    key: name.to_string(),
+                    message,
+                });
+                continue;
+            }
+            if let toml::de::DeValue::Table(table) = value.get_ref()
+                && name != "task_sort"
+            {
+                check_table(content, name, table, &mut lines, &mut issues);
+            }
+        }
+
+        match toml::from_str::<Config>(content) {
+            Ok(config) => {
+                for (key, message) in config.check_values() {
+                    issues.push(ConfigIssue {
+                        line: lines.get(&key).copied(),
+                        key,
+                        message,
+                    });
+                }
+            }
+            Err(e) => issues.push(ConfigIssue {
+                line: e.span().map(|span| line_at(content, span.start)),
+                key: String::new(),
+                message: e.message().to_string(),
+            }),
+        }
+
+        issues.sort_by_key(|issue| issue.line);
+        issues
+    }
+
+    /// Validate the config file at `path`; a missing file has no problems
+    pub fn check_file(path: &PathBuf) -> Result<Vec<ConfigIssue>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(path).context("Failed to read config file")?;
+        Ok(Self::validate(&content))
+    }
+
+    /// Values that parse but make no sense, as (dotted key, message) pairs
+    pub fn check_values(&self) -> Vec<(String, String)> {
+        let mut issues = Vec::new();
+        let mut issue = |key: &str, message: String| issues.push((key.to_string(), message));
+
+        if let Some(server) = &self.sync.server {
+            let host = server
+                .strip_prefix("https://")
+                .or_else(|| server.strip_prefix("http://"))
+                .and_then(|rest| rest.split(['/', ':']).next())
+                .unwrap_or("");
+            if host.is_empty() || server.contains(char::is_whitespace) {
+                issue(
+                    "sync.server",
+                    format!("\"{}\" is not a valid http:// or https:// URL", server),
+                );
+            }
+        }
+        match self.sync.interval_secs {
+            1..=9 => issue(
+                "sync.interval_secs",
+                format!(
+                    "syncing every {}s is too often; use 0 for manual sync or at least 10",
+                    self.sync.interval_secs
+                ),
+            ),
+            secs if secs > 86_400 => issue(
+                "sync.interval_secs",
+                "more than a day between syncs; use 0 for manual sync only".to_string(),
+            ),
+            _ => {}
+        }
+        if self.sync.enabled && self.sync.server.is_none() {
+            issue(
+                "sync.enabled",
+                "sync is enabled but no server is set".to_string(),
+            );
+        } else if self.sync.enabled && !self.sync.has_token() {
+            issue(
+                "sync.enabled",
+                "sync is enabled but no token or token_command is set".to_string(),
+            );
+        }
+        if self.sync.token.is_some() && self.sync.token_command.is_some() {
+            issue(
+                "sync.token",
+                "ignored because token_command is set".to_string(),
+            );
+        }
+        if self.serve.port == 0 {
+            issue(
+                "serve.port",
+                "port 0 is not a port to listen on".to_string(),
+            );
+        }
+        for (key, value) in [
+            (
+                "retention.archive_completed_after",
+                &self.retention.archive_completed_after,
+            ),
+            (
+                "retention.purge_archived_after",
+                &self.retention.purge_archived_after,
+            ),
+            ("escalation.medium", &self.escalation.medium),
+            ("escalation.high", &self.escalation.high),
+            ("escalation.urgent", &self.escalation.urgent),
+        ] {
+            if let Some(Err(e)) = value.as_deref().map(parse_age) {
+                issue(key, e);
+            }
+        }
+        for (prefix, template) in &self.links.templates {
+            let key = format!("links.templates.{}", prefix);
+            let is_key = prefix.len() > 2
+                && prefix.starts_with(|c: char| c.is_ascii_uppercase())
+                && prefix.strip_suffix('-').is_some_and(|key| {
+                    key.chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                });
+            if prefix != "#" && !is_key {
+                issue(
+                    &key,
+                    "use \"#\" or a key like \"OPS-\" (capitals, then a dash)".to_string(),
+                );
+            } else if !template.contains("{id}") && !template.contains("{ref}") {
+                issue(
+                    &key,
+                    "the URL needs {id} (the number) or {ref} (the whole reference)".to_string(),
+                );
+            }
+        }
+        let mut names = vec![DEFAULT_WORKSPACE.to_string()];
+        for workspace in &self.workspaces {
+            let name = workspace.name.trim().to_lowercase();
+            if name.is_empty() || workspace.path.trim().is_empty() {
+                issue(
+                    "workspaces",
+                    "a workspace needs a name and a path".to_string(),
+                );
+            } else if names.contains(&name) {
+                issue(
+                    "workspaces",
+                    format!("\"{}\" is used more than once", workspace.name),
+                );
+            }
+            names.push(name);
+        }
+        for rule in &self.notification_rules {
+            if rule.list.is_none() && rule.tag.is_none() {
+                issue(
+                    "notification_rules",
+                    "a rule needs a list or a tag to apply to".to_string(),
+                );
+            }
+        }
+        if !valid_format(&self.date_format) {
+            issue(
+                "date_format",
+                format!("\"{}\" is not a valid date format", self.date_format),
+            );
+        }
+        if !valid_format(&self.time_format) {
+            issue(
+                "time_format",
+                format!("\"{}\" is not a valid time format", self.time_format),
+            );
+        }
+        if let Some(id) = &self.default_list_id
+            && uuid::Uuid::parse_str(id).is_err()
+        {
+            issue("default_list_id", format!("\"{}\" is not a list ID", id));
+        }
+        if let Some(quiet) = self.quiet_hours
+            && quiet.start == quiet.end
+        {
+            issue(
+                "quiet_hours.start",
+                "start and end are the same, so quiet hours never apply".to_string(),
+            );
+        }
+        issues
+    }
+
+    /// Set a dotted key such as `sync.interval_secs` from command-line text
+    ///
+    /// `true`, `false`, numbers and `[...]` arrays are read as TOML, anything else as a
+    /// string. An empty value resets the key to its default.
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<()> {
+        let (table, name) = key.rsplit_once('.').unwrap_or(("", key));
+        if KNOWN_KEYS.iter().any(|(t, _)| *t == key && !key.is_empty()) {
+            anyhow::bail!(
+                "{} is a table; set one of its keys, e.g. {}.<key>",
+                key,
+                key
+            );
+        }
+        if table != "task_sort"
+            && let Some(message) = unknown_key(table, name)
+        {
+            anyhow::bail!("{}: {}", key, message);
+        }
+
+        let mut doc = toml::Table::try_from(&*self).context("Failed to serialize config")?;
+        let mut target = &mut doc;
+        for part in table.split('.').filter(|p| !p.is_empty()) {
+            target = target
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .with_context(|| format!("{} is not a table", table))?;
+        }
+        if value.is_empty() {
+            target.remove(name);
+        } else {
+            let literal = toml::from_str::<toml::Table>(&format!("v = {}", value))
+                .ok()
+                .and_then(|mut t| t.remove("v"))
+                .filter(|v| {
+                    matches!(
+                        v,
+                        toml::Value::Boolean(_)
+                            | toml::Value::Integer(_)
+                            | toml::Value::Float(_)
+                            | toml::Value::Array(_)
+                    )
+                });
+            target.insert(
+                name.to_string(),
+                literal.unwrap_or_else(|| toml::Value::String(value.to_string())),
+            );
+        }
+
+        let config: Config = doc
+            .try_into()
+            .map_err(|e: toml::de::Error| anyhow::anyhow!("{}: {}", key, e.message()))?;
+        if let Some((_, message)) = config.check_values().into_iter().find(|(k, _)| k == key) {
+            anyhow::bail!("{}: {}", key, message);
+        }
+        *self = config;
+        Ok(())
+    }
+}
+
+/// Report the keys of `table` (at dotted path `name`) it doesn't accept,
+/// going into the tables nested in it, and note the line of each
+fn check_table(
+    content: &str,
+    name: &str,
+    table: &toml::de::DeTable,
+    lines: &mut HashMap<String, usize>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (sub, value) in table.iter() {
+        let dotted = format!("{}.{}", name, sub.get_ref());
+        let line = line_at(content, sub.span().start);
+        lines.insert(dotted.clone(), line);
+        if let Some(message) = unknown_key(name, sub.get_ref()) {
+            issues.push(ConfigIssue {
+                line: Some(line),
+                key: dotted,
+                message,
+            });
+        } else if let toml::de::DeValue::Table(nested) = value.get_ref()
+            && KNOWN_KEYS.iter().any(|(t, _)| *t == dotted)
+        {
+            check_table(content, &dotted, nested, lines, issues);
+        }
+    }
+}
+
+/// Message for a key that `table` doesn't accept, with a suggestion if one is close
+fn unknown_key(table: &str, name: &str) -> Option<String> {
+    let known = KNOWN_KEYS.iter().find(|(t, _)| *t == table)?.1;
+    if known.contains(&name) {
+        return None;
+    }
+    let closest = known
+        .iter()
+        .map(|k| (edit_distance(k, name), k))
+        .filter(|(d, _)| *d <= 2)
+        .min();
+    Some(match closest {
+        Some((_, suggestion)) => format!("unknown key, did you mean `{}`?", suggestion),
+        None => "unknown key".to_string(),
+    })
+}
+
+/// Levenshtein distance between two short strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(prev + cost);
+            prev = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// 1-based line number of a byte offset
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let mut config = Config::default();
+        config.sync.server = Some("https://sync.example.com".to_string());
+        config.sync.token_command = Some("pass show tickit".to_string());
+        config.default_list_id = Some(uuid::Uuid::new_v4().to_string());
+        config.quiet_hours = Some(QuietHours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        });
+        let content = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(Config::validate(&content), Vec::new());
+    }
+
+    #[test]
+    fn test_template_matches_defaults() {
+        assert_eq!(Config::validate(TEMPLATE), Vec::new());
+        let parsed: Config = toml::from_str(TEMPLATE).unwrap();
+        assert_eq!(
+            toml::to_string(&parsed).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+
+        // Every option is mentioned, set or as an example
+        for (_, keys) in KNOWN_KEYS {
+            for key in *keys {
+                assert!(
+                    TEMPLATE.contains(&format!("{} =", key))
+                        || TEMPLATE.contains(&format!("{}]", key)),
+                    "{} is missing from the template",
+                    key
+                );
+            }
+        }
+        for theme in Theme::all() {
+            let name = toml::Value::try_from(Theme(*theme)).unwrap();
+            assert!(TEMPLATE.contains(name.as_str().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_lines() {
+        let content = "vim_mod = true\n\n[sync]\nserver = \"example.com\"\ninterval_secs = 5\n";
+        let issues = Config::validate(content);
+        let found: Vec<(Option<usize>, &str)> =
+            issues.iter().map(|i| (i.line, i.key.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (Some(1), "vim_mod"),
+                (Some(4), "sync.server"),
+                (Some(5), "sync.interval_secs"),
+            ]
+        );
+        assert!(issues[0].message.contains("`vim_mode`"));
+
+        let issues = Config::validate("[display]\ndensity = \"tiny\"\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_update_check_schedule() {
+        let config: Config = toml::from_str("[updates]\ncheck = \"on-start\"\n").unwrap();
+        assert_eq!(config.updates.check, UpdateCheck::OnStart);
+        assert_eq!(Config::default().updates.check, UpdateCheck::Daily);
+
+        let now = Utc::now();
+        let hour_ago = Some(now - Duration::hours(1));
+        assert!(UpdateCheck::OnStart.is_due(hour_ago, now));
+        assert!(UpdateCheck::Daily.is_due(None, now));
+        assert!(!UpdateCheck::Daily.is_due(hour_ago, now));
+        assert!(UpdateCheck::Daily.is_due(Some(now - Duration::days(2)), now));
+        assert!(!UpdateCheck::Never.is_due(None, now));
+    }
+
+    #[test]
+    fn test_save_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        Config::write_template(&path).unwrap();
+
+        // What the TUI's settings do
+        let mut config = Config::load_from(&path).unwrap();
+        config.show_completed = false;
+        config.sync.interval_secs = 600;
+        config.sync.server = Some("https://sync.example.com".to_string());
+        config.save_to(&path).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        for comment in TEMPLATE.lines().filter(|l| l.starts_with('#')) {
+            assert!(saved.contains(comment), "lost {:?}", comment);
+        }
+        assert!(saved.contains("\nshow_completed = false\n"));
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(
+            toml::to_string(&loaded).unwrap(),
+            toml::to_string(&config).unwrap()
+        );
+
+        // Saving what's there changes nothing; unsetting removes the key
+        loaded.save_to(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+        config.sync.server = None;
+        config.save_to(&path).unwrap();
+        assert!(Config::load_from(&path).unwrap().sync.server.is_none());
+
+        // A file with errors isn't saved over
+        std::fs::write(&path, "theme = \n").unwrap();
+        assert!(config.save_to(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theme = \n");
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+        assert_eq!(parse_age(" 6w "), Ok(Duration::days(42)));
+        assert_eq!(parse_age("3m"), Ok(Duration::days(90)));
+        assert_eq!(parse_age("1y"), Ok(Duration::days(365)));
+        for bad in [
+            "",
+            "d",
+            "30",
+            "-1d",
+            "3x",
+            "3é",
+            "é",
+            "3dé",
+            "１d",
+            "99999999999999y",
+        ] {
+            assert!(parse_age(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_escalation_rules() {
+        let config = EscalationConfig {
+            urgent: Some("1d".to_string()),
+            high: Some("1w".to_string()),
+            ..EscalationConfig::default()
+        };
+        let now = Utc::now();
+        let due = |days| now + Duration::days(days);
+        assert_eq!(config.priority_for(due(-3), now), Some(Priority::Urgent));
+        assert_eq!(config.priority_for(due(1), now), Some(Priority::Urgent));
+        assert_eq!(config.priority_for(due(5), now), Some(Priority::High));
+        assert_eq!(config.priority_for(due(30), now), None);
+        assert_eq!(EscalationConfig::default().priority_for(due(0), now), None);
+    }
+
+    #[test]
+    fn test_date_formats() {
+        let mut config = Config::default();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        let at = date.and_hms_opt(14, 7, 0).unwrap();
+        assert_eq!(config.format_datetime(at), "2026-03-05 14:07");
+
+        config.date_format = "%d.%m.%Y".to_string();
+        config.time_format = "%-I:%M %p".to_string();
+        assert_eq!(config.format_datetime(at), "05.03.2026 2:07 PM");
+
+        // Invalid formats fall back instead of panicking
+        config.date_format = "%Q".to_string();
+        assert_eq!(config.format_date(date), "2026-03-05");
+
+        config.week_starts_on = Weekday::Sun;
+        let days: Vec<_> = config.week_days().collect();
+        assert_eq!(days.first(), Some(&Weekday::Sun));
+        assert_eq!(days.last(), Some(&Weekday::Sat));
+        let parsed: Config = toml::from_str("week_starts_on = \"sunday\"").unwrap();
+        assert_eq!(parsed.week_starts_on, Weekday::Sun);
+    }
+
+    #[test]
+    fn test_set_key() {
+        let mut config = Config::default();
+        config.set_key("sync.interval_secs", "600").unwrap();
+        assert_eq!(config.sync.interval_secs, 600);
+        config.set_key("display.high_contrast", "true").unwrap();
+        assert!(config.display.high_contrast);
+        config.set_key("sync.server", "https://a.example").unwrap();
+        assert_eq!(config.sync.server.as_deref(), Some("https://a.example"));
+        config.set_key("sync.server", "").unwrap();
+        assert_eq!(config.sync.server, None);
+        config.set_key("display.colors", "256").unwrap();
+        assert_eq!(config.display.colors, ColorDepth::Ansi256);
+        assert!(!config.display.truecolor());
+        config.set_key("display.unicode", "false").unwrap();
+        assert!(!config.display.unicode());
+        let saved: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved.display.colors, ColorDepth::Ansi256);
+
+        assert!(config.set_key("sync.intervl_secs", "600").is_err());
+        assert!(config.set_key("sync.interval_secs", "3").is_err());
+        assert!(config.set_key("display.density", "tiny").is_err());
+        assert!(config.set_key("display.colors", "16").is_err());
+        assert!(config.set_key("sync", "1").is_err());
+        assert_eq!(config.sync.interval_secs, 600);
+
+        config
+            .set_key("notifications.review.stale_list_days", "21")
+            .unwrap();
+        assert_eq!(config.notifications.review.stale_list_days, 21);
+        assert!(config.set_key("notifications.review.days", "3").is_err());
+    }
+
+    #[test]
+    fn test_notifications_config() {
+        // The old `notifications = false` still turns them off
+        let config: Config = toml::from_str("notifications = false").unwrap();
+        assert!(!config.notifications.enabled);
+        assert!(!config.notifications.review.enabled);
+
+        let content = "[notifications.review]\nenabled = true\nday = \"Fri\"\nstale_days = 3\n";
+        let config: Config = toml::from_str(content).unwrap();
+        assert!(config.notifications.enabled);
+        assert!(config.notifications.review.enabled);
+        assert_eq!(config.notifications.review.day, Weekday::Fri);
+        let issues = Config::validate(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "notifications.review.stale_days");
+        assert_eq!(issues[0].line, Some(4));
+    }
+
+    #[test]
+    fn test_confirm_config() {
+        // Keys left out keep asking
+        let config: Config = toml::from_str("[confirm]\ndelete_task = false\n").unwrap();
+        assert!(!config.confirm.delete_task);
+        assert!(config.confirm.bulk_delete && config.confirm.delete_list);
+        assert!(config.confirm.delete_tag && config.confirm.reset_list);
+        assert!(config.confirm.default_yes);
+        assert_eq!(config.confirm.bulk_limit, 25);
+
+        let mut config = Config::default();
+        config.set_key("confirm.default_yes", "false").unwrap();
+        config.set_key("confirm.bulk_limit", "0").unwrap();
+        assert!(!config.confirm.default_yes);
+        assert_eq!(config.confirm.bulk_limit, 0);
+        assert!(config.set_key("confirm.purge", "false").is_err());
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let content = "[[workspaces]]\nname = \"Work\"\npath = \"/tmp/work.sqlite\"\n";
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.workspace_names(), ["default", "Work"]);
+        assert_eq!(
+            config.workspace_path("work").unwrap(),
+            PathBuf::from("/tmp/work.sqlite")
+        );
+        assert!(config.workspace_path("home").is_err());
+        assert!(Config::validate(content).is_empty());
+
+        let clash = "[[workspaces]]\nname = \"Default\"\npath = \"x.sqlite\"\n";
+        let issues = Config::validate(clash);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "workspaces");
+    }
 }
@@ -0,0 +1,1354 @@
+//! Library API for working with tasks without the TUI
+//!
+//! [`TickitCore`] owns the database and config, and the `tickit` CLI is built
+//! on it; the TUI holds one too and hands it the tasks it creates and
+//! completes, so hooks and linked issues work the same way. Its operations validate their input, record what they change in the
+//! undo [`journal`] and leave tombstones for sync, so tools that embed tickit
+//! behave the same way `tickit` itself does.
+//!
+//! ```no_run
+//! use tickit::core::{NewTask, TickitCore};
+//!
+//! let core = TickitCore::open()?;
+//! let inbox = core.db().get_inbox()?;
+//! let task = core.add_task(NewTask::new("Water the plants", inbox.id))?;
+//! core.complete(vec![task])?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::hooks::{self, Hook};
+use crate::issues::{self, IssueRef};
+use crate::journal;
+use crate::models::{Attachment, Comment, List, Priority, Tag, Task};
+use crate::quickadd;
+use crate::sync::{self, BlobStore, SyncClient};
+
+/// A task, list or tag that was asked for by name couldn't be resolved
+#[derive(Debug)]
+pub enum LookupError {
+    /// Nothing matches
+    NotFound(String),
+    /// Several tasks match and there was no way to ask which one
+    Ambiguous(String),
+}
+
+impl LookupError {
+    /// Process exit code the CLI uses for this error
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::NotFound(_) => 3,
+            Self::Ambiguous(_) => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(message) | Self::Ambiguous(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// A task to create with [`TickitCore::add_task`]
+#[derive(Debug, Clone)]
+pub struct NewTask {
+    pub title: String,
+    pub list_id: Uuid,
+    pub description: Option<String>,
+    /// Checked and completed by [`TickitCore::normalize_url`]
+    pub url: Option<String>,
+    pub priority: Priority,
+    pub due_date: Option<DateTime<Utc>>,
+    pub tag_ids: Vec<Uuid>,
+}
+
+impl NewTask {
+    /// A medium-priority task with nothing but a title
+    pub fn new(title: impl Into<String>, list_id: Uuid) -> Self {
+        Self {
+            title: title.into(),
+            list_id,
+            description: None,
+            url: None,
+            priority: Priority::default(),
+            due_date: None,
+            tag_ids: Vec::new(),
+        }
+    }
+}
+
+/// Changes for [`TickitCore::edit`]; fields left at `None` aren't touched
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    pub title: Option<String>,
+    /// `Some(None)` clears the description
+    pub description: Option<Option<String>>,
+    /// `Some(None)` clears the URL
+    pub url: Option<Option<String>>,
+    pub priority: Option<Priority>,
+    /// `Some(None)` clears the due date
+    pub due_date: Option<Option<DateTime<Utc>>>,
+    pub list_id: Option<Uuid>,
+    /// Tags to add (`true`) or remove (`false`)
+    pub tags: Vec<(Uuid, bool)>,
+    /// Hours between reminders while overdue (0: the configured default);
+    /// `Some(None)` stops nagging
+    pub nag_hours: Option<Option<u32>>,
+}
+
+impl TaskChanges {
+    /// Whether applying these changes would do nothing
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.url.is_none()
+            && self.priority.is_none()
+            && self.due_date.is_none()
+            && self.list_id.is_none()
+            && self.tags.is_empty()
+            && self.nag_hours.is_none()
+    }
+
+    fn apply(&self, task: &mut Task) {
+        if let Some(title) = &self.title {
+            task.title = title.clone();
+        }
+        if let Some(description) = &self.description {
+            task.description = description.clone();
+        }
+        if let Some(url) = &self.url {
+            task.url = url.clone();
+        }
+        if let Some(priority) = self.priority {
+            task.priority = priority;
+        }
+        if let Some(due) = self.due_date {
+            task.due_date = due;
+        }
+        if let Some(list_id) = self.list_id {
+            task.list_id = list_id;
+        }
+        for &(tag_id, add) in &self.tags {
+            task.tag_ids.retain(|&id| id != tag_id);
+            if add {
+                task.tag_ids.push(tag_id);
+            }
+        }
+        if let Some(nag_hours) = self.nag_hours {
+            task.nag_hours = nag_hours;
+        }
+    }
+}
+
+/// Which tasks [`TickitCore::tasks`] returns; the default is every open task
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    /// Words that must appear in the title or description
+    pub text: Option<String>,
+    pub list_id: Option<Uuid>,
+    pub tag_id: Option<Uuid>,
+    pub priority: Option<Priority>,
+    /// Only tasks due before this time
+    pub due_before: Option<DateTime<Utc>>,
+    pub include_completed: bool,
+}
+
+/// Tasks the `[retention]` policy archives and purges, see [`TickitCore::maintain`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Cleanup {
+    pub archived: Vec<Task>,
+    pub purged: Vec<Task>,
+}
+
+impl Cleanup {
+    /// Whether there's nothing to archive or purge
+    pub fn is_empty(&self) -> bool {
+        self.archived.is_empty() && self.purged.is_empty()
+    }
+}
+
+/// A task whose priority [`TickitCore::escalate`] raised
+#[derive(Debug, Clone, Serialize)]
+pub struct Escalation {
+    pub task: Task,
+    /// Priority before it was raised
+    pub from: Priority,
+}
+
+/// What a sync with the server did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncSummary {
+    /// Local changes uploaded
+    pub sent: usize,
+    /// Changes the server sent back
+    pub received: usize,
+    /// Of those, how many were applied locally
+    pub applied: usize,
+    /// Conflicts the server resolved in its own favor
+    pub conflicts: usize,
+    /// Records changed on both sides that kept local changes, per
+    /// `sync.conflict_policy`; they're uploaded on the next sync
+    pub kept_local: usize,
+    /// Attachments whose files aren't downloaded yet; they're tried again
+    /// on the next sync
+    pub missing_blobs: usize,
+}
+
+/// What [`TickitCore::refresh_links`] did
+#[derive(Debug, Default)]
+pub struct LinkRefresh {
+    /// Tasks whose title or state changed, as saved
+    pub updated: Vec<Task>,
+    /// Linked tasks already matching their issue
+    pub unchanged: usize,
+    /// Linked tasks whose issue couldn't be fetched
+    pub failed: Vec<(Task, anyhow::Error)>,
+}
+
+/// What [`TickitCore::import_jira`] did
+#[derive(Debug, Default)]
+pub struct JiraImport {
+    /// Tasks created for issues not linked yet
+    pub added: Vec<Task>,
+    /// Issues some task already links to
+    pub skipped: usize,
+}
+
+/// Tickit's task operations, for the CLI and for programs embedding tickit
+pub struct TickitCore {
+    db: Database,
+    config: Config,
+}
+
+impl TickitCore {
+    /// Open the default database with the settings in config.toml
+    ///
+    /// A config.toml that fails to parse is an error, so hooks and issue
+    /// links it sets aren't skipped without notice.
+    pub fn open() -> Result<Self> {
+        let path = Config::default_path()?;
+        let config = Config::load_from(&path).with_context(|| {
+            format!(
+                "Could not load {} (`tickit config check` shows what's wrong)",
+                path.display()
+            )
+        })?;
+        Ok(Self::new(Database::open()?, config))
+    }
+
+    /// Work on `db` with `config`
+    pub fn new(db: Database, config: Config) -> Self {
+        Self { db, config }
+    }
+
+    /// Use `config` from now on, e.g. after config.toml changed
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// The underlying database, for reads the API doesn't cover
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
+
+    /// Settings in effect
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Give the database back, e.g. to hand it to the TUI
+    pub fn into_db(self) -> Database {
+        self.db
+    }
+
+    // ==================== Lookups ====================
+
+    /// The list called `name`, ignoring case
+    pub fn list_named(&self, name: &str) -> Result<Option<List>> {
+        Ok(self
+            .db
+            .get_lists()?
+            .into_iter()
+            .find(|l| l.name.to_lowercase() == name.to_lowercase()))
+    }
+
+    /// The tag called `name`, ignoring case
+    pub fn tag_named(&self, name: &str) -> Result<Option<Tag>> {
+        Ok(self
+            .db
+            .get_tags()?
+            .into_iter()
+            .find(|t| t.name.to_lowercase() == name.to_lowercase()))
+    }
+
+    /// The list called `name`, or a [`LookupError::NotFound`]
+    pub fn find_list(&self, name: &str) -> Result<List> {
+        self.list_named(name)?
+            .ok_or_else(|| LookupError::NotFound(format!("List not found: {}", name)).into())
+    }
+
+    /// The tag called `name`, or a [`LookupError::NotFound`]
+    pub fn find_tag(&self, name: &str) -> Result<Tag> {
+        self.tag_named(name)?
+            .ok_or_else(|| LookupError::NotFound(format!("Tag not found: {}", name)).into())
+    }
+
+    // ==================== Lists and tags ====================
+
+    /// Create a list, refusing empty names and names already taken
+    pub fn create_list(&self, name: &str, icon: Option<&str>) -> Result<List> {
+        let name = self.check_list_name(name, None)?;
+        let mut list = List::new(&name);
+        if let Some(icon) = icon {
+            list = list.with_icon(icon);
+        }
+        self.db.insert_list(&list)?;
+        Ok(list)
+    }
+
+    /// `name` trimmed, refusing empty names and names another list has
+    ///
+    /// `renaming` is the list being renamed, which may keep its own name.
+    pub fn check_list_name(&self, name: &str, renaming: Option<Uuid>) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("List name cannot be empty");
+        }
+        if let Some(list) = self.list_named(name)?
+            && Some(list.id) != renaming
+        {
+            anyhow::bail!("A list named \"{}\" already exists", name);
+        }
+        Ok(name.to_string())
+    }
+
+    /// Create a tag, refusing empty names and names already taken
+    pub fn create_tag(&self, name: &str, color: Option<&str>) -> Result<Tag> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Tag name cannot be empty");
+        }
+        if self.tag_named(name)?.is_some() {
+            anyhow::bail!("A tag named \"{}\" already exists", name);
+        }
+        let mut tag = Tag::new(name);
+        if let Some(color) = color {
+            tag = tag.with_color(color);
+        }
+        self.db.insert_tag(&tag)?;
+        Ok(tag)
+    }
+
+    // ==================== Tasks ====================
+
+    /// Tasks matching `query`
+    pub fn tasks(&self, query: &TaskQuery) -> Result<Vec<Task>> {
+        let tasks = match &query.text {
+            Some(text) => self.db.search_tasks(text)?,
+            None => self.db.get_all_tasks()?,
+        };
+        Ok(tasks
+            .into_iter()
+            .filter(|t| query.include_completed || !t.completed)
+            .filter(|t| query.list_id.is_none_or(|id| t.list_id == id))
+            .filter(|t| query.tag_id.is_none_or(|id| t.tag_ids.contains(&id)))
+            .filter(|t| query.priority.is_none_or(|p| t.priority == p))
+            .filter(|t| {
+                query
+                    .due_before
+                    .is_none_or(|d| t.due_date.is_some_and(|due| due < d))
+            })
+            .collect())
+    }
+
+    /// Create a task after checking its title and URL
+    pub fn add_task(&self, new: NewTask) -> Result<Task> {
+        let mut added = self.add_tasks(vec![new])?;
+        Ok(added.remove(0))
+    }
+
+    /// Create several tasks at once, as one undoable operation; if any is
+    /// invalid, none are added
+    pub fn add_tasks(&self, new: Vec<NewTask>) -> Result<Vec<Task>> {
+        let mut tasks = Vec::with_capacity(new.len());
+        for new in new {
+            let title = new.title.trim();
+            if title.is_empty() {
+                anyhow::bail!("Task title cannot be empty");
+            }
+            let url = match new.url.as_deref() {
+                Some(url) => Self::normalize_url(url).map_err(anyhow::Error::msg)?,
+                None => None,
+            };
+
+            let mut task = Task::new(title, new.list_id);
+            task.description = new.description.filter(|d| !d.trim().is_empty());
+            task.url = url;
+            task.priority = new.priority;
+            task.due_date = new.due_date;
+            for tag_id in new.tag_ids {
+                if !task.tag_ids.contains(&tag_id) {
+                    task.tag_ids.push(tag_id);
+                }
+            }
+            tasks.push(task);
+        }
+
+        self.db.insert_tasks(&tasks)?;
+        let ids: Vec<Uuid> = tasks.iter().map(|t| t.id).collect();
+        journal::record(&self.db, describe("add", &tasks), &[], &ids)?;
+        self.run_hooks(&tasks, []);
+        Ok(tasks)
+    }
+
+    /// Copy `task`, open and with the same details, into `list_id` or its own list
+    pub fn duplicate(&self, task: &Task, list_id: Option<Uuid>) -> Result<Task> {
+        let mut copy = task.duplicate();
+        if let Some(list_id) = list_id {
+            copy.list_id = list_id;
+        }
+        self.db.insert_task(&copy)?;
+        journal::record(
+            &self.db,
+            describe("duplicate", std::slice::from_ref(task)),
+            &[],
+            &[copy.id],
+        )?;
+        self.run_hooks([&copy], []);
+        Ok(copy)
+    }
+
+    /// Drop a title into the Inbox with no other details
+    pub fn capture(&self, title: &str) -> Result<Uuid> {
+        let id = Self::capture_into(&self.db, title)?;
+        if let Ok(task) = self.db.get_task(id) {
+            self.run_hooks([&task], []);
+        }
+        Ok(id)
+    }
+
+    /// Drop a title into the Inbox of `db`: one insert and its undo entry
+    ///
+    /// Doesn't load config.toml or run hooks, for `tickit capture` bound to
+    /// a global hotkey.
+    pub fn capture_into(db: &Database, title: &str) -> Result<Uuid> {
+        let title = title.trim();
+        if title.is_empty() {
+            anyhow::bail!("Nothing to capture");
+        }
+        let id = db.capture(title)?;
+        journal::record(db, format!("capture \"{}\"", title), &[], &[id])?;
+        Ok(id)
+    }
+
+    /// Complete the open ones among `tasks`, returning them
+    ///
+    /// Linked issues are left alone; pass the result to
+    /// [`close_issues`](Self::close_issues) to close them too.
+    pub fn complete(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
+        let tasks: Vec<Task> = tasks.into_iter().filter(|t| !t.completed).collect();
+        let tasks = self.update("complete", tasks, Task::complete)?;
+        self.run_hooks([], &tasks);
+        Ok(tasks)
+    }
+
+    /// Reopen the completed ones among `tasks`, returning them
+    pub fn reopen(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
+        let tasks: Vec<Task> = tasks.into_iter().filter(|t| t.completed).collect();
+        self.update("reopen", tasks, Task::uncomplete)
+    }
+
+    /// Apply `changes` to every task, after checking the title and URL
+    pub fn edit(&self, tasks: Vec<Task>, changes: &TaskChanges) -> Result<Vec<Task>> {
+        let mut changes = changes.clone();
+        if let Some(title) = &changes.title {
+            let title = title.trim();
+            if title.is_empty() {
+                anyhow::bail!("Task title cannot be empty");
+            }
+            changes.title = Some(title.to_string());
+        }
+        if let Some(Some(description)) = &changes.description
+            && description.trim().is_empty()
+        {
+            changes.description = Some(None);
+        }
+        if let Some(Some(url)) = &changes.url {
+            changes.url = Some(Self::normalize_url(url).map_err(anyhow::Error::msg)?);
+        }
+        self.update("edit", tasks, |t| changes.apply(t))
+    }
+
+    /// Push the due date of every task to `when`, as read by
+    /// [`quickadd::parse_defer`]: `monday`, `tonight`, `+1w`...
+    pub fn defer(&self, tasks: Vec<Task>, when: &str) -> Result<Vec<Task>> {
+        if quickadd::parse_defer(when, None).is_none() {
+            anyhow::bail!("Invalid date: {} (try monday, tonight, 3d or +1w)", when);
+        }
+        self.update("defer", tasks, |t| {
+            t.due_date = quickadd::parse_defer(when, t.due_date);
+            t.updated_at = Utc::now();
+        })
+    }
+
+    /// Move a task to another list
+    pub fn move_task(&self, task: Task, list_id: Uuid) -> Result<Task> {
+        let mut moved = self.update("move", vec![task], |t| t.list_id = list_id)?;
+        Ok(moved.remove(0))
+    }
+
+    /// Add (`true`) or remove (`false`) tags on a task
+    pub fn retag(&self, task: Task, tags: &[(Uuid, bool)]) -> Result<Task> {
+        let changes = TaskChanges {
+            tags: tags.to_vec(),
+            ..TaskChanges::default()
+        };
+        let mut retagged = self.update("retag", vec![task], |t| changes.apply(t))?;
+        Ok(retagged.remove(0))
+    }
+
+    /// Add a timestamped comment to a task
+    pub fn add_comment(&self, task: &Task, body: &str) -> Result<Comment> {
+        let body = body.trim();
+        if body.is_empty() {
+            anyhow::bail!("Comment is empty");
+        }
+        let comment = Comment::new(task.id, body);
+        self.db.insert_comment(&comment)?;
+        Ok(comment)
+    }
+
+    /// Delete a comment, leaving a tombstone so sync deletes it elsewhere too
+    pub fn delete_comment(&self, comment: &Comment) -> Result<()> {
+        self.db.delete_comment(comment.id)?;
+        self.db.record_tombstone(comment.id, "comment")
+    }
+
+    /// Attach the file at `path` to a task, copying it into the blob store
+    /// next to the database
+    pub fn attach(&self, task: &Task, path: &std::path::Path) -> Result<Attachment> {
+        let name = path
+            .file_name()
+            .with_context(|| format!("{} is not a file", path.display()))?
+            .to_string_lossy()
+            .into_owned();
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Could not read {}", path.display()))?
+            .len();
+        let limit = self.config.sync.max_blob_bytes;
+        if limit > 0 && size > limit {
+            anyhow::bail!(
+                "{} is {} bytes, over sync.max_blob_bytes ({})",
+                name,
+                size,
+                limit
+            );
+        }
+        let blob = BlobStore::for_database(&self.db)?.add(path)?;
+        let attachment = Attachment::new(task.id, name, blob.hash, blob.size);
+        self.db.insert_attachment(&attachment)?;
+        Ok(attachment)
+    }
+
+    /// Remove an attachment, leaving a tombstone so sync removes it
+    /// elsewhere too
+    pub fn detach(&self, attachment: &Attachment) -> Result<()> {
+        self.db.delete_attachment(attachment.id)?;
+        self.db.record_tombstone(attachment.id, "attachment")
+    }
+
+    /// Fold `others` into `task` (see [`Task::absorb`]) and delete them,
+    /// keeping their comments and dependencies on `task`
+    pub fn merge(&self, task: Task, others: &[Task]) -> Result<Task> {
+        if others.iter().any(|other| other.id == task.id) {
+            anyhow::bail!("Can't merge a task with itself");
+        }
+        let label = match others {
+            [other] => format!("merge \"{}\" into \"{}\"", other.title, task.title),
+            _ => format!("merge {} tasks into \"{}\"", others.len(), task.title),
+        };
+        let mut before = vec![task.clone()];
+        before.extend_from_slice(others);
+        journal::record(&self.db, label, &before, &[])?;
+
+        let mut merged = task;
+        for other in others {
+            merged.absorb(other);
+        }
+        self.db.update_task(&merged)?;
+        for other in others {
+            self.db.carry_over_task(other.id, merged.id)?;
+            self.db.record_audit(
+                merged.id,
+                &format!("merge:{}", other.id),
+                &format!("Merged in \"{}\"", other.title),
+            )?;
+        }
+        let ids: Vec<Uuid> = others.iter().map(|t| t.id).collect();
+        self.db.delete_tasks(&ids)?;
+        Ok(merged)
+    }
+
+    /// Store tasks changed outside the core, such as in the TUI, as one
+    /// undoable operation, running the hooks for those created or completed
+    ///
+    /// `before` holds the changed and deleted tasks as they were, `after`
+    /// the created and changed ones as they are to be; a task only in
+    /// `before` is deleted.
+    pub fn save(&self, label: &str, before: &[Task], after: &[Task]) -> Result<()> {
+        let was = |task: &Task| before.iter().find(|t| t.id == task.id);
+        let (changed, created): (Vec<Task>, Vec<Task>) =
+            after.iter().cloned().partition(|t| was(t).is_some());
+        let deleted: Vec<Uuid> = before
+            .iter()
+            .filter(|t| !after.iter().any(|a| a.id == t.id))
+            .map(|t| t.id)
+            .collect();
+
+        let created_ids: Vec<Uuid> = created.iter().map(|t| t.id).collect();
+        journal::record(&self.db, label, before, &created_ids)?;
+        self.db.insert_tasks(&created)?;
+        self.db.update_tasks(&changed)?;
+        self.db.delete_tasks(&deleted)?;
+
+        let completed = changed
+            .iter()
+            .filter(|t| t.completed && was(t).is_some_and(|b| !b.completed));
+        self.run_hooks(&created, completed);
+        Ok(())
+    }
+
+    /// Delete tasks, leaving tombstones so sync deletes them elsewhere too
+    pub fn delete(&self, tasks: &[Task]) -> Result<()> {
+        journal::record(&self.db, describe("delete", tasks), tasks, &[])?;
+        let ids: Vec<Uuid> = tasks.iter().map(|t| t.id).collect();
+        self.db.delete_tasks(&ids)
+    }
+
+    /// Completed tasks finished at least `older_than` ago, which [`archive`](Self::archive) would hide
+    pub fn archivable(&self, older_than: Duration) -> Result<Vec<Task>> {
+        let cutoff = Utc::now() - older_than;
+        Ok(self
+            .db
+            .get_all_tasks()?
+            .into_iter()
+            .filter(|t| t.completed && t.completed_at.unwrap_or(t.updated_at) <= cutoff)
+            .collect())
+    }
+
+    /// Hide tasks from every view while keeping them in the database
+    pub fn archive(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
+        let now = Utc::now();
+        self.update("archive", tasks, |t| t.archived_at = Some(now))
+    }
+
+    /// Tasks archived at least `older_than` ago, which [`purge`](Self::purge) would delete
+    pub fn purgeable(&self, older_than: Duration) -> Result<Vec<Task>> {
+        let cutoff = Utc::now() - older_than;
+        Ok(self
+            .db
+            .get_archived_tasks()?
+            .into_iter()
+            .filter(|t| t.archived_at.is_some_and(|at| at <= cutoff))
+            .collect())
+    }
+
+    /// Permanently delete archived tasks
+    pub fn purge(&self, tasks: &[Task]) -> Result<()> {
+        journal::record(&self.db, describe("purge", tasks), tasks, &[])?;
+        let ids: Vec<Uuid> = tasks.iter().map(|t| t.id).collect();
+        self.db.delete_tasks(&ids)
+    }
+
+    /// What [`maintain`](Self::maintain) would archive and purge right now
+    pub fn cleanup_due(&self) -> Result<Cleanup> {
+        let policy = &self.config.retention;
+        Ok(Cleanup {
+            archived: match policy.archive_after() {
+                Some(age) => self.archivable(age)?,
+                None => Vec::new(),
+            },
+            purged: match policy.purge_after() {
+                Some(age) => self.purgeable(age)?,
+                None => Vec::new(),
+            },
+        })
+    }
+
+    /// Apply the `[retention]` policy: purge tasks archived long enough ago,
+    /// then archive tasks completed long enough ago
+    pub fn maintain(&self) -> Result<Cleanup> {
+        let due = self.cleanup_due()?;
+        if !due.purged.is_empty() {
+            self.purge(&due.purged)?;
+        }
+        let archived = if due.archived.is_empty() {
+            Vec::new()
+        } else {
+            self.archive(due.archived)?
+        };
+        Ok(Cleanup {
+            archived,
+            purged: due.purged,
+        })
+    }
+
+    /// Raise open tasks whose due date is close, per `[escalation]`, noting
+    /// why in each task's audit trail
+    ///
+    /// A task is raised to a given priority only once, so lowering it again
+    /// by hand sticks. Not journaled: `tickit undo` keeps the user's last command.
+    pub fn escalate(&self) -> Result<Vec<Escalation>> {
+        let rules = &self.config.escalation;
+        let now = Utc::now();
+        let mut raised = Vec::new();
+        for mut task in self.db.get_tasks_with_filter(None, Some(false), None)? {
+            let Some(due) = task.due_date else {
+                continue;
+            };
+            let Some(priority) = rules.priority_for(due, now) else {
+                continue;
+            };
+            let kind = format!("escalate:{}", priority.name().to_lowercase());
+            if priority <= task.priority || self.db.has_audit(task.id, &kind)? {
+                continue;
+            }
+
+            let from = task.priority;
+            task.priority = priority;
+            task.updated_at = now;
+            self.db.update_task(&task)?;
+            let when = if due < now { "was due" } else { "is due" };
+            self.db.record_audit(
+                task.id,
+                &kind,
+                &format!(
+                    "Priority raised from {} to {}: it {} {}",
+                    from.name(),
+                    priority.name(),
+                    when,
+                    self.config.format_date(due.date_naive())
+                ),
+            )?;
+            raised.push(Escalation { task, from });
+        }
+        Ok(raised)
+    }
+
+    /// Revert the last operation, returning it (or `None` if there was none)
+    pub fn undo(&self) -> Result<Option<journal::Entry>> {
+        journal::undo(&self.db)
+    }
+
+    /// Journal `tasks` under `verb`, change each with `change`, and save them
+    fn update(
+        &self,
+        verb: &str,
+        mut tasks: Vec<Task>,
+        change: impl Fn(&mut Task),
+    ) -> Result<Vec<Task>> {
+        journal::record(&self.db, describe(verb, &tasks), &tasks, &[])?;
+        for task in &mut tasks {
+            change(task);
+        }
+        self.db.update_tasks(&tasks)?;
+        Ok(tasks)
+    }
+
+    // ==================== Issue links ====================
+
+    /// Copy the title and open/closed state of linked GitHub/GitLab/Jira issues
+    /// onto their tasks, as one undoable operation
+    pub fn refresh_links(&self) -> Result<LinkRefresh> {
+        let links = &self.config.links;
+        let mut refresh = LinkRefresh::default();
+        let mut before = Vec::new();
+
+        for task in self.db.get_all_tasks()? {
+            let Some(issue) = task.url.as_deref().and_then(|u| IssueRef::parse(u, links)) else {
+                continue;
+            };
+            let state = match issue.fetch(links) {
+                Ok(state) => state,
+                Err(e) => {
+                    refresh.failed.push((task, e));
+                    continue;
+                }
+            };
+            let mut after = task.clone();
+            after.title = state.title;
+            if state.open && after.completed {
+                after.uncomplete();
+            } else if !state.open && !after.completed {
+                after.complete();
+            }
+            if after.title == task.title && after.completed == task.completed {
+                refresh.unchanged += 1;
+                continue;
+            }
+            before.push(task);
+            refresh.updated.push(after);
+        }
+
+        if !refresh.updated.is_empty() {
+            journal::record(&self.db, describe("refresh", &before), &before, &[])?;
+            self.db.update_tasks(&refresh.updated)?;
+            for (old, new) in before.iter().zip(&refresh.updated) {
+                if new.completed && !old.completed {
+                    hooks::run(&self.config.hooks, Hook::TaskCompleted, new);
+                }
+            }
+        }
+        Ok(refresh)
+    }
+
+    /// Add a task to `list_id` for each Jira issue matching `jql` that no task
+    /// links to yet, with the issue's priority, due date and URL
+    pub fn import_jira(&self, list_id: Uuid, jql: &str) -> Result<JiraImport> {
+        let links = &self.config.links;
+        let linked: HashSet<IssueRef> = self
+            .db
+            .get_all_tasks()?
+            .iter()
+            .filter_map(|t| t.url.as_deref().and_then(|u| IssueRef::parse(u, links)))
+            .collect();
+
+        let mut import = JiraImport::default();
+        let mut new = Vec::new();
+        for found in issues::search_jira(links, jql)? {
+            if linked.contains(&found.issue) {
+                import.skipped += 1;
+                continue;
+            }
+            new.push(NewTask {
+                url: Some(found.issue.web_url()),
+                priority: found.priority,
+                due_date: found.due_date,
+                ..NewTask::new(found.title, list_id)
+            });
+        }
+        if !new.is_empty() {
+            import.added = self.add_tasks(new)?;
+        }
+        Ok(import)
+    }
+
+    /// Close the issues linked to `tasks` if `links.close_issues` is on,
+    /// returning each issue with the outcome
+    pub fn close_issues(&self, tasks: &[Task]) -> Vec<(IssueRef, Result<()>)> {
+        self.closable_issues(tasks)
+            .into_iter()
+            .map(|issue| {
+                let result = issue.close(&self.config.links);
+                (issue, result)
+            })
+            .collect()
+    }
+
+    /// [`close_issues`](Self::close_issues) on another thread, logging
+    /// failures, for callers that can't wait on the network
+    pub fn close_issues_in_background<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) {
+        let issues = self.closable_issues(tasks);
+        if issues.is_empty() {
+            return;
+        }
+        let links = self.config.links.clone();
+        std::thread::spawn(move || {
+            for issue in issues {
+                if let Err(e) = issue.close(&links) {
+                    tracing::warn!("Could not close {}: {:#}", issue, e);
+                }
+            }
+        });
+    }
+
+    /// The issues linked to `tasks`, if `links.close_issues` is on
+    fn closable_issues<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) -> Vec<IssueRef> {
+        let links = &self.config.links;
+        if !links.close_issues {
+            return Vec::new();
+        }
+        tasks
+            .into_iter()
+            .filter_map(|t| t.url.as_deref().and_then(|u| IssueRef::parse(u, links)))
+            .collect()
+    }
+
+    // ==================== Hooks ====================
+
+    /// Run the configured hooks for tasks just `created` or `completed`,
+    /// whether the CLI or the TUI changed them
+    pub fn run_hooks<'a>(
+        &self,
+        created: impl IntoIterator<Item = &'a Task>,
+        completed: impl IntoIterator<Item = &'a Task>,
+    ) {
+        for task in created {
+            hooks::run(&self.config.hooks, Hook::TaskCreated, task);
+        }
+        for task in completed {
+            hooks::run(&self.config.hooks, Hook::TaskCompleted, task);
+        }
+    }
+
+    // ==================== Sync ====================
+
+    /// What the first sync of a device that already has tasks would do,
+    /// found by downloading from the server without uploading; `None` once
+    /// the device has synced or while it has no tasks
+    pub fn sync_preview(&self) -> Result<Option<sync::SyncPreview>> {
+        let last_sync = self.db.get_last_sync()?;
+        let local = sync::local_changes(&self.db, last_sync)?;
+        if !sync::needs_preview(last_sync, &local) {
+            return Ok(None);
+        }
+        let mut client = SyncClient::new(self.config.sync.clone());
+        let remote = client.sync(Vec::new(), None)?;
+        Ok(Some(sync::SyncPreview::new(&local, &remote.changes)))
+    }
+
+    /// Exchange changes with the sync server; `force` sends and asks for everything
+    pub fn sync(&self, force: bool) -> Result<SyncSummary> {
+        self.sync_with(force, None, None)
+    }
+
+    /// [`sync`](Self::sync), with `ask` choosing between local and server
+    /// changes when `sync.conflict_policy` is `ask`, and `progress` told
+    /// about each uploaded batch
+    #[tracing::instrument(level = "debug", skip(self, ask, progress))]
+    pub fn sync_with(
+        &self,
+        force: bool,
+        ask: Option<sync::AskFn>,
+        progress: Option<sync::ProgressFn>,
+    ) -> Result<SyncSummary> {
+        if self.db.is_in_memory() {
+            // Sending a throwaway database would push its Inbox to the server
+            anyhow::bail!("Sync is off for in-memory databases");
+        }
+        // Local time from before gathering changes, so edits made meanwhile go next time
+        let started = Utc::now();
+        let last_sync = if force {
+            None
+        } else {
+            self.db.get_last_sync()?
+        };
+        let changes = sync::local_changes(&self.db, last_sync)?;
+        let sent = changes.len();
+        let sent_tasks = sync::task_ids(&changes);
+
+        let store = BlobStore::for_database(&self.db)?;
+        let mut client = SyncClient::new(self.config.sync.clone());
+        client.upload_blobs(&store, &changes)?;
+        let mut response = client.sync_with_progress(changes, last_sync, progress)?;
+        let policy = self.config.sync.conflict_policy;
+        let kept_local = sync::resolve_conflicts(&self.db, &mut response, last_sync, policy, ask)?;
+        let applied = sync::apply_changes(&self.db, &response);
+        sync::record_sync_base(&self.db, &sent_tasks, &response)?;
+        // The earlier of the two clocks, so skew can't skip changes
+        self.db.set_last_sync(response.server_time.min(started))?;
+        client.download_blobs(&store, &sync::missing_blobs(&self.db, &store)?);
+
+        let summary = SyncSummary {
+            sent,
+            received: response.changes.len(),
+            applied,
+            conflicts: response.conflicts.len(),
+            kept_local,
+            missing_blobs: sync::missing_blobs(&self.db, &store)?.len(),
+        };
+        tracing::debug!(?summary, "sync finished");
+        hooks::run(&self.config.hooks, Hook::SyncFinished, &summary);
+        Ok(summary)
+    }
+
+    // ==================== Validation ====================
+
+    /// Check a URL, adding `https://` to bare domains; `None` for blank input
+    pub fn normalize_url(s: &str) -> std::result::Result<Option<String>, String> {
+        let url = s.trim();
+        if url.is_empty() {
+            return Ok(None);
+        }
+        if url.contains(char::is_whitespace) {
+            return Err("URL cannot contain spaces".to_string());
+        }
+        if let Some((scheme, rest)) = url.split_once("://") {
+            let valid_scheme = !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+            if !valid_scheme || rest.is_empty() {
+                return Err(format!("Invalid URL '{}'", url));
+            }
+            return Ok(Some(url.to_string()));
+        }
+        if url.starts_with("mailto:") {
+            return Ok(Some(url.to_string()));
+        }
+        if url.contains('.') {
+            return Ok(Some(format!("https://{}", url)));
+        }
+        Err(format!("Invalid URL '{}'", url))
+    }
+
+    /// Check a color: a hex code like `#ff8800` or a terminal color name
+    pub fn normalize_color(s: &str) -> Result<String> {
+        let color = s.trim();
+        match color.parse::<ratatui::style::Color>() {
+            Ok(_) if !color.is_empty() => Ok(color.to_string()),
+            _ => anyhow::bail!(
+                "Invalid color '{}' (use a hex code like #ff8800 or a color name)",
+                color
+            ),
+        }
+    }
+}
+
+/// Journal label for an operation, e.g. `delete "Old task"` or `complete 3 tasks`
+pub fn describe(verb: &str, tasks: &[Task]) -> String {
+    match tasks {
+        [task] => format!("{} \"{}\"", verb, task.title),
+        _ => format!("{} {} tasks", verb, tasks.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn core() -> TickitCore {
+        TickitCore::new(Database::open_in_memory().unwrap(), Config::default())
+    }
+
+    #[test]
+    fn test_save() {
+        let core = core();
+        let inbox = core.db().get_inbox().unwrap();
+        let kept = core.add_task(NewTask::new("Kept", inbox.id)).unwrap();
+        let gone = core.add_task(NewTask::new("Gone", inbox.id)).unwrap();
+        let mut done = kept.clone();
+        done.complete();
+        let new = Task::new("New", inbox.id);
+
+        core.save("tidy", &[kept.clone(), gone.clone()], &[done, new.clone()])
+            .unwrap();
+        assert!(core.db().get_task(kept.id).unwrap().completed);
+        assert!(core.db().get_task(gone.id).is_err());
+        assert!(core.db().get_task(new.id).is_ok());
+
+        // One undo puts all of it back
+        assert_eq!(core.undo().unwrap().unwrap().label, "tidy");
+        assert!(!core.db().get_task(kept.id).unwrap().completed);
+        assert!(core.db().get_task(gone.id).is_ok());
+        assert!(core.db().get_task(new.id).is_err());
+    }
+
+    #[test]
+    fn test_add_validates() {
+        let core = core();
+        let inbox = core.db().get_inbox().unwrap();
+
+        assert!(core.add_task(NewTask::new("  ", inbox.id)).is_err());
+        let mut new = NewTask::new("Read", inbox.id);
+        new.url = Some("not a url".to_string());
+        assert!(core.add_task(new.clone()).is_err());
+
+        new.url = Some("example.com".to_string());
+        let task = core.add_task(new).unwrap();
+        assert_eq!(task.url.as_deref(), Some("https://example.com"));
+        assert!(core.create_list("inbox", None).is_err());
+    }
+
+    #[test]
+    fn test_check_list_name() {
+        let core = core();
+        let work = core.create_list(" Work ", None).unwrap();
+        assert_eq!(work.name, "Work");
+
+        assert!(core.check_list_name("   ", Some(work.id)).is_err());
+        assert!(core.check_list_name("inbox", Some(work.id)).is_err());
+        // A list may keep its own name, in any case
+        assert_eq!(
+            core.check_list_name(" WORK ", Some(work.id)).unwrap(),
+            "WORK"
+        );
+        assert!(core.check_list_name("work", None).is_err());
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        let url = |s| TickitCore::normalize_url(s);
+        assert_eq!(url("  "), Ok(None));
+        assert_eq!(
+            url("example.com"),
+            Ok(Some("https://example.com".to_string()))
+        );
+        assert_eq!(
+            url(" http://example.com/a?b=1 "),
+            Ok(Some("http://example.com/a?b=1".to_string()))
+        );
+        assert_eq!(
+            url("obsidian://open?vault=notes"),
+            Ok(Some("obsidian://open?vault=notes".to_string()))
+        );
+        assert_eq!(
+            url("mailto:me@example.com"),
+            Ok(Some("mailto:me@example.com".to_string()))
+        );
+        assert!(url("example .com").is_err());
+        assert!(url("https://").is_err());
+        assert!(url("ht tp://x").is_err());
+        assert!(url("://example.com").is_err());
+        assert!(url("localhost").is_err());
+    }
+
+    #[test]
+    fn test_normalize_color() {
+        assert_eq!(TickitCore::normalize_color(" #FF8800 ").unwrap(), "#FF8800");
+        assert_eq!(TickitCore::normalize_color("red").unwrap(), "red");
+        assert!(TickitCore::normalize_color("").is_err());
+        assert!(TickitCore::normalize_color("#ff88").is_err());
+        assert!(TickitCore::normalize_color("reddish").is_err());
+    }
+
+    #[test]
+    fn test_complete_and_undo() {
+        let core = core();
+        let inbox = core.db().get_inbox().unwrap();
+        let task = core.add_task(NewTask::new("Ship it", inbox.id)).unwrap();
+
+        let done = core.complete(vec![task.clone()]).unwrap();
+        assert_eq!(done.len(), 1);
+        assert!(core.tasks(&TaskQuery::default()).unwrap().is_empty());
+        // Already completed tasks are skipped
+        assert!(core.complete(done).unwrap().is_empty());
+
+        let entry = core.undo().unwrap().unwrap();
+        assert_eq!(entry.label, "complete \"Ship it\"");
+        assert_eq!(core.tasks(&TaskQuery::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_maintain() {
+        let mut config = Config::default();
+        config.retention.archive_completed_after = Some("14d".to_string());
+        config.retention.purge_archived_after = Some("6m".to_string());
+        let core = TickitCore::new(Database::open_in_memory().unwrap(), config);
+        let inbox = core.db().get_inbox().unwrap();
+        let mut tasks = core
+            .add_tasks(vec![
+                NewTask::new("Old", inbox.id),
+                NewTask::new("Recent", inbox.id),
+                NewTask::new("Ancient", inbox.id),
+                NewTask::new("Open", inbox.id),
+            ])
+            .unwrap();
+        let now = Utc::now();
+        for (task, days) in tasks.iter_mut().zip([30, 3, 400]) {
+            task.complete();
+            task.completed_at = Some(now - Duration::days(days));
+        }
+        tasks[2].archived_at = Some(now - Duration::days(200));
+        core.db().update_tasks(&tasks).unwrap();
+
+        let titles = |tasks: &[Task]| tasks.iter().map(|t| t.title.clone()).collect::<Vec<_>>();
+        let due = core.cleanup_due().unwrap();
+        assert_eq!(titles(&due.archived), ["Old"]);
+        assert_eq!(titles(&due.purged), ["Ancient"]);
+
+        core.maintain().unwrap();
+        assert!(core.db().get_task(tasks[2].id).is_err());
+        assert!(
+            core.db()
+                .get_task(tasks[0].id)
+                .unwrap()
+                .archived_at
+                .is_some()
+        );
+        assert!(core.cleanup_due().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_escalate() {
+        let mut config = Config::default();
+        config.escalation.urgent = Some("1d".to_string());
+        let core = TickitCore::new(Database::open_in_memory().unwrap(), config);
+        let inbox = core.db().get_inbox().unwrap();
+        let mut soon = NewTask::new("Soon", inbox.id);
+        soon.due_date = crate::quickadd::end_of_day(Utc::now().date_naive());
+        let mut later = NewTask::new("Later", inbox.id);
+        later.due_date = Some(Utc::now() + Duration::days(5));
+        let tasks = core.add_tasks(vec![soon, later]).unwrap();
+
+        let raised = core.escalate().unwrap();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].from, Priority::Medium);
+        assert_eq!(raised[0].task.priority, Priority::Urgent);
+        let audit = core.db().get_audit(tasks[0].id).unwrap();
+        assert!(
+            audit[0]
+                .1
+                .starts_with("Priority raised from Medium to Urgent")
+        );
+        // The stored day, as the config formats dates
+        let due = raised[0].task.due_date.unwrap().date_naive();
+        assert!(audit[0].1.ends_with(&core.config().format_date(due)));
+
+        // Lowered by hand: left alone from then on
+        let mut task = core.db().get_task(tasks[0].id).unwrap();
+        task.priority = Priority::Low;
+        core.db().update_task(&task).unwrap();
+        assert!(core.escalate().unwrap().is_empty());
+        assert!(core.db().get_audit(tasks[1].id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let core = core();
+        let inbox = core.db().get_inbox().unwrap();
+        let work = core.create_list("Work", None).unwrap();
+        let tag = core.create_tag("home", None).unwrap();
+        let mut new = NewTask::new("Water plants", inbox.id);
+        new.priority = Priority::High;
+        new.tag_ids = vec![tag.id];
+        let task = core.add_task(new).unwrap();
+        let task = core.complete(vec![task]).unwrap().remove(0);
+
+        let copy = core.duplicate(&task, Some(work.id)).unwrap();
+        assert_ne!(copy.id, task.id);
+        assert_eq!(copy.list_id, work.id);
+        assert_eq!(copy.priority, Priority::High);
+        assert_eq!(copy.tag_ids, vec![tag.id]);
+        assert!(!copy.completed && copy.completed_at.is_none());
+
+        // Undo removes the copy only
+        assert_eq!(
+            core.undo().unwrap().unwrap().label,
+            "duplicate \"Water plants\""
+        );
+        assert!(core.db().get_task(copy.id).is_err());
+        assert!(core.db().get_task(task.id).unwrap().completed);
+    }
+
+    #[test]
+    fn test_merge() {
+        let core = core();
+        let inbox = core.db().get_inbox().unwrap();
+        let (home, work) = (
+            core.create_tag("home", None).unwrap(),
+            core.create_tag("work", None).unwrap(),
+        );
+        let soon = Utc::now() + Duration::days(1);
+        let mut new = NewTask::new("Renew passport", inbox.id);
+        new.description = Some("Photos first".to_string());
+        new.tag_ids = vec![home.id];
+        let task = core.add_task(new).unwrap();
+        let mut new = NewTask::new("renew passport", inbox.id);
+        new.description = Some("Form at the post office".to_string());
+        new.url = Some("https://example.com/passport".to_string());
+        new.tag_ids = vec![home.id, work.id];
+        new.priority = Priority::High;
+        new.due_date = Some(soon);
+        let other = core.add_task(new).unwrap();
+        let blocked = core
+            .add_task(NewTask::new("Book flights", inbox.id))
+            .unwrap();
+        core.db().add_dependency(blocked.id, other.id).unwrap();
+        core.add_comment(&other, "Costs 80").unwrap();
+
+        let merged = core
+            .merge(task.clone(), std::slice::from_ref(&other))
+            .unwrap();
+        assert_eq!(merged.title, "Renew passport");
+        assert_eq!(
+            merged.description.as_deref(),
+            Some("Photos first\n\nForm at the post office")
+        );
+        assert_eq!(merged.url.as_deref(), Some("https://example.com/passport"));
+        assert_eq!(merged.tag_ids, vec![home.id, work.id]);
+        assert_eq!(merged.priority, Priority::High);
+        assert_eq!(merged.due_date, Some(soon));
+        assert!(core.db().get_task(other.id).is_err());
+        assert_eq!(core.db().get_comments(task.id).unwrap()[0].body, "Costs 80");
+        assert!(
+            core.db()
+                .get_dependencies()
+                .unwrap()
+                .contains(&(blocked.id, task.id))
+        );
+        assert!(core.merge(merged.clone(), &[merged]).is_err());
+
+        // Undo brings back both as they were
+        core.undo().unwrap();
+        assert_eq!(core.db().get_task(task.id).unwrap().tag_ids, vec![home.id]);
+        assert!(core.db().get_task(other.id).is_ok());
+    }
+
+    #[test]
+    fn test_attach() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.sync.max_blob_bytes = 10;
+        let db = Database::open_path(&dir.path().join("tickit.sqlite")).unwrap();
+        let core = TickitCore::new(db, config);
+        let inbox = core.db().get_inbox().unwrap();
+        let task = core.add_task(NewTask::new("Taxes", inbox.id)).unwrap();
+        let file = dir.path().join("receipt.txt");
+        std::fs::write(&file, "Paid 80").unwrap();
+        let since = Utc::now();
+
+        let attachment = core.attach(&task, &file).unwrap();
+        assert_eq!(
+            (attachment.name.as_str(), attachment.size),
+            ("receipt.txt", 7)
+        );
+        let store = BlobStore::for_database(core.db()).unwrap();
+        assert!(store.contains(&attachment.hash));
+        let blobs = sync::attachment_blobs(&sync::local_changes(core.db(), Some(since)).unwrap());
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].hash, attachment.hash);
+
+        std::fs::write(&file, "Paid 80 in cash").unwrap();
+        assert!(core.attach(&task, &file).is_err());
+        // An in-memory database has nowhere to keep files
+        assert!(self::core().attach(&task, &file).is_err());
+
+        // Another device gets the attachment, and its file on the next download
+        let other = Database::open_path(&dir.path().join("other").join("tickit.sqlite")).unwrap();
+        let response = sync::SyncResponse {
+            server_time: Utc::now(),
+            changes: sync::local_changes(core.db(), None).unwrap(),
+            conflicts: Vec::new(),
+        };
+        sync::apply_changes(&other, &response);
+        assert_eq!(other.get_attachments(task.id).unwrap()[0], attachment);
+        let other_store = BlobStore::for_database(&other).unwrap();
+        assert_eq!(sync::missing_blobs(&other, &other_store).unwrap(), blobs);
+
+        // Removing it is synced as a tombstone
+        core.detach(&attachment).unwrap();
+        assert!(core.db().get_attachments(task.id).unwrap().is_empty());
+        assert!(
+            sync::local_changes(core.db(), Some(since))
+                .unwrap()
+                .iter()
+                .any(
+                    |r| r.key() == (sync::RecordType::Attachment, attachment.id, None)
+                        && matches!(r, sync::SyncRecord::Deleted { .. })
+                )
+        );
+    }
+}
@@ -1,11 +1,20 @@
 //! Database module for SQLite storage
 
 use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OpenFlags, params};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid::Uuid;
 
-use crate::models::{List, Priority, Tag, Task};
+use crate::models::{Attachment, Comment, DeletedListTasks, List, Priority, Tag, Task};
+
+/// Set by [`Database::set_ephemeral`]
+static EPHEMERAL: AtomicBool = AtomicBool::new(false);
+
+/// Set by [`Database::set_path`]
+static PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
 
 /// Database connection wrapper
 pub struct Database {
@@ -13,13 +22,65 @@ pub struct Database {
 }
 
 impl Database {
-    /// Open or create the database at the default location
+    /// Open or create the database at the default location (or the one
+    /// given to [`set_path`](Self::set_path)), or an empty in-memory one
+    /// after [`set_ephemeral`](Self::set_ephemeral)
     pub fn open() -> Result<Self> {
-        let path = Self::default_path()?;
+        if Self::is_ephemeral() {
+            return Self::open_in_memory();
+        }
+        let path = Self::current_path()?;
         Self::open_path(&path)
     }
 
+    /// Make every later [`open`](Self::open) in this process use the
+    /// database at `path`, e.g. a workspace's
+    pub fn set_path(path: PathBuf) {
+        *PATH.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+    }
+
+    /// The database [`open`](Self::open) uses
+    pub fn current_path() -> Result<PathBuf> {
+        match &*PATH.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(path) => Ok(path.clone()),
+            None => Self::default_path(),
+        }
+    }
+
+    /// Open an empty database that lives in memory and is gone when dropped
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open database")?;
+        let db = Self { conn };
+        db.init()?;
+        Ok(db)
+    }
+
+    /// Make every later [`open`](Self::open) in this process use a fresh
+    /// in-memory database instead of the user's (`--ephemeral`)
+    pub fn set_ephemeral() {
+        EPHEMERAL.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`set_ephemeral`](Self::set_ephemeral) was called
+    pub fn is_ephemeral() -> bool {
+        EPHEMERAL.load(Ordering::Relaxed)
+    }
+
+    /// Whether this database lives in memory rather than in a file
+    pub fn is_in_memory(&self) -> bool {
+        self.conn.path().is_none_or(str::is_empty)
+    }
+
+    /// The database file, `None` in memory
+    pub fn path(&self) -> Option<PathBuf> {
+        self.conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    }
+
     /// Open or create the database at a specific path
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
     pub fn open_path(path: &PathBuf) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
@@ -34,6 +95,24 @@ impl Database {
         Ok(db)
     }
 
+    /// Open the default database for reading only, skipping schema setup
+    ///
+    /// Cheap enough to call on every status bar refresh, and never blocks or
+    /// is blocked by a running TUI for long. Fails if the database doesn't exist yet.
+    pub fn open_read_only() -> Result<Self> {
+        if Self::is_ephemeral() {
+            return Self::open_in_memory();
+        }
+        let path = Self::current_path()?;
+        let conn = Connection::open_with_flags(
+            &path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .context("Failed to open database")?;
+        conn.busy_timeout(std::time::Duration::from_millis(200))?;
+        Ok(Self { conn })
+    }
+
     /// Get the default database path
     pub fn default_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
@@ -62,7 +141,8 @@ impl Database {
                 is_inbox INTEGER NOT NULL DEFAULT 0,
                 sort_order INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                checklist INTEGER NOT NULL DEFAULT 0
             );
 
             -- Tags table
@@ -87,6 +167,9 @@ impl Database {
                 updated_at TEXT NOT NULL,
                 completed_at TEXT,
                 due_date TEXT,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                archived_at TEXT,
+                nag_hours INTEGER,
                 FOREIGN KEY (list_id) REFERENCES lists(id) ON DELETE CASCADE
             );
 
@@ -99,6 +182,15 @@ impl Database {
                 FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
             );
 
+            -- Task dependencies (task_id waits on blocked_by)
+            CREATE TABLE IF NOT EXISTS task_dependencies (
+                task_id TEXT NOT NULL,
+                blocked_by TEXT NOT NULL,
+                PRIMARY KEY (task_id, blocked_by),
+                FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                FOREIGN KEY (blocked_by) REFERENCES tasks(id) ON DELETE CASCADE
+            );
+
             -- Indexes for common queries
             CREATE INDEX IF NOT EXISTS idx_tasks_list ON tasks(list_id);
             CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
@@ -120,6 +212,65 @@ impl Database {
             );
 
             CREATE INDEX IF NOT EXISTS idx_tombstones_deleted ON sync_tombstones(deleted_at);
+
+            -- Tasks as of the last sync, to merge edits made on both sides
+            CREATE TABLE IF NOT EXISTS sync_base (
+                id TEXT PRIMARY KEY,
+                task TEXT NOT NULL
+            );
+
+            -- Last CLI operation, for `tickit undo` (a single row)
+            CREATE TABLE IF NOT EXISTS cli_journal (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                entry TEXT NOT NULL
+            );
+
+            -- When each nagging task was last reminded of
+            CREATE TABLE IF NOT EXISTS sent_nags (
+                task_id TEXT PRIMARY KEY,
+                sent_at TEXT NOT NULL
+            );
+
+            -- Reminders already sent, so each is shown once a day
+            CREATE TABLE IF NOT EXISTS sent_notifications (
+                task_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                day TEXT NOT NULL,
+                PRIMARY KEY (task_id, kind, day)
+            );
+
+            -- Changes tickit made on its own (e.g. priority escalation), with why
+            CREATE TABLE IF NOT EXISTS task_audit (
+                task_id TEXT NOT NULL,
+                at TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_task_audit_task ON task_audit(task_id);
+
+            -- Comments on tasks
+            CREATE TABLE IF NOT EXISTS task_comments (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                body TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_task_comments_task ON task_comments(task_id);
+
+            -- Files attached to tasks; the contents are blobs named by hash
+            CREATE TABLE IF NOT EXISTS task_attachments (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_task_attachments_task ON task_attachments(task_id);
             "#,
         )?;
 
@@ -154,6 +305,68 @@ impl Database {
             )?;
         }
 
+        // Check if tasks.sort_order column exists
+        let has_task_sort_order: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('tasks') WHERE name = 'sort_order'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_task_sort_order {
+            self.conn.execute_batch(
+                "ALTER TABLE tasks ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+
+        // Check if tasks.archived_at column exists
+        let has_task_archived_at: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('tasks') WHERE name = 'archived_at'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_task_archived_at {
+            self.conn
+                .execute_batch("ALTER TABLE tasks ADD COLUMN archived_at TEXT;")?;
+        }
+
+        // Check if tasks.nag_hours column exists
+        let has_task_nag_hours: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('tasks') WHERE name = 'nag_hours'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_task_nag_hours {
+            self.conn
+                .execute_batch("ALTER TABLE tasks ADD COLUMN nag_hours INTEGER;")?;
+        }
+
+        // Check if lists.checklist column exists
+        let has_list_checklist: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('lists') WHERE name = 'checklist'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_list_checklist {
+            self.conn.execute_batch(
+                "ALTER TABLE lists ADD COLUMN checklist INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+
         Ok(())
     }
 
@@ -178,8 +391,8 @@ impl Database {
     /// Insert a new list
     pub fn insert_list(&self, list: &List) -> Result<()> {
         self.conn.execute(
-            r#"INSERT INTO lists (id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+            r#"INSERT INTO lists (id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at, checklist)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
             params![
                 list.id.to_string(),
                 list.name,
@@ -190,6 +403,7 @@ impl Database {
                 list.sort_order,
                 list.created_at.to_rfc3339(),
                 list.updated_at.to_rfc3339(),
+                list.is_checklist as i32,
             ],
         )?;
         Ok(())
@@ -198,7 +412,7 @@ impl Database {
     /// Get all lists
     pub fn get_lists(&self) -> Result<Vec<List>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at 
+            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at, checklist
              FROM lists ORDER BY sort_order, name"
         )?;
 
@@ -217,6 +431,7 @@ impl Database {
                 updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                     .unwrap()
                     .with_timezone(&chrono::Utc),
+                is_checklist: row.get::<_, i32>(9)? != 0,
             })
         })?;
 
@@ -226,7 +441,7 @@ impl Database {
     /// Get the inbox list
     pub fn get_inbox(&self) -> Result<List> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at 
+            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at, checklist
              FROM lists WHERE is_inbox = 1"
         )?;
 
@@ -245,6 +460,7 @@ impl Database {
                 updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                     .unwrap()
                     .with_timezone(&chrono::Utc),
+                is_checklist: row.get::<_, i32>(9)? != 0,
             })
         })
         .map_err(Into::into)
@@ -254,7 +470,7 @@ impl Database {
     pub fn update_list(&self, list: &List) -> Result<()> {
         self.conn.execute(
             r#"UPDATE lists SET name = ?2, description = ?3, icon = ?4, color = ?5, 
-               sort_order = ?6, updated_at = ?7 WHERE id = ?1"#,
+               sort_order = ?6, updated_at = ?7, checklist = ?8 WHERE id = ?1"#,
             params![
                 list.id.to_string(),
                 list.name,
@@ -263,27 +479,74 @@ impl Database {
                 list.color,
                 list.sort_order,
                 chrono::Utc::now().to_rfc3339(),
+                list.is_checklist as i32,
             ],
         )?;
         Ok(())
     }
 
+    /// Update several lists in a single transaction
+    pub fn update_lists(&self, lists: &[List]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for list in lists {
+            self.update_list(list)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Delete a list (moves tasks to inbox)
     pub fn delete_list(&self, list_id: Uuid) -> Result<()> {
         let inbox = self.get_inbox()?;
+        self.delete_list_with(list_id, DeletedListTasks::Move(inbox.id))
+    }
 
-        // Move tasks to inbox
-        self.conn.execute(
-            "UPDATE tasks SET list_id = ?1 WHERE list_id = ?2",
-            params![inbox.id.to_string(), list_id.to_string()],
-        )?;
+    /// Delete a list, moving, archiving or deleting its tasks (archived ones included)
+    ///
+    /// Moved and archived tasks are marked as updated so sync picks them up,
+    /// and the list and deleted tasks leave tombstones.
+    pub fn delete_list_with(&self, list_id: Uuid, tasks: DeletedListTasks) -> Result<()> {
+        let inbox = self.get_inbox()?;
+        if inbox.id == list_id {
+            anyhow::bail!("Cannot delete inbox");
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let tx = self.conn.unchecked_transaction()?;
+        match tasks {
+            DeletedListTasks::Move(target) => {
+                self.conn.execute(
+                    "UPDATE tasks SET list_id = ?1, updated_at = ?3 WHERE list_id = ?2",
+                    params![target.to_string(), list_id.to_string(), now],
+                )?;
+            }
+            DeletedListTasks::Archive => {
+                self.conn.execute(
+                    "UPDATE tasks SET list_id = ?1, archived_at = COALESCE(archived_at, ?3),
+                     updated_at = ?3 WHERE list_id = ?2",
+                    params![inbox.id.to_string(), list_id.to_string(), now],
+                )?;
+            }
+            DeletedListTasks::Delete => {
+                let mut stmt = self
+                    .conn
+                    .prepare("SELECT id FROM tasks WHERE list_id = ?1")?;
+                let ids = stmt
+                    .query_map(params![list_id.to_string()], |row| row.get::<_, String>(0))?
+                    .filter_map(|id| id.ok()?.parse().ok())
+                    .collect::<Vec<Uuid>>();
+                for id in ids {
+                    self.delete_task(id)?;
+                    self.record_tombstone(id, "task")?;
+                }
+            }
+        }
 
-        // Delete the list
         self.conn.execute(
             "DELETE FROM lists WHERE id = ?1 AND is_inbox = 0",
             params![list_id.to_string()],
         )?;
-
+        self.record_tombstone(list_id, "list")?;
+        tx.commit()?;
         Ok(())
     }
 
@@ -354,14 +617,39 @@ impl Database {
         Ok(())
     }
 
+    /// Move every task tagged `from` over to `into`, then delete `from`
+    ///
+    /// Affected tasks are marked as updated so sync picks up the new links.
+    pub fn merge_tag(&self, from: Uuid, into: Uuid) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "UPDATE tasks SET updated_at = ?2
+             WHERE id IN (SELECT task_id FROM task_tags WHERE tag_id = ?1)",
+            params![from.to_string(), chrono::Utc::now().to_rfc3339()],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id)
+             SELECT task_id, ?2 FROM task_tags WHERE tag_id = ?1",
+            params![from.to_string(), into.to_string()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM task_tags WHERE tag_id = ?1",
+            params![from.to_string()],
+        )?;
+        self.delete_tag(from)?;
+        self.record_tombstone(from, "tag")?;
+        tx.commit()?;
+        Ok(())
+    }
+
     // ==================== Tasks ====================
 
     /// Insert a new task
     pub fn insert_task(&self, task: &Task) -> Result<()> {
         self.conn.execute(
             r#"INSERT INTO tasks (id, title, description, url, priority, completed, list_id, 
-               created_at, updated_at, completed_at, due_date)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+               created_at, updated_at, completed_at, due_date, sort_order, archived_at, nag_hours)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"#,
             params![
                 task.id.to_string(),
                 task.title,
@@ -374,6 +662,9 @@ impl Database {
                 task.updated_at.to_rfc3339(),
                 task.completed_at.map(|dt| dt.to_rfc3339()),
                 task.due_date.map(|dt| dt.to_rfc3339()),
+                task.sort_order,
+                task.archived_at.map(|dt| dt.to_rfc3339()),
+                task.nag_hours,
             ],
         )?;
 
@@ -388,6 +679,36 @@ impl Database {
         Ok(())
     }
 
+    /// Add a task with only a title to the Inbox in one statement, returning its ID
+    ///
+    /// Skips reading the Inbox first, for `tickit capture` bound to a global hotkey.
+    pub fn capture(&self, title: &str) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        let now = chrono::Utc::now().to_rfc3339();
+        let inserted = self
+            .conn
+            .prepare_cached(
+                r#"INSERT INTO tasks (id, title, priority, completed, list_id, created_at, updated_at)
+                   SELECT ?1, ?2, 'medium', 0, id, ?3, ?3 FROM lists WHERE is_inbox = 1 LIMIT 1"#,
+            )?
+            .execute(params![id.to_string(), title, now])?;
+        if inserted == 0 {
+            anyhow::bail!("Inbox list is missing");
+        }
+        Ok(id)
+    }
+
+    /// Insert several tasks in a single transaction
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn insert_tasks(&self, tasks: &[Task]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for task in tasks {
+            self.insert_task(task)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Get all tasks for a list
     pub fn get_tasks_for_list(&self, list_id: Uuid) -> Result<Vec<Task>> {
         self.get_tasks_with_filter(Some(list_id), None, None)
@@ -398,7 +719,8 @@ impl Database {
         self.get_tasks_with_filter(None, None, None)
     }
 
-    /// Get tasks with optional filters
+    /// Get tasks with optional filters, leaving out archived tasks
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn get_tasks_with_filter(
         &self,
         list_id: Option<Uuid>,
@@ -411,7 +733,7 @@ impl Database {
              FROM tasks t",
         );
 
-        let mut conditions = Vec::new();
+        let mut conditions = vec!["t.archived_at IS NULL"];
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
         if tag_id.is_some() {
@@ -433,10 +755,8 @@ impl Database {
             params_vec.push(Box::new(tid.to_string()));
         }
 
-        if !conditions.is_empty() {
-            sql.push_str(" WHERE ");
-            sql.push_str(&conditions.join(" AND "));
-        }
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
 
         sql.push_str(" ORDER BY t.completed, t.priority DESC, t.created_at DESC");
 
@@ -455,7 +775,8 @@ impl Database {
             // Get fresh row for this task
             let mut task_stmt = self.conn.prepare(
                 "SELECT id, title, description, url, priority, completed, list_id, 
-                 created_at, updated_at, completed_at, due_date FROM tasks WHERE id = ?1",
+                 created_at, updated_at, completed_at, due_date, sort_order, archived_at, nag_hours
+                 FROM tasks WHERE id = ?1",
             )?;
 
             let task = task_stmt.query_row(params![task_id], |row| {
@@ -490,6 +811,12 @@ impl Database {
                         .get::<_, Option<String>>(10)?
                         .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
                         .map(|dt| dt.with_timezone(&chrono::Utc)),
+                    sort_order: row.get(11)?,
+                    archived_at: row
+                        .get::<_, Option<String>>(12)?
+                        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&chrono::Utc)),
+                    nag_hours: row.get(13)?,
                 })
             })?;
 
@@ -519,7 +846,8 @@ impl Database {
     pub fn update_task(&self, task: &Task) -> Result<()> {
         self.conn.execute(
             r#"UPDATE tasks SET title = ?2, description = ?3, url = ?4, priority = ?5, 
-               completed = ?6, list_id = ?7, updated_at = ?8, completed_at = ?9, due_date = ?10 
+               completed = ?6, list_id = ?7, updated_at = ?8, completed_at = ?9, due_date = ?10,
+               sort_order = ?11, archived_at = ?12, nag_hours = ?13
                WHERE id = ?1"#,
             params![
                 task.id.to_string(),
@@ -532,6 +860,9 @@ impl Database {
                 chrono::Utc::now().to_rfc3339(),
                 task.completed_at.map(|dt| dt.to_rfc3339()),
                 task.due_date.map(|dt| dt.to_rfc3339()),
+                task.sort_order,
+                task.archived_at.map(|dt| dt.to_rfc3339()),
+                task.nag_hours,
             ],
         )?;
 
@@ -560,12 +891,35 @@ impl Database {
         Ok(())
     }
 
+    /// Update several tasks in a single transaction
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn update_tasks(&self, tasks: &[Task]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for task in tasks {
+            self.update_task(task)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Delete several tasks in a single transaction, recording tombstones for sync
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn delete_tasks(&self, task_ids: &[Uuid]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for id in task_ids {
+            self.delete_task(*id)?;
+            self.record_tombstone(*id, "task")?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Get task count for a list
     pub fn get_task_count(&self, list_id: Uuid, include_completed: bool) -> Result<i32> {
         let sql = if include_completed {
-            "SELECT COUNT(*) FROM tasks WHERE list_id = ?1"
+            "SELECT COUNT(*) FROM tasks WHERE list_id = ?1 AND archived_at IS NULL"
         } else {
-            "SELECT COUNT(*) FROM tasks WHERE list_id = ?1 AND completed = 0"
+            "SELECT COUNT(*) FROM tasks WHERE list_id = ?1 AND completed = 0 AND archived_at IS NULL"
         };
 
         self.conn
@@ -576,9 +930,9 @@ impl Database {
     /// Get total task count
     pub fn get_total_task_count(&self, include_completed: bool) -> Result<i32> {
         let sql = if include_completed {
-            "SELECT COUNT(*) FROM tasks"
+            "SELECT COUNT(*) FROM tasks WHERE archived_at IS NULL"
         } else {
-            "SELECT COUNT(*) FROM tasks WHERE completed = 0"
+            "SELECT COUNT(*) FROM tasks WHERE completed = 0 AND archived_at IS NULL"
         };
 
         self.conn
@@ -586,8 +940,141 @@ impl Database {
             .map_err(Into::into)
     }
 
+    /// Count open tasks that were due before `now`
+    pub fn get_overdue_count(&self, now: chrono::DateTime<chrono::Utc>) -> Result<i32> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM tasks
+                 WHERE completed = 0 AND archived_at IS NULL
+                   AND due_date IS NOT NULL AND julianday(due_date) < julianday(?1)",
+                params![now.to_rfc3339()],
+                |row| row.get(0),
+            )
+            .map_err(Into::into)
+    }
+
+    /// Get (completed, total) task counts for every list that has tasks
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn get_list_progress(&self) -> Result<HashMap<Uuid, (i32, i32)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT list_id, SUM(completed), COUNT(*) FROM tasks
+                 WHERE archived_at IS NULL GROUP BY list_id",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            Ok((id, row.get::<_, i32>(1)?, row.get::<_, i32>(2)?))
+        })?;
+
+        let mut progress = HashMap::new();
+        for row in rows {
+            let (id, done, total) = row?;
+            if let Ok(id) = Uuid::parse_str(&id) {
+                progress.insert(id, (done, total));
+            }
+        }
+        Ok(progress)
+    }
+
+    // ==================== Dependencies ====================
+
+    /// Record that `task_id` is blocked by `blocked_by`
+    pub fn add_dependency(&self, task_id: Uuid, blocked_by: Uuid) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_dependencies (task_id, blocked_by) VALUES (?1, ?2)",
+            params![task_id.to_string(), blocked_by.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Give task `into` copies of the comments and attachments of task
+    /// `from`, and its place in dependencies on either side, before `from`
+    /// is deleted
+    pub fn carry_over_task(&self, from: Uuid, into: Uuid) -> Result<()> {
+        for comment in self.get_comments(from)? {
+            let mut copy = Comment::new(into, comment.body);
+            copy.created_at = comment.created_at;
+            self.insert_comment(&copy)?;
+        }
+        for attachment in self.get_attachments(from)? {
+            let mut copy = Attachment::new(into, attachment.name, attachment.hash, attachment.size);
+            copy.created_at = attachment.created_at;
+            self.insert_attachment(&copy)?;
+        }
+        for (task_id, blocked_by) in self.get_dependencies()? {
+            if task_id == from && blocked_by != into {
+                self.add_dependency(into, blocked_by)?;
+            } else if blocked_by == from && task_id != into {
+                self.add_dependency(task_id, into)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every blocker of a task
+    pub fn clear_dependencies(&self, task_id: Uuid) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1",
+            params![task_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// All `(task, blocked_by)` pairs between tasks that still exist
+    pub fn get_dependencies(&self) -> Result<Vec<(Uuid, Uuid)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.task_id, d.blocked_by FROM task_dependencies d
+             JOIN tasks t ON t.id = d.task_id
+             JOIN tasks b ON b.id = d.blocked_by",
+        )?;
+
+        let pairs = stmt.query_map([], |row| {
+            Ok((
+                Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
+                Uuid::parse_str(&row.get::<_, String>(1)?).unwrap(),
+            ))
+        })?;
+
+        pairs.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// IDs of tasks waiting on at least one open task
+    pub fn get_blocked_task_ids(&self) -> Result<Vec<Uuid>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT d.task_id FROM task_dependencies d
+             JOIN tasks b ON b.id = d.blocked_by
+             WHERE b.completed = 0",
+        )?;
+
+        let ids = stmt.query_map([], |row| {
+            Ok(Uuid::parse_str(&row.get::<_, String>(0)?).unwrap())
+        })?;
+
+        ids.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
     // ==================== Sync ====================
 
+    /// Count records changed or deleted after `since` (everything if never synced)
+    pub fn count_changes_since(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<usize> {
+        // An empty string sorts before every RFC 3339 timestamp
+        let since = since.map(|s| s.to_rfc3339()).unwrap_or_default();
+        let count: i64 = self.conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM tasks WHERE updated_at > ?1)
+                  + (SELECT COUNT(*) FROM lists WHERE updated_at > ?1)
+                  + (SELECT COUNT(*) FROM tags WHERE updated_at > ?1)
+                  + (SELECT COUNT(*) FROM task_comments WHERE updated_at > ?1)
+                  + (SELECT COUNT(*) FROM task_attachments WHERE created_at > ?1)
+                  + (SELECT COUNT(*) FROM sync_tombstones WHERE deleted_at > ?1)",
+            params![since],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
     /// Record a tombstone for a deleted record (for sync)
     pub fn record_tombstone(&self, id: Uuid, record_type: &str) -> Result<()> {
         self.conn.execute(
@@ -597,6 +1084,15 @@ impl Database {
         Ok(())
     }
 
+    /// Remove a tombstone (used when a deletion is undone before it is synced)
+    pub fn remove_tombstone(&self, id: Uuid) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM sync_tombstones WHERE id = ?1",
+            params![id.to_string()],
+        )?;
+        Ok(())
+    }
+
     /// Get tombstones since a given time
     pub fn get_tombstones_since(
         &self,
@@ -638,6 +1134,23 @@ impl Database {
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Number of tombstones kept for sync
+    pub fn tombstone_count(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM sync_tombstones", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count as usize)
+            .map_err(Into::into)
+    }
+
+    /// SQLite journal mode, e.g. "delete" or "wal"
+    pub fn journal_mode(&self) -> Result<String> {
+        self.conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .map_err(Into::into)
+    }
+
     /// Clear old tombstones (older than given time)
     pub fn clear_old_tombstones(&self, older_than: chrono::DateTime<chrono::Utc>) -> Result<usize> {
         let count = self.conn.execute(
@@ -671,33 +1184,398 @@ impl Database {
         Ok(())
     }
 
-    /// Get last sync timestamp
-    pub fn get_last_sync(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
-        if let Some(value) = self.get_sync_state("last_sync")? {
-            Ok(chrono::DateTime::parse_from_rfc3339(&value)
-                .ok()
-                .map(|dt| dt.with_timezone(&chrono::Utc)))
-        } else {
-            Ok(None)
+    /// A task as it was after the last sync, if it was synced
+    pub fn get_sync_base(&self, task_id: Uuid) -> Result<Option<Task>> {
+        let result = self.conn.query_row(
+            "SELECT task FROM sync_base WHERE id = ?1",
+            params![task_id.to_string()],
+            |row| row.get::<_, String>(0),
+        );
+        match result {
+            Ok(json) => Ok(serde_json::from_str(&json).ok()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Set last sync timestamp
-    pub fn set_last_sync(&self, timestamp: chrono::DateTime<chrono::Utc>) -> Result<()> {
-        self.set_sync_state("last_sync", &timestamp.to_rfc3339())
+    /// Remember `tasks` as they are after a sync; `None` forgets a deleted task
+    pub fn set_sync_base(&self, tasks: &[(Uuid, Option<Task>)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (id, task) in tasks {
+            match task {
+                Some(task) => tx.execute(
+                    "INSERT OR REPLACE INTO sync_base (id, task) VALUES (?1, ?2)",
+                    params![id.to_string(), serde_json::to_string(task)?],
+                )?,
+                None => tx.execute(
+                    "DELETE FROM sync_base WHERE id = ?1",
+                    params![id.to_string()],
+                )?,
+            };
+        }
+        tx.commit()?;
+        Ok(())
     }
 
-    /// Get tasks modified since a given time
-    pub fn get_tasks_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Task>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id FROM tasks WHERE updated_at > ?1")?;
+    /// Save the undo journal entry of the last CLI operation, replacing any previous one
+    pub fn save_journal(&self, entry: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO cli_journal (id, entry) VALUES (1, ?1)",
+            params![entry],
+        )?;
+        Ok(())
+    }
 
-        let task_ids: Vec<String> = stmt
-            .query_map(params![since.to_rfc3339()], |row| row.get(0))?
-            .collect::<Result<Vec<_>, _>>()?;
+    /// Remove and return the saved undo journal entry
+    pub fn take_journal(&self) -> Result<Option<String>> {
+        let entry =
+            match self
+                .conn
+                .query_row("SELECT entry FROM cli_journal WHERE id = 1", [], |row| {
+                    row.get(0)
+                }) {
+                Ok(entry) => Some(entry),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e.into()),
+            };
+        self.conn.execute("DELETE FROM cli_journal", [])?;
+        Ok(entry)
+    }
 
-        let mut result = Vec::new();
+    /// Whether a `kind` reminder for the task was already sent on `day`
+    pub fn was_notified(&self, task_id: Uuid, kind: &str, day: chrono::NaiveDate) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sent_notifications WHERE task_id = ?1 AND kind = ?2 AND day = ?3",
+            params![task_id.to_string(), kind, day.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Record that a `kind` reminder for the task was sent on `day`, forgetting earlier days
+    pub fn record_notification(
+        &self,
+        task_id: Uuid,
+        kind: &str,
+        day: chrono::NaiveDate,
+    ) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM sent_notifications WHERE day < ?1",
+            params![day.to_string()],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO sent_notifications (task_id, kind, day) VALUES (?1, ?2, ?3)",
+            params![task_id.to_string(), kind, day.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// When a nagging task was last reminded of, if ever
+    pub fn last_nag(&self, task_id: Uuid) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let result = self.conn.query_row(
+            "SELECT sent_at FROM sent_nags WHERE task_id = ?1",
+            params![task_id.to_string()],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(sent_at) => Ok(chrono::DateTime::parse_from_rfc3339(&sent_at)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc))),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Record that a nagging task was reminded of at `at`
+    pub fn record_nag(&self, task_id: Uuid, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sent_nags (task_id, sent_at) VALUES (?1, ?2)",
+            params![task_id.to_string(), at.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Note an automatic change to a task; `kind` identifies the rule, `message` explains it
+    pub fn record_audit(&self, task_id: Uuid, kind: &str, message: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO task_audit (task_id, at, kind, message) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                task_id.to_string(),
+                chrono::Utc::now().to_rfc3339(),
+                kind,
+                message
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Whether a `kind` change was ever recorded for the task
+    pub fn has_audit(&self, task_id: Uuid, kind: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM task_audit WHERE task_id = ?1 AND kind = ?2",
+            params![task_id.to_string(), kind],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Automatic changes to a task as (time, message), oldest first
+    pub fn get_audit(&self, task_id: Uuid) -> Result<Vec<(chrono::DateTime<chrono::Utc>, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT at, message FROM task_audit WHERE task_id = ?1 ORDER BY at, rowid")?;
+        let rows = stmt.query_map(params![task_id.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let (at, message) = row?;
+            if let Ok(at) = chrono::DateTime::parse_from_rfc3339(&at) {
+                entries.push((at.with_timezone(&chrono::Utc), message));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Get last sync timestamp
+    pub fn get_last_sync(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        if let Some(value) = self.get_sync_state("last_sync")? {
+            Ok(chrono::DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Set last sync timestamp
+    pub fn set_last_sync(&self, timestamp: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        self.set_sync_state("last_sync", &timestamp.to_rfc3339())
+    }
+
+    // ==================== Comments ====================
+
+    /// Add a comment to a task
+    pub fn insert_comment(&self, comment: &Comment) -> Result<()> {
+        self.upsert_comment(comment)
+    }
+
+    /// Comments on a task, oldest first
+    pub fn get_comments(&self, task_id: Uuid) -> Result<Vec<Comment>> {
+        self.query_comments(
+            "SELECT id, task_id, body, created_at, updated_at FROM task_comments
+             WHERE task_id = ?1 ORDER BY created_at, rowid",
+            params![task_id.to_string()],
+        )
+    }
+
+    /// All comments, oldest first
+    pub fn get_all_comments(&self) -> Result<Vec<Comment>> {
+        self.query_comments(
+            "SELECT id, task_id, body, created_at, updated_at FROM task_comments
+             ORDER BY created_at, rowid",
+            [],
+        )
+    }
+
+    /// Comments added or edited since a given time
+    pub fn get_comments_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Comment>> {
+        self.query_comments(
+            "SELECT id, task_id, body, created_at, updated_at FROM task_comments
+             WHERE updated_at > ?1",
+            params![since.to_rfc3339()],
+        )
+    }
+
+    fn query_comments(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Comment>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let parse_time = |s: String| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_default()
+        };
+        let comments = stmt.query_map(params, |row| {
+            Ok(Comment {
+                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_default(),
+                task_id: Uuid::parse_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                body: row.get(2)?,
+                created_at: parse_time(row.get(3)?),
+                updated_at: parse_time(row.get(4)?),
+            })
+        })?;
+        comments.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Delete a comment, without a tombstone
+    pub fn delete_comment(&self, id: Uuid) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM task_comments WHERE id = ?1",
+            params![id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    // ==================== Attachments ====================
+
+    /// Attach a file to a task
+    pub fn insert_attachment(&self, attachment: &Attachment) -> Result<()> {
+        self.upsert_attachment(attachment)
+    }
+
+    /// Files attached to a task, oldest first
+    pub fn get_attachments(&self, task_id: Uuid) -> Result<Vec<Attachment>> {
+        self.query_attachments(
+            "SELECT id, task_id, name, hash, size, created_at FROM task_attachments
+             WHERE task_id = ?1 ORDER BY created_at, rowid",
+            params![task_id.to_string()],
+        )
+    }
+
+    /// All attachments, oldest first
+    pub fn get_all_attachments(&self) -> Result<Vec<Attachment>> {
+        self.query_attachments(
+            "SELECT id, task_id, name, hash, size, created_at FROM task_attachments
+             ORDER BY created_at, rowid",
+            [],
+        )
+    }
+
+    /// Attachments added since a given time
+    pub fn get_attachments_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Attachment>> {
+        self.query_attachments(
+            "SELECT id, task_id, name, hash, size, created_at FROM task_attachments
+             WHERE created_at > ?1",
+            params![since.to_rfc3339()],
+        )
+    }
+
+    fn query_attachments(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Attachment>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let attachments = stmt.query_map(params, |row| {
+            Ok(Attachment {
+                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap_or_default(),
+                task_id: Uuid::parse_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                name: row.get(2)?,
+                hash: row.get(3)?,
+                size: row.get::<_, i64>(4)? as u64,
+                created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+                    .unwrap_or_default(),
+            })
+        })?;
+        attachments
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Delete an attachment, without a tombstone; its blob stays stored
+    pub fn delete_attachment(&self, id: Uuid) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM task_attachments WHERE id = ?1",
+            params![id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Whether auto-sync is paused (`tickit sync pause`)
+    pub fn is_sync_paused(&self) -> Result<bool> {
+        Ok(self.get_sync_state("paused")?.as_deref() == Some("1"))
+    }
+
+    /// Pause or resume auto-sync
+    pub fn set_sync_paused(&self, paused: bool) -> Result<()> {
+        self.set_sync_state("paused", if paused { "1" } else { "0" })
+    }
+
+    /// Day the weekly review reminder was last sent
+    pub fn last_review(&self) -> Result<Option<chrono::NaiveDate>> {
+        Ok(self
+            .get_sync_state("last_review")?
+            .and_then(|day| day.parse().ok()))
+    }
+
+    /// Note that the weekly review reminder was sent on `day`
+    pub fn set_last_review(&self, day: chrono::NaiveDate) -> Result<()> {
+        self.set_sync_state("last_review", &day.to_string())
+    }
+
+    /// When every completed task was completed, archived ones included
+    pub fn get_completion_times(&self) -> Result<Vec<chrono::DateTime<chrono::Utc>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT completed_at FROM tasks WHERE completed = 1 AND completed_at IS NOT NULL",
+        )?;
+        let times = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|at| chrono::DateTime::parse_from_rfc3339(&at.ok()?).ok())
+            .map(|at| at.with_timezone(&chrono::Utc))
+            .collect();
+        Ok(times)
+    }
+
+    /// Get tasks modified since a given time
+    pub fn get_tasks_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM tasks WHERE updated_at > ?1")?;
+
+        let task_ids: Vec<String> = stmt
+            .query_map(params![since.to_rfc3339()], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::new();
+        for task_id in task_ids {
+            if let Ok(task) = self.get_task_by_id(&task_id) {
+                result.push(task);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get archived tasks, oldest archive first
+    pub fn get_archived_tasks(&self) -> Result<Vec<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM tasks WHERE archived_at IS NOT NULL ORDER BY archived_at")?;
+
+        let task_ids: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::new();
+        for task_id in task_ids {
+            if let Ok(task) = self.get_task_by_id(&task_id) {
+                result.push(task);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get tasks completed since a given time, most recent first
+    pub fn get_completed_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM tasks WHERE completed = 1 AND completed_at >= ?1
+             AND archived_at IS NULL ORDER BY completed_at DESC LIMIT ?2",
+        )?;
+
+        let task_ids: Vec<String> = stmt
+            .query_map(params![since.to_rfc3339(), limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::new();
         for task_id in task_ids {
             if let Ok(task) = self.get_task_by_id(&task_id) {
                 result.push(task);
@@ -707,11 +1585,49 @@ impl Database {
         Ok(result)
     }
 
+    /// Tasks whose title or description contains `query` (case-insensitive),
+    /// ordered like [`Self::get_tasks_with_filter`]
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn search_tasks(&self, query: &str) -> Result<Vec<Task>> {
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut stmt = self.conn.prepare(
+            r"SELECT id FROM tasks
+             WHERE archived_at IS NULL
+             AND (title LIKE ?1 ESCAPE '\' OR description LIKE ?1 ESCAPE '\')
+             ORDER BY completed, priority DESC, created_at DESC",
+        )?;
+
+        let task_ids: Vec<String> = stmt
+            .query_map(params![pattern], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::new();
+        for task_id in task_ids {
+            if let Ok(task) = self.get_task_by_id(&task_id) {
+                result.push(task);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get a single task
+    pub fn get_task(&self, task_id: Uuid) -> Result<Task> {
+        self.get_task_by_id(&task_id.to_string())
+    }
+
     /// Get a task by ID
     fn get_task_by_id(&self, task_id: &str) -> Result<Task> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, description, url, priority, completed, list_id, 
-             created_at, updated_at, completed_at, due_date FROM tasks WHERE id = ?1",
+             created_at, updated_at, completed_at, due_date, sort_order, archived_at, nag_hours
+                 FROM tasks WHERE id = ?1",
         )?;
 
         let task = stmt.query_row(params![task_id], |row| {
@@ -746,6 +1662,12 @@ impl Database {
                     .get::<_, Option<String>>(10)?
                     .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
                     .map(|dt| dt.with_timezone(&chrono::Utc)),
+                sort_order: row.get(11)?,
+                archived_at: row
+                    .get::<_, Option<String>>(12)?
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|dt| dt.with_timezone(&chrono::Utc)),
+                nag_hours: row.get(13)?,
             })
         })?;
 
@@ -757,7 +1679,7 @@ impl Database {
     /// Get lists modified since a given time
     pub fn get_lists_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<List>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at 
+            "SELECT id, name, description, icon, color, is_inbox, sort_order, created_at, updated_at, checklist
              FROM lists WHERE updated_at > ?1"
         )?;
 
@@ -776,6 +1698,7 @@ impl Database {
                 updated_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                     .unwrap()
                     .with_timezone(&chrono::Utc),
+                is_checklist: row.get::<_, i32>(9)? != 0,
             })
         })?;
 
@@ -934,6 +1857,42 @@ impl Database {
         Ok(())
     }
 
+    /// Upsert a comment (only if newer)
+    pub fn upsert_comment(&self, comment: &Comment) -> Result<()> {
+        self.conn.execute(
+            r#"INSERT INTO task_comments (id, task_id, body, created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5)
+               ON CONFLICT(id) DO UPDATE SET body = excluded.body, updated_at = excluded.updated_at
+               WHERE excluded.updated_at > task_comments.updated_at"#,
+            params![
+                comment.id.to_string(),
+                comment.task_id.to_string(),
+                comment.body,
+                comment.created_at.to_rfc3339(),
+                comment.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Upsert an attachment; attachments never change, so a copy already
+    /// stored is kept
+    pub fn upsert_attachment(&self, attachment: &Attachment) -> Result<()> {
+        self.conn.execute(
+            r#"INSERT OR IGNORE INTO task_attachments (id, task_id, name, hash, size, created_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+            params![
+                attachment.id.to_string(),
+                attachment.task_id.to_string(),
+                attachment.name,
+                attachment.hash,
+                attachment.size as i64,
+                attachment.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
     /// Upsert a task-tag link
     pub fn upsert_task_tag(&self, link: &crate::sync::TaskTagLink) -> Result<()> {
         self.conn.execute(
@@ -961,6 +1920,17 @@ mod tests {
         assert!(lists[0].is_inbox);
     }
 
+    #[test]
+    fn test_in_memory() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.is_in_memory());
+        assert_eq!(db.get_lists().unwrap().len(), 1);
+
+        let dir = tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.sqlite")).unwrap();
+        assert!(!db.is_in_memory());
+    }
+
     #[test]
     fn test_task_crud() {
         let dir = tempdir().unwrap();
@@ -991,4 +1961,351 @@ mod tests {
         let tasks = db.get_tasks_for_list(inbox.id).unwrap();
         assert!(tasks.is_empty());
     }
+
+    #[test]
+    fn test_bulk_task_operations() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let mut a = Task::new("A", inbox.id);
+        let mut b = Task::new("B", inbox.id);
+        db.insert_task(&a).unwrap();
+        db.insert_task(&b).unwrap();
+
+        a.complete();
+        b.complete();
+        db.update_tasks(&[a.clone(), b.clone()]).unwrap();
+        let tasks = db.get_tasks_for_list(inbox.id).unwrap();
+        assert!(tasks.iter().all(|t| t.completed));
+
+        db.delete_tasks(&[a.id, b.id]).unwrap();
+        assert!(db.get_tasks_for_list(inbox.id).unwrap().is_empty());
+        assert_eq!(db.get_all_tombstones().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_list_progress() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let mut done = Task::new("Done", inbox.id);
+        done.complete();
+        db.insert_task(&done).unwrap();
+        db.insert_task(&Task::new("Open", inbox.id)).unwrap();
+
+        let progress = db.get_list_progress().unwrap();
+        assert_eq!(progress.get(&inbox.id), Some(&(1, 2)));
+    }
+
+    #[test]
+    fn test_overdue_count() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let now = chrono::Utc::now();
+        let mut late = Task::new("Late", inbox.id);
+        late.due_date = Some(now - chrono::Duration::hours(1));
+        db.insert_task(&late).unwrap();
+        let mut late_done = late.clone();
+        late_done.id = Uuid::new_v4();
+        late_done.complete();
+        db.insert_task(&late_done).unwrap();
+        let mut later = Task::new("Later", inbox.id);
+        later.due_date = Some(now + chrono::Duration::hours(1));
+        db.insert_task(&later).unwrap();
+        db.insert_task(&Task::new("Undated", inbox.id)).unwrap();
+
+        assert_eq!(db.get_overdue_count(now).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_completed_since() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let mut old = Task::new("Old", inbox.id);
+        old.complete();
+        old.completed_at = Some(chrono::Utc::now() - chrono::Duration::days(2));
+        db.insert_task(&old).unwrap();
+        let mut recent = Task::new("Recent", inbox.id);
+        recent.complete();
+        db.insert_task(&recent).unwrap();
+        db.insert_task(&Task::new("Open", inbox.id)).unwrap();
+
+        let since = chrono::Utc::now() - chrono::Duration::days(1);
+        let done = db.get_completed_since(since, 5).unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].title, "Recent");
+    }
+
+    #[test]
+    fn test_search_tasks() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        db.insert_task(&Task::new("Write REPORT", inbox.id))
+            .unwrap();
+        let mut notes = Task::new("Meeting", inbox.id);
+        notes.description = Some("Bring the report draft".to_string());
+        db.insert_task(&notes).unwrap();
+        db.insert_task(&Task::new("100% done", inbox.id)).unwrap();
+
+        assert_eq!(db.search_tasks("report").unwrap().len(), 2);
+        // LIKE wildcards in the query are matched literally
+        let literal = db.search_tasks("0%").unwrap();
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal[0].title, "100% done");
+        assert!(db.search_tasks("_").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_tag() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let (old, new) = (Tag::new("wrk"), Tag::new("work"));
+        db.insert_tag(&old).unwrap();
+        db.insert_tag(&new).unwrap();
+        let mut only_old = Task::new("Only old", inbox.id);
+        only_old.tag_ids = vec![old.id];
+        let mut both = Task::new("Both", inbox.id);
+        both.tag_ids = vec![old.id, new.id];
+        db.insert_task(&only_old).unwrap();
+        db.insert_task(&both).unwrap();
+
+        db.merge_tag(old.id, new.id).unwrap();
+
+        let tags = db.get_tags().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(db.get_task(only_old.id).unwrap().tag_ids, vec![new.id]);
+        assert_eq!(db.get_task(both.id).unwrap().tag_ids, vec![new.id]);
+        assert_eq!(db.get_all_tombstones().unwrap()[0].0, old.id);
+    }
+
+    #[test]
+    fn test_checklist_flag() {
+        let db = Database::open_in_memory().unwrap();
+        let mut list = List::new("Packing");
+        list.is_checklist = true;
+        db.insert_list(&list).unwrap();
+        let find = |db: &Database| {
+            db.get_lists()
+                .unwrap()
+                .into_iter()
+                .find(|l| l.id == list.id)
+                .unwrap()
+        };
+        assert!(find(&db).is_checklist);
+
+        list.is_checklist = false;
+        db.update_list(&list).unwrap();
+        assert!(!find(&db).is_checklist);
+        assert!(!db.get_inbox().unwrap().is_checklist);
+    }
+
+    #[test]
+    fn test_delete_list_with() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let new_list = |name: &str| {
+            let list = List::new(name);
+            db.insert_list(&list).unwrap();
+            let task = Task::new(format!("{} task", name), list.id);
+            db.insert_task(&task).unwrap();
+            (list, task)
+        };
+
+        let (keep, _) = new_list("Keep");
+        let (moved, moved_task) = new_list("Moved");
+        db.delete_list_with(moved.id, DeletedListTasks::Move(keep.id))
+            .unwrap();
+        assert_eq!(db.get_task(moved_task.id).unwrap().list_id, keep.id);
+
+        let (archived, archived_task) = new_list("Archived");
+        db.delete_list_with(archived.id, DeletedListTasks::Archive)
+            .unwrap();
+        let task = db.get_task(archived_task.id).unwrap();
+        assert_eq!(task.list_id, inbox.id);
+        assert!(task.archived_at.is_some());
+
+        let (deleted, deleted_task) = new_list("Deleted");
+        db.delete_list_with(deleted.id, DeletedListTasks::Delete)
+            .unwrap();
+        assert!(db.get_task(deleted_task.id).is_err());
+
+        let names: Vec<String> = db
+            .get_lists()
+            .unwrap()
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Keep".to_string()));
+        assert!(
+            db.delete_list_with(inbox.id, DeletedListTasks::Delete)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let blocker = Task::new("Blocker", inbox.id);
+        let blocked = Task::new("Blocked", inbox.id);
+        db.insert_task(&blocker).unwrap();
+        db.insert_task(&blocked).unwrap();
+
+        db.add_dependency(blocked.id, blocker.id).unwrap();
+        db.add_dependency(blocked.id, blocker.id).unwrap();
+        assert_eq!(
+            db.get_dependencies().unwrap(),
+            vec![(blocked.id, blocker.id)]
+        );
+        assert_eq!(db.get_blocked_task_ids().unwrap(), vec![blocked.id]);
+
+        // Pairs with a deleted task are ignored
+        db.delete_task(blocker.id).unwrap();
+        assert!(db.get_dependencies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_tombstone() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let id = Uuid::new_v4();
+        db.record_tombstone(id, "task").unwrap();
+        assert_eq!(db.get_all_tombstones().unwrap().len(), 1);
+
+        db.remove_tombstone(id).unwrap();
+        assert!(db.get_all_tombstones().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sent_notifications() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let id = Uuid::new_v4();
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let next = day.succ_opt().unwrap();
+        assert!(!db.was_notified(id, "overdue", day).unwrap());
+
+        db.record_notification(id, "overdue", day).unwrap();
+        db.record_notification(id, "overdue", day).unwrap();
+        assert!(db.was_notified(id, "overdue", day).unwrap());
+        assert!(!db.was_notified(id, "due-today", day).unwrap());
+        assert!(!db.was_notified(id, "overdue", next).unwrap());
+
+        // Recording a new day forgets the old ones
+        db.record_notification(id, "overdue", next).unwrap();
+        assert!(!db.was_notified(id, "overdue", day).unwrap());
+    }
+
+    #[test]
+    fn test_archived_tasks_are_hidden() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let mut task = Task::new("Old report", inbox.id);
+        task.complete();
+        db.insert_task(&task).unwrap();
+        db.insert_task(&Task::new("Current", inbox.id)).unwrap();
+
+        task.archived_at = Some(chrono::Utc::now());
+        db.update_task(&task).unwrap();
+
+        let titles: Vec<String> = db
+            .get_all_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["Current"]);
+        assert!(db.search_tasks("report").unwrap().is_empty());
+        assert_eq!(db.get_total_task_count(true).unwrap(), 1);
+
+        let archived = db.get_archived_tasks().unwrap();
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].archived_at.is_some());
+        assert!(db.get_task(task.id).unwrap().archived_at.is_some());
+    }
+
+    #[test]
+    fn test_capture() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let id = db.capture("Call mom").unwrap();
+        let task = db.get_task(id).unwrap();
+        assert_eq!(task.title, "Call mom");
+        assert_eq!(task.list_id, db.get_inbox().unwrap().id);
+        assert_eq!(task.priority, Priority::Medium);
+        assert!(!task.completed);
+    }
+
+    #[test]
+    fn test_comments() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.sqlite");
+        let db = Database::open_path(&path).unwrap();
+
+        let inbox = db.get_inbox().unwrap();
+        let task = Task::new("Report", inbox.id);
+        db.insert_task(&task).unwrap();
+        let since = chrono::Utc::now();
+        let first = Comment::new(task.id, "Asked for numbers");
+        let second = Comment::new(task.id, "Drafting");
+        db.insert_comment(&first).unwrap();
+        db.insert_comment(&second).unwrap();
+
+        let bodies = |comments: Vec<Comment>| -> Vec<String> {
+            comments.into_iter().map(|c| c.body).collect()
+        };
+        assert_eq!(
+            bodies(db.get_comments(task.id).unwrap()),
+            ["Asked for numbers", "Drafting"]
+        );
+        assert_eq!(db.get_comments_since(since).unwrap().len(), 2);
+        assert_eq!(db.count_changes_since(Some(since)).unwrap(), 2);
+
+        // An older copy from sync doesn't overwrite a newer edit
+        let mut edited = first.clone();
+        edited.body = "Asked Ana for numbers".to_string();
+        edited.updated_at = chrono::Utc::now();
+        db.upsert_comment(&edited).unwrap();
+        db.upsert_comment(&first).unwrap();
+        assert_eq!(
+            db.get_comments(task.id).unwrap()[0].body,
+            "Asked Ana for numbers"
+        );
+
+        db.delete_comment(second.id).unwrap();
+        assert_eq!(db.get_all_comments().unwrap().len(), 1);
+
+        // Comments go with their task
+        db.delete_task(task.id).unwrap();
+        assert!(db.get_all_comments().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,114 @@
+//! Task dependencies
+//!
+//! Dependencies are `(task, blocked_by)` pairs. These helpers walk them to
+//! reject cycles and to draw the blocked-by / blocks trees of a task.
+
+use uuid::Uuid;
+
+/// Which way to walk the dependency graph from a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Tasks this one waits on
+    BlockedBy,
+    /// Tasks waiting on this one
+    Blocks,
+}
+
+/// Direct neighbours of `task` in the given direction
+pub fn neighbours(edges: &[(Uuid, Uuid)], task: Uuid, direction: Direction) -> Vec<Uuid> {
+    edges
+        .iter()
+        .filter_map(|&(t, blocker)| match direction {
+            Direction::BlockedBy if t == task => Some(blocker),
+            Direction::Blocks if blocker == task => Some(t),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether making `task` blocked by `blocker` would create a cycle
+pub fn would_cycle(edges: &[(Uuid, Uuid)], task: Uuid, blocker: Uuid) -> bool {
+    let mut stack = vec![blocker];
+    let mut seen = Vec::new();
+    while let Some(current) = stack.pop() {
+        if current == task {
+            return true;
+        }
+        if seen.contains(&current) {
+            continue;
+        }
+        seen.push(current);
+        stack.extend(neighbours(edges, current, Direction::BlockedBy));
+    }
+    false
+}
+
+/// Tree rows below `root` as `(prefix, task)`, e.g. `├─ `, `│  └─ `
+///
+/// The root itself is not included. A task already on the current path is
+/// skipped, so malformed (cyclic) data still terminates.
+pub fn tree(edges: &[(Uuid, Uuid)], root: Uuid, direction: Direction) -> Vec<(String, Uuid)> {
+    let mut rows = Vec::new();
+    let mut path = vec![root];
+    walk(edges, root, direction, "", &mut path, &mut rows);
+    rows
+}
+
+fn walk(
+    edges: &[(Uuid, Uuid)],
+    node: Uuid,
+    direction: Direction,
+    indent: &str,
+    path: &mut Vec<Uuid>,
+    rows: &mut Vec<(String, Uuid)>,
+) {
+    let children: Vec<Uuid> = neighbours(edges, node, direction)
+        .into_iter()
+        .filter(|c| !path.contains(c))
+        .collect();
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        rows.push((
+            format!("{}{}", indent, if last { "└─ " } else { "├─ " }),
+            *child,
+        ));
+        path.push(*child);
+        let child_indent = format!("{}{}", indent, if last { "   " } else { "│  " });
+        walk(edges, *child, direction, &child_indent, path, rows);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_detection() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        // a waits on b, b waits on c
+        let edges = vec![(a, b), (b, c)];
+        assert!(would_cycle(&edges, c, a));
+        assert!(would_cycle(&edges, a, a));
+        assert!(!would_cycle(&edges, a, c));
+    }
+
+    #[test]
+    fn test_tree_rows() {
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let edges = vec![(a, b), (b, c), (a, d)];
+
+        let rows = tree(&edges, a, Direction::BlockedBy);
+        let prefixes: Vec<&str> = rows.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(prefixes, vec!["├─ ", "│  └─ ", "└─ "]);
+        assert_eq!(rows.iter().map(|r| r.1).collect::<Vec<_>>(), vec![b, c, d]);
+
+        let blocks = tree(&edges, c, Direction::Blocks);
+        assert_eq!(blocks.iter().map(|r| r.1).collect::<Vec<_>>(), vec![b, a]);
+    }
+}
@@ -0,0 +1,246 @@
+//! Differences between the database and an earlier JSON export
+//!
+//! `tickit diff` reads back what `tickit export` wrote and lines its tasks
+//! up with the current ones by ID: tasks only in the database were added,
+//! tasks only in the export were removed, and tasks in both are compared
+//! field by field. Timestamps alone don't count as a change.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{List, Tag, Task};
+
+/// The parts of a JSON export a diff needs
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub lists: Vec<List>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    pub tasks: Vec<Task>,
+}
+
+impl Snapshot {
+    /// Parse the output of `tickit export --format json`
+    pub fn parse(json: &[u8]) -> Result<Self> {
+        serde_json::from_slice(json)
+            .context("Not a tickit JSON export (encrypted exports need `tickit decrypt` first)")
+    }
+}
+
+/// One field of a task that differs, shown as text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// A task in both the export and the database whose fields differ
+#[derive(Debug, Clone, Serialize)]
+pub struct Changed {
+    /// The task as it is now
+    pub task: Task,
+    pub changes: Vec<FieldChange>,
+}
+
+/// What happened to the tasks since an export
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Diff {
+    pub added: Vec<Task>,
+    pub removed: Vec<Task>,
+    pub changed: Vec<Changed>,
+    pub unchanged: usize,
+}
+
+impl Diff {
+    /// Compare `before` (the export) with `after` (the database), naming
+    /// lists and tags from either side
+    pub fn new(before: &Snapshot, after: &[Task], lists: &[List], tags: &[Tag]) -> Self {
+        let list_names: HashMap<Uuid, &str> = before
+            .lists
+            .iter()
+            .chain(lists)
+            .map(|l| (l.id, l.name.as_str()))
+            .collect();
+        let tag_names: HashMap<Uuid, &str> = before
+            .tags
+            .iter()
+            .chain(tags)
+            .map(|t| (t.id, t.name.as_str()))
+            .collect();
+        let list_name = |id: Uuid| {
+            list_names
+                .get(&id)
+                .map_or("(deleted list)", |n| n)
+                .to_string()
+        };
+        let tag_list = |ids: &[Uuid]| {
+            let mut names: Vec<&str> = ids
+                .iter()
+                .map(|id| tag_names.get(id).copied().unwrap_or("(deleted tag)"))
+                .collect();
+            names.sort_unstable();
+            if names.is_empty() {
+                "(none)".to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+
+        let old: HashMap<Uuid, &Task> = before.tasks.iter().map(|t| (t.id, t)).collect();
+        let mut diff = Diff {
+            removed: before
+                .tasks
+                .iter()
+                .filter(|t| !after.iter().any(|a| a.id == t.id))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        for task in after {
+            let Some(old) = old.get(&task.id) else {
+                diff.added.push(task.clone());
+                continue;
+            };
+            let changes = compare(old, task, &list_name, &tag_list);
+            if changes.is_empty() {
+                diff.unchanged += 1;
+            } else {
+                diff.changed.push(Changed {
+                    task: task.clone(),
+                    changes,
+                });
+            }
+        }
+        diff
+    }
+
+    /// Whether nothing was added, removed or changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The fields a user would notice that differ between `old` and `new`
+fn compare(
+    old: &Task,
+    new: &Task,
+    list_name: &dyn Fn(Uuid) -> String,
+    tag_list: &dyn Fn(&[Uuid]) -> String,
+) -> Vec<FieldChange> {
+    let text = |s: &Option<String>| s.clone().unwrap_or_else(|| "(none)".to_string());
+    let date = |d: &Option<DateTime<Utc>>| {
+        d.map_or_else(|| "(none)".to_string(), |d| d.date_naive().to_string())
+    };
+    let status = |t: &Task| if t.completed { "done" } else { "open" }.to_string();
+    let nag = |t: &Task| match t.nag_hours {
+        None => "off".to_string(),
+        Some(0) => "on".to_string(),
+        Some(hours) => format!("every {}h", hours),
+    };
+    let shown = [
+        ("title", old.title.clone(), new.title.clone()),
+        (
+            "description",
+            text(&old.description),
+            text(&new.description),
+        ),
+        ("url", text(&old.url), text(&new.url)),
+        (
+            "priority",
+            old.priority.to_string(),
+            new.priority.to_string(),
+        ),
+        ("status", status(old), status(new)),
+        ("list", list_name(old.list_id), list_name(new.list_id)),
+        ("tags", tag_list(&old.tag_ids), tag_list(&new.tag_ids)),
+        ("due date", date(&old.due_date), date(&new.due_date)),
+        (
+            "position",
+            old.sort_order.to_string(),
+            new.sort_order.to_string(),
+        ),
+        ("nag", nag(old), nag(new)),
+        (
+            "archived",
+            date(&old.archived_at).replace("(none)", "no"),
+            date(&new.archived_at).replace("(none)", "no"),
+        ),
+    ];
+    shown
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| FieldChange {
+            field,
+            before,
+            after,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Priority;
+
+    #[test]
+    fn test_diff() {
+        let (inbox, work) = (List::new("Inbox"), List::new("Work"));
+        let urgent = Tag::new("urgent");
+        let kept = Task::new("Unchanged", inbox.id);
+        let gone = Task::new("Removed", inbox.id);
+        let edited = Task::new("Edited", inbox.id);
+        let json = serde_json::json!({
+            "exported_at": "2026-10-11T09:00:00Z",
+            "lists": [inbox],
+            "tags": [urgent],
+            "tasks": [kept, gone, edited],
+        });
+        let snapshot = Snapshot::parse(json.to_string().as_bytes()).unwrap();
+
+        let mut now_edited = edited.clone();
+        now_edited.list_id = work.id;
+        now_edited.priority = Priority::High;
+        now_edited.tag_ids = vec![urgent.id];
+        let mut touched = kept.clone();
+        touched.updated_at = Utc::now() + chrono::Duration::hours(1);
+        let new = Task::new("Added", work.id);
+        let diff = Diff::new(
+            &snapshot,
+            &[touched, now_edited, new.clone()],
+            &[inbox, work],
+            &[],
+        );
+
+        assert_eq!(
+            diff.added.iter().map(|t| t.id).collect::<Vec<_>>(),
+            [new.id]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|t| t.id).collect::<Vec<_>>(),
+            [gone.id]
+        );
+        assert_eq!(diff.unchanged, 1);
+        let changes: Vec<(&str, &str, &str)> = diff.changed[0]
+            .changes
+            .iter()
+            .map(|c| (c.field, c.before.as_str(), c.after.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("priority", "Medium", "High"),
+                ("list", "Inbox", "Work"),
+                ("tags", "(none)", "urgent"),
+            ]
+        );
+        assert!(Snapshot::parse(b"age-encryption.org/v1").is_err());
+    }
+}
@@ -1,34 +1,62 @@
 //! Export functionality for tasks
 
-use anyhow::Result;
-use chrono::Utc;
-use std::io::Write;
-
-use crate::models::{ExportFormat, List, Priority, Tag, Task};
-
-/// Export tasks to a specific format
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::models::{Comment, ExportFormat, List, Priority, Tag, Task};
+
+/// Export tasks to a specific format.
+///
+/// Markdown is for reading, so it uses the configured date and time formats;
+/// the other formats keep ISO dates so they can be read back by other tools.
+/// Comments go with their task everywhere but todo.txt, which has no room
+/// for them.
 pub fn export_tasks<W: Write>(
     writer: &mut W,
     tasks: &[Task],
     lists: &[List],
     tags: &[Tag],
+    comments: &[Comment],
     format: ExportFormat,
+    config: &Config,
 ) -> Result<()> {
     match format {
-        ExportFormat::Json => export_json(writer, tasks, lists, tags),
+        ExportFormat::Json => export_json(writer, tasks, lists, tags, comments),
         ExportFormat::TodoTxt => export_todotxt(writer, tasks, lists, tags),
-        ExportFormat::Markdown => export_markdown(writer, tasks, lists, tags),
-        ExportFormat::Csv => export_csv(writer, tasks, lists, tags),
+        ExportFormat::Markdown => export_markdown(writer, tasks, lists, tags, comments, config),
+        ExportFormat::Csv => export_csv(writer, tasks, lists, tags, comments),
     }
 }
 
+/// The comments on `task`, oldest first
+fn comments_on<'a>(comments: &'a [Comment], task: &Task) -> Vec<&'a Comment> {
+    let mut on_task: Vec<_> = comments.iter().filter(|c| c.task_id == task.id).collect();
+    on_task.sort_by_key(|c| c.created_at);
+    on_task
+}
+
 /// Export to JSON format
 fn export_json<W: Write>(
     writer: &mut W,
     tasks: &[Task],
     lists: &[List],
     tags: &[Tag],
+    comments: &[Comment],
 ) -> Result<()> {
+    let tasks = tasks
+        .iter()
+        .map(|task| {
+            let mut value = serde_json::to_value(task)?;
+            let on_task = comments_on(comments, task);
+            if !on_task.is_empty() {
+                value["comments"] = serde_json::to_value(on_task)?;
+            }
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>>>()?;
     let export = serde_json::json!({
         "exported_at": Utc::now().to_rfc3339(),
         "lists": lists,
@@ -112,13 +140,15 @@ fn export_markdown<W: Write>(
     tasks: &[Task],
     lists: &[List],
     tags: &[Tag],
+    comments: &[Comment],
+    config: &Config,
 ) -> Result<()> {
     writeln!(writer, "# Tasks")?;
     writeln!(writer)?;
     writeln!(
         writer,
         "Exported: {}",
-        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        config.format_datetime(Local::now().naive_local())
     )?;
     writeln!(writer)?;
 
@@ -170,7 +200,21 @@ fn export_markdown<W: Write>(
 
             // Due date
             if let Some(due) = task.due_date {
-                writeln!(writer, "  - 📅 Due: {}", due.format("%Y-%m-%d"))?;
+                writeln!(
+                    writer,
+                    "  - 📅 Due: {}",
+                    config.format_date(due.date_naive())
+                )?;
+            }
+
+            for comment in comments_on(comments, task) {
+                let at = comment.created_at.with_timezone(&Local).naive_local();
+                writeln!(
+                    writer,
+                    "  - 💬 {}: {}",
+                    config.format_datetime(at),
+                    comment.body
+                )?;
             }
         }
 
@@ -186,11 +230,12 @@ fn export_csv<W: Write>(
     tasks: &[Task],
     lists: &[List],
     tags: &[Tag],
+    comments: &[Comment],
 ) -> Result<()> {
     // Header
     writeln!(
         writer,
-        "Title,Description,URL,Priority,Completed,List,Tags,Due Date,Created At"
+        "Title,Description,URL,Priority,Completed,List,Tags,Due Date,Created At,Comments"
     )?;
 
     for task in tasks {
@@ -207,9 +252,15 @@ fn export_csv<W: Write>(
             .map(|t| t.name.as_str())
             .collect();
 
+        // One line per comment, with its time
+        let comment_lines: Vec<_> = comments_on(comments, task)
+            .into_iter()
+            .map(|c| format!("{} {}", c.created_at.format("%Y-%m-%d %H:%M:%S"), c.body))
+            .collect();
+
         writeln!(
             writer,
-            "{},{},{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{}",
             csv_escape(&task.title),
             csv_escape(task.description.as_deref().unwrap_or("")),
             csv_escape(task.url.as_deref().unwrap_or("")),
@@ -221,6 +272,7 @@ fn export_csv<W: Write>(
                 .map(|d| d.format("%Y-%m-%d").to_string())
                 .unwrap_or_default(),
             task.created_at.format("%Y-%m-%d %H:%M:%S"),
+            csv_escape(&comment_lines.join("\n")),
         )?;
     }
 
@@ -235,3 +287,296 @@ fn csv_escape(s: &str) -> String {
         s.to_string()
     }
 }
+
+/// Write tasks with a due date as an iCalendar feed of all-day events
+///
+/// Events rather than to-dos, since that's what Google and Apple Calendar
+/// show next to meetings. Completed tasks are marked as cancelled.
+pub fn export_ical<W: Write>(
+    writer: &mut W,
+    tasks: &[Task],
+    lists: &[List],
+    tags: &[Tag],
+) -> Result<()> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//tickit//tickit {}//EN", crate::VERSION),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Tickit".to_string(),
+    ];
+    let stamp = |at: chrono::DateTime<Utc>| at.format("%Y%m%dT%H%M%SZ").to_string();
+
+    for task in tasks {
+        let Some(due) = task.due_date else {
+            continue;
+        };
+        let day = due.date_naive();
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@tickit", task.id));
+        lines.push(format!("DTSTAMP:{}", stamp(task.updated_at)));
+        lines.push(format!("LAST-MODIFIED:{}", stamp(task.updated_at)));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+        if let Some(next) = day.succ_opt() {
+            lines.push(format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")));
+        }
+        lines.push(format!("SUMMARY:{}", ical_escape(&task.title)));
+        let description: Vec<&str> = [task.description.as_deref(), task.url.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if !description.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                ical_escape(&description.join("\n\n"))
+            ));
+        }
+        if let Some(url) = &task.url {
+            lines.push(format!("URL:{}", url));
+        }
+        let categories: Vec<String> = lists
+            .iter()
+            .filter(|l| l.id == task.list_id)
+            .map(|l| l.name.as_str())
+            .chain(
+                task.tag_ids
+                    .iter()
+                    .filter_map(|id| tags.iter().find(|t| t.id == *id))
+                    .map(|t| t.name.as_str()),
+            )
+            .map(ical_escape)
+            .collect();
+        if !categories.is_empty() {
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        lines.push(format!(
+            "PRIORITY:{}",
+            match task.priority {
+                Priority::Urgent => 1,
+                Priority::High => 3,
+                Priority::Medium => 5,
+                Priority::Low => 9,
+            }
+        ));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        if task.completed {
+            lines.push("STATUS:CANCELLED".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(writer, "{}\r\n", fold_line(&line))?;
+    }
+    Ok(())
+}
+
+/// Escape a text value for iCalendar
+fn ical_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Split a content line into 75-byte pieces, continued with a leading space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// How [`encrypt`] locks an export
+#[derive(Debug, Clone)]
+pub enum Encryption {
+    /// A passphrase, stretched with scrypt
+    Passphrase(String),
+    /// age public keys (`age1...`); any of their identities can decrypt
+    Recipients(Vec<String>),
+}
+
+/// What [`decrypt`] unlocks an export with
+#[derive(Debug, Clone)]
+pub enum Decryption {
+    Passphrase(String),
+    /// An age identity file, as made by `age-keygen`
+    IdentityFile(PathBuf),
+}
+
+/// Write `plain` as an age file, readable by `age -d` as well as
+/// [`decrypt`]; `armor` writes it as PEM-style text
+pub fn encrypt<W: Write>(
+    writer: W,
+    plain: &[u8],
+    encryption: &Encryption,
+    armor: bool,
+) -> Result<()> {
+    let encryptor = match encryption {
+        Encryption::Passphrase(passphrase) => {
+            age::Encryptor::with_user_passphrase(passphrase.as_str().into())
+        }
+        Encryption::Recipients(keys) => {
+            let recipients = keys
+                .iter()
+                .map(|key| {
+                    key.trim()
+                        .parse::<age::x25519::Recipient>()
+                        .map_err(|e| anyhow::anyhow!("Invalid age recipient {}: {}", key, e))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))?
+        }
+    };
+    let format = if armor {
+        age::armor::Format::AsciiArmor
+    } else {
+        age::armor::Format::Binary
+    };
+    let output = age::armor::ArmoredWriter::wrap_output(writer, format)?;
+    let mut stream = encryptor.wrap_output(output)?;
+    stream.write_all(plain)?;
+    stream.finish()?.finish()?;
+    Ok(())
+}
+
+/// Read an age file written by [`encrypt`] (armored or not)
+pub fn decrypt<R: Read>(reader: R, decryption: &Decryption) -> Result<Vec<u8>> {
+    let decryptor = age::Decryptor::new(age::armor::ArmoredReader::new(reader))
+        .context("Not an encrypted export")?;
+    let identities: Vec<Box<dyn age::Identity>> = match decryption {
+        Decryption::Passphrase(passphrase) => {
+            if !decryptor.is_scrypt() {
+                anyhow::bail!("This export was encrypted to a key; pass its identity file");
+            }
+            vec![Box::new(age::scrypt::Identity::new(
+                passphrase.as_str().into(),
+            ))]
+        }
+        Decryption::IdentityFile(path) => age::IdentityFile::from_file(path.display().to_string())
+            .with_context(|| format!("Could not read {}", path.display()))?
+            .into_identities()?,
+    };
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|i| i.as_ref()))
+        .map_err(|e| match e {
+            age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                anyhow::anyhow!("Wrong passphrase or key")
+            }
+            e => e.into(),
+        })?;
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain)?;
+    Ok(plain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let plain = b"{\"tasks\": []}";
+
+        // scrypt is deliberately slow, so the passphrase path runs once
+        let mut sealed = Vec::new();
+        let passphrase = Encryption::Passphrase("correct horse".to_string());
+        encrypt(&mut sealed, plain, &passphrase, false).unwrap();
+        let opened = decrypt(&sealed[..], &Decryption::Passphrase("correct horse".into())).unwrap();
+        assert_eq!(opened, plain);
+
+        let dir = tempfile::tempdir().unwrap();
+        let identity = age::x25519::Identity::generate();
+        let key_file = dir.path().join("key.txt");
+        use age::secrecy::ExposeSecret;
+        std::fs::write(&key_file, identity.to_string().expose_secret()).unwrap();
+        let mut sealed = Vec::new();
+        let recipients = Encryption::Recipients(vec![identity.to_public().to_string()]);
+        encrypt(&mut sealed, plain, &recipients, true).unwrap();
+        assert!(sealed.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----"));
+        assert_eq!(
+            decrypt(&sealed[..], &Decryption::IdentityFile(key_file)).unwrap(),
+            plain
+        );
+        assert!(decrypt(&sealed[..], &Decryption::Passphrase("x".into())).is_err());
+        assert!(decrypt(&plain[..], &Decryption::Passphrase("x".into())).is_err());
+        assert!(
+            encrypt(
+                Vec::new(),
+                plain,
+                &Encryption::Recipients(vec!["age1nope".into()]),
+                false
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_export_comments() {
+        let list = List::new("Work");
+        let task = Task::new("Report", list.id);
+        let other = Task::new("Other", list.id);
+        let mut first = Comment::new(task.id, "Asked Ana for numbers");
+        first.created_at = "2030-01-02T10:00:00Z".parse().unwrap();
+        let mut second = Comment::new(task.id, "Numbers in, drafting");
+        second.created_at = "2030-01-03T10:00:00Z".parse().unwrap();
+        let comments = [second, first];
+        let tasks = [task, other];
+        let lists = [list];
+        let config = Config::default();
+        let export = |format| {
+            let mut out = Vec::new();
+            export_tasks(&mut out, &tasks, &lists, &[], &comments, format, &config).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let markdown = export(ExportFormat::Markdown);
+        let first_at = markdown.find("💬").unwrap();
+        assert!(markdown[first_at..].contains("Asked Ana for numbers"));
+        assert!(
+            markdown.find("Asked Ana").unwrap() < markdown.find("Numbers in").unwrap(),
+            "comments are oldest first"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&export(ExportFormat::Json)).unwrap();
+        assert_eq!(json["tasks"][0]["comments"].as_array().unwrap().len(), 2);
+        assert!(json["tasks"][1].get("comments").is_none());
+
+        let csv = export(ExportFormat::Csv);
+        assert!(csv.lines().next().unwrap().ends_with(",Comments"));
+        assert!(csv.contains("\"2030-01-02 10:00:00 Asked Ana for numbers\n2030-01-03"));
+    }
+
+    #[test]
+    fn test_export_ical() {
+        let list = List::new("Work");
+        let mut due = Task::new("Send report; v2, final", list.id)
+            .with_due_date("2030-01-02T23:59:59Z".parse().unwrap());
+        due.description = Some("x".repeat(100));
+        let undated = Task::new("Someday", list.id);
+
+        let mut out = Vec::new();
+        export_ical(&mut out, &[due.clone(), undated], &[list], &[]).unwrap();
+        let ical = String::from_utf8(out).unwrap();
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ical.contains(&format!("UID:{}@tickit\r\n", due.id)));
+        assert!(ical.contains("DTSTART;VALUE=DATE:20300102\r\n"));
+        assert!(ical.contains("DTEND;VALUE=DATE:20300103\r\n"));
+        assert!(ical.contains("SUMMARY:Send report\\; v2\\, final\r\n"));
+        assert!(ical.contains("CATEGORIES:Work\r\n"));
+        assert!(ical.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ical.contains("\r\n x"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Fuzzy matching for jump-to-task
+//!
+//! A query matches when its characters appear in order in the candidate
+//! (case-insensitive). Consecutive runs and matches at word starts score higher.
+
+/// A successful match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Higher is better
+    pub score: i64,
+    /// Char indices of the matched characters in the candidate
+    pub indices: Vec<usize>,
+}
+
+/// Match `query` against `candidate`, returning `None` if any query char is missing
+///
+/// Every occurrence of the first query char is tried as a starting point and the
+/// best-scoring match is kept. Characters are compared one to one, so the
+/// indices line up with `candidate.chars()` even where lowercasing a
+/// character yields several.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+
+    let Some(&first) = query.first() else {
+        return Some(Match {
+            score: 0,
+            indices: Vec::new(),
+        });
+    };
+    (0..chars.len())
+        .filter(|&i| same(chars[i], first))
+        .filter_map(|start| match_from(&query, &chars, start))
+        .max_by_key(|m| m.score)
+}
+
+/// Whether `a` and `b` are the same character, ignoring case
+fn same(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Greedily match `query` against `chars` starting at `start`
+fn match_from(query: &[char], chars: &[char], start: usize) -> Option<Match> {
+    let mut indices = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut wanted = query.iter().peekable();
+
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        let Some(&&q) = wanted.peek() else {
+            break;
+        };
+        if !same(c, q) {
+            continue;
+        }
+        score += 1;
+        if indices.last().is_some_and(|&last| last + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !chars[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        indices.push(i);
+        wanted.next();
+    }
+
+    if wanted.peek().is_some() {
+        return None;
+    }
+    // Prefer tighter, earlier matches
+    let spread = (indices[indices.len() - 1] - start) as i64 + start as i64 / 2;
+    Some(Match {
+        score: score * 10 - spread,
+        indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_matching() {
+        let m = fuzzy_match("bml", "Buy milk").unwrap();
+        assert_eq!(m.indices, vec![0, 4, 6]);
+        assert!(fuzzy_match("xyz", "Buy milk").is_none());
+        assert!(fuzzy_match("", "anything").is_some());
+    }
+
+    #[test]
+    fn test_indices_follow_candidate_chars() {
+        // 'İ' lowercases to two chars, which used to shift every later index
+        let m = fuzzy_match("stan", "İstanbul trip").unwrap();
+        assert_eq!(m.indices, vec![1, 2, 3, 4]);
+        let m = fuzzy_match("trip", "İstanbul trip").unwrap();
+        assert_eq!(m.indices, vec![9, 10, 11, 12]);
+        assert_eq!(
+            fuzzy_match("MILK", "Buy milk").unwrap().indices,
+            vec![4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn test_word_starts_rank_higher() {
+        let word = fuzzy_match("rep", "Write report").unwrap();
+        let scattered = fuzzy_match("rep", "Prepare slides").unwrap();
+        assert!(word.score > scattered.score);
+    }
+}
@@ -0,0 +1,195 @@
+//! Startup checks for problems that otherwise only show up as odd behavior
+//! later: a database on a network share, a sync server that can't be
+//! reached or whose clock disagrees with ours, and deletion records piling up
+//!
+//! The TUI lists what these find under `!`.
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::SyncConfig;
+use crate::db::Database;
+
+/// Tombstones (records of deletions kept for sync) above this many are reported
+pub const TOMBSTONE_LIMIT: usize = 50_000;
+
+/// Clock differences with the sync server above this are reported
+pub const MAX_CLOCK_SKEW: chrono::Duration = chrono::Duration::minutes(2);
+
+/// Filesystems where SQLite's file locking can't be relied on
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.sshfs",
+    "fuse.davfs",
+];
+
+/// Something worth knowing about, found at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Stable name of the check, for remembering dismissals
+    pub id: &'static str,
+    pub title: String,
+    pub detail: String,
+}
+
+impl Warning {
+    fn new(id: &'static str, title: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            title: title.into(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Checks on the database at `path`; quick, so they run before the TUI opens
+pub fn check_database(db: &Database, path: &Path) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if let Some(fs) = network_filesystem(path) {
+        let journal = db.journal_mode().unwrap_or_else(|_| "unknown".to_string());
+        warnings.push(Warning::new(
+            "network-database",
+            format!("Database is on a network share ({})", fs),
+            format!(
+                "SQLite can't lock files reliably there, and WAL can't be used \
+                 (journal mode: {}). Two machines writing at once can corrupt it; \
+                 keep it on a local disk and use sync instead.",
+                journal
+            ),
+        ));
+    }
+    if let Ok(count) = db.tombstone_count()
+        && count > TOMBSTONE_LIMIT
+    {
+        warnings.push(Warning::new(
+            "tombstones",
+            "Many deletion records",
+            format!(
+                "Every deleted task, list and tag leaves a record so the deletion can \
+                 sync, and {} of them slow down each sync.",
+                count
+            ),
+        ));
+    }
+    warnings
+}
+
+/// Checks against the sync server; these wait on the network, so the TUI
+/// runs them in the background
+pub fn check_server(config: &SyncConfig) -> Vec<Warning> {
+    let Some(server) = config.server.as_deref().filter(|_| config.enabled) else {
+        return Vec::new();
+    };
+    if !config.network_allowed().unwrap_or(true) {
+        return Vec::new();
+    }
+    let response = match ureq::head(server).timeout(Duration::from_secs(5)).call() {
+        Ok(response) => response,
+        // Any answer, even an error status, means the server is up
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => {
+            return vec![Warning::new(
+                "sync-unreachable",
+                "Sync server can't be reached",
+                format!("{}. Changes are kept here and sync once it's back.", e),
+            )];
+        }
+    };
+    response
+        .header("Date")
+        .and_then(|date| clock_skew(date, Utc::now()))
+        .filter(|skew| skew.abs() > MAX_CLOCK_SKEW)
+        .map(|skew| {
+            vec![Warning::new(
+                "clock-skew",
+                "Clock differs from the sync server",
+                format!(
+                    "This machine is {} minute(s) {} the server. Sync goes by \
+                     timestamps, so edits can be lost or come back; set the clock \
+                     automatically (NTP).",
+                    skew.num_minutes().abs(),
+                    if skew > chrono::Duration::zero() {
+                        "ahead of"
+                    } else {
+                        "behind"
+                    }
+                ),
+            )]
+        })
+        .unwrap_or_default()
+}
+
+/// How far `now` is ahead of `server_date`, an HTTP `Date` header
+pub fn clock_skew(server_date: &str, now: DateTime<Utc>) -> Option<chrono::Duration> {
+    let server = DateTime::parse_from_rfc2822(server_date).ok()?;
+    Some(now - server.with_timezone(&Utc))
+}
+
+/// Type of the network filesystem holding `path`, if it's on one
+fn network_filesystem(path: &Path) -> Option<String> {
+    if cfg!(windows) && path.to_string_lossy().starts_with(r"\\") {
+        return Some("SMB".to_string());
+    }
+    let path = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    mount_type(&mounts, &path)
+        .filter(|fs| NETWORK_FILESYSTEMS.contains(fs))
+        .map(str::to_string)
+}
+
+/// Filesystem type of the innermost mount in `mounts` (as in /proc/mounts)
+/// that contains `path`
+fn mount_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_, mount_point, fs) = (fields.next()?, fields.next()?, fields.next()?);
+            // Spaces in mount points are written as \040
+            let mount_point = mount_point.replace("\\040", " ");
+            path.starts_with(&mount_point)
+                .then_some((mount_point.len(), fs))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs)| fs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_type() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      server:/home /home nfs4 rw 0 0\n\
+                      /dev/sdb1 /home/me/local\\040disk ext4 rw 0 0\n";
+        let fs = |path: &str| mount_type(mounts, Path::new(path));
+        assert_eq!(fs("/var/lib/tickit.db"), Some("ext4"));
+        assert_eq!(fs("/home/me/.config/tickit/tickit.db"), Some("nfs4"));
+        assert_eq!(fs("/home/me/local disk/tickit.db"), Some("ext4"));
+        // Components, not string prefixes
+        assert_eq!(fs("/homework/tickit.db"), Some("ext4"));
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:05:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            clock_skew("Sun, 01 Mar 2026 12:00:00 GMT", now),
+            Some(chrono::Duration::minutes(5))
+        );
+        assert_eq!(clock_skew("yesterday", now), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Business days for due-date suggestions
+//!
+//! `[calendar]` in config.toml names an .ics file of holidays (a path, or a
+//! URL such as a company calendar feed) and whether weekends count.
+//! Natural-language dates like `next-business-day` or `3bd` skip the days
+//! that don't, and the TUI date picker dims them. Feeds are cached in the
+//! cache directory and fetched again once the copy is a day old; if the feed
+//! can't be reached the cached copy is used.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::config::CalendarConfig;
+
+/// How long a fetched holiday feed is used before fetching it again
+pub const FEED_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Set by [`configure`]
+static CONFIG: OnceLock<CalendarConfig> = OnceLock::new();
+
+/// Loaded on first use by [`business_days`]
+static DAYS: OnceLock<BusinessDays> = OnceLock::new();
+
+/// Which days work happens on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BusinessDays {
+    pub holidays: BTreeSet<NaiveDate>,
+    pub skip_weekends: bool,
+}
+
+impl BusinessDays {
+    /// Read the holidays named in `config`
+    pub fn load(config: &CalendarConfig) -> Result<Self> {
+        let holidays = match config.holidays.as_deref().map(str::trim) {
+            None | Some("") => BTreeSet::new(),
+            Some(source) if source.starts_with("http://") || source.starts_with("https://") => {
+                parse_ics(&fetch_feed(source)?)
+            }
+            Some(path) => {
+                let path = expand_home(path);
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+                parse_ics(&text)
+            }
+        };
+        Ok(Self {
+            holidays,
+            skip_weekends: config.skip_weekends,
+        })
+    }
+
+    /// Whether `date` is a working day
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        let day_off = self.skip_weekends && matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        !day_off && !self.holidays.contains(&date)
+    }
+
+    /// The `count`th business day after `from` (`from` itself never counts)
+    pub fn add_business_days(&self, from: NaiveDate, count: u32) -> Option<NaiveDate> {
+        let mut date = from;
+        for _ in 0..count.max(1) {
+            // A calendar with no working days at all would loop forever
+            date = (1..=366)
+                .filter_map(|n| date.checked_add_days(chrono::Days::new(n)))
+                .find(|d| self.is_business_day(*d))?;
+        }
+        Some(date)
+    }
+}
+
+/// Use `config` for [`business_days`]; only the first call counts, and
+/// without one `[calendar]` is read from config.toml when first needed
+pub fn configure(config: &CalendarConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+/// The business days from the configured calendar, loaded once; weekends
+/// only if the holidays can't be read
+pub fn business_days() -> &'static BusinessDays {
+    DAYS.get_or_init(|| {
+        let config = CONFIG.get().cloned().unwrap_or_else(|| {
+            crate::Config::default_path()
+                .and_then(|path| crate::Config::load_from(&path))
+                .map(|config| config.calendar)
+                .unwrap_or_default()
+        });
+        BusinessDays::load(&config).unwrap_or_else(|e| {
+            tracing::warn!("Could not load holidays: {:#}", e);
+            BusinessDays {
+                holidays: BTreeSet::new(),
+                skip_weekends: config.skip_weekends,
+            }
+        })
+    })
+}
+
+/// The dates of every event in an iCalendar file
+///
+/// All-day events cover DTSTART up to (not including) DTEND; timed events
+/// cover the day they start on. Recurring events only count once.
+pub fn parse_ics(text: &str) -> BTreeSet<NaiveDate> {
+    // Lines starting with a space or tab continue the previous one
+    let unfolded = text
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut dates = BTreeSet::new();
+    let mut start = None;
+    let mut end = None;
+    for line in unfolded.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match name.to_ascii_uppercase().as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                start = None;
+                end = None;
+            }
+            "DTSTART" => start = parse_ics_date(value),
+            "DTEND" => {
+                // Timed ends don't extend the event past its start day
+                let all_day =
+                    params.to_ascii_uppercase().contains("VALUE=DATE") || value.trim().len() == 8;
+                end = parse_ics_date(value).filter(|_| all_day);
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if let Some(first) = start.take() {
+                    let last = end.take().and_then(|d: NaiveDate| d.pred_opt());
+                    let last = last.filter(|l| *l >= first).unwrap_or(first);
+                    dates.extend(first.iter_days().take_while(|d| *d <= last));
+                }
+            }
+            _ => {}
+        }
+    }
+    dates
+}
+
+/// The date of an iCalendar DATE or DATE-TIME value
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim().get(..8)?, "%Y%m%d").ok()
+}
+
+/// The feed at `url`, from the cache when it's fresh enough
+fn fetch_feed(url: &str) -> Result<String> {
+    let cache = cache_path(url);
+    let cached = cache.as_ref().and_then(|path| {
+        let age = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        Some((age, std::fs::read_to_string(path).ok()?))
+    });
+    if let Some((age, text)) = &cached
+        && *age < FEED_MAX_AGE
+    {
+        return Ok(text.clone());
+    }
+
+    let fetched = ureq::get(url)
+        .set("User-Agent", &format!("tickit/{}", crate::VERSION))
+        .timeout(Duration::from_secs(10))
+        .call()
+        .map_err(anyhow::Error::from)
+        .and_then(|response| response.into_string().map_err(Into::into));
+    match (fetched, cached) {
+        (Ok(text), _) => {
+            if let Some(path) = &cache {
+                if let Some(dir) = path.parent() {
+                    let _ = std::fs::create_dir_all(dir);
+                }
+                let _ = std::fs::write(path, &text);
+            }
+            Ok(text)
+        }
+        (Err(e), Some((_, text))) => {
+            tracing::warn!("Could not fetch holidays, using cached copy: {:#}", e);
+            Ok(text)
+        }
+        (Err(e), None) => Err(e).with_context(|| format!("Could not fetch {}", url)),
+    }
+}
+
+/// Where the feed at `url` is cached
+fn cache_path(url: &str) -> Option<PathBuf> {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    url.hash(&mut hasher);
+    let dir = dirs::cache_dir()?.join("tickit");
+    Some(dir.join(format!("holidays-{:016x}.ics", hasher.finish())))
+}
+
+/// `~/...` paths relative to the home directory
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Christmas\r\n\
+            DTSTART;VALUE=DATE:20261225\r\n\
+            DTEND;VALUE=DATE:20261227\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Offsite\r\n\
+            DTSTART;TZID=Europe/Lisbon:20261103T090000\r\n\
+            DTEND;TZID=Europe/Lisbon:20261104T170000\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:2026\r\n \
+            0101\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let dates: Vec<_> = parse_ics(ics).into_iter().collect();
+        assert_eq!(
+            dates,
+            [
+                date("2026-01-01"),
+                date("2026-11-03"),
+                date("2026-12-25"),
+                date("2026-12-26"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_business_days() {
+        let days = BusinessDays {
+            holidays: [date("2026-12-25")].into(),
+            skip_weekends: true,
+        };
+        // Thursday 24th: Christmas is Friday, then the weekend
+        assert_eq!(
+            days.add_business_days(date("2026-12-24"), 1),
+            Some(date("2026-12-28"))
+        );
+        assert_eq!(
+            days.add_business_days(date("2026-12-22"), 3),
+            Some(date("2026-12-28"))
+        );
+        assert!(!days.is_business_day(date("2026-12-26")));
+
+        let every_day = BusinessDays::default();
+        assert_eq!(
+            every_day.add_business_days(date("2026-12-25"), 1),
+            Some(date("2026-12-26"))
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! User commands run on task events, configured under `[hooks]`
+//!
+//! Each hook is a shell command. It gets the task (or, for
+//! `on_sync_finished`, a summary of the sync) as JSON on stdin and the hook
+//! name in `TICKIT_HOOK`. Hooks run in the background with their output
+//! discarded, and a failing hook never fails the operation that triggered it.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::config::HooksConfig;
+
+/// An event a hook can be attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    TaskCreated,
+    TaskCompleted,
+    SyncFinished,
+}
+
+impl Hook {
+    /// Key of the hook in config.toml
+    pub fn key(self) -> &'static str {
+        match self {
+            Hook::TaskCreated => "on_task_created",
+            Hook::TaskCompleted => "on_task_completed",
+            Hook::SyncFinished => "on_sync_finished",
+        }
+    }
+}
+
+/// Run the command configured for `hook`, if any, with `payload` on stdin
+pub fn run(config: &HooksConfig, hook: Hook, payload: &impl Serialize) {
+    let Some(command) = config.command(hook) else {
+        return;
+    };
+    let result = serde_json::to_vec(payload)
+        .map_err(std::io::Error::from)
+        .and_then(|input| spawn(command, hook, &input));
+    if let Err(e) = result {
+        tracing::warn!("{} hook `{}` failed: {}", hook.key(), command, e);
+    }
+}
+
+fn spawn(command: &str, hook: Hook, input: &[u8]) -> std::io::Result<()> {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let mut child = cmd
+        .env("TICKIT_HOOK", hook.key())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes the pipe early; that's fine
+        let _ = stdin.write_all(input);
+    }
+    // Reap the process without making the caller wait for it
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_run_pipes_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.json");
+        let config = HooksConfig {
+            on_task_completed: Some(format!(
+                "printf '%s ' \"$TICKIT_HOOK\" > '{0}'; cat >> '{0}'",
+                out.display()
+            )),
+            ..HooksConfig::default()
+        };
+
+        // Not configured: nothing runs
+        run(&config, Hook::TaskCreated, &serde_json::json!({}));
+        run(
+            &config,
+            Hook::TaskCompleted,
+            &serde_json::json!({"title": "Done"}),
+        );
+
+        let expected = "on_task_completed {\"title\":\"Done\"}";
+        for _ in 0..100 {
+            if std::fs::read_to_string(&out).is_ok_and(|s| s == expected) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        panic!("hook output: {:?}", std::fs::read_to_string(&out));
+    }
+}
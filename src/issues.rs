@@ -0,0 +1,519 @@
+//! GitHub, GitLab and Jira issues linked to tasks through their URL
+//!
+//! A task whose `url` points at an issue is linked to it: `tickit link
+//! refresh` copies the issue's title and open/closed state onto the task, and
+//! with `links.close_issues` completing the task closes the issue. Jira
+//! issues assigned to the user can also be imported with [`search_jira`].
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+use crate::config::LinksConfig;
+use crate::models::Priority;
+
+/// Issues `tickit link import jira` picks when no JQL is given
+pub const DEFAULT_JIRA_JQL: &str =
+    "assignee = currentUser() AND statusCategory != Done ORDER BY duedate";
+
+/// Where a linked issue lives
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Provider {
+    GitHub,
+    /// GitLab on this host
+    GitLab(String),
+    /// Jira on this host
+    Jira(String),
+}
+
+/// An issue a task URL points at
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IssueRef {
+    pub provider: Provider,
+    /// `owner/repo` on GitHub, the full project path on GitLab, the project
+    /// key on Jira
+    pub project: String,
+    pub number: u64,
+}
+
+/// What an issue looks like upstream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueState {
+    pub title: String,
+    pub open: bool,
+}
+
+impl IssueRef {
+    /// The issue `url` points at, if it's a GitHub, GitLab or Jira issue
+    pub fn parse(url: &str, config: &LinksConfig) -> Option<Self> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let rest = rest.split(['?', '#']).next()?.trim_end_matches('/');
+        let (host, path) = rest.split_once('/')?;
+        let host = host.to_ascii_lowercase();
+
+        if host == "github.com" || host == "www.github.com" {
+            // Pull requests share issue numbers and endpoints, so closing
+            // a linked one would close the pull request: they aren't issues
+            let parts: Vec<&str> = path.split('/').collect();
+            return match parts.as_slice() {
+                [owner, repo, "issues", number] => Some(Self {
+                    provider: Provider::GitHub,
+                    project: format!("{}/{}", owner, repo),
+                    number: number.parse().ok()?,
+                }),
+                _ => None,
+            };
+        }
+
+        let gitlab = host == "gitlab.com"
+            || config
+                .gitlab_host
+                .as_deref()
+                .is_some_and(|h| h.eq_ignore_ascii_case(&host));
+        if gitlab {
+            let (project, issue) = path.split_once("/-/")?;
+            let number = issue.strip_prefix("issues/")?.parse().ok()?;
+            return Some(Self {
+                provider: Provider::GitLab(host),
+                project: project.to_string(),
+                number,
+            });
+        }
+
+        let jira = config
+            .jira_host
+            .as_deref()
+            .is_some_and(|h| h.eq_ignore_ascii_case(&host));
+        if jira {
+            let (project, number) = path.strip_prefix("browse/")?.rsplit_once('-')?;
+            if project.is_empty() || project.contains('/') {
+                return None;
+            }
+            return Some(Self {
+                provider: Provider::Jira(host),
+                project: project.to_ascii_uppercase(),
+                number: number.parse().ok()?,
+            });
+        }
+        None
+    }
+
+    /// The issue's page in the browser
+    pub fn web_url(&self) -> String {
+        match &self.provider {
+            Provider::GitHub => {
+                format!("https://github.com/{}/issues/{}", self.project, self.number)
+            }
+            Provider::GitLab(host) => {
+                format!("https://{}/{}/-/issues/{}", host, self.project, self.number)
+            }
+            Provider::Jira(host) => format!("https://{}/browse/{}", host, self),
+        }
+    }
+
+    /// Fetch the issue's title and state
+    pub fn fetch(&self, config: &LinksConfig) -> Result<IssueState> {
+        let body = self.request("GET", &self.api_url(), config, None)?;
+        if let Provider::Jira(_) = self.provider {
+            let fields = &body["fields"];
+            let summary = fields["summary"].as_str().context("Issue has no summary")?;
+            return Ok(IssueState {
+                title: jira_title(&self.to_string(), summary),
+                open: fields["status"]["statusCategory"]["key"].as_str() != Some("done"),
+            });
+        }
+        let title = body["title"]
+            .as_str()
+            .context("Issue has no title")?
+            .to_string();
+        let open = match body["state"].as_str() {
+            Some("open" | "opened") => true,
+            Some("closed") => false,
+            other => anyhow::bail!("Unknown issue state: {:?}", other),
+        };
+        Ok(IssueState { title, open })
+    }
+
+    /// Close the issue
+    pub fn close(&self, config: &LinksConfig) -> Result<()> {
+        let (method, body) = match self.provider {
+            Provider::GitHub => ("PATCH", json!({ "state": "closed" })),
+            Provider::GitLab(_) => ("PUT", json!({ "state_event": "close" })),
+            Provider::Jira(_) => return self.close_jira(config),
+        };
+        self.request(method, &self.api_url(), config, Some(body))
+            .map(|_| ())
+    }
+
+    /// Jira has no "closed" state, only workflow transitions: take the first
+    /// one that ends in a done status
+    fn close_jira(&self, config: &LinksConfig) -> Result<()> {
+        let url = format!("{}/transitions", self.api_url());
+        let body = self.request("GET", &url, config, None)?;
+        let transition = body["transitions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|t| t["to"]["statusCategory"]["key"].as_str() == Some("done"))
+            .and_then(|t| t["id"].as_str())
+            .with_context(|| format!("{} has no transition to a done status", self))?;
+        let body = json!({ "transition": { "id": transition } });
+        self.request("POST", &url, config, Some(body)).map(|_| ())
+    }
+
+    /// API endpoint for the issue
+    fn api_url(&self) -> String {
+        match &self.provider {
+            Provider::GitHub => format!(
+                "https://api.github.com/repos/{}/issues/{}",
+                self.project, self.number
+            ),
+            Provider::GitLab(host) => format!(
+                "https://{}/api/v4/projects/{}/issues/{}",
+                host,
+                self.project.replace('/', "%2F"),
+                self.number
+            ),
+            Provider::Jira(host) => format!("https://{}/rest/api/2/issue/{}", host, self),
+        }
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        url: &str,
+        config: &LinksConfig,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let mut request = ureq::request(method, url)
+            .set("User-Agent", &format!("tickit/{}", crate::VERSION))
+            .timeout(Duration::from_secs(15));
+        match self.provider {
+            Provider::GitHub => {
+                request = request.set("Accept", "application/vnd.github+json");
+                if let Some(token) = config.github_token()? {
+                    request = request.set("Authorization", &format!("Bearer {}", token));
+                }
+            }
+            Provider::GitLab(_) => {
+                if let Some(token) = config.gitlab_token()? {
+                    request = request.set("PRIVATE-TOKEN", &token);
+                }
+            }
+            Provider::Jira(_) => request = jira_auth(request, config)?,
+        }
+        send(request, body, self)
+    }
+}
+
+/// A Jira issue found by [`search_jira`], ready to become a task
+#[derive(Debug, Clone, PartialEq)]
+pub struct JiraIssue {
+    pub issue: IssueRef,
+    /// `[KEY-1] Summary`, as `tickit link refresh` would set it
+    pub title: String,
+    pub priority: Priority,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+impl JiraIssue {
+    fn from_json(host: &str, value: &Value) -> Option<Self> {
+        let key = value["key"].as_str()?;
+        let (project, number) = key.rsplit_once('-')?;
+        let fields = &value["fields"];
+        Some(Self {
+            issue: IssueRef {
+                provider: Provider::Jira(host.to_string()),
+                project: project.to_string(),
+                number: number.parse().ok()?,
+            },
+            title: jira_title(key, fields["summary"].as_str().unwrap_or_default()),
+            priority: jira_priority(fields["priority"]["name"].as_str().unwrap_or_default()),
+            due_date: fields["duedate"]
+                .as_str()
+                .and_then(crate::quickadd::parse_due_date),
+        })
+    }
+}
+
+/// The Jira issues matching `jql` on `links.jira_host`, following every page
+pub fn search_jira(config: &LinksConfig, jql: &str) -> Result<Vec<JiraIssue>> {
+    let host = config
+        .jira_host
+        .as_deref()
+        .context("Set links.jira_host to import from Jira")?
+        .to_ascii_lowercase();
+    // Jira Cloud retired offset paging on /search for a token-based endpoint
+    let cloud = host.ends_with(".atlassian.net");
+    let url = if cloud {
+        format!("https://{}/rest/api/2/search/jql", host)
+    } else {
+        format!("https://{}/rest/api/2/search", host)
+    };
+    let what = format!("Jira at {}", host);
+    let query = json!({
+        "jql": jql,
+        "fields": ["summary", "priority", "duedate"],
+        "maxResults": 100,
+    });
+    jira_pages(&host, cloud, query, |body| {
+        let request = ureq::post(&url)
+            .set("User-Agent", &format!("tickit/{}", crate::VERSION))
+            .timeout(Duration::from_secs(30));
+        send(jira_auth(request, config)?, Some(body), &what)
+    })
+}
+
+/// Fetch every page of a Jira search with `fetch`, by page token on Jira
+/// Cloud and by offset elsewhere
+///
+/// The offset counts the issues each page returned, not the ones that
+/// parsed, so an issue [`JiraIssue::from_json`] skips can't stall paging.
+fn jira_pages(
+    host: &str,
+    cloud: bool,
+    query: Value,
+    mut fetch: impl FnMut(Value) -> Result<Value>,
+) -> Result<Vec<JiraIssue>> {
+    let mut issues = Vec::new();
+    let mut page_token: Option<String> = None;
+    let mut start_at = 0;
+
+    loop {
+        let mut body = query.clone();
+        if cloud {
+            if let Some(token) = &page_token {
+                body["nextPageToken"] = json!(token);
+            }
+        } else {
+            body["startAt"] = json!(start_at);
+        }
+        let page = fetch(body)?;
+
+        let found = page["issues"].as_array().cloned().unwrap_or_default();
+        let count = found.len();
+        issues.extend(found.iter().filter_map(|v| JiraIssue::from_json(host, v)));
+
+        let done = if cloud {
+            page_token = page["nextPageToken"].as_str().map(str::to_string);
+            page_token.is_none() || page["isLast"].as_bool().unwrap_or(false)
+        } else {
+            start_at += count;
+            start_at >= page["total"].as_u64().unwrap_or(0) as usize
+        };
+        if done || count == 0 {
+            return Ok(issues);
+        }
+    }
+}
+
+/// Jira priority names, including the older Blocker..Trivial scheme
+pub fn jira_priority(name: &str) -> Priority {
+    match name.to_ascii_lowercase().as_str() {
+        "highest" | "blocker" => Priority::Urgent,
+        "high" | "critical" | "major" => Priority::High,
+        "low" | "lowest" | "minor" | "trivial" => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// Task title for a Jira issue, keeping the key visible
+fn jira_title(key: &str, summary: &str) -> String {
+    format!("[{}] {}", key, summary.trim())
+}
+
+/// Basic auth when `jira_email` is set (Jira Cloud), a bearer token otherwise
+fn jira_auth(request: ureq::Request, config: &LinksConfig) -> Result<ureq::Request> {
+    let Some(token) = config.jira_token()? else {
+        return Ok(request);
+    };
+    let header = match &config.jira_email {
+        Some(email) => format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", email, token))
+        ),
+        None => format!("Bearer {}", token),
+    };
+    Ok(request.set("Authorization", &header))
+}
+
+/// Send `request`, turning HTTP errors into the API's own message
+fn send(
+    request: ureq::Request,
+    body: Option<Value>,
+    what: &dyn std::fmt::Display,
+) -> Result<Value> {
+    let response = match body {
+        Some(body) => request.send_json(body),
+        None => request.call(),
+    };
+    match response {
+        Ok(response) => {
+            let text = response
+                .into_string()
+                .context("Invalid response from issue API")?;
+            if text.trim().is_empty() {
+                // Jira answers transitions with 204 No Content
+                return Ok(Value::Null);
+            }
+            serde_json::from_str(&text).context("Invalid response from issue API")
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response
+                .into_json::<Value>()
+                .ok()
+                .and_then(|v| {
+                    v["message"].as_str().map(str::to_string).or_else(|| {
+                        // Jira lists its errors instead
+                        v["errorMessages"][0].as_str().map(str::to_string)
+                    })
+                })
+                .unwrap_or_default();
+            anyhow::bail!("{} returned {}: {}", what, status, message)
+        }
+        Err(e) => anyhow::bail!("Could not reach {}: {}", what, e),
+    }
+}
+
+impl std::fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.provider {
+            Provider::GitHub | Provider::GitLab(_) => {
+                write!(f, "{}#{}", self.project, self.number)
+            }
+            Provider::Jira(_) => write!(f, "{}-{}", self.project, self.number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = LinksConfig {
+            gitlab_host: Some("git.example.com".to_string()),
+            ..LinksConfig::default()
+        };
+
+        let issue = IssueRef::parse("https://github.com/rust-lang/rust/issues/42#top", &config);
+        assert_eq!(
+            issue,
+            Some(IssueRef {
+                provider: Provider::GitHub,
+                project: "rust-lang/rust".to_string(),
+                number: 42,
+            })
+        );
+        assert_eq!(
+            issue.unwrap().api_url(),
+            "https://api.github.com/repos/rust-lang/rust/issues/42"
+        );
+
+        let issue =
+            IssueRef::parse("https://git.example.com/group/sub/app/-/issues/7", &config).unwrap();
+        assert_eq!(
+            issue.provider,
+            Provider::GitLab("git.example.com".to_string())
+        );
+        assert_eq!(
+            issue.api_url(),
+            "https://git.example.com/api/v4/projects/group%2Fsub%2Fapp/issues/7"
+        );
+
+        assert_eq!(issue.to_string(), "group/sub/app#7");
+
+        assert!(IssueRef::parse("https://github.com/rust-lang/rust", &config).is_none());
+        assert!(IssueRef::parse("https://github.com/rust-lang/rust/pull/42", &config).is_none());
+        assert!(
+            IssueRef::parse(
+                "https://git.example.com/group/app/-/merge_requests/3",
+                &config
+            )
+            .is_none()
+        );
+        assert!(IssueRef::parse("https://gitlab.example.org/a/-/issues/1", &config).is_none());
+        assert!(IssueRef::parse("https://example.com/a/b/issues/1", &config).is_none());
+    }
+
+    #[test]
+    fn test_parse_jira() {
+        let config = LinksConfig {
+            jira_host: Some("acme.atlassian.net".to_string()),
+            ..LinksConfig::default()
+        };
+
+        let issue = IssueRef::parse(
+            "https://acme.atlassian.net/browse/OPS-12?focused=1",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            issue.provider,
+            Provider::Jira("acme.atlassian.net".to_string())
+        );
+        assert_eq!(issue.to_string(), "OPS-12");
+        assert_eq!(issue.web_url(), "https://acme.atlassian.net/browse/OPS-12");
+        assert_eq!(
+            issue.api_url(),
+            "https://acme.atlassian.net/rest/api/2/issue/OPS-12"
+        );
+
+        assert!(IssueRef::parse("https://acme.atlassian.net/browse/OPS", &config).is_none());
+        assert!(IssueRef::parse("https://other.atlassian.net/browse/OPS-1", &config).is_none());
+    }
+
+    #[test]
+    fn test_jira_issue() {
+        let value = json!({
+            "key": "OPS-7",
+            "fields": {
+                "summary": " Rotate certificates ",
+                "priority": { "name": "Highest" },
+                "duedate": "2026-03-01",
+            },
+        });
+        let issue = JiraIssue::from_json("acme.atlassian.net", &value).unwrap();
+        assert_eq!(issue.title, "[OPS-7] Rotate certificates");
+        assert_eq!(issue.priority, Priority::Urgent);
+        assert_eq!(
+            issue.due_date.map(|d| d.date_naive().to_string()),
+            Some("2026-03-01".to_string())
+        );
+        assert_eq!(
+            issue.issue.web_url(),
+            "https://acme.atlassian.net/browse/OPS-7"
+        );
+
+        assert_eq!(jira_priority("Minor"), Priority::Low);
+        assert_eq!(jira_priority("Critical"), Priority::High);
+        assert_eq!(jira_priority("Custom"), Priority::Medium);
+    }
+
+    #[test]
+    fn test_jira_pages() {
+        // Jira Server, two issues a page; the first page has an issue
+        // without a key that can't become a task
+        let pages = [
+            json!({ "total": 3, "issues": [{ "key": "OPS-1" }, { "fields": {} }] }),
+            json!({ "total": 3, "issues": [{ "key": "OPS-3" }] }),
+        ];
+        let mut offsets = Vec::new();
+        let issues = jira_pages("jira.example.com", false, json!({}), |body| {
+            let start_at = body["startAt"].as_u64().unwrap() as usize;
+            offsets.push(start_at);
+            anyhow::ensure!(offsets.len() <= pages.len(), "Paging did not stop");
+            Ok(pages[start_at / 2].clone())
+        })
+        .unwrap();
+        assert_eq!(offsets, [0, 2]);
+        let keys: Vec<String> = issues.iter().map(|i| i.issue.to_string()).collect();
+        assert_eq!(keys, ["OPS-1", "OPS-3"]);
+    }
+}
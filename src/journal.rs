@@ -0,0 +1,121 @@
+//! Undo journal for CLI commands
+//!
+//! Commands that change tasks save what those tasks looked like beforehand
+//! (and which ones they created), so `tickit undo` can put things back. Only
+//! the last operation is kept, like a shell's single-level undo.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::Task;
+
+/// One undoable operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// What was done, e.g. "delete 3 tasks"
+    pub label: String,
+    /// When it was done
+    pub at: DateTime<Utc>,
+    /// Changed or deleted tasks, as they were before
+    #[serde(default)]
+    pub before: Vec<Task>,
+    /// Tasks the operation created
+    #[serde(default)]
+    pub created: Vec<Uuid>,
+}
+
+/// Save `before` and `created` as the operation to undo next
+///
+/// An operation that touched no tasks leaves the previous entry in place.
+pub fn record(
+    db: &Database,
+    label: impl Into<String>,
+    before: &[Task],
+    created: &[Uuid],
+) -> Result<()> {
+    if before.is_empty() && created.is_empty() {
+        return Ok(());
+    }
+    let entry = Entry {
+        label: label.into(),
+        at: Utc::now(),
+        before: before.to_vec(),
+        created: created.to_vec(),
+    };
+    db.save_journal(&serde_json::to_string(&entry)?)
+}
+
+/// Revert the last recorded operation, returning it (or `None` if there was none)
+pub fn undo(db: &Database) -> Result<Option<Entry>> {
+    let Some(saved) = db.take_journal()? else {
+        return Ok(None);
+    };
+    let entry: Entry = serde_json::from_str(&saved)?;
+
+    for task in &entry.before {
+        if db.get_task(task.id).is_ok() {
+            db.update_task(task)?;
+        } else {
+            // Deleted by the operation: bring it back and stop sync deleting it
+            db.insert_task(task)?;
+            db.remove_tombstone(task.id)?;
+        }
+    }
+    db.delete_tasks(&entry.created)?;
+
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_undo_restores_tasks() {
+        let dir = tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.sqlite")).unwrap();
+        let inbox = db.get_inbox().unwrap();
+
+        let kept = Task::new("Kept", inbox.id);
+        let deleted = Task::new("Deleted", inbox.id);
+        db.insert_task(&kept).unwrap();
+        db.insert_task(&deleted).unwrap();
+
+        let mut done = kept.clone();
+        done.complete();
+        record(
+            &db,
+            "done and delete",
+            &[kept.clone(), deleted.clone()],
+            &[],
+        )
+        .unwrap();
+        db.update_task(&done).unwrap();
+        db.delete_tasks(&[deleted.id]).unwrap();
+
+        let entry = undo(&db).unwrap().unwrap();
+        assert_eq!(entry.label, "done and delete");
+        assert!(!db.get_task(kept.id).unwrap().completed);
+        assert_eq!(db.get_task(deleted.id).unwrap().title, "Deleted");
+        assert!(db.get_all_tombstones().unwrap().is_empty());
+        assert!(undo(&db).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_removes_created_tasks() {
+        let dir = tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.sqlite")).unwrap();
+        let inbox = db.get_inbox().unwrap();
+
+        let added = Task::new("Added", inbox.id);
+        db.insert_task(&added).unwrap();
+        record(&db, "add", &[], &[added.id]).unwrap();
+
+        undo(&db).unwrap();
+        assert!(db.get_task(added.id).is_err());
+    }
+}
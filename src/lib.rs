@@ -3,6 +3,7 @@
 //! Features:
 //! - Beautiful TUI with multiple themes
 //! - CLI for quick task management
+//! - [`TickitCore`] for using tickit as a library
 //! - Lists and tags for organization
 //! - SQLite storage
 //! - Optional sync with self-hosted server
@@ -11,20 +12,45 @@
 #![allow(clippy::enum_variant_names)]
 #![allow(clippy::single_match)]
 pub mod app;
+pub mod autolink;
 pub mod config;
+pub mod core;
 pub mod db;
+pub mod deps;
+pub mod diff;
 pub mod export;
+pub mod fuzzy;
+pub mod health;
+pub mod holidays;
+pub mod hooks;
+pub mod issues;
+pub mod journal;
+pub mod logging;
 pub mod models;
 pub mod notifications;
+pub mod quickadd;
+#[cfg(feature = "debug-tools")]
+pub mod seed;
+pub mod serve;
+pub mod stats;
 pub mod sync;
 pub mod theme;
 
-pub use config::{Config, SyncConfig};
+pub use config::{
+    CalendarConfig, ColorDepth, Config, ConfigIssue, ConfirmConfig, ConflictPolicy, Density,
+    DisplayConfig, HooksConfig, LinksConfig, NotificationRule, QuietHours, ServeConfig,
+    StatusSegment, SyncConfig, UpdateCheck, UpdatesConfig,
+};
+pub use core::{LookupError, TickitCore};
 pub use db::Database;
-pub use models::{ExportFormat, List, Priority, Tag, Task};
+pub use models::{
+    Attachment, Comment, DeletedListTasks, ExportFormat, List, Priority, Tag, Task, TaskSort,
+};
 pub use sync::{SyncClient, SyncRecord, SyncRequest, SyncResponse, SyncStatus};
 pub use theme::Theme;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 /// Current version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -162,16 +188,20 @@ pub fn detect_package_manager() -> PackageManager {
 
 /// Run the update command and return the result
 pub fn run_update(pm: &PackageManager) -> Result<(), String> {
-    use std::process::Stdio;
+    run_update_with_progress(pm, |_| {}, &AtomicBool::new(false))
+}
 
+/// Run the update command, reporting each line of output as it arrives
+///
+/// The command is killed and an error returned as soon as `cancel` is set.
+pub fn run_update_with_progress(
+    pm: &PackageManager,
+    mut progress: impl FnMut(&str),
+    cancel: &AtomicBool,
+) -> Result<(), String> {
     match pm {
         PackageManager::Cargo => {
-            match std::process::Command::new("cargo")
-                .args(["install", "tickit"])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-            {
+            match run_streaming("cargo", &["install", "tickit"], &mut progress, cancel) {
                 Ok(status) if status.success() => Ok(()),
                 Ok(status) => Err(format!("Update failed with status: {}", status)),
                 Err(e) => Err(format!("Failed to run cargo: {}", e)),
@@ -179,28 +209,14 @@ pub fn run_update(pm: &PackageManager) -> Result<(), String> {
         }
         PackageManager::Homebrew { formula } => {
             // First update the tap to get latest formula
-            let _ = std::process::Command::new("brew")
-                .args(["update"])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status();
+            let _ = run_streaming("brew", &["update"], &mut progress, cancel);
 
             // Then upgrade the formula
-            match std::process::Command::new("brew")
-                .args(["upgrade", formula])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-            {
+            match run_streaming("brew", &["upgrade", formula], &mut progress, cancel) {
                 Ok(status) if status.success() => Ok(()),
                 Ok(_) => {
                     // upgrade returns non-zero if already up to date, try reinstall
-                    match std::process::Command::new("brew")
-                        .args(["reinstall", formula])
-                        .stdout(Stdio::null())
-                        .stderr(Stdio::null())
-                        .status()
-                    {
+                    match run_streaming("brew", &["reinstall", formula], &mut progress, cancel) {
                         Ok(status) if status.success() => Ok(()),
                         Ok(status) => Err(format!("Update failed with status: {}", status)),
                         Err(e) => Err(format!("Failed to run brew: {}", e)),
@@ -211,3 +227,60 @@ pub fn run_update(pm: &PackageManager) -> Result<(), String> {
         }
     }
 }
+
+/// Run a command, forwarding its stdout and stderr lines to `progress`
+fn run_streaming(
+    program: &str,
+    args: &[&str],
+    progress: &mut impl FnMut(&str),
+    cancel: &AtomicBool,
+) -> std::io::Result<std::process::ExitStatus> {
+    use std::io::{BufRead, BufReader, Read};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+
+    if cancel.load(Ordering::Relaxed) {
+        return Err(std::io::Error::other("cancelled"));
+    }
+
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    fn forward(reader: impl Read + Send + 'static, tx: mpsc::Sender<String>) {
+        std::thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(stderr, tx);
+    }
+
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::other("cancelled"));
+        }
+        match rx.recv_timeout(std::time::Duration::from_millis(100)) {
+            Ok(line) if !line.trim().is_empty() => progress(line.trim()),
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // Both pipes closed
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    child.wait()
+}
@@ -0,0 +1,146 @@
+//! Log file setup
+//!
+//! Logs go to `tickit.log` in the state directory (`~/.local/state/tickit` on
+//! Linux) rather than the terminal, where they would garble the TUI. Warnings
+//! are always kept; `--verbose` adds debug output and span timings, and
+//! `RUST_LOG` overrides both. The file is rotated when it grows past
+//! [`MAX_LOG_SIZE`], keeping [`KEEP_LOGS`] old copies.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Size at which the log is rotated on startup
+pub const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Rotated logs kept as `tickit.log.1` (newest) to `tickit.log.N`
+pub const KEEP_LOGS: usize = 3;
+
+/// Where the log file lives
+pub fn log_path() -> Result<PathBuf> {
+    let dir = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .context("Could not determine state directory")?;
+    Ok(dir.join("tickit").join("tickit.log"))
+}
+
+/// Send tracing output to the log file; `verbose` logs debug output for tickit
+pub fn init(verbose: bool) -> Result<()> {
+    let path = log_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    rotate(&path, MAX_LOG_SIZE, KEEP_LOGS)?;
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    let default = if verbose { "tickit=debug" } else { "warn" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    let spans = if verbose {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(spans)
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Shift `path` to `path.1`, `path.1` to `path.2` and so on once it exceeds
+/// `max_size`, dropping the oldest
+fn rotate(path: &Path, max_size: u64, keep: usize) -> Result<()> {
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size <= max_size {
+        return Ok(());
+    }
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    let _ = std::fs::remove_file(rotated(keep));
+    for n in (1..keep).rev() {
+        let _ = std::fs::rename(rotated(n), rotated(n + 1));
+    }
+    if keep == 0 {
+        std::fs::remove_file(path)?;
+    } else {
+        std::fs::rename(path, rotated(1))?;
+    }
+    Ok(())
+}
+
+/// The last `count` lines of the log
+pub fn tail(path: &Path, count: usize) -> Result<Vec<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut lines = std::collections::VecDeque::with_capacity(count);
+    for line in BufReader::new(file).lines() {
+        if lines.len() == count {
+            lines.pop_front();
+        }
+        lines.push_back(line?);
+    }
+    Ok(lines.into())
+}
+
+/// Print lines appended to the log until interrupted, starting from its end
+pub fn follow(path: &Path, out: &mut impl Write) -> Result<()> {
+    let mut position = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    loop {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if size < position {
+            // Rotated or truncated: start over on the new file
+            position = 0;
+        }
+        if size > position {
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(position))?;
+            let mut chunk = Vec::new();
+            file.take(size - position).read_to_end(&mut chunk)?;
+            out.write_all(&chunk)?;
+            out.flush()?;
+            position = size;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_and_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickit.log");
+        let rotated = |n: usize| dir.path().join(format!("tickit.log.{}", n));
+
+        // Small enough: left alone
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        rotate(&path, 100, 2).unwrap();
+        assert_eq!(tail(&path, 2).unwrap(), ["two", "three"]);
+
+        rotate(&path, 4, 2).unwrap();
+        assert!(!path.exists());
+        assert!(rotated(1).exists());
+        assert!(tail(&path, 10).unwrap().is_empty());
+
+        // Older copies shift up and the oldest is dropped
+        std::fs::write(&path, "second\n").unwrap();
+        rotate(&path, 4, 2).unwrap();
+        std::fs::write(&path, "third\n").unwrap();
+        rotate(&path, 4, 2).unwrap();
+        assert_eq!(tail(&rotated(1), 1).unwrap(), ["third"]);
+        assert_eq!(tail(&rotated(2), 1).unwrap(), ["second"]);
+        assert!(!rotated(3).exists());
+    }
+}
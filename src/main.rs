@@ -2,20 +2,44 @@
 //!
 //! Terminal-based task manager with beautiful TUI and CLI modes.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use crossterm::style::{Color, Stylize};
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use tickit::{Database, ExportFormat, List, Priority, Tag, Task};
+use tickit::core::{NewTask, TaskChanges, TaskQuery};
+use tickit::export::{Decryption, Encryption};
+use tickit::{
+    Attachment, Database, DeletedListTasks, ExportFormat, List, LookupError, Priority, Tag, Task,
+    TickitCore, notifications, quickadd,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "tickit")]
 #[command(author, version, about = "A stunning terminal-based task manager")]
 struct Cli {
-    /// Enable verbose logging
+    /// Write debug output to the log file (see `tickit logs`)
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Print results, and errors, as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Never prompt; fail instead of asking (implied when stdin isn't a terminal)
+    #[arg(long, global = true)]
+    no_input: bool,
+
+    /// Use a throwaway in-memory database; your saved tasks aren't read or changed
+    #[arg(long, global = true)]
+    ephemeral: bool,
+
+    /// Use this workspace's database instead of the default one
+    #[arg(short = 'W', long, global = true, value_name = "NAME")]
+    workspace: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -23,12 +47,51 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Start the TUI (default)
-    Ui,
+    #[command(visible_alias = "tui")]
+    Ui {
+        /// Open this list
+        #[arg(short, long)]
+        list: Option<String>,
+
+        /// Filter tasks by this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Open a view or smart list
+        #[arg(long, value_parser = [
+            "tasks", "lists", "tags", "today", "upcoming", "overdue", "someday", "no-date",
+        ])]
+        view: Option<String>,
+
+        /// Select this task (ID or title)
+        #[arg(long)]
+        task: Option<String>,
+    },
+
+    /// Add a task to the Inbox as fast as possible (for a global hotkey)
+    Capture {
+        /// Task title, taken as typed
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+    },
 
     /// Add a new task
     Add {
         /// Task title
-        title: String,
+        #[arg(required_unless_present_any = ["interactive", "stdin", "from_file"])]
+        title: Option<String>,
+
+        /// Prompt for each field instead
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Add one task per line of standard input (quick add syntax per line)
+        #[arg(long, conflicts_with_all = ["title", "interactive", "from_file"])]
+        stdin: bool,
+
+        /// Add one task per line of a file (quick add syntax per line)
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["title", "interactive"])]
+        from_file: Option<PathBuf>,
 
         /// Task description
         #[arg(short, long)]
@@ -38,9 +101,9 @@ enum Commands {
         #[arg(short, long)]
         url: Option<String>,
 
-        /// Priority (low, medium, high, urgent)
-        #[arg(short, long, default_value = "medium")]
-        priority: String,
+        /// Priority (low, medium, high, urgent; default medium)
+        #[arg(short, long)]
+        priority: Option<String>,
 
         /// List name to add task to
         #[arg(short, long)]
@@ -50,9 +113,42 @@ enum Commands {
         #[arg(short, long)]
         tags: Option<String>,
 
-        /// Due date (YYYY-MM-DD format)
+        /// Due date (YYYY-MM-DD, today, tomorrow, a weekday, 3d, 2w)
         #[arg(long)]
         due: Option<String>,
+
+        /// Create the list and tags if they don't exist, instead of failing
+        #[arg(long)]
+        create_missing: bool,
+
+        /// Use the list, tags and priority of this task (ID or title); flags override
+        #[arg(long, value_name = "TASK", conflicts_with_all = ["stdin", "from_file"])]
+        like: Option<String>,
+    },
+
+    /// Copy a task, open and with the same details, tags and due date
+    #[command(alias = "dup")]
+    Duplicate {
+        /// Task ID or title (partial match)
+        task: String,
+
+        /// Put the copy in this list instead (or a unique prefix of its name)
+        #[arg(short, long)]
+        list: Option<String>,
+    },
+
+    /// Fold a near-duplicate into a task: descriptions, tags, comments and the
+    /// earliest due date end up on the first task, and the second is deleted
+    Merge {
+        /// Task to keep: ID or title (partial match)
+        task: String,
+
+        /// Task to merge into it and delete
+        other: String,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// List tasks
@@ -69,33 +165,264 @@ enum Commands {
         /// Filter by tag
         #[arg(short, long)]
         tag: Option<String>,
+    },
+
+    /// Search task titles and descriptions
+    Search {
+        /// Text to look for (case-insensitive)
+        query: String,
+
+        /// Only tasks in this list
+        #[arg(short, long)]
+        list: Option<String>,
+
+        /// Only tasks with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Only tasks with this priority (low, medium, high, urgent)
+        #[arg(short, long)]
+        priority: Option<String>,
 
-        /// Output as JSON
+        /// Only tasks due before this date (YYYY-MM-DD, today, tomorrow, 3d, ...)
         #[arg(long)]
-        json: bool,
+        due_before: Option<String>,
+
+        /// Include completed tasks
+        #[arg(short, long)]
+        all: bool,
     },
 
-    /// Mark task as complete
-    Done {
+    /// Today's agenda: overdue tasks and tasks due today, by list
+    Today,
+
+    /// One-line summary of overdue and due tasks for a status bar or prompt
+    Status {
+        /// waybar (JSON with text, tooltip and class), tmux (with colors) or plain text
+        #[arg(long, default_value = "plain", value_parser = ["waybar", "tmux", "plain"])]
+        format: String,
+    },
+
+    /// Open tasks due in the next few days, by day
+    Upcoming {
+        /// How many days ahead to look
+        #[arg(short, long, default_value_t = 7)]
+        days: u32,
+    },
+
+    /// Completed and open task counts and completion streaks
+    Stats {
+        /// Also show a year of completions as a heatmap, a column per week
+        #[arg(long)]
+        heatmap: bool,
+
+        /// Also show open and done tasks for each of the last few days
+        #[arg(long)]
+        burndown: bool,
+
+        /// Burndown of this list only
+        #[arg(short, long, requires = "burndown", conflicts_with = "tag")]
+        list: Option<String>,
+
+        /// Burndown of tasks with this tag only
+        #[arg(short, long, requires = "burndown")]
+        tag: Option<String>,
+
+        /// Days the burndown covers, ending today
+        #[arg(long, requires = "burndown", default_value_t = tickit::stats::BURNDOWN_DAYS)]
+        days: usize,
+    },
+
+    /// Show every detail of a task
+    Show {
         /// Task ID or title (partial match)
         task: String,
     },
 
-    /// Mark task as not complete
-    Undo {
+    /// Add a timestamped comment to a task, or delete one
+    Comment {
+        /// Task ID or title (partial match)
+        task: String,
+
+        /// Comment text
+        #[arg(required_unless_present = "delete", conflicts_with = "delete")]
+        text: Vec<String>,
+
+        /// Delete the comment with this number, as listed by `tickit show`
+        #[arg(long, value_name = "N")]
+        delete: Option<usize>,
+    },
+
+    /// Attach a file to a task, list its attachments, or remove one
+    Attach {
         /// Task ID or title (partial match)
         task: String,
+
+        /// File to attach; without one, the task's attachments are listed
+        #[arg(conflicts_with = "delete")]
+        file: Option<PathBuf>,
+
+        /// Remove the attachment with this number, as listed
+        #[arg(long, value_name = "N")]
+        delete: Option<usize>,
+    },
+
+    /// Mark task as complete
+    Done {
+        #[command(flatten)]
+        selection: TaskSelection,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// List the tasks that would be completed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Mark task as not complete, or with no task given, undo the last command
+    Undo {
+        #[command(flatten)]
+        selection: TaskSelection,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// List the tasks that would be reopened without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Delete a task
     #[command(alias = "rm")]
     Delete {
+        #[command(flatten)]
+        selection: TaskSelection,
+
+        /// Skip confirmation
+        #[arg(short = 'y', long = "yes", visible_alias = "force", short_alias = 'f')]
+        yes: bool,
+
+        /// List the tasks that would be deleted without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Archive completed tasks, hiding them everywhere without deleting them
+    Archive {
+        /// Archive completed tasks
+        #[arg(long, required = true)]
+        completed: bool,
+
+        /// Only tasks completed longer ago than this (e.g. 30d, 6w, 3m, 1y)
+        #[arg(long, value_parser = tickit::config::parse_age)]
+        older_than: Option<chrono::Duration>,
+
+        /// List the tasks that would be archived without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Permanently delete archived tasks
+    Purge {
+        /// Purge archived tasks
+        #[arg(long, required = true)]
+        archived: bool,
+
+        /// Only tasks archived longer ago than this (e.g. 30d, 6w, 3m, 1y)
+        #[arg(long, value_parser = tickit::config::parse_age)]
+        older_than: Option<chrono::Duration>,
+
+        /// List the tasks that would be purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Archive and purge old tasks as set under [retention] in config.toml
+    Maintain {
+        /// List what would be archived and purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move a task to another list
+    #[command(alias = "mv")]
+    Move {
+        /// Task ID or title (partial match)
+        task: String,
+
+        /// List name (or a unique prefix of it)
+        list: String,
+    },
+
+    /// Push a task's due date: `tickit defer report monday`, `+1w` (from
+    /// its due date), `tonight`, `next week`...
+    Defer {
+        /// Task ID or title (partial match)
+        task: String,
+
+        /// New due date; a leading `+` pushes the current one
+        #[arg(num_args = 1.., required = true)]
+        when: Vec<String>,
+    },
+
+    /// Add or remove tags on a task: `tickit tag report +work -old`
+    Tag {
         /// Task ID or title (partial match)
         task: String,
 
+        /// `+name` (or just `name`) adds a tag, `-name` removes it
+        #[arg(required = true, allow_hyphen_values = true)]
+        changes: Vec<String>,
+
+        /// Create tags that don't exist yet
+        #[arg(long)]
+        create: bool,
+    },
+
+    /// Change priority, list, due date or tags of one or more tasks
+    Edit {
+        #[command(flatten)]
+        selection: TaskSelection,
+
+        /// New priority (low, medium, high, urgent)
+        #[arg(long)]
+        set_priority: Option<String>,
+
+        /// Move to this list
+        #[arg(long)]
+        set_list: Option<String>,
+
+        /// New due date (YYYY-MM-DD, today, tomorrow, 3d, ...) or "none"
+        #[arg(long)]
+        set_due: Option<String>,
+
+        /// Tag to add
+        #[arg(long)]
+        add_tag: Option<String>,
+
+        /// Tag to remove
+        #[arg(long)]
+        remove_tag: Option<String>,
+
+        /// Remind again every N hours while overdue ("on" for the
+        /// configured `nag_hours`), or "off"
+        #[arg(long, value_name = "HOURS")]
+        set_nag: Option<String>,
+
         /// Skip confirmation
         #[arg(short, long)]
-        force: bool,
+        yes: bool,
+
+        /// List the tasks that would be updated without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Manage lists
@@ -110,6 +437,25 @@ enum Commands {
         command: Option<TagCommands>,
     },
 
+    /// Check, show or change settings in config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Developer tools: generate large test databases
+    #[cfg(feature = "debug-tools")]
+    Debug {
+        #[command(subcommand)]
+        command: DebugCommands,
+    },
+
+    /// Work with GitHub/GitLab/Jira issues linked to tasks through their URL
+    Link {
+        #[command(subcommand)]
+        command: LinkCommands,
+    },
+
     /// Export tasks
     Export {
         /// Output file path
@@ -123,6 +469,34 @@ enum Commands {
         /// Filter by list
         #[arg(short, long)]
         list: Option<String>,
+
+        /// Encrypt with a passphrase (asked for, or read from TICKIT_PASSPHRASE)
+        #[arg(long)]
+        encrypt: bool,
+
+        /// Encrypt to an age public key instead (age1...); repeat for several
+        #[arg(short, long = "recipient", value_name = "KEY")]
+        recipients: Vec<String>,
+    },
+
+    /// Decrypt an export made with `tickit export --encrypt`
+    Decrypt {
+        /// Encrypted export
+        file: PathBuf,
+
+        /// Write the export here instead of to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// age identity file, for exports encrypted to a public key
+        #[arg(short, long)]
+        identity: Option<PathBuf>,
+    },
+
+    /// Compare tasks with an earlier JSON export: added, removed and changed
+    Diff {
+        /// File written by `tickit export --format json`
+        export: PathBuf,
     },
 
     /// Check for updates and install if available
@@ -137,15 +511,84 @@ enum Commands {
         /// Force full sync (ignore last_sync timestamp)
         #[arg(long)]
         force: bool,
+
+        /// Run a first sync without showing what it will do and asking
+        #[arg(short, long)]
+        yes: bool,
+
+        #[command(subcommand)]
+        command: Option<SyncCommands>,
+    },
+
+    /// Serve a token-protected HTTP API on 127.0.0.1 for scripts and extensions
+    Serve {
+        /// Port to listen on (default: serve.port in config.toml, 7171)
+        #[arg(long)]
+        port: Option<u16>,
+    },
+
+    /// Show the end of the log file
+    Logs {
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+
+        /// Keep printing new lines as they're written
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Print the log file's path and exit
+        #[arg(long)]
+        path: bool,
+    },
+
+    /// List today's due-date reminders, or send them once (for cron or systemd timers)
+    Notify {
+        /// Send the reminders that haven't been shown today, then exit
+        #[arg(long)]
+        check: bool,
+
+        /// Print the weekly review: quiet lists and old tasks with no due date
+        #[arg(long, conflicts_with = "check")]
+        review: bool,
     },
 }
 
+/// Tasks a command applies to: named ones, or every task matching the filters
+#[derive(clap::Args, Debug)]
+struct TaskSelection {
+    /// Task IDs or titles (partial match)
+    tasks: Vec<String>,
+
+    /// Every task in this list
+    #[arg(short, long)]
+    list: Option<String>,
+
+    /// Every task with this tag
+    #[arg(short, long)]
+    tag: Option<String>,
+
+    /// Only completed tasks
+    #[arg(short, long)]
+    completed: bool,
+}
+
+impl TaskSelection {
+    /// Whether tasks are picked by filter rather than by name
+    const fn is_filter(&self) -> bool {
+        self.list.is_some() || self.tag.is_some() || self.completed
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum ListCommands {
     /// List all lists
     #[command(alias = "ls")]
     List,
 
+    /// Task counts, completion rate and last activity per list
+    Stats,
+
     /// Add a new list
     Add {
         /// List name
@@ -154,6 +597,10 @@ enum ListCommands {
         /// Icon/emoji
         #[arg(short, long, default_value = "📋")]
         icon: String,
+
+        /// Make it a checklist that `tickit lists reset` can uncheck in one go
+        #[arg(long)]
+        checklist: bool,
     },
 
     /// Delete a list
@@ -161,6 +608,65 @@ enum ListCommands {
     Delete {
         /// List name
         name: String,
+
+        /// What to do with its tasks
+        #[arg(long, default_value = "move", value_parser = ["move", "archive", "delete"])]
+        tasks: String,
+
+        /// List to move the tasks to (default: Inbox)
+        #[arg(long, value_name = "LIST")]
+        to: Option<String>,
+
+        /// Skip the confirmation prompt when deleting tasks
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Rename a list or change its icon, color, description or position
+    Edit {
+        /// List name (or a unique prefix of it)
+        name: String,
+
+        /// New name
+        #[arg(long)]
+        rename: Option<String>,
+
+        /// New icon/emoji
+        #[arg(short, long)]
+        icon: Option<String>,
+
+        /// New color (hex or a color name), or "none" to clear it
+        #[arg(short, long)]
+        color: Option<String>,
+
+        /// New description, or "" to clear it
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// New position among the lists, starting at 1 (the inbox stays first)
+        #[arg(short, long)]
+        position: Option<usize>,
+
+        /// Whether it's a checklist that can be reset
+        #[arg(long, value_name = "BOOL")]
+        checklist: Option<bool>,
+    },
+
+    /// Put lists in the given order; lists not named keep their order after them
+    Reorder {
+        /// List names (or unique prefixes), first to last
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+
+    /// Uncheck every completed task in a checklist, to use it again
+    Reset {
+        /// List name (or a unique prefix of it)
+        name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
     },
 }
 
@@ -170,6 +676,10 @@ enum TagCommands {
     #[command(alias = "ls")]
     List,
 
+    /// Task counts, completion rate and last activity per tag, and the
+    /// tags that could be deleted or merged
+    Stats,
+
     /// Add a new tag
     Add {
         /// Tag name
@@ -180,54 +690,395 @@ enum TagCommands {
         color: Option<String>,
     },
 
-    /// Delete a tag
+    /// Delete a tag, untagging its tasks
     #[command(alias = "rm")]
     Delete {
         /// Tag name
         name: String,
+
+        /// Move its tasks to this tag instead
+        #[arg(long, value_name = "TAG")]
+        reassign: Option<String>,
+
+        /// Save the affected tasks to a file first (format from the extension)
+        #[arg(long, value_name = "FILE")]
+        export: Option<PathBuf>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
     },
-}
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Rename a tag or change its color
+    Edit {
+        /// Tag name (or a unique prefix of it)
+        name: String,
 
-    // Initialize logging
-    if cli.verbose {
-        tracing_subscriber::fmt()
-            .with_env_filter("tickit=debug")
-            .init();
-    }
+        /// New name
+        #[arg(long)]
+        rename: Option<String>,
 
-    match cli.command {
-        None | Some(Commands::Ui) => {
-            // Start TUI
-            tickit::app::run()?;
-        }
+        /// New color (hex)
+        #[arg(short, long)]
+        color: Option<String>,
+    },
 
-        Some(Commands::Add {
-            title,
-            description,
-            url,
-            priority,
-            list,
-            tags,
-            due,
-        }) => {
-            let db = Database::open()?;
+    /// Move every task from one tag to another and delete the first
+    Merge {
+        /// Tag to merge away
+        from: String,
 
-            // Find list
-            let list_id = if let Some(list_name) = list {
-                let lists = db.get_lists()?;
-                lists
-                    .iter()
-                    .find(|l| l.name.to_lowercase() == list_name.to_lowercase())
-                    .map(|l| l.id)
-                    .unwrap_or_else(|| db.get_inbox().unwrap().id)
-            } else {
-                db.get_inbox()?.id
-            };
+        /// Tag to keep
+        into: String,
+    },
+}
 
-            // Parse priority
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// Report unknown keys and invalid values, with line numbers
+    Check,
+
+    /// Print the path of config.toml
+    Path,
+
+    /// Print the settings in effect (tokens are hidden)
+    Show,
+
+    /// Write a config.toml listing every option with its default
+    Init {
+        /// Replace an existing config.toml
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Change a setting, e.g. `sync.interval_secs 600` (an empty value resets it)
+    Set {
+        /// Dotted key
+        key: String,
+
+        /// New value
+        value: String,
+    },
+}
+
+#[cfg(feature = "debug-tools")]
+#[derive(Subcommand, Debug)]
+enum DebugCommands {
+    /// Fill a database with generated lists, tags and tasks
+    Seed {
+        /// How many tasks to generate
+        #[arg(long, default_value_t = 10_000)]
+        tasks: usize,
+
+        /// Seed for the generator; the same seed gives the same data
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+
+        /// Database file to fill instead of your own
+        #[arg(long)]
+        db: Option<PathBuf>,
+
+        /// Add to a database that already has tasks
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SyncCommands {
+    /// Stop syncing in the background until resumed; `tickit sync` still syncs
+    Pause,
+
+    /// Sync in the background again
+    Resume,
+}
+
+#[derive(Subcommand, Debug)]
+enum LinkCommands {
+    /// Update linked tasks from their issue's title and open/closed state
+    Refresh,
+
+    /// Add tasks for Jira issues (by default, the open ones assigned to you)
+    Jira {
+        /// List to add the tasks to (default: inbox)
+        #[arg(short, long)]
+        list: Option<String>,
+
+        /// Jira query picking the issues
+        #[arg(long, default_value = tickit::issues::DEFAULT_JIRA_JQL)]
+        jql: String,
+    },
+}
+
+/// Set by `--no-input`
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether we may ask the user something: stdin is a terminal and `--no-input` wasn't given
+fn can_prompt() -> bool {
+    use std::io::IsTerminal;
+    !NO_INPUT.load(Ordering::Relaxed) && std::io::stdin().is_terminal()
+}
+
+/// Whether stdout is a terminal; when it isn't, output sticks to plain ASCII labels
+fn fancy_output() -> bool {
+    use std::io::IsTerminal;
+    static FANCY: OnceLock<bool> = OnceLock::new();
+    *FANCY.get_or_init(|| std::io::stdout().is_terminal())
+}
+
+/// Date and time formats from config.toml, read once without creating the file
+fn display_config() -> &'static tickit::Config {
+    static CONFIG: OnceLock<tickit::Config> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        tickit::Config::default_path()
+            .and_then(|path| tickit::Config::load_from(&path))
+            .unwrap_or_default()
+    })
+}
+
+/// A date in the configured `date_format`
+fn format_date(date: chrono::NaiveDate) -> String {
+    display_config().format_date(date)
+}
+
+/// A timestamp in local time, in the configured date and time formats
+fn format_timestamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    display_config().format_datetime(at.with_timezone(&chrono::Local).naive_local())
+}
+
+/// `println!` for status lines like `✓ Added: ...`, dropping the leading
+/// symbol when output isn't a terminal
+macro_rules! say {
+    ($($arg:tt)*) => {
+        println!("{}", strip_mark(&format!($($arg)*)))
+    };
+}
+
+/// `text` without its leading symbol (the first word, if it has no letters or
+/// digits) when output isn't a terminal
+fn strip_mark(text: &str) -> String {
+    let trimmed = text.trim_start();
+    let indent = &text[..text.len() - trimmed.len()];
+    match trimmed.split_once(' ') {
+        Some((mark, rest)) if !fancy_output() && !mark.chars().any(char::is_alphanumeric) => {
+            format!("{}{}", indent, rest)
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Checkbox for a task, `☐`/`☑` or `[ ]`/`[x]`
+fn checkbox(done: bool) -> &'static str {
+    match (fancy_output(), done) {
+        (true, true) => "☑",
+        (true, false) => "☐",
+        (false, true) => "[x]",
+        (false, false) => "[ ]",
+    }
+}
+
+/// Priority icon, or its short label when output isn't a terminal
+fn priority_icon(priority: Priority) -> &'static str {
+    if fancy_output() {
+        priority.icon()
+    } else {
+        priority.label()
+    }
+}
+
+/// `icon name`, or just the name when output isn't a terminal
+fn with_icon(icon: &str, name: &str) -> String {
+    if fancy_output() {
+        format!("{} {}", icon, name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+
+    if let Err(err) = run(cli) {
+        let lookup = err.downcast_ref::<LookupError>();
+        let code = lookup.map_or(1, LookupError::exit_code);
+        if json {
+            eprintln!(
+                "{}",
+                serde_json::json!({ "error": format!("{:#}", err), "code": code })
+            );
+        } else if let Some(lookup) = lookup {
+            eprintln!("{}", lookup);
+        } else {
+            eprintln!("Error: {:?}", err);
+        }
+        std::process::exit(code);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let json = cli.json;
+    NO_INPUT.store(cli.no_input, Ordering::Relaxed);
+    if cli.ephemeral {
+        Database::set_ephemeral();
+    }
+    if let Some(name) = &cli.workspace {
+        Database::set_path(display_config().workspace_path(name)?);
+    }
+
+    // Log to a file so output never lands in the TUI; logging is best effort
+    if let Err(e) = tickit::logging::init(cli.verbose)
+        && cli.verbose
+    {
+        eprintln!("Could not open the log file: {:#}", e);
+    }
+
+    match cli.command {
+        None => {
+            // Start TUI
+            tickit::app::run()?;
+        }
+
+        Some(Commands::Ui {
+            list,
+            tag,
+            view,
+            task,
+        }) => {
+            let db = Database::open()?;
+            let mut start = tickit::app::StartAt {
+                view,
+                ..Default::default()
+            };
+            if let Some(name) = list {
+                let list = db
+                    .get_lists()?
+                    .into_iter()
+                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                    .ok_or_else(|| LookupError::NotFound(format!("List not found: {}", name)))?;
+                start.list = Some(list.id);
+            }
+            if let Some(name) = tag {
+                let tag = db
+                    .get_tags()?
+                    .into_iter()
+                    .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                    .ok_or_else(|| LookupError::NotFound(format!("Tag not found: {}", name)))?;
+                start.tag = Some(tag.id);
+            }
+            if let Some(query) = task {
+                let Some(task) = select_task(&db.get_all_tasks()?, &query, json)? else {
+                    return Ok(());
+                };
+                start.task = Some(task.id);
+            }
+            tickit::app::run_at(&start)?;
+        }
+
+        Some(Commands::Capture { text }) => {
+            let title = text.join(" ");
+            let title = title.trim();
+            let id = TickitCore::capture_into(&Database::open()?, title)?;
+            if json {
+                println!("{}", serde_json::json!({ "id": id, "title": title }));
+            } else {
+                say!("✓ Captured: {}", title);
+            }
+        }
+
+        Some(Commands::Add {
+            stdin,
+            from_file,
+            list,
+            priority,
+            tags,
+            create_missing,
+            ..
+        }) if stdin || from_file.is_some() => {
+            let text = match from_file {
+                Some(path) => std::fs::read_to_string(&path)?,
+                None => std::io::read_to_string(std::io::stdin())?,
+            };
+            add_batch(
+                &TickitCore::open()?,
+                &text,
+                list,
+                priority.as_deref().unwrap_or("medium"),
+                tags,
+                create_missing,
+                json,
+            )?;
+        }
+
+        Some(Commands::Add {
+            title,
+            interactive,
+            description,
+            url,
+            priority,
+            list,
+            tags,
+            due,
+            create_missing,
+            like,
+            ..
+        }) => {
+            let core = TickitCore::open()?;
+
+            // --like fills in the list, tags and priority that weren't given
+            let like = match like {
+                Some(query) => match select_task(&core.db().get_all_tasks()?, &query, json)? {
+                    Some(task) => Some(task),
+                    None => return Ok(()),
+                },
+                None => None,
+            };
+            let priority = priority
+                .or_else(|| like.as_ref().map(|t| t.priority.to_string()))
+                .unwrap_or_else(|| "medium".to_string());
+            let list = match (list, &like) {
+                (None, Some(t)) => core
+                    .db()
+                    .get_lists()?
+                    .into_iter()
+                    .find(|l| l.id == t.list_id)
+                    .map(|l| l.name),
+                (list, _) => list,
+            };
+
+            let AddAnswers {
+                title,
+                description,
+                url,
+                priority,
+                list,
+                tags,
+                due,
+            } = if interactive {
+                if !can_prompt() {
+                    anyhow::bail!("--interactive needs a terminal and no --no-input");
+                }
+                add_wizard(core.db(), title, priority, list)?
+            } else {
+                AddAnswers {
+                    title: title.unwrap_or_default(),
+                    description,
+                    url,
+                    priority,
+                    list,
+                    tags,
+                    due,
+                }
+            };
+
+            // Find list
+            let list_id = if let Some(list_name) = list {
+                find_or_create_list(&core, &list_name, create_missing)?.id
+            } else {
+                core.db().get_inbox()?.id
+            };
+
+            // Parse priority
             let priority = match priority.to_lowercase().as_str() {
                 "low" | "l" => Priority::Low,
                 "high" | "h" => Priority::High,
@@ -236,60 +1087,58 @@ fn main() -> Result<()> {
             };
 
             // Parse due date
-            let due_date = due.and_then(|s| {
-                chrono::NaiveDate::parse_from_str(&s, "%Y-%m-%d")
-                    .ok()
-                    .map(|date| date.and_hms_opt(23, 59, 59).unwrap().and_utc())
-            });
+            let due_date = due.and_then(|s| quickadd::parse_due_date(&s));
 
-            // Create task
-            let mut task = Task::new(&title, list_id);
-            task.priority = priority;
-            task.description = description;
-            task.url = url;
-            task.due_date = due_date;
+            let mut new = NewTask::new(&title, list_id);
+            new.priority = priority;
+            new.description = description;
+            new.url = url;
+            new.due_date = due_date;
 
             // Add tags
             if let Some(tag_str) = tags {
-                let db_tags = db.get_tags()?;
-                for tag_name in tag_str.split(',').map(|s| s.trim()) {
-                    if let Some(tag) = db_tags
-                        .iter()
-                        .find(|t| t.name.to_lowercase() == tag_name.to_lowercase())
-                    {
-                        task.tag_ids.push(tag.id);
-                    }
+                for tag_name in tag_str
+                    .split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                {
+                    new.tag_ids
+                        .push(find_or_create_tag(&core, tag_name, create_missing)?.id);
                 }
             }
+            if let Some(like) = &like {
+                new.tag_ids.extend(&like.tag_ids);
+            }
 
-            db.insert_task(&task)?;
-            println!("✓ Added: {}", title);
+            let task = core.add_task(new)?;
+            say!("✓ Added: {}", task.title);
         }
 
-        Some(Commands::List {
-            list,
-            all,
-            tag,
-            json,
-        }) => {
+        Some(Commands::List { list, all, tag }) => {
             let db = Database::open()?;
             let lists = db.get_lists()?;
             let tags = db.get_tags()?;
 
             // Find list filter
-            let list_id = list.and_then(|name| {
-                lists
-                    .iter()
-                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
-                    .map(|l| l.id)
-            });
+            let list_id = list
+                .map(|name| {
+                    lists
+                        .iter()
+                        .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                        .map(|l| l.id)
+                        .ok_or_else(|| LookupError::NotFound(format!("List not found: {}", name)))
+                })
+                .transpose()?;
 
             // Find tag filter
-            let tag_id = tag.and_then(|name| {
-                tags.iter()
-                    .find(|t| t.name.to_lowercase() == name.to_lowercase())
-                    .map(|t| t.id)
-            });
+            let tag_id = tag
+                .map(|name| {
+                    tags.iter()
+                        .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                        .map(|t| t.id)
+                        .ok_or_else(|| LookupError::NotFound(format!("Tag not found: {}", name)))
+                })
+                .transpose()?;
 
             let completed = if all { None } else { Some(false) };
             let tasks = db.get_tasks_with_filter(list_id, completed, tag_id)?;
@@ -301,8 +1150,8 @@ fn main() -> Result<()> {
                 println!("No tasks found.");
             } else {
                 for task in tasks {
-                    let checkbox = if task.completed { "☑" } else { "☐" };
-                    let priority = task.priority.icon();
+                    let checkbox = checkbox(task.completed);
+                    let priority = priority_icon(task.priority);
                     let list_name = lists
                         .iter()
                         .find(|l| l.id == task.list_id)
@@ -314,144 +1163,1152 @@ fn main() -> Result<()> {
             }
         }
 
-        Some(Commands::Done { task }) => {
-            let db = Database::open()?;
-            let tasks = db.get_all_tasks()?;
+        Some(Commands::Search {
+            query,
+            list,
+            tag,
+            priority,
+            due_before,
+            all,
+        }) => {
+            let core = TickitCore::open()?;
+            let lists = core.db().get_lists()?;
+            let tags = core.db().get_tags()?;
+
+            let list_id = match list {
+                Some(name) => match lists
+                    .iter()
+                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                {
+                    Some(l) => Some(l.id),
+                    None => return not_found(format!("List not found: {}", name)),
+                },
+                None => None,
+            };
+            let tag_id = match tag {
+                Some(name) => match tags
+                    .iter()
+                    .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                {
+                    Some(t) => Some(t.id),
+                    None => return not_found(format!("Tag not found: {}", name)),
+                },
+                None => None,
+            };
+            let priority = priority
+                .map(|p| {
+                    quickadd::parse_priority(&p)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown priority: {}", p))
+                })
+                .transpose()?;
+            let due_before = due_before
+                .map(|d| {
+                    quickadd::parse_due_date(&d)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid date: {}", d))
+                })
+                .transpose()?;
 
-            if let Some(mut t) = find_task(&tasks, &task) {
-                t.complete();
-                db.update_task(&t)?;
-                println!("✓ Completed: {}", t.title);
+            let tasks = core.tasks(&TaskQuery {
+                text: Some(query),
+                list_id,
+                tag_id,
+                priority,
+                due_before,
+                include_completed: all,
+            })?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tasks)?);
+            } else if tasks.is_empty() {
+                println!("No tasks found.");
             } else {
-                println!("Task not found: {}", task);
+                for task in tasks {
+                    let checkbox = checkbox(task.completed);
+                    let list_name = lists
+                        .iter()
+                        .find(|l| l.id == task.list_id)
+                        .map(|l| l.name.as_str())
+                        .unwrap_or("?");
+                    let due = task.due_date.map_or(String::new(), |d| {
+                        let date = format_date(d.date_naive());
+                        if fancy_output() {
+                            format!(" 📅{}", date)
+                        } else {
+                            format!(" due {}", date)
+                        }
+                    });
+
+                    println!(
+                        "{} {} {} [{}]{}",
+                        checkbox,
+                        priority_icon(task.priority),
+                        task.title,
+                        list_name,
+                        due
+                    );
+                }
             }
         }
 
-        Some(Commands::Undo { task }) => {
-            let db = Database::open()?;
-            let tasks = db.get_all_tasks()?;
+        Some(Commands::Today) => {
+            print_today(&Database::open()?, json)?;
+        }
+
+        Some(Commands::Status { format }) => {
+            print_status(&format, json)?;
+        }
+
+        Some(Commands::Upcoming { days }) => {
+            print_upcoming(&Database::open()?, days, json)?;
+        }
+
+        Some(Commands::Stats {
+            heatmap,
+            burndown,
+            list,
+            tag,
+            days,
+        }) => {
+            use tickit::stats::Scope;
 
-            if let Some(mut t) = find_task(&tasks, &task) {
-                t.uncomplete();
-                db.update_task(&t)?;
-                println!("↺ Reopened: {}", t.title);
+            let db = Database::open()?;
+            let scope = if let Some(name) = list {
+                let list = db
+                    .get_lists()?
+                    .into_iter()
+                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                    .ok_or_else(|| LookupError::NotFound(format!("List not found: {}", name)))?;
+                (Scope::List(list.id), list.name)
+            } else if let Some(name) = tag {
+                let tag = db
+                    .get_tags()?
+                    .into_iter()
+                    .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                    .ok_or_else(|| LookupError::NotFound(format!("Tag not found: {}", name)))?;
+                (Scope::Tag(tag.id), format!("#{}", tag.name))
             } else {
-                println!("Task not found: {}", task);
-            }
+                (Scope::All, "all tasks".to_string())
+            };
+            print_stats(&db, heatmap, burndown.then_some((scope, days)), json)?;
         }
 
-        Some(Commands::Delete { task, force }) => {
+        Some(Commands::Show { task }) => {
             let db = Database::open()?;
             let tasks = db.get_all_tasks()?;
 
-            if let Some(t) = find_task(&tasks, &task) {
-                if !force {
-                    print!("Delete \"{}\"? [y/N] ", t.title);
-                    use std::io::{self, Write};
-                    io::stdout().flush()?;
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input)?;
-                    if !input.trim().eq_ignore_ascii_case("y") {
-                        println!("Cancelled.");
-                        return Ok(());
-                    }
-                }
-                db.delete_task(t.id)?;
-                println!("✗ Deleted: {}", t.title);
-            } else {
-                println!("Task not found: {}", task);
+            if let Some(t) = select_task(&tasks, &task, json)? {
+                show_task(&db, &tasks, &t, json)?;
             }
         }
 
-        Some(Commands::Lists { command }) => {
-            let db = Database::open()?;
+        Some(Commands::Comment { task, text, delete }) => {
+            let core = TickitCore::open()?;
+            let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? else {
+                return Ok(());
+            };
 
-            match command {
-                None | Some(ListCommands::List) => {
-                    let lists = db.get_lists()?;
-                    for list in lists {
-                        let inbox = if list.is_inbox { " (default)" } else { "" };
-                        let count = db.get_task_count(list.id, false)?;
-                        println!("{} {} ({} tasks){}", list.icon, list.name, count, inbox);
-                    }
-                }
-                Some(ListCommands::Add { name, icon }) => {
-                    let list = List::new(&name).with_icon(&icon);
-                    db.insert_list(&list)?;
-                    println!("✓ Created list: {} {}", icon, name);
+            if let Some(n) = delete {
+                let comments = core.db().get_comments(t.id)?;
+                let Some(comment) = n.checked_sub(1).and_then(|i| comments.get(i)) else {
+                    return not_found(format!("No comment {} on: {}", n, t.title));
+                };
+                core.delete_comment(comment)?;
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "deleted": comment, "task": t.id })
+                    );
+                } else {
+                    say!("✓ Deleted comment {} on: {}", n, t.title);
                 }
-                Some(ListCommands::Delete { name }) => {
-                    let lists = db.get_lists()?;
-                    if let Some(list) = lists
-                        .iter()
-                        .find(|l| l.name.to_lowercase() == name.to_lowercase())
-                    {
-                        if list.is_inbox {
-                            println!("Cannot delete inbox.");
-                        } else {
-                            db.delete_list(list.id)?;
-                            println!("✗ Deleted list: {}", name);
-                        }
-                    } else {
-                        println!("List not found: {}", name);
-                    }
+            } else {
+                let comment = core.add_comment(&t, &text.join(" "))?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&comment)?);
+                } else {
+                    say!("💬 Commented on: {}", t.title);
                 }
             }
         }
 
-        Some(Commands::Tags { command }) => {
-            let db = Database::open()?;
+        Some(Commands::Attach { task, file, delete }) => {
+            let core = TickitCore::open()?;
+            let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? else {
+                return Ok(());
+            };
 
-            match command {
-                None | Some(TagCommands::List) => {
-                    let tags = db.get_tags()?;
-                    if tags.is_empty() {
-                        println!("No tags yet.");
-                    } else {
-                        for tag in tags {
-                            println!("● {} ({})", tag.name, tag.color);
-                        }
-                    }
+            if let Some(path) = file {
+                let attachment = core.attach(&t, &path)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&attachment)?);
+                } else {
+                    say!("📎 Attached {} to: {}", attachment.name, t.title);
                 }
-                Some(TagCommands::Add { name, color }) => {
-                    let mut tag = Tag::new(&name);
-                    if let Some(c) = color {
-                        tag = tag.with_color(&c);
-                    }
-                    db.insert_tag(&tag)?;
-                    println!("✓ Created tag: {}", name);
+            } else if let Some(n) = delete {
+                let attachments = core.db().get_attachments(t.id)?;
+                let Some(attachment) = n.checked_sub(1).and_then(|i| attachments.get(i)) else {
+                    return not_found(format!("No attachment {} on: {}", n, t.title));
+                };
+                core.detach(attachment)?;
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "deleted": attachment, "task": t.id })
+                    );
+                } else {
+                    say!("✓ Removed {} from: {}", attachment.name, t.title);
                 }
-                Some(TagCommands::Delete { name }) => {
-                    let tags = db.get_tags()?;
-                    if let Some(tag) = tags
+            } else {
+                let attachments = attachment_paths(core.db(), t.id)?;
+                if json {
+                    let value: Vec<_> = attachments
                         .iter()
-                        .find(|t| t.name.to_lowercase() == name.to_lowercase())
-                    {
-                        db.delete_tag(tag.id)?;
-                        println!("✗ Deleted tag: {}", name);
-                    } else {
-                        println!("Tag not found: {}", name);
+                        .map(|(attachment, path)| {
+                            let mut value = serde_json::json!(attachment);
+                            value["path"] = serde_json::json!(path);
+                            value
+                        })
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&value)?);
+                } else if attachments.is_empty() {
+                    println!("No attachments on: {}", t.title);
+                } else {
+                    for (i, line) in attachment_lines(&attachments).iter().enumerate() {
+                        println!("  [{}] {}", i + 1, line);
                     }
                 }
             }
         }
 
-        Some(Commands::Export {
-            output,
-            format,
-            list,
+        Some(Commands::Done {
+            selection,
+            yes,
+            dry_run,
         }) => {
-            let db = Database::open()?;
-            let lists = db.get_lists()?;
-            let tags = db.get_tags()?;
+            let core = TickitCore::open()?;
+            let Some(tasks) = resolve_selection(core.db(), &selection, json)? else {
+                return Ok(());
+            };
+            let tasks: Vec<Task> = tasks.into_iter().filter(|t| !t.completed).collect();
+            if !preview_bulk("completed", &tasks, yes, dry_run, json)? {
+                return Ok(());
+            }
+            if selection.is_filter() && !confirm_bulk("Complete", &tasks, yes)? {
+                return Ok(());
+            }
+            let tasks = core.complete(tasks)?;
+            report_bulk("✓ Completed", "completed", &tasks, json)?;
+            for (issue, result) in core.close_issues(&tasks) {
+                match result {
+                    Ok(()) if !json => say!("✓ Closed {}", issue),
+                    Ok(()) => {}
+                    Err(e) => eprintln!("Could not close {}: {:#}", issue, e),
+                }
+            }
+        }
 
-            // Filter by list
-            let list_id = list.and_then(|name| {
-                lists
-                    .iter()
-                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
-                    .map(|l| l.id)
-            });
+        Some(Commands::Undo { selection, .. })
+            if selection.tasks.is_empty() && !selection.is_filter() =>
+        {
+            match TickitCore::open()?.undo()? {
+                Some(entry) if json => {
+                    let undone = serde_json::json!({ "undone": entry.label, "at": entry.at });
+                    println!("{}", serde_json::to_string_pretty(&undone)?);
+                }
+                Some(entry) => say!("↶ Undid: {} ({})", entry.label, format_timestamp(entry.at)),
+                None if json => anyhow::bail!("Nothing to undo"),
+                None => println!("Nothing to undo."),
+            }
+        }
+
+        Some(Commands::Undo {
+            selection,
+            yes,
+            dry_run,
+        }) => {
+            let core = TickitCore::open()?;
+            let Some(tasks) = resolve_selection(core.db(), &selection, json)? else {
+                return Ok(());
+            };
+            let tasks: Vec<Task> = tasks.into_iter().filter(|t| t.completed).collect();
+            if !preview_bulk("reopened", &tasks, yes, dry_run, json)? {
+                return Ok(());
+            }
+            if selection.is_filter() && !confirm_bulk("Reopen", &tasks, yes)? {
+                return Ok(());
+            }
+            let tasks = core.reopen(tasks)?;
+            report_bulk("↺ Reopened", "reopened", &tasks, json)?;
+        }
+
+        Some(Commands::Delete {
+            selection,
+            yes,
+            dry_run,
+        }) => {
+            let core = TickitCore::open()?;
+            let Some(tasks) = resolve_selection(core.db(), &selection, json)? else {
+                return Ok(());
+            };
+            if !preview_bulk("deleted", &tasks, yes, dry_run, json)? {
+                return Ok(());
+            }
+            if json && !yes && !tasks.is_empty() {
+                anyhow::bail!("Pass --yes to delete tasks with --json");
+            }
+            // Deleting always asks, even for a single named task
+            if tasks.len() == 1 && !yes && can_prompt() {
+                use std::io::{self, Write};
+                print!("Delete \"{}\"? [y/N] ", tasks[0].title);
+                io::stdout().flush()?;
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                if !input.trim().eq_ignore_ascii_case("y") {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            } else if !confirm_bulk("Delete", &tasks, yes)? {
+                return Ok(());
+            }
+            core.delete(&tasks)?;
+            report_bulk("✗ Deleted", "deleted", &tasks, json)?;
+        }
+
+        Some(Commands::Archive {
+            older_than,
+            dry_run,
+            ..
+        }) => {
+            let core = TickitCore::open()?;
+            let tasks = core.archivable(older_than.unwrap_or_default())?;
+            if dry_run {
+                return report_dry_run("archived", &tasks, json);
+            }
+            let tasks = core.archive(tasks)?;
+            report_bulk("✓ Archived", "archived", &tasks, json)?;
+        }
+
+        Some(Commands::Purge {
+            older_than,
+            dry_run,
+            yes,
+            ..
+        }) => {
+            let core = TickitCore::open()?;
+            let tasks = core.purgeable(older_than.unwrap_or_default())?;
+            if !preview_bulk("purged", &tasks, yes, dry_run, json)? {
+                return Ok(());
+            }
+            if json && !yes && !tasks.is_empty() {
+                anyhow::bail!("Pass --yes to purge tasks with --json");
+            }
+            if !confirm_bulk("Permanently delete", &tasks, yes)? {
+                return Ok(());
+            }
+            core.purge(&tasks)?;
+            report_bulk("✗ Purged", "purged", &tasks, json)?;
+        }
+
+        Some(Commands::Maintain { dry_run }) => {
+            let core = TickitCore::open()?;
+            if !core.config().retention.is_set() {
+                if json {
+                    println!("{}", serde_json::json!({ "archived": [], "purged": [] }));
+                } else {
+                    println!("No retention policy set. Add one to config.toml:\n");
+                    println!("  [retention]");
+                    println!("  archive_completed_after = \"14d\"");
+                    println!("  purge_archived_after = \"6m\"");
+                }
+                return Ok(());
+            }
+
+            let cleanup = if dry_run {
+                core.cleanup_due()?
+            } else {
+                core.maintain()?
+            };
+            if json {
+                let ids = |tasks: &[Task]| {
+                    tasks
+                        .iter()
+                        .map(|t| serde_json::json!({ "id": t.id, "title": t.title }))
+                        .collect::<Vec<_>>()
+                };
+                let summary = serde_json::json!({
+                    "dry_run": dry_run,
+                    "archived": ids(&cleanup.archived),
+                    "purged": ids(&cleanup.purged),
+                });
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+                return Ok(());
+            }
+            let (archived, purged) = if dry_run {
+                ("would be archived", "would be purged")
+            } else {
+                ("archived", "purged")
+            };
+            println!("{} task(s) {}.", cleanup.archived.len(), archived);
+            println!("{} task(s) {}.", cleanup.purged.len(), purged);
+            if !dry_run && !cleanup.purged.is_empty() {
+                // Give the space of purged tasks back to the file system
+                core.db().execute_raw("VACUUM")?;
+            }
+        }
+
+        Some(Commands::Move { task, list }) => {
+            let core = TickitCore::open()?;
+            let lists = core.db().get_lists()?;
+            let names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+            let Ok(name) = complete_name(&list, &names) else {
+                return not_found(format!("List not found: {}", list));
+            };
+            let target = lists.iter().find(|l| l.name == name).unwrap();
+
+            if let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? {
+                let t = core.move_task(t, target.id)?;
+                report_bulk(&format!("→ Moved to {}", target.name), "moved", &[t], json)?;
+            }
+        }
+
+        Some(Commands::Defer { task, when }) => {
+            let core = TickitCore::open()?;
+            if let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? {
+                let deferred = core.defer(vec![t], &when.join(" "))?;
+                let due = deferred[0].due_date.map(|d| format_date(d.date_naive()));
+                report_bulk(
+                    &format!("⏭ Deferred to {}", due.unwrap_or_default()),
+                    "deferred",
+                    &deferred,
+                    json,
+                )?;
+            }
+        }
+
+        Some(Commands::Duplicate { task, list }) => {
+            let core = TickitCore::open()?;
+            let target = match list {
+                Some(list) => {
+                    let lists = core.db().get_lists()?;
+                    let names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+                    let Ok(name) = complete_name(&list, &names) else {
+                        return not_found(format!("List not found: {}", list));
+                    };
+                    lists.iter().find(|l| l.name == name).map(|l| l.id)
+                }
+                None => None,
+            };
+
+            if let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? {
+                let copy = core.duplicate(&t, target)?;
+                report_bulk("✓ Duplicated", "duplicated", &[copy], json)?;
+            }
+        }
+
+        Some(Commands::Merge { task, other, yes }) => {
+            let core = TickitCore::open()?;
+            let tasks = core.db().get_all_tasks()?;
+            let Some(task) = select_task(&tasks, &task, json)? else {
+                return Ok(());
+            };
+            let Some(other) = select_task(&tasks, &other, json)? else {
+                return Ok(());
+            };
+            if !yes {
+                if json || !can_prompt() {
+                    anyhow::bail!("Pass --yes to merge tasks without prompting");
+                }
+                let answer = ask(
+                    &format!(
+                        "Merge \"{}\" into \"{}\" and delete it? [y/N]",
+                        other.title, task.title
+                    ),
+                    None,
+                )?;
+                if !answer.eq_ignore_ascii_case("y") {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            }
+            let merged = core.merge(task, std::slice::from_ref(&other))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&merged)?);
+            } else {
+                say!("✓ Merged \"{}\" into: {}", other.title, merged.title);
+            }
+        }
+
+        Some(Commands::Tag {
+            task,
+            changes,
+            create,
+        }) => {
+            let core = TickitCore::open()?;
+            let mut tags = core.db().get_tags()?;
+
+            // `-name` values need hyphens allowed, which also captures a trailing --create
+            let create = create || changes.iter().any(|c| c == "--create");
+            let mut resolved = Vec::new();
+            for change in changes.iter().filter(|c| *c != "--create") {
+                let (name, add) = match change.strip_prefix('-') {
+                    Some(name) => (name, false),
+                    None => (change.strip_prefix('+').unwrap_or(change), true),
+                };
+                if name.is_empty() {
+                    continue;
+                }
+                let tag_id = match tags.iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+                    Some(tag) => tag.id,
+                    None if add && create => {
+                        let tag = core.create_tag(name, None)?;
+                        if !json {
+                            say!("✓ Created tag: {}", name);
+                        }
+                        tags.push(tag.clone());
+                        tag.id
+                    }
+                    // Nothing to remove
+                    None if !add => continue,
+                    None => {
+                        return not_found(format!(
+                            "Tag not found: {} (use --create to add it)",
+                            name
+                        ));
+                    }
+                };
+                resolved.push((tag_id, add));
+            }
+
+            if let Some(t) = select_task(&core.db().get_all_tasks()?, &task, json)? {
+                let t = core.retag(t, &resolved)?;
+                let names: Vec<&str> = tags
+                    .iter()
+                    .filter(|tag| t.tag_ids.contains(&tag.id))
+                    .map(|tag| tag.name.as_str())
+                    .collect();
+                let prefix = if names.is_empty() {
+                    "🏷 No tags".to_string()
+                } else {
+                    format!("🏷 {}", names.join(", "))
+                };
+                report_bulk(&prefix, "tagged", &[t], json)?;
+            }
+        }
+
+        Some(Commands::Edit {
+            selection,
+            set_priority,
+            set_list,
+            set_due,
+            add_tag,
+            remove_tag,
+            set_nag,
+            yes,
+            dry_run,
+        }) => {
+            let core = TickitCore::open()?;
+            let lists = core.db().get_lists()?;
+            let tags = core.db().get_tags()?;
+
+            let priority = set_priority
+                .map(|p| {
+                    quickadd::parse_priority(&p)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown priority: {}", p))
+                })
+                .transpose()?;
+            let due = match set_due.as_deref() {
+                Some("none") => Some(None),
+                Some(d) => Some(Some(
+                    quickadd::parse_due_date(d)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid date: {}", d))?,
+                )),
+                None => None,
+            };
+            let nag_hours = match set_nag.as_deref() {
+                Some("off") => Some(None),
+                Some("on") => Some(Some(0)),
+                Some(hours) => Some(Some(
+                    hours
+                        .trim_end_matches('h')
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|h| *h > 0)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Invalid nag interval: {} (hours, on or off)", hours)
+                        })?,
+                )),
+                None => None,
+            };
+            let list_id = match set_list {
+                Some(name) => match lists
+                    .iter()
+                    .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                {
+                    Some(l) => Some(l.id),
+                    None => return not_found(format!("List not found: {}", name)),
+                },
+                None => None,
+            };
+            let mut tag_changes = Vec::new();
+            for (name, add) in [(add_tag, true), (remove_tag, false)] {
+                if let Some(name) = name {
+                    match tags
+                        .iter()
+                        .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                    {
+                        Some(tag) => tag_changes.push((tag.id, add)),
+                        None => return not_found(format!("Tag not found: {}", name)),
+                    }
+                }
+            }
+            let changes = TaskChanges {
+                priority,
+                due_date: due,
+                list_id,
+                tags: tag_changes,
+                nag_hours,
+                ..TaskChanges::default()
+            };
+            if changes.is_empty() {
+                anyhow::bail!(
+                    "Nothing to change: pass --set-priority, --set-list, --set-due, --add-tag, --remove-tag or --set-nag"
+                );
+            }
+
+            let Some(tasks) = resolve_selection(core.db(), &selection, json)? else {
+                return Ok(());
+            };
+            if !preview_bulk("updated", &tasks, yes, dry_run, json)? {
+                return Ok(());
+            }
+            if selection.is_filter() && !confirm_bulk("Edit", &tasks, yes)? {
+                return Ok(());
+            }
+            let tasks = core.edit(tasks, &changes)?;
+            report_bulk("✎ Updated", "updated", &tasks, json)?;
+        }
+
+        Some(Commands::Lists { command }) => {
+            let db = Database::open()?;
+
+            match command {
+                None | Some(ListCommands::List) if json => {
+                    let mut lists = Vec::new();
+                    for list in db.get_lists()? {
+                        let open_tasks = db.get_task_count(list.id, false)?;
+                        let mut value = serde_json::to_value(&list)?;
+                        value["open_tasks"] = serde_json::json!(open_tasks);
+                        lists.push(value);
+                    }
+                    println!("{}", serde_json::to_string_pretty(&lists)?);
+                }
+                None | Some(ListCommands::List) => {
+                    let lists = db.get_lists()?;
+                    for list in lists {
+                        let inbox = if list.is_inbox {
+                            " (default)"
+                        } else if list.is_checklist {
+                            " (checklist)"
+                        } else {
+                            ""
+                        };
+                        let count = db.get_task_count(list.id, false)?;
+                        println!(
+                            "{} ({} tasks){}",
+                            with_icon(&list.icon, &list.name),
+                            count,
+                            inbox
+                        );
+                    }
+                }
+                Some(ListCommands::Stats) => {
+                    let tasks = tickit::stats::Usage::load_tasks(&db)?;
+                    let usage = tickit::stats::Usage::lists(&db.get_lists()?, &tasks);
+                    print_usage("list", &usage, &[], json)?;
+                }
+                Some(ListCommands::Add {
+                    name,
+                    icon,
+                    checklist,
+                }) => {
+                    let mut list = List::new(&name).with_icon(&icon);
+                    list.is_checklist = checklist;
+                    db.insert_list(&list)?;
+                    say!("✓ Created list: {} {}", icon, name);
+                }
+                Some(ListCommands::Delete {
+                    name,
+                    tasks,
+                    to,
+                    yes,
+                }) => {
+                    let lists = db.get_lists()?;
+                    let Some(list) = lists
+                        .iter()
+                        .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                    else {
+                        return not_found(format!("List not found: {}", name));
+                    };
+                    if list.is_inbox {
+                        println!("Cannot delete inbox.");
+                        return Ok(());
+                    }
+                    if to.is_some() && tasks != "move" {
+                        anyhow::bail!("--to only applies to --tasks move");
+                    }
+                    let target = match to {
+                        Some(to) => {
+                            let names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+                            let Ok(found) = complete_name(&to, &names) else {
+                                return not_found(format!("List not found: {}", to));
+                            };
+                            let target = lists.iter().find(|l| l.name == found).unwrap();
+                            if target.id == list.id {
+                                anyhow::bail!("Can't move a list's tasks to itself");
+                            }
+                            target
+                        }
+                        None => lists.iter().find(|l| l.is_inbox).unwrap(),
+                    };
+
+                    let affected = db.get_tasks_for_list(list.id)?;
+                    let (what, outcome) = match tasks.as_str() {
+                        "archive" => (DeletedListTasks::Archive, "archived".to_string()),
+                        "delete" => (DeletedListTasks::Delete, "deleted".to_string()),
+                        _ => (
+                            DeletedListTasks::Move(target.id),
+                            format!("moved to {}", target.name),
+                        ),
+                    };
+                    if what == DeletedListTasks::Delete && !confirm_bulk("Delete", &affected, yes)?
+                    {
+                        return Ok(());
+                    }
+                    db.delete_list_with(list.id, what)?;
+                    say!(
+                        "✗ Deleted list: {} ({} tasks {})",
+                        list.name,
+                        affected.len(),
+                        outcome
+                    );
+                }
+                Some(ListCommands::Edit {
+                    name,
+                    rename,
+                    icon,
+                    color,
+                    description,
+                    position,
+                    checklist,
+                }) => {
+                    let mut lists = db.get_lists()?;
+                    let names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+                    let Ok(found) = complete_name(&name, &names) else {
+                        return not_found(format!("List not found: {}", name));
+                    };
+                    let index = lists.iter().position(|l| l.name == found).unwrap();
+
+                    let core = TickitCore::open()?;
+                    let rename = rename
+                        .map(|name| core.check_list_name(&name, Some(lists[index].id)))
+                        .transpose()?;
+                    let color = match color.as_deref() {
+                        None => None,
+                        Some("none") => Some(None),
+                        Some(color) => Some(Some(TickitCore::normalize_color(color)?)),
+                    };
+
+                    let list = &mut lists[index];
+                    if let Some(new_name) = rename {
+                        list.name = new_name;
+                    }
+                    if let Some(icon) = icon {
+                        list.icon = icon;
+                    }
+                    if let Some(color) = color {
+                        list.color = color;
+                    }
+                    if let Some(description) = description {
+                        list.description = (!description.is_empty()).then_some(description);
+                    }
+                    if let Some(checklist) = checklist {
+                        list.is_checklist = checklist;
+                    }
+                    let edited = list.clone();
+
+                    let mut changed = vec![edited.clone()];
+                    if let Some(position) = position {
+                        if edited.is_inbox {
+                            anyhow::bail!("The inbox always comes first");
+                        }
+                        let mut others: Vec<List> = lists
+                            .into_iter()
+                            .filter(|l| !l.is_inbox && l.id != edited.id)
+                            .collect();
+                        let at = position.saturating_sub(1).min(others.len());
+                        others.insert(at, edited.clone());
+                        changed = renumber_lists(others);
+                        // The edited list is saved even when its position didn't change
+                        if !changed.iter().any(|l| l.id == edited.id) {
+                            changed.push(edited.clone());
+                        }
+                    }
+                    db.update_lists(&changed)?;
+                    say!("✓ Updated list: {}", with_icon(&edited.icon, &edited.name));
+                }
+                Some(ListCommands::Reset { name, yes }) => {
+                    let lists = db.get_lists()?;
+                    let names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+                    let Ok(found) = complete_name(&name, &names) else {
+                        return not_found(format!("List not found: {}", name));
+                    };
+                    let list = lists.iter().find(|l| l.name == found).unwrap();
+                    if !list.is_checklist {
+                        anyhow::bail!(
+                            "{} isn't a checklist; make it one with `tickit lists edit \"{}\" --checklist true`",
+                            list.name,
+                            list.name
+                        );
+                    }
+
+                    let done: Vec<Task> = db
+                        .get_tasks_for_list(list.id)?
+                        .into_iter()
+                        .filter(|t| t.completed)
+                        .collect();
+                    if !confirm_bulk("Uncheck", &done, yes)? {
+                        return Ok(());
+                    }
+                    let core = TickitCore::new(db, display_config().clone());
+                    let reset = core.reopen(done)?;
+                    report_bulk("↺ Unchecked", "unchecked", &reset, json)?;
+                }
+                Some(ListCommands::Reorder { names }) => {
+                    let lists = db.get_lists()?;
+                    let all_names: Vec<&str> = lists
+                        .iter()
+                        .filter(|l| !l.is_inbox)
+                        .map(|l| l.name.as_str())
+                        .collect();
+
+                    let mut ordered: Vec<List> = Vec::new();
+                    for name in &names {
+                        let Ok(found) = complete_name(name, &all_names) else {
+                            return not_found(format!("List not found: {}", name));
+                        };
+                        if !ordered.iter().any(|l| l.name == found) {
+                            ordered.extend(lists.iter().find(|l| l.name == found).cloned());
+                        }
+                    }
+                    let rest: Vec<List> = lists
+                        .iter()
+                        .filter(|l| !l.is_inbox && !ordered.iter().any(|o| o.id == l.id))
+                        .cloned()
+                        .collect();
+                    ordered.extend(rest);
+
+                    let summary: Vec<String> = ordered
+                        .iter()
+                        .map(|l| with_icon(&l.icon, &l.name))
+                        .collect();
+                    db.update_lists(&renumber_lists(ordered))?;
+                    say!("✓ Lists reordered: {}", summary.join(", "));
+                }
+            }
+        }
+
+        Some(Commands::Config { command }) => {
+            run_config_command(command, json)?;
+        }
+
+        #[cfg(feature = "debug-tools")]
+        Some(Commands::Debug {
+            command:
+                DebugCommands::Seed {
+                    tasks,
+                    seed,
+                    db,
+                    yes,
+                },
+        }) => {
+            let db = match db {
+                Some(path) => Database::open_path(&path)?,
+                None => Database::open()?,
+            };
+            let existing = db.get_total_task_count(true)?;
+            if existing > 0 && !yes {
+                anyhow::bail!(
+                    "The database already has {} tasks; pass --yes to add to them, or --db to fill another file",
+                    existing
+                );
+            }
+            let started = std::time::Instant::now();
+            let stats = tickit::seed::seed(&db, tasks, seed)?;
+            say!(
+                "✓ Added {} tasks, {} lists and {} tags in {:.1?}",
+                stats.tasks,
+                stats.lists,
+                stats.tags,
+                started.elapsed()
+            );
+        }
+
+        Some(Commands::Link {
+            command: LinkCommands::Refresh,
+        }) => {
+            let refresh = TickitCore::open()?.refresh_links()?;
+            if json {
+                let failed: Vec<_> = refresh
+                    .failed
+                    .iter()
+                    .map(|(t, e)| serde_json::json!({"id": t.id, "title": t.title, "error": e.to_string()}))
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "updated": refresh.updated,
+                        "unchanged": refresh.unchanged,
+                        "failed": failed,
+                    }))?
+                );
+            } else {
+                for task in &refresh.updated {
+                    say!(
+                        "↻ {} {}",
+                        if task.completed { "closed" } else { "open  " },
+                        task.title
+                    );
+                }
+                for (task, e) in &refresh.failed {
+                    say!("✗ {}: {:#}", task.title, e);
+                }
+                say!(
+                    "✓ {} updated, {} unchanged, {} failed",
+                    refresh.updated.len(),
+                    refresh.unchanged,
+                    refresh.failed.len()
+                );
+            }
+        }
+
+        Some(Commands::Link {
+            command: LinkCommands::Jira { list, jql },
+        }) => {
+            let core = TickitCore::open()?;
+            let list_id = match list {
+                Some(name) => core.find_list(&name)?.id,
+                None => core.db().get_inbox()?.id,
+            };
+            let import = core.import_jira(list_id, &jql)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "added": import.added,
+                        "skipped": import.skipped,
+                    }))?
+                );
+            } else {
+                for task in &import.added {
+                    say!("+ {}", task.title);
+                }
+                say!(
+                    "✓ {} added, {} already linked",
+                    import.added.len(),
+                    import.skipped
+                );
+            }
+        }
+
+        Some(Commands::Tags { command }) => {
+            let db = Database::open()?;
+
+            match command {
+                None | Some(TagCommands::List) => {
+                    let tags = db.get_tags()?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&tags)?);
+                    } else if tags.is_empty() {
+                        println!("No tags yet.");
+                    } else {
+                        for tag in tags {
+                            say!("● {} ({})", tag.name, tag.color);
+                        }
+                    }
+                }
+                Some(TagCommands::Stats) => {
+                    let tags = db.get_tags()?;
+                    let tasks = tickit::stats::Usage::load_tasks(&db)?;
+                    let usage = tickit::stats::Usage::tags(&tags, &tasks);
+                    print_usage("tag", &usage, &tickit::stats::similar_tags(&tags), json)?;
+                }
+                Some(TagCommands::Add { name, color }) => {
+                    let mut tag = Tag::new(&name);
+                    if let Some(c) = color {
+                        tag = tag.with_color(&c);
+                    }
+                    db.insert_tag(&tag)?;
+                    say!("✓ Created tag: {}", name);
+                }
+                Some(TagCommands::Delete {
+                    name,
+                    reassign,
+                    export,
+                    yes,
+                }) => {
+                    let tags = db.get_tags()?;
+                    let Some(tag) = tags
+                        .iter()
+                        .find(|t| t.name.to_lowercase() == name.to_lowercase())
+                    else {
+                        return not_found(format!("Tag not found: {}", name));
+                    };
+                    let target = match reassign {
+                        Some(into) => {
+                            let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+                            let Ok(found) = complete_name(&into, &names) else {
+                                return not_found(format!("Tag not found: {}", into));
+                            };
+                            let target = tags.iter().find(|t| t.name == found).unwrap();
+                            if target.id == tag.id {
+                                anyhow::bail!("Can't reassign a tag's tasks to itself");
+                            }
+                            Some(target)
+                        }
+                        None => None,
+                    };
+
+                    let tasks = db.get_tasks_with_filter(None, None, Some(tag.id))?;
+                    if let Some(path) = export {
+                        let format = ExportFormat::from_name(
+                            &path.extension().unwrap_or_default().to_string_lossy(),
+                        );
+                        let mut file = std::fs::File::create(&path)?;
+                        tickit::export::export_tasks(
+                            &mut file,
+                            &tasks,
+                            &db.get_lists()?,
+                            &tags,
+                            &db.get_all_comments()?,
+                            format,
+                            display_config(),
+                        )?;
+                        say!("Exported {} tasks to {}", tasks.len(), path.display());
+                    }
+
+                    let action = match target {
+                        Some(target) => format!("Move #{} to #{} on", tag.name, target.name),
+                        None => format!("Remove #{} from", tag.name),
+                    };
+                    if !confirm_bulk(&action, &tasks, yes)? {
+                        return Ok(());
+                    }
+                    match target {
+                        Some(target) => {
+                            db.merge_tag(tag.id, target.id)?;
+                            say!(
+                                "✗ Deleted tag: {} ({} tasks moved to {})",
+                                tag.name,
+                                tasks.len(),
+                                target.name
+                            );
+                        }
+                        None => {
+                            db.delete_tag(tag.id)?;
+                            db.record_tombstone(tag.id, "tag")?;
+                            say!(
+                                "✗ Deleted tag: {} ({} tasks untagged)",
+                                tag.name,
+                                tasks.len()
+                            );
+                        }
+                    }
+                }
+                Some(TagCommands::Edit {
+                    name,
+                    rename,
+                    color,
+                }) => {
+                    let tags = db.get_tags()?;
+                    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+                    let Ok(found) = complete_name(&name, &names) else {
+                        return not_found(format!("Tag not found: {}", name));
+                    };
+                    let mut tag = tags.iter().find(|t| t.name == found).unwrap().clone();
+
+                    if let Some(new_name) = rename {
+                        if tags
+                            .iter()
+                            .any(|t| t.id != tag.id && t.name.eq_ignore_ascii_case(&new_name))
+                        {
+                            anyhow::bail!(
+                                "A tag named \"{}\" already exists; use `tickit tags merge` to combine them",
+                                new_name
+                            );
+                        }
+                        tag.name = new_name;
+                    }
+                    if let Some(color) = color {
+                        tag.color = color;
+                    }
+                    tag.touch();
+                    db.update_tag(&tag)?;
+                    say!("✓ Updated tag: {} ({})", tag.name, tag.color);
+                }
+                Some(TagCommands::Merge { from, into }) => {
+                    let tags = db.get_tags()?;
+                    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+                    let mut resolved = Vec::new();
+                    for name in [&from, &into] {
+                        match complete_name(name, &names) {
+                            Ok(found) => {
+                                resolved.push(tags.iter().find(|t| t.name == found).unwrap())
+                            }
+                            Err(_) => return not_found(format!("Tag not found: {}", name)),
+                        }
+                    }
+                    let (from_tag, into_tag) = (resolved[0], resolved[1]);
+                    if from_tag.id == into_tag.id {
+                        anyhow::bail!("Can't merge a tag into itself");
+                    }
+
+                    let moved = db
+                        .get_tasks_with_filter(None, None, Some(from_tag.id))?
+                        .len();
+                    db.merge_tag(from_tag.id, into_tag.id)?;
+                    say!(
+                        "✓ Merged {} into {} ({} tasks)",
+                        from_tag.name,
+                        into_tag.name,
+                        moved
+                    );
+                }
+            }
+        }
+
+        Some(Commands::Export {
+            output,
+            format,
+            list,
+            encrypt,
+            recipients,
+        }) => {
+            use std::io::Write;
+
+            let db = Database::open()?;
+            let lists = db.get_lists()?;
+            let tags = db.get_tags()?;
+
+            // Filter by list
+            let list_id = list
+                .map(|name| {
+                    lists
+                        .iter()
+                        .find(|l| l.name.to_lowercase() == name.to_lowercase())
+                        .map(|l| l.id)
+                        .ok_or_else(|| LookupError::NotFound(format!("List not found: {}", name)))
+                })
+                .transpose()?;
 
             let tasks = if let Some(lid) = list_id {
                 db.get_tasks_for_list(lid)?
@@ -459,272 +2316,2065 @@ fn main() -> Result<()> {
                 db.get_all_tasks()?
             };
 
-            // Parse format
-            let fmt = match format.to_lowercase().as_str() {
-                "todotxt" | "todo.txt" | "txt" => ExportFormat::TodoTxt,
-                "markdown" | "md" => ExportFormat::Markdown,
-                "csv" => ExportFormat::Csv,
-                _ => ExportFormat::Json,
-            };
+            let fmt = ExportFormat::from_name(&format);
+            let encryption = if !recipients.is_empty() {
+                Some(Encryption::Recipients(recipients))
+            } else if encrypt {
+                Some(Encryption::Passphrase(read_passphrase(true)?))
+            } else {
+                None
+            };
+
+            // Export
+            let mut exported = Vec::new();
+            tickit::export::export_tasks(
+                &mut exported,
+                &tasks,
+                &lists,
+                &tags,
+                &db.get_all_comments()?,
+                fmt,
+                display_config(),
+            )?;
+            if let Some(path) = output {
+                match &encryption {
+                    Some(encryption) => {
+                        let file = std::fs::File::create(&path)?;
+                        tickit::export::encrypt(file, &exported, encryption, false)?
+                    }
+                    None => std::fs::write(&path, &exported)?,
+                }
+                println!(
+                    "Exported {} tasks to {}{}",
+                    tasks.len(),
+                    path.display(),
+                    if encryption.is_some() {
+                        " (encrypted)"
+                    } else {
+                        ""
+                    }
+                );
+            } else {
+                let stdout = std::io::stdout();
+                match &encryption {
+                    // Armored, so it's safe to print to a terminal
+                    Some(encryption) => {
+                        tickit::export::encrypt(stdout.lock(), &exported, encryption, true)?
+                    }
+                    None => stdout.lock().write_all(&exported)?,
+                }
+            }
+        }
+
+        Some(Commands::Diff { export }) => {
+            use tickit::diff::{Diff, Snapshot};
+
+            let snapshot = Snapshot::parse(
+                &std::fs::read(&export)
+                    .with_context(|| format!("Failed to read {}", export.display()))?,
+            )?;
+            let db = Database::open()?;
+            // Exports leave archived tasks out, so only those it had count
+            let mut tasks = db.get_all_tasks()?;
+            tasks.extend(
+                db.get_archived_tasks()?
+                    .into_iter()
+                    .filter(|t| snapshot.tasks.iter().any(|s| s.id == t.id)),
+            );
+            let diff = Diff::new(&snapshot, &tasks, &db.get_lists()?, &db.get_tags()?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+                return Ok(());
+            }
+
+            let color = use_color();
+            if let Some(at) = snapshot.exported_at {
+                println!("Since {} ({}):", export.display(), format_timestamp(at));
+            }
+            for task in &diff.added {
+                println!("{} {}", paint("+", Color::Green, color), task.title);
+            }
+            for task in &diff.removed {
+                println!("{} {}", paint("-", Color::Red, color), task.title);
+            }
+            for changed in &diff.changed {
+                println!(
+                    "{} {}",
+                    paint("~", Color::Yellow, color),
+                    changed.task.title
+                );
+                for change in &changed.changes {
+                    println!(
+                        "    {}: {} → {}",
+                        change.field,
+                        first_line(&change.before),
+                        first_line(&change.after)
+                    );
+                }
+            }
+            if !diff.is_empty() {
+                println!();
+            }
+            println!(
+                "{} added, {} removed, {} changed, {} unchanged",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len(),
+                diff.unchanged
+            );
+        }
+
+        Some(Commands::Decrypt {
+            file,
+            output,
+            identity,
+        }) => {
+            use std::io::Write;
+
+            let decryption = match identity {
+                Some(path) => Decryption::IdentityFile(path),
+                None => Decryption::Passphrase(read_passphrase(false)?),
+            };
+            let input = std::fs::File::open(&file)
+                .with_context(|| format!("Could not open {}", file.display()))?;
+            let plain = tickit::export::decrypt(std::io::BufReader::new(input), &decryption)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, &plain)?;
+                    say!("✓ Decrypted {} to {}", file.display(), path.display());
+                }
+                None => std::io::stdout().write_all(&plain)?,
+            }
+        }
+
+        Some(Commands::Update) => {
+            run_update_command();
+        }
+
+        Some(Commands::Sync {
+            command: Some(command),
+            ..
+        }) => {
+            let paused = matches!(command, SyncCommands::Pause);
+            Database::open()?.set_sync_paused(paused)?;
+            if json {
+                println!("{}", serde_json::json!({ "paused": paused }));
+            } else if paused {
+                say!(
+                    "⏸ Auto-sync paused. `tickit sync` still syncs; `tickit sync resume` turns it back on."
+                );
+            } else {
+                say!("✓ Auto-sync resumed.");
+            }
+        }
+
+        Some(Commands::Sync {
+            status,
+            force,
+            yes,
+            command: None,
+        }) => {
+            run_sync_command(status, force, yes, json)?;
+        }
+
+        Some(Commands::Serve { port }) => {
+            run_serve_command(port)?;
+        }
+
+        Some(Commands::Logs {
+            lines,
+            follow,
+            path,
+        }) => {
+            let log = tickit::logging::log_path()?;
+            if path {
+                println!("{}", log.display());
+                return Ok(());
+            }
+            for line in tickit::logging::tail(&log, lines)? {
+                println!("{}", line);
+            }
+            if follow {
+                tickit::logging::follow(&log, &mut std::io::stdout())?;
+            } else if !log.exists() {
+                println!("No log yet at {}", log.display());
+            }
+        }
+
+        Some(Commands::Notify { check, review }) => {
+            let config = tickit::Config::load()?;
+            let db = Database::open()?;
+
+            if review {
+                print_review(&db, &config.notifications.review, json)?;
+                return Ok(());
+            }
+
+            if !check {
+                let pending = notifications::pending_reminders(
+                    &db,
+                    &config.notification_rules,
+                    config.notifications.nag_hours,
+                )?;
+                if json {
+                    let rows: Vec<_> = pending
+                        .iter()
+                        .map(|(t, r)| serde_json::json!({"id": t.id, "title": t.title, "reminder": r.key()}))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&rows)?);
+                } else if pending.is_empty() {
+                    println!("No reminders pending today.");
+                } else {
+                    for (task, reminder) in &pending {
+                        println!("{:<13} {}", reminder.key(), task.title);
+                    }
+                    println!("\nRun `tickit notify --check` to send them.");
+                }
+                return Ok(());
+            }
+
+            // Escalate first, so reminders go out with the raised priority
+            let core = TickitCore::new(db, config.clone());
+            let escalated = core.escalate()?;
+            let db = core.into_db();
+
+            let quiet = config
+                .quiet_hours
+                .is_some_and(|q| q.contains(chrono::Local::now().time()));
+            let (sent, reviewed) = if config.notifications.enabled && !quiet {
+                (
+                    notifications::send_reminders(
+                        &db,
+                        config.quiet_hours,
+                        &config.notification_rules,
+                        config.notifications.nag_hours,
+                    )?,
+                    notifications::send_review(
+                        &db,
+                        &config.notifications.review,
+                        config.quiet_hours,
+                    )?,
+                )
+            } else {
+                (0, false)
+            };
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({"sent": sent, "review": reviewed, "enabled": config.notifications.enabled, "quiet_hours": quiet, "escalated": escalated.len()})
+                );
+                return Ok(());
+            }
+            for raised in &escalated {
+                say!(
+                    "↑ {}: {} → {}",
+                    raised.task.title,
+                    raised.from.name(),
+                    raised.task.priority.name()
+                );
+            }
+            if !config.notifications.enabled {
+                say!("⚠ Notifications are disabled in config.");
+            } else if quiet {
+                println!("Quiet hours, no reminders sent.");
+            } else {
+                say!("✓ Sent {} reminder(s)", sent);
+                if reviewed {
+                    say!("✓ Sent the weekly review");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the weekly review digest, whether or not it's due
+fn print_review(db: &Database, config: &tickit::config::ReviewConfig, json: bool) -> Result<()> {
+    let review = notifications::Review::gather(db, config, chrono::Utc::now())?;
+    if json {
+        let lists: Vec<_> = review
+            .stale_lists
+            .iter()
+            .map(|(l, at)| serde_json::json!({"id": l.id, "name": l.name, "last_change": at}))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "stale_lists": lists,
+                "undated_tasks": review.undated_tasks,
+            }))?
+        );
+        return Ok(());
+    }
+    if review.is_empty() {
+        println!("Nothing to review.");
+        return Ok(());
+    }
+    if !review.stale_lists.is_empty() {
+        println!("Lists with no changes for {} days:", config.stale_list_days);
+        for (list, at) in &review.stale_lists {
+            println!(
+                "  {}  (last change {})",
+                with_icon(&list.icon, &list.name),
+                format_timestamp(*at)
+            );
+        }
+    }
+    if !review.undated_tasks.is_empty() {
+        if !review.stale_lists.is_empty() {
+            println!();
+        }
+        println!(
+            "Open tasks with no due date, older than {} days:",
+            config.undated_task_days
+        );
+        for task in &review.undated_tasks {
+            println!(
+                "  {}  (created {})",
+                task.title,
+                format_timestamp(task.created_at)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Run a `tickit config` subcommand
+fn run_config_command(command: ConfigCommands, json: bool) -> Result<()> {
+    use tickit::Config;
+
+    let path = Config::default_path()?;
+    match command {
+        ConfigCommands::Check => {
+            let issues = Config::check_file(&path)?;
+            if json {
+                let rows: Vec<_> = issues
+                    .iter()
+                    .map(|i| serde_json::json!({ "line": i.line, "key": i.key, "message": i.message }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else if !path.exists() {
+                println!("No config file at {}; using defaults.", path.display());
+            } else if issues.is_empty() {
+                say!("✓ {} is valid", path.display());
+            } else {
+                for issue in &issues {
+                    eprintln!("{}: {}", path.display(), issue);
+                }
+            }
+            if !issues.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        ConfigCommands::Path => {
+            if json {
+                println!("{}", serde_json::json!({ "path": path }));
+            } else {
+                println!("{}", path.display());
+            }
+        }
+        ConfigCommands::Init { force } => {
+            if path.exists() && !force {
+                anyhow::bail!(
+                    "{} already exists; pass --force to replace it",
+                    path.display()
+                );
+            }
+            Config::write_template(&path)?;
+            if json {
+                println!("{}", serde_json::json!({ "path": path }));
+            } else {
+                say!("✓ Wrote {}", path.display());
+            }
+        }
+        ConfigCommands::Show => {
+            let mut config = Config::load_from(&path)?;
+            for token in [
+                &mut config.sync.token,
+                &mut config.serve.token,
+                &mut config.links.github_token,
+                &mut config.links.gitlab_token,
+            ] {
+                if token.is_some() {
+                    *token = Some("<hidden>".to_string());
+                }
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config)?);
+            } else {
+                print!("{}", toml::to_string_pretty(&config)?);
+            }
+        }
+        ConfigCommands::Set { key, value } => {
+            let mut config = Config::load_from(&path)?;
+            config.set_key(&key, &value)?;
+            config.save_to(&path)?;
+            if json {
+                println!("{}", serde_json::json!({ "key": key, "value": value }));
+            } else if value.is_empty() {
+                say!("✓ Reset {}", key);
+            } else {
+                say!("✓ Set {} = {}", key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run the sync command
+fn run_sync_command(status_only: bool, force: bool, yes: bool, json: bool) -> Result<()> {
+    use tickit::{Config, ConflictPolicy, sync};
+
+    let config = Config::load()?;
+    let db = Database::open()?;
+    // Every workspace would share this device's ID and server account
+    if Database::current_path()? != Database::default_path()? {
+        anyhow::bail!("Sync only covers the default workspace");
+    }
+
+    if status_only && json {
+        let status = serde_json::json!({
+            "enabled": config.sync.enabled,
+            "configured": config.sync.server.is_some() && config.sync.has_token(),
+            "server": config.sync.server,
+            "last_sync": db.get_last_sync()?,
+            "paused": db.is_sync_paused()?,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    if !config.sync.enabled {
+        say!("⚠ Sync is disabled in config.");
+        println!("\nTo enable sync, add to ~/.config/tickit/config.toml:");
+        println!();
+        println!("  [sync]");
+        println!("  enabled = true");
+        println!("  server = \"http://your-server:3030\"");
+        println!("  token = \"your-token\"");
+        return Ok(());
+    }
+
+    if config.sync.server.is_none() || !config.sync.has_token() {
+        say!("⚠ Sync is enabled but not configured.");
+        println!("\nMissing server and/or token in config.");
+        return Ok(());
+    }
+
+    if status_only {
+        let last_sync = db.get_last_sync()?;
+        println!("Sync Status:");
+        println!(
+            "  Server: {}",
+            config.sync.server.as_deref().unwrap_or("not set")
+        );
+        println!("  Enabled: {}", config.sync.enabled);
+        if db.is_sync_paused()? {
+            println!("  Auto-sync: paused (tickit sync resume)");
+        }
+        println!(
+            "  Last sync: {}",
+            last_sync
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "never".to_string())
+        );
+        return Ok(());
+    }
+
+    let core = TickitCore::new(db, config);
+    if !yes && !confirm_first_sync(&core, json)? {
+        return Ok(());
+    }
+
+    if force {
+        say!("⟳ Force syncing (ignoring last_sync)...");
+    } else {
+        say!("⟳ Syncing...");
+    }
+
+    let policy = core.config().sync.conflict_policy;
+    let mut ask = |conflict: &sync::FieldConflict| ask_conflict(conflict);
+    let ask: Option<sync::AskFn> =
+        (policy == ConflictPolicy::Ask && can_prompt() && !json).then_some(&mut ask);
+    // Large syncs go up in batches; show how far along they are
+    let fancy = fancy_output();
+    let mut progress = |p: sync::SyncProgress| {
+        if p.batches < 2 || json {
+            return;
+        }
+        if fancy {
+            print!("\r  Uploaded {}/{} changes", p.sent, p.total);
+            if p.batch == p.batches {
+                println!();
+            }
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        } else {
+            println!(
+                "  Batch {}/{}: uploaded {}/{} changes",
+                p.batch, p.batches, p.sent, p.total
+            );
+        }
+    };
+    match core.sync_with(force, ask, Some(&mut progress)) {
+        Ok(summary) => {
+            println!("  Uploaded {} changes", summary.sent);
+            println!("  Received {} changes from server", summary.received);
+            if summary.conflicts > 0 {
+                say!("  ⚠ {} conflicts (server won)", summary.conflicts);
+            }
+            if summary.kept_local > 0 {
+                say!(
+                    "  ⚠ {} changed on both sides, kept local changes ({}); they go up next sync",
+                    summary.kept_local,
+                    policy.name()
+                );
+            }
+            if summary.missing_blobs > 0 {
+                say!(
+                    "  ⚠ {} attachments not downloaded yet; they're tried again next sync",
+                    summary.missing_blobs
+                );
+            }
+            say!("✓ Sync complete! Applied {} changes.", summary.applied);
+        }
+        Err(e) if json => {
+            anyhow::bail!("Sync failed: {}", e);
+        }
+        Err(e) => {
+            say!("✗ Sync failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the local HTTP API until interrupted
+fn run_serve_command(port: Option<u16>) -> Result<()> {
+    use tickit::serve::{CALENDAR_TOKEN_FILE, TOKEN_FILE, load_or_create_token};
+
+    let config = tickit::Config::load()?;
+    let config_path = tickit::Config::default_path()?;
+    let token_path = |file: &str| config_path.with_file_name(file);
+    let token = match config.serve.token.clone() {
+        Some(token) => {
+            say!("✓ Using the token in config.toml (serve.token)");
+            token
+        }
+        None => {
+            let path = token_path(TOKEN_FILE);
+            let (token, generated) = load_or_create_token(&path)?;
+            if generated {
+                say!("✓ Generated a token and saved it to {}:", path.display());
+                println!("  {}", token);
+            } else {
+                say!("✓ Using the token in {}", path.display());
+            }
+            token
+        }
+    };
+    let calendar_token = match config.serve.calendar_token.clone() {
+        Some(token) => token,
+        None => load_or_create_token(&token_path(CALENDAR_TOKEN_FILE))?.0,
+    };
+    let port = port.unwrap_or(config.serve.port);
+
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| anyhow::anyhow!("Could not listen on 127.0.0.1:{}: {}", port, e))?;
+    let port = listener.local_addr()?.port();
+    say!("⟳ Serving on http://127.0.0.1:{} (Ctrl+C to stop)", port);
+    println!(
+        "  Calendar feed: http://127.0.0.1:{}/calendar.ics?token={}",
+        port, calendar_token
+    );
+    tickit::serve::Server::new(TickitCore::new(Database::open()?, config), token)
+        .with_calendar_token(calendar_token)
+        .run(listener)
+}
+
+/// Run the update command
+fn run_update_command() {
+    use tickit::{
+        VERSION, VersionCheck, check_for_updates_crates_io, detect_package_manager, run_update,
+    };
+
+    say!("✓ Checking for updates...\n");
+
+    let pm = detect_package_manager();
+    println!("  Installed via: {}", pm.name());
+    println!("  Current version: {}", VERSION);
+
+    // Use crates.io API (no rate limits, more reliable)
+    let check = check_for_updates_crates_io();
+
+    match check {
+        VersionCheck::UpdateAvailable { latest, .. } => {
+            println!("  Latest version: {}", latest);
+            say!("\n⬆ Update available! Installing...\n");
+
+            match run_update(&pm) {
+                Ok(()) => {
+                    say!("✓ Successfully updated to {}!", latest);
+                    println!("\nRestart tickit to use the new version.");
+                }
+                Err(e) => {
+                    say!("✗ Update failed: {}", e);
+                    println!("\nYou can manually update with:");
+                    println!("  {}", pm.update_command());
+                    std::process::exit(1);
+                }
+            }
+        }
+        VersionCheck::UpToDate => {
+            say!("\n✓ Already on the latest version!");
+        }
+        VersionCheck::CheckFailed(msg) => {
+            say!("\n⚠ Could not check for updates: {}", msg);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Add a task for every non-empty line of `text`
+///
+/// Each line uses the quick add syntax (`#tag @list !high due:fri`), and
+/// markdown bullets or checkboxes in front of it are dropped, so a todo.md
+/// file can be piped in. The flags give the defaults for every line; unknown
+/// tags are created and an unknown `@list` falls back to the default list.
+fn add_batch(
+    core: &TickitCore,
+    text: &str,
+    list: Option<String>,
+    priority: &str,
+    tags: Option<String>,
+    create_missing: bool,
+    json: bool,
+) -> Result<()> {
+    let default_list = match list {
+        Some(name) => find_or_create_list(core, &name, create_missing)?.id,
+        None => core.db().get_inbox()?.id,
+    };
+    let mut known_lists = core.db().get_lists()?;
+    let mut known_tags = core.db().get_tags()?;
+
+    let default_priority = quickadd::parse_priority(priority).unwrap_or_default();
+    let default_tags: Vec<String> = tags
+        .iter()
+        .flat_map(|t| t.split(','))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut tasks = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        // Markdown headings and blank lines aren't tasks
+        if line.is_empty() || line.starts_with("# ") || line.trim_start_matches('#').is_empty() {
+            continue;
+        }
+        let line = ["- [ ] ", "* [ ] ", "- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .unwrap_or(line);
+
+        let parsed = quickadd::QuickAdd::parse(line);
+        if parsed.title.is_empty() {
+            continue;
+        }
+        // `@work-stuff` is how quick add spells "Work Stuff"
+        let list_id = match parsed.list.as_deref() {
+            Some(name) => {
+                let wanted = name.to_lowercase();
+                match known_lists.iter().find(|l| {
+                    let list_name = l.name.to_lowercase();
+                    list_name == wanted || list_name.replace(' ', "-") == wanted
+                }) {
+                    Some(list) => list.id,
+                    None => {
+                        let list = find_or_create_list(core, name, create_missing)?;
+                        known_lists.push(list.clone());
+                        list.id
+                    }
+                }
+            }
+            None => default_list,
+        };
+
+        let mut task = NewTask::new(&parsed.title, list_id);
+        task.priority = parsed.priority.unwrap_or(default_priority);
+        task.due_date = parsed.due_date;
+        for name in default_tags.iter().chain(&parsed.tags) {
+            let tag_id = match known_tags
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+            {
+                Some(tag) => tag.id,
+                None => {
+                    let tag = find_or_create_tag(core, name, create_missing)?;
+                    known_tags.push(tag.clone());
+                    tag.id
+                }
+            };
+            if !task.tag_ids.contains(&tag_id) {
+                task.tag_ids.push(tag_id);
+            }
+        }
+        tasks.push(task);
+    }
+
+    let tasks = core.add_tasks(tasks)?;
+    report_bulk("✓ Added", "added", &tasks, json)
+}
+
+/// Fields of a new task, as given to `add` or collected by its wizard
+struct AddAnswers {
+    title: String,
+    description: Option<String>,
+    url: Option<String>,
+    priority: String,
+    list: Option<String>,
+    tags: Option<String>,
+    due: Option<String>,
+}
+
+/// Prompt for each field of a new task, using the flags given as defaults
+///
+/// List and tag names can be abbreviated to any unique prefix, and due dates
+/// accept the same words as quick add (`tomorrow`, `fri`, `3d`, ...).
+fn add_wizard(
+    db: &Database,
+    title: Option<String>,
+    priority: String,
+    list: Option<String>,
+) -> Result<AddAnswers> {
+    let lists = db.get_lists()?;
+    let tags = db.get_tags()?;
+    let list_names: Vec<&str> = lists.iter().map(|l| l.name.as_str()).collect();
+    let tag_names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+
+    println!("New task (Enter skips optional fields, Ctrl+D cancels)\n");
+
+    let title = loop {
+        let answer = ask("Title", title.as_deref())?;
+        if !answer.is_empty() {
+            break answer;
+        }
+        println!("  A title is required.");
+    };
+    let description = Some(ask("Description", None)?).filter(|s| !s.is_empty());
+    let url = Some(ask("URL", None)?).filter(|s| !s.is_empty());
+
+    let default_list = list.or_else(|| lists.iter().find(|l| l.is_inbox).map(|l| l.name.clone()));
+    println!("  Lists: {}", list_names.join(", "));
+    let list = loop {
+        let answer = ask("List", default_list.as_deref())?;
+        match complete_name(&answer, &list_names) {
+            Ok(name) => break Some(name.to_string()),
+            Err(hint) => println!("  {}", hint),
+        }
+    };
+
+    let tags = if tag_names.is_empty() {
+        None
+    } else {
+        println!("  Tags: {}", tag_names.join(", "));
+        loop {
+            let answer = ask("Tags (comma-separated)", None)?;
+            let picked: Result<Vec<&str>, String> = answer
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| complete_name(s, &tag_names))
+                .collect();
+            match picked {
+                Ok(names) if names.is_empty() => break None,
+                Ok(names) => break Some(names.join(",")),
+                Err(hint) => println!("  {}", hint),
+            }
+        }
+    };
+
+    let priority = loop {
+        let answer = ask("Priority (low/medium/high/urgent)", Some(&priority))?;
+        match quickadd::parse_priority(&answer) {
+            Some(p) => break p.name().to_lowercase(),
+            None => println!("  Unknown priority: {}", answer),
+        }
+    };
+
+    let due = loop {
+        let answer = ask("Due (e.g. tomorrow, fri, 3d, 2026-01-31)", None)?;
+        if answer.is_empty() {
+            break None;
+        }
+        match quickadd::parse_due_date(&answer) {
+            Some(date) => {
+                println!("  → {}", format_date(date.date_naive()));
+                break Some(date.format("%Y-%m-%d").to_string());
+            }
+            None => println!("  Couldn't read that date: {}", answer),
+        }
+    };
+
+    Ok(AddAnswers {
+        title,
+        description,
+        url,
+        priority,
+        list,
+        tags,
+        due,
+    })
+}
+
+/// The export passphrase, from `TICKIT_PASSPHRASE` or typed without echo;
+/// `confirm` asks for it twice
+fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var("TICKIT_PASSPHRASE")
+        && !passphrase.is_empty()
+    {
+        return Ok(passphrase);
+    }
+    if !can_prompt() {
+        anyhow::bail!("Set TICKIT_PASSPHRASE to encrypt or decrypt without a terminal");
+    }
+    let passphrase = read_hidden("Passphrase: ")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("The passphrase can't be empty");
+    }
+    if confirm && read_hidden("Repeat passphrase: ")? != passphrase {
+        anyhow::bail!("The passphrases don't match");
+    }
+    Ok(passphrase)
+}
+
+/// Read a line from the terminal without echoing it
+fn read_hidden(label: &str) -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::io::Write;
+
+    eprint!("{}", label);
+    std::io::stderr().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let mut input = String::new();
+    let result = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(input),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(anyhow::anyhow!("Cancelled"));
+            }
+            KeyCode::Esc => break Err(anyhow::anyhow!("Cancelled")),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    };
+    let _ = crossterm::terminal::disable_raw_mode();
+    eprintln!();
+    result
+}
+
+/// Show what the first sync of a device that already has tasks will do and
+/// ask before running it; true to go ahead
+fn confirm_first_sync(core: &TickitCore, json: bool) -> Result<bool> {
+    use std::io::{self, Write};
+
+    let Some(preview) = core.sync_preview()? else {
+        return Ok(true);
+    };
+    if json || !can_prompt() {
+        anyhow::bail!(
+            "First sync would {}. Pass --yes to run it",
+            preview.describe()
+        );
+    }
+    say!(
+        "⚠ First sync from this device: it will {}.",
+        preview.describe()
+    );
+    if preview.duplicates > 0 {
+        println!(
+            "  Tasks, lists and tags created separately here and on the server are kept twice."
+        );
+    }
+    print!("Sync now? [y/N] ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let confirmed = input.trim().eq_ignore_ascii_case("y");
+    if !confirmed {
+        println!("Cancelled.");
+    }
+    Ok(confirmed)
+}
+
+/// Ask which side of a sync conflict to keep; Enter keeps the server's
+fn ask_conflict(conflict: &tickit::sync::FieldConflict) -> tickit::sync::Side {
+    use tickit::sync::Side;
+
+    println!(
+        "Conflict in the {} of \"{}\":",
+        conflict.field, conflict.task.title
+    );
+    println!("  [l] local:  {}", conflict.local);
+    println!("  [r] server: {}", conflict.remote);
+    match ask("Keep", Some("r")) {
+        Ok(answer) if answer.eq_ignore_ascii_case("l") => Side::Local,
+        _ => Side::Remote,
+    }
+}
+
+/// Print `label [default]: ` and read a trimmed answer, falling back to the default
+fn ask(label: &str, default: Option<&str>) -> Result<String> {
+    use std::io::{self, Write};
+
+    match default {
+        Some(default) => print!("{} [{}]: ", label, default),
+        None => print!("{}: ", label),
+    }
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        println!();
+        anyhow::bail!("Cancelled");
+    }
+    let answer = input.trim();
+    Ok(match default {
+        Some(default) if answer.is_empty() => default.to_string(),
+        _ => answer.to_string(),
+    })
+}
+
+/// The name in `names` that `input` spells out or uniquely starts (case-insensitive)
+fn complete_name<'a>(input: &str, names: &[&'a str]) -> Result<&'a str, String> {
+    let input_lower = input.to_lowercase();
+    if let Some(exact) = names.iter().find(|n| n.to_lowercase() == input_lower) {
+        return Ok(exact);
+    }
+    let matches: Vec<&str> = names
+        .iter()
+        .filter(|n| n.to_lowercase().starts_with(&input_lower))
+        .copied()
+        .collect();
+    match matches.as_slice() {
+        [name] => Ok(name),
+        [] => Err(format!("No match for \"{}\"", input)),
+        _ => Err(format!("\"{}\" could be: {}", input, matches.join(", "))),
+    }
+}
+
+/// Print overdue tasks and tasks due today, grouped by list
+fn print_today(db: &Database, json: bool) -> Result<()> {
+    let lists = db.get_lists()?;
+    let today = chrono::Local::now().date_naive();
+
+    let open = db.get_tasks_with_filter(None, Some(false), None)?;
+    let (overdue, due_today) = due_by(&open, today);
+    let is_overdue = |t: &Task| overdue.iter().any(|o| o.id == t.id);
+    let tasks: Vec<&Task> = overdue.iter().chain(&due_today).copied().collect();
+
+    if json {
+        let agenda = serde_json::json!({ "overdue": overdue, "today": due_today });
+        println!("{}", serde_json::to_string_pretty(&agenda)?);
+        return Ok(());
+    }
+
+    let color = use_color();
+    println!(
+        "{}",
+        paint(
+            &with_icon("📅", &today.format("%A, %b %-d").to_string()),
+            Color::Cyan,
+            color
+        )
+    );
+    if tasks.is_empty() {
+        println!("\nNothing due today.");
+        return Ok(());
+    }
+
+    for list in &lists {
+        let in_list: Vec<&Task> = tasks
+            .iter()
+            .copied()
+            .filter(|t| t.list_id == list.id)
+            .collect();
+        if in_list.is_empty() {
+            continue;
+        }
+        println!(
+            "\n{}",
+            with_icon(&list.icon, &paint(&list.name, Color::Blue, color))
+        );
+        for task in in_list {
+            let due = if is_overdue(task) {
+                let date = task.due_date.map_or(today, |d| d.date_naive());
+                let label = if date == today {
+                    "  overdue".to_string()
+                } else {
+                    format!("  overdue {}", format_date(date))
+                };
+                paint(&label, Color::Red, color)
+            } else {
+                String::new()
+            };
+            println!("  {}{}", agenda_row(task, color), due);
+        }
+    }
+    Ok(())
+}
+
+/// Print counts of overdue and due-today tasks and the next task to do, in
+/// a format a status bar or prompt can show
+fn print_status(format: &str, json: bool) -> Result<()> {
+    // Status bars run this every few seconds, so read without schema setup;
+    // a database from an older version still has to be migrated once
+    let mut tasks = match Database::open_read_only()
+        .and_then(|db| db.get_tasks_with_filter(None, Some(false), None))
+    {
+        Ok(tasks) => tasks,
+        Err(_) => Database::open()?.get_tasks_with_filter(None, Some(false), None)?,
+    };
+    let today = chrono::Local::now().date_naive();
+
+    // Soonest due first, then the most important
+    tasks.sort_by_key(|t| {
+        (
+            t.due_date.is_none(),
+            t.due_date,
+            std::cmp::Reverse(t.priority),
+        )
+    });
+    let (overdue, due_today) = due_by(&tasks, today);
+    let next = tasks.first();
+
+    if json {
+        let status = serde_json::json!({
+            "overdue": overdue.len(),
+            "today": due_today.len(),
+            "next": next,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    let title = |t: &Task| {
+        if t.title.chars().count() > 40 {
+            format!("{}…", t.title.chars().take(39).collect::<String>())
+        } else {
+            t.title.clone()
+        }
+    };
+    let tmux = format == "tmux";
+    let mut parts = Vec::new();
+    if !overdue.is_empty() {
+        let text = format!("{} overdue", overdue.len());
+        parts.push(if tmux {
+            format!("#[fg=red]{}#[default]", text)
+        } else {
+            text
+        });
+    }
+    if !due_today.is_empty() {
+        let text = format!("{} today", due_today.len());
+        parts.push(if tmux {
+            format!("#[fg=yellow]{}#[default]", text)
+        } else {
+            text
+        });
+    }
+    match next {
+        // tmux reads `#` as the start of a format
+        Some(t) if tmux => parts.push(format!("next: {}", title(t).replace('#', "##"))),
+        Some(t) => parts.push(format!("next: {}", title(t))),
+        None => parts.push("all done".to_string()),
+    }
+    let text = parts.join(" · ");
+
+    if format == "waybar" {
+        let tooltip: Vec<String> = overdue
+            .iter()
+            .map(|t| format!("overdue: {}", t.title))
+            .chain(due_today.iter().map(|t| format!("today: {}", t.title)))
+            .collect();
+        let class = if !overdue.is_empty() {
+            "overdue"
+        } else if !due_today.is_empty() {
+            "due"
+        } else {
+            "clear"
+        };
+        let bar = serde_json::json!({
+            "text": text,
+            "tooltip": tooltip.join("\n"),
+            "class": class,
+            "alt": class,
+        });
+        println!("{}", bar);
+    } else {
+        println!("{}", text);
+    }
+    Ok(())
+}
+
+/// Print open tasks due in the next `days` days, grouped by day
+fn print_upcoming(db: &Database, days: u32, json: bool) -> Result<()> {
+    let lists = db.get_lists()?;
+    let today = chrono::Local::now().date_naive();
+    let last = today + chrono::Duration::days(i64::from(days));
 
-            // Export
-            if let Some(path) = output {
-                let mut file = std::fs::File::create(&path)?;
-                tickit::export::export_tasks(&mut file, &tasks, &lists, &tags, fmt)?;
-                println!("Exported {} tasks to {}", tasks.len(), path.display());
-            } else {
-                let mut stdout = std::io::stdout();
-                tickit::export::export_tasks(&mut stdout, &tasks, &lists, &tags, fmt)?;
-            }
-        }
+    let mut tasks: Vec<Task> = db
+        .get_tasks_with_filter(None, Some(false), None)?
+        .into_iter()
+        .filter(|t| {
+            t.due_date
+                .map(|d| d.date_naive())
+                .is_some_and(|d| d > today && d <= last)
+        })
+        .collect();
+    tasks.sort_by_key(|t| t.due_date);
 
-        Some(Commands::Update) => {
-            run_update_command();
-        }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tasks)?);
+        return Ok(());
+    }
 
-        Some(Commands::Sync { status, force }) => {
-            run_sync_command(status, force)?;
-        }
+    let color = use_color();
+    if tasks.is_empty() {
+        println!("Nothing due in the next {} days.", days);
+        return Ok(());
     }
 
+    let mut current = None;
+    for task in &tasks {
+        let date = task.due_date.map(|d| d.date_naive());
+        if date != current {
+            if current.is_some() {
+                println!();
+            }
+            current = date;
+            let heading = date.map_or(String::new(), |d| d.format("%a %b %-d").to_string());
+            println!("{}", paint(&heading, Color::Cyan, color));
+        }
+        let list_name = lists
+            .iter()
+            .find(|l| l.id == task.list_id)
+            .map(|l| l.name.as_str())
+            .unwrap_or("?");
+        println!(
+            "  {} {}",
+            agenda_row(task, color),
+            paint(&format!("[{}]", list_name), Color::DarkGrey, color)
+        );
+    }
     Ok(())
 }
 
-/// Run the sync command
-fn run_sync_command(status_only: bool, force: bool) -> Result<()> {
-    use tickit::{
-        Config, Database,
-        sync::{SyncClient, SyncRecord},
+/// A task as `☐ ● title`, with urgent and high priorities colored
+fn agenda_row(task: &Task, color: bool) -> String {
+    let icon = match task.priority {
+        Priority::Urgent => paint(priority_icon(task.priority), Color::Red, color),
+        Priority::High => paint(priority_icon(task.priority), Color::Yellow, color),
+        _ => priority_icon(task.priority).to_string(),
     };
+    format!("{} {} {}", checkbox(false), icon, task.title)
+}
 
-    let config = Config::load()?;
-    let db = Database::open()?;
+/// Open tasks due by `today`, split into overdue ones and ones due today
+///
+/// Due dates are stored as the last second of their day in UTC, so they're
+/// compared by that date; converted to local time they could land on
+/// another day.
+fn due_by(tasks: &[Task], today: chrono::NaiveDate) -> (Vec<&Task>, Vec<&Task>) {
+    tasks
+        .iter()
+        .filter(|t| t.due_date.is_some_and(|d| d.date_naive() <= today))
+        .partition(|t| t.due_date.is_some_and(|d| d.date_naive() < today))
+}
 
-    if !config.sync.enabled {
-        println!("⚠ Sync is disabled in config.");
-        println!("\nTo enable sync, add to ~/.config/tickit/config.toml:");
-        println!();
-        println!("  [sync]");
-        println!("  enabled = true");
-        println!("  server = \"http://your-server:3030\"");
-        println!("  token = \"your-token\"");
+/// Whether to color output: stdout is a terminal and `NO_COLOR` isn't set
+fn use_color() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// `text` in `color`, or unchanged when color is off
+fn paint(text: &str, color: Color, enabled: bool) -> String {
+    if enabled {
+        text.with(color).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Print task counts and streaks, and with `heatmap` the year's completions
+fn print_stats(
+    db: &Database,
+    heatmap: bool,
+    burndown: Option<((tickit::stats::Scope, String), usize)>,
+    json: bool,
+) -> Result<()> {
+    use tickit::stats::{Burndown, Completions, HEATMAP_WEEKS, Heatmap, Summary, shade_glyph};
+
+    let today = chrono::Local::now().date_naive();
+    let week_start = display_config().week_starts_on;
+    let completions = Completions::load(db)?;
+    let open = db.get_total_task_count(false)?.max(0) as u32;
+    let summary = Summary::new(open, &completions, today, week_start);
+    let burndown = burndown
+        .map(|((scope, name), days)| anyhow::Ok((name, Burndown::load(db, scope, today, days)?)))
+        .transpose()?;
+
+    if json {
+        let mut value = serde_json::to_value(summary)?;
+        if heatmap {
+            let since = today - chrono::Duration::weeks(HEATMAP_WEEKS as i64);
+            value["completions_by_day"] = completions
+                .days()
+                .filter(|(day, _)| *day > since)
+                .map(|(day, count)| (day.to_string(), serde_json::json!(count)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        if let Some((_, burndown)) = &burndown {
+            value["burndown"] = serde_json::to_value(&burndown.days)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
         return Ok(());
     }
 
-    if config.sync.server.is_none() || config.sync.token.is_none() {
-        println!("⚠ Sync is enabled but not configured.");
-        println!("\nMissing server and/or token in config.");
+    let days = |n: u32| format!("{} {}", n, if n == 1 { "day" } else { "days" });
+    println!("Open tasks:      {}", summary.open);
+    println!(
+        "Completed:       {} ({} in the last year, {} this week)",
+        summary.completed, summary.completed_last_year, summary.completed_this_week
+    );
+    println!("Current streak:  {}", days(summary.current_streak));
+    println!("Longest streak:  {}", days(summary.longest_streak));
+    if let Some((name, burndown)) = &burndown {
+        print_burndown(name, burndown);
+    }
+    if !heatmap {
         return Ok(());
     }
 
-    let mut client = SyncClient::new(config.sync.clone());
+    let map = Heatmap::new(&completions, today, week_start);
+    let unicode = fancy_output();
+    let color = use_color();
+    let cell = |shade: u8| {
+        let glyph = shade_glyph(shade, unicode).to_string();
+        let tint = match shade {
+            0 => Color::DarkGrey,
+            1 | 2 => Color::DarkGreen,
+            _ => Color::Green,
+        };
+        paint(&glyph, tint, color)
+    };
+    println!();
+    println!("    {}", map.month_labels());
+    for (row, shades) in map.rows.iter().enumerate() {
+        // Label every other weekday, like GitHub
+        let label = if row % 2 == 0 {
+            map.row_day(row).to_string()
+        } else {
+            String::new()
+        };
+        let cells: String = shades
+            .iter()
+            .map(|shade| shade.map_or(" ".to_string(), cell))
+            .collect();
+        println!("{:<4}{}", label, cells);
+    }
+    let legend: Vec<String> = (0..=4).map(cell).collect();
+    println!("    Less {} More", legend.join(" "));
+    Ok(())
+}
 
-    if status_only {
-        let last_sync = db.get_last_sync()?;
-        println!("Sync Status:");
+/// The first line of `text`, marked when more lines follow
+fn first_line(text: &str) -> String {
+    match text.split_once('\n') {
+        Some((first, _)) => format!("{} …", first),
+        None => text.to_string(),
+    }
+}
+
+/// A row per list or tag, flagging unused ones, then the tags with
+/// look-alike names in `similar`
+fn print_usage(
+    kind: &str,
+    usage: &[tickit::stats::Usage],
+    similar: &[Vec<String>],
+    json: bool,
+) -> Result<()> {
+    if json {
+        let mut value = serde_json::json!({ kind.to_string() + "s": usage });
+        if kind == "tag" {
+            value["similar"] = serde_json::to_value(similar)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    if usage.is_empty() {
+        println!("No {}s yet.", kind);
+        return Ok(());
+    }
+
+    let color = use_color();
+    let width = usage
+        .iter()
+        .map(|u| u.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!(
+        "{:<width$}  {:>5}  {:>5}  {:>5}  {:>4}  Last activity",
+        "Name", "Tasks", "Open", "Done", "Rate"
+    );
+    for u in usage {
+        let rate = u
+            .completion_rate
+            .map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+        let last = u
+            .last_activity
+            .map_or(paint("unused", Color::Yellow, color), |at| {
+                format_date(at.with_timezone(&chrono::Local).date_naive())
+            });
         println!(
-            "  Server: {}",
-            config.sync.server.as_deref().unwrap_or("not set")
+            "{:<width$}  {:>5}  {:>5}  {:>5}  {:>4}  {}",
+            u.name, u.tasks, u.open, u.completed, rate, last
         );
-        println!("  Enabled: {}", config.sync.enabled);
+    }
+
+    let unused: Vec<&str> = usage
+        .iter()
+        .filter(|u| u.tasks == 0)
+        .map(|u| u.name.as_str())
+        .collect();
+    if !unused.is_empty() {
+        println!();
         println!(
-            "  Last sync: {}",
-            last_sync
-                .map(|t| t.to_string())
-                .unwrap_or_else(|| "never".to_string())
+            "Unused: {} (`tickit {}s delete` removes one)",
+            unused.join(", "),
+            kind
         );
-        return Ok(());
     }
-
-    if force {
-        println!("⟳ Force syncing (ignoring last_sync)...");
-    } else {
-        println!("⟳ Syncing...");
+    for names in similar {
+        println!(
+            "Similar: {} (`tickit tags merge` combines them)",
+            names.join(", ")
+        );
     }
+    Ok(())
+}
 
-    // Gather local changes - use None for force sync to get everything
-    let last_sync = if force { None } else { db.get_last_sync()? };
-    let mut changes: Vec<SyncRecord> = Vec::new();
+/// A bar per day: open tasks, then the ones done since the first day
+fn print_burndown(name: &str, burndown: &tickit::stats::Burndown) {
+    const WIDTH: u32 = 40;
 
-    // Get all data for full sync, or changes since last sync
-    let tasks = if let Some(since) = last_sync {
-        db.get_tasks_since(since)?
+    let color = use_color();
+    let (open_bar, done_bar) = if fancy_output() {
+        ("█", "░")
     } else {
-        db.get_all_tasks()?
+        ("#", "=")
     };
-    for task in tasks {
-        changes.push(SyncRecord::Task(task));
+    let most = burndown.most().max(1);
+    println!();
+    println!("Burndown of {}, last {} days:", name, burndown.days.len());
+    for day in &burndown.days {
+        // Scale the open + done total, so bars only round down once
+        let total = (day.open + day.done) * WIDTH / most;
+        let open = day.open * WIDTH / most;
+        println!(
+            "  {}  {}{}{} {} open, {} done",
+            day.day.format("%b %d"),
+            paint(&open_bar.repeat(open as usize), Color::Yellow, color),
+            paint(
+                &done_bar.repeat((total - open) as usize),
+                Color::Green,
+                color
+            ),
+            " ".repeat((WIDTH - total) as usize),
+            day.open,
+            day.done
+        );
     }
+}
 
-    let lists = if let Some(since) = last_sync {
-        db.get_lists_since(since)?
-    } else {
-        db.get_lists()?
+/// Print all fields of a task, its tags, list and dependencies
+fn show_task(db: &Database, tasks: &[Task], task: &Task, json: bool) -> Result<()> {
+    use tickit::deps::{self, Direction};
+
+    let lists = db.get_lists()?;
+    let tags = db.get_tags()?;
+    let edges = db.get_dependencies()?;
+    let comments = db.get_comments(task.id)?;
+    let attachments = attachment_paths(db, task.id)?;
+    let links = tickit::autolink::task_links(task, &display_config().links);
+
+    let list = lists.iter().find(|l| l.id == task.list_id);
+    let tag_names: Vec<&str> = tags
+        .iter()
+        .filter(|t| task.tag_ids.contains(&t.id))
+        .map(|t| t.name.as_str())
+        .collect();
+    let title_of = |id: uuid::Uuid| {
+        tasks
+            .iter()
+            .find(|t| t.id == id)
+            .map_or("?", |t| t.title.as_str())
     };
-    for list in lists {
-        changes.push(SyncRecord::List(list));
+
+    if json {
+        let related = |direction| {
+            deps::neighbours(&edges, task.id, direction)
+                .into_iter()
+                .map(|id| serde_json::json!({ "id": id, "title": title_of(id) }))
+                .collect::<Vec<_>>()
+        };
+        let mut value = serde_json::to_value(task)?;
+        value["list"] = serde_json::json!(list.map(|l| &l.name));
+        value["tags"] = serde_json::json!(tag_names);
+        value["blocked_by"] = serde_json::json!(related(Direction::BlockedBy));
+        value["blocks"] = serde_json::json!(related(Direction::Blocks));
+        value["history"] = db
+            .get_audit(task.id)?
+            .into_iter()
+            .map(|(at, message)| serde_json::json!({ "at": at, "message": message }))
+            .collect();
+        value["comments"] = serde_json::to_value(&comments)?;
+        value["attachments"] = attachments
+            .iter()
+            .map(|(attachment, path)| {
+                let mut value = serde_json::json!(attachment);
+                value["path"] = serde_json::json!(path);
+                value
+            })
+            .collect();
+        value["links"] = links
+            .iter()
+            .map(|link| serde_json::json!({ "label": link.label, "url": link.url }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
     }
 
-    let tags = if let Some(since) = last_sync {
-        db.get_tags_since(since)?
-    } else {
-        db.get_tags()?
-    };
-    for tag in tags {
-        changes.push(SyncRecord::Tag(tag));
-    }
-
-    // Get tombstones
-    if let Some(since) = last_sync {
-        let tombstones = db.get_tombstones_since(since)?;
-        for tomb in tombstones {
-            let record_type = match tomb.1.as_str() {
-                "task" => tickit::sync::RecordType::Task,
-                "list" => tickit::sync::RecordType::List,
-                "tag" => tickit::sync::RecordType::Tag,
-                "task_tag" => tickit::sync::RecordType::TaskTag,
-                _ => continue,
-            };
-            changes.push(SyncRecord::Deleted {
-                id: tomb.0,
-                record_type,
-                deleted_at: tomb.2,
-            });
+    let checkbox = checkbox(task.completed);
+    let local = format_timestamp;
+
+    println!("{} {}", checkbox, task.title);
+    println!();
+    println!("  ID:        {}", task.id);
+    println!(
+        "  List:      {}",
+        list.map_or("?".to_string(), |l| with_icon(&l.icon, &l.name))
+    );
+    println!(
+        "  Priority:  {}",
+        with_icon(task.priority.icon(), task.priority.name())
+    );
+    if !tag_names.is_empty() {
+        println!("  Tags:      {}", tag_names.join(", "));
+    }
+    if let Some(due) = task.due_date {
+        println!("  Due:       {}", format_date(due.date_naive()));
+    }
+    if let Some(url) = &task.url {
+        println!("  URL:       {}", url);
+    }
+    // Links found in the title and description, past the URL above
+    for link in links
+        .iter()
+        .filter(|link| Some(&link.url) != task.url.as_ref())
+    {
+        if link.label == link.url {
+            println!("  Link:      {}", link.url);
+        } else {
+            println!("  Link:      {} → {}", link.label, link.url);
         }
     }
+    println!("  Created:   {}", local(task.created_at));
+    println!("  Updated:   {}", local(task.updated_at));
+    if let Some(completed_at) = task.completed_at {
+        println!("  Completed: {}", local(completed_at));
+    }
 
-    println!("  Uploading {} changes...", changes.len());
-
-    // Sync - pass None for force sync to get all changes from server
-    match client.sync(changes, if force { None } else { db.get_last_sync()? }) {
-        Ok(response) => {
-            println!("  Received {} changes from server", response.changes.len());
+    // Comments are numbered for `tickit comment --delete N`
+    let mut history = db.get_audit(task.id)?;
+    history.extend(
+        comments
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.created_at, format!("💬 [{}] {}", i + 1, c.body))),
+    );
+    history.sort_by_key(|(at, _)| *at);
+    if !history.is_empty() {
+        println!();
+        println!("  History:");
+        for (at, message) in &history {
+            println!("    {}  {}", local(*at), message);
+        }
+    }
 
-            // Sort changes: lists first, then tags, then tasks (to satisfy FK constraints)
-            let mut lists = Vec::new();
-            let mut tags = Vec::new();
-            let mut tasks = Vec::new();
-            let mut deletes = Vec::new();
+    // Numbered for `tickit attach --delete N`
+    if !attachments.is_empty() {
+        println!();
+        println!("  Attachments:");
+        for (i, line) in attachment_lines(&attachments).iter().enumerate() {
+            println!("    [{}] {}", i + 1, line);
+        }
+    }
 
-            for record in response.changes {
-                match &record {
-                    SyncRecord::List(_) => lists.push(record),
-                    SyncRecord::Tag(_) => tags.push(record),
-                    SyncRecord::Task(_) => tasks.push(record),
-                    SyncRecord::Deleted { .. } => deletes.push(record),
-                    _ => {}
-                }
+    for (heading, direction) in [
+        ("Blocked by", Direction::BlockedBy),
+        ("Blocks", Direction::Blocks),
+    ] {
+        let rows = deps::tree(&edges, task.id, direction);
+        if !rows.is_empty() {
+            println!();
+            println!("  {}:", heading);
+            for (prefix, id) in rows {
+                println!("    {}{}", prefix, title_of(id));
             }
+        }
+    }
 
-            // Disable FK constraints during sync
-            let _ = db.execute_raw("PRAGMA foreign_keys = OFF");
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        println!();
+        for line in description.lines() {
+            println!("  {}", line);
+        }
+    }
 
-            // Apply incoming changes in order
-            let mut applied = 0;
-            for record in lists.into_iter().chain(tags).chain(tasks).chain(deletes) {
-                let result = match record {
-                    SyncRecord::Task(task) => db.upsert_task(&task),
-                    SyncRecord::List(list) => db.upsert_list(&list),
-                    SyncRecord::Tag(tag) => db.upsert_tag(&tag),
-                    SyncRecord::Deleted {
-                        id, record_type, ..
-                    } => {
-                        match record_type {
-                            tickit::sync::RecordType::Task => {
-                                let _ = db.delete_task(id);
-                            }
-                            tickit::sync::RecordType::List => {
-                                let _ = db.delete_list(id);
-                            }
-                            tickit::sync::RecordType::Tag => {
-                                let _ = db.delete_tag(id);
-                            }
-                            _ => {}
-                        }
-                        Ok(())
-                    }
-                    _ => Ok(()),
-                };
-                if result.is_ok() {
-                    applied += 1;
-                }
-            }
+    Ok(())
+}
 
-            // Re-enable FK constraints
-            let _ = db.execute_raw("PRAGMA foreign_keys = ON");
+/// The attachments of a task with where their files are stored, `None`
+/// while sync hasn't downloaded one yet
+fn attachment_paths(
+    db: &Database,
+    task_id: uuid::Uuid,
+) -> Result<Vec<(Attachment, Option<PathBuf>)>> {
+    let store = tickit::sync::BlobStore::for_database(db).ok();
+    Ok(db
+        .get_attachments(task_id)?
+        .into_iter()
+        .map(|attachment| {
+            let path = store
+                .as_ref()
+                .filter(|store| store.contains(&attachment.hash))
+                .and_then(|store| store.path(&attachment.hash).ok());
+            (attachment, path)
+        })
+        .collect())
+}
+
+/// "name (size)  path" for each attachment
+fn attachment_lines(attachments: &[(Attachment, Option<PathBuf>)]) -> Vec<String> {
+    attachments
+        .iter()
+        .map(|(attachment, path)| {
+            let path = path.as_ref().map_or_else(
+                || "not downloaded yet".to_string(),
+                |path| path.display().to_string(),
+            );
+            format!(
+                "{} ({})  {}",
+                attachment.name,
+                format_size(attachment.size),
+                path
+            )
+        })
+        .collect()
+}
+
+/// A byte count in B, KiB or MiB
+fn format_size(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    match bytes as f64 {
+        b if b < KIB => format!("{} B", bytes),
+        b if b < KIB * KIB => format!("{:.1} KiB", b / KIB),
+        b => format!("{:.1} MiB", b / (KIB * KIB)),
+    }
+}
+
+/// Give `lists` consecutive sort orders from 0, returning those that changed
+///
+/// The inbox keeps its own `sort_order` of -1 so it stays first.
+fn renumber_lists(lists: Vec<List>) -> Vec<List> {
+    lists
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, mut list)| {
+            (list.sort_order != i as i32).then(|| {
+                list.sort_order = i as i32;
+                list
+            })
+        })
+        .collect()
+}
+
+/// The list called `name` (case-insensitive), created when missing if `create` is set
+fn find_or_create_list(core: &TickitCore, name: &str, create: bool) -> Result<List> {
+    if let Some(list) = core.list_named(name)? {
+        return Ok(list);
+    }
+    if !create {
+        return Err(LookupError::NotFound(format!(
+            "List not found: {} (use --create-missing to create it)",
+            name
+        ))
+        .into());
+    }
+    let list = core.create_list(name, None)?;
+    say!("✓ Created list: {}", name);
+    Ok(list)
+}
+
+/// The tag called `name` (case-insensitive), created when missing if `create` is set
+fn find_or_create_tag(core: &TickitCore, name: &str, create: bool) -> Result<Tag> {
+    if let Some(tag) = core.tag_named(name)? {
+        return Ok(tag);
+    }
+    if !create {
+        return Err(LookupError::NotFound(format!(
+            "Tag not found: {} (use --create-missing to create it)",
+            name
+        ))
+        .into());
+    }
+    let tag = core.create_tag(name, None)?;
+    say!("✓ Created tag: {}", name);
+    Ok(tag)
+}
+
+/// Report a missing task, list or tag as an error, so scripts see exit code 3
+fn not_found<T>(message: String) -> Result<T> {
+    Err(LookupError::NotFound(message).into())
+}
 
-            // Update last sync time
-            db.set_last_sync(response.server_time)?;
+/// Tasks named in `selection`, or matching its filters
+///
+/// Returns `None` if picking between several matching tasks was cancelled,
+/// so nothing is changed.
+fn resolve_selection(
+    db: &Database,
+    selection: &TaskSelection,
+    json: bool,
+) -> Result<Option<Vec<Task>>> {
+    if selection.tasks.is_empty() && !selection.is_filter() {
+        anyhow::bail!("Name one or more tasks, or pick them with --list, --tag or --completed");
+    }
 
-            if !response.conflicts.is_empty() {
-                println!("  ⚠ {} conflicts (server won)", response.conflicts.len());
+    let list_id = match &selection.list {
+        Some(name) => match db
+            .get_lists()?
+            .into_iter()
+            .find(|l| l.name.to_lowercase() == name.to_lowercase())
+        {
+            Some(l) => Some(l.id),
+            None => {
+                return not_found(format!("List not found: {}", name));
             }
+        },
+        None => None,
+    };
+    let tag_id = match &selection.tag {
+        Some(name) => match db
+            .get_tags()?
+            .into_iter()
+            .find(|t| t.name.to_lowercase() == name.to_lowercase())
+        {
+            Some(t) => Some(t.id),
+            None => {
+                return not_found(format!("Tag not found: {}", name));
+            }
+        },
+        None => None,
+    };
 
-            println!("✓ Sync complete! Applied {} changes.", applied);
-        }
-        Err(e) => {
-            println!("✗ Sync failed: {}", e);
-            std::process::exit(1);
+    let completed = selection.completed.then_some(true);
+    let candidates = if selection.is_filter() {
+        db.get_tasks_with_filter(list_id, completed, tag_id)?
+    } else {
+        db.get_all_tasks()?
+    };
+    if selection.tasks.is_empty() {
+        return Ok(Some(candidates));
+    }
+
+    let mut picked: Vec<Task> = Vec::new();
+    for query in &selection.tasks {
+        let Some(task) = select_task(&candidates, query, json)? else {
+            return Ok(None);
+        };
+        if !picked.iter().any(|t| t.id == task.id) {
+            picked.push(task);
         }
     }
+    Ok(Some(picked))
+}
+
+/// List `tasks` and ask before `action` is applied to all of them
+///
+/// Returns whether to go ahead. When prompting isn't possible, `--yes` is required.
+fn confirm_bulk(action: &str, tasks: &[Task], yes: bool) -> Result<bool> {
+    use std::io::{self, Write};
+
+    if yes || tasks.is_empty() {
+        return Ok(true);
+    }
+    if !can_prompt() {
+        anyhow::bail!(
+            "{} {} task(s)? Pass --yes to confirm without prompting",
+            action,
+            tasks.len()
+        );
+    }
 
-    Ok(())
+    for t in tasks {
+        let checkbox = checkbox(t.completed);
+        println!("  {}  {} {}", short_id(t), checkbox, t.title);
+    }
+    print!("{} {} tasks? [y/N] ", action, tasks.len());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let confirmed = input.trim().eq_ignore_ascii_case("y");
+    if !confirmed {
+        println!("Cancelled.");
+    }
+    Ok(confirmed)
 }
 
-/// Run the update command
-fn run_update_command() {
-    use tickit::{
-        VERSION, VersionCheck, check_for_updates_crates_io, detect_package_manager, run_update,
-    };
+/// Whether to go on with a command on `tasks`, which `--dry-run`, or a
+/// selection over `[confirm] bulk_limit` without `--yes`, only lists
+fn preview_bulk(past: &str, tasks: &[Task], yes: bool, dry_run: bool, json: bool) -> Result<bool> {
+    let limit = display_config().confirm.bulk_limit;
+    let too_many = !yes && limit > 0 && tasks.len() > limit;
+    if !dry_run && !too_many {
+        return Ok(true);
+    }
+    report_dry_run(past, tasks, json)?;
+    if too_many && !dry_run {
+        eprintln!(
+            "Nothing changed: that's more than {} tasks ([confirm] bulk_limit). Pass --yes to go ahead.",
+            limit
+        );
+    }
+    Ok(false)
+}
 
-    println!("✓ Checking for updates...\n");
+/// List the tasks a `--dry-run` would change, and how many
+fn report_dry_run(past: &str, tasks: &[Task], json: bool) -> Result<()> {
+    if json {
+        let summary = serde_json::json!({
+            "dry_run": true,
+            "tasks": tasks
+                .iter()
+                .map(|t| serde_json::json!({ "id": t.id, "title": t.title }))
+                .collect::<Vec<_>>(),
+            "count": tasks.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
 
-    let pm = detect_package_manager();
-    println!("  Installed via: {}", pm.name());
-    println!("  Current version: {}", VERSION);
+    for t in tasks {
+        println!("  {}  {} {}", short_id(t), checkbox(t.completed), t.title);
+    }
+    println!("{} task(s) would be {}.", tasks.len(), past);
+    Ok(())
+}
 
-    // Use crates.io API (no rate limits, more reliable)
-    let check = check_for_updates_crates_io();
+/// Summarize what a bulk command did, one line per task
+fn report_bulk(prefix: &str, past: &str, tasks: &[Task], json: bool) -> Result<()> {
+    if json {
+        let summary = serde_json::json!({
+            past: tasks
+                .iter()
+                .map(|t| serde_json::json!({ "id": t.id, "title": t.title }))
+                .collect::<Vec<_>>(),
+            "count": tasks.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
 
-    match check {
-        VersionCheck::UpdateAvailable { latest, .. } => {
-            println!("  Latest version: {}", latest);
-            println!("\n⬆ Update available! Installing...\n");
+    if tasks.is_empty() {
+        println!("No tasks to change.");
+        return Ok(());
+    }
+    for t in tasks {
+        say!("{}: {}", prefix, t.title);
+    }
+    if tasks.len() > 1 {
+        println!("{} tasks {}.", tasks.len(), past);
+    }
+    Ok(())
+}
 
-            match run_update(&pm) {
-                Ok(()) => {
-                    println!("✓ Successfully updated to {}!", latest);
-                    println!("\nRestart tickit to use the new version.");
-                }
-                Err(e) => {
-                    println!("✗ Update failed: {}", e);
-                    println!("\nYou can manually update with:");
-                    println!("  {}", pm.update_command());
-                    std::process::exit(1);
-                }
-            }
-        }
-        VersionCheck::UpToDate => {
-            println!("\n✓ Already on the latest version!");
+/// Resolve `query` to a single task
+///
+/// If several tasks match, asks which one on a terminal, and otherwise fails
+/// with the candidates listed. Returns `None` if the user cancelled.
+fn select_task(tasks: &[Task], query: &str, json: bool) -> Result<Option<Task>> {
+    use std::io::{self, Write};
+
+    let candidates = find_tasks(tasks, query);
+    match candidates.as_slice() {
+        [] => {
+            return not_found(format!("Task not found: {}", query));
         }
-        VersionCheck::CheckFailed(msg) => {
-            println!("\n⚠ Could not check for updates: {}", msg);
-            std::process::exit(1);
+        [task] => return Ok(Some((*task).clone())),
+        _ => {}
+    }
+
+    let rows: Vec<String> = candidates
+        .iter()
+        .map(|t| {
+            let checkbox = checkbox(t.completed);
+            format!("{}  {} {}", short_id(t), checkbox, t.title)
+        })
+        .collect();
+
+    if json || !can_prompt() {
+        return Err(LookupError::Ambiguous(format!(
+            "{} tasks match \"{}\", use a longer title or an ID:\n  {}",
+            rows.len(),
+            query,
+            rows.join("\n  ")
+        ))
+        .into());
+    }
+
+    println!("Several tasks match \"{}\":", query);
+    for (i, row) in rows.iter().enumerate() {
+        println!("  {}. {}", i + 1, row);
+    }
+    print!("Which one? [1-{}, Enter to cancel] ", rows.len());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    match input.trim().parse::<usize>() {
+        Ok(n) if (1..=candidates.len()).contains(&n) => Ok(Some(candidates[n - 1].clone())),
+        _ => {
+            println!("Cancelled.");
+            Ok(None)
         }
     }
 }
 
-/// Find a task by ID or partial title match
-fn find_task(tasks: &[Task], query: &str) -> Option<Task> {
-    // Try UUID first
+/// Tasks matching `query`, trying the most specific kind of match first:
+/// full ID, exact title, ID prefix (at least 4 characters), then partial title
+fn find_tasks<'a>(tasks: &'a [Task], query: &str) -> Vec<&'a Task> {
     if let Ok(uuid) = uuid::Uuid::parse_str(query) {
-        return tasks.iter().find(|t| t.id == uuid).cloned();
+        return tasks.iter().filter(|t| t.id == uuid).collect();
+    }
+
+    let query_lower = query.trim().to_lowercase();
+    let exact: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.title.to_lowercase() == query_lower)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let is_id_prefix = query_lower.len() >= 4
+        && query_lower
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == '-');
+    if is_id_prefix {
+        let by_id: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| t.id.to_string().starts_with(&query_lower))
+            .collect();
+        if !by_id.is_empty() {
+            return by_id;
+        }
     }
 
-    // Try partial title match
-    let query_lower = query.to_lowercase();
     tasks
         .iter()
-        .find(|t| t.title.to_lowercase().contains(&query_lower))
-        .cloned()
+        .filter(|t| t.title.to_lowercase().contains(&query_lower))
+        .collect()
+}
+
+/// First 8 characters of a task's ID, enough to pick it on the command line
+fn short_id(task: &Task) -> String {
+    task.id.to_string()[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Commands {
+        Cli::try_parse_from(std::iter::once("tickit").chain(args.iter().copied()))
+            .unwrap()
+            .command
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bulk_arguments() {
+        let Commands::Done { selection, yes, .. } = parse(&["done", "a", "b", "c"]) else {
+            panic!("not done");
+        };
+        assert_eq!(selection.tasks, ["a", "b", "c"]);
+        assert!(!selection.is_filter() && !yes);
+
+        let Commands::Delete { selection, yes, .. } =
+            parse(&["delete", "--list", "Work", "--completed", "--force"])
+        else {
+            panic!("not delete");
+        };
+        assert!(selection.tasks.is_empty() && selection.is_filter() && yes);
+
+        let Commands::Edit {
+            selection,
+            set_priority,
+            ..
+        } = parse(&["edit", "--tag", "urgent", "--set-priority", "high"])
+        else {
+            panic!("not edit");
+        };
+        assert_eq!(selection.tag.as_deref(), Some("urgent"));
+        assert_eq!(set_priority.as_deref(), Some("high"));
+    }
+
+    #[test]
+    fn test_due_by_timezone() {
+        // Run again with the clock in Tokyo, where a due date's last second
+        // in UTC is already the next morning, and in Honolulu, where it's
+        // the afternoon of the due day
+        let Some(tz) = std::env::var_os("TZ").filter(|tz| tz == "JST-9" || tz == "HST10") else {
+            for tz in ["JST-9", "HST10"] {
+                let status = std::process::Command::new(std::env::current_exe().unwrap())
+                    .args(["--exact", "tests::test_due_by_timezone"])
+                    .env("TZ", tz)
+                    .output()
+                    .unwrap();
+                assert!(
+                    status.status.success(),
+                    "{}: {}",
+                    tz,
+                    String::from_utf8_lossy(&status.stdout)
+                );
+            }
+            return;
+        };
+        let offset = if tz == "JST-9" { 9 } else { -10 };
+        assert_eq!(
+            chrono::Local::now().offset().local_minus_utc(),
+            offset * 3600
+        );
+
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let due = |days: i64, title: &str| {
+            let mut task = Task::new(title, uuid::Uuid::new_v4());
+            task.due_date = tickit::quickadd::end_of_day(today + chrono::Duration::days(days));
+            task
+        };
+        let tasks = [
+            due(-1, "Yesterday"),
+            due(0, "Today"),
+            due(1, "Tomorrow"),
+            Task::new("Undated", uuid::Uuid::new_v4()),
+        ];
+        let (overdue, due_today) = due_by(&tasks, today);
+        let titles = |tasks: Vec<&Task>| -> Vec<String> {
+            tasks.into_iter().map(|t| t.title.clone()).collect()
+        };
+        assert_eq!(titles(overdue), ["Yesterday"]);
+        assert_eq!(titles(due_today), ["Today"]);
+    }
+
+    #[test]
+    fn test_resolve_selection() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let work = List::new("Work");
+        db.insert_list(&work).unwrap();
+        let urgent = Tag::new("urgent");
+        db.insert_tag(&urgent).unwrap();
+
+        let mut report = Task::new("Write report", work.id);
+        report.complete();
+        let mut slides = Task::new("Slides", work.id);
+        slides.tag_ids.push(urgent.id);
+        let mut milk = Task::new("Buy milk", inbox.id);
+        milk.tag_ids.push(urgent.id);
+        for task in [&report, &slides, &milk] {
+            db.insert_task(task).unwrap();
+        }
+
+        let select = |tasks: &[&str], list: Option<&str>, tag: Option<&str>, completed| {
+            let selection = TaskSelection {
+                tasks: tasks.iter().map(|t| t.to_string()).collect(),
+                list: list.map(str::to_string),
+                tag: tag.map(str::to_string),
+                completed,
+            };
+            resolve_selection(&db, &selection, false).map(|tasks| {
+                tasks.map(|tasks| {
+                    let mut titles: Vec<String> = tasks.into_iter().map(|t| t.title).collect();
+                    titles.sort();
+                    titles
+                })
+            })
+        };
+
+        assert!(select(&[], None, None, false).is_err());
+        // Named tasks, each once
+        assert_eq!(
+            select(&["milk", "report", "Buy milk"], None, None, false).unwrap(),
+            Some(vec!["Buy milk".to_string(), "Write report".to_string()])
+        );
+        // Filters combine, and names are looked up among the filtered tasks
+        assert_eq!(
+            select(&[], Some("work"), None, true).unwrap(),
+            Some(vec!["Write report".to_string()])
+        );
+        assert_eq!(
+            select(&[], None, Some("URGENT"), false).unwrap(),
+            Some(vec!["Buy milk".to_string(), "Slides".to_string()])
+        );
+        // A task, list or tag that doesn't resolve is a not-found error
+        let not_found = |result: Result<Option<Vec<String>>>| {
+            result
+                .unwrap_err()
+                .downcast_ref::<LookupError>()
+                .map(LookupError::exit_code)
+        };
+        assert_eq!(
+            not_found(select(&["milk"], Some("Work"), None, false)),
+            Some(3)
+        );
+        assert_eq!(not_found(select(&[], Some("Home"), None, false)), Some(3));
+        assert_eq!(not_found(select(&[], None, Some("later"), false)), Some(3));
+    }
+
+    #[test]
+    fn test_confirm_bulk_without_prompt() {
+        NO_INPUT.store(true, Ordering::Relaxed);
+        let tasks = vec![
+            Task::new("One", uuid::Uuid::new_v4()),
+            Task::new("Two", uuid::Uuid::new_v4()),
+        ];
+
+        assert!(confirm_bulk("Delete", &tasks, true).unwrap());
+        assert!(confirm_bulk("Delete", &[], false).unwrap());
+        let error = confirm_bulk("Delete", &tasks, false).unwrap_err();
+        assert!(error.to_string().contains("Delete 2 task(s)? Pass --yes"));
+    }
 }
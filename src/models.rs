@@ -81,6 +81,16 @@ impl Priority {
         }
     }
 
+    /// Short text label, for when color and shape aren't enough
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "med",
+            Self::High => "high",
+            Self::Urgent => "URG",
+        }
+    }
+
     /// Get the icon for this priority
     pub const fn icon(&self) -> &'static str {
         match self {
@@ -91,6 +101,16 @@ impl Priority {
         }
     }
 
+    /// Get the icon for this priority in plain ASCII
+    pub const fn ascii_icon(&self) -> &'static str {
+        match self {
+            Self::Low => ".",
+            Self::Medium => "-",
+            Self::High => "!",
+            Self::Urgent => "#",
+        }
+    }
+
     /// Get next priority (cycles)
     pub fn next(&self) -> Self {
         match self {
@@ -146,6 +166,16 @@ pub struct Task {
     /// Optional due date
     #[serde(default, with = "date_or_datetime")]
     pub due_date: Option<DateTime<Utc>>,
+    /// Position within its list for manual sorting
+    #[serde(default)]
+    pub sort_order: i32,
+    /// When the task was archived (hidden everywhere but kept until purged)
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Remind again every this many hours while overdue ("nag"); 0 uses
+    /// `[notifications] nag_hours`
+    #[serde(default)]
+    pub nag_hours: Option<u32>,
 }
 
 impl Task {
@@ -165,6 +195,9 @@ impl Task {
             updated_at: now,
             completed_at: None,
             due_date: None,
+            sort_order: 0,
+            archived_at: None,
+            nag_hours: None,
         }
     }
 
@@ -182,6 +215,11 @@ impl Task {
         self.updated_at = Utc::now();
     }
 
+    /// Whether the task is past its due date and not done yet
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        !self.completed && self.due_date.is_some_and(|due| due < now)
+    }
+
     /// Toggle completion status
     pub fn toggle(&mut self) {
         if self.completed {
@@ -191,6 +229,55 @@ impl Task {
         }
     }
 
+    /// A new, open copy of this task with the same details and tags
+    pub fn duplicate(&self) -> Self {
+        let mut copy = Task::new(&self.title, self.list_id);
+        copy.description = self.description.clone();
+        copy.url = self.url.clone();
+        copy.priority = self.priority;
+        copy.tag_ids = self.tag_ids.clone();
+        copy.due_date = self.due_date;
+        copy.nag_hours = self.nag_hours;
+        copy
+    }
+
+    /// Take in the details of `other`, a near-duplicate: its description (and
+    /// URL, if it differs) are added to this one's, the tags combined, and the
+    /// earlier due date and higher priority kept
+    pub fn absorb(&mut self, other: &Task) {
+        let mut notes: Vec<String> = self
+            .description
+            .iter()
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect();
+        let mut add_note = |note: &str| {
+            let note = note.trim();
+            if !note.is_empty() && !notes.iter().any(|n| n.contains(note)) {
+                notes.push(note.to_string());
+            }
+        };
+        if let Some(description) = &other.description {
+            add_note(description);
+        }
+        match (&self.url, &other.url) {
+            (None, Some(url)) => self.url = Some(url.clone()),
+            (Some(url), Some(other_url)) if url != other_url => add_note(other_url),
+            _ => {}
+        }
+        self.description = (!notes.is_empty()).then(|| notes.join("\n\n"));
+
+        for tag_id in &other.tag_ids {
+            if !self.tag_ids.contains(tag_id) {
+                self.tag_ids.push(*tag_id);
+            }
+        }
+        self.due_date = self.due_date.into_iter().chain(other.due_date).min();
+        self.priority = self.priority.max(other.priority);
+        self.nag_hours = self.nag_hours.or(other.nag_hours);
+        self.updated_at = Utc::now();
+    }
+
     /// Set the description
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
@@ -224,6 +311,94 @@ impl Task {
     }
 }
 
+/// What happens to the tasks of a list being deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletedListTasks {
+    /// Move them to another list
+    Move(Uuid),
+    /// Archive them, keeping them in the Inbox so they can be restored
+    Archive,
+    /// Delete them along with the list
+    Delete,
+}
+
+/// Sort order for tasks in the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskSort {
+    /// Highest priority first, newest first within a priority (default)
+    #[default]
+    Priority,
+    /// Earliest due date first, undated tasks last
+    DueDate,
+    /// Newest first
+    Created,
+    /// Alphabetical by title
+    Title,
+    /// User-defined order
+    Manual,
+}
+
+impl TaskSort {
+    /// Get all sort orders
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Priority,
+            Self::DueDate,
+            Self::Created,
+            Self::Title,
+            Self::Manual,
+        ]
+    }
+
+    /// Get the display name
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Priority => "priority",
+            Self::DueDate => "due date",
+            Self::Created => "created",
+            Self::Title => "A-Z",
+            Self::Manual => "manual",
+        }
+    }
+
+    /// Get next sort order (cycles)
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Priority => Self::DueDate,
+            Self::DueDate => Self::Created,
+            Self::Created => Self::Title,
+            Self::Title => Self::Manual,
+            Self::Manual => Self::Priority,
+        }
+    }
+
+    /// Sort tasks in place; incomplete tasks always come before completed ones
+    pub fn apply(&self, tasks: &mut [Task]) {
+        tasks.sort_by(|a, b| {
+            a.completed.cmp(&b.completed).then_with(|| match self {
+                Self::Priority => b
+                    .priority
+                    .cmp(&a.priority)
+                    .then_with(|| b.created_at.cmp(&a.created_at)),
+                Self::DueDate => match (a.due_date, b.due_date) {
+                    (Some(x), Some(y)) => x.cmp(&y),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
+                }
+                .then_with(|| b.priority.cmp(&a.priority)),
+                Self::Created => b.created_at.cmp(&a.created_at),
+                Self::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+                Self::Manual => a
+                    .sort_order
+                    .cmp(&b.sort_order)
+                    .then_with(|| a.created_at.cmp(&b.created_at)),
+            })
+        });
+    }
+}
+
 /// A list/project that contains tasks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct List {
@@ -245,6 +420,9 @@ pub struct List {
     pub updated_at: DateTime<Utc>,
     /// Sort order
     pub sort_order: i32,
+    /// Whether this is a reusable checklist whose tasks can all be reset at once
+    #[serde(default)]
+    pub is_checklist: bool,
 }
 
 impl List {
@@ -261,6 +439,7 @@ impl List {
             created_at: now,
             updated_at: now,
             sort_order: 0,
+            is_checklist: false,
         }
     }
 
@@ -277,6 +456,7 @@ impl List {
             created_at: now,
             updated_at: now,
             sort_order: -1, // Always first
+            is_checklist: false,
         }
     }
 
@@ -340,18 +520,6 @@ impl Tag {
 
     /// Generate a random pleasant color
     fn random_color() -> String {
-        const COLORS: &[&str] = &[
-            "#f38ba8", // Red
-            "#fab387", // Peach
-            "#f9e2af", // Yellow
-            "#a6e3a1", // Green
-            "#94e2d5", // Teal
-            "#89b4fa", // Blue
-            "#cba6f7", // Mauve
-            "#f5c2e7", // Pink
-            "#eba0ac", // Maroon
-            "#89dceb", // Sky
-        ];
         use std::time::{SystemTime, UNIX_EPOCH};
         let seed = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -361,6 +529,81 @@ impl Tag {
     }
 }
 
+/// A timestamped comment on a task, like a Taskwarrior annotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    /// Unique identifier
+    pub id: Uuid,
+    /// The task commented on
+    pub task_id: Uuid,
+    /// Comment text
+    pub body: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Comment {
+    /// Create a new comment on a task
+    pub fn new(task_id: Uuid, body: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            body: body.into(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// A file attached to a task; its contents are a blob in the blob store,
+/// named by their SHA-256
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Unique identifier
+    pub id: Uuid,
+    /// The task the file is attached to
+    pub task_id: Uuid,
+    /// File name, without its directory
+    pub name: String,
+    /// SHA-256 of the contents, in lowercase hex
+    pub hash: String,
+    /// Size in bytes
+    pub size: u64,
+    /// When it was attached
+    pub created_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Attach the blob `hash` of `size` bytes to a task as `name`
+    pub fn new(task_id: Uuid, name: impl Into<String>, hash: String, size: u64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            task_id,
+            name: name.into(),
+            hash,
+            size,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Pleasant colors for tags and lists
+pub const COLORS: &[&str] = &[
+    "#f38ba8", // Red
+    "#fab387", // Peach
+    "#f9e2af", // Yellow
+    "#a6e3a1", // Green
+    "#94e2d5", // Teal
+    "#89b4fa", // Blue
+    "#cba6f7", // Mauve
+    "#f5c2e7", // Pink
+    "#eba0ac", // Maroon
+    "#89dceb", // Sky
+];
+
 /// Export format for tasks
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -400,4 +643,14 @@ impl ExportFormat {
             Self::Csv => "csv",
         }
     }
+
+    /// Parse a format name or file extension, falling back to JSON
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "todotxt" | "todo.txt" | "txt" => Self::TodoTxt,
+            "markdown" | "md" => Self::Markdown,
+            "csv" => Self::Csv,
+            _ => Self::Json,
+        }
+    }
 }
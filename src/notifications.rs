@@ -1,8 +1,314 @@
 //! Desktop notifications for task reminders
+//!
+//! `[[notification_rules]]` in config.toml turn reminders off for the tasks
+//! of a list or tag, or start them some days before the due date, and
+//! `[notifications.review]` adds a weekly reminder to look at lists and
+//! tasks that have gone quiet.
+//!
+//! Overdue reminders go out once a day, except for tasks set to nag: those
+//! are reminded of again every few hours until they're done or rescheduled.
 
-use crate::models::{Priority, Task};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
 use notify_rust::{Notification, Timeout};
 
+use crate::config::{NotificationRule, QuietHours, ReviewConfig};
+use crate::db::Database;
+use crate::models::{List, Priority, Task};
+
+/// Kind of reminder sent for a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reminder {
+    /// Due today
+    DueToday,
+    /// High or urgent and due tomorrow
+    DueTomorrow,
+    /// Due in this many days, inside a rule's `days_before`
+    DueSoon(u32),
+    /// Past its due date
+    Overdue,
+    /// Past its due date and set to nag, repeated every few hours
+    Nag,
+}
+
+impl Reminder {
+    /// Key stored in the database to avoid repeating a reminder
+    pub const fn key(&self) -> &'static str {
+        match self {
+            Self::DueToday => "due-today",
+            Self::DueTomorrow => "due-tomorrow",
+            Self::DueSoon(_) => "due-soon",
+            Self::Overdue => "overdue",
+            Self::Nag => "nag",
+        }
+    }
+
+    /// Reminder due for `task` on `today` under `rule`, if any
+    pub fn for_task(task: &Task, today: NaiveDate, rule: TaskRule) -> Option<Self> {
+        if task.completed || rule.muted {
+            return None;
+        }
+        let due = task.due_date?.date_naive();
+        let days = (due - today).num_days();
+        let days_before = i64::from(rule.days_before.unwrap_or(0));
+        if due == today {
+            Some(Self::DueToday)
+        } else if due < today {
+            Some(Self::Overdue)
+        } else if days == 1
+            && (days_before >= 1 || matches!(task.priority, Priority::High | Priority::Urgent))
+        {
+            Some(Self::DueTomorrow)
+        } else if days <= days_before {
+            Some(Self::DueSoon(days as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Show the desktop notification for `task`
+    pub fn send(&self, task: &Task) -> Result<(), notify_rust::error::Error> {
+        match self {
+            Self::DueToday => notify_task_due_today(task),
+            Self::DueTomorrow => notify_task_due_tomorrow(task),
+            Self::DueSoon(days) => notify_task_due_soon(task, *days),
+            Self::Overdue => notify_task_overdue(task),
+            Self::Nag => notify_task_nag(task),
+        }
+    }
+}
+
+/// How often `task` nags while overdue, if it does; `default_hours` is
+/// `[notifications] nag_hours`
+pub fn nag_interval(task: &Task, default_hours: u32) -> Option<Duration> {
+    let hours = match task.nag_hours? {
+        0 => default_hours,
+        hours => hours,
+    };
+    Some(Duration::hours(i64::from(hours.max(1))))
+}
+
+/// What the notification rules matching one task add up to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskRule {
+    /// A matching rule turns reminders off, which wins over the others
+    pub muted: bool,
+    /// The earliest `days_before` of the matching rules
+    pub days_before: Option<u32>,
+}
+
+impl TaskRule {
+    /// Combine the `rules` that apply to a task in `list` with `tags`
+    pub fn new(rules: &[NotificationRule], list: Option<&str>, tags: &[&str]) -> Self {
+        rules
+            .iter()
+            .filter(|rule| rule.matches(list, tags))
+            .fold(Self::default(), |acc, rule| Self {
+                muted: acc.muted || !rule.notify,
+                days_before: acc.days_before.max(rule.days_before),
+            })
+    }
+}
+
+/// Reminders for today that haven't been sent yet, following `rules`, and
+/// nags last sent at least their interval ago (`nag_hours` by default)
+pub fn pending_reminders(
+    db: &Database,
+    rules: &[NotificationRule],
+    nag_hours: u32,
+) -> Result<Vec<(Task, Reminder)>> {
+    let now = Utc::now();
+    let today = now.with_timezone(&Local).date_naive();
+    let lists = db.get_lists()?;
+    let tags = db.get_tags()?;
+    let mut pending = Vec::new();
+    for task in db.get_all_tasks()? {
+        let list = lists
+            .iter()
+            .find(|l| l.id == task.list_id)
+            .map(|l| l.name.as_str());
+        let tag_names: Vec<&str> = tags
+            .iter()
+            .filter(|t| task.tag_ids.contains(&t.id))
+            .map(|t| t.name.as_str())
+            .collect();
+        let rule = TaskRule::new(rules, list, &tag_names);
+        let reminder = match Reminder::for_task(&task, today, rule) {
+            // A nagging task repeats every few hours instead of once a day
+            Some(Reminder::Overdue) if let Some(every) = nag_interval(&task, nag_hours) => db
+                .last_nag(task.id)?
+                .is_none_or(|at| now - at >= every)
+                .then_some(Reminder::Nag),
+            Some(reminder) if !db.was_notified(task.id, reminder.key(), today)? => Some(reminder),
+            _ => None,
+        };
+        if let Some(reminder) = reminder {
+            pending.push((task, reminder));
+        }
+    }
+    Ok(pending)
+}
+
+/// Send today's pending reminders, each at most once a day, and return how many were shown
+///
+/// Nothing is sent while `quiet_hours` are in effect; the reminders stay pending.
+pub fn send_reminders(
+    db: &Database,
+    quiet_hours: Option<QuietHours>,
+    rules: &[NotificationRule],
+    nag_hours: u32,
+) -> Result<usize> {
+    let now = Local::now();
+    if quiet_hours.is_some_and(|q| q.contains(now.time())) {
+        return Ok(0);
+    }
+    let mut sent = 0;
+    for (task, reminder) in pending_reminders(db, rules, nag_hours)? {
+        if reminder.send(&task).is_ok() {
+            if reminder == Reminder::Nag {
+                db.record_nag(task.id, now.to_utc())?;
+            } else {
+                db.record_notification(task.id, reminder.key(), now.date_naive())?;
+            }
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+/// Lists and tasks that have gone quiet, for the weekly review
+#[derive(Debug, Clone, Default)]
+pub struct Review {
+    /// Lists (other than the inbox) with no changes for `stale_list_days`,
+    /// with their last change
+    pub stale_lists: Vec<(List, DateTime<Utc>)>,
+    /// Open tasks without a due date created `undated_task_days` ago or more
+    pub undated_tasks: Vec<Task>,
+}
+
+impl Review {
+    /// Gather what `config` asks about, as of `now`
+    pub fn gather(db: &Database, config: &ReviewConfig, now: DateTime<Utc>) -> Result<Self> {
+        let tasks = db.get_all_tasks()?;
+        let stale_before = now - Duration::days(config.stale_list_days.into());
+        let undated_before = now - Duration::days(config.undated_task_days.into());
+
+        let mut stale_lists: Vec<_> = db
+            .get_lists()?
+            .into_iter()
+            .filter(|list| !list.is_inbox)
+            .map(|list| {
+                let last_change = tasks
+                    .iter()
+                    .filter(|t| t.list_id == list.id)
+                    .map(|t| t.updated_at)
+                    .fold(list.updated_at, DateTime::max);
+                (list, last_change)
+            })
+            .filter(|(_, last_change)| *last_change < stale_before)
+            .collect();
+        stale_lists.sort_by_key(|(_, last_change)| *last_change);
+
+        let mut undated_tasks: Vec<_> = tasks
+            .into_iter()
+            .filter(|t| !t.completed && t.due_date.is_none() && t.created_at < undated_before)
+            .collect();
+        undated_tasks.sort_by_key(|t| t.created_at);
+
+        Ok(Self {
+            stale_lists,
+            undated_tasks,
+        })
+    }
+
+    /// Whether there's nothing to review
+    pub fn is_empty(&self) -> bool {
+        self.stale_lists.is_empty() && self.undated_tasks.is_empty()
+    }
+
+    /// Notification text, e.g. "2 quiet lists: Garden, Taxes · 5 tasks with no due date"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.stale_lists.is_empty() {
+            let names: Vec<&str> = self
+                .stale_lists
+                .iter()
+                .take(3)
+                .map(|(l, _)| l.name.as_str())
+                .collect();
+            let more = if self.stale_lists.len() > 3 {
+                ", …"
+            } else {
+                ""
+            };
+            parts.push(format!(
+                "{} quiet {}: {}{}",
+                self.stale_lists.len(),
+                if self.stale_lists.len() == 1 {
+                    "list"
+                } else {
+                    "lists"
+                },
+                names.join(", "),
+                more
+            ));
+        }
+        if !self.undated_tasks.is_empty() {
+            parts.push(format!(
+                "{} {} with no due date",
+                self.undated_tasks.len(),
+                if self.undated_tasks.len() == 1 {
+                    "task"
+                } else {
+                    "tasks"
+                }
+            ));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Whether the weekly review is due on `today`: it's enabled, and it wasn't
+/// sent since this week's review day
+pub fn review_due(db: &Database, config: &ReviewConfig, today: NaiveDate) -> Result<bool> {
+    if !config.enabled {
+        return Ok(false);
+    }
+    let since_day = today.weekday().days_since(config.day);
+    let review_day = today - Duration::days(since_day.into());
+    Ok(db.last_review()?.is_none_or(|last| last < review_day))
+}
+
+/// Send the weekly review if it's due and there's something in it, returning
+/// whether it was shown
+///
+/// Like reminders, nothing is sent during `quiet_hours`.
+pub fn send_review(
+    db: &Database,
+    config: &ReviewConfig,
+    quiet_hours: Option<QuietHours>,
+) -> Result<bool> {
+    let now = Local::now();
+    if quiet_hours.is_some_and(|q| q.contains(now.time()))
+        || !review_due(db, config, now.date_naive())?
+    {
+        return Ok(false);
+    }
+    let review = Review::gather(db, config, now.to_utc())?;
+    let shown = !review.is_empty()
+        && Notification::new()
+            .summary("🗂 Weekly Review")
+            .body(&review.summary())
+            .appname("Tickit")
+            .timeout(Timeout::Milliseconds(10000))
+            .show()
+            .is_ok();
+    if review.is_empty() || shown {
+        db.set_last_review(now.date_naive())?;
+    }
+    Ok(shown)
+}
+
 /// Send a notification for a task that's due today
 pub fn notify_task_due_today(task: &Task) -> Result<(), notify_rust::error::Error> {
     let priority_emoji = match task.priority {
@@ -34,6 +340,18 @@ pub fn notify_task_due_tomorrow(task: &Task) -> Result<(), notify_rust::error::E
     Ok(())
 }
 
+/// Send a notification for a task due in a few days (a rule's `days_before`)
+pub fn notify_task_due_soon(task: &Task, days: u32) -> Result<(), notify_rust::error::Error> {
+    Notification::new()
+        .summary(&format!("📅 Task Due in {} Days", days))
+        .body(&task.title)
+        .appname("Tickit")
+        .timeout(Timeout::Milliseconds(8000))
+        .show()?;
+
+    Ok(())
+}
+
 /// Send a notification for overdue tasks
 pub fn notify_task_overdue(task: &Task) -> Result<(), notify_rust::error::Error> {
     Notification::new()
@@ -46,6 +364,18 @@ pub fn notify_task_overdue(task: &Task) -> Result<(), notify_rust::error::Error>
     Ok(())
 }
 
+/// Send a repeated notification for an overdue task set to nag
+pub fn notify_task_nag(task: &Task) -> Result<(), notify_rust::error::Error> {
+    Notification::new()
+        .summary("🔔 Still Overdue")
+        .body(&task.title)
+        .appname("Tickit")
+        .timeout(Timeout::Milliseconds(10000))
+        .show()?;
+
+    Ok(())
+}
+
 /// Send a generic notification
 pub fn notify(title: &str, body: &str) -> Result<(), notify_rust::error::Error> {
     Notification::new()
@@ -67,4 +397,180 @@ mod tests {
         // Just test it doesn't panic - actual notification depends on system
         let _ = notify("Test", "Test body");
     }
+
+    #[test]
+    fn test_reminder_for_task() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let due = |days: i64, priority: Priority| {
+            let mut task = Task::new("Task", uuid::Uuid::new_v4());
+            task.due_date = crate::quickadd::end_of_day(today + chrono::Duration::days(days));
+            task.priority = priority;
+            Reminder::for_task(&task, today, TaskRule::default())
+        };
+        assert_eq!(due(0, Priority::Low), Some(Reminder::DueToday));
+        assert_eq!(due(-2, Priority::Low), Some(Reminder::Overdue));
+        assert_eq!(due(1, Priority::High), Some(Reminder::DueTomorrow));
+        assert_eq!(due(1, Priority::Medium), None);
+        assert_eq!(due(3, Priority::Urgent), None);
+    }
+
+    #[test]
+    fn test_reminder_timezone() {
+        // Due dates are the last second of their day in UTC, which east of
+        // UTC is already the next day: run again with the clock in Tokyo
+        if std::env::var_os("TZ").is_none_or(|tz| tz != "JST-9") {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "notifications::tests::test_reminder_timezone"])
+                .env("TZ", "JST-9")
+                .output()
+                .unwrap();
+            assert!(
+                status.status.success(),
+                "{}",
+                String::from_utf8_lossy(&status.stdout)
+            );
+            return;
+        }
+        assert_eq!(Local::now().offset().local_minus_utc(), 9 * 3600);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let mut task = Task::new("Task", uuid::Uuid::new_v4());
+        task.due_date = crate::quickadd::end_of_day(today);
+        let reminder = |today| Reminder::for_task(&task, today, TaskRule::default());
+        assert_eq!(reminder(today), Some(Reminder::DueToday));
+        assert_eq!(reminder(today.succ_opt().unwrap()), Some(Reminder::Overdue));
+    }
+
+    #[test]
+    fn test_notification_rules() {
+        let rules: Vec<NotificationRule> = toml::from_str::<crate::Config>(
+            r#"
+            [[notification_rules]]
+            list = "Someday"
+            notify = false
+
+            [[notification_rules]]
+            tag = "bills"
+            days_before = 3
+            "#,
+        )
+        .unwrap()
+        .notification_rules;
+
+        let someday = TaskRule::new(&rules, Some("someday"), &["Bills"]);
+        assert!(someday.muted);
+        let bills = TaskRule::new(&rules, Some("Home"), &["Bills"]);
+        assert_eq!(
+            bills,
+            TaskRule {
+                muted: false,
+                days_before: Some(3)
+            }
+        );
+        assert_eq!(
+            TaskRule::new(&rules, Some("Home"), &[]),
+            TaskRule::default()
+        );
+
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let mut task = Task::new("Rent", uuid::Uuid::new_v4());
+        let due_in = |task: &mut Task, days: i64, rule| {
+            task.due_date = crate::quickadd::end_of_day(today + chrono::Duration::days(days));
+            Reminder::for_task(task, today, rule)
+        };
+        assert_eq!(due_in(&mut task, 3, bills), Some(Reminder::DueSoon(3)));
+        assert_eq!(due_in(&mut task, 1, bills), Some(Reminder::DueTomorrow));
+        assert_eq!(due_in(&mut task, 4, bills), None);
+        assert_eq!(due_in(&mut task, -1, someday), None);
+    }
+
+    #[test]
+    fn test_nag() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let yesterday = Utc::now() - Duration::days(1);
+        let mut nagging = Task::new("Pay rent", inbox.id).with_due_date(yesterday);
+        nagging.nag_hours = Some(0);
+        db.insert_task(&nagging).unwrap();
+        db.insert_task(&Task::new("Quiet", inbox.id).with_due_date(yesterday))
+            .unwrap();
+        let kinds = || -> Vec<(String, Reminder)> {
+            pending_reminders(&db, &[], 4)
+                .unwrap()
+                .into_iter()
+                .map(|(t, r)| (t.title, r))
+                .collect()
+        };
+        assert!(kinds().contains(&("Pay rent".to_string(), Reminder::Nag)));
+        assert!(kinds().contains(&("Quiet".to_string(), Reminder::Overdue)));
+
+        // Due again once the interval has passed, not before
+        db.record_nag(nagging.id, Utc::now() - Duration::hours(3))
+            .unwrap();
+        assert!(!kinds().iter().any(|(title, _)| title == "Pay rent"));
+        db.record_nag(nagging.id, Utc::now() - Duration::hours(5))
+            .unwrap();
+        assert!(kinds().iter().any(|(title, _)| title == "Pay rent"));
+
+        // A task's own interval wins over the default
+        nagging.nag_hours = Some(1);
+        assert_eq!(nag_interval(&nagging, 4), Some(Duration::hours(1)));
+        nagging.nag_hours = None;
+        assert_eq!(nag_interval(&nagging, 4), None);
+    }
+
+    #[test]
+    fn test_review() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let long_ago = now - Duration::days(60);
+
+        let mut garden = List::new("Garden");
+        garden.updated_at = long_ago;
+        db.insert_list(&garden).unwrap();
+        let mut busy = List::new("Work");
+        busy.updated_at = long_ago;
+        db.insert_list(&busy).unwrap();
+        let mut old = Task::new("Someday maybe", busy.id);
+        old.created_at = long_ago;
+        db.insert_task(&old).unwrap();
+        let mut dated = Task::new("Taxes", busy.id).with_due_date(now);
+        dated.created_at = long_ago;
+        db.insert_task(&dated).unwrap();
+
+        let config = ReviewConfig {
+            enabled: true,
+            ..ReviewConfig::default()
+        };
+        let review = Review::gather(&db, &config, now).unwrap();
+        let stale: Vec<&str> = review
+            .stale_lists
+            .iter()
+            .map(|(l, _)| l.name.as_str())
+            .collect();
+        assert_eq!(stale, ["Garden"]);
+        assert_eq!(review.undated_tasks.len(), 1);
+        assert_eq!(
+            review.summary(),
+            "1 quiet list: Garden · 1 task with no due date"
+        );
+
+        // Once a week, from the review day on
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        assert!(review_due(&db, &config, monday).unwrap());
+        db.set_last_review(monday).unwrap();
+        assert!(!review_due(&db, &config, monday + Duration::days(6)).unwrap());
+        assert!(review_due(&db, &config, monday + Duration::days(7)).unwrap());
+        let off = ReviewConfig::default();
+        assert!(!review_due(&db, &off, monday + Duration::days(7)).unwrap());
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let quiet: QuietHours = toml::from_str("start = \"22:00\"\nend = \"07:00\"").unwrap();
+        let at = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(quiet.contains(at(23, 30)));
+        assert!(quiet.contains(at(6, 59)));
+        assert!(!quiet.contains(at(7, 0)));
+        assert!(!quiet.contains(at(12, 0)));
+    }
 }
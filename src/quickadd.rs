@@ -0,0 +1,241 @@
+//! Quick-add syntax for creating tasks from a single line
+//!
+//! `Buy milk #errands @home !high due:tomorrow` becomes a task titled
+//! "Buy milk" tagged `errands`, in list `home`, with high priority, due tomorrow.
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Utc, Weekday};
+
+use crate::holidays;
+use crate::models::Priority;
+
+/// A parsed quick-add line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuickAdd {
+    /// Remaining words joined as the title
+    pub title: String,
+    /// Tag names from `#tag` tokens
+    pub tags: Vec<String>,
+    /// List name from an `@list` token
+    pub list: Option<String>,
+    /// Priority from a `!priority` token
+    pub priority: Option<Priority>,
+    /// Due date from a `due:` token
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+impl QuickAdd {
+    /// Parse a quick-add line
+    ///
+    /// Tokens that don't parse (e.g. `due:someday`) are kept in the title.
+    pub fn parse(input: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut words = Vec::new();
+
+        for word in input.split_whitespace() {
+            if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+                if !parsed.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                    parsed.tags.push(tag.to_string());
+                }
+            } else if let Some(list) = word.strip_prefix('@').filter(|l| !l.is_empty()) {
+                parsed.list = Some(list.to_string());
+            } else if let Some(priority) = word.strip_prefix('!').and_then(parse_priority) {
+                parsed.priority = Some(priority);
+            } else if let Some(due) = word.strip_prefix("due:").and_then(parse_due_date) {
+                parsed.due_date = Some(due);
+            } else {
+                words.push(word);
+            }
+        }
+
+        parsed.title = words.join(" ");
+        parsed
+    }
+}
+
+/// Parse a priority name or its first letter
+pub fn parse_priority(s: &str) -> Option<Priority> {
+    match s.to_lowercase().as_str() {
+        "low" | "l" => Some(Priority::Low),
+        "medium" | "med" | "m" => Some(Priority::Medium),
+        "high" | "h" => Some(Priority::High),
+        "urgent" | "u" => Some(Priority::Urgent),
+        _ => None,
+    }
+}
+
+/// Parse a due date: `YYYY-MM-DD`, `today`, `tonight`, `tomorrow`, a weekday
+/// name, `weekend`, `next-week`, `next-month`, `next-business-day`, or a
+/// relative offset like `3d` / `2w` / `3bd` (business days, per
+/// [`crate::holidays`])
+pub fn parse_due_date(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim().to_lowercase();
+    if s.is_empty() {
+        return None;
+    }
+
+    let today = Local::now().date_naive();
+    let date = match s.as_str() {
+        // Due dates have no time of day, so tonight is today
+        "today" | "tod" | "tonight" => Some(today),
+        "tomorrow" | "tom" => today.succ_opt(),
+        "weekend" | "this-weekend" => Some(match today.weekday() {
+            Weekday::Sat | Weekday::Sun => today,
+            _ => next_weekday(today, Weekday::Sat),
+        }),
+        "next-week" | "nextweek" | "week" => Some(today + Duration::days(7)),
+        "next-month" | "nextmonth" | "month" => today.checked_add_months(Months::new(1)),
+        "next-business-day" | "business-day" | "nbd" => {
+            holidays::business_days().add_business_days(today, 1)
+        }
+        _ => NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+            .ok()
+            .or_else(|| {
+                let count = s.strip_prefix('+').unwrap_or(&s).strip_suffix("bd")?;
+                holidays::business_days().add_business_days(today, count.parse().ok()?)
+            })
+            .or_else(|| parse_weekday(&s).map(|day| next_weekday(today, day)))
+            .or_else(|| parse_offset(&s).map(|days| today + Duration::days(days))),
+    }?;
+
+    end_of_day(date)
+}
+
+/// Parse when to defer a task to: anything [`parse_due_date`] reads, where
+/// an offset with a leading `+` (`+1w`, `+3bd`) pushes the current due date
+/// instead of counting from today, unless that date has already passed.
+/// Spaces may stand in for dashes (`next week`).
+pub fn parse_defer(s: &str, due: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let s = s.trim().to_lowercase().replace(' ', "-");
+    let Some(offset) = s.strip_prefix('+') else {
+        return parse_due_date(&s);
+    };
+    let today = Local::now().date_naive();
+    let from = due
+        .map(|due| due.date_naive())
+        .filter(|due| *due > today)
+        .unwrap_or(today);
+    let date = match offset.strip_suffix("bd") {
+        Some(count) => holidays::business_days().add_business_days(from, count.parse().ok()?),
+        None => parse_offset(offset).map(|days| from + Duration::days(days)),
+    }?;
+    end_of_day(date)
+}
+
+/// Due dates are stored as the last second of their day
+pub fn end_of_day(date: NaiveDate) -> Option<DateTime<Utc>> {
+    Some(date.and_hms_opt(23, 59, 59)?.and_utc())
+}
+
+/// Parse a weekday name or its three-letter abbreviation
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    if s.len() < 3 {
+        return None;
+    }
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .find(|day| {
+        let name = match day {
+            Weekday::Mon => "monday",
+            Weekday::Tue => "tuesday",
+            Weekday::Wed => "wednesday",
+            Weekday::Thu => "thursday",
+            Weekday::Fri => "friday",
+            Weekday::Sat => "saturday",
+            Weekday::Sun => "sunday",
+        };
+        name.starts_with(s)
+    })
+}
+
+/// The next occurrence of `day` after `from` (never `from` itself)
+fn next_weekday(from: NaiveDate, day: Weekday) -> NaiveDate {
+    let ahead = (day.num_days_from_monday() + 7 - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(if ahead == 0 { 7 } else { ahead as i64 })
+}
+
+/// Parse `Nd` or `Nw` into a number of days
+fn parse_offset(s: &str) -> Option<i64> {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let unit = s.chars().last()?;
+    let n: i64 = s[..s.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        'd' => Some(n),
+        'w' => Some(n * 7),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_line() {
+        let parsed = QuickAdd::parse("Buy milk #errands @home !high due:tomorrow");
+        assert_eq!(parsed.title, "Buy milk");
+        assert_eq!(parsed.tags, vec!["errands"]);
+        assert_eq!(parsed.list.as_deref(), Some("home"));
+        assert_eq!(parsed.priority, Some(Priority::High));
+        let tomorrow = Local::now().date_naive().succ_opt().unwrap();
+        assert_eq!(parsed.due_date.unwrap().date_naive(), tomorrow);
+    }
+
+    #[test]
+    fn test_unparsed_tokens_stay_in_title() {
+        let parsed = QuickAdd::parse("Call # mom !soon due:someday");
+        assert_eq!(parsed.title, "Call # mom !soon due:someday");
+        assert!(parsed.tags.is_empty());
+        assert_eq!(parsed.priority, None);
+        assert_eq!(parsed.due_date, None);
+    }
+
+    #[test]
+    fn test_parse_due_date_formats() {
+        let today = Local::now().date_naive();
+        let day = |s: &str| parse_due_date(s).map(|d| d.date_naive());
+
+        assert_eq!(day("2030-01-15"), NaiveDate::from_ymd_opt(2030, 1, 15));
+        assert_eq!(day("today"), Some(today));
+        assert_eq!(day("3d"), Some(today + Duration::days(3)));
+        assert_eq!(day("2w"), Some(today + Duration::days(14)));
+        assert!(day("fri").unwrap() > today);
+        assert_eq!(day("fri").unwrap().weekday(), Weekday::Fri);
+        let business = day("nbd").unwrap();
+        assert!(business > today && !matches!(business.weekday(), Weekday::Sat | Weekday::Sun));
+        assert!(day("2bd").unwrap() > business);
+        assert_eq!(day("nope"), None);
+        assert_eq!(day("tonight"), Some(today));
+        let weekend = day("weekend").unwrap();
+        assert!(matches!(weekend.weekday(), Weekday::Sat | Weekday::Sun));
+        assert!(weekend >= today && weekend < today + Duration::days(7));
+    }
+
+    #[test]
+    fn test_parse_defer() {
+        let today = Local::now().date_naive();
+        let day = |s: &str, due: Option<NaiveDate>| {
+            parse_defer(s, due.and_then(end_of_day)).map(|d| d.date_naive())
+        };
+        let later = today + Duration::days(10);
+        let past = today - Duration::days(10);
+
+        // `+` pushes a future due date, or counts from today
+        assert_eq!(day("+1w", Some(later)), Some(later + Duration::days(7)));
+        assert_eq!(day("+2d", Some(past)), Some(today + Duration::days(2)));
+        assert_eq!(day("+2d", None), Some(today + Duration::days(2)));
+        assert!(day("+1bd", Some(later)).unwrap() > later);
+        // Anything else is the same date whatever the task had
+        assert_eq!(day("3d", Some(later)), Some(today + Duration::days(3)));
+        assert_eq!(day("next week", None), Some(today + Duration::days(7)));
+        assert_eq!(day("Monday", None).unwrap().weekday(), Weekday::Mon);
+        assert_eq!(day("+soon", None), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! Generate large synthetic databases for benchmarks and profiling
+//!
+//! Only built with the `debug-tools` feature. Output is deterministic for a
+//! given seed, so benchmark runs compare like with like.
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+
+use crate::db::Database;
+use crate::models::{COLORS, List, Priority, Tag, Task};
+
+const VERBS: &[&str] = &[
+    "Write", "Review", "Fix", "Plan", "Call", "Email", "Update", "Clean", "Buy", "Read", "Draft",
+    "Test", "Refactor", "Book", "Prepare",
+];
+const NOUNS: &[&str] = &[
+    "report",
+    "invoice",
+    "presentation",
+    "garden",
+    "groceries",
+    "budget",
+    "release notes",
+    "dentist appointment",
+    "backlog",
+    "kitchen",
+    "slides",
+    "contract",
+    "newsletter",
+    "roadmap",
+    "birthday gift",
+    "server logs",
+];
+const LISTS: &[&str] = &[
+    "Work",
+    "Home",
+    "Errands",
+    "Side project",
+    "Reading",
+    "Health",
+    "Finance",
+    "Travel",
+];
+const TAGS: &[&str] = &[
+    "urgent",
+    "waiting",
+    "phone",
+    "computer",
+    "quick",
+    "deep-work",
+    "family",
+    "outside",
+    "weekly",
+    "someday",
+];
+
+/// What [`seed`] created
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeedStats {
+    pub lists: usize,
+    pub tags: usize,
+    pub tasks: usize,
+}
+
+/// Small xorshift generator; good enough for spreading fake data around
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// True with probability `percent`/100
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+/// Fill `db` with `tasks` tasks spread over a handful of lists and tags:
+/// about a third completed, half with due dates around today, some tagged
+/// and some with descriptions
+pub fn seed(db: &Database, tasks: usize, seed: u64) -> Result<SeedStats> {
+    let mut rng = Rng(seed.max(1));
+
+    let mut stats = SeedStats {
+        tasks,
+        ..SeedStats::default()
+    };
+
+    // Reuse lists and tags from an earlier run
+    let existing = db.get_lists()?;
+    let mut lists = vec![db.get_inbox()?];
+    for &name in LISTS {
+        match existing.iter().find(|l| l.name == name) {
+            Some(list) => lists.push(list.clone()),
+            None => {
+                let list = List::new(name);
+                db.insert_list(&list)?;
+                lists.push(list);
+                stats.lists += 1;
+            }
+        }
+    }
+    let existing = db.get_tags()?;
+    let mut tags = Vec::new();
+    for (i, &name) in TAGS.iter().enumerate() {
+        match existing.iter().find(|t| t.name == name) {
+            Some(tag) => tags.push(tag.clone()),
+            None => {
+                let tag = Tag::new(name).with_color(COLORS[i % COLORS.len()]);
+                db.insert_tag(&tag)?;
+                tags.push(tag);
+                stats.tags += 1;
+            }
+        }
+    }
+
+    let now = Utc::now();
+    // Insert in chunks so memory stays flat for very large counts
+    let mut batch = Vec::with_capacity(tasks.min(10_000));
+    for i in 0..tasks {
+        let title = format!("{} {} #{}", rng.pick(VERBS), rng.pick(NOUNS), i + 1);
+        let list_id = lists[rng.below(lists.len())].id;
+        let mut task = Task::new(&title, list_id);
+        task.priority = match rng.below(10) {
+            0..=2 => Priority::Low,
+            3..=6 => Priority::Medium,
+            7..=8 => Priority::High,
+            _ => Priority::Urgent,
+        };
+        if rng.chance(50) {
+            task.due_date = Some(now + Duration::hours(rng.below(24 * 120) as i64 - 24 * 60));
+        }
+        if rng.chance(20) {
+            task.description = Some(format!(
+                "Notes about the {}.\n\n- [ ] first step\n- [x] second step",
+                rng.pick(NOUNS)
+            ));
+        }
+        for _ in 0..rng.below(4) {
+            let tag_id = tags[rng.below(tags.len())].id;
+            if !task.tag_ids.contains(&tag_id) {
+                task.tag_ids.push(tag_id);
+            }
+        }
+        if rng.chance(35) {
+            task.complete();
+        }
+        batch.push(task);
+        if batch.len() == 10_000 {
+            db.insert_tasks(&batch)?;
+            batch.clear();
+        }
+    }
+    db.insert_tasks(&batch)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open_path(&dir.path().join("test.db")).unwrap();
+        let stats = seed(&db, 250, 7).unwrap();
+        assert_eq!(stats.tasks, 250);
+        assert_eq!(
+            db.get_tasks_with_filter(None, None, None).unwrap().len(),
+            250
+        );
+        assert_eq!(db.get_lists().unwrap().len(), LISTS.len() + 1);
+        assert_eq!(db.get_tags().unwrap().len(), TAGS.len());
+
+        // A second run adds tasks but no more lists or tags
+        let stats = seed(&db, 10, 8).unwrap();
+        assert_eq!((stats.lists, stats.tags), (0, 0));
+        assert_eq!(db.get_lists().unwrap().len(), LISTS.len() + 1);
+    }
+}
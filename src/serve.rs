@@ -0,0 +1,832 @@
+//! Local HTTP API over the task database (`tickit serve`)
+//!
+//! A small blocking HTTP/1.1 server for scripts and extensions on the same
+//! machine. It only listens on 127.0.0.1, answers one request at a time and
+//! closes each connection after responding. Every request except CORS
+//! preflights needs `Authorization: Bearer <token>`.
+//!
+//! | Method   | Path          | Body / query                                              |
+//! |----------|---------------|-----------------------------------------------------------|
+//! | `GET`    | `/tasks`      | `?q=`, `list=`, `tag=`, `priority=`, `all=true`           |
+//! | `POST`   | `/tasks`      | `title`, `description`, `url`, `priority`, `due`, `list`, `tags` |
+//! | `GET`    | `/tasks/{id}` |                                                           |
+//! | `PATCH`  | `/tasks/{id}` | `title`, `description`, `url`, `completed`, `priority`, `due`, `list`, `add_tags`, `remove_tags`, `nag_hours` (`null` clears `description`, `url`, `due` and `nag_hours`) |
+//! | `DELETE` | `/tasks/{id}` |                                                           |
+//! | `GET`    | `/lists`      |                                                           |
+//! | `POST`   | `/lists`      | `name`, `icon`                                            |
+//! | `GET`    | `/tags`       |                                                           |
+//! | `POST`   | `/tags`       | `name`, `color`                                           |
+//! | `GET`    | `/calendar.ics` | `?token=`, `list=`, `tag=`, `all=true`                  |
+//!
+//! Changes go through [`TickitCore`], so they can be undone with `tickit undo`
+//! and are picked up by sync. Calendar apps can't send headers, so
+//! `/calendar.ics` instead takes a separate read-only token in the URL.
+//!
+//! Tokens set in config.toml are used as they are. Otherwise `tickit serve`
+//! generates them into files next to it ([`TOKEN_FILE`], [`CALENDAR_TOKEN_FILE`]),
+//! so config.toml and its comments are never rewritten.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::core::{LookupError, NewTask, TaskChanges, TaskQuery, TickitCore};
+use crate::models::Task;
+use crate::quickadd;
+
+/// Largest request body accepted
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Longest request line or header accepted
+const MAX_LINE: u64 = 8 * 1024;
+
+/// Most headers accepted in one request
+const MAX_HEADERS: usize = 100;
+
+/// File next to config.toml holding the generated API token
+pub const TOKEN_FILE: &str = ".serve_token";
+
+/// File next to config.toml holding the generated calendar token
+pub const CALENDAR_TOKEN_FILE: &str = ".calendar_token";
+
+/// The token saved in `path`, generating and saving one if there is none yet
+///
+/// Returns whether the token was just generated.
+pub fn load_or_create_token(path: &Path) -> Result<(String, bool)> {
+    if let Ok(content) = std::fs::read_to_string(path)
+        && !content.trim().is_empty()
+    {
+        return Ok((content.trim().to_string(), false));
+    }
+    let token = Uuid::new_v4().simple().to_string();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, &token)
+        .with_context(|| format!("Could not save the token to {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok((token, true))
+}
+
+/// A parsed HTTP request
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Decoded query parameters, in order
+    pub query: Vec<(String, String)>,
+    /// Headers with lowercased names
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read one request from a connection
+    pub fn read(reader: &mut impl BufRead) -> Result<Self> {
+        let mut request_line = String::new();
+        read_line(reader, &mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            anyhow::bail!("Malformed request line");
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let mut headers = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if read_line(reader, &mut line)? == 0 {
+                break;
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if headers.len() == MAX_HEADERS {
+                anyhow::bail!("Too many headers");
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        let mut request = Self {
+            method: method.to_ascii_uppercase(),
+            path: path.to_string(),
+            query: query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (decode(key), decode(value))
+                })
+                .collect(),
+            headers,
+            body: Vec::new(),
+        };
+
+        let length: usize = request
+            .header("content-length")
+            .map(|l| l.parse())
+            .transpose()
+            .context("Invalid Content-Length")?
+            .unwrap_or(0);
+        if length > MAX_BODY {
+            anyhow::bail!("Request body too large");
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+        Ok(request)
+    }
+
+    /// Value of the header `name` (lowercase)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Value of the query parameter `name`
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read a line of at most [`MAX_LINE`] bytes into `line`, returning its length
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<usize> {
+    let read = reader.by_ref().take(MAX_LINE).read_line(line)?;
+    if read as u64 == MAX_LINE && !line.ends_with('\n') {
+        anyhow::bail!("Request line or header too long");
+    }
+    Ok(read)
+}
+
+/// An HTTP response with an optional JSON body
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: Option<Value>,
+    /// A body in another format and its content type, sent instead of `body`
+    pub text: Option<(&'static str, String)>,
+}
+
+impl Response {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            body: Some(body),
+            text: None,
+        }
+    }
+
+    fn empty(status: u16) -> Self {
+        Self {
+            status,
+            body: None,
+            text: None,
+        }
+    }
+
+    fn text(content_type: &'static str, text: String) -> Self {
+        Self {
+            status: 200,
+            body: None,
+            text: Some((content_type, text)),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "error": message.into() }))
+    }
+
+    /// Send the response and its CORS headers
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let (content_type, body) = match (&self.text, &self.body) {
+            (Some((content_type, text)), _) => (*content_type, text.clone().into_bytes()),
+            (None, Some(value)) => ("application/json", serde_json::to_vec_pretty(value)?),
+            (None, None) => ("application/json", Vec::new()),
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: {}\r\n\
+             Content-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\n\
+             Access-Control-Allow-Methods: GET, POST, PATCH, DELETE, OPTIONS\r\n\
+             Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+             Connection: close\r\n",
+            self.status,
+            reason(self.status),
+            content_type,
+            body.len()
+        )?;
+        if self.status == 401 {
+            write!(writer, "WWW-Authenticate: Bearer\r\n")?;
+        }
+        write!(writer, "\r\n")?;
+        writer.write_all(&body)?;
+        writer.flush()
+    }
+}
+
+/// Serves the HTTP API for one database
+pub struct Server {
+    core: TickitCore,
+    token: String,
+    calendar_token: Option<String>,
+}
+
+impl Server {
+    /// Serve `core` to clients presenting `token`
+    pub fn new(core: TickitCore, token: impl Into<String>) -> Self {
+        Self {
+            core,
+            token: token.into(),
+            calendar_token: None,
+        }
+    }
+
+    /// Also serve `/calendar.ics` to clients with `?token=<token>`
+    pub fn with_calendar_token(mut self, token: impl Into<String>) -> Self {
+        self.calendar_token = Some(token.into());
+        self
+    }
+
+    /// Answer connections on `listener` until the process is stopped
+    pub fn run(&self, listener: TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = self.serve_connection(stream) {
+                        tracing::debug!("HTTP connection failed: {}", e);
+                    }
+                }
+                Err(e) => tracing::debug!("HTTP accept failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn serve_connection(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let response = match Request::read(&mut reader) {
+            Ok(request) => self.handle(&request),
+            Err(e) => Response::error(400, e.to_string()),
+        };
+        let mut stream = stream;
+        response.write_to(&mut stream)?;
+        Ok(())
+    }
+
+    /// Route a request and run it
+    pub fn handle(&self, request: &Request) -> Response {
+        if request.method == "OPTIONS" {
+            return Response::empty(204);
+        }
+        if request.path == "/calendar.ics" {
+            return self.calendar(request);
+        }
+        let authorized = request
+            .header("authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .is_some_and(|token| same_token(token.trim(), &self.token));
+        if !authorized {
+            return Response::error(401, "Missing or wrong token");
+        }
+
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["tasks"]) => self.list_tasks(request),
+            ("POST", ["tasks"]) => self.create_task(request),
+            ("GET", ["tasks", id]) => self.task(id).map(|t| Response::json(200, json!(t))),
+            ("PATCH", ["tasks", id]) => self.update_task(id, request),
+            ("DELETE", ["tasks", id]) => self.delete_task(id),
+            ("GET", ["lists"]) => self
+                .core
+                .db()
+                .get_lists()
+                .map(|l| Response::json(200, json!(l))),
+            ("POST", ["lists"]) => self.create_list(request),
+            ("GET", ["tags"]) => self
+                .core
+                .db()
+                .get_tags()
+                .map(|t| Response::json(200, json!(t))),
+            ("POST", ["tags"]) => self.create_tag(request),
+            (_, ["tasks"] | ["tasks", _] | ["lists"] | ["tags"]) => {
+                Ok(Response::error(405, "Method not allowed"))
+            }
+            _ => Ok(Response::error(404, "No such endpoint")),
+        };
+        result.unwrap_or_else(failure)
+    }
+
+    fn list_tasks(&self, request: &Request) -> Result<Response> {
+        Ok(Response::json(
+            200,
+            json!(self.core.tasks(&self.query(request)?)?),
+        ))
+    }
+
+    /// Tasks with a due date as an iCalendar feed, for calendar subscriptions
+    fn calendar(&self, request: &Request) -> Response {
+        if request.method != "GET" {
+            return Response::error(405, "Method not allowed");
+        }
+        let authorized = match (&self.calendar_token, request.param("token")) {
+            (Some(expected), Some(given)) => same_token(given, expected),
+            _ => false,
+        };
+        if !authorized {
+            return Response::error(401, "Missing or wrong calendar token");
+        }
+        let feed = || -> Result<String> {
+            let tasks = self.core.tasks(&self.query(request)?)?;
+            let mut out = Vec::new();
+            crate::export::export_ical(
+                &mut out,
+                &tasks,
+                &self.core.db().get_lists()?,
+                &self.core.db().get_tags()?,
+            )?;
+            Ok(String::from_utf8(out)?)
+        };
+        match feed() {
+            Ok(ical) => Response::text("text/calendar; charset=utf-8", ical),
+            Err(e) => failure(e),
+        }
+    }
+
+    /// Task filters from the query string
+    fn query(&self, request: &Request) -> Result<TaskQuery> {
+        Ok(TaskQuery {
+            text: request.param("q").map(str::to_string),
+            list_id: request
+                .param("list")
+                .map(|name| self.core.find_list(name).map(|l| l.id))
+                .transpose()?,
+            tag_id: request
+                .param("tag")
+                .map(|name| self.core.find_tag(name).map(|t| t.id))
+                .transpose()?,
+            priority: request.param("priority").map(priority).transpose()?,
+            due_before: None,
+            include_completed: request
+                .param("all")
+                .is_some_and(|v| v == "true" || v == "1"),
+        })
+    }
+
+    fn create_task(&self, request: &Request) -> Result<Response> {
+        let input: TaskInput = body(request)?;
+        let list_id = match &input.list {
+            Some(name) => self.core.find_list(name)?.id,
+            None => self.core.db().get_inbox()?.id,
+        };
+        let mut new = NewTask::new(input.title, list_id);
+        new.description = input.description;
+        new.url = input.url;
+        new.priority = input
+            .priority
+            .as_deref()
+            .map(priority)
+            .transpose()?
+            .unwrap_or_default();
+        new.due_date = input.due.as_deref().map(due).transpose()?;
+        for name in &input.tags {
+            new.tag_ids.push(self.core.find_tag(name)?.id);
+        }
+        Ok(Response::json(201, json!(self.core.add_task(new)?)))
+    }
+
+    fn update_task(&self, id: &str, request: &Request) -> Result<Response> {
+        let patch: TaskPatch = body(request)?;
+        let task = self.task(id)?;
+
+        let mut changes = TaskChanges {
+            title: patch.title,
+            description: patch.description,
+            url: patch.url,
+            priority: patch.priority.as_deref().map(priority).transpose()?,
+            due_date: patch
+                .due
+                .map(|d| d.as_deref().map(due).transpose())
+                .transpose()?,
+            list_id: patch
+                .list
+                .map(|name| self.core.find_list(&name).map(|l| l.id))
+                .transpose()?,
+            tags: Vec::new(),
+            nag_hours: patch.nag_hours,
+        };
+        for (names, add) in [(&patch.add_tags, true), (&patch.remove_tags, false)] {
+            for name in names {
+                changes.tags.push((self.core.find_tag(name)?.id, add));
+            }
+        }
+
+        let id = task.id;
+        let task = if changes.is_empty() {
+            task
+        } else {
+            self.core.edit(vec![task], &changes)?.remove(0)
+        };
+        match patch.completed {
+            Some(true) => {
+                let completed = self.core.complete(vec![task])?;
+                for (issue, result) in self.core.close_issues(&completed) {
+                    if let Err(e) = result {
+                        tracing::warn!("Could not close {}: {:#}", issue, e);
+                    }
+                }
+            }
+            Some(false) => {
+                self.core.reopen(vec![task])?;
+            }
+            None => {}
+        }
+        Ok(Response::json(200, json!(self.core.db().get_task(id)?)))
+    }
+
+    fn delete_task(&self, id: &str) -> Result<Response> {
+        let task = self.task(id)?;
+        self.core.delete(&[task])?;
+        Ok(Response::empty(204))
+    }
+
+    fn create_list(&self, request: &Request) -> Result<Response> {
+        let input: ListInput = body(request)?;
+        let list = self.core.create_list(&input.name, input.icon.as_deref())?;
+        Ok(Response::json(201, json!(list)))
+    }
+
+    fn create_tag(&self, request: &Request) -> Result<Response> {
+        let input: TagInput = body(request)?;
+        let tag = self.core.create_tag(&input.name, input.color.as_deref())?;
+        Ok(Response::json(201, json!(tag)))
+    }
+
+    /// The task with ID `id`, or a [`LookupError::NotFound`]
+    fn task(&self, id: &str) -> Result<Task> {
+        let not_found = || LookupError::NotFound(format!("Task not found: {}", id)).into();
+        let Ok(uuid) = Uuid::parse_str(id) else {
+            return Err(not_found());
+        };
+        self.core
+            .db()
+            .get_task(uuid)
+            .map_err(|e| match e.downcast_ref::<rusqlite::Error>() {
+                Some(rusqlite::Error::QueryReturnedNoRows) => not_found(),
+                _ => e,
+            })
+    }
+}
+
+/// The response to a request that failed with `e`: 404 or 409 for names
+/// that don't resolve, 500 when the database failed and 400 otherwise
+fn failure(e: anyhow::Error) -> Response {
+    match e.downcast_ref::<LookupError>() {
+        Some(LookupError::NotFound(message)) => Response::error(404, message.clone()),
+        Some(LookupError::Ambiguous(message)) => Response::error(409, message.clone()),
+        None if e.chain().any(|cause| cause.is::<rusqlite::Error>()) => {
+            tracing::warn!("HTTP request failed: {:#}", e);
+            Response::error(500, "Database error")
+        }
+        None => Response::error(400, format!("{:#}", e)),
+    }
+}
+
+/// Body of `POST /tasks`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskInput {
+    title: String,
+    description: Option<String>,
+    url: Option<String>,
+    priority: Option<String>,
+    due: Option<String>,
+    list: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Body of `PATCH /tasks/{id}`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskPatch {
+    title: Option<String>,
+    /// `null` clears the description, leaving the field out keeps it
+    #[serde(default, deserialize_with = "present")]
+    description: Option<Option<String>>,
+    /// `null` clears the URL, leaving the field out keeps it
+    #[serde(default, deserialize_with = "present")]
+    url: Option<Option<String>>,
+    completed: Option<bool>,
+    priority: Option<String>,
+    /// `null` clears the due date, leaving the field out keeps it
+    #[serde(default, deserialize_with = "present")]
+    due: Option<Option<String>>,
+    list: Option<String>,
+    #[serde(default)]
+    add_tags: Vec<String>,
+    #[serde(default)]
+    remove_tags: Vec<String>,
+    /// Hours between reminders while overdue (0: the configured default);
+    /// `null` stops nagging
+    #[serde(default, deserialize_with = "present")]
+    nag_hours: Option<Option<u32>>,
+}
+
+/// Body of `POST /lists`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ListInput {
+    name: String,
+    icon: Option<String>,
+}
+
+/// Body of `POST /tags`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TagInput {
+    name: String,
+    color: Option<String>,
+}
+
+/// Tell a `null` field apart from a missing one
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    d: D,
+) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(d).map(Some)
+}
+
+fn body<T: for<'de> Deserialize<'de>>(request: &Request) -> Result<T> {
+    serde_json::from_slice(&request.body).context("Invalid JSON body")
+}
+
+fn priority(s: &str) -> Result<crate::models::Priority> {
+    quickadd::parse_priority(s).ok_or_else(|| anyhow::anyhow!("Unknown priority: {}", s))
+}
+
+fn due(s: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    quickadd::parse_due_date(s).ok_or_else(|| anyhow::anyhow!("Invalid date: {}", s))
+}
+
+/// Compare tokens without stopping at the first difference
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Decode `%XX` escapes and `+` in a query string component
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::Database;
+
+    #[test]
+    fn test_load_or_create_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TOKEN_FILE);
+
+        let (token, generated) = load_or_create_token(&path).unwrap();
+        assert!(generated && token.len() == 32);
+        assert_eq!(load_or_create_token(&path).unwrap(), (token, false));
+
+        std::fs::write(&path, " hand-picked\n").unwrap();
+        assert_eq!(
+            load_or_create_token(&path).unwrap(),
+            ("hand-picked".to_string(), false)
+        );
+    }
+
+    fn server() -> Server {
+        let db = Database::open_in_memory().unwrap();
+        Server::new(TickitCore::new(db, Config::default()), "secret").with_calendar_token("cal")
+    }
+
+    fn request(method: &str, path: &str, body: Option<Value>) -> Request {
+        let raw = match body {
+            Some(body) => {
+                let body = body.to_string();
+                format!(
+                    "{} {} HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: {}\r\n\r\n{}",
+                    method,
+                    path,
+                    body.len(),
+                    body
+                )
+            }
+            None => format!(
+                "{} {} HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+                method, path
+            ),
+        };
+        Request::read(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "GET /tasks?q=buy+milk&list=My%20List HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let req = Request::read(&mut raw.as_bytes()).unwrap();
+        assert_eq!(req.method, "GET");
+        assert_eq!(req.path, "/tasks");
+        assert_eq!(req.param("q"), Some("buy milk"));
+        assert_eq!(req.param("list"), Some("My List"));
+        assert_eq!(req.header("host"), Some("localhost"));
+        assert_eq!(decode("100%"), "100%");
+
+        // Lines and headers are capped, so a client can't make the server buffer without end
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE as usize));
+        assert!(Request::read(&mut long.as_bytes()).is_err());
+        let many = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-A: b\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert!(Request::read(&mut many.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_requires_token() {
+        let server = server();
+        let mut req = request("GET", "/tasks", None);
+        req.headers.clear();
+        assert_eq!(server.handle(&req).status, 401);
+        req.headers
+            .push(("authorization".to_string(), "Bearer wrong!".to_string()));
+        assert_eq!(server.handle(&req).status, 401);
+        assert_eq!(server.handle(&request("GET", "/tasks", None)).status, 200);
+        assert_eq!(server.handle(&request("GET", "/nope", None)).status, 404);
+        assert_eq!(server.handle(&request("PUT", "/tasks", None)).status, 405);
+    }
+
+    #[test]
+    fn test_task_round_trip() {
+        let server = server();
+        let created = server.handle(&request(
+            "POST",
+            "/tasks",
+            Some(json!({"title": "Buy milk", "priority": "high", "due": "2030-01-02"})),
+        ));
+        assert_eq!(created.status, 201);
+        let id = created.body.unwrap()["id"].as_str().unwrap().to_string();
+
+        let unknown = server.handle(&request(
+            "POST",
+            "/tasks",
+            Some(json!({"title": "x", "list": "Nowhere"})),
+        ));
+        assert_eq!(unknown.status, 404);
+        let empty = server.handle(&request("POST", "/tasks", Some(json!({"title": " "}))));
+        assert_eq!(empty.status, 400);
+
+        let path = format!("/tasks/{}", id);
+        let patched = server.handle(&request(
+            "PATCH",
+            &path,
+            Some(json!({"completed": true, "due": null})),
+        ));
+        assert_eq!(patched.status, 200);
+        let task = patched.body.unwrap();
+        assert_eq!(task["completed"], true);
+        assert_eq!(task["due_date"], Value::Null);
+        assert_eq!(task["priority"], "high");
+
+        let renamed = server.handle(&request(
+            "PATCH",
+            &path,
+            Some(
+                json!({"title": "Buy oat milk", "description": "2 cartons", "url": "example.com"}),
+            ),
+        ));
+        let task = renamed.body.unwrap();
+        assert_eq!(task["title"], "Buy oat milk");
+        assert_eq!(task["description"], "2 cartons");
+        assert_eq!(task["url"], "https://example.com");
+        let cleared = server.handle(&request(
+            "PATCH",
+            &path,
+            Some(json!({"description": null, "url": null})),
+        ));
+        let task = cleared.body.unwrap();
+        assert_eq!(task["description"], Value::Null);
+        assert_eq!(task["url"], Value::Null);
+        let untitled = server.handle(&request("PATCH", &path, Some(json!({"title": ""}))));
+        assert_eq!(untitled.status, 400);
+
+        let open = server.handle(&request("GET", "/tasks", None)).body.unwrap();
+        assert_eq!(open.as_array().unwrap().len(), 0);
+        let all = server
+            .handle(&request("GET", "/tasks?all=true", None))
+            .body
+            .unwrap();
+        assert_eq!(all.as_array().unwrap().len(), 1);
+
+        assert_eq!(server.handle(&request("DELETE", &path, None)).status, 204);
+        assert_eq!(server.handle(&request("GET", &path, None)).status, 404);
+    }
+
+    #[test]
+    fn test_failure_status() {
+        let status = |e: anyhow::Error| failure(e).status;
+        assert_eq!(status(LookupError::NotFound("x".into()).into()), 404);
+        assert_eq!(status(anyhow::anyhow!("Invalid date: someday")), 400);
+        let broken = anyhow::Error::from(rusqlite::Error::InvalidQuery).context("Saving");
+        assert_eq!(status(broken), 500);
+    }
+
+    #[test]
+    fn test_calendar_feed() {
+        let server = server();
+        for (title, due) in [("Dentist", Some("2030-01-02")), ("Someday", None)] {
+            let body = match due {
+                Some(due) => json!({"title": title, "due": due}),
+                None => json!({"title": title}),
+            };
+            server.handle(&request("POST", "/tasks", Some(body)));
+        }
+
+        // The API token doesn't open the feed, and the feed token doesn't open the API
+        assert_eq!(
+            server.handle(&request("GET", "/calendar.ics", None)).status,
+            401
+        );
+        let wrong = server.handle(&request("GET", "/calendar.ics?token=secret", None));
+        assert_eq!(wrong.status, 401);
+        let mut api = request("GET", "/tasks?token=cal", None);
+        api.headers.clear();
+        assert_eq!(server.handle(&api).status, 401);
+
+        let feed = server.handle(&request("GET", "/calendar.ics?token=cal", None));
+        assert_eq!(feed.status, 200);
+        let (content_type, ical) = feed.text.unwrap();
+        assert!(content_type.starts_with("text/calendar"));
+        assert!(ical.contains("SUMMARY:Dentist"));
+        assert!(!ical.contains("Someday"));
+    }
+}
@@ -0,0 +1,542 @@
+//! Completion statistics and a year-at-a-glance heatmap
+//!
+//! Everything is computed from `completed_at`, archived tasks included, in
+//! local time. The heatmap has a column per week and a row per weekday,
+//! like a GitHub contribution graph; each day's shade is its completion
+//! count relative to the busiest day.
+//!
+//! A burndown follows one list or tag day by day: how many of its tasks
+//! were still open at the end of each day, and how many had been done since
+//! the first. Deleted tasks are gone from the database, so they don't count.
+//!
+//! Usage counts each list's or tag's tasks, archived ones included, so
+//! tags nothing uses anymore stand out.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::models::{List, Tag, Task};
+
+/// Weeks shown in the heatmap: a year, and the current week
+pub const HEATMAP_WEEKS: usize = 53;
+
+/// Days a burndown covers unless told otherwise: four weeks
+pub const BURNDOWN_DAYS: usize = 28;
+
+/// Completions per local day
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completions(BTreeMap<NaiveDate, u32>);
+
+impl Completions {
+    /// Every completion in the database
+    pub fn load(db: &Database) -> Result<Self> {
+        Ok(Self::from_days(
+            db.get_completion_times()?
+                .into_iter()
+                .map(|at| at.with_timezone(&Local).date_naive()),
+        ))
+    }
+
+    /// Count completions on each day
+    pub fn from_days(days: impl IntoIterator<Item = NaiveDate>) -> Self {
+        let mut counts = BTreeMap::new();
+        for day in days {
+            *counts.entry(day).or_insert(0) += 1;
+        }
+        Self(counts)
+    }
+
+    /// Completions on `day`
+    pub fn on(&self, day: NaiveDate) -> u32 {
+        self.0.get(&day).copied().unwrap_or(0)
+    }
+
+    /// Days with at least one completion and their counts, oldest first
+    pub fn days(&self) -> impl Iterator<Item = (NaiveDate, u32)> + '_ {
+        self.0.iter().map(|(day, count)| (*day, *count))
+    }
+
+    /// Completions from `from` to `to`, inclusive
+    pub fn between(&self, from: NaiveDate, to: NaiveDate) -> u32 {
+        self.0.range(from..=to).map(|(_, count)| count).sum()
+    }
+
+    /// Days in a row with completions, ending today (or yesterday, so a
+    /// streak isn't lost before the day is over)
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        let Some(mut day) = [today, today - Duration::days(1)]
+            .into_iter()
+            .find(|day| self.on(*day) > 0)
+        else {
+            return 0;
+        };
+        let mut streak = 0;
+        while self.on(day) > 0 {
+            streak += 1;
+            day -= Duration::days(1);
+        }
+        streak
+    }
+
+    /// The most days in a row with completions
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for day in self.0.keys() {
+            streak = match previous {
+                Some(p) if p.succ_opt() == Some(*day) => streak + 1,
+                _ => 1,
+            };
+            longest = longest.max(streak);
+            previous = Some(*day);
+        }
+        longest
+    }
+}
+
+/// Task counts and streaks for `tickit stats` and the TUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub open: u32,
+    pub completed: u32,
+    pub completed_this_week: u32,
+    pub completed_last_year: u32,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+impl Summary {
+    /// Summarize `completions` and the open tasks as of `today`
+    pub fn new(
+        open: u32,
+        completions: &Completions,
+        today: NaiveDate,
+        week_starts_on: Weekday,
+    ) -> Self {
+        let week_start = today - Duration::days(today.weekday().days_since(week_starts_on).into());
+        Self {
+            open,
+            completed: completions.days().map(|(_, count)| count).sum(),
+            completed_this_week: completions.between(week_start, today),
+            completed_last_year: completions.between(today - Duration::days(364), today),
+            current_streak: completions.current_streak(today),
+            longest_streak: completions.longest_streak(),
+        }
+    }
+
+    /// Load everything from the database
+    pub fn load(db: &Database, today: NaiveDate, week_starts_on: Weekday) -> Result<Self> {
+        let open = db.get_total_task_count(false)?.max(0) as u32;
+        Ok(Self::new(
+            open,
+            &Completions::load(db)?,
+            today,
+            week_starts_on,
+        ))
+    }
+}
+
+/// Heatmap of the last [`HEATMAP_WEEKS`] weeks up to `today`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    /// First day of the first column
+    pub start: NaiveDate,
+    pub today: NaiveDate,
+    /// Shade of each cell by weekday row, then week column: 0 for none, 1-4
+    /// from fewest to most completions, None for days after today
+    pub rows: Vec<Vec<Option<u8>>>,
+}
+
+impl Heatmap {
+    pub fn new(completions: &Completions, today: NaiveDate, week_starts_on: Weekday) -> Self {
+        let into_week = today.weekday().days_since(week_starts_on);
+        let start = today - Duration::days(into_week as i64 + 7 * (HEATMAP_WEEKS as i64 - 1));
+        let most = completions.0.range(start..=today).map(|(_, c)| *c).max();
+        let rows = (0..7)
+            .map(|row| {
+                (0..HEATMAP_WEEKS)
+                    .map(|week| {
+                        let day = start + Duration::days((week * 7 + row) as i64);
+                        (day <= today).then(|| shade(completions.on(day), most.unwrap_or(0)))
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { start, today, rows }
+    }
+
+    /// Abbreviated month names over the first column of each month
+    pub fn month_labels(&self) -> String {
+        let mut labels = vec![' '; HEATMAP_WEEKS];
+        let mut last_month = None;
+        for week in 0..HEATMAP_WEEKS {
+            let day = self.start + Duration::days(week as i64 * 7);
+            if last_month != Some(day.month()) {
+                last_month = Some(day.month());
+                // Skip a label that would run into the next one
+                let name = day.format("%b").to_string();
+                if labels[week.saturating_sub(1)] == ' ' && week + name.len() <= HEATMAP_WEEKS {
+                    for (i, c) in name.chars().enumerate() {
+                        labels[week + i] = c;
+                    }
+                }
+            }
+        }
+        labels.into_iter().collect()
+    }
+
+    /// Weekday of each row
+    pub fn row_day(&self, row: usize) -> Weekday {
+        (self.start + Duration::days(row as i64)).weekday()
+    }
+}
+
+/// Tasks a burndown follows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scope {
+    #[default]
+    All,
+    List(uuid::Uuid),
+    Tag(uuid::Uuid),
+}
+
+impl Scope {
+    pub fn includes(&self, task: &Task) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::List(id) => task.list_id == *id,
+            Scope::Tag(id) => task.tag_ids.contains(id),
+        }
+    }
+}
+
+/// The end of one day in a burndown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BurndownDay {
+    pub day: NaiveDate,
+    /// Tasks created by then and not yet completed
+    pub open: u32,
+    /// Tasks completed since the first day
+    pub done: u32,
+}
+
+/// Open and done tasks for each of the last few days up to today
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Burndown {
+    pub days: Vec<BurndownDay>,
+}
+
+impl Burndown {
+    /// Follow `tasks` over the `days` days ending `today`
+    pub fn new<'a>(
+        tasks: impl IntoIterator<Item = &'a Task>,
+        today: NaiveDate,
+        days: usize,
+    ) -> Self {
+        let local = |at: chrono::DateTime<chrono::Utc>| at.with_timezone(&Local).date_naive();
+        let spans: Vec<(NaiveDate, Option<NaiveDate>)> = tasks
+            .into_iter()
+            .map(|task| {
+                let completed = task
+                    .completed
+                    .then(|| task.completed_at.map(local))
+                    .flatten();
+                (local(task.created_at), completed)
+            })
+            .collect();
+        let start = today - Duration::days(days.max(1) as i64 - 1);
+        let days = (0..days.max(1))
+            .map(|i| {
+                let day = start + Duration::days(i as i64);
+                let mut open = 0;
+                let mut done = 0;
+                for (created, completed) in &spans {
+                    match completed {
+                        Some(at) if *at <= day => done += u32::from(*at >= start),
+                        _ if *created <= day => open += 1,
+                        _ => {}
+                    }
+                }
+                BurndownDay { day, open, done }
+            })
+            .collect();
+        Self { days }
+    }
+
+    /// Every task in `scope`, archived ones included
+    pub fn load(db: &Database, scope: Scope, today: NaiveDate, days: usize) -> Result<Self> {
+        let mut tasks = db.get_all_tasks()?;
+        tasks.extend(db.get_archived_tasks()?);
+        Ok(Self::new(
+            tasks.iter().filter(|task| scope.includes(task)),
+            today,
+            days,
+        ))
+    }
+
+    /// The highest open + done of any day, for scaling a chart
+    pub fn most(&self) -> u32 {
+        self.days
+            .iter()
+            .map(|day| day.open + day.done)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// How much one list or tag is used
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Usage {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub tasks: u32,
+    pub open: u32,
+    pub completed: u32,
+    /// Share of its tasks that are done, 0-1; none without tasks
+    pub completion_rate: Option<f64>,
+    /// Latest change to any of its tasks
+    pub last_activity: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Usage {
+    /// Count the tasks of `tasks` in `scope`
+    pub fn new<'a>(
+        scope: Scope,
+        id: uuid::Uuid,
+        name: &str,
+        tasks: impl IntoIterator<Item = &'a Task>,
+    ) -> Self {
+        let (mut open, mut completed, mut last_activity) = (0, 0, None);
+        for task in tasks.into_iter().filter(|task| scope.includes(task)) {
+            if task.completed {
+                completed += 1;
+            } else {
+                open += 1;
+            }
+            last_activity = last_activity.max(Some(task.updated_at));
+        }
+        let total = open + completed;
+        Self {
+            id,
+            name: name.to_string(),
+            tasks: total,
+            open,
+            completed,
+            completion_rate: (total > 0).then(|| f64::from(completed) / f64::from(total)),
+            last_activity,
+        }
+    }
+
+    /// Usage of every list, in sidebar order
+    pub fn lists(lists: &[List], tasks: &[Task]) -> Vec<Self> {
+        lists
+            .iter()
+            .map(|list| Self::new(Scope::List(list.id), list.id, &list.name, tasks))
+            .collect()
+    }
+
+    /// Usage of every tag, most used first
+    pub fn tags(tags: &[Tag], tasks: &[Task]) -> Vec<Self> {
+        let mut usage: Vec<Self> = tags
+            .iter()
+            .map(|tag| Self::new(Scope::Tag(tag.id), tag.id, &tag.name, tasks))
+            .collect();
+        usage.sort_by(|a, b| b.tasks.cmp(&a.tasks).then_with(|| a.name.cmp(&b.name)));
+        usage
+    }
+
+    /// Every task, archived ones included, for counting usage
+    pub fn load_tasks(db: &Database) -> Result<Vec<Task>> {
+        let mut tasks = db.get_all_tasks()?;
+        tasks.extend(db.get_archived_tasks()?);
+        Ok(tasks)
+    }
+}
+
+/// Tags whose names differ only in case, punctuation or a plural "s",
+/// and so could likely be merged; groups of two or more names
+pub fn similar_tags(tags: &[Tag]) -> Vec<Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for tag in tags {
+        let mut key: String = tag
+            .name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if key.len() > 3 && key.ends_with('s') {
+            key.pop();
+        }
+        groups.entry(key).or_default().push(tag.name.clone());
+    }
+    groups
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .collect()
+}
+
+/// 1-4 by quarters of the busiest day, 0 for none
+fn shade(count: u32, most: u32) -> u8 {
+    if count == 0 || most == 0 {
+        0
+    } else {
+        (count * 4).div_ceil(most).min(4) as u8
+    }
+}
+
+/// Characters for shades 0-4, in Unicode or ASCII
+pub fn shade_glyph(shade: u8, unicode: bool) -> char {
+    let glyphs = if unicode {
+        ['·', '░', '▒', '▓', '█']
+    } else {
+        ['.', '-', '+', '*', '#']
+    };
+    glyphs[usize::from(shade.min(4))]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_streaks_and_summary() {
+        let today = date("2026-03-12");
+        let completions = Completions::from_days(
+            [
+                "2026-02-01",
+                "2026-02-02",
+                "2026-02-03",
+                "2026-02-04",
+                "2026-03-10",
+                "2026-03-11",
+                "2026-03-11",
+            ]
+            .map(date),
+        );
+        assert_eq!(completions.current_streak(today), 2);
+        assert_eq!(completions.current_streak(date("2026-03-14")), 0);
+        assert_eq!(completions.longest_streak(), 4);
+
+        let summary = Summary::new(5, &completions, today, Weekday::Mon);
+        assert_eq!(
+            summary,
+            Summary {
+                open: 5,
+                completed: 7,
+                completed_this_week: 3,
+                completed_last_year: 7,
+                current_streak: 2,
+                longest_streak: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_burndown() {
+        let today = Local::now().date_naive();
+        let days_ago = |n: i64| {
+            (today - Duration::days(n))
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_local_timezone(Local)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let list = uuid::Uuid::new_v4();
+        let task = |created: i64, completed: Option<i64>| {
+            let mut task = Task::new("Task", list);
+            task.created_at = days_ago(created);
+            task.completed = completed.is_some();
+            task.completed_at = completed.map(days_ago);
+            task
+        };
+        let tasks = [
+            task(10, Some(8)), // done before the burndown starts
+            task(5, Some(1)),
+            task(5, None),
+            task(1, None),
+        ];
+
+        let burndown = Burndown::new(&tasks, today, 3);
+        let counts: Vec<(u32, u32)> = burndown.days.iter().map(|d| (d.open, d.done)).collect();
+        assert_eq!(counts, [(2, 0), (2, 1), (2, 1)]);
+        assert_eq!(burndown.days[2].day, today);
+        assert_eq!(burndown.most(), 3);
+
+        assert!(Scope::List(list).includes(&tasks[0]));
+        assert!(!Scope::Tag(list).includes(&tasks[0]));
+    }
+
+    #[test]
+    fn test_usage() {
+        let list = List::new("Work");
+        let (bug, bugs, docs) = (Tag::new("bug"), Tag::new("Bugs"), Tag::new("docs"));
+        let mut done = Task::new("Fix crash", list.id);
+        done.tag_ids = vec![bug.id];
+        done.completed = true;
+        let mut open = Task::new("Fix login", list.id);
+        open.tag_ids = vec![bug.id];
+        open.updated_at = done.updated_at + Duration::hours(1);
+        let tasks = [done, open.clone()];
+
+        let lists = Usage::lists(std::slice::from_ref(&list), &tasks);
+        assert_eq!(
+            (lists[0].tasks, lists[0].open, lists[0].completed),
+            (2, 1, 1)
+        );
+        assert_eq!(lists[0].completion_rate, Some(0.5));
+        assert_eq!(lists[0].last_activity, Some(open.updated_at));
+
+        let tags = Usage::tags(&[docs.clone(), bugs.clone(), bug.clone()], &tasks);
+        let counts: Vec<(&str, u32)> = tags.iter().map(|u| (u.name.as_str(), u.tasks)).collect();
+        assert_eq!(counts, [("bug", 2), ("Bugs", 0), ("docs", 0)]);
+        assert_eq!(tags[2].completion_rate, None);
+        assert_eq!(tags[2].last_activity, None);
+
+        assert_eq!(
+            similar_tags(&[bug, docs, bugs]),
+            [vec!["bug".to_string(), "Bugs".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let today = date("2026-03-12");
+        let completions = Completions::from_days(
+            [
+                "2026-03-09",
+                "2026-03-11",
+                "2026-03-11",
+                "2026-03-11",
+                "2026-03-11",
+            ]
+            .map(date),
+        );
+        let heatmap = Heatmap::new(&completions, today, Weekday::Mon);
+
+        assert_eq!(heatmap.rows.len(), 7);
+        assert_eq!(heatmap.start.weekday(), Weekday::Mon);
+        assert_eq!(heatmap.row_day(0), Weekday::Mon);
+        let last = HEATMAP_WEEKS - 1;
+        assert_eq!(heatmap.rows[0][last], Some(1)); // Monday, one
+        assert_eq!(heatmap.rows[2][last], Some(4)); // Wednesday, the busiest
+        assert_eq!(heatmap.rows[3][last], Some(0)); // today, none yet
+        assert_eq!(heatmap.rows[4][last], None); // tomorrow
+        assert_eq!(shade(1, 1), 4);
+        assert_eq!(shade(2, 7), 2);
+        assert_eq!(heatmap.month_labels().len(), HEATMAP_WEEKS);
+        // A year back from March, with no room left for the last "Mar"
+        let labels = heatmap.month_labels();
+        assert!(labels.starts_with("Mar"));
+        assert!(labels.trim_end().ends_with("Feb"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Content-addressed file blobs for sync
+//!
+//! Files attached to tasks are kept by the SHA-256 of their contents in
+//! `blobs/` next to the database, so a file is stored and sent once however
+//! many tasks refer to it. They travel separately from records, in chunks,
+//! and a broken transfer resumes where it stopped. A sync uploads the blobs
+//! of new attachments before their records, then downloads the blobs of
+//! attachments it doesn't have:
+//!
+//! - `HEAD /api/v1/blobs/<hash>` answers `Upload-Offset`, the bytes the
+//!   server has so far (404 if none)
+//! - `PATCH /api/v1/blobs/<hash>` appends a chunk at `Upload-Offset`, with
+//!   the whole size in `Upload-Length`
+//! - `GET /api/v1/blobs/<hash>` with `Range: bytes=<offset>-` downloads the
+//!   rest of a partly downloaded blob
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Bytes sent per upload request
+pub const BLOB_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// A stored file, named by its contents
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlobRef {
+    /// SHA-256 of the contents, in lowercase hex
+    pub hash: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// The local blob directory
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    dir: PathBuf,
+}
+
+impl BlobStore {
+    /// The store next to the database file of `db`
+    pub fn for_database(db: &crate::db::Database) -> Result<Self> {
+        let path = db
+            .path()
+            .context("Attachments need a database file, not one in memory")?;
+        let dir = path.parent().context("Database path has no directory")?;
+        Ok(Self::at(dir.join("blobs")))
+    }
+
+    /// A store in `dir`
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Copy the file at `path` into the store
+    pub fn add(&self, path: &Path) -> Result<BlobRef> {
+        let file =
+            File::open(path).with_context(|| format!("Could not read {}", path.display()))?;
+        let blob = hash_reader(file)?;
+        if !self.contains(&blob.hash) {
+            let target = self.path(&blob.hash)?;
+            create_parent(&target)?;
+            // Copy under a temporary name so a half-copied file never looks stored
+            let partial = target.with_extension("copy");
+            fs::copy(path, &partial)?;
+            fs::rename(&partial, &target)?;
+        }
+        Ok(blob)
+    }
+
+    /// Store `data`
+    pub fn add_bytes(&self, data: &[u8]) -> Result<BlobRef> {
+        let blob = hash_reader(data)?;
+        if !self.contains(&blob.hash) {
+            let target = self.path(&blob.hash)?;
+            create_parent(&target)?;
+            let partial = target.with_extension("copy");
+            fs::write(&partial, data)?;
+            fs::rename(&partial, &target)?;
+        }
+        Ok(blob)
+    }
+
+    /// Whether the whole blob is stored
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path(hash).is_ok_and(|path| path.is_file())
+    }
+
+    /// Where the blob is (or would be) stored, e.g. `blobs/ba/ba7816bf…`
+    pub fn path(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid blob hash '{}'", hash);
+        }
+        let hash = hash.to_ascii_lowercase();
+        Ok(self.dir.join(&hash[..2]).join(hash))
+    }
+
+    /// Where a download in progress is kept until it's complete
+    pub(crate) fn partial_path(&self, hash: &str) -> Result<PathBuf> {
+        Ok(self.path(hash)?.with_extension("part"))
+    }
+
+    /// Move a finished download into place if its contents match `blob`,
+    /// removing it if they don't
+    pub(crate) fn finish_download(&self, blob: &BlobRef) -> Result<PathBuf> {
+        let partial = self.partial_path(&blob.hash)?;
+        let found = hash_reader(File::open(&partial)?)?;
+        if found != *blob {
+            let _ = fs::remove_file(&partial);
+            anyhow::bail!(
+                "Downloaded blob {} doesn't match (got {} bytes with hash {})",
+                blob.hash,
+                found.size,
+                found.hash
+            );
+        }
+        let target = self.path(&blob.hash)?;
+        fs::rename(&partial, &target)?;
+        Ok(target)
+    }
+}
+
+/// Hash and count everything `reader` gives
+pub fn hash_reader(mut reader: impl Read) -> Result<BlobRef> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(BlobRef { hash, size })
+}
+
+/// Append what `reader` gives to `path`, creating it if needed
+pub(crate) fn append(path: &Path, reader: &mut impl Read) -> Result<u64> {
+    create_parent(path)?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let written = io::copy(reader, &mut file)?;
+    file.flush()?;
+    Ok(written)
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::at(dir.path());
+
+        let blob = store.add_bytes(b"abc").unwrap();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            blob,
+            BlobRef {
+                hash: abc.to_string(),
+                size: 3
+            }
+        );
+        assert!(store.contains(abc));
+        assert_eq!(store.path(abc).unwrap(), dir.path().join("ba").join(abc));
+        assert!(store.path("../../etc/passwd").is_err());
+
+        // A download resumed in two parts is checked before it's kept
+        let other = hash_reader(&b"hello world"[..]).unwrap();
+        let partial = store.partial_path(&other.hash).unwrap();
+        append(&partial, &mut &b"hello "[..]).unwrap();
+        append(&partial, &mut &b"world"[..]).unwrap();
+        assert!(store.finish_download(&other).is_ok());
+        assert!(store.contains(&other.hash));
+
+        let corrupt = BlobRef {
+            hash: abc.replace('b', "c"),
+            size: 3,
+        };
+        append(
+            &store.partial_path(&corrupt.hash).unwrap(),
+            &mut &b"abc"[..],
+        )
+        .unwrap();
+        assert!(store.finish_download(&corrupt).is_err());
+        assert!(!store.partial_path(&corrupt.hash).unwrap().exists());
+    }
+}
@@ -0,0 +1,124 @@
+//! Previewing the first sync of a device that already has tasks
+//!
+//! A first sync uploads everything on this device and downloads everything
+//! on the server. When both sides were filled in separately that can leave
+//! two copies of every task, so the first sync is previewed from a
+//! download-only request and has to be confirmed.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::SyncRecord;
+
+/// What a first sync would do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncPreview {
+    /// Local records that would be uploaded
+    pub push: usize,
+    /// Server records that would be downloaded
+    pub pull: usize,
+    /// Records on both sides, where one copy would win
+    pub conflicts: usize,
+    /// Tasks, lists and tags with the same name on both sides but different
+    /// ids, which would end up twice
+    pub duplicates: usize,
+}
+
+impl SyncPreview {
+    /// Compare what this device would upload with what the server has
+    pub fn new(local: &[SyncRecord], remote: &[SyncRecord]) -> Self {
+        let remote_keys: HashSet<_> = remote.iter().map(SyncRecord::key).collect();
+        let remote_names: HashSet<_> = remote.iter().filter_map(name_key).collect();
+        let conflicts = local
+            .iter()
+            .filter(|r| remote_keys.contains(&r.key()))
+            .count();
+        let duplicates = local
+            .iter()
+            .filter(|r| !remote_keys.contains(&r.key()))
+            .filter_map(name_key)
+            .filter(|name| remote_names.contains(name))
+            .count();
+        Self {
+            push: local.len(),
+            pull: remote.len(),
+            conflicts,
+            duplicates,
+        }
+    }
+
+    /// One line for a confirmation prompt
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "upload {} local records, download {} from the server",
+            self.push, self.pull
+        );
+        if self.conflicts > 0 {
+            text.push_str(&format!(", {} on both sides", self.conflicts));
+        }
+        if self.duplicates > 0 {
+            text.push_str(&format!(", {} may be duplicated", self.duplicates));
+        }
+        text
+    }
+}
+
+/// Whether a sync needs a preview first: this device never synced and
+/// already has tasks of its own
+pub fn needs_preview(last_sync: Option<DateTime<Utc>>, local: &[SyncRecord]) -> bool {
+    last_sync.is_none() && local.iter().any(|r| matches!(r, SyncRecord::Task(_)))
+}
+
+/// Kind and lowercased name of a task, list or tag, to spot copies made on
+/// two devices
+fn name_key(record: &SyncRecord) -> Option<(&'static str, String)> {
+    match record {
+        SyncRecord::Task(task) => Some(("task", task.title.trim().to_lowercase())),
+        SyncRecord::List(list) => Some(("list", list.name.trim().to_lowercase())),
+        SyncRecord::Tag(tag) => Some(("tag", tag.name.trim().to_lowercase())),
+        SyncRecord::TaskTag(_)
+        | SyncRecord::Comment(_)
+        | SyncRecord::Attachment(_)
+        | SyncRecord::Deleted { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{List, Task};
+
+    #[test]
+    fn test_sync_preview() {
+        let inbox = List::new("Inbox");
+        let other_inbox = List::new("inbox");
+        let shared = Task::new("Shared", inbox.id);
+        let local = vec![
+            SyncRecord::List(inbox.clone()),
+            SyncRecord::Task(shared.clone()),
+            SyncRecord::Task(Task::new("Buy milk", inbox.id)),
+            SyncRecord::Task(Task::new("Only here", inbox.id)),
+        ];
+        let remote = vec![
+            SyncRecord::List(other_inbox.clone()),
+            SyncRecord::Task(shared),
+            SyncRecord::Task(Task::new("Buy Milk ", other_inbox.id)),
+        ];
+
+        let preview = SyncPreview::new(&local, &remote);
+        assert_eq!(
+            preview,
+            SyncPreview {
+                push: 4,
+                pull: 3,
+                conflicts: 1,
+                duplicates: 2,
+            }
+        );
+        assert!(needs_preview(None, &local));
+        assert!(!needs_preview(Some(Utc::now()), &local));
+        assert!(!needs_preview(None, &local[..1]));
+    }
+}
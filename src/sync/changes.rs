@@ -0,0 +1,151 @@
+//! Moving records between the local database and a sync exchange
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use super::{BlobRef, BlobStore, RecordType, SyncRecord, SyncResponse};
+use crate::db::Database;
+
+/// Local records changed since `since` (everything for a full sync), with
+/// tombstones for what was deleted
+#[tracing::instrument(level = "debug", skip(db))]
+pub fn local_changes(db: &Database, since: Option<DateTime<Utc>>) -> Result<Vec<SyncRecord>> {
+    let Some(since) = since else {
+        let mut changes: Vec<SyncRecord> = Vec::new();
+        changes.extend(db.get_all_tasks()?.into_iter().map(SyncRecord::Task));
+        changes.extend(db.get_lists()?.into_iter().map(SyncRecord::List));
+        changes.extend(db.get_tags()?.into_iter().map(SyncRecord::Tag));
+        changes.extend(db.get_all_comments()?.into_iter().map(SyncRecord::Comment));
+        changes.extend(
+            db.get_all_attachments()?
+                .into_iter()
+                .map(SyncRecord::Attachment),
+        );
+        return Ok(changes);
+    };
+
+    let mut changes: Vec<SyncRecord> = Vec::new();
+    changes.extend(db.get_tasks_since(since)?.into_iter().map(SyncRecord::Task));
+    changes.extend(db.get_lists_since(since)?.into_iter().map(SyncRecord::List));
+    changes.extend(db.get_tags_since(since)?.into_iter().map(SyncRecord::Tag));
+    changes.extend(
+        db.get_comments_since(since)?
+            .into_iter()
+            .map(SyncRecord::Comment),
+    );
+    changes.extend(
+        db.get_attachments_since(since)?
+            .into_iter()
+            .map(SyncRecord::Attachment),
+    );
+    for (id, record_type, deleted_at) in db.get_tombstones_since(since)? {
+        let record_type = match record_type.as_str() {
+            "task" => RecordType::Task,
+            "list" => RecordType::List,
+            "tag" => RecordType::Tag,
+            "task_tag" => RecordType::TaskTag,
+            "comment" => RecordType::Comment,
+            "attachment" => RecordType::Attachment,
+            _ => continue,
+        };
+        changes.push(SyncRecord::Deleted {
+            id,
+            record_type,
+            deleted_at,
+        });
+    }
+    Ok(changes)
+}
+
+/// Apply the server's changes to the local database, returning how many applied
+#[tracing::instrument(level = "debug", skip_all, fields(received = response.changes.len()))]
+pub fn apply_changes(db: &Database, response: &SyncResponse) -> usize {
+    // Sort changes: lists first, then tags, then tasks, then comments and
+    // attachments (to satisfy FK constraints)
+    let mut lists = Vec::new();
+    let mut tags = Vec::new();
+    let mut tasks = Vec::new();
+    let mut task_tags = Vec::new();
+    let mut comments = Vec::new();
+    let mut deletes = Vec::new();
+
+    for record in &response.changes {
+        match record {
+            SyncRecord::List(_) => lists.push(record),
+            SyncRecord::Tag(_) => tags.push(record),
+            SyncRecord::Task(_) => tasks.push(record),
+            SyncRecord::TaskTag(_) => task_tags.push(record),
+            SyncRecord::Comment(_) | SyncRecord::Attachment(_) => comments.push(record),
+            SyncRecord::Deleted { .. } => deletes.push(record),
+        }
+    }
+
+    // Disable FK constraints during sync
+    let _ = db.execute_raw("PRAGMA foreign_keys = OFF");
+
+    let mut applied = 0;
+    for record in lists
+        .into_iter()
+        .chain(tags)
+        .chain(tasks)
+        .chain(task_tags)
+        .chain(comments)
+        .chain(deletes)
+    {
+        let result = match record {
+            SyncRecord::Task(task) => db.upsert_task(task),
+            SyncRecord::List(list) => db.upsert_list(list),
+            SyncRecord::Tag(tag) => db.upsert_tag(tag),
+            SyncRecord::TaskTag(link) => db.upsert_task_tag(link),
+            SyncRecord::Comment(comment) => db.upsert_comment(comment),
+            SyncRecord::Attachment(attachment) => db.upsert_attachment(attachment),
+            SyncRecord::Deleted {
+                id, record_type, ..
+            } => match record_type {
+                RecordType::Task => db.delete_task_by_id(*id),
+                RecordType::List => db.delete_list_by_id(*id),
+                RecordType::Tag => db.delete_tag_by_id(*id),
+                RecordType::TaskTag => Ok(()), // Handled by task update
+                RecordType::Comment => db.delete_comment(*id),
+                RecordType::Attachment => db.delete_attachment(*id),
+            },
+        };
+        if result.is_ok() {
+            applied += 1;
+        }
+    }
+
+    // Re-enable FK constraints
+    let _ = db.execute_raw("PRAGMA foreign_keys = ON");
+
+    applied
+}
+
+/// Blobs of the attachments among `records`, each once
+pub fn attachment_blobs(records: &[SyncRecord]) -> Vec<BlobRef> {
+    let mut blobs: Vec<BlobRef> = Vec::new();
+    for record in records {
+        if let SyncRecord::Attachment(attachment) = record {
+            let blob = BlobRef {
+                hash: attachment.hash.clone(),
+                size: attachment.size,
+            };
+            if !blobs.contains(&blob) {
+                blobs.push(blob);
+            }
+        }
+    }
+    blobs
+}
+
+/// Blobs of local attachments that aren't in `store`, such as those whose
+/// download failed
+pub fn missing_blobs(db: &Database, store: &BlobStore) -> Result<Vec<BlobRef>> {
+    let attachments = db.get_all_attachments()?;
+    let records: Vec<SyncRecord> = attachments
+        .into_iter()
+        .filter(|attachment| !store.contains(&attachment.hash))
+        .map(SyncRecord::Attachment)
+        .collect();
+    Ok(attachment_blobs(&records))
+}
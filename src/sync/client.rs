@@ -1,29 +1,80 @@
 //! Sync client for communicating with tickit-sync server
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use super::{SyncRecord, SyncRequest, SyncResponse, SyncStatus};
+use super::blobs::{self, BLOB_CHUNK_BYTES, BlobRef, BlobStore};
+use super::{SyncProgress, SyncRecord, SyncRequest, SyncResponse, SyncStatus};
 use crate::config::SyncConfig;
 
+/// Called after each batch of a sync is uploaded
+pub type ProgressFn<'a> = &'a mut dyn FnMut(SyncProgress);
+
+/// Failed background syncs retried before waiting for the next interval
+pub const MAX_SYNC_RETRIES: u32 = 5;
+
+/// Wait before the first retry of a failed sync; each retry doubles it
+const RETRY_BASE: Duration = Duration::from_secs(5);
+
+/// Longest wait between retries
+const RETRY_CAP: Duration = Duration::from_secs(5 * 60);
+
+/// How long to wait before retry number `attempt` (from 1) of a failed
+/// sync: exponential backoff, capped, with up to ±25% jitter so devices
+/// that failed together don't retry together
+pub fn retry_delay(attempt: u32) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+    let backoff = RETRY_BASE
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(RETRY_CAP);
+    // A fresh RandomState is randomly seeded, which is random enough here
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    let jitter = (random % 501) as f64 / 1000.0 - 0.25;
+    backoff.mul_f64(1.0 + jitter)
+}
+
 /// Client for syncing with a tickit-sync server
 pub struct SyncClient {
     config: SyncConfig,
     device_id: Uuid,
     status: SyncStatus,
+    /// Why `token_command` gave no token, if it failed
+    token_error: Option<String>,
 }
 
 impl SyncClient {
-    /// Create a new sync client
+    /// Create a new sync client, resolving the token from `token_command` if set
     pub fn new(config: SyncConfig) -> Self {
         // Generate or load persistent device ID
         let device_id = Self::get_or_create_device_id();
 
-        Self {
+        let mut client = Self {
             config,
             device_id,
             status: SyncStatus::default(),
+            token_error: None,
+        };
+        client.resolve_token();
+        client
+    }
+
+    /// Replace the configured token with the resolved one
+    fn resolve_token(&mut self) {
+        match self.config.resolve_token() {
+            Ok(token) => {
+                self.config.token = token;
+                self.token_error = None;
+            }
+            Err(e) => {
+                self.config.token = None;
+                self.token_error = Some(e.to_string());
+            }
         }
     }
 
@@ -40,6 +91,7 @@ impl SyncClient {
     /// Update configuration
     pub fn update_config(&mut self, config: SyncConfig) {
         self.config = config;
+        self.resolve_token();
     }
 
     /// Perform a sync operation
@@ -48,24 +100,83 @@ impl SyncClient {
         local_changes: Vec<SyncRecord>,
         last_sync: Option<DateTime<Utc>>,
     ) -> Result<SyncResponse> {
+        self.sync_with_progress(local_changes, last_sync, None)
+    }
+
+    /// [`sync`](Self::sync), uploading in batches of at most
+    /// `max_batch_records` records and `max_batch_bytes` bytes and calling
+    /// `progress` after each one
+    pub fn sync_with_progress(
+        &mut self,
+        local_changes: Vec<SyncRecord>,
+        last_sync: Option<DateTime<Utc>>,
+        mut progress: Option<ProgressFn>,
+    ) -> Result<SyncResponse> {
+        if let Some(e) = &self.token_error {
+            anyhow::bail!("{}", e);
+        }
         if !self.is_enabled() {
             anyhow::bail!("Sync is not enabled or not configured");
         }
+        if !self.config.network_allowed()? {
+            anyhow::bail!("Sync skipped: network_check says this network isn't allowed");
+        }
 
-        let server = self.config.server.as_ref().unwrap();
-        let token = self.config.token.as_ref().unwrap();
+        let server = self.config.server.clone().unwrap();
+        let token = self.config.token.clone().unwrap();
 
         self.status.syncing = true;
 
-        let request = SyncRequest {
-            device_id: self.device_id,
-            last_sync,
-            changes: local_changes,
+        let total = local_changes.len();
+        let batches = split_batches(
+            local_changes,
+            self.config.max_batch_records,
+            self.config.max_batch_bytes,
+        );
+        let count = batches.len();
+        let pause = Duration::from_millis(self.config.batch_pause_ms);
+        let mut sent = 0;
+        let mut merged = None;
+        let mut failed = None;
+        for (i, changes) in batches.into_iter().enumerate() {
+            if i > 0 && !pause.is_zero() {
+                std::thread::sleep(pause);
+            }
+            sent += changes.len();
+            let request = SyncRequest {
+                device_id: self.device_id,
+                last_sync,
+                changes,
+            };
+            match self.do_sync(&server, &token, &request) {
+                Ok(response) => merged = Some(merge_responses(merged, response)),
+                Err(e) if count > 1 => {
+                    failed = Some(e.context(format!("Batch {} of {} failed", i + 1, count)));
+                    break;
+                }
+                Err(e) => {
+                    failed = Some(e);
+                    break;
+                }
+            }
+            let step = SyncProgress {
+                sent,
+                total,
+                batch: i + 1,
+                batches: count,
+            };
+            self.status.progress = Some(step);
+            if let Some(progress) = progress.as_mut() {
+                progress(step);
+            }
+        }
+        let result = match failed {
+            Some(e) => Err(e),
+            None => Ok(merged.expect("at least one batch is sent")),
         };
 
-        let result = self.do_sync(server, token, &request);
-
         self.status.syncing = false;
+        self.status.progress = None;
 
         match &result {
             Ok(response) => {
@@ -73,6 +184,7 @@ impl SyncClient {
                 self.status.last_error = None;
             }
             Err(e) => {
+                tracing::warn!("Sync failed: {:#}", e);
                 self.status.last_error = Some(e.to_string());
             }
         }
@@ -80,14 +192,144 @@ impl SyncClient {
         result
     }
 
+    /// Upload the blobs of the attachments in `changes`, so no device gets
+    /// an attachment the server can't send
+    pub fn upload_blobs(&self, store: &BlobStore, changes: &[SyncRecord]) -> Result<()> {
+        for blob in super::attachment_blobs(changes) {
+            // One that isn't stored here came from the server and is still
+            // to be downloaded
+            if store.contains(&blob.hash) {
+                self.upload_blob(store, &blob)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Download those of `blobs` that aren't stored yet; one that fails is
+    /// logged and tried again on the next sync
+    pub fn download_blobs(&self, store: &BlobStore, blobs: &[BlobRef]) {
+        for blob in blobs {
+            if let Err(e) = self.download_blob(store, blob) {
+                tracing::warn!("Could not download blob {}: {:#}", blob.hash, e);
+            }
+        }
+    }
+
+    /// Upload a stored blob, continuing from what the server already has
+    pub fn upload_blob(&self, store: &BlobStore, blob: &BlobRef) -> Result<()> {
+        self.check_blob_size(blob)?;
+        let url = self.blob_url(&blob.hash)?;
+        let mut offset = self.server_blob_offset(&url)?;
+        if offset >= blob.size {
+            return Ok(());
+        }
+
+        let mut file = std::fs::File::open(store.path(&blob.hash)?)
+            .with_context(|| format!("Blob {} is not stored here", blob.hash))?;
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(offset))?;
+        let mut chunk = vec![0; BLOB_CHUNK_BYTES];
+        while offset < blob.size {
+            let n = std::io::Read::read(&mut file, &mut chunk)?;
+            if n == 0 {
+                anyhow::bail!("Blob {} is shorter than {} bytes", blob.hash, blob.size);
+            }
+            self.blob_request("PATCH", &url)
+                .set("Upload-Offset", &offset.to_string())
+                .set("Upload-Length", &blob.size.to_string())
+                .set("Content-Type", "application/offset+octet-stream")
+                .send_bytes(&chunk[..n])
+                .map_err(|e| anyhow::anyhow!("Blob upload failed at byte {}: {}", offset, e))?;
+            offset += n as u64;
+        }
+        Ok(())
+    }
+
+    /// Download a blob into the store, continuing a partial download, and
+    /// return where it's stored
+    pub fn download_blob(&self, store: &BlobStore, blob: &BlobRef) -> Result<std::path::PathBuf> {
+        if store.contains(&blob.hash) {
+            return store.path(&blob.hash);
+        }
+        self.check_blob_size(blob)?;
+        let url = self.blob_url(&blob.hash)?;
+        let partial = store.partial_path(&blob.hash)?;
+        let offset = std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+
+        if offset < blob.size {
+            let response = self
+                .blob_request("GET", &url)
+                .set("Range", &format!("bytes={}-", offset))
+                .call()
+                .map_err(|e| anyhow::anyhow!("Blob download failed: {}", e))?;
+            if offset > 0 && response.status() != 206 {
+                // The server sent the whole blob, not the rest
+                std::fs::remove_file(&partial)?;
+            }
+            let limit = blob.size - if response.status() == 206 { offset } else { 0 };
+            blobs::append(
+                &partial,
+                &mut std::io::Read::take(response.into_reader(), limit),
+            )?;
+        }
+        store.finish_download(blob)
+    }
+
+    /// Bytes of the blob at `url` the server has (0 if none)
+    fn server_blob_offset(&self, url: &str) -> Result<u64> {
+        match self.blob_request("HEAD", url).call() {
+            Ok(response) => Ok(response
+                .header("Upload-Offset")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0)),
+            Err(ureq::Error::Status(404, _)) => Ok(0),
+            Err(e) => Err(anyhow::anyhow!("Blob check failed: {}", e)),
+        }
+    }
+
+    fn check_blob_size(&self, blob: &BlobRef) -> Result<()> {
+        let limit = self.config.max_blob_bytes;
+        if limit > 0 && blob.size > limit {
+            anyhow::bail!(
+                "Blob {} is {} bytes, over sync.max_blob_bytes ({})",
+                blob.hash,
+                blob.size,
+                limit
+            );
+        }
+        Ok(())
+    }
+
+    fn blob_url(&self, hash: &str) -> Result<String> {
+        if let Some(e) = &self.token_error {
+            anyhow::bail!("{}", e);
+        }
+        if !self.is_enabled() {
+            anyhow::bail!("Sync is not enabled or not configured");
+        }
+        let server = self.config.server.as_deref().unwrap_or_default();
+        Ok(format!(
+            "{}/api/v1/blobs/{}",
+            server.trim_end_matches('/'),
+            hash
+        ))
+    }
+
+    fn blob_request(&self, method: &str, url: &str) -> ureq::Request {
+        let token = self.config.token.as_deref().unwrap_or_default();
+        ureq::request(method, url)
+            .set("Authorization", &format!("Bearer {}", token))
+            .timeout(Duration::from_secs(60))
+    }
+
     /// Perform the actual HTTP sync request
+    #[tracing::instrument(level = "debug", skip_all, fields(server = %server, changes = request.changes.len()))]
     fn do_sync(&self, server: &str, token: &str, request: &SyncRequest) -> Result<SyncResponse> {
         let url = format!("{}/api/v1/sync", server.trim_end_matches('/'));
 
         let response = ureq::post(&url)
             .set("Authorization", &format!("Bearer {}", token))
             .set("Content-Type", "application/json")
-            .timeout(std::time::Duration::from_secs(30))
+            .timeout(Duration::from_secs(30))
             .send_json(request)
             .map_err(|e| anyhow::anyhow!("Sync request failed: {}", e))?;
 
@@ -109,7 +351,7 @@ impl SyncClient {
     }
 
     /// Get or create a persistent device ID
-    fn get_or_create_device_id() -> Uuid {
+    pub fn get_or_create_device_id() -> Uuid {
         // Use consistent path: ~/.config/tickit/.device_id
         let path = dirs::home_dir()
             .map(|p| p.join(".config").join("tickit").join(".device_id"))
@@ -138,4 +380,342 @@ impl SyncClient {
     pub fn device_id(&self) -> Uuid {
         self.device_id
     }
+
+    /// This machine's host name, to tell devices apart
+    pub fn device_name() -> Option<String> {
+        let from_env = ["HOSTNAME", "COMPUTERNAME"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok());
+        let name = from_env
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .or_else(|| {
+                let output = std::process::Command::new("hostname").output().ok()?;
+                String::from_utf8(output.stdout).ok()
+            })?;
+        Some(name.trim().to_string()).filter(|n| !n.is_empty())
+    }
+}
+
+/// Split `changes` into batches within the record and byte limits (0 means
+/// no limit); there is always at least one batch, so an empty sync still
+/// fetches the server's changes
+///
+/// Lists and tags go first, so no batch has a task whose list or tags the
+/// server hasn't seen yet.
+fn split_batches(
+    mut changes: Vec<SyncRecord>,
+    max_records: usize,
+    max_bytes: usize,
+) -> Vec<Vec<SyncRecord>> {
+    changes.sort_by_key(|record| match record {
+        SyncRecord::List(_) => 0,
+        SyncRecord::Tag(_) => 1,
+        SyncRecord::Task(_) => 2,
+        SyncRecord::TaskTag(_) | SyncRecord::Comment(_) | SyncRecord::Attachment(_) => 3,
+        SyncRecord::Deleted { .. } => 4,
+    });
+    let mut batches = vec![Vec::new()];
+    let mut bytes = 0;
+    for record in changes {
+        let size = if max_bytes > 0 {
+            serde_json::to_vec(&record)
+                .map(|v| v.len() + 1)
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let current = batches.last().unwrap();
+        let full = (max_records > 0 && current.len() >= max_records)
+            || (max_bytes > 0 && bytes + size > max_bytes);
+        // A record bigger than the limit still goes, on its own
+        if full && !current.is_empty() {
+            batches.push(Vec::new());
+            bytes = 0;
+        }
+        bytes += size;
+        batches.last_mut().unwrap().push(record);
+    }
+    batches
+}
+
+/// Combine the responses to two batches of one sync
+///
+/// Every batch asks for changes since the same `last_sync`, so the server
+/// sends overlapping changes; later copies replace earlier ones. The
+/// earliest server time is kept so nothing changed between batches is missed.
+fn merge_responses(earlier: Option<SyncResponse>, later: SyncResponse) -> SyncResponse {
+    let Some(mut merged) = earlier else {
+        return later;
+    };
+    merged.server_time = merged.server_time.min(later.server_time);
+    let mut index: HashMap<_, _> = merged
+        .changes
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.key(), i))
+        .collect();
+    for record in later.changes {
+        match index.get(&record.key()) {
+            Some(&i) => merged.changes[i] = record,
+            None => {
+                index.insert(record.key(), merged.changes.len());
+                merged.changes.push(record);
+            }
+        }
+    }
+    for id in later.conflicts {
+        if !merged.conflicts.contains(&id) {
+            merged.conflicts.push(id);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+
+    fn tasks(n: usize) -> Vec<SyncRecord> {
+        let list = Uuid::new_v4();
+        (0..n)
+            .map(|i| SyncRecord::Task(Task::new(format!("Task {}", i), list)))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_batches() {
+        let sizes =
+            |batches: Vec<Vec<SyncRecord>>| batches.iter().map(Vec::len).collect::<Vec<_>>();
+        assert_eq!(sizes(split_batches(tasks(5), 2, 0)), [2, 2, 1]);
+        assert_eq!(sizes(split_batches(tasks(5), 0, 0)), [5]);
+        assert_eq!(sizes(split_batches(Vec::new(), 2, 0)), [0]);
+
+        // Each record is a few hundred bytes; one byte allows one per batch
+        assert_eq!(sizes(split_batches(tasks(3), 0, 1)), [1, 1, 1]);
+
+        // Lists and tags are sent before the tasks that use them
+        let list = crate::models::List::new("Work");
+        let tag = crate::models::Tag::new("urgent");
+        let mut changes = tasks(3);
+        changes.push(SyncRecord::List(list.clone()));
+        changes.push(SyncRecord::Tag(tag.clone()));
+        let batches = split_batches(changes, 2, 0);
+        assert_eq!(sizes(batches.clone()), [2, 2, 1]);
+        assert!(
+            matches!(&batches[0][..], [SyncRecord::List(l), SyncRecord::Tag(t)]
+            if l.id == list.id && t.id == tag.id)
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        for _ in 0..20 {
+            let first = retry_delay(1);
+            assert!(first >= Duration::from_millis(3750) && first <= Duration::from_millis(6250));
+        }
+        let third = retry_delay(3);
+        assert!(third >= Duration::from_secs(15) && third <= Duration::from_secs(25));
+        assert!(retry_delay(40) <= RETRY_CAP.mul_f64(1.25));
+    }
+
+    #[test]
+    fn test_merge_responses() {
+        let changes = tasks(2);
+        let SyncRecord::Task(first) = &changes[0] else {
+            unreachable!()
+        };
+        let mut renamed = first.clone();
+        renamed.title = "Renamed".to_string();
+        let earlier = SyncResponse {
+            server_time: Utc::now() - chrono::Duration::seconds(5),
+            changes: changes.clone(),
+            conflicts: vec![first.id],
+        };
+        let later = SyncResponse {
+            server_time: Utc::now(),
+            changes: vec![SyncRecord::Task(renamed)],
+            conflicts: vec![first.id],
+        };
+        let server_time = earlier.server_time;
+
+        let merged = merge_responses(Some(earlier), later);
+        assert_eq!(merged.server_time, server_time);
+        assert_eq!(merged.changes.len(), 2);
+        assert!(matches!(&merged.changes[0], SyncRecord::Task(t) if t.title == "Renamed"));
+        assert_eq!(merged.conflicts, [first.id]);
+    }
+
+    type Log = std::sync::Arc<std::sync::Mutex<Vec<String>>>;
+    type Blobs = std::sync::Arc<std::sync::Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// A blob server on a local port, answering HEAD, PATCH and ranged GET
+    /// on `/api/v1/blobs/<hash>` from `blobs` and logging each request
+    fn blob_server(blobs: Blobs, log: Log) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (blobs, log) = (blobs.clone(), log.clone());
+                std::thread::spawn(move || serve_blobs(stream.unwrap(), blobs, log));
+            }
+        });
+        url
+    }
+
+    fn serve_blobs(stream: std::net::TcpStream, blobs: Blobs, log: Log) {
+        use std::io::{BufRead, Read, Write};
+        let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+        let mut stream = stream;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let mut parts = line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let hash = parts
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("/api/v1/blobs/")
+                .to_string();
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let Some((name, value)) = line.trim_end().split_once(':') else {
+                    break;
+                };
+                headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+            }
+            let header = |name: &str| headers.get(name).cloned().unwrap_or_default();
+            let mut body = vec![0; header("content-length").parse().unwrap_or(0)];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut blobs = blobs.lock().unwrap();
+            let stored = blobs.get(&hash).cloned();
+            let (status, extra, data) = match (method.as_str(), stored) {
+                ("HEAD", Some(data)) => (
+                    "200 OK",
+                    format!("Upload-Offset: {}\r\n", data.len()),
+                    vec![],
+                ),
+                ("PATCH", stored) => {
+                    let mut data = stored.unwrap_or_default();
+                    log.lock().unwrap().push(format!(
+                        "PATCH {} {}",
+                        header("upload-offset"),
+                        body.len()
+                    ));
+                    data.extend(body);
+                    blobs.insert(hash, data);
+                    ("204 No Content", String::new(), vec![])
+                }
+                ("GET", Some(data)) => {
+                    let range = header("range");
+                    log.lock().unwrap().push(format!("GET {}", range));
+                    let from: usize = range
+                        .trim_start_matches("bytes=")
+                        .trim_end_matches('-')
+                        .parse()
+                        .unwrap_or(0);
+                    ("206 Partial Content", String::new(), data[from..].to_vec())
+                }
+                _ => ("404 Not Found", String::new(), vec![]),
+            };
+            drop(blobs);
+            let length = if method == "HEAD" { 0 } else { data.len() };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}\r\n",
+                status, length, extra
+            )
+            .unwrap();
+            if method != "HEAD" {
+                stream.write_all(&data).unwrap();
+            }
+        }
+    }
+
+    fn blob_client(server: &str, max_blob_bytes: u64) -> SyncClient {
+        SyncClient {
+            config: SyncConfig {
+                enabled: true,
+                server: Some(server.to_string()),
+                token: Some("secret".to_string()),
+                max_blob_bytes,
+                ..SyncConfig::default()
+            },
+            device_id: Uuid::nil(),
+            status: SyncStatus::default(),
+            token_error: None,
+        }
+    }
+
+    #[test]
+    fn test_blob_transfers() {
+        let blobs = Blobs::default();
+        let log = Log::default();
+        let server = blob_server(blobs.clone(), log.clone());
+        let client = blob_client(&server, 0);
+
+        // A bit over one and a half chunks
+        let data: Vec<u8> = (0..BLOB_CHUNK_BYTES * 3 / 2 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let here = tempfile::tempdir().unwrap();
+        let store = BlobStore::at(here.path());
+        let blob = store.add_bytes(&data).unwrap();
+        let attachment = crate::models::Attachment::new(
+            Uuid::new_v4(),
+            "scan.pdf",
+            blob.hash.clone(),
+            blob.size,
+        );
+        let changes = vec![SyncRecord::Attachment(attachment)];
+
+        // An upload cut off after 1000 bytes goes on from there
+        blobs
+            .lock()
+            .unwrap()
+            .insert(blob.hash.clone(), data[..1000].to_vec());
+        client.upload_blobs(&store, &changes).unwrap();
+        assert_eq!(blobs.lock().unwrap()[&blob.hash], data);
+        assert_eq!(
+            std::mem::take(&mut *log.lock().unwrap()),
+            [
+                format!("PATCH 1000 {}", BLOB_CHUNK_BYTES),
+                format!(
+                    "PATCH {} {}",
+                    1000 + BLOB_CHUNK_BYTES,
+                    data.len() - 1000 - BLOB_CHUNK_BYTES
+                ),
+            ]
+        );
+
+        // Nothing is sent again once the server has it all
+        client.upload_blobs(&store, &changes).unwrap();
+        assert!(log.lock().unwrap().is_empty());
+
+        // Another device resumes a download that stopped after 5000 bytes
+        let there = tempfile::tempdir().unwrap();
+        let other = BlobStore::at(there.path());
+        blobs::append(&other.partial_path(&blob.hash).unwrap(), &mut &data[..5000]).unwrap();
+        client.download_blobs(&other, std::slice::from_ref(&blob));
+        assert!(other.contains(&blob.hash));
+        assert_eq!(
+            std::fs::read(other.path(&blob.hash).unwrap()).unwrap(),
+            data
+        );
+        assert_eq!(*log.lock().unwrap(), ["GET bytes=5000-"]);
+
+        // Blobs over max_blob_bytes stay where they are
+        let small = blob_client(&server, 1000);
+        assert!(small.upload_blob(&store, &blob).is_err());
+        let elsewhere = tempfile::tempdir().unwrap();
+        let third = BlobStore::at(elsewhere.path());
+        small.download_blobs(&third, std::slice::from_ref(&blob));
+        assert!(!third.contains(&blob.hash));
+        assert_eq!(log.lock().unwrap().len(), 1);
+    }
 }
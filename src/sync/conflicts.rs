@@ -0,0 +1,449 @@
+//! Settling records changed both locally and on the server
+//!
+//! A task edited on both sides since the last sync is merged field by field
+//! against its copy from that sync: a field changed on one side only keeps
+//! that change, and `sync.conflict_policy` picks between fields changed on
+//! both (or every differing field, for tasks never synced before). Lists,
+//! tags and deleted tasks are settled whole. Whatever ends up differing from
+//! the server's copy gets a fresh `updated_at`, so the next sync uploads it.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{RecordType, SyncRecord, SyncResponse};
+use crate::config::ConflictPolicy;
+use crate::db::Database;
+use crate::models::Task;
+
+/// Which copy of a field to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+/// A task field changed on both sides, for [`ConflictPolicy::Ask`]
+#[derive(Debug)]
+pub struct FieldConflict<'a> {
+    /// The local copy of the task
+    pub task: &'a Task,
+    pub field: &'static str,
+    pub local: String,
+    pub remote: String,
+}
+
+/// Asks which side of a [`FieldConflict`] to keep
+pub type AskFn<'a> = &'a mut dyn FnMut(&FieldConflict) -> Side;
+
+/// Rewrite the server's changes in `response` so that applying them follows
+/// `policy`; `ask` answers for [`ConflictPolicy::Ask`], which is newest-wins
+/// without it. Returns how many records kept local changes.
+pub fn resolve_conflicts(
+    db: &Database,
+    response: &mut SyncResponse,
+    last_sync: Option<DateTime<Utc>>,
+    policy: ConflictPolicy,
+    mut ask: Option<AskFn>,
+) -> Result<usize> {
+    let changed_locally = |updated_at: DateTime<Utc>| last_sync.is_none_or(|t| updated_at > t);
+    let now = Utc::now();
+    let lists = db.get_lists()?;
+    let tags = db.get_tags()?;
+    let mut kept_local = 0;
+
+    for record in &mut response.changes {
+        match record {
+            SyncRecord::Task(remote) => {
+                let Ok(local) = db.get_task(remote.id) else {
+                    continue;
+                };
+                if !changed_locally(local.updated_at) || same_content(&local, remote) {
+                    continue;
+                }
+                let base = db.get_sync_base(remote.id)?;
+                let mut pick =
+                    |field: &'static str, local_value: String, remote_value: String| match (
+                        policy,
+                        ask.as_mut(),
+                    ) {
+                        (ConflictPolicy::ServerWins, _) => Side::Remote,
+                        (ConflictPolicy::ClientWins, _) => Side::Local,
+                        (ConflictPolicy::Ask, Some(ask)) => ask(&FieldConflict {
+                            task: &local,
+                            field,
+                            local: local_value,
+                            remote: remote_value,
+                        }),
+                        (ConflictPolicy::NewestWins | ConflictPolicy::Ask, _) => {
+                            if local.updated_at > remote.updated_at {
+                                Side::Local
+                            } else {
+                                Side::Remote
+                            }
+                        }
+                    };
+                let list_name = |id: Uuid| {
+                    lists
+                        .iter()
+                        .find(|l| l.id == id)
+                        .map_or_else(|| id.to_string(), |l| l.name.clone())
+                };
+                let tag_names = |ids: &[Uuid]| {
+                    ids.iter()
+                        .map(|id| {
+                            tags.iter()
+                                .find(|t| t.id == *id)
+                                .map_or_else(|| id.to_string(), |t| format!("#{}", t.name))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                let merged = merge_task(
+                    base.as_ref(),
+                    &local,
+                    remote,
+                    &mut pick,
+                    &list_name,
+                    &tag_names,
+                );
+                let keeps_local = !same_content(&merged, remote);
+                *remote = merged;
+                // Newer than the local copy, or the database keeps that instead
+                remote.updated_at = if keeps_local {
+                    kept_local += 1;
+                    now.max(local.updated_at + Duration::milliseconds(1))
+                } else {
+                    remote
+                        .updated_at
+                        .max(local.updated_at + Duration::milliseconds(1))
+                };
+            }
+            SyncRecord::List(remote) => {
+                if let Some(local) = lists.iter().find(|l| l.id == remote.id)
+                    && changed_locally(local.updated_at)
+                    && !same_content(local, remote)
+                    && let Some(keep_local) =
+                        settle_whole(policy, local.updated_at, remote.updated_at)
+                {
+                    if keep_local {
+                        kept_local += 1;
+                        *remote = local.clone();
+                    }
+                    remote.updated_at = now.max(local.updated_at + Duration::milliseconds(1));
+                }
+            }
+            SyncRecord::Tag(remote) => {
+                if let Some(local) = tags.iter().find(|t| t.id == remote.id)
+                    && changed_locally(local.updated_at)
+                    && !same_content(local, remote)
+                    && let Some(keep_local) =
+                        settle_whole(policy, local.updated_at, remote.updated_at)
+                {
+                    if keep_local {
+                        kept_local += 1;
+                        *remote = local.clone();
+                    }
+                    remote.updated_at = now.max(local.updated_at + Duration::milliseconds(1));
+                }
+            }
+            SyncRecord::Deleted {
+                id,
+                record_type: RecordType::Task,
+                deleted_at,
+            } => {
+                let Ok(local) = db.get_task(*id) else {
+                    continue;
+                };
+                let keep = match policy {
+                    ConflictPolicy::ServerWins => false,
+                    ConflictPolicy::ClientWins => true,
+                    ConflictPolicy::NewestWins | ConflictPolicy::Ask => {
+                        local.updated_at > *deleted_at
+                    }
+                };
+                if changed_locally(local.updated_at) && keep {
+                    // Restore it on the server too
+                    kept_local += 1;
+                    let mut local = local;
+                    local.updated_at = now.max(local.updated_at + Duration::milliseconds(1));
+                    *record = SyncRecord::Task(local);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(kept_local)
+}
+
+/// IDs of the tasks changed or deleted in `records`
+pub fn task_ids(records: &[SyncRecord]) -> Vec<Uuid> {
+    records
+        .iter()
+        .filter_map(|record| match record {
+            SyncRecord::Task(task) => Some(task.id),
+            SyncRecord::Deleted {
+                id,
+                record_type: RecordType::Task,
+                ..
+            } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Remember the tasks sent (by ID) and received as they now are, as the
+/// base for the next merge
+pub fn record_sync_base(db: &Database, sent: &[Uuid], response: &SyncResponse) -> Result<()> {
+    let received = task_ids(&response.changes);
+    let ids: HashSet<Uuid> = sent.iter().chain(&received).copied().collect();
+    let tasks: Vec<(Uuid, Option<Task>)> = ids
+        .into_iter()
+        .map(|id| (id, db.get_task(id).ok()))
+        .collect();
+    db.set_sync_base(&tasks)
+}
+
+/// Merge `local` into `remote`: fields changed on one side since `base` keep
+/// that change, and `pick` settles fields changed on both
+fn merge_task(
+    base: Option<&Task>,
+    local: &Task,
+    remote: &Task,
+    pick: &mut dyn FnMut(&'static str, String, String) -> Side,
+    list_name: &dyn Fn(Uuid) -> String,
+    tag_names: &dyn Fn(&[Uuid]) -> String,
+) -> Task {
+    let mut merged = remote.clone();
+    let text = |s: &Option<String>| s.clone().unwrap_or_else(|| "(none)".to_string());
+    let date = |d: &Option<DateTime<Utc>>| {
+        d.map_or_else(|| "(none)".to_string(), |d| d.date_naive().to_string())
+    };
+
+    macro_rules! field {
+        ($name:literal, $show:expr, $($field:ident),+) => {
+            let differs = $(local.$field != remote.$field)||+;
+            let local_changed = base.is_none_or(|b| $(b.$field != local.$field)||+);
+            let remote_changed = base.is_none_or(|b| $(b.$field != remote.$field)||+);
+            if differs
+                && local_changed
+                && (!remote_changed || pick($name, $show(local), $show(remote)) == Side::Local)
+            {
+                $(merged.$field = local.$field.clone();)+
+            }
+        };
+    }
+    field!("title", |t: &Task| t.title.clone(), title);
+    field!("description", |t: &Task| text(&t.description), description);
+    field!("url", |t: &Task| text(&t.url), url);
+    field!("priority", |t: &Task| t.priority.to_string(), priority);
+    field!(
+        "completed",
+        |t: &Task| if t.completed { "done" } else { "open" }.to_string(),
+        completed,
+        completed_at
+    );
+    field!("list", |t: &Task| list_name(t.list_id), list_id);
+    field!("due date", |t: &Task| date(&t.due_date), due_date);
+    field!("position", |t: &Task| t.sort_order.to_string(), sort_order);
+    field!(
+        "nag",
+        |t: &Task| t.nag_hours.map_or("off".to_string(), |h| h.to_string()),
+        nag_hours
+    );
+    field!(
+        "archived",
+        |t: &Task| date(&t.archived_at).replace("(none)", "no"),
+        archived_at
+    );
+
+    // Tags added or removed on either side are all kept
+    match base {
+        Some(base) => {
+            let mut tags: Vec<Uuid> = base
+                .tag_ids
+                .iter()
+                .filter(|id| local.tag_ids.contains(id) && remote.tag_ids.contains(id))
+                .copied()
+                .collect();
+            for id in local.tag_ids.iter().chain(&remote.tag_ids) {
+                if !base.tag_ids.contains(id) && !tags.contains(id) {
+                    tags.push(*id);
+                }
+            }
+            merged.tag_ids = tags;
+        }
+        None => {
+            let mut local_tags = local.tag_ids.clone();
+            let mut remote_tags = remote.tag_ids.clone();
+            local_tags.sort();
+            remote_tags.sort();
+            if local_tags != remote_tags
+                && pick("tags", tag_names(&local_tags), tag_names(&remote_tags)) == Side::Local
+            {
+                merged.tag_ids = local.tag_ids.clone();
+            }
+        }
+    }
+    merged
+}
+
+/// Whether a list or tag changed on both sides keeps the local copy; `None`
+/// leaves it to the usual newest-wins upsert
+fn settle_whole(
+    policy: ConflictPolicy,
+    local: DateTime<Utc>,
+    remote: DateTime<Utc>,
+) -> Option<bool> {
+    match policy {
+        ConflictPolicy::ServerWins => Some(false),
+        ConflictPolicy::ClientWins => Some(true),
+        ConflictPolicy::NewestWins | ConflictPolicy::Ask => (local > remote).then_some(true),
+    }
+}
+
+/// Whether two records are the same apart from when they were last changed
+fn same_content<T: Serialize>(a: &T, b: &T) -> bool {
+    let strip = |record: &T| {
+        let mut value = serde_json::to_value(record).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.remove("updated_at");
+        }
+        value
+    };
+    strip(a) == strip(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synced(db: &Database, task: &Task) {
+        db.set_sync_base(&[(task.id, Some(task.clone()))]).unwrap();
+    }
+
+    fn respond(task: Task) -> SyncResponse {
+        SyncResponse {
+            server_time: Utc::now(),
+            changes: vec![SyncRecord::Task(task)],
+            conflicts: Vec::new(),
+        }
+    }
+
+    fn resolved(response: &SyncResponse) -> &Task {
+        match &response.changes[0] {
+            SyncRecord::Task(task) => task,
+            other => panic!("expected a task, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merge_fields() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let last_sync = Utc::now() - Duration::hours(1);
+        let mut task = Task::new("Write report", inbox.id);
+        task.updated_at = last_sync - Duration::hours(1);
+        db.insert_task(&task).unwrap();
+        synced(&db, &task);
+
+        // Completed elsewhere, description edited here, title edited on both
+        let mut local = task.clone();
+        local.description = Some("Draft in docs/".to_string());
+        local.title = "Write the report".to_string();
+        local.updated_at = Utc::now();
+        db.update_task(&local).unwrap();
+        let mut remote = task.clone();
+        remote.complete();
+        remote.title = "Write Q3 report".to_string();
+        remote.updated_at = Utc::now() - Duration::minutes(5);
+
+        let mut response = respond(remote.clone());
+        let kept = resolve_conflicts(
+            &db,
+            &mut response,
+            Some(last_sync),
+            ConflictPolicy::ServerWins,
+            None,
+        )
+        .unwrap();
+        let merged = resolved(&response);
+        assert_eq!(kept, 1);
+        assert!(merged.completed);
+        assert_eq!(merged.description.as_deref(), Some("Draft in docs/"));
+        assert_eq!(merged.title, "Write Q3 report");
+        assert!(merged.updated_at > local.updated_at);
+
+        // Newest wins takes the local title; asking hands the choice over
+        let mut response = respond(remote.clone());
+        resolve_conflicts(
+            &db,
+            &mut response,
+            Some(last_sync),
+            ConflictPolicy::NewestWins,
+            None,
+        )
+        .unwrap();
+        assert_eq!(resolved(&response).title, "Write the report");
+
+        let mut asked = Vec::new();
+        let mut ask = |conflict: &FieldConflict| {
+            asked.push((conflict.field, conflict.remote.clone()));
+            Side::Remote
+        };
+        let mut response = respond(remote);
+        resolve_conflicts(
+            &db,
+            &mut response,
+            Some(last_sync),
+            ConflictPolicy::Ask,
+            Some(&mut ask),
+        )
+        .unwrap();
+        assert_eq!(resolved(&response).title, "Write Q3 report");
+        assert_eq!(asked, [("title", "Write Q3 report".to_string())]);
+    }
+
+    #[test]
+    fn test_client_wins_keeps_deleted_task() {
+        let db = Database::open_in_memory().unwrap();
+        let inbox = db.get_inbox().unwrap();
+        let last_sync = Utc::now() - Duration::hours(1);
+        let task = Task::new("Keep me", inbox.id);
+        db.insert_task(&task).unwrap();
+
+        let deleted = || SyncResponse {
+            server_time: Utc::now(),
+            changes: vec![SyncRecord::Deleted {
+                id: task.id,
+                record_type: RecordType::Task,
+                deleted_at: Utc::now(),
+            }],
+            conflicts: Vec::new(),
+        };
+        let mut response = deleted();
+        resolve_conflicts(
+            &db,
+            &mut response,
+            Some(last_sync),
+            ConflictPolicy::ServerWins,
+            None,
+        )
+        .unwrap();
+        assert!(matches!(response.changes[0], SyncRecord::Deleted { .. }));
+
+        let mut response = deleted();
+        resolve_conflicts(
+            &db,
+            &mut response,
+            Some(last_sync),
+            ConflictPolicy::ClientWins,
+            None,
+        )
+        .unwrap();
+        assert_eq!(resolved(&response).title, "Keep me");
+    }
+}
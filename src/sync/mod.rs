@@ -1,10 +1,18 @@
 //! Sync module for optional cloud synchronization
 //!
-//! This module provides functionality to sync tasks, lists, and tags
-//! with a self-hosted tickit-sync server.
+//! This module provides functionality to sync tasks, lists, tags and the
+//! files attached to tasks with a self-hosted tickit-sync server.
 
+mod blobs;
+mod bootstrap;
+mod changes;
 mod client;
+mod conflicts;
 mod types;
 
-pub use client::SyncClient;
+pub use blobs::{BLOB_CHUNK_BYTES, BlobRef, BlobStore, hash_reader};
+pub use bootstrap::{SyncPreview, needs_preview};
+pub use changes::{apply_changes, attachment_blobs, local_changes, missing_blobs};
+pub use client::{MAX_SYNC_RETRIES, ProgressFn, SyncClient, retry_delay};
+pub use conflicts::{AskFn, FieldConflict, Side, record_sync_base, resolve_conflicts, task_ids};
 pub use types::*;
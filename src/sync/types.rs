@@ -4,7 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::models::{List, Tag, Task};
+use crate::models::{Attachment, Comment, List, Tag, Task};
 
 /// A record that can be synced
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +14,9 @@ pub enum SyncRecord {
     List(List),
     Tag(Tag),
     TaskTag(TaskTagLink),
+    Comment(Comment),
+    /// A file attached to a task; its blob is uploaded before the record
+    Attachment(Attachment),
     /// Tombstone for deleted records
     Deleted {
         id: Uuid,
@@ -22,6 +25,24 @@ pub enum SyncRecord {
     },
 }
 
+impl SyncRecord {
+    /// What the record is about: its kind, its id and the tag of a
+    /// task-tag link; a tombstone has the key of the record it deletes
+    pub fn key(&self) -> (RecordType, Uuid, Option<Uuid>) {
+        match self {
+            SyncRecord::Task(task) => (RecordType::Task, task.id, None),
+            SyncRecord::List(list) => (RecordType::List, list.id, None),
+            SyncRecord::Tag(tag) => (RecordType::Tag, tag.id, None),
+            SyncRecord::TaskTag(link) => (RecordType::TaskTag, link.task_id, Some(link.tag_id)),
+            SyncRecord::Comment(comment) => (RecordType::Comment, comment.id, None),
+            SyncRecord::Attachment(attachment) => (RecordType::Attachment, attachment.id, None),
+            SyncRecord::Deleted {
+                id, record_type, ..
+            } => (*record_type, *id, None),
+        }
+    }
+}
+
 /// Link between task and tag (for junction table sync)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskTagLink {
@@ -31,13 +52,15 @@ pub struct TaskTagLink {
 }
 
 /// Type of record (for tombstones)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum RecordType {
     Task,
     List,
     Tag,
     TaskTag,
+    Comment,
+    Attachment,
 }
 
 /// Request to sync changes with server
@@ -73,6 +96,20 @@ pub struct SyncStatus {
     pub last_error: Option<String>,
     /// Number of pending local changes
     pub pending_changes: usize,
+    /// How far the upload of a sync in several batches has got
+    pub progress: Option<SyncProgress>,
+}
+
+/// Upload progress of a sync sent in batches (see `sync.max_batch_records`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncProgress {
+    /// Records uploaded so far
+    pub sent: usize,
+    /// Records to upload in all
+    pub total: usize,
+    /// Batches uploaded so far
+    pub batch: usize,
+    pub batches: usize,
 }
 
 impl SyncStatus {
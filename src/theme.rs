@@ -161,6 +161,101 @@ impl ThemeColors {
         }
     }
 
+    /// Black background with bright ANSI colors, for low vision or washed-out terminals
+    #[must_use]
+    pub fn high_contrast(self) -> Self {
+        Self {
+            bg: Color::Black,
+            bg_secondary: Color::Black,
+            bg_highlight: Color::DarkGray,
+            fg: Color::White,
+            fg_dim: Color::White,
+            fg_muted: Color::Gray,
+
+            primary: Color::LightCyan,
+            secondary: Color::LightMagenta,
+            accent: Color::LightYellow,
+
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            info: Color::LightCyan,
+
+            border: Color::White,
+            border_focus: Color::LightYellow,
+            selection: Color::Blue,
+
+            priority_low: Color::Gray,
+            priority_medium: Color::White,
+            priority_high: Color::LightYellow,
+            priority_urgent: Color::LightRed,
+            ..self
+        }
+    }
+
+    /// Nearest colors of the xterm 256-color palette, for terminals without truecolor
+    #[must_use]
+    pub fn to_ansi256(self) -> Self {
+        let c = Self::ansi256;
+        Self {
+            bg: c(self.bg),
+            bg_secondary: c(self.bg_secondary),
+            bg_highlight: c(self.bg_highlight),
+            fg: c(self.fg),
+            fg_dim: c(self.fg_dim),
+            fg_muted: c(self.fg_muted),
+            primary: c(self.primary),
+            secondary: c(self.secondary),
+            accent: c(self.accent),
+            success: c(self.success),
+            warning: c(self.warning),
+            error: c(self.error),
+            info: c(self.info),
+            border: c(self.border),
+            border_focus: c(self.border_focus),
+            selection: c(self.selection),
+            logo_primary: c(self.logo_primary),
+            logo_secondary: c(self.logo_secondary),
+            priority_low: c(self.priority_low),
+            priority_medium: c(self.priority_medium),
+            priority_high: c(self.priority_high),
+            priority_urgent: c(self.priority_urgent),
+        }
+    }
+
+    /// Nearest 256-color palette entry for an RGB color; other colors are kept.
+    ///
+    /// Picks the closer of the 6x6x6 color cube and the 24-step grayscale ramp.
+    pub fn ansi256(color: Color) -> Color {
+        let Color::Rgb(r, g, b) = color else {
+            return color;
+        };
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        let level = |v: u8| -> usize {
+            (0..LEVELS.len())
+                .min_by_key(|&i| (LEVELS[i] as i32 - v as i32).abs())
+                .unwrap_or(0)
+        };
+        let distance = |(r2, g2, b2): (u8, u8, u8)| -> i32 {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(r, r2) + d(g, g2) + d(b, b2)
+        };
+
+        let (ri, gi, bi) = (level(r), level(g), level(b));
+        let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+        let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+        let average = (r as u32 + g as u32 + b as u32) / 3;
+        let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+        let gray = 8 + 10 * gray_step;
+
+        if distance((gray, gray, gray)) < distance(cube) {
+            Color::Indexed(232 + gray_step)
+        } else {
+            Color::Indexed(cube_index as u8)
+        }
+    }
+
     /// Adjust color brightness
     fn adjust_brightness(color: Color, amount: i16) -> Color {
         if let Color::Rgb(r, g, b) = color {
@@ -304,3 +399,33 @@ impl ThemeColors {
         Style::default().fg(color)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi256() {
+        assert_eq!(
+            ThemeColors::ansi256(Color::Rgb(0, 0, 0)),
+            Color::Indexed(16)
+        );
+        assert_eq!(
+            ThemeColors::ansi256(Color::Rgb(255, 0, 0)),
+            Color::Indexed(196)
+        );
+        assert_eq!(
+            ThemeColors::ansi256(Color::Rgb(255, 255, 255)),
+            Color::Indexed(231)
+        );
+        assert_eq!(
+            ThemeColors::ansi256(Color::Rgb(40, 42, 54)),
+            Color::Indexed(236)
+        );
+        assert_eq!(ThemeColors::ansi256(Color::Red), Color::Red);
+
+        let colors = Theme::default().colors().to_ansi256();
+        assert!(!matches!(colors.bg, Color::Rgb(..)));
+        assert!(!matches!(colors.primary, Color::Rgb(..)));
+    }
+}
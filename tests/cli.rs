@@ -0,0 +1,82 @@
+//! Exit codes and messages of the `tickit` command
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Run `tickit --ephemeral` with `args` and `stdin`, in a home of its own
+fn tickit(args: &[&str], stdin: &str) -> Output {
+    tickit_in(&tempfile::tempdir().unwrap(), args, stdin)
+}
+
+/// Run `tickit --ephemeral` with `args` and `stdin`, in `home`
+fn tickit_in(home: &tempfile::TempDir, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tickit"))
+        .arg("--ephemeral")
+        .args(args)
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join(".config"))
+        .env("NO_COLOR", "1")
+        .env_remove("RUST_BACKTRACE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_not_found_exit_code() {
+    for json in [false, true] {
+        let mut args = vec!["done", "nothing like it"];
+        if json {
+            args.insert(0, "--json");
+        }
+        let output = tickit(&args, "");
+        assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+        assert!(stderr(&output).contains("Task not found: nothing like it"));
+    }
+}
+
+#[test]
+fn test_add_stdin_unknown_names() {
+    for (line, message) in [
+        ("Call mom @nowhere", "List not found: nowhere"),
+        ("Buy milk #later", "Tag not found: later"),
+    ] {
+        let output = tickit(&["add", "--stdin"], line);
+        assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+        assert!(stderr(&output).contains(message));
+        assert!(stderr(&output).contains("(use --create-missing"));
+
+        let output = tickit(&["add", "--stdin", "--create-missing"], line);
+        assert!(output.status.success(), "{}", stderr(&output));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Created"), "{}", stdout);
+        assert!(stdout.contains("Added: "), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_capture_skips_config() {
+    // A config.toml that doesn't load stops other commands, not capture
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config").join("tickit");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(config.join("config.toml"), "theme = \n").unwrap();
+
+    let output = tickit_in(&home, &["capture", "call", "the", "dentist"], "");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Captured: call the dentist"));
+    assert!(!tickit_in(&home, &["add", "Other"], "").status.success());
+}